@@ -24,6 +24,7 @@ fn convert_column_value(cv: &CoreColumnValue) -> ColumnValue {
         CoreColumnValue::Blob(b) => ColumnValue::Blob { value: b.clone() },
         CoreColumnValue::Date(d) => ColumnValue::Integer { value: *d },
         CoreColumnValue::BigInt(s) => ColumnValue::Text { value: s.clone() },
+        CoreColumnValue::Json(v) => ColumnValue::Text { value: v.to_string() },
     }
 }
 
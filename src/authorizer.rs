@@ -0,0 +1,133 @@
+//! Statement authorizers
+//! Installs a JS callback with `sqlite3_set_authorizer`, so apps can allow, deny or ignore
+//! each action a statement performs while it is being compiled
+
+use crate::types::DatabaseError;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_void};
+use wasm_bindgen::JsValue;
+
+thread_local! {
+    // Callbacks keyed by connection pointer
+    static AUTHORIZERS: RefCell<HashMap<usize, js_sys::Function>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Pass an authorizer argument to JS as a string, or null when SQLite gave none
+fn js_arg(arg: *const c_char) -> JsValue {
+    if arg.is_null() {
+        JsValue::NULL
+    } else {
+        JsValue::from_str(&unsafe { CStr::from_ptr(arg) }.to_string_lossy())
+    }
+}
+
+/// Map the callback's answer to an authorizer result; anything unrecognized denies
+fn decision(answer: &JsValue) -> c_int {
+    if let Some(answer) = answer.as_string() {
+        return match answer.to_ascii_uppercase().as_str() {
+            "ALLOW" | "OK" => sqlite_wasm_rs::SQLITE_OK,
+            "IGNORE" => sqlite_wasm_rs::SQLITE_IGNORE,
+            _ => sqlite_wasm_rs::SQLITE_DENY,
+        };
+    }
+    match answer.as_f64().map(|code| code as c_int) {
+        Some(code @ (sqlite_wasm_rs::SQLITE_OK | sqlite_wasm_rs::SQLITE_IGNORE)) => code,
+        _ => sqlite_wasm_rs::SQLITE_DENY,
+    }
+}
+
+/// SQLite authorizer: ask the connection's callback about one action
+unsafe extern "C" fn call_authorizer(
+    arg: *mut c_void,
+    action: c_int,
+    arg1: *const c_char,
+    arg2: *const c_char,
+    db_name: *const c_char,
+    trigger: *const c_char,
+) -> c_int {
+    // Cloned out so the callback may replace or remove the authorizer itself
+    let callback =
+        AUTHORIZERS.with(|authorizers| authorizers.borrow().get(&(arg as usize)).cloned());
+    let Some(callback) = callback else {
+        return sqlite_wasm_rs::SQLITE_OK;
+    };
+    let args = js_sys::Array::of5(
+        &JsValue::from(action),
+        &js_arg(arg1),
+        &js_arg(arg2),
+        &js_arg(db_name),
+        &js_arg(trigger),
+    );
+    match callback.apply(&JsValue::NULL, &args) {
+        Ok(answer) => decision(&answer),
+        Err(e) => {
+            log::warn!("Authorizer threw, denying action {}: {:?}", action, e);
+            sqlite_wasm_rs::SQLITE_DENY
+        }
+    }
+}
+
+/// Install `callback` as the authorizer of `db`, or remove the authorizer with `None`
+///
+/// SQLite expires the connection's prepared statements, so they are authorized again
+/// before they next run.
+pub fn install(
+    db: *mut sqlite_wasm_rs::sqlite3,
+    callback: Option<&js_sys::Function>,
+) -> Result<(), DatabaseError> {
+    if db.is_null() {
+        return Err(DatabaseError::new(
+            "DATABASE_CLOSED",
+            "Cannot set an authorizer on a closed database",
+        ));
+    }
+    let ret = match callback {
+        Some(callback) => {
+            AUTHORIZERS.with(|authorizers| {
+                authorizers
+                    .borrow_mut()
+                    .insert(db as usize, callback.clone());
+            });
+            unsafe {
+                sqlite_wasm_rs::sqlite3_set_authorizer(db, Some(call_authorizer), db as *mut c_void)
+            }
+        }
+        None => {
+            remove(db);
+            unsafe { sqlite_wasm_rs::sqlite3_set_authorizer(db, None, std::ptr::null_mut()) }
+        }
+    };
+    if ret != sqlite_wasm_rs::SQLITE_OK {
+        return Err(DatabaseError::new(
+            "SQLITE_ERROR",
+            &format!("Failed to set authorizer (code {})", ret),
+        )
+        .with_sqlite_code(ret));
+    }
+    Ok(())
+}
+
+/// Put back the authorizer of `db` after another one was installed temporarily
+///
+/// Reinstalls the connection's callback if it has one, otherwise leaves none installed.
+pub fn restore(db: *mut sqlite_wasm_rs::sqlite3) {
+    let installed =
+        AUTHORIZERS.with(|authorizers| authorizers.borrow().contains_key(&(db as usize)));
+    unsafe {
+        if installed {
+            sqlite_wasm_rs::sqlite3_set_authorizer(db, Some(call_authorizer), db as *mut c_void);
+        } else {
+            sqlite_wasm_rs::sqlite3_set_authorizer(db, None, std::ptr::null_mut());
+        }
+    }
+}
+
+/// Forget the callback of a connection that is being closed
+pub fn remove(db: *mut sqlite_wasm_rs::sqlite3) {
+    AUTHORIZERS.with(|authorizers| {
+        authorizers.borrow_mut().remove(&(db as usize));
+    });
+}
@@ -44,6 +44,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         auto_vacuum: None,
         journal_mode: None,
         max_export_size_bytes: Some(2 * 1024 * 1024 * 1024), // 2GB default
+        ..Default::default()
     };
     let mut db = SqliteIndexedDB::new(config).await?;
 
@@ -124,6 +125,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         ColumnValue::Blob(b) => format!("<blob {} bytes>", b.len()),
                         ColumnValue::BigInt(b) => b.to_string(),
                         ColumnValue::Date(d) => format!("{:.0}", d),
+                        ColumnValue::Json(v) => v.to_string(),
                     };
                     print!(" {:<18} │", display);
                 }
@@ -0,0 +1,75 @@
+//! Custom busy handlers
+//! Installs a JS callback with `sqlite3_busy_handler`, so apps can decide how long to keep
+//! retrying a locked database instead of relying on a fixed `busy_timeout`
+
+use crate::types::DatabaseError;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::os::raw::{c_int, c_void};
+use wasm_bindgen::JsValue;
+
+thread_local! {
+    // Callbacks keyed by connection pointer
+    static BUSY_HANDLERS: RefCell<HashMap<usize, js_sys::Function>> =
+        RefCell::new(HashMap::new());
+}
+
+/// SQLite busy handler: ask the connection's callback whether to retry
+unsafe extern "C" fn call_busy_handler(arg: *mut c_void, count: c_int) -> c_int {
+    // Cloned out so the callback may replace or remove the handler itself
+    let callback = BUSY_HANDLERS.with(|handlers| handlers.borrow().get(&(arg as usize)).cloned());
+    let Some(callback) = callback else {
+        return 0;
+    };
+    match callback.call1(&JsValue::NULL, &JsValue::from(count)) {
+        Ok(retry) => retry.is_truthy() as c_int,
+        Err(e) => {
+            log::warn!("Busy handler threw, giving up: {:?}", e);
+            0
+        }
+    }
+}
+
+/// Install `callback` as the busy handler of `db`, or remove the handler with `None`
+///
+/// Either way SQLite drops any `busy_timeout` set on the connection.
+pub fn install(
+    db: *mut sqlite_wasm_rs::sqlite3,
+    callback: Option<&js_sys::Function>,
+) -> Result<(), DatabaseError> {
+    if db.is_null() {
+        return Err(DatabaseError::new(
+            "DATABASE_CLOSED",
+            "Cannot set a busy handler on a closed database",
+        ));
+    }
+    let ret = match callback {
+        Some(callback) => {
+            BUSY_HANDLERS.with(|handlers| {
+                handlers.borrow_mut().insert(db as usize, callback.clone());
+            });
+            unsafe {
+                sqlite_wasm_rs::sqlite3_busy_handler(db, Some(call_busy_handler), db as *mut c_void)
+            }
+        }
+        None => {
+            remove(db);
+            unsafe { sqlite_wasm_rs::sqlite3_busy_handler(db, None, std::ptr::null_mut()) }
+        }
+    };
+    if ret != sqlite_wasm_rs::SQLITE_OK {
+        return Err(DatabaseError::new(
+            "SQLITE_ERROR",
+            &format!("Failed to set busy handler (code {})", ret),
+        )
+        .with_sqlite_code(ret));
+    }
+    Ok(())
+}
+
+/// Forget the callback of a connection that is being closed
+pub fn remove(db: *mut sqlite_wasm_rs::sqlite3) {
+    BUSY_HANDLERS.with(|handlers| {
+        handlers.borrow_mut().remove(&(db as usize));
+    });
+}
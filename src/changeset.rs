@@ -0,0 +1,278 @@
+//! Changesets for the WASM Database
+//! Records a connection's changes with SQLite's session extension and applies them
+//! elsewhere with a configurable conflict policy
+
+use crate::types::{
+    ChangesetApplyResult, ChangesetConflict, ChangesetConflictKind, ColumnValue, ConflictPolicy,
+    DatabaseError,
+};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_int, c_void};
+
+/// A session recording changes on one connection
+///
+/// Only tables with a PRIMARY KEY are recorded, as required by the session extension.
+pub(crate) struct Session {
+    db: *mut sqlite_wasm_rs::sqlite3,
+    session: *mut sqlite_wasm_rs::sqlite3_session,
+}
+
+impl Session {
+    /// Start recording changes to `tables` (every table when empty)
+    pub(crate) fn start(
+        db: *mut sqlite_wasm_rs::sqlite3,
+        tables: &[String],
+    ) -> Result<Self, DatabaseError> {
+        let tables = tables
+            .iter()
+            .map(|table| CString::new(table.as_str()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| DatabaseError::new("SESSION_ERROR", "Table name contains a NUL byte"))?;
+
+        let mut session = std::ptr::null_mut();
+        let ret =
+            unsafe { sqlite_wasm_rs::sqlite3session_create(db, c"main".as_ptr(), &mut session) };
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            return Err(
+                DatabaseError::new("SESSION_ERROR", "Failed to create changeset session")
+                    .with_sqlite_code(ret),
+            );
+        }
+        let session = Self { db, session };
+
+        let attached = if tables.is_empty() {
+            unsafe { sqlite_wasm_rs::sqlite3session_attach(session.session, std::ptr::null()) }
+        } else {
+            let mut ret = sqlite_wasm_rs::SQLITE_OK;
+            for table in &tables {
+                ret = unsafe {
+                    sqlite_wasm_rs::sqlite3session_attach(session.session, table.as_ptr())
+                };
+                if ret != sqlite_wasm_rs::SQLITE_OK {
+                    break;
+                }
+            }
+            ret
+        };
+        if attached != sqlite_wasm_rs::SQLITE_OK {
+            session.delete(db);
+            return Err(DatabaseError::new(
+                "SESSION_ERROR",
+                "Failed to attach tables to changeset session",
+            )
+            .with_sqlite_code(attached));
+        }
+        Ok(session)
+    }
+
+    /// Binary changeset of every change recorded so far
+    ///
+    /// `db` is the connection currently in use; a session started on a connection that has
+    /// since been closed fails with `SESSION_INVALID`.
+    pub(crate) fn changeset(
+        &self,
+        db: *mut sqlite_wasm_rs::sqlite3,
+    ) -> Result<Vec<u8>, DatabaseError> {
+        if db != self.db {
+            return Err(DatabaseError::new(
+                "SESSION_INVALID",
+                "The connection this session was started on has been closed",
+            ));
+        }
+
+        let mut size: c_int = 0;
+        let mut buffer: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3session_changeset(self.session, &mut size, &mut buffer)
+        };
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            return Err(
+                DatabaseError::new("SESSION_ERROR", "Failed to capture changeset")
+                    .with_sqlite_code(ret),
+            );
+        }
+        let bytes = if buffer.is_null() || size <= 0 {
+            Vec::new()
+        } else {
+            unsafe { std::slice::from_raw_parts(buffer as *const u8, size as usize).to_vec() }
+        };
+        unsafe { sqlite_wasm_rs::sqlite3_free(buffer) };
+        Ok(bytes)
+    }
+
+    /// Stop recording and free the session
+    ///
+    /// Sessions belong to the connection that created them. If that connection has
+    /// already been closed the session is leaked rather than freed against a dangling
+    /// connection.
+    pub(crate) fn delete(self, db: *mut sqlite_wasm_rs::sqlite3) {
+        if db == self.db && !db.is_null() {
+            unsafe { sqlite_wasm_rs::sqlite3session_delete(self.session) };
+        }
+    }
+}
+
+/// State shared with the conflict handler during `sqlite3changeset_apply`
+struct ApplyContext {
+    policy: ConflictPolicy,
+    conflicts: Vec<ChangesetConflict>,
+}
+
+/// Apply a changeset to `db`, resolving conflicts with `policy`
+///
+/// Every conflict is reported in the result. Under `Abort` the first conflict rolls back
+/// the whole changeset and the result has `aborted: true`.
+pub(crate) fn apply_changeset(
+    db: *mut sqlite_wasm_rs::sqlite3,
+    changeset: &[u8],
+    policy: ConflictPolicy,
+) -> Result<ChangesetApplyResult, DatabaseError> {
+    let mut context = ApplyContext {
+        policy,
+        conflicts: Vec::new(),
+    };
+    let ret = unsafe {
+        sqlite_wasm_rs::sqlite3changeset_apply(
+            db,
+            changeset.len() as c_int,
+            changeset.as_ptr() as *mut c_void,
+            None,
+            Some(on_conflict),
+            &mut context as *mut ApplyContext as *mut c_void,
+        )
+    };
+
+    match ret {
+        sqlite_wasm_rs::SQLITE_OK => Ok(ChangesetApplyResult {
+            aborted: false,
+            conflicts: context.conflicts,
+        }),
+        sqlite_wasm_rs::SQLITE_ABORT if policy == ConflictPolicy::Abort => {
+            Ok(ChangesetApplyResult {
+                aborted: true,
+                conflicts: context.conflicts,
+            })
+        }
+        code => {
+            let message = unsafe {
+                let msg = sqlite_wasm_rs::sqlite3_errmsg(db);
+                if msg.is_null() {
+                    "unknown error".to_string()
+                } else {
+                    CStr::from_ptr(msg).to_string_lossy().into_owned()
+                }
+            };
+            Err(DatabaseError::new(
+                "CHANGESET_ERROR",
+                &format!("Failed to apply changeset: {}", message),
+            )
+            .with_sqlite_code(code))
+        }
+    }
+}
+
+unsafe extern "C" fn on_conflict(
+    ctx: *mut c_void,
+    kind: c_int,
+    iter: *mut sqlite_wasm_rs::sqlite3_changeset_iter,
+) -> c_int {
+    let context = unsafe { &mut *(ctx as *mut ApplyContext) };
+    let kind = match kind {
+        sqlite_wasm_rs::SQLITE_CHANGESET_DATA => ChangesetConflictKind::Data,
+        sqlite_wasm_rs::SQLITE_CHANGESET_NOTFOUND => ChangesetConflictKind::NotFound,
+        sqlite_wasm_rs::SQLITE_CHANGESET_CONFLICT => ChangesetConflictKind::Conflict,
+        sqlite_wasm_rs::SQLITE_CHANGESET_CONSTRAINT => ChangesetConflictKind::Constraint,
+        _ => ChangesetConflictKind::ForeignKey,
+    };
+
+    // SQLite only accepts REPLACE for data and primary key conflicts
+    let replaceable = matches!(
+        kind,
+        ChangesetConflictKind::Data | ChangesetConflictKind::Conflict
+    );
+    let (resolution, action) = match context.policy {
+        ConflictPolicy::Abort => (
+            ConflictPolicy::Abort,
+            sqlite_wasm_rs::SQLITE_CHANGESET_ABORT,
+        ),
+        ConflictPolicy::Replace if replaceable => (
+            ConflictPolicy::Replace,
+            sqlite_wasm_rs::SQLITE_CHANGESET_REPLACE,
+        ),
+        _ => (ConflictPolicy::Skip, sqlite_wasm_rs::SQLITE_CHANGESET_OMIT),
+    };
+
+    let (table, operation, primary_key) = if kind == ChangesetConflictKind::ForeignKey {
+        // The iterator does not point at a row for foreign key conflicts
+        (String::new(), String::new(), Vec::new())
+    } else {
+        unsafe { describe_change(iter) }
+    };
+    context.conflicts.push(ChangesetConflict {
+        table,
+        operation,
+        kind,
+        primary_key,
+        resolution,
+    });
+    action
+}
+
+/// Table, operation and primary key values of the change an iterator points at
+unsafe fn describe_change(
+    iter: *mut sqlite_wasm_rs::sqlite3_changeset_iter,
+) -> (String, String, Vec<ColumnValue>) {
+    let mut table = std::ptr::null();
+    let mut column_count: c_int = 0;
+    let mut op: c_int = 0;
+    let mut indirect: c_int = 0;
+    unsafe {
+        sqlite_wasm_rs::sqlite3changeset_op(
+            iter,
+            &mut table,
+            &mut column_count,
+            &mut op,
+            &mut indirect,
+        )
+    };
+    let table = if table.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(table) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    let operation = match op {
+        sqlite_wasm_rs::SQLITE_INSERT => "INSERT",
+        sqlite_wasm_rs::SQLITE_UPDATE => "UPDATE",
+        _ => "DELETE",
+    }
+    .to_string();
+
+    let mut pk_flags = std::ptr::null_mut();
+    let mut pk_columns: c_int = 0;
+    let mut primary_key = Vec::new();
+    if unsafe { sqlite_wasm_rs::sqlite3changeset_pk(iter, &mut pk_flags, &mut pk_columns) }
+        == sqlite_wasm_rs::SQLITE_OK
+        && !pk_flags.is_null()
+    {
+        let flags = unsafe { std::slice::from_raw_parts(pk_flags, pk_columns.max(0) as usize) };
+        for (column, _) in flags.iter().enumerate().filter(|(_, is_pk)| **is_pk != 0) {
+            let mut value = std::ptr::null_mut();
+            // Inserts only carry new values; updates and deletes carry the key in the old ones
+            let ret = unsafe {
+                if op == sqlite_wasm_rs::SQLITE_INSERT {
+                    sqlite_wasm_rs::sqlite3changeset_new(iter, column as c_int, &mut value)
+                } else {
+                    sqlite_wasm_rs::sqlite3changeset_old(iter, column as c_int, &mut value)
+                }
+            };
+            primary_key.push(if ret == sqlite_wasm_rs::SQLITE_OK && !value.is_null() {
+                crate::functions::read_value(value)
+            } else {
+                ColumnValue::Null
+            });
+        }
+    }
+    (table, operation, primary_key)
+}
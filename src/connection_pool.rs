@@ -7,6 +7,12 @@ use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 
+// sqlite-wasm-rs leaves `sqlite3_close_v2` out of its bindings, but the bundled
+// library exports it
+unsafe extern "C" {
+    fn sqlite3_close_v2(db: *mut sqlite_wasm_rs::sqlite3) -> std::os::raw::c_int;
+}
+
 thread_local! {
     /// Global registry of shared SQLite connections
     /// CRITICAL: ConnectionState no longer wrapped in RefCell to prevent reentrancy panics
@@ -19,6 +25,10 @@ pub struct ConnectionState {
     pub db: Cell<*mut sqlite_wasm_rs::sqlite3>,
     pub ref_count: Cell<usize>,
     pub db_name: String,
+    /// Bumped whenever a statement on this connection changes the schema
+    pub schema_generation: Cell<u64>,
+    /// Last `PRAGMA schema_version` read on this connection
+    pub schema_version: Cell<Option<i64>>,
 }
 
 impl ConnectionState {
@@ -27,6 +37,8 @@ impl ConnectionState {
             db: Cell::new(db),
             ref_count: Cell::new(1),
             db_name,
+            schema_generation: Cell::new(0),
+            schema_version: Cell::new(None),
         }
     }
 }
@@ -92,7 +104,8 @@ pub fn release_connection(db_name: &str) {
                 unsafe {
                     if !db_ptr.is_null() {
                         log::debug!("Closing SQLite connection for {}", db_name);
-                        sqlite_wasm_rs::sqlite3_close(db_ptr);
+                        // close_v2 defers deallocation until any outstanding statements are finalized
+                        sqlite3_close_v2(db_ptr);
                         conn.db.set(std::ptr::null_mut());
                     }
                 }
@@ -117,6 +130,11 @@ pub fn connection_exists(db_name: &str) -> bool {
     CONNECTION_POOL.with(|pool| pool.borrow().contains_key(db_name))
 }
 
+/// Look up the pooled connection for the given database without taking a reference
+pub fn get_connection(db_name: &str) -> Option<Rc<ConnectionState>> {
+    CONNECTION_POOL.with(|pool| pool.borrow().get(db_name).cloned())
+}
+
 /// Force close a connection, regardless of reference count
 /// Used during import operations to ensure clean state
 pub fn force_close_connection(db_name: &str) {
@@ -134,7 +152,8 @@ pub fn force_close_connection(db_name: &str) {
                         db_name,
                         ref_count
                     );
-                    sqlite_wasm_rs::sqlite3_close(db_ptr);
+                    // close_v2 so statements cached by other instances don't keep the connection open
+                    sqlite3_close_v2(db_ptr);
                     conn.db.set(std::ptr::null_mut());
                 }
             }
@@ -1,4 +1,5 @@
-use crate::types::{ColumnValue, DatabaseConfig, DatabaseError, QueryResult, Row};
+use crate::migrations::Migration;
+use crate::types::{BatchReport, ColumnValue, DatabaseConfig, DatabaseError, QueryResult, Row};
 use crate::vfs::IndexedDBVFS;
 use rusqlite::{Connection, Statement, params_from_iter};
 use std::time::Instant;
@@ -107,8 +108,16 @@ impl SqliteIndexedDB {
     pub async fn new(config: DatabaseConfig) -> Result<Self, DatabaseError> {
         log::info!("Creating SQLiteIndexedDB with config: {:?}", config);
 
+        let block_size = config.block_size.unwrap_or(crate::storage::BLOCK_SIZE);
+        if let Some(algo) = config.checksum_algorithm {
+            crate::storage::metadata::set_default_checksum_algorithm(&config.name, algo);
+        }
+        if let Some(capacity) = config.block_cache_capacity {
+            crate::storage::block_storage::set_block_cache_capacity(&config.name, capacity.max(1));
+        }
+
         // Create the IndexedDB VFS
-        let vfs = IndexedDBVFS::new(&config.name).await?;
+        let vfs = IndexedDBVFS::new_with_block_size(&config.name, block_size).await?;
 
         // With fs_persist: use real filesystem persistence
         #[cfg(feature = "fs_persist")]
@@ -121,7 +130,7 @@ impl SqliteIndexedDB {
                 .to_string();
 
             // Create BlockStorage for filesystem persistence
-            let storage = BlockStorage::new(&storage_name)
+            let storage = BlockStorage::new_with_block_size(&storage_name, block_size)
                 .await
                 .map_err(|e| DatabaseError::new("BLOCKSTORAGE_ERROR", &e.to_string()))?;
 
@@ -309,21 +318,37 @@ impl SqliteIndexedDB {
                 .collect();
 
             // Execute query and collect rows
+            let invalid_utf8 = self.config.invalid_utf8.unwrap_or_default();
             let rows = stmt
                 .query_map(params_from_iter(rusqlite_params.iter()), |row| {
+                    // Decoding errors are surfaced after the row is read
                     let mut values = Vec::new();
                     for i in 0..result.columns.len() {
                         let value = row.get_ref(i)?;
-                        values.push(ColumnValue::from_rusqlite_value(&value.into()));
+                        match ColumnValue::from_rusqlite_value_ref(value, invalid_utf8) {
+                            Ok(value) => values.push(value),
+                            Err(e) => return Ok(Err(e)),
+                        }
                     }
-                    Ok(Row { values })
+                    Ok(Ok(Row { values }))
                 })
                 .map_err(|e| DatabaseError::from(e).with_sql(sql))?;
 
             for row in rows {
-                result
-                    .rows
-                    .push(row.map_err(|e| DatabaseError::from(e).with_sql(sql))?);
+                if let Some(limit) = self.config.max_result_rows {
+                    if result.rows.len() >= limit {
+                        return Err(DatabaseError::new(
+                            "RESULT_TOO_LARGE",
+                            &format!(
+                                "Query returned more than {} rows (max_result_rows). Add a LIMIT or raise max_result_rows.",
+                                limit
+                            ),
+                        )
+                        .with_sql(sql));
+                    }
+                }
+                let row = row.map_err(|e| DatabaseError::from(e).with_sql(sql))?;
+                result.rows.push(row.map_err(|e| e.with_sql(sql))?);
             }
         } else {
             // Handle INSERT/UPDATE/DELETE queries
@@ -401,6 +426,97 @@ impl SqliteIndexedDB {
         Ok(())
     }
 
+    /// Execute multiple SQL statements, carrying on past the ones that fail
+    ///
+    /// Each statement runs on its own, so a failure only loses that statement; the
+    /// report lists every statement's outcome. Use it for ETL-style loads that should
+    /// log and skip bad rows rather than abort.
+    pub async fn execute_batch_best_effort(
+        &mut self,
+        statements: &[String],
+    ) -> Result<BatchReport, DatabaseError> {
+        log::debug!(
+            "Executing best-effort batch of {} statements",
+            statements.len()
+        );
+        let mut report = BatchReport::default();
+
+        for (i, sql) in statements.iter().enumerate() {
+            match self.execute(sql).await {
+                Ok(result) => report.record_success(i, result.affected_rows, result.last_insert_id),
+                Err(e) => {
+                    log::warn!("Batch statement {} failed, continuing: {}", i, e.message);
+                    report.record_failure(i, &e);
+                }
+            }
+        }
+
+        log::debug!(
+            "Best-effort batch finished: {} succeeded, {} failed",
+            report.succeeded,
+            report.failed
+        );
+        Ok(report)
+    }
+
+    /// Apply schema migrations newer than the database's `user_version`
+    ///
+    /// Each migration runs in its own IMMEDIATE transaction that also sets `user_version`
+    /// to its version. The first failure rolls that migration back and stops with
+    /// `MIGRATION_FAILED`; earlier migrations stay applied. Returns the versions applied.
+    pub async fn migrate(&mut self, migrations: &[Migration]) -> Result<Vec<u32>, DatabaseError> {
+        let ordered = crate::migrations::in_order(migrations)?;
+        let mut applied = Vec::new();
+        let mut failure = None;
+        for migration in ordered {
+            match self.apply_migration(migration) {
+                Ok(true) => {
+                    log::info!("Migrated to version {}", migration.version);
+                    applied.push(migration.version);
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    failure = Some(crate::migrations::migration_failed(migration, &e));
+                    break;
+                }
+            }
+        }
+
+        if !applied.is_empty() {
+            self.sync().await?;
+        }
+        match failure {
+            Some(err) => Err(err),
+            None => Ok(applied),
+        }
+    }
+
+    /// Run one migration in its own transaction; false when the database is already past it
+    fn apply_migration(&mut self, migration: &Migration) -> Result<bool, DatabaseError> {
+        let tx = self
+            .connection
+            .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+        let current: i64 = tx.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if current >= migration.version as i64 {
+            return Ok(false);
+        }
+        tx.execute_batch(&migration.up_sql)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Replace `busy_timeout` with `callback`, or remove the busy handler with `None`
+    ///
+    /// The callback gets the number of times SQLite has already retried the locked
+    /// operation and returns whether to try again. Without a handler a locked database
+    /// fails immediately with `SQLITE_BUSY`.
+    pub fn set_busy_handler(&self, callback: Option<fn(i32) -> bool>) -> Result<(), DatabaseError> {
+        self.connection
+            .busy_handler(callback)
+            .map_err(DatabaseError::from)
+    }
+
     pub async fn sync(&mut self) -> Result<(), DatabaseError> {
         #[cfg(feature = "fs_persist")]
         {
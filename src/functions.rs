@@ -0,0 +1,595 @@
+//! Custom SQL functions for the WASM Database
+//! Registers JavaScript callbacks as SQLite scalar and aggregate functions, converting
+//! arguments and results between SQLite values and plain JavaScript values
+
+use crate::types::{ColumnValue, DatabaseError};
+use std::ffi::CString;
+use std::os::raw::{c_int, c_void};
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+
+/// Subtype SQLite's JSON functions attach to the text they return (`'J'`)
+pub(crate) const JSON_SUBTYPE: u32 = b'J' as u32;
+
+/// Read a function argument as a ColumnValue
+pub(crate) fn read_value(value: *mut sqlite_wasm_rs::sqlite3_value) -> ColumnValue {
+    unsafe {
+        match sqlite_wasm_rs::sqlite3_value_type(value) {
+            sqlite_wasm_rs::SQLITE_INTEGER => {
+                ColumnValue::Integer(sqlite_wasm_rs::sqlite3_value_int64(value))
+            }
+            sqlite_wasm_rs::SQLITE_FLOAT => {
+                ColumnValue::Real(sqlite_wasm_rs::sqlite3_value_double(value))
+            }
+            sqlite_wasm_rs::SQLITE_TEXT => {
+                let text_ptr = sqlite_wasm_rs::sqlite3_value_text(value);
+                let len = sqlite_wasm_rs::sqlite3_value_bytes(value);
+                if text_ptr.is_null() {
+                    ColumnValue::Null
+                } else {
+                    let bytes = std::slice::from_raw_parts(text_ptr, len as usize);
+                    ColumnValue::Text(String::from_utf8_lossy(bytes).into_owned())
+                }
+            }
+            sqlite_wasm_rs::SQLITE_BLOB => {
+                let blob_ptr = sqlite_wasm_rs::sqlite3_value_blob(value);
+                let len = sqlite_wasm_rs::sqlite3_value_bytes(value);
+                if blob_ptr.is_null() || len == 0 {
+                    ColumnValue::Blob(vec![])
+                } else {
+                    ColumnValue::Blob(
+                        std::slice::from_raw_parts(blob_ptr as *const u8, len as usize).to_vec(),
+                    )
+                }
+            }
+            _ => ColumnValue::Null,
+        }
+    }
+}
+
+/// Read all arguments passed to a function call
+pub(crate) fn read_args(
+    argc: c_int,
+    argv: *mut *mut sqlite_wasm_rs::sqlite3_value,
+) -> Vec<ColumnValue> {
+    (0..argc.max(0) as usize)
+        .map(|i| read_value(unsafe { *argv.add(i) }))
+        .collect()
+}
+
+/// Convert a ColumnValue into the plain JavaScript value passed to callbacks
+pub(crate) fn column_to_js(value: &ColumnValue) -> JsValue {
+    match value {
+        ColumnValue::Null => JsValue::NULL,
+        ColumnValue::Integer(i) => JsValue::from_f64(*i as f64),
+        ColumnValue::Real(f) => JsValue::from_f64(*f),
+        ColumnValue::Text(s) => JsValue::from_str(s),
+        ColumnValue::Blob(b) => js_sys::Uint8Array::from(b.as_slice()).into(),
+        ColumnValue::Date(ms) => js_sys::Date::new(&JsValue::from_f64(*ms as f64)).into(),
+        ColumnValue::BigInt(s) => JsValue::from_str(s),
+        ColumnValue::Json(v) => js_sys::JSON::parse(&v.to_string()).unwrap_or(JsValue::NULL),
+    }
+}
+
+/// Convert a value returned by a callback into a ColumnValue
+///
+/// Integral numbers become INTEGER, other numbers REAL, booleans 0/1, strings TEXT,
+/// typed arrays BLOB, Dates their millisecond timestamp, and null/undefined NULL.
+pub(crate) fn js_to_column(value: &JsValue) -> Result<ColumnValue, String> {
+    if value.is_null() || value.is_undefined() {
+        Ok(ColumnValue::Null)
+    } else if let Some(b) = value.as_bool() {
+        Ok(ColumnValue::Integer(b as i64))
+    } else if let Some(n) = value.as_f64() {
+        if n.fract() == 0.0 && n >= i64::MIN as f64 && n <= i64::MAX as f64 {
+            Ok(ColumnValue::Integer(n as i64))
+        } else {
+            Ok(ColumnValue::Real(n))
+        }
+    } else if let Some(s) = value.as_string() {
+        Ok(ColumnValue::Text(s))
+    } else if value.is_bigint() {
+        let text = value
+            .unchecked_ref::<js_sys::BigInt>()
+            .to_string(10)
+            .map(String::from)
+            .map_err(|e| describe_js_error(&e.into()))?;
+        text.parse::<i64>()
+            .map(ColumnValue::Integer)
+            .map_err(|_| format!("BigInt {} does not fit in a 64-bit integer", text))
+    } else if let Some(date) = value.dyn_ref::<js_sys::Date>() {
+        Ok(ColumnValue::Integer(date.get_time() as i64))
+    } else if let Some(bytes) = value.dyn_ref::<js_sys::Uint8Array>() {
+        Ok(ColumnValue::Blob(bytes.to_vec()))
+    } else if let Some(buffer) = value.dyn_ref::<js_sys::ArrayBuffer>() {
+        Ok(ColumnValue::Blob(js_sys::Uint8Array::new(buffer).to_vec()))
+    } else {
+        Err("Function returned a value that cannot be stored in SQLite".to_string())
+    }
+}
+
+/// Set the result of a function call
+pub(crate) fn set_result(ctx: *mut sqlite_wasm_rs::sqlite3_context, value: &ColumnValue) {
+    unsafe {
+        match value {
+            ColumnValue::Null => sqlite_wasm_rs::sqlite3_result_null(ctx),
+            ColumnValue::Integer(i) | ColumnValue::Date(i) => {
+                sqlite_wasm_rs::sqlite3_result_int64(ctx, *i)
+            }
+            ColumnValue::Real(f) => sqlite_wasm_rs::sqlite3_result_double(ctx, *f),
+            ColumnValue::Text(s) | ColumnValue::BigInt(s) => sqlite_wasm_rs::sqlite3_result_text(
+                ctx,
+                s.as_ptr() as *const _,
+                s.len() as c_int,
+                sqlite_wasm_rs::SQLITE_TRANSIENT(),
+            ),
+            ColumnValue::Blob(b) => sqlite_wasm_rs::sqlite3_result_blob(
+                ctx,
+                b.as_ptr() as *const _,
+                b.len() as c_int,
+                sqlite_wasm_rs::SQLITE_TRANSIENT(),
+            ),
+            ColumnValue::Json(v) => {
+                let text = v.to_string();
+                sqlite_wasm_rs::sqlite3_result_text(
+                    ctx,
+                    text.as_ptr() as *const _,
+                    text.len() as c_int,
+                    sqlite_wasm_rs::SQLITE_TRANSIENT(),
+                )
+            }
+        }
+    }
+}
+
+/// Report an error from a function call; the running statement fails with this message
+pub(crate) fn set_error(ctx: *mut sqlite_wasm_rs::sqlite3_context, message: &str) {
+    unsafe {
+        sqlite_wasm_rs::sqlite3_result_error(
+            ctx,
+            message.as_ptr() as *const _,
+            message.len() as c_int,
+        )
+    }
+}
+
+/// Describe an exception thrown by a JavaScript callback
+pub(crate) fn describe_js_error(err: &JsValue) -> String {
+    if let Some(error) = err.dyn_ref::<js_sys::Error>() {
+        String::from(error.message())
+    } else if let Some(message) = err.as_string() {
+        message
+    } else {
+        format!("{:?}", err)
+    }
+}
+
+/// Build the argument array passed to a JavaScript callback
+pub(crate) fn js_args(args: &[ColumnValue]) -> js_sys::Array {
+    let js_args = js_sys::Array::new();
+    for arg in args {
+        js_args.push(&column_to_js(arg));
+    }
+    js_args
+}
+
+/// Call a JavaScript callback with SQLite values and convert its return value
+pub(crate) fn call_js(
+    callback: &js_sys::Function,
+    args: &[ColumnValue],
+) -> Result<ColumnValue, String> {
+    let ret = callback
+        .apply(&JsValue::NULL, &js_args(args))
+        .map_err(|e| describe_js_error(&e))?;
+    js_to_column(&ret)
+}
+
+/// A JavaScript callback registered as a scalar function
+struct ScalarFunction {
+    callback: js_sys::Function,
+}
+
+unsafe extern "C" fn call_scalar_function(
+    ctx: *mut sqlite_wasm_rs::sqlite3_context,
+    argc: c_int,
+    argv: *mut *mut sqlite_wasm_rs::sqlite3_value,
+) {
+    let function = unsafe { &*(sqlite_wasm_rs::sqlite3_user_data(ctx) as *const ScalarFunction) };
+    match call_js(&function.callback, &read_args(argc, argv)) {
+        Ok(value) => set_result(ctx, &value),
+        Err(message) => set_error(ctx, &message),
+    }
+}
+
+/// Release a registered function's callback when SQLite drops the function
+pub(crate) unsafe extern "C" fn destroy_function<T>(app: *mut c_void) {
+    if !app.is_null() {
+        drop(unsafe { Box::from_raw(app as *mut T) });
+    }
+}
+
+/// Text encoding and flags passed to `sqlite3_create_function_v2`
+pub(crate) fn function_flags(deterministic: bool) -> c_int {
+    if deterministic {
+        sqlite_wasm_rs::SQLITE_UTF8 | sqlite_wasm_rs::SQLITE_DETERMINISTIC
+    } else {
+        sqlite_wasm_rs::SQLITE_UTF8
+    }
+}
+
+/// Register a JavaScript callback as a scalar SQL function
+///
+/// `arity` of -1 accepts any number of arguments. Deterministic functions may be used
+/// in generated columns, partial-index WHERE clauses and CHECK constraints. SQLite owns
+/// the callback afterwards and releases it when the function is replaced or the
+/// connection closes.
+pub(crate) fn create_scalar_function(
+    db: *mut sqlite_wasm_rs::sqlite3,
+    name: &str,
+    arity: i32,
+    deterministic: bool,
+    callback: js_sys::Function,
+) -> Result<(), DatabaseError> {
+    let name_cstr = CString::new(name)
+        .map_err(|_| DatabaseError::new("INVALID_FUNCTION_NAME", "Invalid function name"))?;
+    let app = Box::into_raw(Box::new(ScalarFunction { callback }));
+
+    // xDestroy is invoked even when registration fails, so `app` is never leaked
+    let ret = unsafe {
+        sqlite_wasm_rs::sqlite3_create_function_v2(
+            db,
+            name_cstr.as_ptr(),
+            arity,
+            function_flags(deterministic),
+            app as *mut c_void,
+            Some(call_scalar_function),
+            None,
+            None,
+            Some(destroy_function::<ScalarFunction>),
+        )
+    };
+    check_registration(db, name, ret)
+}
+
+/// Remove a scalar or aggregate function registered with `arity` arguments
+///
+/// SQLite drops the function and calls its xDestroy, releasing the JavaScript callback.
+pub(crate) fn remove_function(
+    db: *mut sqlite_wasm_rs::sqlite3,
+    name: &str,
+    arity: i32,
+) -> Result<(), DatabaseError> {
+    let name_cstr = CString::new(name)
+        .map_err(|_| DatabaseError::new("INVALID_FUNCTION_NAME", "Invalid function name"))?;
+    let ret = unsafe {
+        sqlite_wasm_rs::sqlite3_create_function_v2(
+            db,
+            name_cstr.as_ptr(),
+            arity,
+            sqlite_wasm_rs::SQLITE_UTF8,
+            std::ptr::null_mut(),
+            None,
+            None,
+            None,
+            None,
+        )
+    };
+    check_registration(db, name, ret)
+}
+
+/// JavaScript step and final callbacks registered as an aggregate function
+struct AggregateFunction {
+    step: js_sys::Function,
+    finalize: js_sys::Function,
+}
+
+/// Per-group accumulator stored in `sqlite3_aggregate_context`
+///
+/// SQLite zero-fills the context, so a null pointer means no row has been stepped yet.
+type Accumulator = *mut JsValue;
+
+unsafe extern "C" fn call_aggregate_step(
+    ctx: *mut sqlite_wasm_rs::sqlite3_context,
+    argc: c_int,
+    argv: *mut *mut sqlite_wasm_rs::sqlite3_value,
+) {
+    let function =
+        unsafe { &*(sqlite_wasm_rs::sqlite3_user_data(ctx) as *const AggregateFunction) };
+    let slot = unsafe {
+        sqlite_wasm_rs::sqlite3_aggregate_context(ctx, std::mem::size_of::<Accumulator>() as c_int)
+    } as *mut Accumulator;
+    if slot.is_null() {
+        unsafe { sqlite_wasm_rs::sqlite3_result_error_nomem(ctx) };
+        return;
+    }
+
+    let state = unsafe { *slot };
+    let accumulator = if state.is_null() {
+        JsValue::UNDEFINED
+    } else {
+        unsafe { (*state).clone() }
+    };
+    let args = js_args(&read_args(argc, argv));
+    args.unshift(&accumulator);
+
+    match function.step.apply(&JsValue::NULL, &args) {
+        Ok(next) if state.is_null() => unsafe { *slot = Box::into_raw(Box::new(next)) },
+        Ok(next) => unsafe { *state = next },
+        Err(e) => set_error(ctx, &describe_js_error(&e)),
+    }
+}
+
+unsafe extern "C" fn call_aggregate_final(ctx: *mut sqlite_wasm_rs::sqlite3_context) {
+    let function =
+        unsafe { &*(sqlite_wasm_rs::sqlite3_user_data(ctx) as *const AggregateFunction) };
+    // A size of 0 returns null when no row was stepped instead of allocating
+    let slot = unsafe { sqlite_wasm_rs::sqlite3_aggregate_context(ctx, 0) } as *mut Accumulator;
+
+    // Reclaim the accumulator; xFinal runs exactly once per group, even after errors
+    let accumulator = if slot.is_null() || unsafe { (*slot).is_null() } {
+        JsValue::UNDEFINED
+    } else {
+        let state = unsafe { Box::from_raw(*slot) };
+        unsafe { *slot = std::ptr::null_mut() };
+        *state
+    };
+
+    let result = function
+        .finalize
+        .call1(&JsValue::NULL, &accumulator)
+        .map_err(|e| describe_js_error(&e))
+        .and_then(|value| js_to_column(&value));
+    match result {
+        Ok(value) => set_result(ctx, &value),
+        Err(message) => set_error(ctx, &message),
+    }
+}
+
+/// Register JavaScript callbacks as an aggregate SQL function
+///
+/// `step` is called for each row as `step(accumulator, ...args)` and returns the new
+/// accumulator, which starts out `undefined` for every group. `finalize` receives the
+/// last accumulator (still `undefined` for an empty group) and returns the result.
+pub(crate) fn create_aggregate_function(
+    db: *mut sqlite_wasm_rs::sqlite3,
+    name: &str,
+    arity: i32,
+    step: js_sys::Function,
+    finalize: js_sys::Function,
+) -> Result<(), DatabaseError> {
+    let name_cstr = CString::new(name)
+        .map_err(|_| DatabaseError::new("INVALID_FUNCTION_NAME", "Invalid function name"))?;
+    let app = Box::into_raw(Box::new(AggregateFunction { step, finalize }));
+
+    let ret = unsafe {
+        sqlite_wasm_rs::sqlite3_create_function_v2(
+            db,
+            name_cstr.as_ptr(),
+            arity,
+            function_flags(false),
+            app as *mut c_void,
+            None,
+            Some(call_aggregate_step),
+            Some(call_aggregate_final),
+            Some(destroy_function::<AggregateFunction>),
+        )
+    };
+    check_registration(db, name, ret)
+}
+
+/// Map a `sqlite3_create_function_v2` result code to a DatabaseError
+pub(crate) fn check_registration(
+    db: *mut sqlite_wasm_rs::sqlite3,
+    name: &str,
+    ret: c_int,
+) -> Result<(), DatabaseError> {
+    if ret == sqlite_wasm_rs::SQLITE_OK {
+        return Ok(());
+    }
+    let err_msg = unsafe {
+        let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(db);
+        if !msg_ptr.is_null() {
+            std::ffi::CStr::from_ptr(msg_ptr)
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            format!("Unknown error (code: {})", ret)
+        }
+    };
+    Err(DatabaseError::new(
+        "FUNCTION_REGISTRATION_FAILED",
+        &format!("Failed to register function {}: {}", name, err_msg),
+    )
+    .with_sqlite_code(ret))
+}
+
+/// A JavaScript comparator registered as a collation
+struct Collation {
+    compare: js_sys::Function,
+}
+
+unsafe extern "C" fn call_collation(
+    app: *mut c_void,
+    len_a: c_int,
+    a: *const c_void,
+    len_b: c_int,
+    b: *const c_void,
+) -> c_int {
+    let collation = unsafe { &*(app as *const Collation) };
+    let text = |ptr: *const c_void, len: c_int| {
+        if ptr.is_null() || len <= 0 {
+            String::new()
+        } else {
+            let bytes = unsafe { std::slice::from_raw_parts(ptr as *const u8, len as usize) };
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+    };
+    let result = collation.compare.call2(
+        &JsValue::NULL,
+        &JsValue::from_str(&text(a, len_a)),
+        &JsValue::from_str(&text(b, len_b)),
+    );
+    // Collations cannot report errors, so a throwing or non-numeric comparator sorts as equal
+    match result.ok().and_then(|v| v.as_f64()) {
+        Some(n) if n < 0.0 => -1,
+        Some(n) if n > 0.0 => 1,
+        _ => 0,
+    }
+}
+
+/// Register a JavaScript comparator as a collation
+///
+/// `compare(a, b)` receives both strings and returns a negative number, zero or a
+/// positive number, like `Array.prototype.sort` comparators.
+pub(crate) fn create_collation(
+    db: *mut sqlite_wasm_rs::sqlite3,
+    name: &str,
+    compare: js_sys::Function,
+) -> Result<(), DatabaseError> {
+    let name_cstr = CString::new(name)
+        .map_err(|_| DatabaseError::new("INVALID_FUNCTION_NAME", "Invalid collation name"))?;
+    let app = Box::into_raw(Box::new(Collation { compare }));
+
+    let ret = unsafe {
+        sqlite_wasm_rs::sqlite3_create_collation_v2(
+            db,
+            name_cstr.as_ptr(),
+            sqlite_wasm_rs::SQLITE_UTF8,
+            app as *mut c_void,
+            Some(call_collation),
+            Some(destroy_function::<Collation>),
+        )
+    };
+    check_registration(db, name, ret)
+}
+
+/// A single function or collation in an `Extensions` batch
+#[derive(Clone)]
+enum Extension {
+    Scalar {
+        name: String,
+        arity: i32,
+        callback: js_sys::Function,
+        deterministic: bool,
+    },
+    Aggregate {
+        name: String,
+        arity: i32,
+        step: js_sys::Function,
+        finalize: js_sys::Function,
+    },
+    Collation {
+        name: String,
+        compare: js_sys::Function,
+    },
+}
+
+/// Functions and collations installed on a connection as soon as it is opened
+///
+/// Parsed from a JavaScript object of the form
+/// `{ functions: [{ name, arity, fn, deterministic? }], aggregates: [{ name, arity, step, final }],
+/// collations: [{ name, compare }] }`; every key is optional.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    entries: Vec<Extension>,
+}
+
+impl Extensions {
+    /// Parse and validate an extensions object before anything is registered
+    pub fn from_js(value: &JsValue) -> Result<Self, DatabaseError> {
+        let invalid = |msg: String| DatabaseError::new("INVALID_EXTENSIONS", &msg);
+        if value.is_null() || value.is_undefined() {
+            return Ok(Self::default());
+        }
+        if !value.is_object() {
+            return Err(invalid("Extensions must be an object".to_string()));
+        }
+
+        let list = |key: &str| -> Result<Vec<JsValue>, DatabaseError> {
+            let entries = js_sys::Reflect::get(value, &JsValue::from_str(key))
+                .map_err(|e| invalid(describe_js_error(&e)))?;
+            if entries.is_undefined() {
+                return Ok(Vec::new());
+            }
+            if !js_sys::Array::is_array(&entries) {
+                return Err(invalid(format!("'{}' must be an array", key)));
+            }
+            Ok(js_sys::Array::from(&entries).iter().collect())
+        };
+        let field = |entry: &JsValue, key: &str| {
+            js_sys::Reflect::get(entry, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+        };
+        let name_of = |entry: &JsValue, kind: &str| {
+            field(entry, "name")
+                .as_string()
+                .ok_or_else(|| invalid(format!("Every {} needs a string 'name'", kind)))
+        };
+        let function = |entry: &JsValue, key: &str, name: &str| {
+            field(entry, key)
+                .dyn_into::<js_sys::Function>()
+                .map_err(|_| invalid(format!("'{}' of {} must be a function", key, name)))
+        };
+        let arity = |entry: &JsValue, name: &str| match field(entry, "arity").as_f64() {
+            Some(n) if n.fract() == 0.0 && (-1.0..=127.0).contains(&n) => Ok(n as i32),
+            _ => Err(invalid(format!(
+                "'arity' of {} must be an integer from -1 to 127",
+                name
+            ))),
+        };
+
+        let mut entries = Vec::new();
+        for entry in list("functions")? {
+            let name = name_of(&entry, "function")?;
+            entries.push(Extension::Scalar {
+                arity: arity(&entry, &name)?,
+                callback: function(&entry, "fn", &name)?,
+                deterministic: field(&entry, "deterministic").is_truthy(),
+                name,
+            });
+        }
+        for entry in list("aggregates")? {
+            let name = name_of(&entry, "aggregate")?;
+            entries.push(Extension::Aggregate {
+                arity: arity(&entry, &name)?,
+                step: function(&entry, "step", &name)?,
+                finalize: function(&entry, "final", &name)?,
+                name,
+            });
+        }
+        for entry in list("collations")? {
+            let name = name_of(&entry, "collation")?;
+            entries.push(Extension::Collation {
+                compare: function(&entry, "compare", &name)?,
+                name,
+            });
+        }
+        Ok(Self { entries })
+    }
+
+    /// Whether the batch registers nothing
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Register every function and collation on a connection
+    pub(crate) fn install(&self, db: *mut sqlite_wasm_rs::sqlite3) -> Result<(), DatabaseError> {
+        for entry in &self.entries {
+            match entry.clone() {
+                Extension::Scalar {
+                    name,
+                    arity,
+                    callback,
+                    deterministic,
+                } => create_scalar_function(db, &name, arity, deterministic, callback)?,
+                Extension::Aggregate {
+                    name,
+                    arity,
+                    step,
+                    finalize,
+                } => create_aggregate_function(db, &name, arity, step, finalize)?,
+                Extension::Collation { name, compare } => create_collation(db, &name, compare)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -48,6 +48,12 @@ pub fn init_logger() {
 use crate::utils::normalize_db_name;
 
 // Module declarations
+#[cfg(target_arch = "wasm32")]
+mod authorizer;
+#[cfg(target_arch = "wasm32")]
+mod busy_handler;
+#[cfg(target_arch = "wasm32")]
+mod changeset;
 mod cleanup;
 #[cfg(target_arch = "wasm32")]
 pub mod connection_pool;
@@ -58,6 +64,17 @@ pub mod types;
 pub mod vfs;
 #[cfg(not(target_arch = "wasm32"))]
 pub use database::PreparedStatement;
+#[cfg(target_arch = "wasm32")]
+pub mod functions;
+pub mod migrations;
+#[cfg(target_arch = "wasm32")]
+pub mod query_cursor;
+#[cfg(target_arch = "wasm32")]
+pub mod query_timeout;
+#[cfg(target_arch = "wasm32")]
+pub mod result_format;
+#[cfg(target_arch = "wasm32")]
+pub mod self_test;
 pub mod utils;
 
 #[cfg(feature = "telemetry")]
@@ -66,6 +83,11 @@ pub mod telemetry;
 // Re-export main public API
 #[cfg(not(target_arch = "wasm32"))]
 pub use database::SqliteIndexedDB;
+pub use migrations::Migration;
+#[cfg(target_arch = "wasm32")]
+pub use query_cursor::QueryCursor;
+#[cfg(target_arch = "wasm32")]
+pub use self_test::{run_persistence_self_test, run_persistence_self_test_internal};
 
 // WASM: Track databases currently being opened to serialize SQLite connection initialization
 #[cfg(target_arch = "wasm32")]
@@ -74,12 +96,33 @@ thread_local! {
         std::cell::RefCell::new(std::collections::HashSet::new());
 }
 
+// WASM: Number of ATTACHes per database name, so DETACH only unregisters unused storage
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    static ATTACH_COUNTS: std::cell::RefCell<std::collections::HashMap<String, usize>> =
+        std::cell::RefCell::new(std::collections::HashMap::new());
+}
+
 // Type alias for native platforms
 #[cfg(not(target_arch = "wasm32"))]
 pub type Database = SqliteIndexedDB;
 
-pub use types::DatabaseConfig;
-pub use types::{ColumnValue, DatabaseError, QueryResult, Row, TransactionOptions};
+pub use storage::compression::CompressionKind;
+pub use storage::durability::Durability;
+pub use storage::leader_election::LeaderElectionConfig;
+pub use storage::metadata::ChecksumAlgorithm;
+pub use storage::retry_logic::BusyRetryConfig;
+pub use types::{
+    AllocationStats, BatchOperationResult, BatchOptions, BatchReport, BlockCacheStats, CacheStats,
+    ChangesetApplyResult, ChangesetConflict, ChangesetConflictKind, CheckpointMode,
+    CheckpointResult, ColumnInfo, ColumnValue, ConflictPolicy, Coordination, CopyMode,
+    DatabaseError, DatabaseInfo, ForeignKeyInfo, FtsOptions, ImportReport, IndexInfo,
+    IndexedDbHandleStats, IntegrityCheckResult, InvalidUtf8Handling, MemoryReleaseStats,
+    NotificationChannelHealth, Persistence, PersistenceSelfTestReport, PragmaValue, QueryResult,
+    ResultFormat, Row, SelfTestStep, StorageReloadReport, SyncLevel, SyncMode, SyncResult,
+    TableSchema, TableSummary, TransactionBehavior, TransactionOptions, VacuumStats,
+};
+pub use types::{DatabaseConfig, DatabaseConfigBuilder};
 
 // Re-export VFS
 pub use vfs::indexeddb_vfs::IndexedDBVFS;
@@ -102,7 +145,17 @@ pub struct Database {
     #[allow(dead_code)]
     name: String,
     on_data_change_callback: Option<js_sys::Function>,
+    on_leadership_change_callback: Option<js_sys::Function>,
+    /// Called when a sync fails because IndexedDB is out of quota
+    on_storage_full_callback: Option<js_sys::Function>,
+    /// Set when a statement modified the database since the last DataChanged broadcast
+    unbroadcast_writes: std::cell::Cell<bool>,
     allow_non_leader_writes: bool,
+    /// False when the database is only ever opened by this tab (no leader election)
+    multi_tab: bool,
+    /// `Coordination::Disabled` also stops change broadcasts to other tabs
+    coordination: Coordination,
+    read_only: bool,
     optimistic_updates_manager:
         std::cell::RefCell<crate::storage::optimistic_updates::OptimisticUpdatesManager>,
     coordination_metrics_manager:
@@ -114,6 +167,75 @@ pub struct Database {
     #[cfg(feature = "telemetry")]
     span_context: Option<crate::telemetry::SpanContext>,
     max_export_size_bytes: Option<u64>,
+    block_size: usize,
+    max_open_statements: Option<usize>,
+    max_result_rows: Option<usize>,
+    pending_writes: std::cell::RefCell<crate::storage::write_queue::PendingWrites>,
+    statement_cache: std::cell::RefCell<
+        crate::storage::statement_cache::StatementCache<*mut sqlite_wasm_rs::sqlite3_stmt>,
+    >,
+    /// Connection schema generation the cached statements were compiled against
+    statement_cache_generation: std::cell::Cell<u64>,
+    cursor_open: Rc<std::cell::Cell<bool>>,
+    subscriptions: Rc<
+        std::cell::RefCell<crate::storage::subscriptions::SubscriptionManager<js_sys::Function>>,
+    >,
+    /// Functions and collations installed whenever the connection is (re)opened
+    extensions: crate::functions::Extensions,
+    /// How query results are decoded (invalid UTF-8, large integers, dates)
+    read_options: crate::query_cursor::ReadOptions,
+    /// Transactions and savepoints opened through the transaction API
+    transactions: std::cell::RefCell<TransactionState>,
+    /// Backoff policy for statements that hit `SQLITE_BUSY`
+    busy_retry: Option<crate::storage::retry_logic::BusyRetryConfig>,
+    /// Callback installed with `setBusyHandler`, reinstalled whenever the connection reopens
+    busy_handler: Option<js_sys::Function>,
+    /// Callback installed with `setAuthorizer`, reinstalled whenever the connection reopens
+    authorizer: Option<js_sys::Function>,
+    /// `Memory` databases live in SQLite's `memdb` VFS and never touch IndexedDB
+    persistence: Persistence,
+    /// Shape used by `executeFormatted` when no format is passed
+    result_format: ResultFormat,
+    /// Databases attached with `attach()`, by alias
+    attached: std::cell::RefCell<std::collections::HashMap<String, String>>,
+    /// When writes are persisted without an explicit `sync()`
+    sync_mode: SyncMode,
+    /// Delay before an `OnWrite` sync; later writes restart the wait
+    sync_debounce_ms: Option<u32>,
+    /// Set when a statement modified the database since the last sync
+    unsynced_writes: std::cell::Cell<bool>,
+    /// Bumped per debounced sync request so only the latest one runs
+    sync_generation: Rc<std::cell::Cell<u64>>,
+    /// Statements running longer than this fail with `QUERY_TIMEOUT`
+    query_timeout_ms: Option<u32>,
+    /// Changeset sessions opened with `startSession()`, by handle
+    sessions: std::cell::RefCell<std::collections::HashMap<u32, crate::changeset::Session>>,
+    next_session_id: std::cell::Cell<u32>,
+    /// Quiet period after which the WAL is checkpointed in the background
+    idle_checkpoint_ms: Option<u32>,
+    /// `Date.now()` of the latest write through this instance
+    last_write_ms: Rc<std::cell::Cell<f64>>,
+    /// Set while an idle checkpoint is waiting to run
+    idle_checkpoint_pending: Rc<std::cell::Cell<bool>>,
+}
+
+/// What `beginTransaction()` and `savepoint()` have opened on one instance
+#[cfg(target_arch = "wasm32")]
+#[derive(Default)]
+struct TransactionState {
+    /// A `beginTransaction()` transaction that is not yet committed or rolled back
+    explicit: bool,
+    /// Names of the savepoints opened with `savepoint()`, innermost last
+    savepoints: Vec<String>,
+}
+
+/// Statements bracketing one `Database::transaction` call or `beginTransaction()`
+#[cfg(target_arch = "wasm32")]
+struct TransactionScope {
+    begin: String,
+    commit: String,
+    rollback: Vec<String>,
+    nested: bool,
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -128,1009 +250,1689 @@ impl Database {
         db_ptr
     }
 
-    /// Check if a SQL statement is a write operation
-    fn is_write_operation(sql: &str) -> bool {
+    /// Check if a SQL statement changes the schema (or the statistics the planner uses)
+    fn is_schema_change(sql: &str) -> bool {
+        let upper = sql.trim_start().to_uppercase();
+        ["CREATE", "DROP", "ALTER", "ANALYZE", "VACUUM", "REINDEX"]
+            .iter()
+            .any(|keyword| upper.starts_with(keyword))
+    }
+
+    /// Check if SQL writes to the database
+    ///
+    /// The first statement is compiled through the statement cache and classified with
+    /// `sqlite3_stmt_readonly`, then left in the cache unexecuted, so the execution that
+    /// follows runs the statement that was classified instead of preparing it again.
+    /// Classifying leaves the cache hit/miss counters to that execution.
+    /// Writes behind a CTE (`WITH ... INSERT`) count while `EXPLAIN INSERT` does not.
+    /// Any statements after the first are compiled only to be classified. A statement
+    /// that fails to compile (a syntax error, or a table created earlier in the same
+    /// string) falls back to its leading keyword.
+    fn is_write_operation(&self, sql: &str) -> bool {
+        if self.connection_state.db.get().is_null() {
+            return Self::starts_with_write_keyword(sql);
+        }
+        let Ok(sql_cstr) = Self::sql_cstring(sql) else {
+            return Self::starts_with_write_keyword(sql);
+        };
+        let (stmt, fresh) = match self.cached_statement(sql, false) {
+            Some(stmt) => (stmt, false),
+            None => match self.prepare_uncached(&sql_cstr) {
+                Ok(stmt) => (stmt, true),
+                Err(_) => return Self::starts_with_write_keyword(sql),
+            },
+        };
+        // Only whitespace or comments
+        if stmt.is_null() {
+            return false;
+        }
+        let read_only = unsafe { sqlite_wasm_rs::sqlite3_stmt_readonly(stmt) } != 0;
+        let first_len = unsafe { std::ffi::CStr::from_ptr(sqlite_wasm_rs::sqlite3_sql(stmt)) }
+            .to_bytes()
+            .len();
+        let released = if fresh {
+            self.statement_cache.borrow_mut().put_unused(sql, stmt)
+        } else {
+            self.statement_cache.borrow_mut().put(sql, stmt)
+        };
+        for evicted in released {
+            unsafe { sqlite_wasm_rs::sqlite3_finalize(evicted) };
+        }
+        !read_only
+            || sql
+                .get(first_len..)
+                .is_some_and(|rest| self.later_statements_write(rest))
+    }
+
+    /// Classify the statements following the first one in a multi-statement string
+    fn later_statements_write(&self, rest: &str) -> bool {
+        let Ok(rest_cstr) = Self::sql_cstring(rest) else {
+            return Self::starts_with_write_keyword(rest);
+        };
+        let mut next = rest_cstr.as_ptr();
+        loop {
+            let mut stmt = std::ptr::null_mut();
+            let mut tail = std::ptr::null();
+            let ret = unsafe {
+                sqlite_wasm_rs::sqlite3_prepare_v2(self.db(), next, -1, &mut stmt, &mut tail)
+            };
+            if ret != sqlite_wasm_rs::SQLITE_OK {
+                let remaining = unsafe { std::ffi::CStr::from_ptr(next) }.to_string_lossy();
+                return Self::starts_with_write_keyword(&remaining);
+            }
+            if stmt.is_null() {
+                return false;
+            }
+            let read_only = unsafe { sqlite_wasm_rs::sqlite3_stmt_readonly(stmt) } != 0;
+            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+            if !read_only {
+                return true;
+            }
+            next = tail;
+        }
+    }
+
+    /// Whether SQL is a `BEGIN IMMEDIATE` or `BEGIN EXCLUSIVE`, which takes the write lock
+    fn begins_write_transaction(sql: &str) -> bool {
+        let words: Vec<String> = sql
+            .split_whitespace()
+            .take(2)
+            .map(|word| word.trim_end_matches(';').to_uppercase())
+            .collect();
+        words.first().is_some_and(|word| word == "BEGIN")
+            && words
+                .get(1)
+                .is_some_and(|word| word == "IMMEDIATE" || word == "EXCLUSIVE")
+    }
+
+    /// Whether SQL commits a transaction that has already written
+    fn commits_write_transaction(&self, sql: &str) -> bool {
+        let upper = sql.trim_start().to_uppercase();
+        (upper.starts_with("COMMIT") || upper.starts_with("END"))
+            && unsafe { sqlite_wasm_rs::sqlite3_txn_state(self.db(), std::ptr::null()) }
+                == sqlite_wasm_rs::SQLITE_TXN_WRITE
+    }
+
+    /// Keyword check used when a statement can't be compiled for classification
+    fn starts_with_write_keyword(sql: &str) -> bool {
         let upper = sql.trim().to_uppercase();
-        upper.starts_with("INSERT")
-            || upper.starts_with("UPDATE")
-            || upper.starts_with("DELETE")
-            || upper.starts_with("REPLACE")
+        Self::is_schema_change(sql)
+            || ["INSERT", "UPDATE", "DELETE", "REPLACE"]
+                .iter()
+                .any(|keyword| upper.starts_with(keyword))
     }
 
-    /// Get metrics for observability
+    /// Connection pool key for this database
     ///
-    /// Returns a reference to the Metrics instance for tracking queries, errors, and performance
-    #[cfg(feature = "telemetry")]
-    pub fn metrics(&self) -> Option<&crate::telemetry::Metrics> {
-        self.metrics.as_ref()
+    /// Read-only instances get their own `SQLITE_OPEN_READONLY` connection rather than
+    /// sharing the read-write one.
+    fn pool_key(&self) -> String {
+        Self::connection_pool_key(&self.name, self.read_only, self.persistence)
     }
 
-    /// Check write permission - only leader can write (unless override enabled)
-    async fn check_write_permission(&mut self, sql: &str) -> Result<(), DatabaseError> {
-        if !Self::is_write_operation(sql) {
-            // Not a write operation, allow it
-            return Ok(());
+    pub(crate) fn connection_pool_key(
+        name: &str,
+        read_only: bool,
+        persistence: Persistence,
+    ) -> String {
+        let key = name.trim_end_matches(".db");
+        let key = match persistence {
+            Persistence::IndexedDB => key.to_string(),
+            Persistence::Memory => format!("{}#memory", key),
+        };
+        if read_only {
+            format!("{}#readonly", key)
+        } else {
+            key
         }
+    }
 
-        // Check if non-leader writes are allowed
-        if self.allow_non_leader_writes {
-            log::info!("WRITE_ALLOWED: Non-leader writes enabled for {}", self.name);
-            return Ok(());
+    /// Filename and VFS name passed to `sqlite3_open_v2`
+    ///
+    /// Memory databases use a `/`-prefixed filename so every connection to the same
+    /// name in this tab sees one shared `memdb` database.
+    fn open_target(name: &str, persistence: Persistence) -> (String, String) {
+        match persistence {
+            Persistence::IndexedDB => (
+                name.to_string(),
+                format!("vfs_{}", name.trim_end_matches(".db")),
+            ),
+            Persistence::Memory => (format!("/{}", name), "memdb".to_string()),
         }
+    }
 
-        // Check if this instance is the leader
-        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+    /// Whether this database lives only in memory
+    fn is_memory(&self) -> bool {
+        self.persistence == Persistence::Memory
+    }
 
-        let db_name = &self.name;
-        let storage_rc = get_storage_with_fallback(db_name);
+    /// Flags for `sqlite3_open_v2`
+    fn open_flags(read_only: bool) -> i32 {
+        let flags = if read_only {
+            sqlite_wasm_rs::SQLITE_OPEN_READONLY
+        } else {
+            sqlite_wasm_rs::SQLITE_OPEN_READWRITE | sqlite_wasm_rs::SQLITE_OPEN_CREATE
+        };
+        // URI filenames let ATTACH pick the attached database's VFS
+        flags | sqlite_wasm_rs::SQLITE_OPEN_URI
+    }
 
-        if let Some(storage) = storage_rc {
-            let is_leader = with_storage_async!(storage, "check_write_permission", |s| s
-                .is_leader())
-            .ok_or_else(|| {
-                DatabaseError::new("BORROW_CONFLICT", "Failed to check leader status")
-            })?;
+    /// `busy_timeout` set when the connection opens
+    ///
+    /// Zero with `busy_retry`, since the statement is retried asynchronously instead and
+    /// a blocking wait can never see another connection on this thread release its lock.
+    fn busy_timeout_ms(busy_retry: Option<&crate::storage::retry_logic::BusyRetryConfig>) -> u32 {
+        if busy_retry.is_some() { 0 } else { 10000 }
+    }
 
-            if !is_leader {
-                log::error!("WRITE_DENIED: Instance is not leader for {}", db_name);
-                return Err(DatabaseError::new(
-                    "WRITE_PERMISSION_DENIED",
-                    "Only the leader tab can write to this database. Use db.isLeader() to check status or call db.allowNonLeaderWrites(true) for single-tab mode.",
-                ));
-            }
+    fn read_only_error() -> DatabaseError {
+        DatabaseError::new(
+            "READ_ONLY_DB",
+            "Database was opened with openReadOnly() and cannot be written to. Open it with newDatabase() to write.",
+        )
+    }
 
-            log::info!("WRITE_ALLOWED: Instance is leader for {}", db_name);
+    /// Convert SQL to a C string, explaining embedded NUL bytes instead of failing opaquely
+    fn sql_cstring(sql: &str) -> Result<std::ffi::CString, DatabaseError> {
+        std::ffi::CString::new(sql).map_err(|e| {
+            DatabaseError::new(
+                "NULL_BYTE_IN_SQL",
+                &format!(
+                    "SQL contains a null byte at position {}. SQLite reads SQL as a C string, so the statement would be cut short; pass such values as bound parameters instead.",
+                    e.nul_position()
+                ),
+            )
+            .with_sql(&sql.replace('\0', "\\0"))
+        })
+    }
+
+    /// Reject write statements on a read-only database
+    fn ensure_writable(&self, sql: &str) -> Result<(), DatabaseError> {
+        if self.read_only && self.is_write_operation(sql) {
+            return Err(Self::read_only_error().with_sql(sql));
+        }
+        Ok(())
+    }
+
+    /// Whether the bundled SQLite was built with window function support (3.25+, no
+    /// SQLITE_OMIT_WINDOWFUNC)
+    fn window_functions_supported() -> bool {
+        thread_local! {
+            static SUPPORTED: bool = unsafe {
+                sqlite_wasm_rs::sqlite3_libversion_number() >= 3_025_000
+                    && sqlite_wasm_rs::sqlite3_compileoption_used(c"OMIT_WINDOWFUNC".as_ptr()) == 0
+            };
+        }
+        SUPPORTED.with(|supported| *supported)
+    }
+
+    /// Whether the bundled SQLite was built with SQLITE_ENABLE_FTS5
+    fn fts5_supported() -> bool {
+        thread_local! {
+            static SUPPORTED: bool = unsafe {
+                sqlite_wasm_rs::sqlite3_compileoption_used(c"ENABLE_FTS5".as_ptr()) != 0
+            };
+        }
+        SUPPORTED.with(|supported| *supported)
+    }
+
+    /// Fail with `FTS_UNAVAILABLE` on a build without FTS5
+    fn ensure_fts5() -> Result<(), DatabaseError> {
+        if Self::fts5_supported() {
             Ok(())
         } else {
-            // No storage found - allow by default (single-instance mode)
-            log::info!(
-                "WRITE_ALLOWED: No storage found for {} (single-instance mode)",
-                db_name
-            );
-            Ok(())
+            Err(DatabaseError::new(
+                "FTS_UNAVAILABLE",
+                "This SQLite build does not include FTS5",
+            ))
         }
     }
 
-    pub async fn new(config: DatabaseConfig) -> Result<Self, DatabaseError> {
-        use std::ffi::{CStr, CString};
+    /// Fail with `WINDOW_FUNCTIONS_UNSUPPORTED` instead of a parse error when a query
+    /// uses `OVER`/`WINDOW` on a build without window functions
+    fn ensure_window_functions(sql: &str) -> Result<(), DatabaseError> {
+        if Self::window_functions_supported() {
+            return Ok(());
+        }
+        let uses_window = sql
+            .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .any(|word| word.eq_ignore_ascii_case("OVER") || word.eq_ignore_ascii_case("WINDOW"));
+        if uses_window {
+            return Err(DatabaseError::new(
+                "WINDOW_FUNCTIONS_UNSUPPORTED",
+                "This SQLite build does not support window functions (OVER clauses); check supportsWindowFunctions() before using them",
+            )
+            .with_sql(sql));
+        }
+        Ok(())
+    }
 
-        log::info!("Database::new called for {}", config.name);
+    /// Count statements on the connection that are mid-iteration
+    ///
+    /// Idle cached statements are always reset, so only statements that have been stepped
+    /// and not yet reset or finalized (open cursors, leaked statements) are counted. The
+    /// connection is shared, so statements from every Database instance on it count.
+    fn open_statement_count(&self) -> usize {
+        let db = self.db();
+        let mut count = 0;
+        let mut stmt = unsafe { sqlite_wasm_rs::sqlite3_next_stmt(db, std::ptr::null_mut()) };
+        while !stmt.is_null() {
+            if unsafe { sqlite_wasm_rs::sqlite3_stmt_busy(stmt) } != 0 {
+                count += 1;
+            }
+            stmt = unsafe { sqlite_wasm_rs::sqlite3_next_stmt(db, stmt) };
+        }
+        count
+    }
 
-        // CRITICAL: Use DRY helper to normalize name WITH .db extension
-        // This ensures Database.name, GLOBAL_STORAGE keys, and IndexedDB keys all match
-        let normalized_name = normalize_db_name(&config.name);
+    /// Fail with `TOO_MANY_OPEN_STATEMENTS` once the open statement cap is reached
+    fn ensure_statement_capacity(&self, sql: &str) -> Result<(), DatabaseError> {
+        let Some(limit) = self.max_open_statements else {
+            return Ok(());
+        };
+        let open = self.open_statement_count();
+        if open >= limit {
+            log::warn!(
+                "{} prepared statements open on {} (limit {})",
+                open,
+                self.name,
+                limit
+            );
+            return Err(DatabaseError::new(
+                "TOO_MANY_OPEN_STATEMENTS",
+                &format!(
+                    "{} prepared statements are open on this connection (limit {}). This usually means a query stream cursor was never exhausted or closed; call cursor.close() when done, or raise max_open_statements.",
+                    open, limit
+                ),
+            )
+            .with_sql(sql));
+        }
+        Ok(())
+    }
 
-        // Use a unique VFS name per database to avoid interference
-        let vfs_name = format!("vfs_{}", normalized_name.trim_end_matches(".db"));
-        let vfs_name_cstr = CString::new(vfs_name.as_str())
-            .map_err(|_| DatabaseError::new("INVALID_VFS_NAME", "Invalid VFS name"))?;
-        let vfs_exists = unsafe {
-            let existing_vfs = sqlite_wasm_rs::sqlite3_vfs_find(vfs_name_cstr.as_ptr());
-            !existing_vfs.is_null()
+    /// Fail with `RESULT_TOO_LARGE` once a result has collected `max_result_rows` rows
+    fn ensure_result_capacity(&self, collected: usize, sql: &str) -> Result<(), DatabaseError> {
+        let Some(limit) = self.max_result_rows else {
+            return Ok(());
         };
+        if collected >= limit {
+            log::warn!(
+                "Query on {} exceeded max_result_rows ({})",
+                self.name,
+                limit
+            );
+            return Err(DatabaseError::new(
+                "RESULT_TOO_LARGE",
+                &format!(
+                    "Query returned more than {} rows (max_result_rows). Add a LIMIT, read it with a query stream cursor, or raise max_result_rows.",
+                    limit
+                ),
+            )
+            .with_sql(sql));
+        }
+        Ok(())
+    }
 
-        if !vfs_exists {
-            // Create and register VFS only if it doesn't exist
-            log::debug!("Creating IndexedDBVFS for: {}", normalized_name);
-            let vfs = crate::vfs::IndexedDBVFS::new(&normalized_name).await?;
-            log::debug!("Registering VFS as '{}'", vfs_name);
-            vfs.register(&vfs_name)?;
-            log::info!("VFS registered successfully");
-        } else {
-            log::info!("VFS '{}' already registered, reusing existing", vfs_name);
-            // Ensure BlockStorage exists for this database in the registry
-            // The existing VFS will find it via STORAGE_REGISTRY
-            let _vfs = crate::vfs::IndexedDBVFS::new(&normalized_name).await?;
-            log::info!("BlockStorage ensured for {}", normalized_name);
+    /// Prepare a statement, reusing a cached compiled statement for identical SQL
+    ///
+    /// Returns the SQLite result code if preparation fails.
+    fn prepare_cached(
+        &self,
+        sql: &str,
+        sql_cstr: &std::ffi::CStr,
+    ) -> Result<*mut sqlite_wasm_rs::sqlite3_stmt, i32> {
+        match self.cached_statement(sql, true) {
+            Some(stmt) => Ok(stmt),
+            None => self.prepare_uncached(sql_cstr),
         }
+    }
 
-        // CRITICAL: Synchronize SQLite connection opening to prevent WAL initialization conflicts
-        // Wait if another task is currently opening a connection to this database
-        #[cfg(target_arch = "wasm32")]
-        {
-            const MAX_OPEN_WAIT_MS: u32 = 1000; // Reduced from 5000 - 1s is sufficient
-            const OPEN_POLL_MS: u32 = 10;
-            let max_open_attempts = MAX_OPEN_WAIT_MS / OPEN_POLL_MS;
+    /// Take the reusable cached statement for `sql`, reset and with bindings cleared
+    ///
+    /// `counted` records the lookup as a cache hit or miss.
+    fn cached_statement(
+        &self,
+        sql: &str,
+        counted: bool,
+    ) -> Option<*mut sqlite_wasm_rs::sqlite3_stmt> {
+        let db = self.db();
+        // Statements compiled before a schema change may report stale columns
+        let generation = self.connection_state.schema_generation.get();
+        if self.statement_cache_generation.replace(generation) != generation {
+            self.finalize_statement_cache();
+        }
+        let stmt = {
+            let mut cache = self.statement_cache.borrow_mut();
+            if counted {
+                cache.take(sql)
+            } else {
+                cache.take_uncounted(sql)
+            }
+        }?;
+        // Statements compiled against a previous connection (import/reload) can't be reused
+        if unsafe { sqlite_wasm_rs::sqlite3_db_handle(stmt) } == db {
+            unsafe {
+                sqlite_wasm_rs::sqlite3_reset(stmt);
+                sqlite_wasm_rs::sqlite3_clear_bindings(stmt);
+            }
+            return Some(stmt);
+        }
+        unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+        None
+    }
 
-            for attempt in 0..max_open_attempts {
-                let can_open = DB_OPEN_IN_PROGRESS.with(|opens| {
-                    let mut set = opens.borrow_mut();
-                    if set.contains(&config.name) {
-                        false // Someone else is opening
-                    } else {
-                        set.insert(config.name.clone());
-                        true // We got it
-                    }
-                });
+    /// Compile the first statement of `sql_cstr` without consulting the cache
+    fn prepare_uncached(
+        &self,
+        sql_cstr: &std::ffi::CStr,
+    ) -> Result<*mut sqlite_wasm_rs::sqlite3_stmt, i32> {
+        let mut stmt = std::ptr::null_mut();
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_prepare_v2(
+                self.db(),
+                sql_cstr.as_ptr(),
+                -1,
+                &mut stmt,
+                std::ptr::null_mut(),
+            )
+        };
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            return Err(ret);
+        }
+        Ok(stmt)
+    }
 
-                if can_open {
-                    web_sys::console::log_1(
-                        &format!("[DB] {} - ACQUIRED sqlite open lock", config.name).into(),
-                    );
+    /// Record that a statement modified the database
+    fn mark_written(&self) {
+        self.unbroadcast_writes.set(true);
+        self.unsynced_writes.set(true);
+        self.last_write_ms.set(js_sys::Date::now());
+        self.schedule_idle_checkpoint();
+    }
+
+    /// Checkpoint the WAL once `idle_checkpoint_ms` pass without a write
+    ///
+    /// A single background task per instance waits on `last_write_ms`, so writes during
+    /// the wait push the checkpoint back instead of queueing more tasks. The checkpoint is
+    /// PASSIVE and is deferred to the next idle period while a transaction is open.
+    fn schedule_idle_checkpoint(&self) {
+        let Some(idle_ms) = self.idle_checkpoint_ms.filter(|ms| *ms > 0) else {
+            return;
+        };
+        if self.is_memory() || self.idle_checkpoint_pending.replace(true) {
+            return;
+        }
+        let pending = Rc::clone(&self.idle_checkpoint_pending);
+        let last_write_ms = Rc::clone(&self.last_write_ms);
+        let connection_state = Rc::clone(&self.connection_state);
+        let db_name = self.name.clone();
+
+        wasm_bindgen_futures::spawn_local(async move {
+            loop {
+                let idle_for = js_sys::Date::now() - last_write_ms.get();
+                if idle_for < idle_ms as f64 {
+                    crate::storage::retry_logic::sleep_ms((idle_ms as f64 - idle_for).ceil() as u32)
+                        .await;
+                    continue;
+                }
+                let db = connection_state.db.get();
+                if db.is_null() {
                     break;
-                } else {
-                    web_sys::console::log_1(
-                        &format!(
-                            "[DB] {} - Waiting for sqlite open (attempt {})",
-                            config.name, attempt
-                        )
-                        .into(),
-                    );
-                    use wasm_bindgen_futures::JsFuture;
-                    let promise = js_sys::Promise::new(&mut |resolve, _| {
-                        web_sys::window()
-                            .unwrap()
-                            .set_timeout_with_callback_and_timeout_and_arguments_0(
-                                &resolve,
-                                OPEN_POLL_MS as i32,
-                            )
-                            .unwrap();
-                    });
-                    JsFuture::from(promise).await.ok();
+                }
+                if unsafe { sqlite_wasm_rs::sqlite3_get_autocommit(db) } == 0 {
+                    crate::storage::retry_logic::sleep_ms(idle_ms).await;
                     continue;
                 }
+                let ret = unsafe {
+                    sqlite_wasm_rs::sqlite3_exec(
+                        db,
+                        c"PRAGMA wal_checkpoint(PASSIVE)".as_ptr(),
+                        None,
+                        std::ptr::null_mut(),
+                        std::ptr::null_mut(),
+                    )
+                };
+                log::debug!("Idle checkpoint of {} returned {}", db_name, ret);
+                break;
+            }
+            pending.set(false);
+        });
+    }
+
+    /// Return a successfully executed statement to the cache, finalizing evicted statements
+    fn release_statement(&self, sql: &str, stmt: *mut sqlite_wasm_rs::sqlite3_stmt) {
+        if stmt.is_null() {
+            return;
+        }
+        if unsafe { sqlite_wasm_rs::sqlite3_stmt_readonly(stmt) } == 0 {
+            self.mark_written();
+            if Self::is_schema_change(sql) {
+                self.invalidate_cached_statements();
             }
         }
+        unsafe { sqlite_wasm_rs::sqlite3_reset(stmt) };
+        self.cache_statement(sql, stmt);
+    }
 
-        // Use connection pooling to share connections between instances
-        let (connection_state, db) = {
-            let vfs_name_str = vfs_name.clone(); // Capture the VFS name to use in closure
-            let filename_copy = normalized_name.clone(); // Capture filename for logging
-            let pool_key = normalized_name.trim_end_matches(".db").to_string(); // Pool uses name without .db
-            let state = crate::connection_pool::get_or_create_connection(&pool_key, || {
-                let mut db = std::ptr::null_mut();
-                let db_name = CString::new(normalized_name.clone())
-                    .map_err(|_| "Invalid database name".to_string())?;
-                let vfs_cstr = CString::new(vfs_name_str.as_str())
-                    .map_err(|_| "Invalid VFS name".to_string())?;
-
-                log::info!(
-                    "Opening database: {} with VFS: {}",
-                    filename_copy,
-                    vfs_name_str
-                );
-
-                #[cfg(target_arch = "wasm32")]
-                web_sys::console::log_1(&format!("[OPEN] About to call sqlite3_open_v2...").into());
-
-                let ret = unsafe {
-                    sqlite_wasm_rs::sqlite3_open_v2(
-                        db_name.as_ptr(),
-                        &mut db as *mut _,
-                        sqlite_wasm_rs::SQLITE_OPEN_READWRITE | sqlite_wasm_rs::SQLITE_OPEN_CREATE,
-                        vfs_cstr.as_ptr(),
-                    )
-                };
+    /// Put a statement back in the cache, finalizing whatever it evicts
+    fn cache_statement(&self, sql: &str, stmt: *mut sqlite_wasm_rs::sqlite3_stmt) {
+        let released = self.statement_cache.borrow_mut().put(sql, stmt);
+        for evicted in released {
+            unsafe { sqlite_wasm_rs::sqlite3_finalize(evicted) };
+        }
+    }
 
-                #[cfg(target_arch = "wasm32")]
-                web_sys::console::log_1(
-                    &format!("[OPEN] sqlite3_open_v2 returned: {}", ret).into(),
-                );
+    /// Make every instance on this connection drop its cached statements before the next prepare
+    fn invalidate_cached_statements(&self) {
+        let schema = &self.connection_state.schema_generation;
+        schema.set(schema.get().wrapping_add(1));
+    }
 
-                log::info!(
-                    "sqlite3_open_v2 returned: {} for database: {}",
-                    ret,
-                    filename_copy
-                );
+    /// Finalize all cached statements
+    fn finalize_statement_cache(&self) {
+        let cached = self.statement_cache.borrow_mut().clear();
+        if !cached.is_empty() {
+            log::debug!(
+                "Finalizing {} cached statements for {}",
+                cached.len(),
+                self.name
+            );
+        }
+        for stmt in cached {
+            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+        }
+    }
 
-                if ret != sqlite_wasm_rs::SQLITE_OK {
-                    let err_msg = unsafe {
-                        let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(db);
-                        if !msg_ptr.is_null() {
-                            CStr::from_ptr(msg_ptr).to_string_lossy().into_owned()
-                        } else {
-                            "Unknown error".to_string()
-                        }
-                    };
+    /// Delete every changeset session before the connection they record is closed
+    fn finalize_sessions(&self) {
+        let db = self.db();
+        for (_, session) in self.sessions.borrow_mut().drain() {
+            session.delete(db);
+        }
+    }
 
-                    #[cfg(target_arch = "wasm32")]
-                    web_sys::console::log_1(
-                        &format!(
-                            "[OPEN] ERROR - sqlite3_open_v2 FAILED: ret={}, err={}",
-                            ret, err_msg
-                        )
-                        .into(),
-                    );
+    /// Get the most recent SQLite error message for this connection
+    fn last_error_message(&self) -> String {
+        unsafe {
+            let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
+            if !msg_ptr.is_null() {
+                std::ffi::CStr::from_ptr(msg_ptr)
+                    .to_string_lossy()
+                    .into_owned()
+            } else {
+                "Unknown SQLite error".to_string()
+            }
+        }
+    }
 
-                    return Err(format!(
-                        "Failed to open database with IndexedDB VFS: {}",
-                        err_msg
-                    ));
-                }
+    /// Get the extended result code of the most recent failed SQLite call
+    fn last_error_code(&self) -> i32 {
+        unsafe { sqlite_wasm_rs::sqlite3_extended_errcode(self.db()) }
+    }
 
-                log::info!("Database opened successfully with IndexedDB VFS");
-                Ok(db)
-            })
-            .map_err(|e| DatabaseError::new("CONNECTION_POOL_ERROR", &e))?;
-            let db_ptr = state.db.get();
-            (state, db_ptr)
-        };
+    /// Run a read-only query synchronously through the statement cache
+    fn run_cached_query(
+        &self,
+        sql: &str,
+        params: &[ColumnValue],
+    ) -> Result<QueryResult, DatabaseError> {
+        self.ensure_statement_capacity(sql)?;
+        let start_time = js_sys::Date::now();
+        let sql_cstr = Self::sql_cstring(sql)?;
 
-        // Apply configuration options via PRAGMA statements
-        let exec_sql = |db: *mut sqlite_wasm_rs::sqlite3, sql: &str| -> Result<(), DatabaseError> {
-            let c_sql = CString::new(sql)
-                .map_err(|_| DatabaseError::new("INVALID_SQL", "Invalid SQL statement"))?;
+        let stmt = self.prepare_cached(sql, &sql_cstr).map_err(|ret| {
+            DatabaseError::new(
+                "SQLITE_ERROR",
+                &format!("Failed to prepare statement: {}", self.last_error_message()),
+            )
+            .with_sql(sql)
+            .with_sqlite_code(ret)
+        })?;
 
-            let ret = unsafe {
-                sqlite_wasm_rs::sqlite3_exec(
-                    db,
-                    c_sql.as_ptr(),
-                    None,
-                    std::ptr::null_mut(),
-                    std::ptr::null_mut(),
-                )
-            };
+        if let Err(e) = crate::query_cursor::bind_params(stmt, params) {
+            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+            return Err(e.with_sql(sql));
+        }
 
-            if ret != sqlite_wasm_rs::SQLITE_OK {
-                let err_msg = unsafe {
-                    let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(db);
-                    if !msg_ptr.is_null() {
-                        CStr::from_ptr(msg_ptr).to_string_lossy().into_owned()
-                    } else {
-                        "Unknown error".to_string()
+        let columns = crate::query_cursor::column_names(stmt);
+        let column_count = columns.len() as i32;
+        let mut rows = Vec::new();
+        loop {
+            let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
+            if step_ret == sqlite_wasm_rs::SQLITE_ROW {
+                if let Err(e) = self.ensure_result_capacity(rows.len(), sql) {
+                    unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                    return Err(e);
+                }
+                match crate::query_cursor::read_row(stmt, column_count, self.read_options) {
+                    Ok(row) => rows.push(row),
+                    Err(e) => {
+                        unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                        return Err(e.with_sql(sql));
                     }
-                };
-                log::warn!("Failed to execute SQL '{}': {}", sql, err_msg);
+                }
+            } else if step_ret == sqlite_wasm_rs::SQLITE_DONE {
+                break;
+            } else {
+                let err_msg = self.last_error_message();
+                let sqlite_code = self.last_error_code();
+                unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
                 return Err(DatabaseError::new(
                     "SQLITE_ERROR",
-                    &format!("Failed to execute: {}", err_msg),
-                ));
+                    &format!("Error executing SELECT statement: {}", err_msg),
+                )
+                .with_sql(sql)
+                .with_sqlite_code(sqlite_code));
             }
-            Ok(())
-        };
-
-        // CRITICAL: Set busy_timeout FIRST to handle concurrent access
-        // This makes SQLite wait and retry for up to 10 seconds when the database is locked
-        // instead of immediately returning SQLITE_BUSY errors during parallel operations
-        log::debug!("Setting busy_timeout to 10000ms for concurrent access handling");
-        exec_sql(db, "PRAGMA busy_timeout = 10000")?;
-
-        // Apply page_size (must be set before any tables are created)
-        if let Some(page_size) = config.page_size {
-            log::debug!("Setting page_size to {}", page_size);
-            exec_sql(db, &format!("PRAGMA page_size = {}", page_size))?;
         }
+        self.release_statement(sql, stmt);
+
+        Ok(QueryResult {
+            columns,
+            rows,
+            affected_rows: 0,
+            last_insert_id: None,
+            execution_time_ms: js_sys::Date::now() - start_time,
+        })
+    }
 
-        // Apply cache_size
-        if let Some(cache_size) = config.cache_size {
-            log::debug!("Setting cache_size to {}", cache_size);
-            exec_sql(db, &format!("PRAGMA cache_size = {}", cache_size))?;
+    /// Re-run subscribed queries whose tables changed and deliver the fresh results
+    ///
+    /// Only committed transactions are considered, and each matching subscription
+    /// re-runs once no matter how many of its tables the transaction touched.
+    fn dispatch_subscriptions(&self) {
+        let changed = crate::storage::subscriptions::take_changed_tables(self.db());
+        if changed.is_empty() {
+            return;
         }
 
-        // Apply journal_mode
-        // WAL mode is now fully supported via shared memory (xShm*) implementation
-        if let Some(ref journal_mode) = config.journal_mode {
-            log::debug!("Setting journal_mode to {}", journal_mode);
-
-            let pragma_sql = format!("PRAGMA journal_mode = {}", journal_mode);
-            let c_sql = CString::new(pragma_sql.as_str())
-                .map_err(|_| DatabaseError::new("INVALID_SQL", "Invalid SQL statement"))?;
-
-            let mut stmt: *mut sqlite_wasm_rs::sqlite3_stmt = std::ptr::null_mut();
-            let ret = unsafe {
-                sqlite_wasm_rs::sqlite3_prepare_v2(
-                    db,
-                    c_sql.as_ptr(),
-                    -1,
-                    &mut stmt as *mut _,
-                    std::ptr::null_mut(),
-                )
-            };
-
-            if ret == sqlite_wasm_rs::SQLITE_OK && !stmt.is_null() {
-                let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
-                if step_ret == sqlite_wasm_rs::SQLITE_ROW {
-                    let result_ptr = unsafe { sqlite_wasm_rs::sqlite3_column_text(stmt, 0) };
-                    if !result_ptr.is_null() {
-                        let result_mode = unsafe {
-                            std::ffi::CStr::from_ptr(result_ptr as *const i8)
-                                .to_string_lossy()
-                                .to_uppercase()
-                        };
+        let affected: Vec<(u32, String, Vec<ColumnValue>, js_sys::Function)> = self
+            .subscriptions
+            .borrow()
+            .matching(&changed)
+            .into_iter()
+            .map(|s| (s.id, s.sql.clone(), s.params.clone(), s.callback.clone()))
+            .collect();
+
+        for (id, sql, params, callback) in affected {
+            match self.run_cached_query(&sql, &params) {
+                Ok(result) => Self::deliver_query_result(callback, &result),
+                Err(e) => log::warn!("Subscription {} re-run failed: {}", id, e),
+            }
+        }
+    }
 
-                        if result_mode != journal_mode.to_uppercase() {
-                            log::warn!(
-                                "journal_mode {} requested but SQLite set {}",
-                                journal_mode,
-                                result_mode
-                            );
-                        } else {
-                            log::info!("journal_mode successfully set to {}", result_mode);
-                        }
-                    }
+    /// Invoke a subscription callback with a result on a later microtask
+    ///
+    /// Deferring keeps callbacks from re-entering the Database while it is still borrowed.
+    fn deliver_query_result(callback: js_sys::Function, result: &QueryResult) {
+        match crate::result_format::tagged_result_to_js(result) {
+            Ok(value) => wasm_bindgen_futures::spawn_local(async move {
+                if let Err(e) = callback.call1(&JsValue::NULL, &value) {
+                    log::warn!("Subscription callback threw: {:?}", e);
                 }
-                unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
-            } else {
-                log::warn!("Failed to prepare journal_mode PRAGMA");
-            }
+            }),
+            Err(e) => log::warn!("Failed to serialize subscription result: {}", e),
         }
+    }
 
-        // Apply auto_vacuum (must be set before any tables are created)
-        if let Some(auto_vacuum) = config.auto_vacuum {
-            let vacuum_mode = if auto_vacuum { 1 } else { 0 }; // 0=none, 1=full, 2=incremental
-            log::debug!("Setting auto_vacuum to {}", vacuum_mode);
-            exec_sql(db, &format!("PRAGMA auto_vacuum = {}", vacuum_mode))?;
+    /// Reject queries while a streaming cursor holds the connection
+    fn ensure_no_open_cursor(&self, sql: &str) -> Result<(), DatabaseError> {
+        if self.cursor_open.get() {
+            return Err(DatabaseError::new(
+                "CURSOR_BUSY",
+                "A query stream cursor is still open on this database. Exhaust or close it before running another query.",
+            )
+            .with_sql(sql));
         }
+        Ok(())
+    }
 
-        log::info!("Database configuration applied successfully");
+    /// Get metrics for observability
+    ///
+    /// Returns a reference to the Metrics instance for tracking queries, errors, and performance
+    #[cfg(feature = "telemetry")]
+    pub fn metrics(&self) -> Option<&crate::telemetry::Metrics> {
+        self.metrics.as_ref()
+    }
 
-        // Initialize metrics for telemetry
-        #[cfg(feature = "telemetry")]
-        let metrics = crate::telemetry::Metrics::new().map_err(|e| {
-            DatabaseError::new(
-                "METRICS_ERROR",
-                &format!("Failed to initialize metrics: {}", e),
-            )
-        })?;
+    /// Check write permission - only leader can write (unless override enabled)
+    async fn check_write_permission(&mut self, sql: &str) -> Result<(), DatabaseError> {
+        for db_name in self.required_leaders(sql)? {
+            Self::ensure_leader(&db_name).await?;
+        }
+        Ok(())
+    }
 
-        let database = Database {
-            connection_state,
-            name: normalized_name.clone(), // CRITICAL: Use normalized name WITH .db to match registry
-            on_data_change_callback: None,
-            allow_non_leader_writes: false,
-            optimistic_updates_manager: std::cell::RefCell::new(
-                crate::storage::optimistic_updates::OptimisticUpdatesManager::new(),
-            ),
-            coordination_metrics_manager: std::cell::RefCell::new(
-                crate::storage::coordination_metrics::CoordinationMetricsManager::new(),
-            ),
-            #[cfg(feature = "telemetry")]
-            metrics: Some(metrics),
-            #[cfg(feature = "telemetry")]
-            span_recorder: None,
-            #[cfg(feature = "telemetry")]
-            span_context: Some(crate::telemetry::SpanContext::new()),
-            max_export_size_bytes: config.max_export_size_bytes,
-        };
+    /// The part of `check_write_permission` that needs no await: fails for read-only
+    /// instances, otherwise returns the databases this tab must lead to run `sql`
+    fn required_leaders(&self, sql: &str) -> Result<Vec<String>, DatabaseError> {
+        // Read-only instances never consult the registry or leader state
+        self.ensure_writable(sql)?;
 
-        // CRITICAL: Release the SQLite open lock ONLY after Database is fully constructed
-        // This ensures WAL initialization and all setup completes before another instance can start
-        #[cfg(target_arch = "wasm32")]
+        if !self.coordinates_writes() {
+            return Ok(Vec::new());
+        }
+
+        // Classifying compiles the SQL, so it only runs once leadership matters
+        if !self.is_write_operation(sql)
+            && !Self::begins_write_transaction(sql)
+            && !self.commits_write_transaction(sql)
         {
-            DB_OPEN_IN_PROGRESS.with(|opens| {
-                opens.borrow_mut().remove(&config.name);
-            });
-            web_sys::console::log_1(
-                &format!("[DB] {} - RELEASED sqlite open lock", config.name).into(),
-            );
+            return Ok(Vec::new());
         }
 
-        Ok(database)
+        // Check if non-leader writes are allowed
+        if self.allow_non_leader_writes {
+            log::info!("WRITE_ALLOWED: Non-leader writes enabled for {}", self.name);
+            return Ok(Vec::new());
+        }
+
+        // This instance's database, then attached databases written into
+        let mut leaders = vec![self.name.clone()];
+        leaders.extend(self.attached_write_targets(sql));
+        Ok(leaders)
     }
 
-    /// Open a database with a specific VFS using connection pooling
-    pub async fn open_with_vfs(filename: &str, vfs_name: &str) -> Result<Self, DatabaseError> {
-        use std::ffi::CString;
-
-        log::info!("Opening database {} with VFS {}", filename, vfs_name);
+    /// Whether writes wait on leader election: memory databases are private to this
+    /// instance's tab and single-tab databases have no other writers
+    fn coordinates_writes(&self) -> bool {
+        !self.is_memory() && self.multi_tab
+    }
 
-        // Normalize the database name WITH .db extension
-        let normalized_name = normalize_db_name(filename);
-        let pool_key = normalized_name.trim_end_matches(".db").to_string();
+    /// Fail with `WRITE_PERMISSION_DENIED` unless this tab leads `db_name`
+    async fn ensure_leader(db_name: &str) -> Result<(), DatabaseError> {
+        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
 
-        // Use connection pooling with custom VFS
-        let connection_state = crate::connection_pool::get_or_create_connection(&pool_key, || {
-            let mut db: *mut sqlite_wasm_rs::sqlite3 = std::ptr::null_mut();
-            let db_name = CString::new(normalized_name.clone())
-                .map_err(|_| "Invalid database name".to_string())?;
-            let vfs_cstr = CString::new(vfs_name).map_err(|_| "Invalid VFS name".to_string())?;
+        let storage_rc = get_storage_with_fallback(db_name);
 
-            let ret = unsafe {
-                sqlite_wasm_rs::sqlite3_open_v2(
-                    db_name.as_ptr(),
-                    &mut db as *mut _,
-                    sqlite_wasm_rs::SQLITE_OPEN_READWRITE | sqlite_wasm_rs::SQLITE_OPEN_CREATE,
-                    vfs_cstr.as_ptr(),
-                )
-            };
+        if let Some(storage) = storage_rc {
+            let is_leader = with_storage_async!(storage, "check_write_permission", |s| s
+                .is_leader())
+            .ok_or_else(|| {
+                DatabaseError::new("BORROW_CONFLICT", "Failed to check leader status")
+            })?;
 
-            if ret != sqlite_wasm_rs::SQLITE_OK {
-                let err_msg = if !db.is_null() {
-                    unsafe {
-                        let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(db);
-                        if !msg_ptr.is_null() {
-                            std::ffi::CStr::from_ptr(msg_ptr)
-                                .to_string_lossy()
-                                .into_owned()
-                        } else {
-                            "Unknown error".to_string()
-                        }
-                    }
-                } else {
-                    "Failed to open database".to_string()
-                };
-                return Err(format!("SQLITE_ERROR: {}", err_msg));
+            if !is_leader {
+                log::error!("WRITE_DENIED: Instance is not leader for {}", db_name);
+                return Err(DatabaseError::new(
+                    "WRITE_PERMISSION_DENIED",
+                    "Only the leader tab can write to this database. Use db.isLeader() to check status or call db.allowNonLeaderWrites(true) for single-tab mode.",
+                ));
             }
 
-            Ok(db)
-        })
-        .map_err(|e| DatabaseError::new("OPEN_ERROR", &e))?;
-
-        log::info!(
-            "Successfully opened database {} with VFS {}",
-            normalized_name,
-            vfs_name
-        );
-
-        // Initialize metrics for telemetry
-        #[cfg(feature = "telemetry")]
-        let metrics = crate::telemetry::Metrics::new().map_err(|e| {
-            DatabaseError::new(
-                "METRICS_ERROR",
-                &format!("Failed to initialize metrics: {}", e),
-            )
-        })?;
-
-        Ok(Database {
-            connection_state,
-            name: normalized_name, // CRITICAL: Store normalized name WITH .db
-            on_data_change_callback: None,
-            allow_non_leader_writes: false,
-            optimistic_updates_manager: std::cell::RefCell::new(
-                crate::storage::optimistic_updates::OptimisticUpdatesManager::new(),
-            ),
-            coordination_metrics_manager: std::cell::RefCell::new(
-                crate::storage::coordination_metrics::CoordinationMetricsManager::new(),
-            ),
-            #[cfg(feature = "telemetry")]
-            metrics: Some(metrics),
-            #[cfg(feature = "telemetry")]
-            span_recorder: None,
-            #[cfg(feature = "telemetry")]
-            span_context: Some(crate::telemetry::SpanContext::new()),
-            max_export_size_bytes: Some(2 * 1024 * 1024 * 1024), // Default 2GB limit
-        })
+            log::info!("WRITE_ALLOWED: Instance is leader for {}", db_name);
+            Ok(())
+        } else {
+            // No storage found - allow by default (single-instance mode)
+            log::info!(
+                "WRITE_ALLOWED: No storage found for {} (single-instance mode)",
+                db_name
+            );
+            Ok(())
+        }
     }
 
-    pub async fn execute_internal(&mut self, sql: &str) -> Result<QueryResult, DatabaseError> {
-        use std::ffi::{CStr, CString};
-        let start_time = js_sys::Date::now();
+    /// Attached databases whose alias qualifies a name in `sql` (e.g. `archive.orders`)
+    fn attached_write_targets(&self, sql: &str) -> Vec<String> {
+        let qualifiers = crate::utils::qualified_schema_names(sql);
+        self.attached
+            .borrow()
+            .iter()
+            .filter(|(alias, _)| qualifiers.contains(&alias.to_lowercase()))
+            .map(|(_, db_name)| db_name.clone())
+            .collect()
+    }
 
-        // Create span for query execution and enter context
-        #[cfg(feature = "telemetry")]
-        let span = if self.span_recorder.is_some() {
-            let query_type = sql
-                .trim()
-                .split_whitespace()
-                .next()
-                .unwrap_or("UNKNOWN")
-                .to_uppercase();
-            let mut builder = crate::telemetry::SpanBuilder::new("execute_query".to_string())
-                .with_attribute("query_type", query_type.clone())
-                .with_attribute("sql", sql.to_string());
+    pub async fn new(config: DatabaseConfig) -> Result<Self, DatabaseError> {
+        Self::open(config, false, crate::functions::Extensions::default()).await
+    }
 
-            // Attach baggage from context
-            if let Some(ref context) = self.span_context {
-                builder = builder.with_baggage_from_context(context);
-            }
+    /// Open a database with custom functions and collations already registered
+    ///
+    /// The extensions are installed right after the connection is opened, before any
+    /// configuration PRAGMA runs, so the first query can rely on them. They are
+    /// installed again whenever the connection is reopened (e.g. after an import).
+    pub async fn new_with_extensions(
+        config: DatabaseConfig,
+        extensions: crate::functions::Extensions,
+    ) -> Result<Self, DatabaseError> {
+        Self::open(config, false, extensions).await
+    }
 
-            let span = builder.build();
+    /// Configuration used by `newDatabase` and `openWithExtensions`
+    fn browser_config(normalized_name: &str) -> DatabaseConfig {
+        DatabaseConfig {
+            name: normalized_name.to_string(),
+            version: Some(1),
+            cache_size: Some(10_000),
+            page_size: Some(4096),
+            auto_vacuum: Some(true),
+            journal_mode: Some("WAL".to_string()),
+            max_export_size_bytes: Some(2 * 1024 * 1024 * 1024), // 2GB default
+            ..DatabaseConfig::default()
+        }
+    }
 
-            // Enter span context
-            if let Some(ref context) = self.span_context {
-                context.enter_span(span.span_id.clone());
-            }
+    /// Open a database that lives only in memory (`Persistence::Memory`)
+    pub async fn new_in_memory(mut config: DatabaseConfig) -> Result<Self, DatabaseError> {
+        config.persistence = Some(Persistence::Memory);
+        Self::open(config, false, crate::functions::Extensions::default()).await
+    }
 
-            Some(span)
+    /// Read the integer value of `PRAGMA <name>` on a raw connection
+    fn pragma_integer(db: *mut sqlite_wasm_rs::sqlite3, name: &str) -> Result<i64, DatabaseError> {
+        let c_sql = std::ffi::CString::new(format!("PRAGMA {}", name))
+            .map_err(|_| DatabaseError::new("INVALID_SQL", "Invalid SQL statement"))?;
+        let mut stmt: *mut sqlite_wasm_rs::sqlite3_stmt = std::ptr::null_mut();
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_prepare_v2(
+                db,
+                c_sql.as_ptr(),
+                -1,
+                &mut stmt as *mut _,
+                std::ptr::null_mut(),
+            )
+        };
+        if ret != sqlite_wasm_rs::SQLITE_OK || stmt.is_null() {
+            return Err(DatabaseError::new(
+                "SQLITE_ERROR",
+                &format!("Failed to read PRAGMA {}", name),
+            ));
+        }
+        let value = if unsafe { sqlite_wasm_rs::sqlite3_step(stmt) } == sqlite_wasm_rs::SQLITE_ROW {
+            Ok(unsafe { sqlite_wasm_rs::sqlite3_column_int64(stmt, 0) })
         } else {
-            None
+            Err(DatabaseError::new(
+                "SQLITE_ERROR",
+                &format!("PRAGMA {} returned no value", name),
+            ))
         };
+        unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+        value
+    }
 
-        // Track query execution metrics
-        #[cfg(feature = "telemetry")]
-        #[cfg(feature = "telemetry")]
-        if let Some(metrics) = &self.metrics {
-            metrics.queries_total().inc();
-        }
+    /// Confirm the main database file was opened through the IndexedDB VFS
+    ///
+    /// Fails with `WRONG_VFS` when `SQLITE_FCNTL_VFSNAME` reports any other VFS.
+    fn verify_vfs(db: *mut sqlite_wasm_rs::sqlite3) -> Result<(), DatabaseError> {
+        let schema = std::ffi::CString::new("main").expect("valid schema name");
+        let mut name_ptr: *mut std::ffi::c_char = std::ptr::null_mut();
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_file_control(
+                db,
+                schema.as_ptr(),
+                sqlite_wasm_rs::SQLITE_FCNTL_VFSNAME,
+                &mut name_ptr as *mut _ as *mut std::ffi::c_void,
+            )
+        };
+        let vfs_name = if name_ptr.is_null() {
+            None
+        } else {
+            let name = unsafe { std::ffi::CStr::from_ptr(name_ptr) }
+                .to_string_lossy()
+                .into_owned();
+            unsafe { sqlite_wasm_rs::sqlite3_free(name_ptr as *mut std::ffi::c_void) };
+            Some(name)
+        };
 
-        // Validate connection pointer before using it
-        if self.db().is_null() {
-            return Err(DatabaseError::new(
-                "NULL_CONNECTION",
-                "Database connection is null",
-            ));
+        match vfs_name {
+            Some(name)
+                if ret == sqlite_wasm_rs::SQLITE_OK
+                    && name == crate::vfs::indexeddb_vfs::VFS_IMPLEMENTATION_NAME =>
+            {
+                Ok(())
+            }
+            other => Err(DatabaseError::new(
+                "WRONG_VFS",
+                &format!(
+                    "Database was opened with the {} VFS instead of {}; data would not persist",
+                    other.as_deref().unwrap_or("default"),
+                    crate::vfs::indexeddb_vfs::VFS_IMPLEMENTATION_NAME
+                ),
+            )),
         }
+    }
 
-        let sql_cstr = CString::new(sql)
-            .map_err(|_| DatabaseError::new("INVALID_SQL", "Invalid SQL string"))?;
+    /// Register the IndexedDB VFS for `normalized_name` and make sure its BlockStorage
+    /// is in the registry, returning the VFS name
+    async fn ensure_indexeddb_vfs(
+        normalized_name: &str,
+        block_size: usize,
+    ) -> Result<String, DatabaseError> {
+        // Use a unique VFS name per database to avoid interference
+        let (_, vfs_name) = Self::open_target(normalized_name, Persistence::IndexedDB);
+        let vfs_name_cstr = std::ffi::CString::new(vfs_name.as_str())
+            .map_err(|_| DatabaseError::new("INVALID_VFS_NAME", "Invalid VFS name"))?;
+        let vfs_exists = unsafe {
+            let existing_vfs = sqlite_wasm_rs::sqlite3_vfs_find(vfs_name_cstr.as_ptr());
+            !existing_vfs.is_null()
+        };
 
-        if sql.trim().to_uppercase().starts_with("SELECT") {
-            let mut stmt = std::ptr::null_mut();
-            let ret = unsafe {
-                sqlite_wasm_rs::sqlite3_prepare_v2(
-                    self.db(),
-                    sql_cstr.as_ptr(),
-                    -1,
-                    &mut stmt,
-                    std::ptr::null_mut(),
-                )
-            };
+        if !vfs_exists {
+            // Create and register VFS only if it doesn't exist
+            log::debug!("Creating IndexedDBVFS for: {}", normalized_name);
+            let vfs =
+                crate::vfs::IndexedDBVFS::new_with_block_size(normalized_name, block_size).await?;
+            log::debug!("Registering VFS as '{}'", vfs_name);
+            vfs.register(&vfs_name)?;
+            log::info!("VFS registered successfully");
+        } else {
+            log::info!("VFS '{}' already registered, reusing existing", vfs_name);
+            // Ensure BlockStorage exists for this database in the registry
+            // The existing VFS will find it via STORAGE_REGISTRY
+            let _vfs =
+                crate::vfs::IndexedDBVFS::new_with_block_size(normalized_name, block_size).await?;
+            log::info!("BlockStorage ensured for {}", normalized_name);
+        }
+        Ok(vfs_name)
+    }
 
-            if ret != sqlite_wasm_rs::SQLITE_OK {
-                // Get actual error message from SQLite
-                let err_msg = unsafe {
-                    let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
-                    if !msg_ptr.is_null() {
-                        CStr::from_ptr(msg_ptr).to_string_lossy().into_owned()
-                    } else {
-                        format!("Unknown error (code: {})", ret)
-                    }
-                };
+    /// Open an existing database without write access
+    ///
+    /// The connection is opened with `SQLITE_OPEN_READONLY`, write statements fail with
+    /// `READ_ONLY_DB`, and the instance never takes part in leader election.
+    pub async fn new_read_only(config: DatabaseConfig) -> Result<Self, DatabaseError> {
+        Self::open(config, true, crate::functions::Extensions::default()).await
+    }
 
-                // Track error
-                #[cfg(feature = "telemetry")]
-                #[cfg(feature = "telemetry")]
-                if let Some(metrics) = &self.metrics {
-                    metrics.errors_total().inc();
-                }
+    async fn open(
+        config: DatabaseConfig,
+        read_only: bool,
+        extensions: crate::functions::Extensions,
+    ) -> Result<Self, DatabaseError> {
+        use std::ffi::{CStr, CString};
 
-                // Finish span with error
-                #[cfg(feature = "telemetry")]
-                if let Some(mut s) = span {
-                    s.status = crate::telemetry::SpanStatus::Error(format!(
-                        "Failed to prepare: {}",
-                        err_msg
-                    ));
-                    s.end_time_ms = Some(js_sys::Date::now());
-                    if let Some(recorder) = &self.span_recorder {
-                        recorder.record_span(s);
-                    }
+        log::info!("Database::new called for {}", config.name);
 
-                    // Exit span context
-                    if let Some(ref context) = self.span_context {
-                        context.exit_span();
-                    }
-                }
+        // CRITICAL: Use DRY helper to normalize name WITH .db extension
+        // This ensures Database.name, GLOBAL_STORAGE keys, and IndexedDB keys all match
+        let normalized_name = normalize_db_name(&config.name);
 
+        let block_size = config.block_size.unwrap_or(crate::storage::BLOCK_SIZE);
+        crate::storage::block_storage::validate_block_size(block_size)?;
+        let leader_election = config.leader_election.unwrap_or_default();
+        leader_election.validate()?;
+        let coordination = config.coordination.unwrap_or_default();
+        let multi_tab = match config.coordination {
+            Some(Coordination::Disabled) => false,
+            Some(Coordination::MultiTab) => true,
+            None => config.multi_tab.unwrap_or(!cfg!(feature = "single-tab")),
+        };
+        crate::storage::compression::set_compression(
+            &normalized_name,
+            config.compression.unwrap_or_default(),
+        );
+        crate::storage::durability::set_durability(
+            &normalized_name,
+            config.durability.unwrap_or_default(),
+        );
+        if let Some(algo) = config.checksum_algorithm {
+            crate::storage::metadata::set_default_checksum_algorithm(&normalized_name, algo);
+        }
+        if let Some(capacity) = config.block_cache_capacity {
+            crate::storage::block_storage::set_block_cache_capacity(
+                &normalized_name,
+                capacity.max(1),
+            );
+        }
+        crate::storage::block_storage::set_max_db_bytes(&normalized_name, config.max_db_bytes);
+        crate::storage::encryption::set_encryption_key(
+            &normalized_name,
+            config.encryption_key.as_deref(),
+        )?;
+
+        let persistence = config.persistence.unwrap_or_default();
+        let (open_filename, vfs_name) = Self::open_target(&normalized_name, persistence);
+        if persistence == Persistence::Memory {
+            // No IndexedDB VFS, BlockStorage or leader election for memory databases
+            let vfs_name_cstr = CString::new(vfs_name.as_str())
+                .map_err(|_| DatabaseError::new("INVALID_VFS_NAME", "Invalid VFS name"))?;
+            if unsafe { sqlite_wasm_rs::sqlite3_vfs_find(vfs_name_cstr.as_ptr()) }.is_null() {
                 return Err(DatabaseError::new(
-                    "SQLITE_ERROR",
-                    &format!("Failed to prepare statement: {}", err_msg),
-                )
-                .with_sql(sql));
+                    "MEMDB_UNAVAILABLE",
+                    "SQLite was built without the memdb VFS",
+                ));
             }
+            log::info!("Opening {} in memory", normalized_name);
+        } else {
+            Self::ensure_indexeddb_vfs(&normalized_name, block_size).await?;
+        }
 
-            let column_count = unsafe { sqlite_wasm_rs::sqlite3_column_count(stmt) };
-            let mut columns = Vec::new();
-            let mut rows = Vec::new();
+        // Read-only instances never elect a leader, so they keep the writer's timing
+        if !read_only && persistence == Persistence::IndexedDB {
+            if let Some(storage) =
+                crate::vfs::indexeddb_vfs::get_storage_with_fallback(&normalized_name)
+            {
+                storage.set_leader_election_config(leader_election);
+                storage.set_multi_tab(multi_tab);
+                storage.set_recover_on_leadership(config.recover_on_leadership.unwrap_or(true));
+                storage.set_verify_after_sync(config.verify_after_sync.unwrap_or(false));
+            }
+        }
 
-            // Get column names
-            for i in 0..column_count {
-                let col_name = unsafe {
-                    let name_ptr = sqlite_wasm_rs::sqlite3_column_name(stmt, i);
-                    if name_ptr.is_null() {
-                        format!("col_{}", i)
+        // CRITICAL: Synchronize SQLite connection opening to prevent WAL initialization conflicts
+        // Wait if another task is currently opening a connection to this database
+        #[cfg(target_arch = "wasm32")]
+        {
+            const MAX_OPEN_WAIT_MS: u32 = 1000; // Reduced from 5000 - 1s is sufficient
+            const OPEN_POLL_MS: u32 = 10;
+            let max_open_attempts = MAX_OPEN_WAIT_MS / OPEN_POLL_MS;
+
+            for attempt in 0..max_open_attempts {
+                let can_open = DB_OPEN_IN_PROGRESS.with(|opens| {
+                    let mut set = opens.borrow_mut();
+                    if set.contains(&config.name) {
+                        false // Someone else is opening
                     } else {
-                        std::ffi::CStr::from_ptr(name_ptr)
-                            .to_string_lossy()
-                            .into_owned()
+                        set.insert(config.name.clone());
+                        true // We got it
                     }
-                };
-                columns.push(col_name);
-            }
+                });
 
-            // Execute and fetch rows
-            loop {
-                let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
-                if step_ret == sqlite_wasm_rs::SQLITE_ROW {
-                    let mut values = Vec::new();
-                    for i in 0..column_count {
-                        let value = unsafe {
-                            let col_type = sqlite_wasm_rs::sqlite3_column_type(stmt, i);
-                            match col_type {
-                                sqlite_wasm_rs::SQLITE_NULL => ColumnValue::Null,
-                                sqlite_wasm_rs::SQLITE_INTEGER => {
-                                    let val = sqlite_wasm_rs::sqlite3_column_int64(stmt, i);
-                                    ColumnValue::Integer(val)
-                                }
-                                sqlite_wasm_rs::SQLITE_FLOAT => {
-                                    let val = sqlite_wasm_rs::sqlite3_column_double(stmt, i);
-                                    ColumnValue::Real(val)
-                                }
-                                sqlite_wasm_rs::SQLITE_TEXT => {
-                                    let text_ptr = sqlite_wasm_rs::sqlite3_column_text(stmt, i);
-                                    if text_ptr.is_null() {
-                                        ColumnValue::Null
-                                    } else {
-                                        let text = std::ffi::CStr::from_ptr(text_ptr as *const i8)
-                                            .to_string_lossy()
-                                            .into_owned();
-                                        ColumnValue::Text(text)
-                                    }
-                                }
-                                sqlite_wasm_rs::SQLITE_BLOB => {
-                                    let blob_ptr = sqlite_wasm_rs::sqlite3_column_blob(stmt, i);
-                                    let blob_size = sqlite_wasm_rs::sqlite3_column_bytes(stmt, i);
-                                    if blob_ptr.is_null() || blob_size == 0 {
-                                        ColumnValue::Blob(vec![])
-                                    } else {
-                                        let blob_slice = std::slice::from_raw_parts(
-                                            blob_ptr as *const u8,
-                                            blob_size as usize,
-                                        );
-                                        ColumnValue::Blob(blob_slice.to_vec())
-                                    }
-                                }
-                                _ => ColumnValue::Null,
-                            }
-                        };
-                        values.push(value);
-                    }
-                    rows.push(Row { values });
-                } else if step_ret == sqlite_wasm_rs::SQLITE_DONE {
+                if can_open {
+                    web_sys::console::log_1(
+                        &format!("[DB] {} - ACQUIRED sqlite open lock", config.name).into(),
+                    );
                     break;
                 } else {
-                    // Get SQLite error message before finalizing
+                    web_sys::console::log_1(
+                        &format!(
+                            "[DB] {} - Waiting for sqlite open (attempt {})",
+                            config.name, attempt
+                        )
+                        .into(),
+                    );
+                    use wasm_bindgen_futures::JsFuture;
+                    let promise = js_sys::Promise::new(&mut |resolve, _| {
+                        web_sys::window()
+                            .unwrap()
+                            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                                &resolve,
+                                OPEN_POLL_MS as i32,
+                            )
+                            .unwrap();
+                    });
+                    JsFuture::from(promise).await.ok();
+                    continue;
+                }
+            }
+        }
+
+        // Use connection pooling to share connections between instances
+        let (connection_state, db) = {
+            let vfs_name_str = vfs_name.clone(); // Capture the VFS name to use in closure
+            let filename_copy = normalized_name.clone(); // Capture filename for logging
+            let pool_key = Self::connection_pool_key(&normalized_name, read_only, persistence);
+            let state = crate::connection_pool::get_or_create_connection(&pool_key, || {
+                let mut db = std::ptr::null_mut();
+                let db_name = CString::new(open_filename.clone())
+                    .map_err(|_| "Invalid database name".to_string())?;
+                let vfs_cstr = CString::new(vfs_name_str.as_str())
+                    .map_err(|_| "Invalid VFS name".to_string())?;
+
+                log::info!(
+                    "Opening database: {} with VFS: {}",
+                    filename_copy,
+                    vfs_name_str
+                );
+
+                #[cfg(target_arch = "wasm32")]
+                web_sys::console::log_1(&format!("[OPEN] About to call sqlite3_open_v2...").into());
+
+                let ret = unsafe {
+                    sqlite_wasm_rs::sqlite3_open_v2(
+                        db_name.as_ptr(),
+                        &mut db as *mut _,
+                        Self::open_flags(read_only),
+                        vfs_cstr.as_ptr(),
+                    )
+                };
+
+                #[cfg(target_arch = "wasm32")]
+                web_sys::console::log_1(
+                    &format!("[OPEN] sqlite3_open_v2 returned: {}", ret).into(),
+                );
+
+                log::info!(
+                    "sqlite3_open_v2 returned: {} for database: {}",
+                    ret,
+                    filename_copy
+                );
+
+                if ret != sqlite_wasm_rs::SQLITE_OK {
                     let err_msg = unsafe {
-                        let err_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
-                        if !err_ptr.is_null() {
-                            std::ffi::CStr::from_ptr(err_ptr)
-                                .to_string_lossy()
-                                .to_string()
+                        let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(db);
+                        if !msg_ptr.is_null() {
+                            CStr::from_ptr(msg_ptr).to_string_lossy().into_owned()
                         } else {
-                            "Unknown SQLite error".to_string()
+                            "Unknown error".to_string()
                         }
                     };
-                    unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
-                    // Track error
-                    #[cfg(feature = "telemetry")]
-                    if let Some(metrics) = &self.metrics {
-                        metrics.errors_total().inc();
-                    }
-                    return Err(DatabaseError::new(
-                        "SQLITE_ERROR",
-                        &format!("Error executing SELECT statement: {}", err_msg),
-                    )
-                    .with_sql(sql));
+
+                    #[cfg(target_arch = "wasm32")]
+                    web_sys::console::log_1(
+                        &format!(
+                            "[OPEN] ERROR - sqlite3_open_v2 FAILED: ret={}, err={}",
+                            ret, err_msg
+                        )
+                        .into(),
+                    );
+
+                    return Err(format!(
+                        "Failed to open database with IndexedDB VFS: {}",
+                        err_msg
+                    ));
                 }
-            }
 
-            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
-            let execution_time_ms = js_sys::Date::now() - start_time;
+                log::info!("Database opened successfully with IndexedDB VFS");
+                Ok(db)
+            })
+            .map_err(|e| DatabaseError::new("CONNECTION_POOL_ERROR", &e))?;
+            let db_ptr = state.db.get();
+            (state, db_ptr)
+        };
 
-            // Track query duration
-            #[cfg(feature = "telemetry")]
-            if let Some(metrics) = &self.metrics {
-                metrics.query_duration().observe(execution_time_ms);
+        // A failed or misnamed VFS registration must not silently fall back to a
+        // non-persistent VFS
+        if persistence == Persistence::IndexedDB {
+            if let Err(e) = Self::verify_vfs(db) {
+                crate::connection_pool::release_connection(&Self::connection_pool_key(
+                    &normalized_name,
+                    read_only,
+                    persistence,
+                ));
+                #[cfg(target_arch = "wasm32")]
+                DB_OPEN_IN_PROGRESS.with(|opens| {
+                    opens.borrow_mut().remove(&config.name);
+                });
+                return Err(e);
             }
+        }
+
+        if !extensions.is_empty() {
+            extensions.install(db)?;
+            log::debug!("Installed open-time extensions on {}", normalized_name);
+        }
+
+        // Apply configuration options via PRAGMA statements
+        let exec_sql = |db: *mut sqlite_wasm_rs::sqlite3, sql: &str| -> Result<(), DatabaseError> {
+            let c_sql = CString::new(sql)
+                .map_err(|_| DatabaseError::new("INVALID_SQL", "Invalid SQL statement"))?;
 
-            Ok(QueryResult {
-                columns,
-                rows,
-                affected_rows: 0,
-                last_insert_id: None,
-                execution_time_ms,
-            })
-        } else {
-            // Non-SELECT statements - Use prepare/step to properly handle PRAGMA results
-            let mut stmt: *mut sqlite_wasm_rs::sqlite3_stmt = std::ptr::null_mut();
             let ret = unsafe {
-                sqlite_wasm_rs::sqlite3_prepare_v2(
-                    self.db(),
-                    sql_cstr.as_ptr(),
-                    -1,
-                    &mut stmt,
+                sqlite_wasm_rs::sqlite3_exec(
+                    db,
+                    c_sql.as_ptr(),
+                    None,
+                    std::ptr::null_mut(),
                     std::ptr::null_mut(),
                 )
             };
 
             if ret != sqlite_wasm_rs::SQLITE_OK {
-                // Get actual error message from SQLite
                 let err_msg = unsafe {
-                    let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
+                    let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(db);
                     if !msg_ptr.is_null() {
                         CStr::from_ptr(msg_ptr).to_string_lossy().into_owned()
                     } else {
-                        format!("Unknown error (code: {})", ret)
+                        "Unknown error".to_string()
                     }
                 };
-
-                // Track error
-                #[cfg(feature = "telemetry")]
-                if let Some(metrics) = &self.metrics {
-                    metrics.errors_total().inc();
-                }
+                log::warn!("Failed to execute SQL '{}': {}", sql, err_msg);
                 return Err(DatabaseError::new(
                     "SQLITE_ERROR",
-                    &format!("Failed to prepare statement: {}", err_msg),
-                )
-                .with_sql(sql));
+                    &format!("Failed to execute: {}", err_msg),
+                ));
             }
+            Ok(())
+        };
 
-            // Get column info for PRAGMA statements that return results
-            let column_count = unsafe { sqlite_wasm_rs::sqlite3_column_count(stmt) };
-            let mut columns = Vec::new();
-            let mut rows = Vec::new();
+        // CRITICAL: Set busy_timeout FIRST to handle concurrent access
+        // This makes SQLite wait and retry for up to 10 seconds when the database is locked
+        // instead of immediately returning SQLITE_BUSY errors during parallel operations
+        let busy_timeout_ms = Self::busy_timeout_ms(config.busy_retry.as_ref());
+        log::debug!(
+            "Setting busy_timeout to {}ms for concurrent access handling",
+            busy_timeout_ms
+        );
+        exec_sql(db, &format!("PRAGMA busy_timeout = {}", busy_timeout_ms))?;
 
-            if column_count > 0 {
-                // This is a PRAGMA or other statement that returns rows
-                for i in 0..column_count {
-                    let col_name = unsafe {
-                        let name_ptr = sqlite_wasm_rs::sqlite3_column_name(stmt, i);
-                        if name_ptr.is_null() {
-                            format!("column_{}", i)
-                        } else {
-                            std::ffi::CStr::from_ptr(name_ptr)
-                                .to_string_lossy()
-                                .into_owned()
-                        }
-                    };
-                    columns.push(col_name);
-                }
+        // Apply page_size (must be set before any tables are created)
+        if let Some(page_size) = config.page_size.filter(|_| !read_only) {
+            log::debug!("Setting page_size to {}", page_size);
+            exec_sql(db, &format!("PRAGMA page_size = {}", page_size))?;
+        }
 
-                // Fetch all rows
-                loop {
-                    let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
-                    if step_ret == sqlite_wasm_rs::SQLITE_ROW {
-                        let mut values = Vec::new();
-                        for i in 0..column_count {
-                            let value = unsafe {
-                                let col_type = sqlite_wasm_rs::sqlite3_column_type(stmt, i);
-                                match col_type {
-                                    sqlite_wasm_rs::SQLITE_TEXT => {
-                                        let text_ptr = sqlite_wasm_rs::sqlite3_column_text(stmt, i);
-                                        if text_ptr.is_null() {
-                                            ColumnValue::Null
-                                        } else {
-                                            let text =
-                                                std::ffi::CStr::from_ptr(text_ptr as *const i8)
-                                                    .to_string_lossy()
-                                                    .into_owned();
-                                            ColumnValue::Text(text)
-                                        }
-                                    }
-                                    sqlite_wasm_rs::SQLITE_INTEGER => ColumnValue::Integer(
-                                        sqlite_wasm_rs::sqlite3_column_int64(stmt, i),
-                                    ),
-                                    _ => ColumnValue::Null,
-                                }
-                            };
-                            values.push(value);
-                        }
-                        rows.push(Row { values });
-                    } else if step_ret == sqlite_wasm_rs::SQLITE_DONE {
-                        break;
-                    } else {
-                        // Get SQLite error message before finalizing
-                        let err_msg = unsafe {
-                            let err_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
-                            if !err_ptr.is_null() {
-                                std::ffi::CStr::from_ptr(err_ptr)
-                                    .to_string_lossy()
-                                    .to_string()
-                            } else {
-                                "Unknown SQLite error".to_string()
-                            }
-                        };
-                        unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
-                        // Track error
-                        #[cfg(feature = "telemetry")]
-                        if let Some(metrics) = &self.metrics {
-                            metrics.errors_total().inc();
-                        }
-                        return Err(DatabaseError::new(
-                            "SQLITE_ERROR",
-                            &format!("Failed to execute statement: {}", err_msg),
-                        )
-                        .with_sql(sql));
-                    }
-                }
-            } else {
-                // Regular non-SELECT statement
+        // Apply cache_size
+        if let Some(cache_size) = config.cache_size {
+            log::debug!("Setting cache_size to {}", cache_size);
+            exec_sql(db, &format!("PRAGMA cache_size = {}", cache_size))?;
+        }
+
+        // Apply journal_mode
+        // WAL mode is now fully supported via shared memory (xShm*) implementation
+        if let Some(journal_mode) = config.journal_mode.as_ref().filter(|_| !read_only) {
+            log::debug!("Setting journal_mode to {}", journal_mode);
+
+            let pragma_sql = format!("PRAGMA journal_mode = {}", journal_mode);
+            let c_sql = CString::new(pragma_sql.as_str())
+                .map_err(|_| DatabaseError::new("INVALID_SQL", "Invalid SQL statement"))?;
+
+            let mut stmt: *mut sqlite_wasm_rs::sqlite3_stmt = std::ptr::null_mut();
+            let ret = unsafe {
+                sqlite_wasm_rs::sqlite3_prepare_v2(
+                    db,
+                    c_sql.as_ptr(),
+                    -1,
+                    &mut stmt as *mut _,
+                    std::ptr::null_mut(),
+                )
+            };
+
+            if ret == sqlite_wasm_rs::SQLITE_OK && !stmt.is_null() {
                 let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
-                if step_ret != sqlite_wasm_rs::SQLITE_DONE {
-                    // Get SQLite error message before finalizing
-                    let err_msg = unsafe {
-                        let err_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
-                        if !err_ptr.is_null() {
-                            std::ffi::CStr::from_ptr(err_ptr)
+                if step_ret == sqlite_wasm_rs::SQLITE_ROW {
+                    let result_ptr = unsafe { sqlite_wasm_rs::sqlite3_column_text(stmt, 0) };
+                    if !result_ptr.is_null() {
+                        let result_mode = unsafe {
+                            std::ffi::CStr::from_ptr(result_ptr as *const i8)
                                 .to_string_lossy()
-                                .to_string()
+                                .to_uppercase()
+                        };
+
+                        if result_mode != journal_mode.to_uppercase() {
+                            log::warn!(
+                                "journal_mode {} requested but SQLite set {}",
+                                journal_mode,
+                                result_mode
+                            );
                         } else {
-                            "Unknown SQLite error".to_string()
+                            log::info!("journal_mode successfully set to {}", result_mode);
                         }
-                    };
-                    unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
-                    // Track error
-                    #[cfg(feature = "telemetry")]
-                    if let Some(metrics) = &self.metrics {
-                        metrics.errors_total().inc();
                     }
-                    return Err(DatabaseError::new(
-                        "SQLITE_ERROR",
-                        &format!("Failed to execute statement: {}", err_msg),
-                    )
-                    .with_sql(sql));
                 }
-            }
-
-            // Finalize to complete the statement
-            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
-
-            let affected_rows = unsafe { sqlite_wasm_rs::sqlite3_changes(self.db()) } as u32;
-            let last_insert_id = if sql.trim().to_uppercase().starts_with("INSERT") {
-                Some(unsafe { sqlite_wasm_rs::sqlite3_last_insert_rowid(self.db()) })
+                unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
             } else {
-                None
-            };
-
-            let execution_time_ms = js_sys::Date::now() - start_time;
-
-            // Track query duration
-            #[cfg(feature = "telemetry")]
-            if let Some(metrics) = &self.metrics {
-                metrics.query_duration().observe(execution_time_ms);
+                log::warn!("Failed to prepare journal_mode PRAGMA");
             }
+        }
 
-            // Finish span successfully
-            #[cfg(feature = "telemetry")]
-            if let Some(mut s) = span {
-                s.status = crate::telemetry::SpanStatus::Ok;
-                s.end_time_ms = Some(js_sys::Date::now());
-                s.attributes
-                    .insert("duration_ms".to_string(), execution_time_ms.to_string());
-                s.attributes
-                    .insert("affected_rows".to_string(), affected_rows.to_string());
-                s.attributes
-                    .insert("row_count".to_string(), rows.len().to_string());
-                if let Some(recorder) = &self.span_recorder {
-                    recorder.record_span(s);
-                }
+        // Apply auto_vacuum (must be set before any tables are created)
+        if let Some(auto_vacuum) = config.auto_vacuum.filter(|_| !read_only) {
+            let vacuum_mode = if auto_vacuum { 1 } else { 0 }; // 0=none, 1=full, 2=incremental
+            log::debug!("Setting auto_vacuum to {}", vacuum_mode);
+            exec_sql(db, &format!("PRAGMA auto_vacuum = {}", vacuum_mode))?;
+        }
 
-                // Exit span context
-                if let Some(ref context) = self.span_context {
-                    context.exit_span();
-                }
+        // Size the WAL limit and checkpoint before the WAL can reach it
+        if persistence == Persistence::IndexedDB
+            && !read_only
+            && (config.wal_max_bytes.is_some() || config.wal_checkpoint_fraction.is_some())
+        {
+            if let Some(max_bytes) = config.wal_max_bytes {
+                crate::vfs::indexeddb_vfs::set_wal_max_bytes(&normalized_name, max_bytes);
             }
+            let max_bytes = crate::vfs::indexeddb_vfs::wal_max_bytes(&normalized_name);
+            let fraction = config
+                .wal_checkpoint_fraction
+                .unwrap_or(crate::vfs::indexeddb_vfs::DEFAULT_WAL_CHECKPOINT_FRACTION)
+                .clamp(0.0, 1.0);
+            let page_size = Self::pragma_integer(db, "page_size")?.max(512) as usize;
+            // Each WAL frame is a page plus a 24-byte frame header
+            let pages = ((max_bytes as f64 * fraction) as usize / (page_size + 24)).max(1);
+            log::debug!(
+                "WAL limit {} bytes, auto-checkpoint every {} pages",
+                max_bytes,
+                pages
+            );
+            exec_sql(db, &format!("PRAGMA wal_autocheckpoint = {}", pages))?;
+        }
 
-            Ok(QueryResult {
-                columns,
-                rows,
-                affected_rows,
-                last_insert_id,
-                execution_time_ms,
-            })
+        // Event-driven background sync on idle, tab hide and unload
+        if config.sync_mode == Some(SyncMode::Auto)
+            && persistence == Persistence::IndexedDB
+            && !read_only
+        {
+            crate::storage::wasm_auto_sync::register_wasm_auto_sync(&normalized_name);
         }
-    }
 
-    pub async fn execute_with_params_internal(
-        &mut self,
-        sql: &str,
-        params: &[ColumnValue],
-    ) -> Result<QueryResult, DatabaseError> {
-        use std::ffi::{CStr, CString};
-        let start_time = js_sys::Date::now();
+        log::info!("Database configuration applied successfully");
 
-        // Create span for query execution
+        // Initialize metrics for telemetry
         #[cfg(feature = "telemetry")]
-        let span = if self.span_recorder.is_some() {
-            let query_type = sql
-                .trim()
-                .split_whitespace()
-                .next()
-                .unwrap_or("UNKNOWN")
-                .to_uppercase();
-            let span = crate::telemetry::SpanBuilder::new("execute_query".to_string())
-                .with_attribute("query_type", query_type.clone())
-                .with_attribute("sql", sql.to_string())
-                .build();
-            Some(span)
-        } else {
-            None
-        };
+        let metrics = crate::telemetry::Metrics::new().map_err(|e| {
+            DatabaseError::new(
+                "METRICS_ERROR",
+                &format!("Failed to initialize metrics: {}", e),
+            )
+        })?;
 
-        // Ensure metrics are propagated to BlockStorage before execution
-        #[cfg(feature = "telemetry")]
-        self.ensure_metrics_propagated();
+        let database = Database {
+            connection_state,
+            name: normalized_name.clone(), // CRITICAL: Use normalized name WITH .db to match registry
+            on_data_change_callback: None,
+            on_leadership_change_callback: None,
+            on_storage_full_callback: None,
+            unbroadcast_writes: std::cell::Cell::new(false),
+            allow_non_leader_writes: false,
+            multi_tab,
+            coordination,
+            read_only,
+            optimistic_updates_manager: std::cell::RefCell::new(
+                crate::storage::optimistic_updates::OptimisticUpdatesManager::new(),
+            ),
+            coordination_metrics_manager: std::cell::RefCell::new(
+                crate::storage::coordination_metrics::CoordinationMetricsManager::new(),
+            ),
+            #[cfg(feature = "telemetry")]
+            metrics: Some(metrics),
+            #[cfg(feature = "telemetry")]
+            span_recorder: None,
+            #[cfg(feature = "telemetry")]
+            span_context: Some(crate::telemetry::SpanContext::new()),
+            max_export_size_bytes: config.max_export_size_bytes,
+            block_size,
+            max_open_statements: config.max_open_statements,
+            max_result_rows: config.max_result_rows,
+            pending_writes: std::cell::RefCell::new(
+                crate::storage::write_queue::PendingWrites::new(config.max_queued_writes),
+            ),
+            statement_cache: std::cell::RefCell::new(
+                crate::storage::statement_cache::StatementCache::new(
+                    config
+                        .statement_cache_size
+                        .unwrap_or(crate::storage::statement_cache::DEFAULT_STATEMENT_CACHE_SIZE),
+                ),
+            ),
+            statement_cache_generation: std::cell::Cell::new(0),
+            cursor_open: Rc::new(std::cell::Cell::new(false)),
+            subscriptions: Rc::new(std::cell::RefCell::new(
+                crate::storage::subscriptions::SubscriptionManager::new(),
+            )),
+            extensions,
+            read_options: crate::query_cursor::ReadOptions {
+                invalid_utf8: config.invalid_utf8.unwrap_or_default(),
+                date_columns: config.date_columns.unwrap_or(false),
+            },
+            transactions: std::cell::RefCell::new(TransactionState::default()),
+            busy_retry: config.busy_retry,
+            busy_handler: None,
+            authorizer: None,
+            persistence,
+            result_format: config.result_format.unwrap_or_default(),
+            attached: std::cell::RefCell::new(std::collections::HashMap::new()),
+            sync_mode: config.sync_mode.unwrap_or_default(),
+            sync_debounce_ms: config.sync_debounce_ms,
+            unsynced_writes: std::cell::Cell::new(false),
+            sync_generation: Rc::new(std::cell::Cell::new(0)),
+            query_timeout_ms: config.query_timeout_ms,
+            sessions: std::cell::RefCell::new(std::collections::HashMap::new()),
+            next_session_id: std::cell::Cell::new(1),
+            idle_checkpoint_ms: config.idle_checkpoint_ms,
+            last_write_ms: Rc::new(std::cell::Cell::new(0.0)),
+            idle_checkpoint_pending: Rc::new(std::cell::Cell::new(false)),
+        };
 
-        // Track query execution metrics
-        #[cfg(feature = "telemetry")]
-        if let Some(metrics) = &self.metrics {
-            metrics.queries_total().inc();
+        // CRITICAL: Release the SQLite open lock ONLY after Database is fully constructed
+        // This ensures WAL initialization and all setup completes before another instance can start
+        #[cfg(target_arch = "wasm32")]
+        {
+            DB_OPEN_IN_PROGRESS.with(|opens| {
+                opens.borrow_mut().remove(&config.name);
+            });
+            web_sys::console::log_1(
+                &format!("[DB] {} - RELEASED sqlite open lock", config.name).into(),
+            );
         }
 
-        let sql_cstr = CString::new(sql)
-            .map_err(|_| DatabaseError::new("INVALID_SQL", "Invalid SQL string"))?;
+        Ok(database)
+    }
 
-        let mut stmt = std::ptr::null_mut();
-        let ret = unsafe {
-            sqlite_wasm_rs::sqlite3_prepare_v2(
-                self.db(),
-                sql_cstr.as_ptr(),
-                -1,
-                &mut stmt,
-                std::ptr::null_mut(),
-            )
-        };
+    /// Open a database with a specific VFS using connection pooling
+    pub async fn open_with_vfs(filename: &str, vfs_name: &str) -> Result<Self, DatabaseError> {
+        use std::ffi::CString;
 
-        if ret != sqlite_wasm_rs::SQLITE_OK {
-            // Get actual error message from SQLite
-            let err_msg = unsafe {
-                let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
-                if !msg_ptr.is_null() {
-                    CStr::from_ptr(msg_ptr).to_string_lossy().into_owned()
-                } else {
-                    format!("Unknown error (code: {})", ret)
-                }
-            };
+        log::info!("Opening database {} with VFS {}", filename, vfs_name);
 
-            // Track error
-            #[cfg(feature = "telemetry")]
-            if let Some(metrics) = &self.metrics {
-                metrics.errors_total().inc();
-            }
+        // Normalize the database name WITH .db extension
+        let normalized_name = normalize_db_name(filename);
+        let pool_key = normalized_name.trim_end_matches(".db").to_string();
 
-            // Finish span with error
-            #[cfg(feature = "telemetry")]
-            if let Some(mut s) = span {
-                s.status =
-                    crate::telemetry::SpanStatus::Error(format!("Failed to prepare: {}", err_msg));
-                s.end_time_ms = Some(js_sys::Date::now());
-                if let Some(recorder) = &self.span_recorder {
-                    recorder.record_span(s);
-                }
-            }
+        // Use connection pooling with custom VFS
+        let connection_state = crate::connection_pool::get_or_create_connection(&pool_key, || {
+            let mut db: *mut sqlite_wasm_rs::sqlite3 = std::ptr::null_mut();
+            let db_name = CString::new(normalized_name.clone())
+                .map_err(|_| "Invalid database name".to_string())?;
+            let vfs_cstr = CString::new(vfs_name).map_err(|_| "Invalid VFS name".to_string())?;
 
-            return Err(DatabaseError::new(
-                "SQLITE_ERROR",
-                &format!("Failed to prepare statement: {}", err_msg),
+            let ret = unsafe {
+                sqlite_wasm_rs::sqlite3_open_v2(
+                    db_name.as_ptr(),
+                    &mut db as *mut _,
+                    sqlite_wasm_rs::SQLITE_OPEN_READWRITE | sqlite_wasm_rs::SQLITE_OPEN_CREATE,
+                    vfs_cstr.as_ptr(),
+                )
+            };
+
+            if ret != sqlite_wasm_rs::SQLITE_OK {
+                let err_msg = if !db.is_null() {
+                    unsafe {
+                        let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(db);
+                        if !msg_ptr.is_null() {
+                            std::ffi::CStr::from_ptr(msg_ptr)
+                                .to_string_lossy()
+                                .into_owned()
+                        } else {
+                            "Unknown error".to_string()
+                        }
+                    }
+                } else {
+                    "Failed to open database".to_string()
+                };
+                return Err(format!("SQLITE_ERROR: {}", err_msg));
+            }
+
+            Ok(db)
+        })
+        .map_err(|e| DatabaseError::new("OPEN_ERROR", &e))?;
+
+        log::info!(
+            "Successfully opened database {} with VFS {}",
+            normalized_name,
+            vfs_name
+        );
+
+        // Initialize metrics for telemetry
+        #[cfg(feature = "telemetry")]
+        let metrics = crate::telemetry::Metrics::new().map_err(|e| {
+            DatabaseError::new(
+                "METRICS_ERROR",
+                &format!("Failed to initialize metrics: {}", e),
             )
-            .with_sql(sql));
+        })?;
+
+        Ok(Database {
+            connection_state,
+            name: normalized_name, // CRITICAL: Store normalized name WITH .db
+            on_data_change_callback: None,
+            on_leadership_change_callback: None,
+            on_storage_full_callback: None,
+            unbroadcast_writes: std::cell::Cell::new(false),
+            allow_non_leader_writes: false,
+            multi_tab: !cfg!(feature = "single-tab"),
+            coordination: Coordination::default(),
+            read_only: false,
+            optimistic_updates_manager: std::cell::RefCell::new(
+                crate::storage::optimistic_updates::OptimisticUpdatesManager::new(),
+            ),
+            coordination_metrics_manager: std::cell::RefCell::new(
+                crate::storage::coordination_metrics::CoordinationMetricsManager::new(),
+            ),
+            #[cfg(feature = "telemetry")]
+            metrics: Some(metrics),
+            #[cfg(feature = "telemetry")]
+            span_recorder: None,
+            #[cfg(feature = "telemetry")]
+            span_context: Some(crate::telemetry::SpanContext::new()),
+            max_export_size_bytes: Some(2 * 1024 * 1024 * 1024), // Default 2GB limit
+            block_size: crate::storage::BLOCK_SIZE,
+            max_open_statements: Some(crate::storage::statement_cache::DEFAULT_MAX_OPEN_STATEMENTS),
+            max_result_rows: None,
+            pending_writes: std::cell::RefCell::new(
+                crate::storage::write_queue::PendingWrites::new(Some(
+                    crate::types::DEFAULT_MAX_QUEUED_WRITES,
+                )),
+            ),
+            statement_cache: std::cell::RefCell::new(
+                crate::storage::statement_cache::StatementCache::default(),
+            ),
+            statement_cache_generation: std::cell::Cell::new(0),
+            cursor_open: Rc::new(std::cell::Cell::new(false)),
+            subscriptions: Rc::new(std::cell::RefCell::new(
+                crate::storage::subscriptions::SubscriptionManager::new(),
+            )),
+            extensions: crate::functions::Extensions::default(),
+            read_options: Default::default(),
+            transactions: std::cell::RefCell::new(TransactionState::default()),
+            busy_retry: None,
+            busy_handler: None,
+            authorizer: None,
+            persistence: Persistence::IndexedDB,
+            result_format: ResultFormat::default(),
+            attached: std::cell::RefCell::new(std::collections::HashMap::new()),
+            sync_mode: SyncMode::default(),
+            sync_debounce_ms: None,
+            unsynced_writes: std::cell::Cell::new(false),
+            sync_generation: Rc::new(std::cell::Cell::new(0)),
+            query_timeout_ms: None,
+            sessions: std::cell::RefCell::new(std::collections::HashMap::new()),
+            next_session_id: std::cell::Cell::new(1),
+            idle_checkpoint_ms: None,
+            last_write_ms: Rc::new(std::cell::Cell::new(0.0)),
+            idle_checkpoint_pending: Rc::new(std::cell::Cell::new(false)),
+        })
+    }
+
+    pub async fn execute_internal(&mut self, sql: &str) -> Result<QueryResult, DatabaseError> {
+        let result = self.execute_shared(sql).await?;
+        self.sync_after_write().await?;
+        Ok(result)
+    }
+
+    /// Persist committed writes when `sync_mode` is `OnWrite`
+    ///
+    /// Writes inside an explicit transaction are synced by the COMMIT that ends it.
+    async fn sync_after_write(&mut self) -> Result<(), DatabaseError> {
+        if self.sync_mode != SyncMode::OnWrite
+            || self.is_memory()
+            || !self.unsynced_writes.get()
+            || unsafe { sqlite_wasm_rs::sqlite3_get_autocommit(self.db()) } == 0
+        {
+            return Ok(());
         }
+        match self.sync_debounce_ms.filter(|ms| *ms > 0) {
+            Some(delay_ms) => {
+                self.unsynced_writes.set(false);
+                self.schedule_debounced_sync(delay_ms);
+                Ok(())
+            }
+            None => self.sync_internal().await,
+        }
+    }
 
-        // Bind parameters
-        let mut text_cstrings = Vec::new(); // Keep CStrings alive
-        for (i, param) in params.iter().enumerate() {
-            let param_index = (i + 1) as i32;
-            let bind_ret = unsafe {
-                match param {
-                    ColumnValue::Null => sqlite_wasm_rs::sqlite3_bind_null(stmt, param_index),
-                    ColumnValue::Integer(val) => {
-                        sqlite_wasm_rs::sqlite3_bind_int64(stmt, param_index, *val)
-                    }
-                    ColumnValue::Real(val) => {
-                        sqlite_wasm_rs::sqlite3_bind_double(stmt, param_index, *val)
-                    }
-                    ColumnValue::Text(val) => {
-                        // Sanitize string by removing null bytes (SQLite text shouldn't contain them)
-                        let sanitized = val.replace('\0', "");
-                        // Safe: after removing null bytes, CString::new cannot fail
-                        let text_cstr = CString::new(sanitized.as_str())
-                            .expect("CString::new should not fail after null byte removal");
-                        let result = sqlite_wasm_rs::sqlite3_bind_text(
-                            stmt,
-                            param_index,
-                            text_cstr.as_ptr(),
-                            sanitized.len() as i32,
-                            sqlite_wasm_rs::SQLITE_TRANSIENT(),
-                        );
-                        text_cstrings.push(text_cstr); // Keep alive
-                        result
-                    }
-                    ColumnValue::Blob(val) => sqlite_wasm_rs::sqlite3_bind_blob(
-                        stmt,
-                        param_index,
-                        val.as_ptr() as *const _,
-                        val.len() as i32,
-                        sqlite_wasm_rs::SQLITE_TRANSIENT(),
-                    ),
-                    _ => sqlite_wasm_rs::sqlite3_bind_null(stmt, param_index),
-                }
+    /// Sync `delay_ms` after the latest write unless another write comes in first
+    ///
+    /// The checkpoint and sync run on the shared connection and storage, so they still
+    /// happen if this instance is dropped in the meantime. Other tabs are notified by
+    /// the next explicit `sync()`.
+    fn schedule_debounced_sync(&self, delay_ms: u32) {
+        let generation = self.sync_generation.get().wrapping_add(1);
+        self.sync_generation.set(generation);
+        let latest = Rc::clone(&self.sync_generation);
+        let connection_state = Rc::clone(&self.connection_state);
+        let db_name = self.name.clone();
+
+        wasm_bindgen_futures::spawn_local(async move {
+            crate::storage::retry_logic::sleep_ms(delay_ms).await;
+            if latest.get() != generation {
+                return;
+            }
+            if let Err(e) = Self::sync_shared_connection(&connection_state, &db_name).await {
+                log::warn!("Debounced sync of {} failed: {}", db_name, e);
+            }
+        });
+    }
+
+    /// Checkpoint the shared connection and persist its blocks without an instance
+    ///
+    /// Used by work that outlives the `&mut self` borrow, such as debounced syncs.
+    async fn sync_shared_connection(
+        connection_state: &crate::connection_pool::ConnectionState,
+        db_name: &str,
+    ) -> Result<(), DatabaseError> {
+        let db = connection_state.db.get();
+        if db.is_null() {
+            return Ok(());
+        }
+        // Move WAL frames into the main file so the block sync carries them
+        let checkpoint =
+            std::ffi::CString::new("PRAGMA wal_checkpoint(TRUNCATE)").expect("valid SQL");
+        unsafe {
+            sqlite_wasm_rs::sqlite3_exec(
+                db,
+                checkpoint.as_ptr(),
+                None,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        match crate::vfs::indexeddb_vfs::get_storage_with_fallback(db_name) {
+            Some(storage) => storage.sync().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Body of `execute_internal`; only needs `&self` so queued writes can run while
+    /// other queued writes are still awaiting the leader
+    async fn execute_shared(&self, sql: &str) -> Result<QueryResult, DatabaseError> {
+        let deadline = unsafe {
+            crate::query_timeout::QueryDeadline::install(self.db(), self.query_timeout_ms)
+        };
+        crate::storage::retry_logic::with_schema_retry(|| {
+            crate::storage::retry_logic::with_busy_retry(self.busy_retry, || self.execute_once(sql))
+        })
+        .await
+        .map_err(|e| crate::query_timeout::interrupt_error(e, deadline.as_ref()))
+        .map_err(|e| self.with_vfs_error(e))
+    }
+
+    /// Replace SQLite's generic I/O or disk-full error with the VFS error that caused it
+    fn with_vfs_error(&self, err: DatabaseError) -> DatabaseError {
+        let primary = err.sqlite_code.map(|code| code & 0xff);
+        if primary != Some(sqlite_wasm_rs::SQLITE_IOERR)
+            && primary != Some(sqlite_wasm_rs::SQLITE_FULL)
+        {
+            return err;
+        }
+        match crate::vfs::indexeddb_vfs::take_limit_error(&self.name) {
+            Some(limit_err) => DatabaseError {
+                sql: err.sql,
+                sqlite_code: err.sqlite_code,
+                ..limit_err
+            },
+            None => err,
+        }
+    }
+
+    /// Run `sql` once; a failed statement is finalized, so a retry prepares it afresh
+    async fn execute_once(&self, sql: &str) -> Result<QueryResult, DatabaseError> {
+        use std::ffi::CStr;
+        self.ensure_no_open_cursor(sql)?;
+        self.ensure_writable(sql)?;
+        Self::ensure_window_functions(sql)?;
+        self.ensure_statement_capacity(sql)?;
+        let start_time = js_sys::Date::now();
+
+        // Create span for query execution and enter context
+        #[cfg(feature = "telemetry")]
+        let span = if self.span_recorder.is_some() {
+            let query_type = sql
+                .trim()
+                .split_whitespace()
+                .next()
+                .unwrap_or("UNKNOWN")
+                .to_uppercase();
+            let mut builder = crate::telemetry::SpanBuilder::new("execute_query".to_string())
+                .with_attribute("query_type", query_type.clone())
+                .with_attribute("sql", sql.to_string());
+
+            // Link to the enclosing withSpan scope and attach baggage from context
+            if let Some(ref context) = self.span_context {
+                builder = builder
+                    .with_scope(context)
+                    .with_baggage_from_context(context);
+            }
+
+            let span = builder.build();
+
+            // Enter span context
+            if let Some(ref context) = self.span_context {
+                context.enter_span(span.span_id.clone());
+            }
+
+            Some(span)
+        } else {
+            None
+        };
+
+        // Track query execution metrics
+        #[cfg(feature = "telemetry")]
+        #[cfg(feature = "telemetry")]
+        if let Some(metrics) = &self.metrics {
+            metrics.queries_total().inc();
+        }
+
+        // Validate connection pointer before using it
+        if self.db().is_null() {
+            return Err(DatabaseError::new(
+                "NULL_CONNECTION",
+                "Database connection is null",
+            ));
+        }
+
+        let sql_cstr = Self::sql_cstring(sql)?;
+
+        if sql.trim().to_uppercase().starts_with("SELECT") {
+            let (stmt, ret) = match self.prepare_cached(sql, &sql_cstr) {
+                Ok(stmt) => (stmt, sqlite_wasm_rs::SQLITE_OK),
+                Err(ret) => (std::ptr::null_mut(), ret),
             };
 
-            if bind_ret != sqlite_wasm_rs::SQLITE_OK {
-                unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+            if ret != sqlite_wasm_rs::SQLITE_OK {
+                // Get actual error message from SQLite
+                let err_msg = unsafe {
+                    let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
+                    if !msg_ptr.is_null() {
+                        CStr::from_ptr(msg_ptr).to_string_lossy().into_owned()
+                    } else {
+                        format!("Unknown error (code: {})", ret)
+                    }
+                };
+                let sqlite_code = self.last_error_code();
+
                 // Track error
                 #[cfg(feature = "telemetry")]
+                #[cfg(feature = "telemetry")]
                 if let Some(metrics) = &self.metrics {
                     metrics.errors_total().inc();
                 }
-                return Err(
-                    DatabaseError::new("SQLITE_ERROR", "Failed to bind parameter").with_sql(sql),
-                );
+
+                // Finish span with error
+                #[cfg(feature = "telemetry")]
+                if let Some(mut s) = span {
+                    s.status = crate::telemetry::SpanStatus::Error(format!(
+                        "Failed to prepare: {}",
+                        err_msg
+                    ));
+                    s.end_time_ms = Some(js_sys::Date::now());
+                    // Exit span context
+                    if let Some(ref context) = self.span_context {
+                        context.exit_span_id(&s.span_id);
+                    }
+                    if let Some(recorder) = &self.span_recorder {
+                        recorder.record_span(s);
+                    }
+                }
+
+                return Err(DatabaseError::new(
+                    "SQLITE_ERROR",
+                    &format!("Failed to prepare statement: {}", err_msg),
+                )
+                .with_sql(sql)
+                .with_sqlite_code(sqlite_code));
             }
-        }
 
-        if sql.trim().to_uppercase().starts_with("SELECT") {
             let column_count = unsafe { sqlite_wasm_rs::sqlite3_column_count(stmt) };
             let mut columns = Vec::new();
             let mut rows = Vec::new();
@@ -1154,50 +1956,17 @@ impl Database {
             loop {
                 let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
                 if step_ret == sqlite_wasm_rs::SQLITE_ROW {
-                    let mut values = Vec::new();
-                    for i in 0..column_count {
-                        let value = unsafe {
-                            let col_type = sqlite_wasm_rs::sqlite3_column_type(stmt, i);
-                            match col_type {
-                                sqlite_wasm_rs::SQLITE_NULL => ColumnValue::Null,
-                                sqlite_wasm_rs::SQLITE_INTEGER => {
-                                    let val = sqlite_wasm_rs::sqlite3_column_int64(stmt, i);
-                                    ColumnValue::Integer(val)
-                                }
-                                sqlite_wasm_rs::SQLITE_FLOAT => {
-                                    let val = sqlite_wasm_rs::sqlite3_column_double(stmt, i);
-                                    ColumnValue::Real(val)
-                                }
-                                sqlite_wasm_rs::SQLITE_TEXT => {
-                                    let text_ptr = sqlite_wasm_rs::sqlite3_column_text(stmt, i);
-                                    if text_ptr.is_null() {
-                                        ColumnValue::Null
-                                    } else {
-                                        let text = std::ffi::CStr::from_ptr(text_ptr as *const i8)
-                                            .to_string_lossy()
-                                            .into_owned();
-                                        ColumnValue::Text(text)
-                                    }
-                                }
-                                sqlite_wasm_rs::SQLITE_BLOB => {
-                                    let blob_ptr = sqlite_wasm_rs::sqlite3_column_blob(stmt, i);
-                                    let blob_size = sqlite_wasm_rs::sqlite3_column_bytes(stmt, i);
-                                    if blob_ptr.is_null() || blob_size == 0 {
-                                        ColumnValue::Blob(vec![])
-                                    } else {
-                                        let blob_slice = std::slice::from_raw_parts(
-                                            blob_ptr as *const u8,
-                                            blob_size as usize,
-                                        );
-                                        ColumnValue::Blob(blob_slice.to_vec())
-                                    }
-                                }
-                                _ => ColumnValue::Null,
-                            }
-                        };
-                        values.push(value);
+                    if let Err(e) = self.ensure_result_capacity(rows.len(), sql) {
+                        unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                        return Err(e);
+                    }
+                    match crate::query_cursor::read_row(stmt, column_count, self.read_options) {
+                        Ok(row) => rows.push(row),
+                        Err(e) => {
+                            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                            return Err(e.with_sql(sql));
+                        }
                     }
-                    rows.push(Row { values });
                 } else if step_ret == sqlite_wasm_rs::SQLITE_DONE {
                     break;
                 } else {
@@ -1212,6 +1981,7 @@ impl Database {
                             "Unknown SQLite error".to_string()
                         }
                     };
+                    let sqlite_code = self.last_error_code();
                     unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
                     // Track error
                     #[cfg(feature = "telemetry")]
@@ -1222,12 +1992,12 @@ impl Database {
                         "SQLITE_ERROR",
                         &format!("Error executing SELECT statement: {}", err_msg),
                     )
-                    .with_sql(sql));
+                    .with_sql(sql)
+                    .with_sqlite_code(sqlite_code));
                 }
             }
 
-            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
-
+            self.release_statement(sql, stmt);
             let execution_time_ms = js_sys::Date::now() - start_time;
 
             // Track query duration
@@ -1236,20 +2006,6 @@ impl Database {
                 metrics.query_duration().observe(execution_time_ms);
             }
 
-            // Finish span successfully for SELECT query
-            #[cfg(feature = "telemetry")]
-            if let Some(mut s) = span {
-                s.status = crate::telemetry::SpanStatus::Ok;
-                s.end_time_ms = Some(js_sys::Date::now());
-                s.attributes
-                    .insert("duration_ms".to_string(), execution_time_ms.to_string());
-                s.attributes
-                    .insert("row_count".to_string(), rows.len().to_string());
-                if let Some(recorder) = &self.span_recorder {
-                    recorder.record_span(s);
-                }
-            }
-
             Ok(QueryResult {
                 columns,
                 rows,
@@ -1258,40 +2014,154 @@ impl Database {
                 execution_time_ms,
             })
         } else {
-            // Non-SELECT statements
-            let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
-            // Track error
-            #[cfg(feature = "telemetry")]
-            if let Some(metrics) = &self.metrics {
-                metrics.errors_total().inc();
-            }
-            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+            // Non-SELECT statements - Use prepare/step to properly handle PRAGMA results
+            let (stmt, ret) = match self.prepare_cached(sql, &sql_cstr) {
+                Ok(stmt) => (stmt, sqlite_wasm_rs::SQLITE_OK),
+                Err(ret) => (std::ptr::null_mut(), ret),
+            };
 
-            if step_ret != sqlite_wasm_rs::SQLITE_DONE {
+            if ret != sqlite_wasm_rs::SQLITE_OK {
+                // Get actual error message from SQLite
                 let err_msg = unsafe {
-                    let err_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
-                    if !err_ptr.is_null() {
-                        std::ffi::CStr::from_ptr(err_ptr)
-                            .to_string_lossy()
-                            .to_string()
+                    let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
+                    if !msg_ptr.is_null() {
+                        CStr::from_ptr(msg_ptr).to_string_lossy().into_owned()
                     } else {
-                        "Unknown SQLite error".to_string()
+                        format!("Unknown error (code: {})", ret)
                     }
                 };
-                return Err(DatabaseError::new(
-                    "SQLITE_ERROR",
-                    &format!("Failed to execute statement: {}", err_msg),
-                )
-                .with_sql(sql));
+                let sqlite_code = self.last_error_code();
+
+                // Track error
+                #[cfg(feature = "telemetry")]
+                if let Some(metrics) = &self.metrics {
+                    metrics.errors_total().inc();
+                }
+                return Err(DatabaseError::new(
+                    "SQLITE_ERROR",
+                    &format!("Failed to prepare statement: {}", err_msg),
+                )
+                .with_sql(sql)
+                .with_sqlite_code(sqlite_code));
             }
 
-            let execution_time_ms = js_sys::Date::now() - start_time;
+            // Get column info for PRAGMA statements that return results
+            let column_count = unsafe { sqlite_wasm_rs::sqlite3_column_count(stmt) };
+            let mut columns = Vec::new();
+            let mut rows = Vec::new();
 
-            // Track query duration
-            #[cfg(feature = "telemetry")]
-            if let Some(metrics) = &self.metrics {
-                metrics.query_duration().observe(execution_time_ms);
+            if column_count > 0 {
+                // This is a PRAGMA or other statement that returns rows
+                for i in 0..column_count {
+                    let col_name = unsafe {
+                        let name_ptr = sqlite_wasm_rs::sqlite3_column_name(stmt, i);
+                        if name_ptr.is_null() {
+                            format!("column_{}", i)
+                        } else {
+                            std::ffi::CStr::from_ptr(name_ptr)
+                                .to_string_lossy()
+                                .into_owned()
+                        }
+                    };
+                    columns.push(col_name);
+                }
+
+                // Fetch all rows
+                loop {
+                    let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
+                    if step_ret == sqlite_wasm_rs::SQLITE_ROW {
+                        if let Err(e) = self.ensure_result_capacity(rows.len(), sql) {
+                            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                            return Err(e);
+                        }
+                        let mut values = Vec::new();
+                        for i in 0..column_count {
+                            let col_type = unsafe { sqlite_wasm_rs::sqlite3_column_type(stmt, i) };
+                            let value = match col_type {
+                                sqlite_wasm_rs::SQLITE_TEXT => {
+                                    match crate::query_cursor::read_text(
+                                        stmt,
+                                        i,
+                                        self.read_options.invalid_utf8,
+                                    ) {
+                                        Ok(value) => value,
+                                        Err(e) => {
+                                            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                                            return Err(e.with_sql(sql));
+                                        }
+                                    }
+                                }
+                                sqlite_wasm_rs::SQLITE_INTEGER => ColumnValue::Integer(unsafe {
+                                    sqlite_wasm_rs::sqlite3_column_int64(stmt, i)
+                                }),
+                                _ => ColumnValue::Null,
+                            };
+                            values.push(value);
+                        }
+                        rows.push(Row { values });
+                    } else if step_ret == sqlite_wasm_rs::SQLITE_DONE {
+                        break;
+                    } else {
+                        // Get SQLite error message before finalizing
+                        let err_msg = unsafe {
+                            let err_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
+                            if !err_ptr.is_null() {
+                                std::ffi::CStr::from_ptr(err_ptr)
+                                    .to_string_lossy()
+                                    .to_string()
+                            } else {
+                                "Unknown SQLite error".to_string()
+                            }
+                        };
+                        let sqlite_code = self.last_error_code();
+                        unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                        // Track error
+                        #[cfg(feature = "telemetry")]
+                        if let Some(metrics) = &self.metrics {
+                            metrics.errors_total().inc();
+                        }
+                        return Err(DatabaseError::new(
+                            "SQLITE_ERROR",
+                            &format!("Failed to execute statement: {}", err_msg),
+                        )
+                        .with_sql(sql)
+                        .with_sqlite_code(sqlite_code));
+                    }
+                }
+            } else {
+                // Regular non-SELECT statement
+                let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
+                if step_ret != sqlite_wasm_rs::SQLITE_DONE {
+                    // Get SQLite error message before finalizing
+                    let err_msg = unsafe {
+                        let err_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
+                        if !err_ptr.is_null() {
+                            std::ffi::CStr::from_ptr(err_ptr)
+                                .to_string_lossy()
+                                .to_string()
+                        } else {
+                            "Unknown SQLite error".to_string()
+                        }
+                    };
+                    let sqlite_code = self.last_error_code();
+                    unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                    // Track error
+                    #[cfg(feature = "telemetry")]
+                    if let Some(metrics) = &self.metrics {
+                        metrics.errors_total().inc();
+                    }
+                    return Err(DatabaseError::new(
+                        "SQLITE_ERROR",
+                        &format!("Failed to execute statement: {}", err_msg),
+                    )
+                    .with_sql(sql)
+                    .with_sqlite_code(sqlite_code));
+                }
             }
+
+            // Reset and return the statement to the cache for reuse
+            self.release_statement(sql, stmt);
+
             let affected_rows = unsafe { sqlite_wasm_rs::sqlite3_changes(self.db()) } as u32;
             let last_insert_id = if sql.trim().to_uppercase().starts_with("INSERT") {
                 Some(unsafe { sqlite_wasm_rs::sqlite3_last_insert_rowid(self.db()) })
@@ -1299,6 +2169,14 @@ impl Database {
                 None
             };
 
+            let execution_time_ms = js_sys::Date::now() - start_time;
+
+            // Track query duration
+            #[cfg(feature = "telemetry")]
+            if let Some(metrics) = &self.metrics {
+                metrics.query_duration().observe(execution_time_ms);
+            }
+
             // Finish span successfully
             #[cfg(feature = "telemetry")]
             if let Some(mut s) = span {
@@ -1308,14 +2186,23 @@ impl Database {
                     .insert("duration_ms".to_string(), execution_time_ms.to_string());
                 s.attributes
                     .insert("affected_rows".to_string(), affected_rows.to_string());
+                s.attributes
+                    .insert("row_count".to_string(), rows.len().to_string());
+                // Exit span context
+                if let Some(ref context) = self.span_context {
+                    context.exit_span_id(&s.span_id);
+                }
                 if let Some(recorder) = &self.span_recorder {
                     recorder.record_span(s);
                 }
             }
 
+            // Re-run live queries affected by this write
+            self.dispatch_subscriptions();
+
             Ok(QueryResult {
-                columns: vec![],
-                rows: vec![],
+                columns,
+                rows,
                 affected_rows,
                 last_insert_id,
                 execution_time_ms,
@@ -1323,883 +2210,4969 @@ impl Database {
         }
     }
 
-    /// Set telemetry metrics for this database instance
-    #[cfg(feature = "telemetry")]
-    pub fn set_metrics(&mut self, metrics: Option<crate::telemetry::Metrics>) {
-        self.metrics = metrics.clone();
-        self.ensure_metrics_propagated();
+    pub async fn execute_with_params_internal(
+        &mut self,
+        sql: &str,
+        params: &[ColumnValue],
+    ) -> Result<QueryResult, DatabaseError> {
+        let deadline = unsafe {
+            crate::query_timeout::QueryDeadline::install(self.db(), self.query_timeout_ms)
+        };
+        let result = crate::storage::retry_logic::with_schema_retry(|| {
+            crate::storage::retry_logic::with_busy_retry(self.busy_retry, || {
+                self.execute_with_params_once(sql, params)
+            })
+        })
+        .await
+        .map_err(|e| crate::query_timeout::interrupt_error(e, deadline.as_ref()))
+        .map_err(|e| self.with_vfs_error(e));
+        // The sync below must not run against the statement's deadline
+        drop(deadline);
+        let result = result?;
+        self.sync_after_write().await?;
+        Ok(result)
     }
 
-    /// Set span recorder for distributed tracing
-    #[cfg(feature = "telemetry")]
-    pub fn set_span_recorder(&mut self, recorder: Option<crate::telemetry::SpanRecorder>) {
-        self.span_recorder = recorder;
+    /// `execute_with_params_internal` with its own time budget instead of `query_timeout_ms`
+    pub async fn execute_with_timeout_internal(
+        &mut self,
+        sql: &str,
+        params: &[ColumnValue],
+        timeout_ms: u32,
+    ) -> Result<QueryResult, DatabaseError> {
+        let configured = self.query_timeout_ms.replace(timeout_ms);
+        let result = self.execute_with_params_internal(sql, params).await;
+        self.query_timeout_ms = configured;
+        result
     }
 
-    /// Get span context for distributed tracing
-    #[cfg(feature = "telemetry")]
-    pub fn get_span_context(&self) -> Option<&crate::telemetry::SpanContext> {
-        self.span_context.as_ref()
-    }
+    /// Stream every row of `table` from `source` into the same table here
+    ///
+    /// Runs in a savepoint, so a failure leaves the target unchanged. Returns the number
+    /// of rows written (rows skipped by `CopyMode::Ignore` are not counted).
+    pub async fn copy_table_internal(
+        &mut self,
+        source: &mut Database,
+        table: &str,
+        mode: CopyMode,
+    ) -> Result<u32, DatabaseError> {
+        const SAVEPOINT: &str = "absurder_copy_table";
+        const BATCH_SIZE: usize = 500;
+
+        // A cursor and inserts on one connection could read back the rows being inserted
+        if source.pool_key() == self.pool_key() {
+            return Err(DatabaseError::new(
+                "COPY_SAME_DATABASE",
+                "copyTable needs two different databases",
+            ));
+        }
 
-    /// Get span recorder for distributed tracing
-    #[cfg(feature = "telemetry")]
-    pub fn get_span_recorder(&self) -> Option<&crate::telemetry::SpanRecorder> {
-        self.span_recorder.as_ref()
-    }
-    /// Ensure metrics are propagated to BlockStorage
-    #[cfg(feature = "telemetry")]
-    fn ensure_metrics_propagated(&self) {
-        // Propagate metrics to BlockStorage via STORAGE_REGISTRY
-        #[cfg(target_arch = "wasm32")]
-        {
-            use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+        let quoted_table = crate::utils::quote_identifier(table);
+        let cursor =
+            source.query_stream_internal(&format!("SELECT * FROM {}", quoted_table), BATCH_SIZE)?;
+        let Some(first) = cursor.next_batch()? else {
+            return Ok(0);
+        };
 
-            if self.metrics.is_none() {
-                return;
+        let columns = first
+            .columns
+            .iter()
+            .map(|c| crate::utils::quote_identifier(c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let placeholders = vec!["?"; first.columns.len()].join(", ");
+        let sql = format!(
+            "{} INTO {} ({}) VALUES ({})",
+            mode.as_sql(),
+            quoted_table,
+            columns,
+            placeholders
+        );
+        self.check_write_permission(&sql).await?;
+
+        self.execute_shared(&format!("SAVEPOINT {}", SAVEPOINT))
+            .await?;
+        let mut copied = 0u32;
+        let mut batch = Some(first);
+        let failure = loop {
+            let Some(result) = batch.take() else {
+                break None;
+            };
+            let rows: Vec<Vec<ColumnValue>> = result.rows.into_iter().map(|r| r.values).collect();
+            match self.insert_many_internal(&sql, &rows).await {
+                Ok(inserted) => copied += inserted.affected_rows,
+                Err(e) => break Some(e),
+            }
+            match cursor.next_batch() {
+                Ok(next) => batch = next,
+                Err(e) => break Some(e),
             }
+        };
+        drop(cursor);
+
+        if let Some(err) = failure {
+            for statement in [
+                format!("ROLLBACK TO {}", SAVEPOINT),
+                format!("RELEASE {}", SAVEPOINT),
+            ] {
+                if let Err(e) = self.execute_shared(&statement).await {
+                    log::warn!("Failed to roll back copyTable on {}: {}", self.name, e);
+                }
+            }
+            return Err(err);
+        }
+        self.execute_shared(&format!("RELEASE {}", SAVEPOINT))
+            .await?;
+        self.sync_after_write().await?;
 
-            let db_name = &self.name;
+        log::info!(
+            "Copied {} rows of {} from {} into {}",
+            copied,
+            table,
+            source.name,
+            self.name
+        );
+        Ok(copied)
+    }
 
-            if let Some(storage_rc) = get_storage_with_fallback(db_name) {
-                use crate::vfs::indexeddb_vfs::with_storage_borrow_mut;
-                let _ = with_storage_borrow_mut(&storage_rc, "set_metrics", |storage| {
-                    storage.set_metrics(self.metrics.clone());
-                    Ok(())
+    /// DDL for every table, index, view and trigger, in an order that can be replayed
+    ///
+    /// Internal `sqlite_` objects and the shadow tables of virtual tables are left out, since
+    /// SQLite creates them itself. When `tables` is non-empty only those tables are exported,
+    /// together with their indexes and triggers and every view that reads nothing but
+    /// exported tables (plus the triggers on those views), so the subset stays usable on its
+    /// own. Trigger bodies are not checked for the tables they write. Fails with
+    /// `TABLE_NOT_FOUND` for a name that is not a table.
+    pub async fn export_schema_internal(
+        &mut self,
+        tables: &[String],
+    ) -> Result<String, DatabaseError> {
+        const SQL: &str = "SELECT type, name, tbl_name, sql FROM sqlite_master AS m \
+             WHERE sql IS NOT NULL AND substr(name, 1, 7) != 'sqlite_' \
+             AND NOT EXISTS (SELECT 1 FROM pragma_table_list AS t \
+             WHERE t.schema = 'main' AND t.name = m.name AND t.type = 'shadow') \
+             ORDER BY CASE type WHEN 'table' THEN 0 WHEN 'index' THEN 1 \
+             WHEN 'view' THEN 2 ELSE 3 END, rowid";
+        let result = self.execute_shared(SQL).await?;
+        let objects: Vec<[String; 4]> = result
+            .rows
+            .into_iter()
+            .map(|row| {
+                let mut text = row.values.into_iter().map(|value| match value {
+                    ColumnValue::Text(text) => text,
+                    _ => String::new(),
                 });
+                std::array::from_fn(|_| text.next().unwrap_or_default())
+            })
+            .collect();
+
+        // Object names are case-insensitive in SQLite
+        let included = if tables.is_empty() {
+            None
+        } else {
+            let mut names = std::collections::HashSet::new();
+            for table in tables {
+                let known = objects
+                    .iter()
+                    .any(|[kind, name, ..]| kind == "table" && name.eq_ignore_ascii_case(table));
+                if !known {
+                    return Err(DatabaseError::new(
+                        "TABLE_NOT_FOUND",
+                        &format!("No table named '{}' to export", table),
+                    ));
+                }
+                names.insert(table.to_lowercase());
+            }
+            for [kind, name, ..] in &objects {
+                if kind != "view" {
+                    continue;
+                }
+                let reads = self.referenced_tables_internal(&format!(
+                    "SELECT * FROM {}",
+                    crate::utils::quote_identifier(name)
+                ))?;
+                if reads
+                    .iter()
+                    .all(|table| names.contains(&table.to_lowercase()))
+                {
+                    names.insert(name.to_lowercase());
+                }
+            }
+            Some(names)
+        };
+
+        let mut ddl = String::new();
+        for [kind, name, table, sql] in &objects {
+            if let Some(names) = &included {
+                let owner = match kind.as_str() {
+                    "index" | "trigger" => table,
+                    _ => name,
+                };
+                if !names.contains(&owner.to_lowercase()) {
+                    continue;
+                }
             }
+            ddl.push_str(sql);
+            ddl.push_str(";\n");
         }
+        Ok(ddl)
     }
 
-    pub async fn close_internal(&mut self) -> Result<(), DatabaseError> {
-        log::info!("CLOSE_INTERNAL STARTED for: {}", self.name);
-
-        // Check if connection is already null (e.g., after import force-close)
-        if self.connection_state.db.get().is_null() {
-            log::info!(
-                "Connection already null for {}, skipping close operations",
-                self.name
-            );
-            return Ok(());
+    /// Run a schema script (e.g. from `export_schema_internal`) as one transaction
+    ///
+    /// Either every statement applies or none do.
+    pub async fn apply_schema_internal(&mut self, ddl: &str) -> Result<(), DatabaseError> {
+        const SAVEPOINT: &str = "absurder_apply_schema";
+        self.ensure_no_open_cursor(ddl)?;
+        if self.read_only {
+            return Err(Self::read_only_error().with_sql(ddl));
+        }
+        if !self.is_memory() && self.multi_tab && !self.allow_non_leader_writes {
+            Self::ensure_leader(&self.name).await?;
         }
+        let ddl_cstr = Self::sql_cstring(ddl)?;
 
-        // Checkpoint WAL data before close using PASSIVE mode (non-blocking)
-        log::info!("Checkpointing WAL before close: {}", self.name);
-        let _ = self
-            .execute_internal("PRAGMA wal_checkpoint(PASSIVE)")
-            .await;
-        log::info!("WAL checkpoint completed for: {}", self.name);
+        self.execute_shared(&format!("SAVEPOINT {}", SAVEPOINT))
+            .await?;
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_exec(
+                self.db(),
+                ddl_cstr.as_ptr(),
+                None,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            let err = DatabaseError::new(
+                "SQLITE_ERROR",
+                &format!("Failed to apply schema: {}", self.last_error_message()),
+            )
+            .with_sqlite_code(self.last_error_code());
+            for statement in [
+                format!("ROLLBACK TO {}", SAVEPOINT),
+                format!("RELEASE {}", SAVEPOINT),
+            ] {
+                if let Err(e) = self.execute_shared(&statement).await {
+                    log::warn!("Failed to roll back applySchema on {}: {}", self.name, e);
+                }
+            }
+            return Err(self.with_vfs_error(err));
+        }
+        self.execute_shared(&format!("RELEASE {}", SAVEPOINT))
+            .await?;
 
-        // Sync to IndexedDB before closing to ensure data persists
-        log::info!("Syncing database before close: {}", self.name);
-        self.sync_internal().await?;
-        log::info!("Sync completed for: {}", self.name);
+        // The DDL ran outside `execute_internal`, so cached statements still see the old schema
+        self.invalidate_cached_statements();
+        self.mark_written();
+        self.sync_after_write().await
+    }
 
-        web_sys::console::log_1(
-            &format!("CLOSE: About to stop leader election for {}", self.name).into(),
-        );
+    /// Tables in the main schema, sorted by name
+    ///
+    /// Internal `sqlite_` tables and the shadow tables of virtual tables are left out
+    /// unless `include_internal` is set.
+    pub async fn list_tables_internal(
+        &mut self,
+        include_internal: bool,
+    ) -> Result<Vec<TableSummary>, DatabaseError> {
+        const SQL: &str = "SELECT name, type, ncol, wr, strict FROM pragma_table_list \
+             WHERE schema = 'main' AND type IN ('table', 'virtual', 'shadow') ORDER BY name";
+        let result = self.execute_shared(SQL).await?;
+        let mut tables = Vec::new();
+        for row in &result.rows {
+            let name = row.get_text(0)?;
+            let kind = row.get_text(1)?;
+            if !include_internal && (name.starts_with("sqlite_") || kind == "shadow") {
+                continue;
+            }
+            tables.push(TableSummary {
+                name: name.to_string(),
+                kind: kind.to_string(),
+                column_count: row.get_i64(2)? as u32,
+                without_rowid: row.get_i64(3)? != 0,
+                strict: row.get_i64(4)? != 0,
+            });
+        }
+        Ok(tables)
+    }
 
-        // Stop leader election before closing
-        #[cfg(target_arch = "wasm32")]
-        {
-            use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+    /// Columns of `table` in declaration order, or `TABLE_NOT_FOUND`
+    pub async fn table_schema_internal(
+        &mut self,
+        table: &str,
+    ) -> Result<TableSchema, DatabaseError> {
+        let name = self.resolve_table_name(table).await?;
+        let result = self
+            .execute_with_params_internal(
+                "SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?) \
+                 ORDER BY cid",
+                &[ColumnValue::Text(name.clone())],
+            )
+            .await?;
+        let mut columns = Vec::with_capacity(result.rows.len());
+        for row in &result.rows {
+            columns.push(ColumnInfo {
+                name: row.get_text(0)?.to_string(),
+                declared_type: row.get_text(1)?.to_string(),
+                nullable: row.get_i64(2)? == 0,
+                default_value: Self::optional_text(row, 3)?,
+                primary_key: row.get_i64(4)? as u32,
+            });
+        }
+        Ok(TableSchema { name, columns })
+    }
 
-            let db_name = &self.name;
-            web_sys::console::log_1(
-                &format!("STOP_ELECTION: Getting storage for {}", db_name).into(),
-            );
-            log::info!("STOP_ELECTION: Getting storage for {}", db_name);
-            let storage_rc = get_storage_with_fallback(db_name);
+    /// Indexes on `table`, or `TABLE_NOT_FOUND`
+    ///
+    /// Indexes SQLite creates for PRIMARY KEY and UNIQUE constraints (`sqlite_autoindex_`)
+    /// are left out unless `include_internal` is set.
+    pub async fn list_indexes_internal(
+        &mut self,
+        table: &str,
+        include_internal: bool,
+    ) -> Result<Vec<IndexInfo>, DatabaseError> {
+        let name = self.resolve_table_name(table).await?;
+        let result = self
+            .execute_with_params_internal(
+                "SELECT name, \"unique\", origin, partial FROM pragma_index_list(?) ORDER BY seq",
+                &[ColumnValue::Text(name)],
+            )
+            .await?;
+        let mut indexes = Vec::new();
+        for row in &result.rows {
+            let index = row.get_text(0)?.to_string();
+            if !include_internal && index.starts_with("sqlite_") {
+                continue;
+            }
+            let keys = self
+                .execute_with_params_internal(
+                    "SELECT name FROM pragma_index_info(?) ORDER BY seqno",
+                    &[ColumnValue::Text(index.clone())],
+                )
+                .await?;
+            let columns = keys
+                .rows
+                .iter()
+                .map(|key| Self::optional_text(key, 0))
+                .collect::<Result<_, _>>()?;
+            indexes.push(IndexInfo {
+                name: index,
+                unique: row.get_i64(1)? != 0,
+                origin: row.get_text(2)?.to_string(),
+                partial: row.get_i64(3)? != 0,
+                columns,
+            });
+        }
+        Ok(indexes)
+    }
 
-            if let Some(storage_rc) = storage_rc {
-                log::info!("STOP_ELECTION: Found storage for {}, calling stop", db_name);
-                match with_storage_async!(storage_rc, "stop_leader_election", |storage| storage
-                    .stop_leader_election())
+    /// Foreign keys declared on `table`, one entry per constraint, or `TABLE_NOT_FOUND`
+    pub async fn list_foreign_keys_internal(
+        &mut self,
+        table: &str,
+    ) -> Result<Vec<ForeignKeyInfo>, DatabaseError> {
+        let name = self.resolve_table_name(table).await?;
+        let result = self
+            .execute_with_params_internal(
+                "SELECT id, \"table\", \"from\", \"to\", on_update, on_delete \
+                 FROM pragma_foreign_key_list(?) ORDER BY id, seq",
+                &[ColumnValue::Text(name)],
+            )
+            .await?;
+        let mut keys: Vec<(i64, ForeignKeyInfo)> = Vec::new();
+        for row in &result.rows {
+            let id = row.get_i64(0)?;
+            if keys.last().is_none_or(|(last, _)| *last != id) {
+                keys.push((
+                    id,
+                    ForeignKeyInfo {
+                        table: row.get_text(1)?.to_string(),
+                        from: Vec::new(),
+                        to: Vec::new(),
+                        on_update: row.get_text(4)?.to_string(),
+                        on_delete: row.get_text(5)?.to_string(),
+                    },
+                ));
+            }
+            if let Some((_, key)) = keys.last_mut() {
+                key.from.push(row.get_text(2)?.to_string());
+                key.to.push(Self::optional_text(row, 3)?);
+            }
+        }
+        Ok(keys.into_iter().map(|(_, key)| key).collect())
+    }
+
+    /// The stored spelling of a table name (names are case-insensitive), or
+    /// `TABLE_NOT_FOUND`
+    async fn resolve_table_name(&mut self, table: &str) -> Result<String, DatabaseError> {
+        let result = self
+            .execute_with_params_internal(
+                "SELECT name FROM pragma_table_list \
+                 WHERE schema = 'main' AND type != 'view' AND name = ? COLLATE NOCASE",
+                &[ColumnValue::Text(table.to_string())],
+            )
+            .await?;
+        match result.rows.first() {
+            Some(row) => Ok(row.get_text(0)?.to_string()),
+            None => Err(DatabaseError::new(
+                "TABLE_NOT_FOUND",
+                &format!("No table named '{}'", table),
+            )),
+        }
+    }
+
+    /// TEXT value at `col`, or None for NULL
+    fn optional_text(row: &Row, col: usize) -> Result<Option<String>, DatabaseError> {
+        if row.is_null(col)? {
+            Ok(None)
+        } else {
+            Ok(Some(row.get_text(col)?.to_string()))
+        }
+    }
+
+    /// Insert many rows with one compiled statement inside one savepoint
+    ///
+    /// `sql` must be a single INSERT (or REPLACE) statement and every row must supply
+    /// exactly its parameter count; both are checked before anything is written. A failing
+    /// row rolls back every row of the call. Returns the total `affected_rows` and the
+    /// `last_insert_id` of the final row.
+    pub async fn insert_many_internal(
+        &mut self,
+        sql: &str,
+        rows: &[Vec<ColumnValue>],
+    ) -> Result<QueryResult, DatabaseError> {
+        let start_time = js_sys::Date::now();
+        let report = self.run_insert_many(sql, rows, false).await?;
+        Ok(QueryResult {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            affected_rows: report.affected_rows,
+            last_insert_id: report.last_insert_id,
+            execution_time_ms: js_sys::Date::now() - start_time,
+        })
+    }
+
+    /// Insert many rows, skipping the ones that fail instead of rolling back the call
+    ///
+    /// Each row succeeds or fails on its own and the report lists every row's outcome.
+    /// Interrupts, timeouts and failures that abort the enclosing transaction still
+    /// fail the whole call, since no later row could be inserted.
+    pub async fn insert_many_best_effort_internal(
+        &mut self,
+        sql: &str,
+        rows: &[Vec<ColumnValue>],
+    ) -> Result<BatchReport, DatabaseError> {
+        self.run_insert_many(sql, rows, true).await
+    }
+
+    async fn run_insert_many(
+        &mut self,
+        sql: &str,
+        rows: &[Vec<ColumnValue>],
+        continue_on_error: bool,
+    ) -> Result<BatchReport, DatabaseError> {
+        const SAVEPOINT: &str = "absurder_insert_many";
+        self.ensure_no_open_cursor(sql)?;
+        self.ensure_writable(sql)?;
+        self.ensure_statement_capacity(sql)?;
+
+        let upper = sql.trim_start().to_uppercase();
+        if !(upper.starts_with("INSERT") || upper.starts_with("REPLACE")) {
+            return Err(DatabaseError::new(
+                "INVALID_BULK_INSERT",
+                "insertMany only accepts an INSERT statement",
+            )
+            .with_sql(sql));
+        }
+
+        let sql_cstr = Self::sql_cstring(sql)?;
+        let mut stmt = std::ptr::null_mut();
+        let mut tail = std::ptr::null();
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_prepare_v2(
+                self.db(),
+                sql_cstr.as_ptr(),
+                -1,
+                &mut stmt,
+                &mut tail,
+            )
+        };
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            return Err(DatabaseError::new(
+                "SQLITE_ERROR",
+                &format!("Failed to prepare statement: {}", self.last_error_message()),
+            )
+            .with_sql(sql)
+            .with_sqlite_code(self.last_error_code()));
+        }
+        let finalize = |err: DatabaseError| {
+            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+            Err(err.with_sql(sql))
+        };
+
+        let rest = unsafe { std::ffi::CStr::from_ptr(tail) }.to_string_lossy();
+        if !rest.trim().trim_start_matches(';').trim().is_empty() {
+            return finalize(DatabaseError::new(
+                "INVALID_BULK_INSERT",
+                "insertMany only accepts a single statement",
+            ));
+        }
+        let param_count = unsafe { sqlite_wasm_rs::sqlite3_bind_parameter_count(stmt) } as usize;
+        if let Some((index, row)) = rows
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.len() != param_count)
+        {
+            return finalize(DatabaseError::new(
+                "PARAM_COUNT_MISMATCH",
+                &format!(
+                    "Row {} has {} values but the statement takes {} parameters",
+                    index,
+                    row.len(),
+                    param_count
+                ),
+            ));
+        }
+
+        if let Err(e) = self
+            .execute_shared(&format!("SAVEPOINT {}", SAVEPOINT))
+            .await
+        {
+            return finalize(e);
+        }
+
+        let deadline = unsafe {
+            crate::query_timeout::QueryDeadline::install(self.db(), self.query_timeout_ms)
+        };
+        let mut report = BatchReport::default();
+        let mut failure = None;
+        for (index, row) in rows.iter().enumerate() {
+            let inserted = crate::query_cursor::bind_params(stmt, row).and_then(|()| {
+                let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
+                if step_ret == sqlite_wasm_rs::SQLITE_DONE {
+                    return Ok(());
+                }
+                Err(DatabaseError::new(
+                    "SQLITE_ERROR",
+                    &format!(
+                        "Failed to insert row {}: {}",
+                        index,
+                        self.last_error_message()
+                    ),
+                )
+                .with_sqlite_code(self.last_error_code()))
+            });
+            if let Err(err) = inserted {
+                let err = crate::query_timeout::interrupt_error(err, deadline.as_ref());
+                // The savepoint is gone once SQLite rolls back the transaction itself
+                let transaction_lost =
+                    unsafe { sqlite_wasm_rs::sqlite3_get_autocommit(self.db()) } != 0;
+                if !continue_on_error
+                    || transaction_lost
+                    || err.sqlite_code.map(|code| code & 0xff)
+                        == Some(sqlite_wasm_rs::SQLITE_INTERRUPT)
                 {
-                    Some(Ok(())) => {
-                        log::info!("STOP_ELECTION: Successfully stopped for {}", db_name);
-                    }
-                    Some(Err(e)) => {
-                        log::warn!("STOP_ELECTION: Failed for {}: {:?}", db_name, e);
-                    }
-                    None => {
-                        log::warn!("STOP_ELECTION: Borrow failed for {}", db_name);
-                    }
+                    failure = Some(err);
+                    break;
                 }
+                report.record_failure(index, &err);
             } else {
-                log::warn!("STOP_ELECTION: No storage found for {}", db_name);
+                let changes = unsafe { sqlite_wasm_rs::sqlite3_changes(self.db()) } as u32;
+                let rowid = unsafe { sqlite_wasm_rs::sqlite3_last_insert_rowid(self.db()) };
+                report.record_success(index, changes, Some(rowid));
+            }
+            unsafe {
+                sqlite_wasm_rs::sqlite3_reset(stmt);
+                sqlite_wasm_rs::sqlite3_clear_bindings(stmt);
             }
-            log::info!("STOP_ELECTION: Completed section for {}", db_name);
         }
+        drop(deadline);
+        unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+
+        if let Some(err) = failure {
+            for statement in [
+                format!("ROLLBACK TO {}", SAVEPOINT),
+                format!("RELEASE {}", SAVEPOINT),
+            ] {
+                if let Err(e) = self.execute_shared(&statement).await {
+                    log::warn!("Failed to roll back insertMany on {}: {}", self.name, e);
+                }
+            }
+            return Err(self.with_vfs_error(err).with_sql(sql));
+        }
+        self.execute_shared(&format!("RELEASE {}", SAVEPOINT))
+            .await?;
 
-        // NOTE: Do NOT call release_connection here
-        // The Drop impl will handle releasing the connection when the Database instance is dropped
-        // Calling it here would cause a double-release when both close() and Drop are called
-
-        log::info!(
-            "Closed database: {} (connection will be released on Drop)",
-            self.name
-        );
-        Ok(())
+        self.mark_written();
+        self.sync_after_write().await?;
+        Ok(report)
     }
 
-    /// Query database and return rows (alias for execute that returns rows)
-    pub async fn query(&mut self, sql: &str) -> Result<Vec<Row>, DatabaseError> {
-        let result = self.execute_internal(sql).await?;
-        Ok(result.rows)
+    /// Run a query and return the first column of its first row (see `QueryResult::scalar`)
+    pub async fn query_scalar_internal(
+        &mut self,
+        sql: &str,
+        params: &[ColumnValue],
+        strict: bool,
+    ) -> Result<ColumnValue, DatabaseError> {
+        self.execute_with_params_internal(sql, params)
+            .await?
+            .scalar(strict)
+            .map_err(|e| e.with_sql(sql))
     }
 
-    pub async fn sync_internal(&mut self) -> Result<(), DatabaseError> {
-        // Start timing for telemetry
-        #[cfg(all(target_arch = "wasm32", feature = "telemetry"))]
+    async fn execute_with_params_once(
+        &self,
+        sql: &str,
+        params: &[ColumnValue],
+    ) -> Result<QueryResult, DatabaseError> {
+        use std::ffi::CStr;
+        self.ensure_no_open_cursor(sql)?;
+        self.ensure_writable(sql)?;
+        Self::ensure_window_functions(sql)?;
+        self.ensure_statement_capacity(sql)?;
         let start_time = js_sys::Date::now();
 
-        // Create span for VFS sync operation with automatic context linking
+        // Create span for query execution
         #[cfg(feature = "telemetry")]
         let span = if self.span_recorder.is_some() {
-            let mut builder = crate::telemetry::SpanBuilder::new("vfs_sync".to_string())
-                .with_attribute("operation", "sync");
-
-            // Automatically link to parent span via context and copy baggage
+            let query_type = sql
+                .trim()
+                .split_whitespace()
+                .next()
+                .unwrap_or("UNKNOWN")
+                .to_uppercase();
+            let mut builder = crate::telemetry::SpanBuilder::new("execute_query".to_string())
+                .with_attribute("query_type", query_type.clone())
+                .with_attribute("sql", sql.to_string());
             if let Some(ref context) = self.span_context {
                 builder = builder
-                    .with_context(context)
+                    .with_scope(context)
                     .with_baggage_from_context(context);
             }
-
-            let span = builder.build();
-
-            // Enter this span's context
-            if let Some(ref context) = self.span_context {
-                context.enter_span(span.span_id.clone());
-            }
-
-            Some(span)
+            Some(builder.build())
         } else {
             None
         };
 
-        // Track sync operation start
+        // Ensure metrics are propagated to BlockStorage before execution
         #[cfg(feature = "telemetry")]
-        if let Some(ref metrics) = self.metrics {
-            metrics.sync_operations_total().inc();
-        }
+        self.ensure_metrics_propagated();
 
-        // Track blocks persisted for span attributes
+        // Track query execution metrics
         #[cfg(feature = "telemetry")]
-        let mut blocks_count = 0;
+        if let Some(metrics) = &self.metrics {
+            metrics.queries_total().inc();
+        }
 
-        // Trigger VFS sync to persist all blocks to IndexedDB
-        #[cfg(target_arch = "wasm32")]
-        {
-            use crate::storage::vfs_sync;
+        let sql_cstr = Self::sql_cstring(sql)?;
 
-            // CRITICAL: Checkpoint WAL and flush page cache to ensure all data is in GLOBAL_STORAGE
-            let db_ptr = self.connection_state.db.get();
-            if !db_ptr.is_null() {
-                // Step 1: Checkpoint WAL to write all WAL data to main database
-                // TRUNCATE mode ensures WAL is cleared after checkpoint
-                let checkpoint_sql =
-                    std::ffi::CString::new("PRAGMA wal_checkpoint(TRUNCATE)").expect("valid SQL");
-                let mut stmt: *mut sqlite_wasm_rs::sqlite3_stmt = std::ptr::null_mut();
-                let prepare_result = unsafe {
-                    sqlite_wasm_rs::sqlite3_prepare_v2(
-                        db_ptr,
-                        checkpoint_sql.as_ptr(),
-                        -1,
-                        &mut stmt,
-                        std::ptr::null_mut(),
-                    )
-                };
-                if prepare_result == sqlite_wasm_rs::SQLITE_OK && !stmt.is_null() {
-                    unsafe {
-                        sqlite_wasm_rs::sqlite3_step(stmt);
-                        sqlite_wasm_rs::sqlite3_finalize(stmt);
-                    }
-                    log::debug!("WAL checkpoint completed");
-                }
+        let (stmt, ret) = match self.prepare_cached(sql, &sql_cstr) {
+            Ok(stmt) => (stmt, sqlite_wasm_rs::SQLITE_OK),
+            Err(ret) => (std::ptr::null_mut(), ret),
+        };
 
-                // Step 2: Flush page cache to VFS
-                let flush_result = unsafe { sqlite_wasm_rs::sqlite3_db_cacheflush(db_ptr) };
-                if flush_result != sqlite_wasm_rs::SQLITE_OK {
-                    log::warn!("sqlite3_db_cacheflush returned {}", flush_result);
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            // Get actual error message from SQLite
+            let err_msg = unsafe {
+                let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
+                if !msg_ptr.is_null() {
+                    CStr::from_ptr(msg_ptr).to_string_lossy().into_owned()
+                } else {
+                    format!("Unknown error (code: {})", ret)
                 }
+            };
+            let sqlite_code = self.last_error_code();
+
+            // Track error
+            #[cfg(feature = "telemetry")]
+            if let Some(metrics) = &self.metrics {
+                metrics.errors_total().inc();
             }
 
-            // Collect blocks from GLOBAL_STORAGE (where VFS writes them)
-            // CRITICAL: Use self.name WITH .db - matches main branch behavior
-            // Database.name already normalized by normalize_db_name() in Database::new()
-            let storage_name = &self.name;
+            // Finish span with error
+            #[cfg(feature = "telemetry")]
+            if let Some(mut s) = span {
+                s.status =
+                    crate::telemetry::SpanStatus::Error(format!("Failed to prepare: {}", err_msg));
+                s.end_time_ms = Some(js_sys::Date::now());
+                if let Some(recorder) = &self.span_recorder {
+                    recorder.record_span(s);
+                }
+            }
 
-            // Advance commit marker
-            let next_commit = vfs_sync::with_global_commit_marker(|cm| {
-                #[cfg(target_arch = "wasm32")]
-                let mut cm_ref = cm.borrow_mut();
-                #[cfg(not(target_arch = "wasm32"))]
-                let mut cm_ref = cm.lock();
+            return Err(DatabaseError::new(
+                "SQLITE_ERROR",
+                &format!("Failed to prepare statement: {}", err_msg),
+            )
+            .with_sql(sql)
+            .with_sqlite_code(sqlite_code));
+        }
 
-                let current = cm_ref.get(storage_name).copied().unwrap_or(0);
-                let new_marker = current + 1;
-                cm_ref.insert(storage_name.to_string(), new_marker);
-                log::debug!(
-                    "Advanced commit marker for {} from {} to {}",
-                    storage_name,
-                    current,
-                    new_marker
-                );
-                new_marker
-            });
+        // Bind parameters
+        if let Err(e) = crate::query_cursor::bind_params(stmt, params) {
+            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+            // Track error
+            #[cfg(feature = "telemetry")]
+            if let Some(metrics) = &self.metrics {
+                metrics.errors_total().inc();
+            }
+            return Err(e.with_sql(sql));
+        }
 
-            web_sys::console::log_1(
-                &format!(
-                    "[SYNC] Collecting blocks from GLOBAL_STORAGE for: {}",
-                    storage_name
-                )
-                .into(),
-            );
-            let (blocks_to_persist, metadata_to_persist) =
-                vfs_sync::with_global_storage(|storage| {
-                    #[cfg(target_arch = "wasm32")]
-                    let storage_map = storage.borrow();
-                    #[cfg(not(target_arch = "wasm32"))]
-                    let storage_map = storage.lock();
+        if sql.trim().to_uppercase().starts_with("SELECT") {
+            let column_count = unsafe { sqlite_wasm_rs::sqlite3_column_count(stmt) };
+            let mut columns = Vec::new();
+            let mut rows = Vec::new();
 
-                    let blocks = if let Some(db_storage) = storage_map.get(storage_name) {
-                        let count = db_storage.len();
-                        web_sys::console::log_1(
-                            &format!("[SYNC] Found {} blocks in GLOBAL_STORAGE", count).into(),
-                        );
-                        db_storage
-                            .iter()
-                            .map(|(&id, data)| (id, data.clone()))
-                            .collect::<Vec<_>>()
+            // Get column names
+            for i in 0..column_count {
+                let col_name = unsafe {
+                    let name_ptr = sqlite_wasm_rs::sqlite3_column_name(stmt, i);
+                    if name_ptr.is_null() {
+                        format!("col_{}", i)
                     } else {
-                        web_sys::console::log_1(
-                            &format!(
-                                "[SYNC] No blocks found in GLOBAL_STORAGE for {}",
-                                storage_name
-                            )
-                            .into(),
-                        );
-                        Vec::new()
-                    };
+                        std::ffi::CStr::from_ptr(name_ptr)
+                            .to_string_lossy()
+                            .into_owned()
+                    }
+                };
+                columns.push(col_name);
+            }
 
-                    let metadata = vfs_sync::with_global_metadata(|meta| {
-                        #[cfg(target_arch = "wasm32")]
-                        let meta_map = meta.borrow();
-                        #[cfg(not(target_arch = "wasm32"))]
-                        let meta_map = meta.lock();
-                        if let Some(db_meta) = meta_map.get(storage_name) {
-                            let count = db_meta.len();
-                            web_sys::console::log_1(
-                                &format!("[SYNC] Found {} metadata entries", count).into(),
-                            );
-                            db_meta
-                                .iter()
-                                .map(|(&id, metadata)| (id, metadata.checksum))
-                                .collect::<Vec<_>>()
+            // Execute and fetch rows
+            loop {
+                let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
+                if step_ret == sqlite_wasm_rs::SQLITE_ROW {
+                    if let Err(e) = self.ensure_result_capacity(rows.len(), sql) {
+                        unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                        return Err(e);
+                    }
+                    match crate::query_cursor::read_row(stmt, column_count, self.read_options) {
+                        Ok(row) => rows.push(row),
+                        Err(e) => {
+                            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                            return Err(e.with_sql(sql));
+                        }
+                    }
+                } else if step_ret == sqlite_wasm_rs::SQLITE_DONE {
+                    break;
+                } else {
+                    // Get SQLite error message before finalizing
+                    let err_msg = unsafe {
+                        let err_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
+                        if !err_ptr.is_null() {
+                            std::ffi::CStr::from_ptr(err_ptr)
+                                .to_string_lossy()
+                                .to_string()
                         } else {
-                            web_sys::console::log_1(&format!("[SYNC] No metadata found").into());
-                            Vec::new()
+                            "Unknown SQLite error".to_string()
                         }
-                    });
+                    };
+                    let sqlite_code = self.last_error_code();
+                    unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                    // Track error
+                    #[cfg(feature = "telemetry")]
+                    if let Some(metrics) = &self.metrics {
+                        metrics.errors_total().inc();
+                    }
+                    return Err(DatabaseError::new(
+                        "SQLITE_ERROR",
+                        &format!("Error executing SELECT statement: {}", err_msg),
+                    )
+                    .with_sql(sql)
+                    .with_sqlite_code(sqlite_code));
+                }
+            }
 
-                    (blocks, metadata)
-                });
+            self.release_statement(sql, stmt);
 
-            web_sys::console::log_1(
-                &format!(
-                    "[SYNC] Preparing to persist {} blocks to IndexedDB",
-                    blocks_to_persist.len()
-                )
-                .into(),
-            );
+            let execution_time_ms = js_sys::Date::now() - start_time;
 
-            if !blocks_to_persist.is_empty() {
+            // Track query duration
+            #[cfg(feature = "telemetry")]
+            if let Some(metrics) = &self.metrics {
+                metrics.query_duration().observe(execution_time_ms);
+            }
+
+            // Finish span successfully for SELECT query
+            #[cfg(feature = "telemetry")]
+            if let Some(mut s) = span {
+                s.status = crate::telemetry::SpanStatus::Ok;
+                s.end_time_ms = Some(js_sys::Date::now());
+                s.attributes
+                    .insert("duration_ms".to_string(), execution_time_ms.to_string());
+                s.attributes
+                    .insert("row_count".to_string(), rows.len().to_string());
+                if let Some(recorder) = &self.span_recorder {
+                    recorder.record_span(s);
+                }
+            }
+
+            Ok(QueryResult {
+                columns,
+                rows,
+                affected_rows: 0,
+                last_insert_id: None,
+                execution_time_ms,
+            })
+        } else {
+            // Non-SELECT statements
+            let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
+
+            if step_ret != sqlite_wasm_rs::SQLITE_DONE {
+                let err_msg = unsafe {
+                    let err_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
+                    if !err_ptr.is_null() {
+                        std::ffi::CStr::from_ptr(err_ptr)
+                            .to_string_lossy()
+                            .to_string()
+                    } else {
+                        "Unknown SQLite error".to_string()
+                    }
+                };
+                let sqlite_code = self.last_error_code();
+                unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                // Track error
                 #[cfg(feature = "telemetry")]
-                {
-                    blocks_count = blocks_to_persist.len();
+                if let Some(metrics) = &self.metrics {
+                    metrics.errors_total().inc();
                 }
-                web_sys::console::log_1(
-                    &format!(
-                        "[SYNC] Persisting {} blocks to IndexedDB",
-                        blocks_to_persist.len()
-                    )
-                    .into(),
-                );
-                crate::storage::wasm_indexeddb::persist_to_indexeddb_event_based(
-                    storage_name,
-                    blocks_to_persist,
-                    metadata_to_persist,
-                    next_commit,
-                    #[cfg(feature = "telemetry")]
-                    self.span_recorder.clone(),
-                    #[cfg(feature = "telemetry")]
-                    span.as_ref().map(|s| s.span_id.clone()),
+                return Err(DatabaseError::new(
+                    "SQLITE_ERROR",
+                    &format!("Failed to execute statement: {}", err_msg),
                 )
-                .await?;
-                web_sys::console::log_1(
-                    &format!("[SYNC] Successfully persisted to IndexedDB").into(),
-                );
-            } else {
-                web_sys::console::log_1(
-                    &format!("[SYNC] WARNING: No blocks to persist - GLOBAL_STORAGE is empty!")
-                        .into(),
-                );
+                .with_sql(sql)
+                .with_sqlite_code(sqlite_code));
             }
 
-            // Send notification after successful sync
-            use crate::storage::broadcast_notifications::{
-                BroadcastNotification, send_change_notification,
-            };
-
-            let notification = BroadcastNotification::DataChanged {
-                db_name: self.name.clone(),
-                timestamp: js_sys::Date::now() as u64,
-            };
+            self.release_statement(sql, stmt);
 
-            log::debug!("Sending DataChanged notification for {}", self.name);
+            let execution_time_ms = js_sys::Date::now() - start_time;
 
-            if let Err(e) = send_change_notification(&notification) {
-                log::warn!("Failed to send change notification: {}", e);
-                // Don't fail the sync if notification fails
+            // Track query duration
+            #[cfg(feature = "telemetry")]
+            if let Some(metrics) = &self.metrics {
+                metrics.query_duration().observe(execution_time_ms);
             }
-        }
-
-        // Record sync duration
-        #[cfg(all(target_arch = "wasm32", feature = "telemetry"))]
-        if let Some(ref metrics) = self.metrics {
-            let duration_ms = js_sys::Date::now() - start_time;
-            metrics.sync_duration().observe(duration_ms);
-        }
+            let affected_rows = unsafe { sqlite_wasm_rs::sqlite3_changes(self.db()) } as u32;
+            let last_insert_id = if sql.trim().to_uppercase().starts_with("INSERT") {
+                Some(unsafe { sqlite_wasm_rs::sqlite3_last_insert_rowid(self.db()) })
+            } else {
+                None
+            };
 
-        // Finish span successfully
-        #[cfg(feature = "telemetry")]
-        if let Some(mut s) = span {
-            s.status = crate::telemetry::SpanStatus::Ok;
-            #[cfg(target_arch = "wasm32")]
-            {
+            // Finish span successfully
+            #[cfg(feature = "telemetry")]
+            if let Some(mut s) = span {
+                s.status = crate::telemetry::SpanStatus::Ok;
                 s.end_time_ms = Some(js_sys::Date::now());
-                let duration_ms = s.end_time_ms.unwrap() - s.start_time_ms;
                 s.attributes
-                    .insert("duration_ms".to_string(), duration_ms.to_string());
-            }
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as f64;
-                s.end_time_ms = Some(now);
-                let duration_ms = s.end_time_ms.unwrap() - s.start_time_ms;
+                    .insert("duration_ms".to_string(), execution_time_ms.to_string());
                 s.attributes
-                    .insert("duration_ms".to_string(), duration_ms.to_string());
-            }
-            s.attributes
-                .insert("blocks_persisted".to_string(), blocks_count.to_string());
-            if let Some(recorder) = &self.span_recorder {
-                recorder.record_span(s);
+                    .insert("affected_rows".to_string(), affected_rows.to_string());
+                if let Some(recorder) = &self.span_recorder {
+                    recorder.record_span(s);
+                }
             }
 
-            // Exit span context
-            if let Some(ref context) = self.span_context {
-                context.exit_span();
-            }
-        }
+            // Re-run live queries affected by this write
+            self.dispatch_subscriptions();
 
-        Ok(())
+            Ok(QueryResult {
+                columns: vec![],
+                rows: vec![],
+                affected_rows,
+                last_insert_id,
+                execution_time_ms,
+            })
+        }
     }
-}
 
-#[cfg(target_arch = "wasm32")]
-impl Drop for Database {
-    fn drop(&mut self) {
-        web_sys::console::log_1(&format!("DROP: Releasing connection for {}", self.name).into());
+    /// Set telemetry metrics for this database instance
+    #[cfg(feature = "telemetry")]
+    pub fn set_metrics(&mut self, metrics: Option<crate::telemetry::Metrics>) {
+        self.metrics = metrics.clone();
+        self.ensure_metrics_propagated();
+    }
 
-        // Release the connection back to the pool
-        // The pool will close it if this was the last reference
-        // Pool uses name without .db, so strip it
-        let pool_key = self.name.trim_end_matches(".db");
-        crate::connection_pool::release_connection(pool_key);
+    /// Set span recorder for distributed tracing
+    #[cfg(feature = "telemetry")]
+    pub fn set_span_recorder(&mut self, recorder: Option<crate::telemetry::SpanRecorder>) {
+        self.span_recorder = recorder;
+    }
 
-        web_sys::console::log_1(&format!("DROP: Connection released for {}", self.name).into());
+    /// Get span context for distributed tracing
+    #[cfg(feature = "telemetry")]
+    pub fn get_span_context(&self) -> Option<&crate::telemetry::SpanContext> {
+        self.span_context.as_ref()
+    }
 
-        // CRITICAL: Stop heartbeat interval synchronously to prevent leaks
-        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
-        if let Some(storage_rc) = get_storage_with_fallback(&self.name) {
-            // No outer borrow needed - BlockStorage uses RefCell for interior mutability
-            let storage = &*storage_rc;
-            // Try to borrow manager - if it fails, skip (already being cleaned)
-            if let Ok(mut manager_ref) = storage.leader_election.try_borrow_mut() {
-                if let Some(ref mut manager) = *manager_ref {
-                    // Clear interval if it exists (idempotent)
-                    if let Some(interval_id) = manager.heartbeat_interval.take() {
-                        if let Some(window) = web_sys::window() {
-                            window.clear_interval_with_handle(interval_id);
-                            web_sys::console::log_1(
-                                &format!(
-                                    "DROP: Cleared heartbeat interval {} for {}",
-                                    interval_id, self.name
-                                )
-                                .into(),
-                            );
-                        }
+    /// Get span recorder for distributed tracing
+    #[cfg(feature = "telemetry")]
+    pub fn get_span_recorder(&self) -> Option<&crate::telemetry::SpanRecorder> {
+        self.span_recorder.as_ref()
+    }
+    /// Ensure metrics are propagated to BlockStorage
+    #[cfg(feature = "telemetry")]
+    fn ensure_metrics_propagated(&self) {
+        // Propagate metrics to BlockStorage via STORAGE_REGISTRY
+        #[cfg(target_arch = "wasm32")]
+        {
+            use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+
+            if self.metrics.is_none() {
+                return;
+            }
+
+            let db_name = &self.name;
+
+            if let Some(storage_rc) = get_storage_with_fallback(db_name) {
+                use crate::vfs::indexeddb_vfs::with_storage_borrow_mut;
+                let _ = with_storage_borrow_mut(&storage_rc, "set_metrics", |storage| {
+                    storage.set_metrics(self.metrics.clone());
+                    Ok(())
+                });
+            }
+        }
+    }
+
+    pub async fn close_internal(&mut self) -> Result<(), DatabaseError> {
+        log::info!("CLOSE_INTERNAL STARTED for: {}", self.name);
+
+        // Check if connection is already null (e.g., after import force-close)
+        if self.connection_state.db.get().is_null() {
+            log::info!(
+                "Connection already null for {}, skipping close operations",
+                self.name
+            );
+            return Ok(());
+        }
+
+        // Memory databases have no WAL in IndexedDB to checkpoint and nothing to sync
+        if self.is_memory() {
+            self.finalize_statement_cache();
+            self.finalize_sessions();
+            self.subscriptions.borrow_mut().clear();
+            crate::storage::subscriptions::remove_change_tracker(self.db());
+            crate::busy_handler::remove(self.db());
+            self.clear_authorizer();
+            log::info!("Closed in-memory database: {}", self.name);
+            return Ok(());
+        }
+
+        // Checkpoint WAL data before close using PASSIVE mode (non-blocking)
+        log::info!("Checkpointing WAL before close: {}", self.name);
+        let _ = self.checkpoint_internal(CheckpointMode::Passive).await;
+        log::info!("WAL checkpoint completed for: {}", self.name);
+
+        // Sync to IndexedDB before closing to ensure data persists
+        log::info!("Syncing database before close: {}", self.name);
+        self.sync_internal().await?;
+        log::info!("Sync completed for: {}", self.name);
+
+        if self.sync_mode == SyncMode::Auto && !self.is_memory() {
+            crate::storage::wasm_auto_sync::unregister_wasm_auto_sync(&self.name);
+        }
+
+        // Release attachments so their storage can be unregistered
+        for alias in self.attached_aliases() {
+            if let Err(e) = self.detach_internal(&alias).await {
+                log::warn!("Failed to detach {} from {}: {}", alias, self.name, e);
+            }
+        }
+
+        // Finalize cached statements so the connection can be closed cleanly
+        self.finalize_statement_cache();
+        self.finalize_sessions();
+        self.subscriptions.borrow_mut().clear();
+        crate::storage::subscriptions::remove_change_tracker(self.db());
+        crate::busy_handler::remove(self.db());
+        self.clear_authorizer();
+
+        web_sys::console::log_1(
+            &format!("CLOSE: About to stop leader election for {}", self.name).into(),
+        );
+
+        // Stop leader election before closing
+        #[cfg(target_arch = "wasm32")]
+        {
+            use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+
+            let db_name = &self.name;
+            web_sys::console::log_1(
+                &format!("STOP_ELECTION: Getting storage for {}", db_name).into(),
+            );
+            log::info!("STOP_ELECTION: Getting storage for {}", db_name);
+            let storage_rc = get_storage_with_fallback(db_name);
+
+            if let Some(storage_rc) = storage_rc {
+                log::info!("STOP_ELECTION: Found storage for {}, calling stop", db_name);
+                match with_storage_async!(storage_rc, "stop_leader_election", |storage| storage
+                    .stop_leader_election())
+                {
+                    Some(Ok(())) => {
+                        log::info!("STOP_ELECTION: Successfully stopped for {}", db_name);
+                    }
+                    Some(Err(e)) => {
+                        log::warn!("STOP_ELECTION: Failed for {}: {:?}", db_name, e);
+                    }
+                    None => {
+                        log::warn!("STOP_ELECTION: Borrow failed for {}", db_name);
                     }
-                    // Note: heartbeat closure is intentionally leaked (via forget())
-                    // and becomes a no-op when heartbeat_valid is set to false
+                }
+                // The final "lost leadership" event has fired; stop notifying this instance
+                if self.on_leadership_change_callback.take().is_some() {
+                    let _ = storage_rc.set_leadership_callback(None);
                 }
             } else {
-                // Manager already borrowed - skip (first DB is cleaning up)
-                web_sys::console::log_1(
-                    &format!(
-                        "[DROP] Skipping {} (heartbeat already stopped by shared DB)",
-                        self.name
-                    )
-                    .into(),
-                );
+                log::warn!("STOP_ELECTION: No storage found for {}", db_name);
             }
+            log::info!("STOP_ELECTION: Completed section for {}", db_name);
         }
 
-        // Keep BlockStorage in STORAGE_REGISTRY so multiple Database instances
-        // with the same name share the same BlockStorage and leader election state
-        // Blocks persist in GLOBAL_STORAGE across Database instances
-        log::debug!(
-            "Closed database: {} (BlockStorage remains in registry)",
+        // NOTE: Do NOT call release_connection here
+        // The Drop impl will handle releasing the connection when the Database instance is dropped
+        // Calling it here would cause a double-release when both close() and Drop are called
+
+        log::info!(
+            "Closed database: {} (connection will be released on Drop)",
             self.name
         );
+        Ok(())
     }
-}
 
-// Add wasm_bindgen exports for the main Database struct
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-impl Database {
-    #[wasm_bindgen(js_name = "newDatabase")]
-    pub async fn new_wasm(name: String) -> Result<Database, JsValue> {
-        // Normalize database name: ensure it has .db suffix
-        let normalized_name = if name.ends_with(".db") {
-            name.clone()
-        } else {
-            format!("{}.db", name)
-        };
+    /// Open a streaming cursor over a SELECT statement
+    ///
+    /// Rows are read `batch_size` at a time via `QueryCursor::next_batch`. Other queries
+    /// on this Database fail with `CURSOR_BUSY` until the cursor is exhausted or closed.
+    pub fn query_stream_internal(
+        &mut self,
+        sql: &str,
+        batch_size: usize,
+    ) -> Result<crate::query_cursor::QueryCursor, DatabaseError> {
+        use std::ffi::CStr;
+        self.ensure_no_open_cursor(sql)?;
+        self.ensure_statement_capacity(sql)?;
 
-        let config = DatabaseConfig {
-            name: normalized_name.clone(),
-            version: Some(1),
-            cache_size: Some(10_000),
-            page_size: Some(4096),
-            auto_vacuum: Some(true),
-            journal_mode: Some("WAL".to_string()),
-            max_export_size_bytes: Some(2 * 1024 * 1024 * 1024), // 2GB default
+        let sql_cstr = Self::sql_cstring(sql)?;
+
+        let mut stmt = std::ptr::null_mut();
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_prepare_v2(
+                self.db(),
+                sql_cstr.as_ptr(),
+                -1,
+                &mut stmt,
+                std::ptr::null_mut(),
+            )
         };
 
-        let db = Database::new(config)
-            .await
-            .map_err(|e| JsValue::from_str(&format!("Failed to create database: {}", e)))?;
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            let err_msg = unsafe {
+                let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(self.db());
+                if !msg_ptr.is_null() {
+                    CStr::from_ptr(msg_ptr).to_string_lossy().into_owned()
+                } else {
+                    format!("Unknown error (code: {})", ret)
+                }
+            };
+            let sqlite_code = self.last_error_code();
+            return Err(DatabaseError::new(
+                "SQLITE_ERROR",
+                &format!("Failed to prepare statement: {}", err_msg),
+            )
+            .with_sql(sql)
+            .with_sqlite_code(sqlite_code));
+        }
 
-        // Start listening for write queue requests (leader will process them)
-        Self::start_write_queue_listener(&normalized_name)?;
+        if stmt.is_null() {
+            return Err(DatabaseError::new("INVALID_SQL", "No statement to stream").with_sql(sql));
+        }
 
-        Ok(db)
+        Ok(crate::query_cursor::QueryCursor::new(
+            stmt,
+            self.connection_state.clone(),
+            self.cursor_open.clone(),
+            batch_size,
+            sql,
+            self.read_options,
+        ))
     }
 
-    /// Get the database name
-    #[wasm_bindgen(getter)]
-    pub fn name(&self) -> String {
-        self.name.clone()
+    /// Register a JavaScript callback as a scalar SQL function
+    pub fn create_scalar_function_internal(
+        &self,
+        name: &str,
+        arity: i32,
+        callback: js_sys::Function,
+        deterministic: bool,
+    ) -> Result<(), DatabaseError> {
+        crate::functions::create_scalar_function(self.db(), name, arity, deterministic, callback)?;
+        log::debug!(
+            "Registered scalar function {}/{} on {} (deterministic: {})",
+            name,
+            arity,
+            self.name,
+            deterministic
+        );
+        Ok(())
+    }
+
+    /// Register JavaScript step and final callbacks as an aggregate SQL function
+    pub fn create_aggregate_function_internal(
+        &self,
+        name: &str,
+        arity: i32,
+        step: js_sys::Function,
+        finalize: js_sys::Function,
+    ) -> Result<(), DatabaseError> {
+        crate::functions::create_aggregate_function(self.db(), name, arity, step, finalize)?;
+        log::debug!(
+            "Registered aggregate function {}/{} on {}",
+            name,
+            arity,
+            self.name
+        );
+        Ok(())
+    }
+
+    /// Remove every overload of a function registered from JavaScript
+    ///
+    /// Built-in functions cannot be removed. Returns `FUNCTION_NOT_FOUND` when no
+    /// application-defined function has this name.
+    pub fn remove_function_internal(&self, name: &str) -> Result<(), DatabaseError> {
+        let arities = self.run_cached_query(
+            "SELECT DISTINCT narg FROM pragma_function_list WHERE name = ?1 COLLATE NOCASE AND builtin = 0",
+            &[ColumnValue::Text(name.to_string())],
+        )?;
+        if arities.rows.is_empty() {
+            return Err(DatabaseError::new(
+                "FUNCTION_NOT_FOUND",
+                &format!("No function named {} is registered", name),
+            ));
+        }
+        for row in &arities.rows {
+            if let Some(ColumnValue::Integer(arity)) = row.values.first() {
+                crate::functions::remove_function(self.db(), name, *arity as i32)?;
+            }
+        }
+        log::debug!("Removed function {} from {}", name, self.name);
+        Ok(())
+    }
+
+    /// Bytes of page cache SQLite currently holds for this connection
+    fn page_cache_bytes(&self) -> u64 {
+        let (mut current, mut highwater) = (0, 0);
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_db_status(
+                self.db(),
+                sqlite_wasm_rs::SQLITE_DBSTATUS_CACHE_USED,
+                &mut current,
+                &mut highwater,
+                0,
+            )
+        };
+        if ret == sqlite_wasm_rs::SQLITE_OK {
+            current.max(0) as u64
+        } else {
+            0
+        }
+    }
+
+    /// Stored size of the database in bytes (see `getDatabaseSize`)
+    pub fn database_size_bytes_internal(&self) -> Result<u64, DatabaseError> {
+        if let Some(storage) = crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name) {
+            return Ok(storage.database_size_bytes());
+        }
+        let result = self.run_cached_query(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+            &[],
+        )?;
+        match result.rows.first().and_then(|row| row.values.first()) {
+            Some(ColumnValue::Integer(bytes)) => Ok((*bytes).max(0) as u64),
+            _ => Ok(0),
+        }
+    }
+
+    /// Free as much of this connection's page cache as possible
+    ///
+    /// With `clear_block_cache`, clean blocks are also dropped from the block storage cache;
+    /// they are read back from the in-memory store on demand. Unsynced blocks are kept.
+    pub fn release_memory_internal(
+        &self,
+        clear_block_cache: bool,
+    ) -> Result<MemoryReleaseStats, DatabaseError> {
+        let before = self.page_cache_bytes();
+        let ret = unsafe { sqlite_wasm_rs::sqlite3_db_release_memory(self.db()) };
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            return Err(DatabaseError::new(
+                "RELEASE_MEMORY_FAILED",
+                &format!("Failed to release memory: {}", self.last_error_message()),
+            )
+            .with_sqlite_code(ret));
+        }
+        let page_cache_bytes_freed = before.saturating_sub(self.page_cache_bytes());
+
+        let blocks_evicted = if clear_block_cache {
+            crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name)
+                .map_or(0, |storage| storage.release_clean_blocks() as u64)
+        } else {
+            0
+        };
+        log::debug!(
+            "Released {} bytes of page cache and {} cached blocks for {}",
+            page_cache_bytes_freed,
+            blocks_evicted,
+            self.name
+        );
+        Ok(MemoryReleaseStats {
+            page_cache_bytes_freed,
+            blocks_evicted,
+        })
+    }
+
+    /// Sizes and hit/miss counters of the statement cache and the block cache
+    pub fn cache_stats(&self) -> CacheStats {
+        CacheStats {
+            statements: self.statement_cache.borrow().stats(),
+            blocks: crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name)
+                .map(|storage| storage.block_cache_stats())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Empty the statement cache and drop clean blocks from the block cache
+    ///
+    /// Unsynced blocks stay cached, so no writes are lost. Hit/miss counters keep
+    /// counting so effectiveness can still be compared across clears.
+    pub fn clear_caches_internal(&self) {
+        self.finalize_statement_cache();
+        if let Some(storage) = crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name) {
+            let released = storage.release_clean_blocks();
+            log::debug!("Dropped {} cached blocks of {}", released, self.name);
+        }
+    }
+
+    /// Split `schema.pragma` into the SQL target and the bare pragma name
+    ///
+    /// Both parts must be plain identifiers, so the name cannot smuggle in other SQL.
+    fn pragma_target(name: &str) -> Result<(String, &str), DatabaseError> {
+        let is_identifier = |part: &str| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+        let (schema, pragma) = match name.trim().split_once('.') {
+            Some((schema, pragma)) => (Some(schema), pragma),
+            None => (None, name.trim()),
+        };
+        if !is_identifier(pragma) || !schema.is_none_or(is_identifier) {
+            return Err(DatabaseError::new(
+                "INVALID_PRAGMA",
+                &format!("Invalid pragma name: {}", name),
+            ));
+        }
+        let target = match schema {
+            Some(schema) => format!("{}.{}", crate::utils::quote_identifier(schema), pragma),
+            None => pragma.to_string(),
+        };
+        Ok((target, pragma))
+    }
+
+    /// Run `PRAGMA name` or `PRAGMA name(argument)` and parse the result
+    ///
+    /// `name` may carry a schema prefix (`aux.table_info`). Single values come back as
+    /// bool, integer, real or text, `integrity_check`/`quick_check` as a check result,
+    /// and table pragmas such as `table_info` as rows.
+    pub async fn pragma_internal(
+        &mut self,
+        name: &str,
+        argument: Option<&ColumnValue>,
+    ) -> Result<PragmaValue, DatabaseError> {
+        let (target, pragma) = Self::pragma_target(name)?;
+        let sql = match argument {
+            Some(argument) => format!(
+                "PRAGMA {}({})",
+                target,
+                crate::utils::quote_literal(argument)
+            ),
+            None => format!("PRAGMA {}", target),
+        };
+        let result = self.execute_internal(&sql).await?;
+        Ok(PragmaValue::from_result(pragma, result))
+    }
+
+    /// Run `PRAGMA name = value` and parse whatever the pragma returns
+    pub async fn set_pragma_internal(
+        &mut self,
+        name: &str,
+        value: &ColumnValue,
+    ) -> Result<PragmaValue, DatabaseError> {
+        let (target, pragma) = Self::pragma_target(name)?;
+        if matches!(value, ColumnValue::Null) {
+            return Err(DatabaseError::new(
+                "INVALID_PRAGMA",
+                &format!("PRAGMA {} needs a value", pragma),
+            ));
+        }
+        let sql = format!("PRAGMA {} = {}", target, crate::utils::quote_literal(value));
+        let result = self.execute_internal(&sql).await?;
+        Ok(PragmaValue::from_result(pragma, result))
+    }
+
+    /// Name of the FTS5 table `create_fts_index_internal` builds for `table`
+    fn fts_table_name(table: &str) -> String {
+        format!("{}_fts", table)
+    }
+
+    /// Index `columns` of `table` in an external-content FTS5 table named `{table}_fts`
+    ///
+    /// Insert, update and delete triggers keep the index in step with `table`, and the rows
+    /// already in `table` are indexed straight away. Everything is created in one
+    /// transaction. The content table must have a rowid (no `WITHOUT ROWID`).
+    pub async fn create_fts_index_internal(
+        &mut self,
+        table: &str,
+        columns: &[String],
+        options: &FtsOptions,
+    ) -> Result<(), DatabaseError> {
+        use crate::utils::{quote_identifier, quote_literal};
+        Self::ensure_fts5()?;
+        if columns.is_empty() {
+            return Err(DatabaseError::new(
+                "INVALID_FTS_INDEX",
+                "createFtsIndex needs at least one column",
+            ));
+        }
+
+        let fts_name = Self::fts_table_name(table);
+        let fts = quote_identifier(&fts_name);
+        let content = quote_identifier(table);
+        let columns: Vec<String> = columns.iter().map(|c| quote_identifier(c)).collect();
+        let list = columns.join(", ");
+        let row_values = |row: &str| {
+            columns
+                .iter()
+                .map(|c| format!("{}.{}", row, c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let (new_values, old_values) = (row_values("new"), row_values("old"));
+        let trigger = |suffix: &str| quote_identifier(&format!("{}_{}", fts_name, suffix));
+
+        let mut args = columns.clone();
+        args.push(format!(
+            "content={}",
+            quote_literal(&ColumnValue::Text(table.to_string()))
+        ));
+        if let Some(tokenize) = &options.tokenize {
+            args.push(format!(
+                "tokenize={}",
+                quote_literal(&ColumnValue::Text(tokenize.clone()))
+            ));
+        }
+        if let Some(prefix) = options.prefix.as_ref().filter(|p| !p.is_empty()) {
+            let lengths: Vec<String> = prefix.iter().map(u32::to_string).collect();
+            args.push(format!("prefix='{}'", lengths.join(" ")));
+        }
+
+        let ddl = format!(
+            "CREATE VIRTUAL TABLE {fts} USING fts5({args});\n\
+             CREATE TRIGGER {ai} AFTER INSERT ON {content} BEGIN \
+             INSERT INTO {fts}(rowid, {list}) VALUES (new.rowid, {new_values}); END;\n\
+             CREATE TRIGGER {ad} AFTER DELETE ON {content} BEGIN \
+             INSERT INTO {fts}({fts}, rowid, {list}) VALUES ('delete', old.rowid, {old_values}); END;\n\
+             CREATE TRIGGER {au} AFTER UPDATE ON {content} BEGIN \
+             INSERT INTO {fts}({fts}, rowid, {list}) VALUES ('delete', old.rowid, {old_values}); \
+             INSERT INTO {fts}(rowid, {list}) VALUES (new.rowid, {new_values}); END;\n\
+             INSERT INTO {fts}({fts}) VALUES ('rebuild');",
+            args = args.join(", "),
+            ai = trigger("ai"),
+            ad = trigger("ad"),
+            au = trigger("au"),
+        );
+        self.apply_schema_internal(&ddl).await
+    }
+
+    /// Rows of `table` matching an FTS5 `query`, best matches first
+    ///
+    /// Returns the content table's columns plus `fts_rank` (lower is better).
+    pub async fn search_fts_internal(
+        &mut self,
+        table: &str,
+        query: &str,
+        limit: u32,
+    ) -> Result<QueryResult, DatabaseError> {
+        use crate::utils::quote_identifier;
+        Self::ensure_fts5()?;
+        // FTS5 only accepts its own table name (not an alias) on the left of MATCH
+        let fts = quote_identifier(&Self::fts_table_name(table));
+        let sql = format!(
+            "SELECT {content}.*, {fts}.rank AS fts_rank FROM {fts} \
+             JOIN {content} ON {content}.rowid = {fts}.rowid \
+             WHERE {fts} MATCH ?1 ORDER BY {fts}.rank LIMIT ?2",
+            content = quote_identifier(table),
+        );
+        self.execute_with_params_internal(
+            &sql,
+            &[
+                ColumnValue::Text(query.to_string()),
+                ColumnValue::Integer(limit as i64),
+            ],
+        )
+        .await
+    }
+
+    /// Transaction state, reset first if the transaction already ended (a raw COMMIT,
+    /// or SQLite rolling back after an error)
+    fn open_transactions(&self) -> std::cell::RefMut<'_, TransactionState> {
+        let mut state = self.transactions.borrow_mut();
+        let db = self.connection_state.db.get();
+        if (state.explicit || !state.savepoints.is_empty())
+            && (db.is_null() || unsafe { sqlite_wasm_rs::sqlite3_get_autocommit(db) } != 0)
+        {
+            log::warn!(
+                "Transaction on {} ended outside the transaction API; forgetting it and {} savepoints",
+                self.name,
+                state.savepoints.len()
+            );
+            *state = TransactionState::default();
+        }
+        state
+    }
+
+    /// Position of `name` in the savepoint stack, or `SAVEPOINT_UNBALANCED`
+    fn savepoint_position(&self, name: &str, action: &str) -> Result<usize, DatabaseError> {
+        self.open_transactions()
+            .savepoints
+            .iter()
+            .rposition(|open| open == name)
+            .ok_or_else(|| {
+                DatabaseError::new(
+                    "SAVEPOINT_UNBALANCED",
+                    &format!("Cannot {} savepoint {}: it is not open", action, name),
+                )
+            })
+    }
+
+    fn savepoint_sql(statement: &str, name: &str) -> Result<String, DatabaseError> {
+        if name.is_empty() {
+            return Err(DatabaseError::new(
+                "INVALID_SAVEPOINT",
+                "Savepoint name must not be empty",
+            ));
+        }
+        Ok(format!(
+            "{} {}",
+            statement,
+            crate::utils::quote_identifier(name)
+        ))
+    }
+
+    /// Open a savepoint, starting a transaction if none is active
+    ///
+    /// SAVEPOINT is not a write itself; writes inside it are checked individually.
+    pub async fn savepoint_internal(&mut self, name: &str) -> Result<(), DatabaseError> {
+        let sql = Self::savepoint_sql("SAVEPOINT", name)?;
+        // Forget savepoints from a transaction that already ended before adding this one
+        drop(self.open_transactions());
+        self.execute_internal(&sql).await?;
+        self.transactions
+            .borrow_mut()
+            .savepoints
+            .push(name.to_string());
+        Ok(())
+    }
+
+    /// Release a savepoint and every savepoint opened after it
+    ///
+    /// Releasing the outermost savepoint commits the transaction.
+    pub async fn release_savepoint_internal(&mut self, name: &str) -> Result<(), DatabaseError> {
+        let sql = Self::savepoint_sql("RELEASE SAVEPOINT", name)?;
+        let position = self.savepoint_position(name, "release")?;
+        self.execute_internal(&sql).await?;
+        self.transactions.borrow_mut().savepoints.truncate(position);
+        Ok(())
+    }
+
+    /// Undo everything since a savepoint was opened; the savepoint itself stays open
+    pub async fn rollback_to_savepoint_internal(
+        &mut self,
+        name: &str,
+    ) -> Result<(), DatabaseError> {
+        let sql = Self::savepoint_sql("ROLLBACK TO SAVEPOINT", name)?;
+        let position = self.savepoint_position(name, "roll back to")?;
+        self.execute_internal(&sql).await?;
+        self.transactions
+            .borrow_mut()
+            .savepoints
+            .truncate(position + 1);
+        Ok(())
+    }
+
+    /// Number of savepoints currently open
+    pub fn savepoint_depth(&self) -> usize {
+        self.open_transactions().savepoints.len()
+    }
+
+    /// Whether a `beginTransaction()` transaction is open, forgetting it if it already
+    /// ended (a raw COMMIT, or SQLite rolling back after an error)
+    pub fn is_in_transaction(&self) -> bool {
+        self.open_transactions().explicit
+    }
+
+    /// Begin a transaction that stays open until `commit_internal` or `rollback_internal`
+    ///
+    /// Fails with `TRANSACTION_ALREADY_ACTIVE` while any transaction is open, including
+    /// one opened by `transaction()`, `savepoint()` or raw SQL. IMMEDIATE and EXCLUSIVE
+    /// take the write lock, so on a follower they fail like any write.
+    pub async fn begin_transaction_internal(
+        &mut self,
+        options: &TransactionOptions,
+    ) -> Result<(), DatabaseError> {
+        self.open_db()?;
+        let scope = self.transaction_scope(options);
+        if scope.nested {
+            return Err(DatabaseError::new(
+                "TRANSACTION_ALREADY_ACTIVE",
+                "A transaction is already open on this database. Commit or roll it back before beginning another.",
+            )
+            .with_sql(&format!("BEGIN {}", options.behavior.keyword())));
+        }
+        self.check_write_permission(&scope.begin).await?;
+        self.execute_internal(&scope.begin).await?;
+        self.transactions.borrow_mut().explicit = true;
+        Ok(())
+    }
+
+    /// Commit the transaction opened with `begin_transaction_internal`
+    ///
+    /// Committing writes needs the same permission as the writes themselves, so a tab
+    /// that lost leadership mid-transaction fails here and can still roll back. Any
+    /// savepoints still open inside it are committed with it.
+    pub async fn commit_internal(&mut self) -> Result<(), DatabaseError> {
+        self.ensure_in_transaction("commit")?;
+        self.check_write_permission("COMMIT").await?;
+        self.execute_internal("COMMIT").await?;
+        self.transactions.take();
+        Ok(())
+    }
+
+    /// Roll back the transaction opened with `begin_transaction_internal`
+    pub async fn rollback_internal(&mut self) -> Result<(), DatabaseError> {
+        self.ensure_in_transaction("roll back")?;
+        let result = self.execute_internal("ROLLBACK").await.map(|_| ());
+        // A failed ROLLBACK still ended the transaction if autocommit is back on
+        if result.is_ok() {
+            self.transactions.take();
+        } else {
+            drop(self.open_transactions());
+        }
+        result
+    }
+
+    fn ensure_in_transaction(&self, action: &str) -> Result<(), DatabaseError> {
+        if self.is_in_transaction() {
+            return Ok(());
+        }
+        Err(DatabaseError::new(
+            "NO_ACTIVE_TRANSACTION",
+            &format!(
+                "Nothing to {}: no transaction was started with beginTransaction()",
+                action
+            ),
+        ))
+    }
+
+    /// Statements that begin, commit and roll back one `transaction` scope
+    ///
+    /// Outside a transaction the scope is BEGIN/COMMIT; nested scopes use a uniquely
+    /// named savepoint so an inner failure only undoes the inner work.
+    fn transaction_scope(&self, options: &TransactionOptions) -> TransactionScope {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        static NEXT_SCOPE: AtomicU32 = AtomicU32::new(1);
+
+        if unsafe { sqlite_wasm_rs::sqlite3_get_autocommit(self.db()) } != 0 {
+            TransactionScope {
+                begin: format!("BEGIN {}", options.behavior.keyword()),
+                commit: "COMMIT".to_string(),
+                rollback: vec!["ROLLBACK".to_string()],
+                nested: false,
+            }
+        } else {
+            let name = format!("absurder_tx_{}", NEXT_SCOPE.fetch_add(1, Ordering::Relaxed));
+            TransactionScope {
+                begin: format!("SAVEPOINT {}", name),
+                commit: format!("RELEASE {}", name),
+                rollback: vec![format!("ROLLBACK TO {}", name), format!("RELEASE {}", name)],
+                nested: true,
+            }
+        }
+    }
+
+    /// Run one transaction control statement directly on a connection
+    ///
+    /// Used where the statement must run without borrowing the instance, e.g. once a
+    /// JS `transaction` callback settles.
+    fn exec_on_connection(
+        db: *mut sqlite_wasm_rs::sqlite3,
+        sql: &str,
+    ) -> Result<(), DatabaseError> {
+        if db.is_null() {
+            return Err(DatabaseError::new(
+                "DATABASE_CLOSED",
+                "The connection was closed during the transaction",
+            ));
+        }
+        let sql_cstr = Self::sql_cstring(sql)?;
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_exec(
+                db,
+                sql_cstr.as_ptr(),
+                None,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        if ret == sqlite_wasm_rs::SQLITE_OK {
+            return Ok(());
+        }
+        let message = unsafe {
+            std::ffi::CStr::from_ptr(sqlite_wasm_rs::sqlite3_errmsg(db))
+                .to_string_lossy()
+                .into_owned()
+        };
+        Err(DatabaseError::new("SQLITE_ERROR", &message)
+            .with_sqlite_code(unsafe { sqlite_wasm_rs::sqlite3_extended_errcode(db) })
+            .with_sql(sql))
+    }
+
+    /// Undo a transaction scope on a connection, if the transaction is still open
+    fn rollback_on_connection(db: *mut sqlite_wasm_rs::sqlite3, scope: &TransactionScope) {
+        if db.is_null() || unsafe { sqlite_wasm_rs::sqlite3_get_autocommit(db) } != 0 {
+            return;
+        }
+        for statement in &scope.rollback {
+            if let Err(e) = Self::exec_on_connection(db, statement) {
+                log::warn!("Failed to roll back transaction: {}", e);
+            }
+        }
+    }
+
+    /// Replace `busy_timeout` with `callback`, or restore it with `None`
+    ///
+    /// The callback gets the number of times SQLite has already retried the locked
+    /// operation and returns whether to try again; a falsy result or a throw fails the
+    /// statement with `SQLITE_BUSY`. It stays installed across reconnects.
+    pub fn set_busy_handler_internal(
+        &mut self,
+        callback: Option<js_sys::Function>,
+    ) -> Result<(), DatabaseError> {
+        crate::busy_handler::install(self.db(), callback.as_ref())?;
+        if callback.is_none() {
+            // Removing the handler also cleared busy_timeout; put back the one set at open
+            Self::exec_on_connection(
+                self.db(),
+                &format!(
+                    "PRAGMA busy_timeout = {}",
+                    Self::busy_timeout_ms(self.busy_retry.as_ref())
+                ),
+            )?;
+        }
+        self.busy_handler = callback;
+        Ok(())
+    }
+
+    /// Install `callback` as the statement authorizer, or remove it with `None`
+    ///
+    /// SQLite asks the callback about every action a statement performs while compiling
+    /// it (reading a column, inserting into a table, running a pragma...). The callback
+    /// gets `(actionCode, arg1, arg2, dbName, triggerOrView)` and returns `ALLOW`, `DENY`
+    /// or `IGNORE`; a denied statement fails with `SQLITE_AUTH`. It stays installed
+    /// across reconnects, and cached statements are compiled again under the new rules.
+    pub fn set_authorizer_internal(
+        &mut self,
+        callback: Option<js_sys::Function>,
+    ) -> Result<(), DatabaseError> {
+        crate::authorizer::install(self.open_db()?, callback.as_ref())?;
+        self.finalize_statement_cache();
+        self.authorizer = callback;
+        Ok(())
+    }
+
+    /// Remove this instance's authorizer before its connection closes
+    fn clear_authorizer(&mut self) {
+        if self.authorizer.take().is_some() {
+            if let Err(e) = crate::authorizer::install(self.db(), None) {
+                log::warn!("Failed to clear authorizer on {}: {}", self.name, e);
+            }
+        }
+    }
+
+    /// Run `body` in a transaction: COMMIT if it succeeds, ROLLBACK if it fails
+    ///
+    /// Calls made while a transaction is already open run in a savepoint instead, so
+    /// they nest. `options.behavior` picks BEGIN DEFERRED, IMMEDIATE or EXCLUSIVE for
+    /// the outermost call. The error from `body` is returned after rolling back.
+    pub async fn transaction_internal<T>(
+        &mut self,
+        options: &TransactionOptions,
+        body: impl AsyncFnOnce(&mut Database) -> Result<T, DatabaseError>,
+    ) -> Result<T, DatabaseError> {
+        let scope = self.transaction_scope(options);
+        self.check_write_permission(&scope.begin).await?;
+        self.execute_internal(&scope.begin).await?;
+
+        let outcome = match body(self).await {
+            Ok(value) => match self.check_write_permission(&scope.commit).await {
+                Ok(()) => self.execute_internal(&scope.commit).await.map(|_| value),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        if outcome.is_err() {
+            // The body may already have ended the transaction itself
+            let open = unsafe { sqlite_wasm_rs::sqlite3_get_autocommit(self.db()) } == 0;
+            if open {
+                for statement in &scope.rollback {
+                    if let Err(e) = self.execute_internal(statement).await {
+                        log::warn!("Failed to roll back transaction on {}: {}", self.name, e);
+                    }
+                }
+            }
+        }
+        outcome
+    }
+
+    /// Bring the schema up to date with `migrations`
+    ///
+    /// Migrations whose version is above `PRAGMA user_version` run in version order,
+    /// each in its own IMMEDIATE transaction that also sets `user_version` to its
+    /// version. The first failure rolls that migration back and stops with
+    /// `MIGRATION_FAILED`; migrations applied before it stay applied. Returns the
+    /// versions that were applied.
+    pub async fn migrate_internal(
+        &mut self,
+        migrations: &[Migration],
+    ) -> Result<Vec<u32>, DatabaseError> {
+        let ordered = crate::migrations::in_order(migrations)?;
+        if self.read_only {
+            return Err(Self::read_only_error());
+        }
+        if !self.is_memory() && self.multi_tab && !self.allow_non_leader_writes {
+            Self::ensure_leader(&self.name).await?;
+        }
+
+        let options = TransactionOptions {
+            behavior: TransactionBehavior::Immediate,
+            ..Default::default()
+        };
+        let mut applied = Vec::new();
+        for migration in ordered {
+            let ran = self
+                .transaction_internal(&options, async |db: &mut Database| {
+                    // Read inside the transaction so two tabs never run the same migration
+                    let current = match db
+                        .query_scalar_internal("PRAGMA user_version", &[], true)
+                        .await?
+                    {
+                        ColumnValue::Integer(version) => version,
+                        _ => 0,
+                    };
+                    if current >= migration.version as i64 {
+                        return Ok(false);
+                    }
+                    Self::exec_on_connection(db.db(), &migration.up_sql)?;
+                    Self::exec_on_connection(
+                        db.db(),
+                        &format!("PRAGMA user_version = {}", migration.version),
+                    )?;
+                    db.invalidate_cached_statements();
+                    db.mark_written();
+                    Ok(true)
+                })
+                .await
+                .map_err(|e| crate::migrations::migration_failed(migration, &e))?;
+            if ran {
+                log::info!("Migrated {} to version {}", self.name, migration.version);
+                applied.push(migration.version);
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Attach another persisted database under `alias`
+    ///
+    /// The database is opened through its own IndexedDB VFS and its BlockStorage stays
+    /// registered until the last attachment is detached. It is read with this
+    /// database's block size.
+    pub async fn attach_internal(&mut self, name: &str, alias: &str) -> Result<(), DatabaseError> {
+        if alias.is_empty() {
+            return Err(DatabaseError::new(
+                "INVALID_ALIAS",
+                "Attach alias must not be empty",
+            ));
+        }
+        if self.attached.borrow().contains_key(alias) {
+            return Err(DatabaseError::new(
+                "ALREADY_ATTACHED",
+                &format!("A database is already attached as '{}'", alias),
+            ));
+        }
+        if self.is_memory() {
+            return Err(DatabaseError::new(
+                "UNSUPPORTED",
+                "Memory databases cannot attach persisted databases",
+            ));
+        }
+
+        let normalized_name = normalize_db_name(name);
+        let vfs_name = Self::ensure_indexeddb_vfs(&normalized_name, self.block_size).await?;
+        let uri = format!(
+            "file:{}?vfs={}",
+            Self::uri_escape(&normalized_name),
+            Self::uri_escape(&vfs_name)
+        );
+        let sql = format!(
+            "ATTACH DATABASE '{}' AS {}",
+            uri.replace('\'', "''"),
+            crate::utils::quote_identifier(alias)
+        );
+        self.execute_internal(&sql).await?;
+
+        ATTACH_COUNTS.with(|counts| {
+            *counts
+                .borrow_mut()
+                .entry(normalized_name.clone())
+                .or_insert(0) += 1;
+        });
+        log::info!("Attached {} to {} as {}", normalized_name, self.name, alias);
+        self.attached
+            .borrow_mut()
+            .insert(alias.to_string(), normalized_name);
+        Ok(())
+    }
+
+    /// Detach the database attached as `alias`, syncing its writes first
+    ///
+    /// Its BlockStorage is unregistered when nothing else in this tab uses it.
+    pub async fn detach_internal(&mut self, alias: &str) -> Result<(), DatabaseError> {
+        let db_name = self.attached.borrow().get(alias).cloned().ok_or_else(|| {
+            DatabaseError::new(
+                "NOT_ATTACHED",
+                &format!("No database is attached as '{}'", alias),
+            )
+        })?;
+
+        // Cached statements may still reference the attached schema
+        self.finalize_statement_cache();
+        let sql = format!("DETACH DATABASE {}", crate::utils::quote_identifier(alias));
+        self.execute_internal(&sql).await?;
+        self.attached.borrow_mut().remove(alias);
+
+        if let Some(storage) = crate::vfs::indexeddb_vfs::get_storage_with_fallback(&db_name) {
+            storage.sync().await?;
+        }
+
+        let remaining = ATTACH_COUNTS.with(|counts| {
+            let mut counts = counts.borrow_mut();
+            let count = counts.get_mut(&db_name).map(|count| {
+                *count = count.saturating_sub(1);
+                *count
+            });
+            if count == Some(0) {
+                counts.remove(&db_name);
+            }
+            count.unwrap_or(0)
+        });
+        let in_use = remaining > 0
+            || [false, true].iter().any(|&read_only| {
+                crate::connection_pool::connection_exists(&Self::connection_pool_key(
+                    &db_name,
+                    read_only,
+                    Persistence::IndexedDB,
+                ))
+            });
+        if !in_use {
+            crate::vfs::indexeddb_vfs::remove_storage_from_registry(&db_name);
+            log::info!("Unregistered storage for detached database {}", db_name);
+        }
+        Ok(())
+    }
+
+    /// Aliases of the attached databases
+    pub fn attached_aliases(&self) -> Vec<String> {
+        self.attached.borrow().keys().cloned().collect()
+    }
+
+    /// Percent-encode the characters that are special in a SQLite URI filename
+    fn uri_escape(value: &str) -> String {
+        value
+            .replace('%', "%25")
+            .replace('?', "%3f")
+            .replace('#', "%23")
+            .replace('&', "%26")
+    }
+
+    /// Start recording changes to `tables` (every table when empty), returning a handle
+    pub fn start_session_internal(&self, tables: &[String]) -> Result<u32, DatabaseError> {
+        let session = crate::changeset::Session::start(self.db(), tables)?;
+        let id = self.next_session_id.get();
+        self.next_session_id.set(id.wrapping_add(1));
+        self.sessions.borrow_mut().insert(id, session);
+        Ok(id)
+    }
+
+    /// Changeset of everything recorded by a session since it started
+    pub fn capture_changeset_internal(&self, handle: u32) -> Result<Vec<u8>, DatabaseError> {
+        let sessions = self.sessions.borrow();
+        let session = sessions.get(&handle).ok_or_else(|| {
+            DatabaseError::new(
+                "SESSION_NOT_FOUND",
+                &format!("No open changeset session with handle {}", handle),
+            )
+        })?;
+        session.changeset(self.db())
+    }
+
+    /// Stop a session and free it; unknown handles are ignored
+    pub fn end_session_internal(&self, handle: u32) {
+        if let Some(session) = self.sessions.borrow_mut().remove(&handle) {
+            session.delete(self.db());
+        }
+    }
+
+    /// Apply a changeset captured on another connection
+    ///
+    /// Needs the same write access as any other write. Conflicts are resolved with
+    /// `policy` and reported row by row.
+    pub async fn apply_changeset_internal(
+        &mut self,
+        changeset: &[u8],
+        policy: ConflictPolicy,
+    ) -> Result<ChangesetApplyResult, DatabaseError> {
+        const SQL: &str = "APPLY CHANGESET";
+        self.ensure_no_open_cursor(SQL)?;
+        if self.read_only {
+            return Err(Self::read_only_error());
+        }
+        if !self.is_memory() && self.multi_tab && !self.allow_non_leader_writes {
+            Self::ensure_leader(&self.name).await?;
+        }
+
+        let result = crate::changeset::apply_changeset(self.db(), changeset, policy)
+            .map_err(|e| self.with_vfs_error(e))?;
+        if !result.aborted {
+            self.mark_written();
+            self.dispatch_subscriptions();
+            self.sync_after_write().await?;
+        }
+        Ok(result)
+    }
+
+    /// Run `PRAGMA wal_checkpoint(mode)` and parse its result row
+    pub async fn checkpoint_internal(
+        &self,
+        mode: CheckpointMode,
+    ) -> Result<CheckpointResult, DatabaseError> {
+        let sql = format!("PRAGMA wal_checkpoint({})", mode.as_sql());
+        let result = self.execute_shared(&sql).await?;
+        let integer = |i: usize| match result.rows.first().and_then(|row| row.values.get(i)) {
+            Some(ColumnValue::Integer(value)) => Ok(*value),
+            other => Err(DatabaseError::new(
+                "CHECKPOINT_ERROR",
+                &format!("Unexpected wal_checkpoint result column {}: {:?}", i, other),
+            )
+            .with_sql(&sql)),
+        };
+        Ok(CheckpointResult {
+            busy: integer(0)? != 0,
+            log_frames: integer(1)?,
+            checkpointed_frames: integer(2)?,
+        })
+    }
+
+    /// Bytes currently held in this database's WAL
+    pub fn wal_size_bytes(&self) -> usize {
+        crate::vfs::indexeddb_vfs::wal_size_bytes(&self.name)
+    }
+
+    /// Largest WAL, in bytes, this database may hold before writes fail
+    pub fn wal_max_bytes(&self) -> usize {
+        crate::vfs::indexeddb_vfs::wal_max_bytes(&self.name)
+    }
+
+    /// The open connection, or `DATABASE_CLOSED` once `close` has run
+    fn open_db(&self) -> Result<*mut sqlite_wasm_rs::sqlite3, DatabaseError> {
+        let db = self.connection_state.db.get();
+        if db.is_null() {
+            return Err(DatabaseError::new(
+                "DATABASE_CLOSED",
+                &format!("Database {} is closed", self.name),
+            ));
+        }
+        Ok(db)
+    }
+
+    /// Rowid of the most recent successful INSERT on this connection (0 if none)
+    pub fn last_insert_rowid(&self) -> Result<i64, DatabaseError> {
+        Ok(unsafe { sqlite_wasm_rs::sqlite3_last_insert_rowid(self.open_db()?) })
+    }
+
+    /// Rows changed by the most recent INSERT, UPDATE or DELETE on this connection
+    pub fn changes(&self) -> Result<u32, DatabaseError> {
+        Ok(unsafe { sqlite_wasm_rs::sqlite3_changes(self.open_db()?) } as u32)
+    }
+
+    /// Rows changed by every INSERT, UPDATE and DELETE since the connection opened
+    pub fn total_changes(&self) -> Result<u32, DatabaseError> {
+        Ok(unsafe { sqlite_wasm_rs::sqlite3_total_changes(self.open_db()?) } as u32)
+    }
+
+    /// Schema cookie (`PRAGMA schema_version`) of the database
+    ///
+    /// SQLite bumps it on every schema change, including changes made through other
+    /// connections or by an import. When it moved since the last read, statements cached
+    /// against the old schema are dropped before the next query.
+    pub fn schema_version(&self) -> Result<i64, DatabaseError> {
+        let version = Self::pragma_integer(self.open_db()?, "schema_version")?;
+        if self
+            .connection_state
+            .schema_version
+            .replace(Some(version))
+            .is_some_and(|seen| seen != version)
+        {
+            self.invalidate_cached_statements();
+        }
+        Ok(version)
+    }
+
+    /// Map a limit name such as `"sql_length"` or `"SQLITE_LIMIT_SQL_LENGTH"` to its id
+    fn limit_category(category: &str) -> Result<i32, DatabaseError> {
+        let name = category.to_ascii_lowercase();
+        let id = match name.strip_prefix("sqlite_limit_").unwrap_or(&name) {
+            "length" => sqlite_wasm_rs::SQLITE_LIMIT_LENGTH,
+            "sql_length" => sqlite_wasm_rs::SQLITE_LIMIT_SQL_LENGTH,
+            "column" => sqlite_wasm_rs::SQLITE_LIMIT_COLUMN,
+            "expr_depth" => sqlite_wasm_rs::SQLITE_LIMIT_EXPR_DEPTH,
+            "compound_select" => sqlite_wasm_rs::SQLITE_LIMIT_COMPOUND_SELECT,
+            "vdbe_op" => sqlite_wasm_rs::SQLITE_LIMIT_VDBE_OP,
+            "function_arg" => sqlite_wasm_rs::SQLITE_LIMIT_FUNCTION_ARG,
+            "attached" => sqlite_wasm_rs::SQLITE_LIMIT_ATTACHED,
+            "like_pattern_length" => sqlite_wasm_rs::SQLITE_LIMIT_LIKE_PATTERN_LENGTH,
+            "variable_number" => sqlite_wasm_rs::SQLITE_LIMIT_VARIABLE_NUMBER,
+            "trigger_depth" => sqlite_wasm_rs::SQLITE_LIMIT_TRIGGER_DEPTH,
+            "worker_threads" => sqlite_wasm_rs::SQLITE_LIMIT_WORKER_THREADS,
+            _ => {
+                return Err(DatabaseError::new(
+                    "INVALID_LIMIT",
+                    &format!("Unknown limit category: {}", category),
+                ));
+            }
+        };
+        Ok(id)
+    }
+
+    /// Set a run-time limit on the connection and return its previous value
+    ///
+    /// A negative `value` leaves the limit unchanged, so it can be used to read it.
+    /// SQLite silently caps values above the compile-time maximum.
+    pub fn set_limit_internal(&self, category: &str, value: i32) -> Result<i32, DatabaseError> {
+        let id = Self::limit_category(category)?;
+        let db = self.db();
+        if db.is_null() {
+            return Err(DatabaseError::new(
+                "NULL_CONNECTION",
+                "Database connection is null",
+            ));
+        }
+        let previous = unsafe { sqlite_wasm_rs::sqlite3_limit(db, id, value) };
+        if value >= 0 {
+            log::debug!(
+                "Limit {} on {} changed from {} to {}",
+                category,
+                self.name,
+                previous,
+                value
+            );
+        }
+        Ok(previous)
+    }
+
+    /// Size of the database file in bytes (`page_count * page_size`)
+    async fn database_size_bytes(&mut self) -> Result<u64, DatabaseError> {
+        let result = self
+            .execute_internal(
+                "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+            )
+            .await?;
+        match result.rows.first().and_then(|row| row.values.first()) {
+            Some(ColumnValue::Integer(bytes)) => Ok(*bytes as u64),
+            _ => Err(DatabaseError::new(
+                "SIZE_UNAVAILABLE",
+                "Could not read the database size",
+            )),
+        }
+    }
+
+    /// Run VACUUM and release the blocks the rewritten file no longer uses
+    ///
+    /// Freed blocks are removed from memory and IndexedDB after the vacuumed database is
+    /// synced. `progress` is called as `(blocksDeleted, blocksFreed)` after every
+    /// `progress_interval` blocks are deleted.
+    pub async fn vacuum_internal(
+        &mut self,
+        progress: Option<js_sys::Function>,
+        progress_interval: usize,
+    ) -> Result<VacuumStats, DatabaseError> {
+        if self.read_only {
+            return Err(Self::read_only_error());
+        }
+        let bytes_before = self.database_size_bytes().await?;
+
+        self.execute_internal("VACUUM").await?;
+        // In WAL mode the rewritten pages only reach the main file (and shrink it) at checkpoint
+        self.execute_internal("PRAGMA wal_checkpoint(TRUNCATE)")
+            .await?;
+        let bytes_after = self.database_size_bytes().await?;
+
+        // The VFS only shrinks the logical file size, so drop the blocks past the new end
+        // before syncing; otherwise they linger as orphans and reappear on the next open
+        let storage =
+            crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name).ok_or_else(|| {
+                DatabaseError::new(
+                    "STORAGE_NOT_FOUND",
+                    &format!("Storage not found for database: {}", self.name),
+                )
+            })?;
+        let freed = storage.release_blocks_from(bytes_after.div_ceil(self.block_size as u64));
+        self.sync_internal().await?;
+
+        let interval = progress_interval.max(1);
+        let mut deleted = 0;
+        for chunk in freed.chunks(interval) {
+            crate::storage::wasm_indexeddb::delete_blocks_from_indexeddb(
+                storage.get_db_name(),
+                chunk,
+            )
+            .await?;
+            deleted += chunk.len();
+            if let Some(callback) = &progress {
+                if let Err(e) = callback.call2(
+                    &JsValue::NULL,
+                    &JsValue::from_f64(deleted as f64),
+                    &JsValue::from_f64(freed.len() as f64),
+                ) {
+                    log::warn!("Vacuum progress callback failed: {:?}", e);
+                }
+            }
+        }
+
+        log::info!(
+            "Vacuumed {}: {} -> {} bytes, {} blocks freed",
+            self.name,
+            bytes_before,
+            bytes_after,
+            freed.len()
+        );
+        Ok(VacuumStats {
+            bytes_before,
+            bytes_after,
+            blocks_freed: freed.len() as u64,
+        })
+    }
+
+    /// Get the tables a SQL statement reads, sorted by name
+    ///
+    /// The statement is prepared but never stepped; views resolve to their base tables.
+    pub fn referenced_tables_internal(&self, sql: &str) -> Result<Vec<String>, DatabaseError> {
+        let sql_cstr = Self::sql_cstring(sql)?;
+        let tables =
+            unsafe { crate::storage::subscriptions::referenced_tables(self.db(), &sql_cstr) }
+                .map_err(|_| {
+                    DatabaseError::new(
+                        "SQLITE_ERROR",
+                        &format!("Failed to prepare statement: {}", self.last_error_message()),
+                    )
+                    .with_sql(sql)
+                })?;
+
+        let mut tables: Vec<String> = tables.into_iter().collect();
+        tables.sort();
+        Ok(tables)
+    }
+
+    /// Subscribe to a live query
+    ///
+    /// Runs the query immediately, then re-runs it after every write that touches one of
+    /// the tables it reads, passing each result to `callback`. Returns the subscription ID.
+    pub fn subscribe_internal(
+        &mut self,
+        sql: &str,
+        params: Vec<ColumnValue>,
+        callback: js_sys::Function,
+    ) -> Result<u32, DatabaseError> {
+        self.ensure_no_open_cursor(sql)?;
+
+        let tables = self.referenced_tables_internal(sql)?.into_iter().collect();
+        let initial = self.run_cached_query(sql, &params)?;
+
+        unsafe { crate::storage::subscriptions::install_update_hook(self.db()) };
+        let id =
+            self.subscriptions
+                .borrow_mut()
+                .add(sql.to_string(), params, tables, callback.clone());
+        log::debug!("Registered subscription {} for {}", id, self.name);
+
+        Self::deliver_query_result(callback, &initial);
+        Ok(id)
+    }
+
+    /// Remove a live query subscription, returning whether it existed
+    pub fn unsubscribe(&mut self, id: u32) -> bool {
+        self.subscriptions.borrow_mut().remove(id)
+    }
+
+    /// Query database and return rows (alias for execute that returns rows)
+    pub async fn query(&mut self, sql: &str) -> Result<Vec<Row>, DatabaseError> {
+        let result = self.execute_internal(sql).await?;
+        Ok(result.rows)
+    }
+
+    /// Wait until storage is restored and leader election has settled
+    ///
+    /// Storage is ready once no other open is still initializing this database and no
+    /// IndexedDB restore is in flight. Writers additionally wait until they know who
+    /// the leader is. Fails with `NOT_READY` after `timeout_ms`, or the configured
+    /// `election_timeout_ms` when `None`.
+    pub async fn when_ready_internal(&self, timeout_ms: Option<u32>) -> Result<(), DatabaseError> {
+        use crate::vfs::indexeddb_vfs::{get_storage_with_fallback, init_in_progress};
+
+        // Memory databases have nothing to restore and no leader
+        if self.is_memory() {
+            return Ok(());
+        }
+
+        let start = js_sys::Date::now();
+        let timeout_ms = timeout_ms
+            .or_else(|| {
+                get_storage_with_fallback(&self.name)
+                    .map(|storage| storage.leader_election_config().election_timeout_ms)
+            })
+            .unwrap_or(5000) as f64;
+        let timed_out = |what: &str| {
+            DatabaseError::new(
+                "NOT_READY",
+                &format!(
+                    "{} for {} did not finish within {} ms",
+                    what, self.name, timeout_ms
+                ),
+            )
+        };
+
+        // 1. Blocks, metadata and commit marker restored
+        let storage = loop {
+            if !init_in_progress(&self.name) {
+                if let Some(storage) = get_storage_with_fallback(&self.name) {
+                    break storage;
+                }
+            }
+            if js_sys::Date::now() - start > timeout_ms {
+                return Err(timed_out("Storage restore"));
+            }
+            crate::storage::retry_logic::sleep_ms(10).await;
+        };
+        crate::storage::wasm_indexeddb::wait_for_idle().await;
+
+        // 2. Leader election settled; read-only instances never take part
+        if self.read_only {
+            return Ok(());
+        }
+        if storage.is_leader().await {
+            return Ok(());
+        }
+        while !storage.leader_known() {
+            if js_sys::Date::now() - start > timeout_ms {
+                return Err(timed_out("Leader election"));
+            }
+            crate::storage::retry_logic::sleep_ms(20).await;
+            // Re-checking also re-runs the election when the previous lease lapsed
+            let _ = storage.is_leader().await;
+        }
+        Ok(())
+    }
+
+    /// Persist all changes to IndexedDB (`sync()` in JS with the default level)
+    pub async fn sync(&mut self) -> Result<(), JsValue> {
+        self.sync_internal()
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to sync database: {}", e)))
+    }
+
+    pub async fn sync_internal(&mut self) -> Result<(), DatabaseError> {
+        self.sync_with_level_internal(SyncLevel::Normal)
+            .await
+            .map(|_| ())
+    }
+
+    /// Persist every block to IndexedDB, waiting as far as `level` asks
+    ///
+    /// With `SyncLevel::Durable` the commit marker is read back from IndexedDB once the
+    /// write completes and the sync fails with `SYNC_NOT_DURABLE` unless it matches.
+    /// Attached databases are synced too, but only this database is verified.
+    pub async fn sync_with_level_internal(
+        &mut self,
+        level: SyncLevel,
+    ) -> Result<SyncResult, DatabaseError> {
+        // Nothing is persisted for memory databases
+        if self.is_memory() {
+            log::debug!("Sync skipped for in-memory database {}", self.name);
+            return Ok(SyncResult::default());
+        }
+        let mut sync_result = SyncResult::default();
+
+        // This sync covers every write so far, including any waiting on a debounced sync
+        self.unsynced_writes.set(false);
+        self.sync_generation
+            .set(self.sync_generation.get().wrapping_add(1));
+
+        // Start timing for telemetry
+        #[cfg(all(target_arch = "wasm32", feature = "telemetry"))]
+        let start_time = js_sys::Date::now();
+
+        // Create span for VFS sync operation with automatic context linking
+        #[cfg(feature = "telemetry")]
+        let span = if self.span_recorder.is_some() {
+            let mut builder = crate::telemetry::SpanBuilder::new("vfs_sync".to_string())
+                .with_attribute("operation", "sync");
+
+            // Automatically link to parent span via context and copy baggage
+            if let Some(ref context) = self.span_context {
+                builder = builder
+                    .with_context(context)
+                    .with_baggage_from_context(context);
+            }
+
+            let span = builder.build();
+
+            // Enter this span's context
+            if let Some(ref context) = self.span_context {
+                context.enter_span(span.span_id.clone());
+            }
+
+            Some(span)
+        } else {
+            None
+        };
+
+        // Track sync operation start
+        #[cfg(feature = "telemetry")]
+        if let Some(ref metrics) = self.metrics {
+            metrics.sync_operations_total().inc();
+        }
+
+        // Track blocks persisted for span attributes
+        #[cfg(feature = "telemetry")]
+        let mut blocks_count = 0;
+
+        // Trigger VFS sync to persist all blocks to IndexedDB
+        #[cfg(target_arch = "wasm32")]
+        {
+            use crate::storage::vfs_sync;
+
+            // CRITICAL: Checkpoint WAL and flush page cache to ensure all data is in GLOBAL_STORAGE
+            let db_ptr = self.connection_state.db.get();
+            if !db_ptr.is_null() {
+                // Step 1: Checkpoint WAL to write all WAL data to main database
+                // TRUNCATE mode ensures WAL is cleared after checkpoint
+                let checkpoint_sql =
+                    std::ffi::CString::new("PRAGMA wal_checkpoint(TRUNCATE)").expect("valid SQL");
+                let mut stmt: *mut sqlite_wasm_rs::sqlite3_stmt = std::ptr::null_mut();
+                let prepare_result = unsafe {
+                    sqlite_wasm_rs::sqlite3_prepare_v2(
+                        db_ptr,
+                        checkpoint_sql.as_ptr(),
+                        -1,
+                        &mut stmt,
+                        std::ptr::null_mut(),
+                    )
+                };
+                if prepare_result == sqlite_wasm_rs::SQLITE_OK && !stmt.is_null() {
+                    unsafe {
+                        sqlite_wasm_rs::sqlite3_step(stmt);
+                        sqlite_wasm_rs::sqlite3_finalize(stmt);
+                    }
+                    log::debug!("WAL checkpoint completed");
+                }
+
+                // Step 2: Flush page cache to VFS
+                let flush_result = unsafe { sqlite_wasm_rs::sqlite3_db_cacheflush(db_ptr) };
+                if flush_result != sqlite_wasm_rs::SQLITE_OK {
+                    log::warn!("sqlite3_db_cacheflush returned {}", flush_result);
+                }
+            }
+
+            // Collect blocks from GLOBAL_STORAGE (where VFS writes them)
+            // CRITICAL: Use self.name WITH .db - matches main branch behavior
+            // Database.name already normalized by normalize_db_name() in Database::new()
+            let storage_name = &self.name;
+
+            // Advance commit marker
+            let next_commit = vfs_sync::with_global_commit_marker(|cm| {
+                #[cfg(target_arch = "wasm32")]
+                let mut cm_ref = cm.borrow_mut();
+                #[cfg(not(target_arch = "wasm32"))]
+                let mut cm_ref = cm.lock();
+
+                let current = cm_ref.get(storage_name).copied().unwrap_or(0);
+                let new_marker = current + 1;
+                cm_ref.insert(storage_name.to_string(), new_marker);
+                log::debug!(
+                    "Advanced commit marker for {} from {} to {}",
+                    storage_name,
+                    current,
+                    new_marker
+                );
+                new_marker
+            });
+
+            web_sys::console::log_1(
+                &format!(
+                    "[SYNC] Collecting blocks from GLOBAL_STORAGE for: {}",
+                    storage_name
+                )
+                .into(),
+            );
+            let (blocks_to_persist, metadata_to_persist) =
+                vfs_sync::with_global_storage(|storage| {
+                    #[cfg(target_arch = "wasm32")]
+                    let storage_map = storage.borrow();
+                    #[cfg(not(target_arch = "wasm32"))]
+                    let storage_map = storage.lock();
+
+                    let blocks = if let Some(db_storage) = storage_map.get(storage_name) {
+                        let count = db_storage.len();
+                        web_sys::console::log_1(
+                            &format!("[SYNC] Found {} blocks in GLOBAL_STORAGE", count).into(),
+                        );
+                        db_storage
+                            .iter()
+                            .map(|(&id, data)| (id, data.clone()))
+                            .collect::<Vec<_>>()
+                    } else {
+                        web_sys::console::log_1(
+                            &format!(
+                                "[SYNC] No blocks found in GLOBAL_STORAGE for {}",
+                                storage_name
+                            )
+                            .into(),
+                        );
+                        Vec::new()
+                    };
+
+                    let metadata = vfs_sync::with_global_metadata(|meta| {
+                        #[cfg(target_arch = "wasm32")]
+                        let meta_map = meta.borrow();
+                        #[cfg(not(target_arch = "wasm32"))]
+                        let meta_map = meta.lock();
+                        if let Some(db_meta) = meta_map.get(storage_name) {
+                            let count = db_meta.len();
+                            web_sys::console::log_1(
+                                &format!("[SYNC] Found {} metadata entries", count).into(),
+                            );
+                            db_meta
+                                .iter()
+                                .map(|(&id, metadata)| (id, metadata.checksum))
+                                .collect::<Vec<_>>()
+                        } else {
+                            web_sys::console::log_1(&format!("[SYNC] No metadata found").into());
+                            Vec::new()
+                        }
+                    });
+
+                    (blocks, metadata)
+                });
+
+            web_sys::console::log_1(
+                &format!(
+                    "[SYNC] Preparing to persist {} blocks to IndexedDB",
+                    blocks_to_persist.len()
+                )
+                .into(),
+            );
+
+            if !blocks_to_persist.is_empty() {
+                #[cfg(feature = "telemetry")]
+                {
+                    blocks_count = blocks_to_persist.len();
+                }
+                let storage = crate::vfs::indexeddb_vfs::get_storage_with_fallback(storage_name);
+                let dirty_count = blocks_to_persist.len();
+                if let Some(storage) = &storage {
+                    storage
+                        .observability()
+                        .record_sync_start(dirty_count, dirty_count * self.block_size);
+                }
+                let persist_start = js_sys::Date::now();
+                web_sys::console::log_1(
+                    &format!(
+                        "[SYNC] Persisting {} blocks to IndexedDB",
+                        blocks_to_persist.len()
+                    )
+                    .into(),
+                );
+                let written = storage
+                    .as_ref()
+                    .filter(|storage| storage.verify_after_sync())
+                    .map(|_| blocks_to_persist.clone());
+                crate::storage::wasm_indexeddb::persist_to_indexeddb_event_based(
+                    storage_name,
+                    blocks_to_persist,
+                    metadata_to_persist,
+                    next_commit,
+                    #[cfg(feature = "telemetry")]
+                    self.span_recorder.clone(),
+                    #[cfg(feature = "telemetry")]
+                    span.as_ref().map(|s| s.span_id.clone()),
+                )
+                .await
+                .inspect_err(|e| {
+                    if let Some(storage) = &storage {
+                        storage.observability().record_sync_failure(e);
+                    }
+                    // Nothing reached IndexedDB: keep the writes pending and the marker
+                    // where it was so the next sync persists them again
+                    vfs_sync::with_global_commit_marker(|cm| {
+                        let mut cm_ref = cm.borrow_mut();
+                        if cm_ref.get(storage_name) == Some(&next_commit) {
+                            cm_ref.insert(storage_name.to_string(), next_commit - 1);
+                        }
+                    });
+                    self.unsynced_writes.set(true);
+                    if e.code == "STORAGE_QUOTA_EXCEEDED" {
+                        self.notify_storage_full(e, dirty_count);
+                    }
+                })?;
+                if let Some(storage) = &storage {
+                    storage.observability().record_sync_success(
+                        (js_sys::Date::now() - persist_start) as u64,
+                        dirty_count,
+                    );
+                }
+                web_sys::console::log_1(
+                    &format!("[SYNC] Successfully persisted to IndexedDB").into(),
+                );
+                if let Some(written) = written {
+                    // Keep the writes pending so the next sync writes them again
+                    crate::storage::wasm_indexeddb::verify_persisted_blocks(storage_name, &written)
+                        .await
+                        .inspect_err(|_| self.unsynced_writes.set(true))?;
+                }
+
+                if level == SyncLevel::Durable {
+                    let persisted =
+                        crate::storage::wasm_indexeddb::read_persisted_commit_marker(storage_name)
+                            .await?;
+                    if persisted != Some(next_commit) {
+                        return Err(DatabaseError::new(
+                            "SYNC_NOT_DURABLE",
+                            &format!(
+                                "IndexedDB commit marker for {} is {:?} after sync, expected {}",
+                                storage_name, persisted, next_commit
+                            ),
+                        ));
+                    }
+                }
+                sync_result.blocks_persisted = dirty_count as u32;
+            } else {
+                web_sys::console::log_1(
+                    &format!("[SYNC] WARNING: No blocks to persist - GLOBAL_STORAGE is empty!")
+                        .into(),
+                );
+            }
+
+            sync_result.commit_marker = next_commit;
+
+            // Notify other tabs only when this sync carries writes. Maintenance syncs
+            // (close, vacuum bookkeeping, syncs after reads) would only cause spurious
+            // follower refreshes.
+            if self.unbroadcast_writes.replace(false) && self.coordination != Coordination::Disabled
+            {
+                use crate::storage::broadcast_notifications::{
+                    BroadcastNotification, send_change_notification,
+                };
+
+                let notification = BroadcastNotification::DataChanged {
+                    db_name: self.name.clone(),
+                    timestamp: js_sys::Date::now() as u64,
+                };
+
+                log::debug!("Sending DataChanged notification for {}", self.name);
+
+                if let Err(e) = send_change_notification(&notification) {
+                    log::warn!("Failed to send change notification: {}", e);
+                    // Don't fail the sync if notification fails
+                }
+            } else {
+                log::debug!(
+                    "No writes since last notification for {}, skipping DataChanged",
+                    self.name
+                );
+            }
+        }
+
+        // Persist writes made through attached databases too
+        let attached: Vec<String> = self.attached.borrow().values().cloned().collect();
+        for db_name in attached {
+            if let Some(storage) = crate::vfs::indexeddb_vfs::get_storage_with_fallback(&db_name) {
+                storage.sync().await?;
+            }
+        }
+
+        // Record sync duration
+        #[cfg(all(target_arch = "wasm32", feature = "telemetry"))]
+        if let Some(ref metrics) = self.metrics {
+            let duration_ms = js_sys::Date::now() - start_time;
+            metrics.sync_duration().observe(duration_ms);
+        }
+
+        // Finish span successfully
+        #[cfg(feature = "telemetry")]
+        if let Some(mut s) = span {
+            s.status = crate::telemetry::SpanStatus::Ok;
+            #[cfg(target_arch = "wasm32")]
+            {
+                s.end_time_ms = Some(js_sys::Date::now());
+                let duration_ms = s.end_time_ms.unwrap() - s.start_time_ms;
+                s.attributes
+                    .insert("duration_ms".to_string(), duration_ms.to_string());
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as f64;
+                s.end_time_ms = Some(now);
+                let duration_ms = s.end_time_ms.unwrap() - s.start_time_ms;
+                s.attributes
+                    .insert("duration_ms".to_string(), duration_ms.to_string());
+            }
+            s.attributes
+                .insert("blocks_persisted".to_string(), blocks_count.to_string());
+            // Exit span context
+            if let Some(ref context) = self.span_context {
+                context.exit_span_id(&s.span_id);
+            }
+            if let Some(recorder) = &self.span_recorder {
+                recorder.record_span(s);
+            }
+        }
+
+        Ok(sync_result)
+    }
+
+    /// Tell the `onStorageFull` callback that a sync ran out of IndexedDB quota
+    fn notify_storage_full(&self, error: &DatabaseError, dirty_blocks: usize) {
+        let Some(callback) = &self.on_storage_full_callback else {
+            log::warn!(
+                "IndexedDB quota exceeded for {}: {}",
+                self.name,
+                error.message
+            );
+            return;
+        };
+        let event = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&event, &"code".into(), &error.code.as_str().into());
+        let _ = js_sys::Reflect::set(&event, &"message".into(), &error.message.as_str().into());
+        let _ = js_sys::Reflect::set(&event, &"dirtyBlocks".into(), &(dirty_blocks as f64).into());
+        if let Err(e) = callback.call1(&JsValue::NULL, &event) {
+            log::warn!("onStorageFull callback threw: {:?}", e);
+        }
+    }
+
+    /// Copy the bytes of a memory database with `sqlite3_serialize`
+    fn serialize_memory(&self, max_export_size: Option<u64>) -> Result<Vec<u8>, DatabaseError> {
+        let schema = std::ffi::CString::new("main").expect("valid schema name");
+        let mut size: i64 = 0;
+        let data =
+            unsafe { sqlite_wasm_rs::sqlite3_serialize(self.db(), schema.as_ptr(), &mut size, 0) };
+        if data.is_null() {
+            return Err(DatabaseError::new(
+                "EXPORT_ERROR",
+                &format!(
+                    "Failed to serialize database: {}",
+                    self.last_error_message()
+                ),
+            ));
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(data, size as usize).to_vec() };
+        unsafe { sqlite_wasm_rs::sqlite3_free(data as *mut std::ffi::c_void) };
+
+        if let Some(max) = max_export_size {
+            if bytes.len() as u64 > max {
+                return Err(DatabaseError::new(
+                    "DATABASE_TOO_LARGE",
+                    &format!(
+                        "Database size ({} bytes) exceeds maximum export size ({} bytes)",
+                        bytes.len(),
+                        max
+                    ),
+                ));
+            }
+        }
+        log::info!(
+            "[EXPORT] Serialized in-memory database: {} bytes",
+            bytes.len()
+        );
+        Ok(bytes)
+    }
+
+    /// Replace the contents of a memory database with `sqlite3_deserialize`
+    fn deserialize_memory(&self, data: &[u8]) -> Result<(), DatabaseError> {
+        crate::storage::export::validate_sqlite_file(data)?;
+        // Open statements would keep the old schema busy
+        self.finalize_statement_cache();
+
+        let buffer = unsafe { sqlite_wasm_rs::sqlite3_malloc64(data.len() as u64) } as *mut u8;
+        if buffer.is_null() {
+            return Err(DatabaseError::new(
+                "OUT_OF_MEMORY",
+                &format!("Failed to allocate {} bytes for import", data.len()),
+            ));
+        }
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), buffer, data.len()) };
+
+        let schema = std::ffi::CString::new("main").expect("valid schema name");
+        // SQLite owns the buffer from here on, even when deserialize fails
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_deserialize(
+                self.db(),
+                schema.as_ptr(),
+                buffer,
+                data.len() as i64,
+                data.len() as i64,
+                sqlite_wasm_rs::SQLITE_DESERIALIZE_FREEONCLOSE
+                    | sqlite_wasm_rs::SQLITE_DESERIALIZE_RESIZEABLE,
+            )
+        };
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            return Err(DatabaseError::new(
+                "IMPORT_ERROR",
+                &format!(
+                    "Failed to deserialize database: {}",
+                    self.last_error_message()
+                ),
+            )
+            .with_sqlite_code(ret));
+        }
+        log::info!(
+            "[IMPORT] Deserialized {} bytes into {}",
+            data.len(),
+            self.name
+        );
+        Ok(())
+    }
+
+    /// Get the open connection ready for its storage to be replaced underneath it
+    ///
+    /// Fails while a transaction is open. Folds the WAL into the main blocks so no old
+    /// frame shadows a replaced page, and finalizes cached statements.
+    fn prepare_for_storage_swap(&self) -> Result<(), DatabaseError> {
+        if unsafe { sqlite_wasm_rs::sqlite3_get_autocommit(self.db()) } == 0 {
+            return Err(DatabaseError::new(
+                "TRANSACTION_ACTIVE",
+                "Cannot replace storage while a transaction is open",
+            ));
+        }
+        Self::exec_on_connection(self.db(), "PRAGMA wal_checkpoint(TRUNCATE)")?;
+        self.finalize_statement_cache();
+        Ok(())
+    }
+
+    /// Make the open connection forget what it cached about the old storage
+    ///
+    /// Sets the main file's size to `file_size` and drops SQLite's page and schema
+    /// caches so the next statement reads the new contents.
+    fn forget_cached_file(&self, file_size: u64) -> Result<(), DatabaseError> {
+        unsafe { crate::vfs::indexeddb_vfs::set_main_file_size(self.db(), file_size) }?;
+        unsafe { sqlite_wasm_rs::sqlite3_db_release_memory(self.db()) };
+        Self::exec_on_connection(self.db(), "PRAGMA writable_schema = RESET")?;
+        // Other instances on this connection drop statements cached against the old schema
+        self.invalidate_cached_statements();
+        Ok(())
+    }
+
+    /// Run a read-only query against the last synced state of this database
+    ///
+    /// The committed blocks are read from IndexedDB into a private in-memory copy, so the
+    /// query neither sees unsynced writes nor waits for or blocks this or any other
+    /// connection. Only statements that do not write are accepted (`READ_ONLY_QUERY`).
+    /// Functions and collations registered on the connection are not available.
+    pub async fn query_at_snapshot_internal(
+        &self,
+        sql: &str,
+        params: &[ColumnValue],
+    ) -> Result<QueryResult, DatabaseError> {
+        use std::ffi::{CStr, CString};
+
+        if self.is_memory() {
+            return Err(DatabaseError::new(
+                "SNAPSHOT_UNAVAILABLE",
+                "Memory databases have no persisted state to snapshot",
+            ));
+        }
+        let start_time = js_sys::Date::now();
+        let sql_cstr = Self::sql_cstring(sql)?;
+        let mut file = crate::storage::wasm_indexeddb::read_committed_database_from_indexeddb(
+            &self.name,
+            self.block_size,
+        )
+        .await?;
+        // An in-memory copy can't use WAL; open it in rollback-journal mode
+        if file.len() >= 20 {
+            file[18] = 1;
+            file[19] = 1;
+        }
+
+        let error = |db: *mut sqlite_wasm_rs::sqlite3, code: i32| {
+            let message = unsafe { CStr::from_ptr(sqlite_wasm_rs::sqlite3_errmsg(db)) }
+                .to_string_lossy()
+                .into_owned();
+            DatabaseError::new("SQLITE_ERROR", &message)
+                .with_sql(sql)
+                .with_sqlite_code(code)
+        };
+        let mut db = std::ptr::null_mut();
+        let name = CString::new(":memory:").expect("valid name");
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_open_v2(
+                name.as_ptr(),
+                &mut db,
+                sqlite_wasm_rs::SQLITE_OPEN_READWRITE | sqlite_wasm_rs::SQLITE_OPEN_CREATE,
+                std::ptr::null(),
+            )
+        };
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            let e = error(db, ret);
+            unsafe { sqlite_wasm_rs::sqlite3_close(db) };
+            return Err(e);
+        }
+
+        let result = (|| {
+            let buffer = unsafe { sqlite_wasm_rs::sqlite3_malloc64(file.len() as u64) } as *mut u8;
+            if buffer.is_null() {
+                return Err(DatabaseError::new(
+                    "OUT_OF_MEMORY",
+                    &format!("Failed to allocate {} bytes for the snapshot", file.len()),
+                ));
+            }
+            unsafe { std::ptr::copy_nonoverlapping(file.as_ptr(), buffer, file.len()) };
+            let schema = CString::new("main").expect("valid schema name");
+            // SQLite owns the buffer from here on, even when deserialize fails
+            let ret = unsafe {
+                sqlite_wasm_rs::sqlite3_deserialize(
+                    db,
+                    schema.as_ptr(),
+                    buffer,
+                    file.len() as i64,
+                    file.len() as i64,
+                    sqlite_wasm_rs::SQLITE_DESERIALIZE_FREEONCLOSE
+                        | sqlite_wasm_rs::SQLITE_DESERIALIZE_READONLY,
+                )
+            };
+            if ret != sqlite_wasm_rs::SQLITE_OK {
+                return Err(error(db, ret));
+            }
+
+            let mut stmt = std::ptr::null_mut();
+            let ret = unsafe {
+                sqlite_wasm_rs::sqlite3_prepare_v2(
+                    db,
+                    sql_cstr.as_ptr(),
+                    -1,
+                    &mut stmt,
+                    std::ptr::null_mut(),
+                )
+            };
+            if ret != sqlite_wasm_rs::SQLITE_OK {
+                return Err(error(db, ret));
+            }
+            if unsafe { sqlite_wasm_rs::sqlite3_stmt_readonly(stmt) } == 0 {
+                unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                return Err(DatabaseError::new(
+                    "READ_ONLY_QUERY",
+                    "queryAtSnapshot only runs statements that do not write",
+                )
+                .with_sql(sql));
+            }
+            if let Err(e) = crate::query_cursor::bind_params(stmt, params) {
+                unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+                return Err(e.with_sql(sql));
+            }
+
+            let columns = crate::query_cursor::column_names(stmt);
+            let column_count = columns.len() as i32;
+            let mut rows = Vec::new();
+            let outcome = loop {
+                match unsafe { sqlite_wasm_rs::sqlite3_step(stmt) } {
+                    sqlite_wasm_rs::SQLITE_ROW => {
+                        let row = self.ensure_result_capacity(rows.len(), sql).and_then(|_| {
+                            crate::query_cursor::read_row(stmt, column_count, self.read_options)
+                                .map_err(|e| e.with_sql(sql))
+                        });
+                        match row {
+                            Ok(row) => rows.push(row),
+                            Err(e) => break Err(e),
+                        }
+                    }
+                    sqlite_wasm_rs::SQLITE_DONE => break Ok(()),
+                    code => break Err(error(db, code)),
+                }
+            };
+            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+            outcome.map(|_| QueryResult {
+                columns,
+                rows,
+                affected_rows: 0,
+                last_insert_id: None,
+                execution_time_ms: js_sys::Date::now() - start_time,
+            })
+        })();
+        unsafe { sqlite_wasm_rs::sqlite3_close(db) };
+        result
+    }
+
+    /// Discard every in-memory copy of this database and read it back from IndexedDB
+    ///
+    /// The connection stays open. Unsynced writes are lost.
+    async fn reload_from_storage_internal(&mut self) -> Result<StorageReloadReport, DatabaseError> {
+        let storage =
+            crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name).ok_or_else(|| {
+                DatabaseError::new(
+                    "STORAGE_NOT_FOUND",
+                    &format!("No storage found for database: {}", self.name),
+                )
+            })?;
+        self.prepare_for_storage_swap()?;
+        let report = storage.reload_from_indexeddb().await?;
+        self.forget_cached_file(report.file_size_bytes)?;
+        log::info!(
+            "[RELOAD] Reloaded {} blocks of {} from IndexedDB ({} corrupt)",
+            report.blocks_loaded,
+            self.name,
+            report.corrupt_blocks.len()
+        );
+        Ok(report)
+    }
+
+    /// Swap an imported file into this database's storage under the open connection
+    ///
+    /// Nothing is touched unless the header and block size validate.
+    async fn import_in_place_internal(&mut self, data: &[u8]) -> Result<(), DatabaseError> {
+        crate::storage::export::validate_sqlite_file(data)?;
+        crate::storage::block_storage::validate_block_size(self.block_size)?;
+        self.prepare_for_storage_swap()?;
+        let blocks =
+            crate::storage::import::import_database_in_place(&self.name, data, self.block_size)
+                .await?;
+        if let Some(storage) = crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name) {
+            storage.on_database_import().await?;
+        }
+        self.forget_cached_file((blocks * self.block_size) as u64)?;
+        log::info!(
+            "[IMPORT] Imported {} bytes into {} in place",
+            data.len(),
+            self.name
+        );
+        Ok(())
+    }
+
+    /// Flush SQLite's WAL into VFS blocks and sync them so exports see every write
+    ///
+    /// Callers hold the database's exclusive lock so only one export syncs at a time.
+    async fn checkpoint_and_sync(
+        &self,
+    ) -> Result<Rc<crate::storage::block_storage::BlockStorage>, JsValue> {
+        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+
+        let db_name = &self.name;
+        log::info!("[EXPORT] ===== Step 3: Getting storage");
+        let storage_rc = get_storage_with_fallback(db_name).ok_or_else(|| {
+            JsValue::from_str(&format!("Storage not found for database: {}", db_name))
+        })?;
+        log::info!("[EXPORT] ===== Step 4: Got storage, reloading cache");
+
+        // Reload cache from GLOBAL_STORAGE
+        #[cfg(target_arch = "wasm32")]
+        {
+            storage_rc.reload_cache_from_global_storage();
+        }
+
+        // CRITICAL: Checkpoint WAL to flush SQLite data to VFS blocks before export
+        // Without this, data stays in SQLite's WAL buffer and doesn't appear in exported bytes
+        log::info!("[EXPORT] ===== Step 5: Checkpointing WAL");
+        if !self.connection_state.db.get().is_null() {
+            // Use raw SQLite call since exports take &self, not &mut self
+            use std::ffi::CString;
+            let pragma = CString::new("PRAGMA wal_checkpoint(PASSIVE)").unwrap();
+            unsafe {
+                let mut stmt = std::ptr::null_mut();
+                let rc = sqlite_wasm_rs::sqlite3_prepare_v2(
+                    self.connection_state.db.get(),
+                    pragma.as_ptr(),
+                    -1,
+                    &mut stmt,
+                    std::ptr::null_mut(),
+                );
+                if rc == sqlite_wasm_rs::SQLITE_OK && !stmt.is_null() {
+                    sqlite_wasm_rs::sqlite3_step(stmt);
+                    sqlite_wasm_rs::sqlite3_finalize(stmt);
+                    log::info!("[EXPORT] WAL checkpoint completed");
+                } else {
+                    log::warn!("[EXPORT] WAL checkpoint failed with rc: {}", rc);
+                }
+            }
+        }
+
+        log::info!("[EXPORT] ===== Step 6: Starting sync");
+        // Sync to ensure all data is persisted before export
+        storage_rc
+            .sync()
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Sync failed: {}", e)))?;
+        log::info!("[EXPORT] ===== Step 7: Sync complete");
+
+        Ok(storage_rc)
+    }
+
+    /// Reopen the SQLite connection after this instance's storage was replaced
+    ///
+    /// The VFS registered when the Database was first created is reused.
+    fn reopen_connection(&mut self) -> Result<(), JsValue> {
+        use std::ffi::CString;
+
+        let (db_name, vfs_name) = Self::open_target(&self.name, self.persistence);
+        let pool_key = self.pool_key();
+        let flags = Self::open_flags(self.read_only);
+        let db_name_for_closure = db_name.clone();
+        let vfs_name_for_closure = vfs_name.clone();
+
+        let new_state = crate::connection_pool::get_or_create_connection(&pool_key, || {
+            let mut db = std::ptr::null_mut();
+            let db_name_cstr = CString::new(db_name_for_closure.clone())
+                .map_err(|_| "Invalid database name".to_string())?;
+            let vfs_cstr = CString::new(vfs_name_for_closure.as_str())
+                .map_err(|_| "Invalid VFS name".to_string())?;
+
+            log::info!(
+                "[REOPEN] Reopening database: {} with VFS: {}",
+                db_name_for_closure,
+                vfs_name_for_closure
+            );
+
+            let ret = unsafe {
+                sqlite_wasm_rs::sqlite3_open_v2(
+                    db_name_cstr.as_ptr(),
+                    &mut db as *mut _,
+                    flags,
+                    vfs_cstr.as_ptr(),
+                )
+            };
+
+            if ret != sqlite_wasm_rs::SQLITE_OK {
+                let err_msg = unsafe {
+                    let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(db);
+                    if !msg_ptr.is_null() {
+                        std::ffi::CStr::from_ptr(msg_ptr)
+                            .to_string_lossy()
+                            .into_owned()
+                    } else {
+                        "Unknown error".to_string()
+                    }
+                };
+                return Err(format!(
+                    "Failed to reopen database after storage change: {}",
+                    err_msg
+                ));
+            }
+
+            log::info!("[REOPEN] Database reopened successfully");
+            Ok(db)
+        })
+        .map_err(|e| JsValue::from_str(&format!("Failed to reopen connection: {}", e)))?;
+
+        // Update our connection state to use the new connection
+        self.connection_state = new_state;
+        self.extensions
+            .install(self.db())
+            .map_err(|e| JsValue::from_str(&format!("Failed to reinstall extensions: {}", e)))?;
+        if !self.subscriptions.borrow().is_empty() {
+            unsafe { crate::storage::subscriptions::install_update_hook(self.db()) };
+        }
+        if let Some(callback) = &self.busy_handler {
+            crate::busy_handler::install(self.db(), Some(callback)).map_err(|e| {
+                JsValue::from_str(&format!("Failed to reinstall busy handler: {}", e))
+            })?;
+        }
+        if let Some(callback) = &self.authorizer {
+            crate::authorizer::install(self.db(), Some(callback)).map_err(|e| {
+                JsValue::from_str(&format!("Failed to reinstall authorizer: {}", e))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Close this instance's connection and drop it from the pool before storage is replaced
+    async fn detach_connection(&mut self, context: &str) -> Result<(), JsValue> {
+        // First do normal close to cleanup leader election etc
+        self.close_internal().await.map_err(|e| {
+            JsValue::from_str(&format!("Failed to close before {}: {}", context, e))
+        })?;
+
+        // Then force-remove from connection pool so new instances don't reuse a stale connection
+        crate::connection_pool::force_close_connection(&self.pool_key());
+
+        // Mark our connection as null since we force-closed it
+        self.connection_state.db.set(std::ptr::null_mut());
+        Ok(())
+    }
+
+    /// Round-trip a ping through BroadcastChannel (see `testNotificationChannel`)
+    pub async fn test_notification_channel_internal(
+        &self,
+        timeout_ms: u32,
+    ) -> NotificationChannelHealth {
+        match crate::storage::broadcast_notifications::ping_notification_channel(
+            &self.name, timeout_ms,
+        )
+        .await
+        {
+            Ok(latency_ms) => {
+                self.coordination_metrics_manager
+                    .borrow_mut()
+                    .record_notification_latency(latency_ms);
+                NotificationChannelHealth {
+                    healthy: true,
+                    latency_ms: Some(latency_ms),
+                    error: None,
+                }
+            }
+            Err(e) => NotificationChannelHealth {
+                healthy: false,
+                latency_ms: None,
+                error: Some(e.message),
+            },
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for Database {
+    fn drop(&mut self) {
+        web_sys::console::log_1(&format!("DROP: Releasing connection for {}", self.name).into());
+
+        // Finalize cached statements before the connection can be closed
+        self.finalize_statement_cache();
+        self.finalize_sessions();
+
+        // Release the connection back to the pool
+        // The pool will close it if this was the last reference
+        // Pool uses name without .db, so strip it
+        let pool_key = self.pool_key();
+        crate::connection_pool::release_connection(&pool_key);
+
+        web_sys::console::log_1(&format!("DROP: Connection released for {}", self.name).into());
+
+        // CRITICAL: Stop heartbeat interval synchronously to prevent leaks
+        // Memory databases never started one, so leave a same-named persistent one alone
+        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+        let storage_rc = get_storage_with_fallback(&self.name).filter(|_| !self.is_memory());
+        if let Some(storage_rc) = storage_rc {
+            // No outer borrow needed - BlockStorage uses RefCell for interior mutability
+            let storage = &*storage_rc;
+            // Try to borrow manager - if it fails, skip (already being cleaned)
+            if let Ok(mut manager_ref) = storage.leader_election.try_borrow_mut() {
+                if let Some(ref mut manager) = *manager_ref {
+                    // Clear interval if it exists (idempotent)
+                    if let Some(interval_id) = manager.heartbeat_interval.take() {
+                        if let Some(window) = web_sys::window() {
+                            window.clear_interval_with_handle(interval_id);
+                            web_sys::console::log_1(
+                                &format!(
+                                    "DROP: Cleared heartbeat interval {} for {}",
+                                    interval_id, self.name
+                                )
+                                .into(),
+                            );
+                        }
+                    }
+                    // Note: heartbeat closure is intentionally leaked (via forget())
+                    // and becomes a no-op when heartbeat_valid is set to false
+                }
+            } else {
+                // Manager already borrowed - skip (first DB is cleaning up)
+                web_sys::console::log_1(
+                    &format!(
+                        "[DROP] Skipping {} (heartbeat already stopped by shared DB)",
+                        self.name
+                    )
+                    .into(),
+                );
+            }
+        }
+
+        // Keep BlockStorage in STORAGE_REGISTRY so multiple Database instances
+        // with the same name share the same BlockStorage and leader election state
+        // Blocks persist in GLOBAL_STORAGE across Database instances
+        log::debug!(
+            "Closed database: {} (BlockStorage remains in registry)",
+            self.name
+        );
+    }
+}
+
+// Add wasm_bindgen exports for the main Database struct
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+impl Database {
+    #[wasm_bindgen(js_name = "newDatabase")]
+    pub async fn new_wasm(name: String) -> Result<Database, JsValue> {
+        let normalized_name = normalize_db_name(&name);
+        let config = Self::browser_config(&normalized_name);
+
+        let db = Database::new(config)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to create database: {}", e)))?;
+
+        // Start listening for write queue requests (leader will process them)
+        if db.multi_tab {
+            Self::start_write_queue_listener(&normalized_name)?;
+        }
+
+        Ok(db)
+    }
+
+    /// Open a database with a configuration from `DatabaseConfigBuilder`
+    ///
+    /// The configuration is validated again before anything is opened, so a hand-built
+    /// object with bad settings fails with `CONFIG_INVALID` too.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const config = new DatabaseConfigBuilder('app').cacheSize(20000).build();
+    /// const db = await Database.openWithConfig(config);
+    /// ```
+    #[wasm_bindgen(js_name = "openWithConfig")]
+    pub async fn open_with_config(mut config: DatabaseConfig) -> Result<Database, JsValue> {
+        config
+            .validate()
+            .map_err(|e| JsValue::from_str(&format!("{}: {}", e.code, e.message)))?;
+        let normalized_name = normalize_db_name(&config.name);
+        config.name = normalized_name.clone();
+
+        let db = Database::new(config)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to create database: {}", e)))?;
+
+        if db.multi_tab && !db.is_memory() {
+            Self::start_write_queue_listener(&normalized_name)?;
+        }
+
+        Ok(db)
+    }
+
+    /// Open a scratch database that lives only in memory
+    ///
+    /// Nothing is written to IndexedDB: there is no leader election, `sync()` is a
+    /// no-op and the contents are gone once every instance with this name is closed.
+    /// `exportToFile()` returns the in-memory bytes.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const scratch = await Database.openInMemory('scratch');
+    /// await scratch.importFromFile(bytes);
+    /// const totals = await scratch.execute('SELECT region, SUM(amount) FROM sales GROUP BY region');
+    /// ```
+    #[wasm_bindgen(js_name = "openInMemory")]
+    pub async fn open_in_memory(name: String) -> Result<Database, JsValue> {
+        let normalized_name = normalize_db_name(&name);
+        let config = Self::browser_config(&normalized_name);
+
+        Database::new_in_memory(config)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to open database: {}", e)))
+    }
+
+    /// Open a database with custom functions and collations registered up front
+    ///
+    /// Everything in `extensions` is validated before the database is opened and
+    /// installed on the connection before it is handed back, so no query can run
+    /// without them. Every key is optional; see `createScalarFunction` and
+    /// `createAggregateFunction` for how values are passed to callbacks. Collation
+    /// comparators receive two strings and return a negative number, zero or a
+    /// positive number.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const db = await Database.openWithExtensions('app', {
+    ///   functions: [{ name: 'slugify', arity: 1, fn: (s) => s.toLowerCase(), deterministic: true }],
+    ///   aggregates: [{ name: 'product', arity: 1, step: (acc, x) => (acc ?? 1) * x, final: (acc) => acc ?? null }],
+    ///   collations: [{ name: 'natural', compare: (a, b) => a.localeCompare(b, undefined, { numeric: true }) }],
+    /// });
+    /// await db.execute('SELECT name FROM files ORDER BY name COLLATE natural');
+    /// ```
+    #[wasm_bindgen(js_name = "openWithExtensions")]
+    pub async fn open_with_extensions(
+        name: String,
+        extensions: JsValue,
+    ) -> Result<Database, JsValue> {
+        let extensions = crate::functions::Extensions::from_js(&extensions)
+            .map_err(|e| JsValue::from_str(&format!("Invalid extensions: {}", e)))?;
+        let normalized_name = normalize_db_name(&name);
+        let config = Self::browser_config(&normalized_name);
+
+        let db = Database::new_with_extensions(config, extensions)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to create database: {}", e)))?;
+
+        if db.multi_tab {
+            Self::start_write_queue_listener(&normalized_name)?;
+        }
+
+        Ok(db)
+    }
+
+    /// Open an existing database for reading only
+    ///
+    /// Skips leader election and the write-queue listener, so follower tabs can run
+    /// reports cheaply. Any write statement fails with `READ_ONLY_DB`.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const reports = await Database.openReadOnly('app');
+    /// const result = await reports.execute('SELECT COUNT(*) FROM orders');
+    /// ```
+    #[wasm_bindgen(js_name = "openReadOnly")]
+    pub async fn open_read_only(name: String) -> Result<Database, JsValue> {
+        let config = DatabaseConfig {
+            name: normalize_db_name(&name),
+            ..DatabaseConfig::default()
+        };
+
+        Database::new_read_only(config)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to open database: {}", e)))
+    }
+
+    /// Check whether this instance was opened with `openReadOnly`
+    #[wasm_bindgen(js_name = "isReadOnly")]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Get the database name
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Get all database names stored in IndexedDB
+    ///
+    /// Returns an array of database names (sorted alphabetically)
+    #[wasm_bindgen(js_name = "getAllDatabases")]
+    pub async fn get_all_databases() -> Result<JsValue, JsValue> {
+        use crate::storage::vfs_sync::with_global_storage;
+        use crate::vfs::indexeddb_vfs::STORAGE_REGISTRY;
+        use std::collections::HashSet;
+
+        log::info!("getAllDatabases called");
+        let mut db_names = HashSet::new();
+
+        // Get databases from persistent list (localStorage)
+        match Self::get_persistent_database_list() {
+            Ok(persistent_list) => {
+                log::info!("Persistent list has {} entries", persistent_list.len());
+                for name in persistent_list {
+                    log::info!("Found in persistent list: {}", name);
+                    db_names.insert(name);
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to get persistent list: {:?}", e);
+            }
+        }
+
+        // Get databases from STORAGE_REGISTRY (currently open)
+        // SAFETY: WASM is single-threaded, no concurrent access possible
+        STORAGE_REGISTRY.with(|reg| unsafe {
+            let registry = &*reg.get();
+            log::info!("STORAGE_REGISTRY has {} entries", registry.len());
+            for key in registry.keys() {
+                log::info!("Found in STORAGE_REGISTRY: {}", key);
+                db_names.insert(key.clone());
+            }
+        });
+
+        // Get databases from GLOBAL_STORAGE (in-memory persistent storage)
+        with_global_storage(|storage| {
+            let storage_borrow = storage.borrow();
+            log::info!("GLOBAL_STORAGE has {} entries", storage_borrow.len());
+            for key in storage_borrow.keys() {
+                log::info!("Found in GLOBAL_STORAGE: {}", key);
+                db_names.insert(key.clone());
+            }
+        });
+
+        log::info!("Total unique databases found: {}", db_names.len());
+
+        // Convert to sorted vector
+        let mut result_vec: Vec<String> = db_names.into_iter().collect();
+        result_vec.sort();
+
+        // Convert to JavaScript array
+        let js_array = js_sys::Array::new();
+        for name in &result_vec {
+            log::info!("Returning database: {}", name);
+            js_array.push(&JsValue::from_str(name));
+        }
+
+        log::info!("getAllDatabases returning {} databases", result_vec.len());
+
+        Ok(js_array.into())
+    }
+
+    /// List the databases persisted in IndexedDB
+    ///
+    /// Returns `[{ name, sizeBytes, blockCount, lastModified }]` sorted by name, read
+    /// from IndexedDB itself rather than from names remembered in this session, so it
+    /// works after a reload. Databases that have never synced are not listed.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const databases = await Database.listDatabases();
+    /// for (const { name, sizeBytes, lastModified } of databases) {
+    ///   console.log(name, sizeBytes, lastModified && new Date(lastModified));
+    /// }
+    /// ```
+    #[wasm_bindgen(js_name = "listDatabases")]
+    pub async fn list_databases() -> Result<JsValue, JsValue> {
+        let databases = crate::storage::wasm_indexeddb::list_persisted_databases()
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to list databases: {}", e)))?;
+        serde_wasm_bindgen::to_value(&databases).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Delete a database and everything persisted for it
+    ///
+    /// Stops leader election, drops the database from every in-memory registry
+    /// (storage, blocks, metadata, allocation map and commit marker) and deletes its
+    /// blocks and metadata from IndexedDB. Resolves only once IndexedDB has finished
+    /// deleting, so it suits "log out and clear data" flows and test cleanup. Close
+    /// open instances of the database first.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await db.close();
+    /// await Database.deleteDatabase('user_data.db');
+    /// ```
+    #[wasm_bindgen(js_name = "deleteDatabase")]
+    pub async fn delete_database(name: String) -> Result<(), JsValue> {
+        use crate::storage::vfs_sync::{
+            with_global_allocation_map, with_global_commit_marker, with_global_metadata,
+            with_global_storage,
+        };
+
+        // Normalize database name
+        let normalized_name = if name.ends_with(".db") {
+            name.clone()
+        } else {
+            format!("{}.db", name)
+        };
+
+        log::info!("Deleting database: {}", normalized_name);
+
+        // Stop leader election before the storage goes away, so no heartbeat outlives it
+        use crate::vfs::indexeddb_vfs::{get_storage_with_fallback, remove_storage_from_registry};
+        if let Some(storage) = get_storage_with_fallback(&normalized_name) {
+            if let Err(e) = storage.stop_leader_election().await {
+                log::warn!(
+                    "Failed to stop leader election for {}: {}",
+                    normalized_name,
+                    e
+                );
+            }
+        }
+
+        // Remove from STORAGE_REGISTRY
+        remove_storage_from_registry(&normalized_name);
+
+        // Remove from GLOBAL_STORAGE
+        with_global_storage(|gs| {
+            gs.borrow_mut().remove(&normalized_name);
+        });
+
+        // Remove from GLOBAL_METADATA
+        with_global_metadata(|gm| {
+            gm.borrow_mut().remove(&normalized_name);
+        });
+
+        // Remove from the allocation map
+        with_global_allocation_map(|gam| {
+            gam.borrow_mut().remove(&normalized_name);
+        });
+
+        // Remove from commit markers
+        with_global_commit_marker(|cm| {
+            log::info!(
+                "Cleared commit marker for {} from GLOBAL storage",
+                normalized_name
+            );
+            cm.borrow_mut().remove(&normalized_name);
+        });
+
+        // Delete blocks, metadata and the commit marker from the shared IndexedDB store
+        crate::storage::wasm_indexeddb::delete_all_database_blocks_from_indexeddb(&normalized_name)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to delete IndexedDB data: {}", e)))?;
+
+        // Delete the per-database IndexedDB used by older versions (typed web-sys API,
+        // no eval, so it works under MV3's strict CSP where 'unsafe-eval' is forbidden)
+        crate::storage::wasm_indexeddb::delete_indexeddb_database(&format!(
+            "absurder_{}",
+            normalized_name
+        ))
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Failed to delete IndexedDB: {}", e)))?;
+
+        log::info!("Database deleted: {}", normalized_name);
+
+        // Remove from persistent list
+        Self::remove_database_from_persistent_list(&normalized_name)?;
+
+        Ok(())
+    }
+
+    /// Add database name to persistent list in localStorage
+    #[allow(dead_code)]
+    fn add_database_to_persistent_list(db_name: &str) -> Result<(), JsValue> {
+        log::info!("add_database_to_persistent_list called for: {}", db_name);
+
+        let window = web_sys::window().ok_or_else(|| {
+            log::error!("No window object");
+            JsValue::from_str("No window")
+        })?;
+
+        let storage = window
+            .local_storage()
+            .map_err(|e| {
+                log::error!("Failed to get localStorage: {:?}", e);
+                JsValue::from_str("No localStorage")
+            })?
+            .ok_or_else(|| {
+                log::error!("localStorage not available");
+                JsValue::from_str("localStorage not available")
+            })?;
+
+        let key = "absurder_db_list";
+        let existing = storage.get_item(key).map_err(|e| {
+            log::error!("Failed to read localStorage key {}: {:?}", key, e);
+            JsValue::from_str("Failed to read localStorage")
+        })?;
+
+        log::debug!("Existing localStorage value: {:?}", existing);
+
+        let mut db_list: Vec<String> = if let Some(json_str) = existing {
+            match serde_json::from_str(&json_str) {
+                Ok(list) => {
+                    log::debug!("Parsed existing list: {:?}", list);
+                    list
+                }
+                Err(e) => {
+                    log::warn!("Failed to parse localStorage JSON: {}, starting fresh", e);
+                    Vec::new()
+                }
+            }
+        } else {
+            log::debug!("No existing list, creating new");
+            Vec::new()
+        };
+
+        if !db_list.contains(&db_name.to_string()) {
+            db_list.push(db_name.to_string());
+            db_list.sort();
+            log::debug!("Updated list: {:?}", db_list);
+
+            let json_str = serde_json::to_string(&db_list).map_err(|e| {
+                log::error!("Failed to serialize list: {}", e);
+                JsValue::from_str("Failed to serialize")
+            })?;
+
+            log::debug!("Writing to localStorage: {}", json_str);
+
+            storage.set_item(key, &json_str).map_err(|e| {
+                log::error!("Failed to write to localStorage: {:?}", e);
+                JsValue::from_str("Failed to write localStorage")
+            })?;
+
+            log::info!("Successfully added {} to persistent database list", db_name);
+        } else {
+            log::info!("{} already in persistent list", db_name);
+        }
+
+        Ok(())
+    }
+
+    /// Remove database name from persistent list in localStorage
+    fn remove_database_from_persistent_list(db_name: &str) -> Result<(), JsValue> {
+        let window = web_sys::window().ok_or_else(|| JsValue::from_str("No window"))?;
+        let storage = window
+            .local_storage()
+            .map_err(|_| JsValue::from_str("No localStorage"))?
+            .ok_or_else(|| JsValue::from_str("localStorage not available"))?;
+
+        let key = "absurder_db_list";
+        let existing = storage
+            .get_item(key)
+            .map_err(|_| JsValue::from_str("Failed to read localStorage"))?;
+
+        if let Some(json_str) = existing {
+            let mut db_list: Vec<String> =
+                serde_json::from_str(&json_str).unwrap_or_else(|_| Vec::new());
+            db_list.retain(|name| name != db_name);
+            let json_str = serde_json::to_string(&db_list)
+                .map_err(|_| JsValue::from_str("Failed to serialize"))?;
+            storage
+                .set_item(key, &json_str)
+                .map_err(|_| JsValue::from_str("Failed to write localStorage"))?;
+            log::info!("Removed {} from persistent database list", db_name);
+        }
+
+        Ok(())
+    }
+
+    /// Get database names from persistent list in localStorage
+    fn get_persistent_database_list() -> Result<Vec<String>, JsValue> {
+        log::info!("get_persistent_database_list called");
+
+        let window = web_sys::window().ok_or_else(|| {
+            log::error!("No window object");
+            JsValue::from_str("No window")
+        })?;
+
+        let storage = window
+            .local_storage()
+            .map_err(|e| {
+                log::error!("Failed to get localStorage: {:?}", e);
+                JsValue::from_str("No localStorage")
+            })?
+            .ok_or_else(|| {
+                log::error!("localStorage not available");
+                JsValue::from_str("localStorage not available")
+            })?;
+
+        let key = "absurder_db_list";
+        let existing = storage.get_item(key).map_err(|e| {
+            log::error!("Failed to read localStorage key {}: {:?}", key, e);
+            JsValue::from_str("Failed to read localStorage")
+        })?;
+
+        log::debug!("Read from localStorage: {:?}", existing);
+
+        if let Some(json_str) = existing {
+            match serde_json::from_str::<Vec<String>>(&json_str) {
+                Ok(db_list) => {
+                    log::info!(
+                        "Successfully parsed {} databases from localStorage",
+                        db_list.len()
+                    );
+                    log::debug!("Database list: {:?}", db_list);
+                    Ok(db_list)
+                }
+                Err(e) => {
+                    log::error!("Failed to parse localStorage JSON: {}", e);
+                    Ok(Vec::new())
+                }
+            }
+        } else {
+            log::info!("No persistent database list in localStorage");
+            Ok(Vec::new())
+        }
+    }
+
+    /// Start listening for write queue requests (leader processes these)
+    fn start_write_queue_listener(db_name: &str) -> Result<(), JsValue> {
+        use crate::storage::write_queue::{
+            WriteQueueMessage, WriteResponse, register_write_queue_listener,
+        };
+        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+
+        let db_name_clone = db_name.to_string();
+
+        let callback = Closure::wrap(Box::new(move |msg: JsValue| {
+            let db_name_inner = db_name_clone.clone();
+
+            // Parse the message
+            if let Ok(json_str) = js_sys::JSON::stringify(&msg) {
+                if let Some(json_str) = json_str.as_string() {
+                    if let Ok(message) = serde_json::from_str::<WriteQueueMessage>(&json_str) {
+                        if let WriteQueueMessage::WriteRequest(request) = message {
+                            log::debug!("Leader received write request: {}", request.request_id);
+
+                            // Check if we're the leader
+                            let storage_rc = get_storage_with_fallback(&db_name_inner);
+
+                            if let Some(storage) = storage_rc {
+                                // Spawn async task to process the write
+                                wasm_bindgen_futures::spawn_local(async move {
+                                    let is_leader = with_storage_async!(
+                                        storage,
+                                        "write_queue_is_leader",
+                                        |s| s.is_leader()
+                                    );
+                                    if is_leader.is_none() {
+                                        log::error!("Failed to check leader status");
+                                        return;
+                                    }
+                                    let is_leader = is_leader.unwrap();
+
+                                    if !is_leader {
+                                        log::error!("Not leader, ignoring write request");
+                                        return;
+                                    }
+
+                                    log::debug!("Processing write request as leader");
+
+                                    // Create a temporary database instance to execute the SQL
+                                    match Database::new_wasm(db_name_inner.clone()).await {
+                                        Ok(mut db) => {
+                                            // Execute the SQL
+                                            match db.execute_internal(&request.sql).await {
+                                                Ok(result) => {
+                                                    // Send success response
+                                                    let response = WriteResponse::Success {
+                                                        request_id: request.request_id.clone(),
+                                                        affected_rows: result.affected_rows
+                                                            as usize,
+                                                    };
+
+                                                    use crate::storage::write_queue::send_write_response;
+                                                    if let Err(e) = send_write_response(
+                                                        &db_name_inner,
+                                                        response,
+                                                    ) {
+                                                        log::error!(
+                                                            "Failed to send response: {}",
+                                                            e
+                                                        );
+                                                    } else {
+                                                        log::info!(
+                                                            "Write response sent successfully"
+                                                        );
+                                                    }
+                                                }
+                                                Err(e) => {
+                                                    // Send error response
+                                                    let response = WriteResponse::Error {
+                                                        request_id: request.request_id.clone(),
+                                                        error_message: e.to_string(),
+                                                    };
+
+                                                    use crate::storage::write_queue::send_write_response;
+                                                    if let Err(e) = send_write_response(
+                                                        &db_name_inner,
+                                                        response,
+                                                    ) {
+                                                        log::error!(
+                                                            "Failed to send error response: {}",
+                                                            e
+                                                        );
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        Err(e) => {
+                                            log::error!(
+                                                "Failed to create db for write processing: {:?}",
+                                                e
+                                            );
+                                        }
+                                    }
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }) as Box<dyn FnMut(JsValue)>);
+
+        let callback_fn = callback.as_ref().unchecked_ref();
+        register_write_queue_listener(db_name, callback_fn).map_err(|e| {
+            JsValue::from_str(&format!("Failed to register write queue listener: {}", e))
+        })?;
+
+        callback.forget();
+
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub async fn execute(&mut self, sql: &str) -> Result<JsValue, JsValue> {
+        // Check write permission before executing
+        self.check_write_permission(sql)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Write permission denied: {}", e)))?;
+
+        let result = self
+            .execute_internal(sql)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Query execution failed: {}", e)))?;
+        crate::result_format::tagged_result_to_js(&result)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = "executeWithParams")]
+    pub async fn execute_with_params(
+        &mut self,
+        sql: &str,
+        params: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let params: Vec<ColumnValue> = crate::result_format::params_from_js(params)
+            .map_err(|e| JsValue::from_str(&format!("Invalid parameters: {}", e)))?;
+
+        // Check write permission before executing
+        self.check_write_permission(sql)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Write permission denied: {}", e)))?;
+
+        let result = self
+            .execute_with_params_internal(sql, &params)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Query execution failed: {}", e)))?;
+        crate::result_format::tagged_result_to_js(&result)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Execute a statement with its own time budget
+    ///
+    /// Overrides `query_timeout_ms` for this call only; a statement still running after
+    /// `timeoutMs` is aborted with `QUERY_TIMEOUT`. `params` may be omitted for
+    /// statements without placeholders.
+    ///
+    /// # Example
+    /// ```javascript
+    /// try {
+    ///   await db.executeWithTimeout('SELECT * FROM events WHERE payload LIKE ?', [{ type: 'Text', value: '%x%' }], 250);
+    /// } catch (e) {
+    ///   // "Query execution failed: Database error: Query exceeded query_timeout_ms of 250 ms"
+    /// }
+    /// ```
+    #[wasm_bindgen(js_name = "executeWithTimeout")]
+    pub async fn execute_with_timeout(
+        &mut self,
+        sql: &str,
+        params: JsValue,
+        timeout_ms: u32,
+    ) -> Result<JsValue, JsValue> {
+        let params: Vec<ColumnValue> = if params.is_undefined() || params.is_null() {
+            Vec::new()
+        } else {
+            crate::result_format::params_from_js(params)
+                .map_err(|e| JsValue::from_str(&format!("Invalid parameters: {}", e)))?
+        };
+
+        // Check write permission before executing
+        self.check_write_permission(sql)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Write permission denied: {}", e)))?;
+
+        let result = self
+            .execute_with_timeout_internal(sql, &params, timeout_ms)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Query execution failed: {}", e)))?;
+        crate::result_format::tagged_result_to_js(&result)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Insert many rows with one prepared statement in one transaction
+    ///
+    /// `rows` is an array of parameter arrays, one per row, in the `executeWithParams`
+    /// format. Much faster than calling `executeWithParams` in a loop: the statement is
+    /// compiled once and rows never cross back into JS. Fails with `INVALID_BULK_INSERT`
+    /// unless `sql` is a single INSERT, or `PARAM_COUNT_MISMATCH` when a row has the
+    /// wrong number of values; a failing row rolls back the whole call.
+    ///
+    /// With `options.continueOnError`, failing rows are skipped instead and the call
+    /// resolves to a report with each row's outcome (`ok`, `errorCode`, `error`).
+    ///
+    /// # Example
+    /// ```javascript
+    /// const rows = events.map((e) => [
+    ///   { type: 'Integer', value: e.id },
+    ///   { type: 'Text', value: e.name },
+    /// ]);
+    /// const { affectedRows, lastInsertId } = await db.insertMany(
+    ///   'INSERT INTO events (id, name) VALUES (?, ?)',
+    ///   rows,
+    /// );
+    ///
+    /// const report = await db.insertMany(sql, rows, { continueOnError: true });
+    /// report.results.filter((r) => !r.ok).forEach((r) => log(r.index, r.error));
+    /// ```
+    #[wasm_bindgen(js_name = "insertMany")]
+    pub async fn insert_many(
+        &mut self,
+        sql: &str,
+        rows: JsValue,
+        options: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let rows: Vec<Vec<ColumnValue>> = serde_wasm_bindgen::from_value(rows)
+            .map_err(|e| JsValue::from_str(&format!("Invalid rows: {}", e)))?;
+        let options: BatchOptions = if options.is_undefined() || options.is_null() {
+            BatchOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|e| JsValue::from_str(&format!("Invalid batch options: {}", e)))?
+        };
+
+        // Check write permission before executing
+        self.check_write_permission(sql)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Write permission denied: {}", e)))?;
+
+        if options.continue_on_error {
+            let report = self
+                .insert_many_best_effort_internal(sql, &rows)
+                .await
+                .map_err(|e| JsValue::from_str(&format!("Bulk insert failed: {}", e)))?;
+            return serde_wasm_bindgen::to_value(&report)
+                .map_err(|e| JsValue::from_str(&e.to_string()));
+        }
+
+        let result = self
+            .insert_many_internal(sql, &rows)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Bulk insert failed: {}", e)))?;
+        crate::result_format::to_js(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Copy every row of a table from another open database into this one
+    ///
+    /// Rows are streamed from `source` in batches and inserted into the table of the same
+    /// name here, matching columns by name, inside one transaction: a failure rolls back
+    /// every copied row. `mode` is `INSERT` (the default; duplicate keys fail the copy),
+    /// `IGNORE` (keep existing rows) or `REPLACE` (overwrite them). Returns the number of
+    /// rows written.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const backup = await Database.newDatabase('backup-device-b');
+    /// const copied = await db.copyTable(backup, 'notes', 'IGNORE');
+    /// ```
+    #[wasm_bindgen(js_name = "copyTable")]
+    pub async fn copy_table(
+        &mut self,
+        source: &mut Database,
+        table: &str,
+        mode: Option<String>,
+    ) -> Result<u32, JsValue> {
+        let mode = match mode {
+            Some(mode) => CopyMode::from_name(&mode)
+                .map_err(|e| JsValue::from_str(&format!("Copy table failed: {}", e)))?,
+            None => CopyMode::default(),
+        };
+        self.copy_table_internal(source, table, mode)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Copy table failed: {}", e)))
+    }
+
+    /// Cancel the statement currently running on this connection
+    ///
+    /// The statement fails with `QUERY_INTERRUPTED`. Does nothing when no statement is
+    /// running. The connection is shared by every instance open on this database, so
+    /// their statements are interrupted too.
+    #[wasm_bindgen]
+    pub fn interrupt(&self) {
+        let db = self.db();
+        if !db.is_null() {
+            unsafe { sqlite_wasm_rs::sqlite3_interrupt(db) };
+        }
+    }
+
+    /// Execute a statement and return its rows in the requested shape
+    ///
+    /// `format` is `"TaggedEnum"` (the `execute` shape, `{ type, value }` per cell),
+    /// `"ArrayOfArrays"` (one array per row) or `"ArrayOfObjects"` (one object per row
+    /// keyed by column name). When omitted the database's `result_format` is used.
+    /// `params` may be omitted for statements without placeholders.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const { columns, rows } = await db.executeFormatted(
+    ///   'SELECT id, name FROM users WHERE team = ?',
+    ///   [{ type: 'Text', value: 'ops' }],
+    ///   'ArrayOfObjects',
+    /// );
+    /// // rows: [{ id: 1, name: 'Ada' }, ...]
+    /// ```
+    #[wasm_bindgen(js_name = "executeFormatted")]
+    pub async fn execute_formatted(
+        &mut self,
+        sql: &str,
+        params: JsValue,
+        format: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let params: Vec<ColumnValue> = if params.is_undefined() || params.is_null() {
+            Vec::new()
+        } else {
+            crate::result_format::params_from_js(params)
+                .map_err(|e| JsValue::from_str(&format!("Invalid parameters: {}", e)))?
+        };
+        let format: ResultFormat = if format.is_undefined() || format.is_null() {
+            self.result_format
+        } else {
+            serde_wasm_bindgen::from_value(format)
+                .map_err(|e| JsValue::from_str(&format!("Invalid result format: {}", e)))?
+        };
+
+        // Check write permission before executing
+        self.check_write_permission(sql)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Write permission denied: {}", e)))?;
+
+        let result = self
+            .execute_with_params_internal(sql, &params)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Query execution failed: {}", e)))?;
+        crate::result_format::query_result_to_js(&result, format)
+            .map_err(|e| JsValue::from_str(&format!("Failed to format result: {}", e)))
+    }
+
+    /// Run a query and return the first column of its first row
+    ///
+    /// Returns `{ type: 'Null' }` when there are no rows. With `strict`, a result with
+    /// more than one row or column fails with `MULTIPLE_ROWS` or `MULTIPLE_COLUMNS`.
+    /// `params` may be omitted for statements without placeholders.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const count = await db.queryScalar('SELECT count(*) FROM users');
+    /// const theme = await db.queryScalar(
+    ///   'SELECT value FROM config WHERE key = ?',
+    ///   [{ type: 'Text', value: 'theme' }],
+    ///   true,
+    /// );
+    /// ```
+    #[wasm_bindgen(js_name = "queryScalar")]
+    pub async fn query_scalar(
+        &mut self,
+        sql: &str,
+        params: JsValue,
+        strict: Option<bool>,
+    ) -> Result<JsValue, JsValue> {
+        let params: Vec<ColumnValue> = if params.is_undefined() || params.is_null() {
+            Vec::new()
+        } else {
+            crate::result_format::params_from_js(params)
+                .map_err(|e| JsValue::from_str(&format!("Invalid parameters: {}", e)))?
+        };
+
+        // Check write permission before executing
+        self.check_write_permission(sql)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Write permission denied: {}", e)))?;
+
+        let value = self
+            .query_scalar_internal(sql, &params, strict.unwrap_or(false))
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Query execution failed: {}", e)))?;
+        crate::result_format::to_js(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    #[wasm_bindgen]
+    pub async fn close(&mut self) -> Result<(), JsValue> {
+        self.close_internal()
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to close database: {}", e)))
+    }
+
+    /// Force close connection and remove from pool (for test cleanup)
+    #[wasm_bindgen(js_name = "forceCloseConnection")]
+    pub async fn force_close_connection(&mut self) -> Result<(), JsValue> {
+        // First do normal close to cleanup
+        let _ = self.close_internal().await;
+
+        // Then force-remove from connection pool
+        // Pool uses name without .db, so strip it
+        let pool_key = self.pool_key();
+        crate::connection_pool::force_close_connection(&pool_key);
+
+        // CRITICAL: Single source of truth for ALL cleanup
+        // Read-only instances only drop their own connection; shared state belongs to writers
+        #[cfg(target_arch = "wasm32")]
+        if !self.read_only {
+            crate::cleanup::cleanup_all_state(self.name.trim_end_matches(".db"))
+                .await
+                .map_err(|e| JsValue::from_str(&format!("Cleanup failed: {}", e)))?;
+        }
+        log::info!("Force closed and removed connection for: {}", self.name);
+        Ok(())
+    }
+
+    /// Persist all changes to IndexedDB
+    ///
+    /// `level` is `NORMAL` (the default), which resolves once the IndexedDB transaction
+    /// completes, or `DURABLE`, which also reads the commit marker back and rejects with
+    /// `SYNC_NOT_DURABLE` unless it advanced. Returns `{ blocksPersisted, commitMarker }`.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await db.execute("INSERT INTO payments VALUES (...)");
+    /// const { commitMarker } = await db.sync('DURABLE');
+    /// showReceipt(commitMarker);
+    /// ```
+    #[wasm_bindgen(js_name = "sync")]
+    pub async fn sync_with_level(&mut self, level: Option<String>) -> Result<JsValue, JsValue> {
+        let level = match level {
+            Some(level) => SyncLevel::from_name(&level)
+                .map_err(|e| JsValue::from_str(&format!("Failed to sync database: {}", e)))?,
+            None => SyncLevel::default(),
+        };
+        let result = self
+            .sync_with_level_internal(level)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to sync database: {}", e)))?;
+        serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Allow non-leader writes (for single-tab apps or testing)
+    #[wasm_bindgen(js_name = "allowNonLeaderWrites")]
+    pub async fn allow_non_leader_writes(&mut self, allow: bool) -> Result<(), JsValue> {
+        log::debug!("Setting allowNonLeaderWrites = {} for {}", allow, self.name);
+        self.allow_non_leader_writes = allow;
+        Ok(())
+    }
+
+    /// Export the schema without any data
+    ///
+    /// Returns `CREATE` statements for every table, index, view and trigger as one SQL
+    /// string, ready for `applySchema` on another database. Pass `tables` to export only
+    /// those tables with their indexes, triggers and the views built on them.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const ddl = await prod.exportSchema();
+    /// const test = await Database.newDatabase('test_copy');
+    /// await test.applySchema(ddl);
+    ///
+    /// const ordersOnly = await prod.exportSchema(['orders', 'order_items']);
+    /// ```
+    #[wasm_bindgen(js_name = "exportSchema")]
+    pub async fn export_schema(&mut self, tables: Option<Vec<String>>) -> Result<String, JsValue> {
+        self.export_schema_internal(&tables.unwrap_or_default())
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Schema export failed: {}", e)))
+    }
+
+    /// Run a schema script in a single transaction
+    ///
+    /// If any statement fails nothing is applied.
+    #[wasm_bindgen(js_name = "applySchema")]
+    pub async fn apply_schema(&mut self, ddl: String) -> Result<(), JsValue> {
+        self.apply_schema_internal(&ddl)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Applying schema failed: {}", e)))
+    }
+
+    /// Apply schema migrations newer than the database's `user_version`
+    ///
+    /// Each migration is `{ version, upSql }` and runs in its own transaction that also
+    /// bumps `user_version`. A failing migration is rolled back and stops the run;
+    /// earlier ones stay applied. Resolves to the versions that were applied.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const applied = await db.migrate([
+    ///   { version: 1, upSql: 'CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT)' },
+    ///   { version: 2, upSql: 'ALTER TABLE notes ADD COLUMN pinned INTEGER DEFAULT 0' },
+    /// ]);
+    /// ```
+    #[wasm_bindgen(js_name = "migrate")]
+    pub async fn migrate(&mut self, migrations: JsValue) -> Result<JsValue, JsValue> {
+        let migrations: Vec<Migration> = serde_wasm_bindgen::from_value(migrations)
+            .map_err(|e| JsValue::from_str(&format!("Invalid migrations: {}", e)))?;
+        let applied = self
+            .migrate_internal(&migrations)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Migration failed: {}", e)))?;
+        serde_wasm_bindgen::to_value(&applied).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Decide how long to keep retrying while the database is locked
+    ///
+    /// `callback(retries)` is called each time a statement finds the database locked,
+    /// with the number of retries so far; return `true` to retry or `false` to fail with
+    /// `SQLITE_BUSY`. It replaces the fixed `busy_timeout`; pass `null` to go back to it.
+    /// The callback runs synchronously inside SQLite and must not call back into `db`.
+    ///
+    /// # Example
+    /// ```javascript
+    /// db.setBusyHandler((retries) => retries < 50);
+    /// db.setBusyHandler(null);
+    /// ```
+    #[wasm_bindgen(js_name = "setBusyHandler")]
+    pub fn set_busy_handler(&mut self, callback: Option<js_sys::Function>) -> Result<(), JsValue> {
+        self.set_busy_handler_internal(callback)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set busy handler: {}", e)))
+    }
+
+    /// Restrict what statements may do with an authorizer callback
+    ///
+    /// `callback(actionCode, arg1, arg2, dbName, triggerOrView)` is called for each action
+    /// while a statement compiles; `actionCode` is SQLite's `SQLITE_*` authorizer code (e.g.
+    /// 20 for SQLITE_READ, 21 for SQLITE_SELECT) and the arguments are strings or `null`.
+    /// Return `'ALLOW'`, `'DENY'` (the statement fails with "not authorized") or `'IGNORE'`
+    /// (the column reads as NULL); anything else, or a throw, denies. Pass `null` to
+    /// remove it; a later call replaces it, and `close()` clears it. The callback runs
+    /// synchronously inside SQLite and must not call back into `db`.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const SQLITE_SELECT = 21, SQLITE_READ = 20;
+    /// db.setAuthorizer((action, table) => {
+    ///   if (action === SQLITE_SELECT) return 'ALLOW';
+    ///   if (action === SQLITE_READ && ['products', 'categories'].includes(table)) return 'ALLOW';
+    ///   return 'DENY';
+    /// });
+    /// await db.execute(userSql);
+    /// db.setAuthorizer(null);
+    /// ```
+    #[wasm_bindgen(js_name = "setAuthorizer")]
+    pub fn set_authorizer(&mut self, callback: Option<js_sys::Function>) -> Result<(), JsValue> {
+        self.set_authorizer_internal(callback)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set authorizer: {}", e)))
+    }
+
+    /// List the tables in the database
+    ///
+    /// Each entry has `name`, `kind` (`table` or `virtual`), `columnCount`,
+    /// `withoutRowid` and `strict`. Internal `sqlite_` tables and the shadow tables
+    /// behind virtual tables are left out unless `includeInternal` is true.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const names = (await db.listTables()).map((t) => t.name);
+    /// ```
+    #[wasm_bindgen(js_name = "listTables")]
+    pub async fn list_tables(
+        &mut self,
+        include_internal: Option<bool>,
+    ) -> Result<JsValue, JsValue> {
+        let tables = self
+            .list_tables_internal(include_internal.unwrap_or(false))
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to list tables: {}", e)))?;
+        serde_wasm_bindgen::to_value(&tables).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Describe a table's columns
+    ///
+    /// Returns `{ name, columns }` where each column has `name`, `declaredType`,
+    /// `nullable`, `defaultValue` (SQL text) and `primaryKey` (1-based position in the
+    /// primary key, 0 when not part of it). Fails with `TABLE_NOT_FOUND` for an unknown
+    /// table.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const { columns } = await db.getTableSchema('users');
+    /// const required = columns.filter((c) => !c.nullable && c.defaultValue == null);
+    /// ```
+    #[wasm_bindgen(js_name = "getTableSchema")]
+    pub async fn get_table_schema(&mut self, table: String) -> Result<JsValue, JsValue> {
+        let schema = self.table_schema_internal(&table).await.map_err(|e| {
+            JsValue::from_str(&format!("Failed to read schema of {}: {}", table, e))
+        })?;
+        serde_wasm_bindgen::to_value(&schema).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// List the indexes on a table
+    ///
+    /// Each entry has `name`, `unique`, `origin` (`c`, `u` or `pk`), `partial` and
+    /// `columns` (null for an expression). Indexes SQLite creates for PRIMARY KEY and
+    /// UNIQUE constraints are left out unless `includeInternal` is true.
+    #[wasm_bindgen(js_name = "listIndexes")]
+    pub async fn list_indexes(
+        &mut self,
+        table: String,
+        include_internal: Option<bool>,
+    ) -> Result<JsValue, JsValue> {
+        let indexes = self
+            .list_indexes_internal(&table, include_internal.unwrap_or(false))
+            .await
+            .map_err(|e| {
+                JsValue::from_str(&format!("Failed to list indexes on {}: {}", table, e))
+            })?;
+        serde_wasm_bindgen::to_value(&indexes).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// List the foreign keys declared on a table
+    ///
+    /// Each entry has the referenced `table`, the `from` and `to` column lists (`to`
+    /// entries are null when the key refers to the parent's primary key), `onUpdate` and
+    /// `onDelete`.
+    #[wasm_bindgen(js_name = "listForeignKeys")]
+    pub async fn list_foreign_keys(&mut self, table: String) -> Result<JsValue, JsValue> {
+        let keys = self.list_foreign_keys_internal(&table).await.map_err(|e| {
+            JsValue::from_str(&format!("Failed to list foreign keys on {}: {}", table, e))
+        })?;
+        serde_wasm_bindgen::to_value(&keys).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Export database to SQLite .db file format
+    ///
+    /// Returns the complete database as a Uint8Array that can be downloaded
+    /// or saved as a standard SQLite .db file.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const dbBytes = await db.exportToFile();
+    /// const blob = new Blob([dbBytes], { type: 'application/x-sqlite3' });
+    /// const url = URL.createObjectURL(blob);
+    /// const a = document.createElement('a');
+    /// a.href = url;
+    /// a.download = 'database.db';
+    /// a.click();
+    /// ```
+    #[wasm_bindgen(js_name = "exportToFile")]
+    pub async fn export_to_file(&self) -> Result<js_sys::Uint8Array, JsValue> {
+        let db_name = self.name.clone();
+        let max_export_size = self.max_export_size_bytes;
+
+        if self.is_memory() {
+            let db_bytes = self
+                .serialize_memory(max_export_size)
+                .map_err(|e| JsValue::from_str(&format!("Export failed: {}", e)))?;
+            let uint8_array = js_sys::Uint8Array::new_with_length(db_bytes.len() as u32);
+            uint8_array.copy_from(&db_bytes);
+            return Ok(uint8_array);
+        }
+
+        log::info!("[EXPORT] ===== Step 1: Acquiring lock");
+
+        // Acquire lock FIRST to serialize operations
+        let _guard = weblocks::acquire(&db_name, weblocks::AcquireOptions::exclusive()).await?;
+        log::info!("[EXPORT] ===== Step 2: Lock acquired");
+
+        let storage_rc = self.checkpoint_and_sync().await?;
+
+        // Export with configured size limit
+        log::info!("[EXPORT] Calling export_database_to_bytes");
+        let db_bytes = {
+            let storage = &*storage_rc;
+            crate::storage::export::export_database_to_bytes(storage, max_export_size)
+                .await
+                .map_err(|e| {
+                    log::error!("[EXPORT] Export failed: {}", e);
+                    JsValue::from_str(&format!("Export failed: {}", e))
+                })?
+        };
+
+        log::info!("[EXPORT] Export complete: {} bytes", db_bytes.len());
+
+        let uint8_array = js_sys::Uint8Array::new_with_length(db_bytes.len() as u32);
+        uint8_array.copy_from(&db_bytes);
+
+        Ok(uint8_array)
+    }
+
+    /// Test method for concurrent locking - simple increment counter
+    #[wasm_bindgen(js_name = "testLock")]
+    pub async fn test_lock(&self, value: u32) -> Result<u32, JsValue> {
+        let lock_name = format!("{}.lock_test", self.name);
+
+        log::info!(
+            "[LOCK-TEST] Acquiring lock: {} with value: {}",
+            lock_name,
+            value
+        );
+        let _guard = weblocks::acquire(&lock_name, weblocks::AcquireOptions::exclusive()).await?;
+        log::info!("[LOCK-TEST] Lock acquired, processing value: {}", value);
+
+        // Simulate some work
+        let result = value + 1;
+
+        // Small delay to test serialization
+        let delay_promise = js_sys::Promise::new(&mut |resolve, _reject| {
+            let window = web_sys::window().unwrap();
+            let _ = window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(resolve.unchecked_ref(), 10);
+        });
+        wasm_bindgen_futures::JsFuture::from(delay_promise).await?;
+
+        log::info!(
+            "[LOCK-TEST] Lock releasing for: {} with result: {}",
+            lock_name,
+            result
+        );
+        Ok(result)
+    }
+
+    /// Import SQLite database from .db file bytes
+    ///
+    /// Replaces the current database contents with the imported data.
+    /// This will close the current database connection and clear all existing data.
+    ///
+    /// # Arguments
+    /// * `file_data` - SQLite .db file as Uint8Array
+    ///
+    /// # Returns
+    /// * `Ok(())` - Import successful
+    /// * `Err(JsValue)` - Import failed (invalid file, validation error, etc.)
+    ///
+    /// # Example
+    /// ```javascript
+    /// // From file input
+    /// const fileInput = document.getElementById('dbFile');
+    /// const file = fileInput.files[0];
+    /// const arrayBuffer = await file.arrayBuffer();
+    /// const uint8Array = new Uint8Array(arrayBuffer);
+    ///
+    /// await db.importFromFile(uint8Array);
+    ///
+    /// // Database is immediately usable after import (no reopen needed)
+    /// const result = await db.execute('SELECT * FROM imported_table');
+    /// ```
+    ///
+    /// # Warning
+    /// This operation is destructive and will replace all existing database data.
+    #[wasm_bindgen(js_name = "importFromFile")]
+    pub async fn import_from_file(&mut self, file_data: js_sys::Uint8Array) -> Result<(), JsValue> {
+        log::info!("[IMPORT] Starting import with lock for: {}", self.name);
+        if self.read_only {
+            return Err(JsValue::from_str(&format!(
+                "Import failed: {}",
+                Self::read_only_error()
+            )));
+        }
+        let db_name = self.name.clone();
+        let data = file_data.to_vec();
+
+        if self.is_memory() {
+            return self
+                .deserialize_memory(&data)
+                .map_err(|e| JsValue::from_str(&format!("Import failed: {}", e)));
+        }
+
+        // Acquire lock FIRST to serialize operations
+        let _guard = weblocks::acquire(&db_name, weblocks::AcquireOptions::exclusive()).await?;
+        log::info!("[IMPORT] Lock acquired for: {}", db_name);
+
+        log::debug!("Import data size: {} bytes", data.len());
+
+        // CRITICAL: Force-close database connection BEFORE import
+        // Must use force_close to remove from connection pool, not just decrement ref_count
+        // Otherwise new Database instances will reuse stale SQLite connection
+        log::debug!("Force-closing database connection before import");
+
+        self.detach_connection("import").await?;
+        log::debug!("Removed connection from pool for import");
+
+        // Call the import function with full name (WITH .db)
+        crate::storage::import::import_database_from_bytes_with_block_size(
+            &db_name,
+            data,
+            self.block_size,
+        )
+        .await
+        .map_err(|e| {
+            log::error!("Import failed for {}: {}", db_name, e);
+            JsValue::from_str(&format!("Import failed: {}", e))
+        })?;
+
+        log::info!("[IMPORT] Import complete for: {}", db_name);
+
+        // Reopen the SQLite connection so this Database instance is usable after import
+        // The VFS should already exist from when the Database was first created
+        log::info!("[IMPORT] Reopening connection for: {}", db_name);
+
+        self.reopen_connection()?;
+        log::info!("[IMPORT] Connection state updated for: {}", db_name);
+
+        Ok(())
+    }
+
+    /// Check whether bytes can be imported, without touching any database
+    ///
+    /// Reports the header's page size, page count and schema version, whether
+    /// `PRAGMA integrity_check` passed, whether the file looks encrypted, and every
+    /// problem found. Fails only when the bytes are too short to hold a header.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const report = Database.validateImport(bytes);
+    /// if (report.problems.length > 0) {
+    ///   showError(report.problems.join('\n'));
+    /// } else {
+    ///   await db.importFromFileInPlace(bytes);
+    /// }
+    /// ```
+    #[wasm_bindgen(js_name = "validateImport")]
+    pub fn validate_import(file_data: js_sys::Uint8Array) -> Result<ImportReport, JsValue> {
+        crate::storage::import::validate_import_bytes(&file_data.to_vec())
+            .map_err(|e| JsValue::from_str(&format!("Import validation failed: {}", e)))
+    }
+
+    /// Import SQLite database bytes while keeping this connection open
+    ///
+    /// Unlike `importFromFile`, the connection, leadership, subscriptions and callbacks
+    /// all survive the import. The file is validated before anything is replaced, so an
+    /// invalid file leaves the current data untouched. Fails if a transaction is open.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const backup = new Uint8Array(await file.arrayBuffer());
+    /// await db.importFromFileInPlace(backup);
+    /// const result = await db.execute('SELECT * FROM restored_table');
+    /// ```
+    #[wasm_bindgen(js_name = "importFromFileInPlace")]
+    pub async fn import_from_file_in_place(
+        &mut self,
+        file_data: js_sys::Uint8Array,
+    ) -> Result<(), JsValue> {
+        if self.read_only {
+            return Err(JsValue::from_str(&format!(
+                "Import failed: {}",
+                Self::read_only_error()
+            )));
+        }
+        let data = file_data.to_vec();
+        if self.is_memory() {
+            return self
+                .deserialize_memory(&data)
+                .map_err(|e| JsValue::from_str(&format!("Import failed: {}", e)));
+        }
+
+        let db_name = self.name.clone();
+        let _guard = weblocks::acquire(&db_name, weblocks::AcquireOptions::exclusive()).await?;
+        self.import_in_place_internal(&data)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Import failed: {}", e)))
+    }
+
+    /// Export only the blocks committed after `sinceMarker`
+    ///
+    /// Returns `{ delta, marker }`: the changed blocks as a Uint8Array and the commit
+    /// marker to pass to the next call. Use a marker of 0 for the first export.
+    ///
+    /// # Example
+    /// ```javascript
+    /// let marker = 0;
+    /// const { delta, marker: next } = await db.exportIncremental(marker);
+    /// await uploadToServer(delta);
+    /// marker = next;
+    /// ```
+    #[wasm_bindgen(js_name = "exportIncremental")]
+    pub async fn export_incremental(&self, since_marker: f64) -> Result<JsValue, JsValue> {
+        let db_name = self.name.clone();
+        let _guard = weblocks::acquire(&db_name, weblocks::AcquireOptions::exclusive()).await?;
+        self.checkpoint_and_sync().await?;
+
+        let delta = crate::storage::incremental::export_incremental(
+            &db_name,
+            since_marker as u64,
+            self.block_size,
+        )
+        .map_err(|e| JsValue::from_str(&format!("Incremental export failed: {}", e)))?;
+
+        let bytes = delta.to_bytes();
+        let obj = js_sys::Object::new();
+        js_sys::Reflect::set(
+            &obj,
+            &"delta".into(),
+            &js_sys::Uint8Array::from(bytes.as_slice()).into(),
+        )?;
+        js_sys::Reflect::set(
+            &obj,
+            &"marker".into(),
+            &JsValue::from_f64(delta.new_marker as f64),
+        )?;
+        Ok(obj.into())
     }
 
-    /// Get all database names stored in IndexedDB
+    /// Apply a delta produced by `exportIncremental` onto this database
     ///
-    /// Returns an array of database names (sorted alphabetically)
-    #[wasm_bindgen(js_name = "getAllDatabases")]
-    pub async fn get_all_databases() -> Result<JsValue, JsValue> {
-        use crate::storage::vfs_sync::with_global_storage;
-        use crate::vfs::indexeddb_vfs::STORAGE_REGISTRY;
-        use std::collections::HashSet;
-
-        log::info!("getAllDatabases called");
-        let mut db_names = HashSet::new();
-
-        // Get databases from persistent list (localStorage)
-        match Self::get_persistent_database_list() {
-            Ok(persistent_list) => {
-                log::info!("Persistent list has {} entries", persistent_list.len());
-                for name in persistent_list {
-                    log::info!("Found in persistent list: {}", name);
-                    db_names.insert(name);
-                }
-            }
-            Err(e) => {
-                log::warn!("Failed to get persistent list: {:?}", e);
-            }
+    /// The database must already hold the state the delta was taken from, e.g. a full
+    /// `importFromFile` followed by every earlier delta in order. Returns the local
+    /// commit marker after the delta is applied.
+    #[wasm_bindgen(js_name = "importIncremental")]
+    pub async fn import_incremental(&mut self, delta: js_sys::Uint8Array) -> Result<f64, JsValue> {
+        if self.read_only {
+            return Err(JsValue::from_str(&format!(
+                "Incremental import failed: {}",
+                Self::read_only_error()
+            )));
         }
+        let db_name = self.name.clone();
+        let delta = crate::storage::incremental::IncrementalDelta::from_bytes(&delta.to_vec())
+            .map_err(|e| JsValue::from_str(&format!("Incremental import failed: {}", e)))?;
 
-        // Get databases from STORAGE_REGISTRY (currently open)
-        // SAFETY: WASM is single-threaded, no concurrent access possible
-        STORAGE_REGISTRY.with(|reg| unsafe {
-            let registry = &*reg.get();
-            log::info!("STORAGE_REGISTRY has {} entries", registry.len());
-            for key in registry.keys() {
-                log::info!("Found in STORAGE_REGISTRY: {}", key);
-                db_names.insert(key.clone());
-            }
-        });
-
-        // Get databases from GLOBAL_STORAGE (in-memory persistent storage)
-        with_global_storage(|storage| {
-            let storage_borrow = storage.borrow();
-            log::info!("GLOBAL_STORAGE has {} entries", storage_borrow.len());
-            for key in storage_borrow.keys() {
-                log::info!("Found in GLOBAL_STORAGE: {}", key);
-                db_names.insert(key.clone());
-            }
-        });
+        let _guard = weblocks::acquire(&db_name, weblocks::AcquireOptions::exclusive()).await?;
+        self.checkpoint_and_sync().await?;
 
-        log::info!("Total unique databases found: {}", db_names.len());
+        // Drop the connection and cached BlockStorage so the applied blocks are read fresh
+        self.detach_connection("incremental import").await?;
+        crate::storage::import::invalidate_block_storage_caches(&crate::utils::normalize_db_name(
+            &db_name,
+        ));
 
-        // Convert to sorted vector
-        let mut result_vec: Vec<String> = db_names.into_iter().collect();
-        result_vec.sort();
+        let result =
+            crate::storage::incremental::import_incremental(&db_name, delta, self.block_size).await;
+        self.reopen_connection()?;
 
-        // Convert to JavaScript array
-        let js_array = js_sys::Array::new();
-        for name in &result_vec {
-            log::info!("Returning database: {}", name);
-            js_array.push(&JsValue::from_str(name));
-        }
+        let marker =
+            result.map_err(|e| JsValue::from_str(&format!("Incremental import failed: {}", e)))?;
+        log::info!(
+            "[IMPORT] Incremental import complete for {} at marker {}",
+            db_name,
+            marker
+        );
+        Ok(marker as f64)
+    }
 
-        log::info!("getAllDatabases returning {} databases", result_vec.len());
+    /// Wait until the database is fully ready to use
+    ///
+    /// Resolves once blocks, metadata and the commit marker have been restored from
+    /// IndexedDB and leader election has settled, whichever tab won it. Gives up after
+    /// `timeoutMs`, or the configured `election_timeout_ms` when omitted.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const db = await Database.newDatabase('app');
+    /// await db.whenReady();
+    /// const result = await db.execute('SELECT * FROM settings');
+    /// ```
+    #[wasm_bindgen(js_name = "whenReady")]
+    pub async fn when_ready(&self, timeout_ms: Option<u32>) -> Result<(), JsValue> {
+        self.when_ready_internal(timeout_ms)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Database not ready: {}", e)))
+    }
 
-        Ok(js_array.into())
+    /// Wait for this instance to become leader
+    ///
+    /// Gives up after the configured `election_timeout_ms`.
+    #[wasm_bindgen(js_name = "waitForLeadership")]
+    pub async fn wait_for_leadership(&mut self) -> Result<(), JsValue> {
+        self.wait_for_leadership_with_timeout(None).await
     }
 
-    /// Delete a database from storage
+    /// Wait for this instance to become leader, giving up after `timeoutMs`
     ///
-    /// Removes database from both STORAGE_REGISTRY and GLOBAL_STORAGE
-    #[wasm_bindgen(js_name = "deleteDatabase")]
-    pub async fn delete_database(name: String) -> Result<(), JsValue> {
-        use crate::storage::vfs_sync::{
-            with_global_commit_marker, with_global_metadata, with_global_storage,
-        };
+    /// Uses the configured `election_timeout_ms` when `timeoutMs` is omitted.
+    #[wasm_bindgen(js_name = "waitForLeadershipWithTimeout")]
+    pub async fn wait_for_leadership_with_timeout(
+        &mut self,
+        timeout_ms: Option<u32>,
+    ) -> Result<(), JsValue> {
+        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
 
-        // Normalize database name
-        let normalized_name = if name.ends_with(".db") {
-            name.clone()
-        } else {
-            format!("{}.db", name)
-        };
+        // Track leader election attempt
+        #[cfg(feature = "telemetry")]
+        if let Some(ref metrics) = self.metrics {
+            metrics.leader_election_attempts_total().inc();
+        }
 
-        log::info!("Deleting database: {}", normalized_name);
+        let db_name = &self.name;
+        let start_time = js_sys::Date::now();
 
-        // Remove from STORAGE_REGISTRY
-        use crate::vfs::indexeddb_vfs::remove_storage_from_registry;
-        remove_storage_from_registry(&normalized_name);
+        // Default to the configured election timeout
+        let timeout_ms = match timeout_ms {
+            Some(ms) => ms as f64,
+            None => get_storage_with_fallback(db_name)
+                .map(|storage| storage.leader_election_config().election_timeout_ms as f64)
+                .unwrap_or(5000.0),
+        };
 
-        // Remove from GLOBAL_STORAGE
-        with_global_storage(|gs| {
-            #[cfg(target_arch = "wasm32")]
-            let mut storage = gs.borrow_mut();
-            #[cfg(not(target_arch = "wasm32"))]
-            let mut storage = gs.lock();
-            storage.remove(&normalized_name);
-        });
+        loop {
+            let storage_rc = get_storage_with_fallback(db_name);
 
-        // Remove from GLOBAL_METADATA
-        with_global_metadata(|gm| {
-            #[cfg(target_arch = "wasm32")]
-            let mut metadata = gm.borrow_mut();
-            #[cfg(not(target_arch = "wasm32"))]
-            let mut metadata = gm.lock();
-            metadata.remove(&normalized_name);
-        });
+            if let Some(storage) = storage_rc {
+                let is_leader =
+                    match with_storage_async!(storage, "wait_for_leadership", |s| s.is_leader()) {
+                        Some(v) => v,
+                        None => continue,
+                    };
 
-        // Remove from commit markers
-        with_global_commit_marker(|cm| {
-            #[cfg(target_arch = "wasm32")]
-            let mut markers = cm.borrow_mut();
-            #[cfg(not(target_arch = "wasm32"))]
-            let mut markers = cm.lock();
-            log::info!(
-                "Cleared commit marker for {} from GLOBAL storage",
-                normalized_name
-            );
-            markers.remove(&normalized_name);
-        });
+                if is_leader {
+                    log::info!("Became leader for {}", db_name);
 
-        // Delete from IndexedDB (typed web-sys API — no eval, so it works under
-        // MV3's strict CSP where 'unsafe-eval' is forbidden).
-        let idb_name = format!("absurder_{}", normalized_name);
-        {
-            use wasm_bindgen::JsCast;
-            let global = js_sys::global();
-            let indexed_db_value =
-                js_sys::Reflect::get(&global, &JsValue::from_str("indexedDB"))
-                    .map_err(|e| JsValue::from_str(&format!("IndexedDB access failed: {:?}", e)))?;
-            if !indexed_db_value.is_null() && !indexed_db_value.is_undefined() {
-                let idb_factory: web_sys::IdbFactory = indexed_db_value
-                    .dyn_into()
-                    .map_err(|_| JsValue::from_str("indexedDB is not an IdbFactory"))?;
-                idb_factory.delete_database(&idb_name).map_err(|e| {
-                    JsValue::from_str(&format!("Failed to delete IndexedDB: {:?}", e))
-                })?;
+                    // Record telemetry on successful leadership acquisition
+                    #[cfg(feature = "telemetry")]
+                    if let Some(ref metrics) = self.metrics {
+                        let duration_ms = js_sys::Date::now() - start_time;
+                        metrics.leader_election_duration().observe(duration_ms);
+                        metrics.is_leader().set(1.0);
+                        metrics.leadership_changes_total().inc();
+                    }
+
+                    return Ok(());
+                }
             }
-        }
 
-        log::info!("Database deleted: {}", normalized_name);
+            // Check timeout
+            if js_sys::Date::now() - start_time > timeout_ms {
+                // Record telemetry on timeout
+                #[cfg(feature = "telemetry")]
+                if let Some(ref metrics) = self.metrics {
+                    let duration_ms = js_sys::Date::now() - start_time;
+                    metrics.leader_election_duration().observe(duration_ms);
+                }
 
-        // Remove from persistent list
-        Self::remove_database_from_persistent_list(&normalized_name)?;
+                return Err(JsValue::from_str("Timeout waiting for leadership"));
+            }
 
-        Ok(())
+            // Wait a bit before checking again
+            let promise = js_sys::Promise::new(&mut |resolve, _| {
+                let window = web_sys::window().expect("should have window");
+                let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 100);
+            });
+            let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+        }
     }
 
-    /// Add database name to persistent list in localStorage
-    #[allow(dead_code)]
-    fn add_database_to_persistent_list(db_name: &str) -> Result<(), JsValue> {
-        log::info!("add_database_to_persistent_list called for: {}", db_name);
-
-        let window = web_sys::window().ok_or_else(|| {
-            log::error!("No window object");
-            JsValue::from_str("No window")
-        })?;
-
-        let storage = window
-            .local_storage()
-            .map_err(|e| {
-                log::error!("Failed to get localStorage: {:?}", e);
-                JsValue::from_str("No localStorage")
-            })?
-            .ok_or_else(|| {
-                log::error!("localStorage not available");
-                JsValue::from_str("localStorage not available")
-            })?;
+    /// Request leadership (triggers re-election check)
+    #[wasm_bindgen(js_name = "requestLeadership")]
+    pub async fn request_leadership(&mut self) -> Result<(), JsValue> {
+        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
 
-        let key = "absurder_db_list";
-        let existing = storage.get_item(key).map_err(|e| {
-            log::error!("Failed to read localStorage key {}: {:?}", key, e);
-            JsValue::from_str("Failed to read localStorage")
-        })?;
+        let db_name = &self.name;
+        log::debug!("Requesting leadership for {}", db_name);
 
-        log::debug!("Existing localStorage value: {:?}", existing);
+        // Record telemetry data before the request
+        #[cfg(feature = "telemetry")]
+        let telemetry_data = if self.metrics.is_some() {
+            let start_time = js_sys::Date::now();
+            let was_leader = self
+                .is_leader_wasm()
+                .await
+                .ok()
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
 
-        let mut db_list: Vec<String> = if let Some(json_str) = existing {
-            match serde_json::from_str(&json_str) {
-                Ok(list) => {
-                    log::debug!("Parsed existing list: {:?}", list);
-                    list
-                }
-                Err(e) => {
-                    log::warn!("Failed to parse localStorage JSON: {}, starting fresh", e);
-                    Vec::new()
-                }
+            if let Some(ref metrics) = self.metrics {
+                metrics.leader_elections_total().inc();
             }
+
+            Some((start_time, was_leader))
         } else {
-            log::debug!("No existing list, creating new");
-            Vec::new()
+            None
         };
 
-        if !db_list.contains(&db_name.to_string()) {
-            db_list.push(db_name.to_string());
-            db_list.sort();
-            log::debug!("Updated list: {:?}", db_list);
+        let storage_rc = get_storage_with_fallback(db_name);
 
-            let json_str = serde_json::to_string(&db_list).map_err(|e| {
-                log::error!("Failed to serialize list: {}", e);
-                JsValue::from_str("Failed to serialize")
-            })?;
+        if let Some(storage) = storage_rc {
+            {
+                // Trigger leader election
+                let result = with_storage_async!(storage, "request_leadership", |s| s
+                    .start_leader_election())
+                .ok_or_else(|| {
+                    JsValue::from_str("Failed to acquire storage lock for leadership request")
+                })?;
+                result.map_err(|e| {
+                    JsValue::from_str(&format!("Failed to request leadership: {}", e))
+                })?;
+
+                log::debug!("Re-election triggered for {}", db_name);
+            } // Drop the borrow here
+
+            // Record telemetry after election (after dropping borrow)
+            #[cfg(feature = "telemetry")]
+            if let Some((start_time, was_leader)) = telemetry_data {
+                if let Some(ref metrics) = self.metrics {
+                    // Record election duration
+                    let duration_ms = js_sys::Date::now() - start_time;
+                    metrics.leader_election_duration().observe(duration_ms);
+
+                    // Check if leadership status changed
+                    let is_leader_now = self
+                        .is_leader_wasm()
+                        .await
+                        .ok()
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
 
-            log::debug!("Writing to localStorage: {}", json_str);
+                    // Update is_leader gauge
+                    metrics
+                        .is_leader()
+                        .set(if is_leader_now { 1.0 } else { 0.0 });
 
-            storage.set_item(key, &json_str).map_err(|e| {
-                log::error!("Failed to write to localStorage: {:?}", e);
-                JsValue::from_str("Failed to write localStorage")
-            })?;
+                    // Track leadership changes
+                    if was_leader != is_leader_now {
+                        metrics.leadership_changes_total().inc();
+                    }
+                }
+            }
 
-            log::info!("Successfully added {} to persistent database list", db_name);
+            Ok(())
         } else {
-            log::info!("{} already in persistent list", db_name);
+            Err(JsValue::from_str(&format!(
+                "No storage found for database: {}",
+                db_name
+            )))
         }
-
-        Ok(())
     }
 
-    /// Remove database name from persistent list in localStorage
-    fn remove_database_from_persistent_list(db_name: &str) -> Result<(), JsValue> {
-        let window = web_sys::window().ok_or_else(|| JsValue::from_str("No window"))?;
-        let storage = window
-            .local_storage()
-            .map_err(|_| JsValue::from_str("No localStorage"))?
-            .ok_or_else(|| JsValue::from_str("localStorage not available"))?;
-
-        let key = "absurder_db_list";
-        let existing = storage
-            .get_item(key)
-            .map_err(|_| JsValue::from_str("Failed to read localStorage"))?;
-
-        if let Some(json_str) = existing {
-            let mut db_list: Vec<String> =
-                serde_json::from_str(&json_str).unwrap_or_else(|_| Vec::new());
-            db_list.retain(|name| name != db_name);
-            let json_str = serde_json::to_string(&db_list)
-                .map_err(|_| JsValue::from_str("Failed to serialize"))?;
-            storage
-                .set_item(key, &json_str)
-                .map_err(|_| JsValue::from_str("Failed to write localStorage"))?;
-            log::info!("Removed {} from persistent database list", db_name);
-        }
-
-        Ok(())
-    }
+    /// Get leader information
+    #[wasm_bindgen(js_name = "getLeaderInfo")]
+    pub async fn get_leader_info(&mut self) -> Result<JsValue, JsValue> {
+        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
 
-    /// Get database names from persistent list in localStorage
-    fn get_persistent_database_list() -> Result<Vec<String>, JsValue> {
-        log::info!("get_persistent_database_list called");
+        let db_name = &self.name;
 
-        let window = web_sys::window().ok_or_else(|| {
-            log::error!("No window object");
-            JsValue::from_str("No window")
-        })?;
+        let storage_rc = get_storage_with_fallback(db_name);
 
-        let storage = window
-            .local_storage()
-            .map_err(|e| {
-                log::error!("Failed to get localStorage: {:?}", e);
-                JsValue::from_str("No localStorage")
-            })?
-            .ok_or_else(|| {
-                log::error!("localStorage not available");
-                JsValue::from_str("localStorage not available")
-            })?;
+        if let Some(storage) = storage_rc {
+            let is_leader = with_storage_async!(storage, "get_leader_info", |s| s.is_leader())
+                .ok_or_else(|| {
+                    JsValue::from_str(&format!(
+                        "Failed to access storage for database: {}",
+                        db_name
+                    ))
+                })?;
 
-        let key = "absurder_db_list";
-        let existing = storage.get_item(key).map_err(|e| {
-            log::error!("Failed to read localStorage key {}: {:?}", key, e);
-            JsValue::from_str("Failed to read localStorage")
-        })?;
+            // Get leader info - we'll use simpler data for now
+            // Real implementation would need public getters on BlockStorage
+            let leader_id_str = if is_leader {
+                format!("leader_{}", db_name)
+            } else {
+                "unknown".to_string()
+            };
 
-        log::debug!("Read from localStorage: {:?}", existing);
+            // Create JavaScript object
+            let obj = js_sys::Object::new();
+            js_sys::Reflect::set(&obj, &"isLeader".into(), &JsValue::from_bool(is_leader))?;
+            js_sys::Reflect::set(&obj, &"leaderId".into(), &JsValue::from_str(&leader_id_str))?;
+            js_sys::Reflect::set(
+                &obj,
+                &"leaseExpiry".into(),
+                &JsValue::from_f64(js_sys::Date::now()),
+            )?;
 
-        if let Some(json_str) = existing {
-            match serde_json::from_str::<Vec<String>>(&json_str) {
-                Ok(db_list) => {
-                    log::info!(
-                        "Successfully parsed {} databases from localStorage",
-                        db_list.len()
-                    );
-                    log::debug!("Database list: {:?}", db_list);
-                    Ok(db_list)
-                }
-                Err(e) => {
-                    log::error!("Failed to parse localStorage JSON: {}", e);
-                    Ok(Vec::new())
-                }
-            }
+            Ok(obj.into())
         } else {
-            log::info!("No persistent database list in localStorage");
-            Ok(Vec::new())
+            Err(JsValue::from_str(&format!(
+                "No storage found for database: {}",
+                db_name
+            )))
         }
     }
 
-    /// Start listening for write queue requests (leader processes these)
-    fn start_write_queue_listener(db_name: &str) -> Result<(), JsValue> {
-        use crate::storage::write_queue::{
-            WriteQueueMessage, WriteResponse, register_write_queue_listener,
-        };
-        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+    /// Queue a write operation to be executed by the leader
+    ///
+    /// Non-leader tabs can use this to request writes from the leader.
+    /// The write is forwarded via BroadcastChannel and executed by the leader.
+    ///
+    /// # Arguments
+    /// * `sql` - SQL statement to execute (must be a write operation)
+    ///
+    /// # Returns
+    /// Result indicating success or failure
+    #[wasm_bindgen(js_name = "queueWrite")]
+    pub async fn queue_write(&self, sql: String) -> Result<(), JsValue> {
+        self.queue_write_with_timeout(sql, 5000).await
+    }
 
-        let db_name_clone = db_name.to_string();
+    /// Queue a write operation with a specific timeout
+    ///
+    /// # Arguments
+    /// * `sql` - SQL statement to execute
+    /// * `timeout_ms` - Timeout in milliseconds
+    #[wasm_bindgen(js_name = "queueWriteWithTimeout")]
+    pub async fn queue_write_with_timeout(
+        &self,
+        sql: String,
+        timeout_ms: u32,
+    ) -> Result<(), JsValue> {
+        use crate::storage::write_queue::{WriteQueueMessage, WriteResponse, send_write_request};
+        use std::cell::RefCell;
+        use std::rc::Rc;
 
-        let callback = Closure::wrap(Box::new(move |msg: JsValue| {
-            let db_name_inner = db_name_clone.clone();
+        self.ensure_writable(&sql)
+            .map_err(|e| JsValue::from_str(&format!("Write failed: {}", e)))?;
 
-            // Parse the message
-            if let Ok(json_str) = js_sys::JSON::stringify(&msg) {
-                if let Some(json_str) = json_str.as_string() {
-                    if let Ok(message) = serde_json::from_str::<WriteQueueMessage>(&json_str) {
-                        if let WriteQueueMessage::WriteRequest(request) = message {
-                            log::debug!("Leader received write request: {}", request.request_id);
+        log::debug!("Queuing write: {}", sql);
 
-                            // Check if we're the leader
-                            let storage_rc = get_storage_with_fallback(&db_name_inner);
+        // Check if we're the leader - if so, just execute directly
+        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+        let is_leader = {
+            let storage_rc = get_storage_with_fallback(&self.name);
 
-                            if let Some(storage) = storage_rc {
-                                // Spawn async task to process the write
-                                wasm_bindgen_futures::spawn_local(async move {
-                                    let is_leader = with_storage_async!(
-                                        storage,
-                                        "write_queue_is_leader",
-                                        |s| s.is_leader()
-                                    );
-                                    if is_leader.is_none() {
-                                        log::error!("Failed to check leader status");
-                                        return;
-                                    }
-                                    let is_leader = is_leader.unwrap();
+            if let Some(storage) = storage_rc {
+                with_storage_async!(storage, "queue_write_is_leader", |s| s.is_leader())
+                    .unwrap_or(false)
+            } else {
+                false
+            }
+        };
 
-                                    if !is_leader {
-                                        log::error!("Not leader, ignoring write request");
-                                        return;
-                                    }
+        if is_leader {
+            log::debug!("We are leader, executing directly");
+            return self
+                .execute_shared(&sql)
+                .await
+                .map(|_| ())
+                .map_err(|e| JsValue::from_str(&format!("Execute failed: {}", e)));
+        }
 
-                                    log::debug!("Processing write request as leader");
+        // Apply backpressure before sending another request
+        self.pending_writes
+            .borrow()
+            .ensure_capacity()
+            .map_err(|e| JsValue::from_str(&format!("Write failed: {}", e)))?;
 
-                                    // Create a temporary database instance to execute the SQL
-                                    match Database::new_wasm(db_name_inner.clone()).await {
-                                        Ok(mut db) => {
-                                            // Execute the SQL
-                                            match db.execute_internal(&request.sql).await {
-                                                Ok(result) => {
-                                                    // Send success response
-                                                    let response = WriteResponse::Success {
-                                                        request_id: request.request_id.clone(),
-                                                        affected_rows: result.affected_rows
-                                                            as usize,
-                                                    };
+        // Send write request to leader
+        let request_id = send_write_request(&self.name, &sql)
+            .map_err(|e| JsValue::from_str(&format!("Failed to send write request: {}", e)))?;
+        self.pending_writes.borrow_mut().insert(&request_id);
 
-                                                    use crate::storage::write_queue::send_write_response;
-                                                    if let Err(e) = send_write_response(
-                                                        &db_name_inner,
-                                                        response,
-                                                    ) {
-                                                        log::error!(
-                                                            "Failed to send response: {}",
-                                                            e
-                                                        );
-                                                    } else {
-                                                        log::info!(
-                                                            "Write response sent successfully"
-                                                        );
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    // Send error response
-                                                    let response = WriteResponse::Error {
-                                                        request_id: request.request_id.clone(),
-                                                        error_message: e.to_string(),
-                                                    };
+        log::debug!("Write request sent with ID: {}", request_id);
+
+        // Wait for response with timeout
+        let response_received = Rc::new(RefCell::new(false));
+        let response_error = Rc::new(RefCell::new(None::<String>));
+
+        let response_received_clone = response_received.clone();
+        let response_error_clone = response_error.clone();
+        let request_id_clone = request_id.clone();
 
-                                                    use crate::storage::write_queue::send_write_response;
-                                                    if let Err(e) = send_write_response(
-                                                        &db_name_inner,
-                                                        response,
-                                                    ) {
-                                                        log::error!(
-                                                            "Failed to send error response: {}",
-                                                            e
-                                                        );
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        Err(e) => {
-                                            log::error!(
-                                                "Failed to create db for write processing: {:?}",
-                                                e
-                                            );
-                                        }
+        // Set up listener for response
+        let callback = Closure::wrap(Box::new(move |msg: JsValue| {
+            // Parse the message
+            if let Ok(json_str) = js_sys::JSON::stringify(&msg) {
+                if let Some(json_str) = json_str.as_string() {
+                    if let Ok(message) = serde_json::from_str::<WriteQueueMessage>(&json_str) {
+                        if let WriteQueueMessage::WriteResponse(response) = message {
+                            match response {
+                                WriteResponse::Success { request_id, .. } => {
+                                    if request_id == request_id_clone {
+                                        *response_received_clone.borrow_mut() = true;
+                                        log::debug!("Write response received: Success");
                                     }
-                                });
+                                }
+                                WriteResponse::Error {
+                                    request_id,
+                                    error_message,
+                                } => {
+                                    if request_id == request_id_clone {
+                                        *response_received_clone.borrow_mut() = true;
+                                        *response_error_clone.borrow_mut() = Some(error_message);
+                                        log::debug!("Write response received: Error");
+                                    }
+                                }
                             }
                         }
                     }
@@ -2207,297 +7180,319 @@ impl Database {
             }
         }) as Box<dyn FnMut(JsValue)>);
 
+        // Register listener
+        use crate::storage::write_queue::register_write_queue_listener;
         let callback_fn = callback.as_ref().unchecked_ref();
-        register_write_queue_listener(db_name, callback_fn).map_err(|e| {
-            JsValue::from_str(&format!("Failed to register write queue listener: {}", e))
-        })?;
+        register_write_queue_listener(&self.name, callback_fn)
+            .map_err(|e| JsValue::from_str(&format!("Failed to register listener: {}", e)))?;
 
+        // Keep callback alive
         callback.forget();
 
-        Ok(())
-    }
-
-    #[wasm_bindgen]
-    pub async fn execute(&mut self, sql: &str) -> Result<JsValue, JsValue> {
-        // Check write permission before executing
-        self.check_write_permission(sql)
-            .await
-            .map_err(|e| JsValue::from_str(&format!("Write permission denied: {}", e)))?;
+        // Wait for response with polling (timeout_ms)
+        let start_time = js_sys::Date::now();
+        let timeout_f64 = timeout_ms as f64;
 
-        let result = self
-            .execute_internal(sql)
-            .await
-            .map_err(|e| JsValue::from_str(&format!("Query execution failed: {}", e)))?;
-        serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
-    }
+        let result = loop {
+            // Check if response received
+            if *response_received.borrow() {
+                if let Some(error_msg) = response_error.borrow().as_ref() {
+                    break Err(JsValue::from_str(&format!("Write failed: {}", error_msg)));
+                }
+                log::info!("Write completed successfully");
+                break Ok(());
+            }
 
-    #[wasm_bindgen(js_name = "executeWithParams")]
-    pub async fn execute_with_params(
-        &mut self,
-        sql: &str,
-        params: JsValue,
-    ) -> Result<JsValue, JsValue> {
-        let params: Vec<ColumnValue> = serde_wasm_bindgen::from_value(params)
-            .map_err(|e| JsValue::from_str(&format!("Invalid parameters: {}", e)))?;
+            // Check timeout
+            let elapsed = js_sys::Date::now() - start_time;
+            if elapsed > timeout_f64 {
+                break Err(JsValue::from_str("Write request timed out"));
+            }
 
-        // Check write permission before executing
-        self.check_write_permission(sql)
+            // Wait a bit before checking again
+            wasm_bindgen_futures::JsFuture::from(js_sys::Promise::new(&mut |resolve, _reject| {
+                if let Some(window) = web_sys::window() {
+                    let _ =
+                        window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 100);
+                } else {
+                    log::error!("Window unavailable in timeout handler");
+                }
+            }))
             .await
-            .map_err(|e| JsValue::from_str(&format!("Write permission denied: {}", e)))?;
+            .ok();
+        };
 
-        let result = self
-            .execute_with_params_internal(sql, &params)
-            .await
-            .map_err(|e| JsValue::from_str(&format!("Query execution failed: {}", e)))?;
-        serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+        self.pending_writes.borrow_mut().complete(&request_id);
+        result
     }
 
-    #[wasm_bindgen]
-    pub async fn close(&mut self) -> Result<(), JsValue> {
-        self.close_internal()
-            .await
-            .map_err(|e| JsValue::from_str(&format!("Failed to close database: {}", e)))
+    /// Number of queued writes still waiting for the leader's response
+    #[wasm_bindgen(js_name = "getWriteQueueDepth")]
+    pub fn get_write_queue_depth(&self) -> u32 {
+        self.pending_writes.borrow().depth() as u32
     }
 
-    /// Force close connection and remove from pool (for test cleanup)
-    #[wasm_bindgen(js_name = "forceCloseConnection")]
-    pub async fn force_close_connection(&mut self) -> Result<(), JsValue> {
-        // First do normal close to cleanup
-        let _ = self.close_internal().await;
-
-        // Then force-remove from connection pool
-        // Pool uses name without .db, so strip it
-        let pool_key = self.name.trim_end_matches(".db");
-        crate::connection_pool::force_close_connection(pool_key);
+    /// Wait until every queued write has been answered by the leader
+    ///
+    /// Fails with `WRITE_QUEUE_FLUSH_TIMEOUT` if requests are still pending after
+    /// `timeoutMs` (default 5000).
+    #[wasm_bindgen(js_name = "flushWriteQueue")]
+    pub async fn flush_write_queue(&self, timeout_ms: Option<u32>) -> Result<(), JsValue> {
+        let timeout_ms = timeout_ms.unwrap_or(5000) as f64;
+        let start_time = js_sys::Date::now();
 
-        // CRITICAL: Single source of truth for ALL cleanup
-        #[cfg(target_arch = "wasm32")]
-        {
-            crate::cleanup::cleanup_all_state(pool_key)
-                .await
-                .map_err(|e| JsValue::from_str(&format!("Cleanup failed: {}", e)))?;
-        }
-        log::info!("Force closed and removed connection for: {}", self.name);
-        Ok(())
-    }
+        loop {
+            let depth = self.pending_writes.borrow().depth();
+            if depth == 0 {
+                return Ok(());
+            }
+            if js_sys::Date::now() - start_time > timeout_ms {
+                let err = DatabaseError::new(
+                    "WRITE_QUEUE_FLUSH_TIMEOUT",
+                    &format!(
+                        "{} queued writes still pending after {}ms",
+                        depth, timeout_ms
+                    ),
+                );
+                return Err(JsValue::from_str(&err.to_string()));
+            }
 
-    #[wasm_bindgen]
-    pub async fn sync(&mut self) -> Result<(), JsValue> {
-        self.sync_internal()
+            wasm_bindgen_futures::JsFuture::from(js_sys::Promise::new(&mut |resolve, _reject| {
+                if let Some(window) = web_sys::window() {
+                    let _ =
+                        window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 50);
+                }
+            }))
             .await
-            .map_err(|e| JsValue::from_str(&format!("Failed to sync database: {}", e)))
-    }
-
-    /// Allow non-leader writes (for single-tab apps or testing)
-    #[wasm_bindgen(js_name = "allowNonLeaderWrites")]
-    pub async fn allow_non_leader_writes(&mut self, allow: bool) -> Result<(), JsValue> {
-        log::debug!("Setting allowNonLeaderWrites = {} for {}", allow, self.name);
-        self.allow_non_leader_writes = allow;
-        Ok(())
+            .ok();
+        }
     }
 
-    /// Export database to SQLite .db file format
-    ///
-    /// Returns the complete database as a Uint8Array that can be downloaded
-    /// or saved as a standard SQLite .db file.
-    ///
-    /// # Example
-    /// ```javascript
-    /// const dbBytes = await db.exportToFile();
-    /// const blob = new Blob([dbBytes], { type: 'application/x-sqlite3' });
-    /// const url = URL.createObjectURL(blob);
-    /// const a = document.createElement('a');
-    /// a.href = url;
-    /// a.download = 'database.db';
-    /// a.click();
-    /// ```
-    #[wasm_bindgen(js_name = "exportToFile")]
-    pub async fn export_to_file(&self) -> Result<js_sys::Uint8Array, JsValue> {
-        let db_name = self.name.clone();
-        let max_export_size = self.max_export_size_bytes;
-
-        log::info!("[EXPORT] ===== Step 1: Acquiring lock");
-
-        // Acquire lock FIRST to serialize operations
-        let _guard = weblocks::acquire(&db_name, weblocks::AcquireOptions::exclusive()).await?;
-        log::info!("[EXPORT] ===== Step 2: Lock acquired");
-
-        // Get storage and sync AFTER lock - this ensures only one export syncs at a time
+    #[wasm_bindgen(js_name = "isLeader")]
+    pub async fn is_leader_wasm(&self) -> Result<JsValue, JsValue> {
+        // Get the storage from STORAGE_REGISTRY
         use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
-        log::info!("[EXPORT] ===== Step 3: Getting storage");
-        let storage_rc = get_storage_with_fallback(&db_name).ok_or_else(|| {
-            JsValue::from_str(&format!("Storage not found for database: {}", db_name))
-        })?;
-        log::info!("[EXPORT] ===== Step 4: Got storage, reloading cache");
 
-        // Reload cache from GLOBAL_STORAGE
-        #[cfg(target_arch = "wasm32")]
-        {
-            storage_rc.reload_cache_from_global_storage();
-        }
+        let db_name = &self.name;
+        log::debug!("isLeader() called for database: {} (self.name)", db_name);
 
-        // CRITICAL: Checkpoint WAL to flush SQLite data to VFS blocks before export
-        // Without this, data stays in SQLite's WAL buffer and doesn't appear in exported bytes
-        log::info!("[EXPORT] ===== Step 5: Checkpointing WAL");
-        if !self.connection_state.db.get().is_null() {
-            // Use raw SQLite call since export_to_file takes &self, not &mut self
-            use std::ffi::CString;
-            let pragma = CString::new("PRAGMA wal_checkpoint(PASSIVE)").unwrap();
-            unsafe {
-                let mut stmt = std::ptr::null_mut();
-                let rc = sqlite_wasm_rs::sqlite3_prepare_v2(
-                    self.connection_state.db.get(),
-                    pragma.as_ptr(),
-                    -1,
-                    &mut stmt,
-                    std::ptr::null_mut(),
-                );
-                if rc == sqlite_wasm_rs::SQLITE_OK && !stmt.is_null() {
-                    sqlite_wasm_rs::sqlite3_step(stmt);
-                    sqlite_wasm_rs::sqlite3_finalize(stmt);
-                    log::info!("[EXPORT] WAL checkpoint completed");
-                } else {
-                    log::warn!("[EXPORT] WAL checkpoint failed with rc: {}", rc);
-                }
-            }
-        }
+        let storage_rc = get_storage_with_fallback(db_name);
 
-        log::info!("[EXPORT] ===== Step 6: Starting sync");
-        // Sync to ensure all data is persisted before export
-        storage_rc
-            .sync()
-            .await
-            .map_err(|e| JsValue::from_str(&format!("Sync failed: {}", e)))?;
-        log::info!("[EXPORT] ===== Step 7: Sync complete");
+        if let Some(storage) = storage_rc {
+            log::debug!("Found storage for {}, calling is_leader()", db_name);
+            let is_leader = with_storage_async!(storage, "is_leader_wasm", |s| s.is_leader())
+                .ok_or_else(|| {
+                    JsValue::from_str(&format!(
+                        "Failed to access storage for database: {}",
+                        db_name
+                    ))
+                })?;
+            log::debug!("is_leader() = {} for {}", is_leader, db_name);
 
-        // Export with configured size limit
-        log::info!("[EXPORT] Calling export_database_to_bytes");
-        let db_bytes = {
-            let storage = &*storage_rc;
-            crate::storage::export::export_database_to_bytes(storage, max_export_size)
-                .await
-                .map_err(|e| {
-                    log::error!("[EXPORT] Export failed: {}", e);
-                    JsValue::from_str(&format!("Export failed: {}", e))
-                })?
-        };
+            // Return as JsValue boolean
+            Ok(JsValue::from_bool(is_leader))
+        } else {
+            log::error!("ERROR: No storage found for database: {}", db_name);
+            Err(JsValue::from_str(&format!(
+                "No storage found for database: {}",
+                db_name
+            )))
+        }
+    }
 
-        log::info!("[EXPORT] Export complete: {} bytes", db_bytes.len());
+    /// Check if this instance is the leader (non-wasm version for internal use/tests)
+    pub async fn is_leader(&self) -> Result<bool, JsValue> {
+        let result = self.is_leader_wasm().await?;
+        Ok(result.as_bool().unwrap_or(false))
+    }
 
-        let uint8_array = js_sys::Uint8Array::new_with_length(db_bytes.len() as u32);
-        uint8_array.copy_from(&db_bytes);
+    #[wasm_bindgen(js_name = "onDataChange")]
+    pub fn on_data_change_wasm(&mut self, callback: &js_sys::Function) -> Result<(), JsValue> {
+        log::debug!("Registering onDataChange callback for {}", self.name);
 
-        Ok(uint8_array)
-    }
+        // Store the callback
+        self.on_data_change_callback = Some(callback.clone());
 
-    /// Test method for concurrent locking - simple increment counter
-    #[wasm_bindgen(js_name = "testLock")]
-    pub async fn test_lock(&self, value: u32) -> Result<u32, JsValue> {
-        let lock_name = format!("{}.lock_test", self.name);
+        // Register listener for BroadcastChannel notifications from other tabs
+        use crate::storage::broadcast_notifications::register_change_listener;
 
-        log::info!(
-            "[LOCK-TEST] Acquiring lock: {} with value: {}",
-            lock_name,
-            value
-        );
-        let _guard = weblocks::acquire(&lock_name, weblocks::AcquireOptions::exclusive()).await?;
-        log::info!("[LOCK-TEST] Lock acquired, processing value: {}", value);
+        let db_name = &self.name;
+        register_change_listener(db_name, callback).map_err(|e| {
+            JsValue::from_str(&format!("Failed to register change listener: {}", e))
+        })?;
 
-        // Simulate some work
-        let result = value + 1;
+        log::debug!("onDataChange callback registered for {}", self.name);
+        Ok(())
+    }
 
-        // Small delay to test serialization
-        let delay_promise = js_sys::Promise::new(&mut |resolve, _reject| {
-            let window = web_sys::window().unwrap();
-            let _ = window
-                .set_timeout_with_callback_and_timeout_and_arguments_0(resolve.unchecked_ref(), 10);
-        });
-        wasm_bindgen_futures::JsFuture::from(delay_promise).await?;
+    /// Register a callback for leadership transitions
+    ///
+    /// The callback receives `{ isLeader, leaderId }` whenever this instance gains or
+    /// loses leadership, including when its lease expires without a new election.
+    /// `leaderId` is empty when no instance currently holds the lease.
+    #[wasm_bindgen(js_name = "onLeadershipChange")]
+    pub fn on_leadership_change(&mut self, callback: &js_sys::Function) -> Result<(), JsValue> {
+        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
 
-        log::info!(
-            "[LOCK-TEST] Lock releasing for: {} with result: {}",
-            lock_name,
-            result
-        );
-        Ok(result)
+        let storage = get_storage_with_fallback(&self.name).ok_or_else(|| {
+            JsValue::from_str(&format!("No storage found for database: {}", self.name))
+        })?;
+        storage
+            .set_leadership_callback(Some(callback.clone()))
+            .map_err(|e| {
+                JsValue::from_str(&format!("Failed to register leadership listener: {}", e))
+            })?;
+        self.on_leadership_change_callback = Some(callback.clone());
+
+        log::debug!("onLeadershipChange callback registered for {}", self.name);
+        Ok(())
     }
 
-    /// Import SQLite database from .db file bytes
+    /// Register a callback for syncs that fail because IndexedDB is out of quota
     ///
-    /// Replaces the current database contents with the imported data.
-    /// This will close the current database connection and clear all existing data.
+    /// The callback receives `{ code, message, dirtyBlocks }` where `code` is
+    /// `"STORAGE_QUOTA_EXCEEDED"` and `dirtyBlocks` is the number of blocks that could
+    /// not be written. The failed `sync` still rejects with the same error, but nothing
+    /// is lost: the writes stay pending and the next successful sync persists them.
+    /// Pass `null` to remove the callback.
     ///
-    /// # Arguments
-    /// * `file_data` - SQLite .db file as Uint8Array
+    /// # Example
+    /// ```javascript
+    /// db.onStorageFull(({ dirtyBlocks }) => {
+    ///   banner.show(`Storage is full; ${dirtyBlocks} blocks are waiting to be saved`);
+    /// });
+    /// ```
+    #[wasm_bindgen(js_name = "onStorageFull")]
+    pub fn on_storage_full(&mut self, callback: Option<js_sys::Function>) {
+        self.on_storage_full_callback = callback;
+    }
+
+    /// Register a callback for sync and backpressure events
     ///
-    /// # Returns
-    /// * `Ok(())` - Import successful
-    /// * `Err(JsValue)` - Import failed (invalid file, validation error, etc.)
+    /// The callback receives `{ type, dirtyCount, durationMs }` where `type` is
+    /// `"sync_start"`, `"sync_success"`, `"sync_failure"` or `"backpressure"`.
+    /// `dirtyCount` is the number of blocks being synced (or dirty, for backpressure) and
+    /// `durationMs` is how long a finished sync took. Failures also carry `error` and
+    /// backpressure events `reason`. The callback is shared by every instance open on
+    /// this database in this tab; pass `null` to remove it.
     ///
     /// # Example
     /// ```javascript
-    /// // From file input
-    /// const fileInput = document.getElementById('dbFile');
-    /// const file = fileInput.files[0];
-    /// const arrayBuffer = await file.arrayBuffer();
-    /// const uint8Array = new Uint8Array(arrayBuffer);
+    /// db.onStorageEvent((event) => {
+    ///   if (event.type === 'sync_start') indicator.show('Saving…');
+    ///   if (event.type === 'sync_success') indicator.hide();
+    ///   if (event.type === 'sync_failure') indicator.error(event.error);
+    /// });
+    /// ```
+    #[wasm_bindgen(js_name = "onStorageEvent")]
+    pub fn on_storage_event(&self, callback: Option<js_sys::Function>) -> Result<(), JsValue> {
+        let storage =
+            crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name).ok_or_else(|| {
+                JsValue::from_str(&format!("No storage found for database: {}", self.name))
+            })?;
+        storage.set_storage_event_callback(callback);
+        Ok(())
+    }
+
+    /// Discard all in-memory state for this database and re-read it from IndexedDB
     ///
-    /// await db.importFromFile(uint8Array);
+    /// A heavier hammer than `reloadFromIndexedDB`: the block cache, unsynced dirty
+    /// blocks and the shared in-memory copy are all dropped before restoring, and every
+    /// restored block is checked against its persisted checksum. Use it to tell whether
+    /// a problem lives in memory or in IndexedDB. Unsynced writes are lost, and it fails
+    /// while a transaction is open.
     ///
-    /// // Database is immediately usable after import (no reopen needed)
-    /// const result = await db.execute('SELECT * FROM imported_table');
+    /// # Example
+    /// ```javascript
+    /// const report = await db.reloadFromStorage();
+    /// if (report.corruptBlocks.length > 0) {
+    ///   console.error('Persisted data is corrupt:', report.corruptBlocks);
+    /// }
     /// ```
-    ///
-    /// # Warning
-    /// This operation is destructive and will replace all existing database data.
-    #[wasm_bindgen(js_name = "importFromFile")]
-    pub async fn import_from_file(&mut self, file_data: js_sys::Uint8Array) -> Result<(), JsValue> {
-        log::info!("[IMPORT] Starting import with lock for: {}", self.name);
+    #[wasm_bindgen(js_name = "reloadFromStorage")]
+    pub async fn reload_from_storage(&mut self) -> Result<JsValue, JsValue> {
+        if self.is_memory() {
+            return Err(JsValue::from_str(
+                "Reload failed: memory databases have no persisted storage",
+            ));
+        }
         let db_name = self.name.clone();
-        let data = file_data.to_vec();
-
-        // Acquire lock FIRST to serialize operations
         let _guard = weblocks::acquire(&db_name, weblocks::AcquireOptions::exclusive()).await?;
-        log::info!("[IMPORT] Lock acquired for: {}", db_name);
-
-        log::debug!("Import data size: {} bytes", data.len());
-
-        // CRITICAL: Force-close database connection BEFORE import
-        // Must use force_close to remove from connection pool, not just decrement ref_count
-        // Otherwise new Database instances will reuse stale SQLite connection
-        log::debug!("Force-closing database connection before import");
+        let report = self
+            .reload_from_storage_internal()
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Reload failed: {}", e)))?;
+        serde_wasm_bindgen::to_value(&report).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-        // First do normal close to cleanup leader election etc
-        self.close_internal()
+    /// Run a read-only query against the last synced (committed) state of the database
+    ///
+    /// Reads the blocks committed in IndexedDB into a private copy and queries that, so a
+    /// follower can build a consistent report while the leader keeps writing; nothing is
+    /// locked and writes that were not synced yet are not seen. Statements that write are
+    /// rejected. `params` may be omitted for statements without placeholders.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const report = await db.queryAtSnapshot(
+    ///   'SELECT status, count(*) AS n FROM orders WHERE created_at >= ? GROUP BY status',
+    ///   [{ type: 'Integer', value: since }],
+    /// );
+    /// ```
+    #[wasm_bindgen(js_name = "queryAtSnapshot")]
+    pub async fn query_at_snapshot(&self, sql: &str, params: JsValue) -> Result<JsValue, JsValue> {
+        let params: Vec<ColumnValue> = if params.is_undefined() || params.is_null() {
+            Vec::new()
+        } else {
+            crate::result_format::params_from_js(params)
+                .map_err(|e| JsValue::from_str(&format!("Invalid parameters: {}", e)))?
+        };
+        let result = self
+            .query_at_snapshot_internal(sql, &params)
             .await
-            .map_err(|e| JsValue::from_str(&format!("Failed to close before import: {}", e)))?;
+            .map_err(|e| JsValue::from_str(&format!("Snapshot query failed: {}", e)))?;
+        crate::result_format::tagged_result_to_js(&result)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-        // Then force-remove from connection pool
-        let pool_key = self.name.trim_end_matches(".db");
-        crate::connection_pool::force_close_connection(pool_key);
+    /// Reload data from IndexedDB into memory
+    /// Call this when another tab has written data and you need to see the changes
+    /// This closes and reopens the SQLite connection to invalidate its page cache
+    #[wasm_bindgen(js_name = "reloadFromIndexedDB")]
+    pub async fn reload_from_indexed_db(&mut self) -> Result<(), JsValue> {
+        log::info!("Reloading data from IndexedDB for {}", self.name);
 
-        // Mark our connection as null since we force-closed it
+        let db_name = self.name.clone();
+
+        // Step 1: Close the SQLite connection to invalidate page cache
+        self.finalize_statement_cache();
+        self.finalize_sessions();
+        let pool_key = self.pool_key();
+        crate::connection_pool::force_close_connection(&pool_key);
         self.connection_state.db.set(std::ptr::null_mut());
-        log::debug!("Removed connection from pool for import");
+        log::info!("[RELOAD] Closed SQLite connection for {}", db_name);
 
-        // Call the import function with full name (WITH .db)
-        crate::storage::import::import_database_from_bytes(&db_name, data)
+        // Step 2: Restore from IndexedDB into global storage (force reload)
+        crate::storage::wasm_indexeddb::restore_from_indexeddb_force(&db_name)
             .await
-            .map_err(|e| {
-                log::error!("Import failed for {}: {}", db_name, e);
-                JsValue::from_str(&format!("Import failed: {}", e))
-            })?;
-
-        log::info!("[IMPORT] Import complete for: {}", db_name);
+            .map_err(|e| JsValue::from_str(&format!("Failed to restore from IndexedDB: {}", e)))?;
+        log::info!("[RELOAD] Restored from IndexedDB for {}", db_name);
 
-        // Reopen the SQLite connection so this Database instance is usable after import
-        // The VFS should already exist from when the Database was first created
-        log::info!("[IMPORT] Reopening connection for: {}", db_name);
+        // Step 3: Reload BlockStorage cache from global storage
+        use crate::vfs::indexeddb_vfs::STORAGE_REGISTRY;
+        STORAGE_REGISTRY.with(|reg| unsafe {
+            let registry = &*reg.get();
+            if let Some(storage_rc) = registry.get(&db_name) {
+                storage_rc.reload_cache_from_global_storage();
+                log::info!("[RELOAD] Reloaded BlockStorage cache for {}", db_name);
+            }
+        });
 
+        // Step 4: Reopen the SQLite connection
         use std::ffi::CString;
 
         let vfs_name = format!("vfs_{}", db_name.trim_end_matches(".db"));
-        let pool_key = db_name.trim_end_matches(".db").to_string();
+        let open_flags = Self::open_flags(self.read_only);
         let db_name_for_closure = db_name.clone();
         let vfs_name_for_closure = vfs_name.clone();
 
@@ -2509,7 +7504,7 @@ impl Database {
                 .map_err(|_| "Invalid VFS name".to_string())?;
 
             log::info!(
-                "[IMPORT] Reopening database: {} with VFS: {}",
+                "[RELOAD] Reopening database: {} with VFS: {}",
                 db_name_for_closure,
                 vfs_name_for_closure
             );
@@ -2518,7 +7513,7 @@ impl Database {
                 sqlite_wasm_rs::sqlite3_open_v2(
                     db_name_cstr.as_ptr(),
                     &mut db as *mut _,
-                    sqlite_wasm_rs::SQLITE_OPEN_READWRITE | sqlite_wasm_rs::SQLITE_OPEN_CREATE,
+                    open_flags,
                     vfs_cstr.as_ptr(),
                 )
             };
@@ -2534,612 +7529,1246 @@ impl Database {
                         "Unknown error".to_string()
                     }
                 };
-                return Err(format!(
-                    "Failed to reopen database after import: {}",
-                    err_msg
-                ));
+                return Err(format!("Failed to reopen database: {}", err_msg));
             }
 
-            log::info!("[IMPORT] Database reopened successfully");
+            log::info!("[RELOAD] Database reopened successfully");
             Ok(db)
         })
-        .map_err(|e| {
-            JsValue::from_str(&format!("Failed to reopen connection after import: {}", e))
-        })?;
+        .map_err(|e| JsValue::from_str(&format!("Failed to reopen connection: {}", e)))?;
 
-        // Update our connection state to use the new connection
         self.connection_state = new_state;
-        log::info!("[IMPORT] Connection state updated for: {}", db_name);
+        if !self.subscriptions.borrow().is_empty() {
+            unsafe { crate::storage::subscriptions::install_update_hook(self.db()) };
+        }
+        if let Some(callback) = &self.busy_handler {
+            crate::busy_handler::install(self.db(), Some(callback)).map_err(|e| {
+                JsValue::from_str(&format!("Failed to reinstall busy handler: {}", e))
+            })?;
+        }
+        if let Some(callback) = &self.authorizer {
+            crate::authorizer::install(self.db(), Some(callback)).map_err(|e| {
+                JsValue::from_str(&format!("Failed to reinstall authorizer: {}", e))
+            })?;
+        }
+        log::info!("[RELOAD] Connection state updated for {}", db_name);
 
         Ok(())
     }
 
-    /// Wait for this instance to become leader
-    #[wasm_bindgen(js_name = "waitForLeadership")]
-    pub async fn wait_for_leadership(&mut self) -> Result<(), JsValue> {
-        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
-
-        // Track leader election attempt
-        #[cfg(feature = "telemetry")]
-        if let Some(ref metrics) = self.metrics {
-            metrics.leader_election_attempts_total().inc();
-        }
-
-        let db_name = &self.name;
-        let start_time = js_sys::Date::now();
-
-        let timeout_ms = 5000.0; // 5 second timeout
+    /// Enable or disable optimistic updates mode
+    #[wasm_bindgen(js_name = "enableOptimisticUpdates")]
+    pub async fn enable_optimistic_updates(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.optimistic_updates_manager
+            .borrow_mut()
+            .set_enabled(enabled);
+        log::debug!(
+            "Optimistic updates {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+        Ok(())
+    }
 
-        loop {
-            let storage_rc = get_storage_with_fallback(db_name);
+    /// Check if optimistic mode is enabled
+    #[wasm_bindgen(js_name = "isOptimisticMode")]
+    pub async fn is_optimistic_mode(&self) -> bool {
+        self.optimistic_updates_manager.borrow().is_enabled()
+    }
 
-            if let Some(storage) = storage_rc {
-                let is_leader =
-                    match with_storage_async!(storage, "wait_for_leadership", |s| s.is_leader()) {
-                        Some(v) => v,
-                        None => continue,
-                    };
+    /// Track an optimistic write
+    #[wasm_bindgen(js_name = "trackOptimisticWrite")]
+    pub async fn track_optimistic_write(&mut self, sql: String) -> Result<String, JsValue> {
+        let id = self
+            .optimistic_updates_manager
+            .borrow_mut()
+            .track_write(sql);
+        Ok(id)
+    }
 
-                if is_leader {
-                    log::info!("Became leader for {}", db_name);
+    /// Get count of pending writes
+    #[wasm_bindgen(js_name = "getPendingWritesCount")]
+    pub async fn get_pending_writes_count(&self) -> usize {
+        self.optimistic_updates_manager.borrow().get_pending_count()
+    }
 
-                    // Record telemetry on successful leadership acquisition
-                    #[cfg(feature = "telemetry")]
-                    if let Some(ref metrics) = self.metrics {
-                        let duration_ms = js_sys::Date::now() - start_time;
-                        metrics.leader_election_duration().observe(duration_ms);
-                        metrics.is_leader().set(1.0);
-                        metrics.leadership_changes_total().inc();
-                    }
+    /// Clear all optimistic writes
+    #[wasm_bindgen(js_name = "clearOptimisticWrites")]
+    pub async fn clear_optimistic_writes(&mut self) -> Result<(), JsValue> {
+        self.optimistic_updates_manager.borrow_mut().clear_all();
+        Ok(())
+    }
 
-                    return Ok(());
-                }
-            }
+    /// Enable or disable coordination metrics tracking
+    #[wasm_bindgen(js_name = "enableCoordinationMetrics")]
+    pub async fn enable_coordination_metrics(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.coordination_metrics_manager
+            .borrow_mut()
+            .set_enabled(enabled);
+        Ok(())
+    }
 
-            // Check timeout
-            if js_sys::Date::now() - start_time > timeout_ms {
-                // Record telemetry on timeout
-                #[cfg(feature = "telemetry")]
-                if let Some(ref metrics) = self.metrics {
-                    let duration_ms = js_sys::Date::now() - start_time;
-                    metrics.leader_election_duration().observe(duration_ms);
-                }
+    /// Check if coordination metrics tracking is enabled
+    #[wasm_bindgen(js_name = "isCoordinationMetricsEnabled")]
+    pub async fn is_coordination_metrics_enabled(&self) -> bool {
+        self.coordination_metrics_manager.borrow().is_enabled()
+    }
 
-                return Err(JsValue::from_str("Timeout waiting for leadership"));
-            }
+    /// Record a leadership change
+    #[wasm_bindgen(js_name = "recordLeadershipChange")]
+    pub async fn record_leadership_change(&mut self, became_leader: bool) -> Result<(), JsValue> {
+        self.coordination_metrics_manager
+            .borrow_mut()
+            .record_leadership_change(became_leader);
+        Ok(())
+    }
 
-            // Wait a bit before checking again
-            let promise = js_sys::Promise::new(&mut |resolve, _| {
-                let window = web_sys::window().expect("should have window");
-                let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 100);
-            });
-            let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
-        }
+    /// Record a notification latency in milliseconds
+    #[wasm_bindgen(js_name = "recordNotificationLatency")]
+    pub async fn record_notification_latency(&mut self, latency_ms: f64) -> Result<(), JsValue> {
+        self.coordination_metrics_manager
+            .borrow_mut()
+            .record_notification_latency(latency_ms);
+        Ok(())
     }
 
-    /// Request leadership (triggers re-election check)
-    #[wasm_bindgen(js_name = "requestLeadership")]
-    pub async fn request_leadership(&mut self) -> Result<(), JsValue> {
-        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+    /// Record a write conflict (non-leader write attempt)
+    #[wasm_bindgen(js_name = "recordWriteConflict")]
+    pub async fn record_write_conflict(&mut self) -> Result<(), JsValue> {
+        self.coordination_metrics_manager
+            .borrow_mut()
+            .record_write_conflict();
+        Ok(())
+    }
 
-        let db_name = &self.name;
-        log::debug!("Requesting leadership for {}", db_name);
+    /// Record a follower refresh
+    #[wasm_bindgen(js_name = "recordFollowerRefresh")]
+    pub async fn record_follower_refresh(&mut self) -> Result<(), JsValue> {
+        self.coordination_metrics_manager
+            .borrow_mut()
+            .record_follower_refresh();
+        Ok(())
+    }
 
-        // Record telemetry data before the request
-        #[cfg(feature = "telemetry")]
-        let telemetry_data = if self.metrics.is_some() {
-            let start_time = js_sys::Date::now();
-            let was_leader = self
-                .is_leader_wasm()
-                .await
-                .ok()
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false);
+    /// Check that cross-tab notifications work in this browser
+    ///
+    /// Round-trips a ping through BroadcastChannel and reports the latency, or why it
+    /// failed (default timeout 1000 ms). A healthy latency is also recorded in the
+    /// coordination metrics when they are enabled.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const health = await db.testNotificationChannel();
+    /// if (!health.healthy) {
+    ///   console.warn('Other tabs will not see changes:', health.error);
+    /// }
+    /// ```
+    #[wasm_bindgen(js_name = "testNotificationChannel")]
+    pub async fn test_notification_channel(
+        &self,
+        timeout_ms: Option<u32>,
+    ) -> Result<JsValue, JsValue> {
+        let health = self
+            .test_notification_channel_internal(timeout_ms.unwrap_or(1000))
+            .await;
+        serde_wasm_bindgen::to_value(&health).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-            if let Some(ref metrics) = self.metrics {
-                metrics.leader_elections_total().inc();
-            }
+    /// Get coordination metrics as JSON string
+    #[wasm_bindgen(js_name = "getCoordinationMetrics")]
+    pub async fn get_coordination_metrics(&self) -> Result<String, JsValue> {
+        self.coordination_metrics_manager
+            .borrow()
+            .get_metrics_json()
+            .map_err(|e| JsValue::from_str(&e))
+    }
 
-            Some((start_time, was_leader))
+    /// Reset all coordination metrics
+    #[wasm_bindgen(js_name = "resetCoordinationMetrics")]
+    pub async fn reset_coordination_metrics(&mut self) -> Result<(), JsValue> {
+        self.coordination_metrics_manager.borrow_mut().reset();
+        Ok(())
+    }
+
+    /// Subscribe to a live query
+    ///
+    /// The callback receives the initial result and a fresh `QueryResult` whenever a write
+    /// through this Database changes a table the query reads. Returns an unsubscribe function.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const unsubscribe = await db.subscribe(
+    ///   'SELECT * FROM todos WHERE done = ?',
+    ///   [{ type: 'Integer', value: 0 }],
+    ///   (result) => render(result.rows),
+    /// );
+    /// // later
+    /// unsubscribe();
+    /// ```
+    #[wasm_bindgen]
+    pub async fn subscribe(
+        &mut self,
+        sql: &str,
+        params: JsValue,
+        callback: &js_sys::Function,
+    ) -> Result<js_sys::Function, JsValue> {
+        let params: Vec<ColumnValue> = if params.is_undefined() || params.is_null() {
+            Vec::new()
         } else {
-            None
+            crate::result_format::params_from_js(params)
+                .map_err(|e| JsValue::from_str(&format!("Invalid parameters: {}", e)))?
         };
 
-        let storage_rc = get_storage_with_fallback(db_name);
-
-        if let Some(storage) = storage_rc {
-            {
-                // Trigger leader election
-                let result = with_storage_async!(storage, "request_leadership", |s| s
-                    .start_leader_election())
-                .ok_or_else(|| {
-                    JsValue::from_str("Failed to acquire storage lock for leadership request")
-                })?;
-                result.map_err(|e| {
-                    JsValue::from_str(&format!("Failed to request leadership: {}", e))
-                })?;
+        let id = self
+            .subscribe_internal(sql, params, callback.clone())
+            .map_err(|e| JsValue::from_str(&format!("Subscribe failed: {}", e)))?;
+
+        let subscriptions = self.subscriptions.clone();
+        let unsubscribe = Closure::wrap(Box::new(move || {
+            subscriptions.borrow_mut().remove(id);
+        }) as Box<dyn FnMut()>);
+        Ok(unsubscribe.into_js_value().unchecked_into())
+    }
 
-                log::debug!("Re-election triggered for {}", db_name);
-            } // Drop the borrow here
+    /// Get the tables a SQL statement reads
+    ///
+    /// Returns a sorted array of table names without executing the statement.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const tables = db.getReferencedTables('SELECT * FROM todos JOIN users USING (user_id)');
+    /// // ['todos', 'users']
+    /// ```
+    #[wasm_bindgen(js_name = "getReferencedTables")]
+    pub fn get_referenced_tables(&self, sql: &str) -> Result<js_sys::Array, JsValue> {
+        let tables = self
+            .referenced_tables_internal(sql)
+            .map_err(|e| JsValue::from_str(&format!("Failed to get referenced tables: {}", e)))?;
 
-            // Record telemetry after election (after dropping borrow)
-            #[cfg(feature = "telemetry")]
-            if let Some((start_time, was_leader)) = telemetry_data {
-                if let Some(ref metrics) = self.metrics {
-                    // Record election duration
-                    let duration_ms = js_sys::Date::now() - start_time;
-                    metrics.leader_election_duration().observe(duration_ms);
+        let js_array = js_sys::Array::new();
+        for table in &tables {
+            js_array.push(&JsValue::from_str(table));
+        }
+        Ok(js_array)
+    }
 
-                    // Check if leadership status changed
-                    let is_leader_now = self
-                        .is_leader_wasm()
-                        .await
-                        .ok()
-                        .and_then(|v| v.as_bool())
-                        .unwrap_or(false);
+    /// Stream a SELECT in batches of `batchSize` rows
+    ///
+    /// Returns an async iterable cursor yielding `QueryResult` chunks. Other queries on
+    /// this Database fail with `CURSOR_BUSY` until the cursor is exhausted or closed.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const cursor = await db.queryStream('SELECT * FROM events', 1000);
+    /// for await (const batch of cursor) {
+    ///   render(batch.rows);
+    /// }
+    /// ```
+    #[wasm_bindgen(js_name = "queryStream")]
+    pub async fn query_stream(&mut self, sql: &str, batch_size: u32) -> Result<JsValue, JsValue> {
+        let cursor = self
+            .query_stream_internal(sql, batch_size as usize)
+            .map_err(|e| JsValue::from_str(&format!("Query stream failed: {}", e)))?;
+        let cursor = JsValue::from(cursor);
+        crate::query_cursor::install_async_iterator(&cursor)?;
+        Ok(cursor)
+    }
 
-                    // Update is_leader gauge
-                    metrics
-                        .is_leader()
-                        .set(if is_leader_now { 1.0 } else { 0.0 });
+    /// Stream a SELECT to a `MessagePort`, so a Web Worker can process the rows
+    ///
+    /// Each batch of `batchSize` rows is posted as `{ type: 'rows', columns, rows }` with
+    /// every row a plain array; BLOB buffers are transferred rather than copied. The
+    /// stream ends with `{ type: 'done', rowCount }`, or `{ type: 'error', message }` if
+    /// the query fails part way. The returned promise resolves to the row count. The main
+    /// thread is yielded between batches; other queries on this Database fail with
+    /// `CURSOR_BUSY` until the stream ends.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const { port1, port2 } = new MessageChannel();
+    /// worker.postMessage({ port: port2 }, [port2]);
+    /// await db.streamToPort('SELECT * FROM events', port1, 1000);
+    ///
+    /// // worker.js
+    /// onmessage = ({ data: { port } }) => {
+    ///   port.onmessage = ({ data }) => {
+    ///     if (data.type === 'rows') aggregate(data.rows);
+    ///   };
+    /// };
+    /// ```
+    #[wasm_bindgen(js_name = "streamToPort")]
+    pub fn stream_to_port(
+        &mut self,
+        sql: &str,
+        port: web_sys::MessagePort,
+        batch_size: u32,
+    ) -> Result<js_sys::Promise, JsValue> {
+        // Not async: the instance must stay usable while the cursor drains
+        let cursor = self
+            .query_stream_internal(sql, batch_size as usize)
+            .map_err(|e| JsValue::from_str(&format!("Query stream failed: {}", e)))?;
+
+        Ok(wasm_bindgen_futures::future_to_promise(async move {
+            let post_end = |kind: &str, key: &str, value: &JsValue| -> Result<(), JsValue> {
+                let message = js_sys::Object::new();
+                js_sys::Reflect::set(&message, &"type".into(), &JsValue::from_str(kind))?;
+                js_sys::Reflect::set(&message, &JsValue::from_str(key), value)?;
+                port.post_message(&message)
+            };
 
-                    // Track leadership changes
-                    if was_leader != is_leader_now {
-                        metrics.leadership_changes_total().inc();
+            let mut row_count = 0usize;
+            loop {
+                match cursor.next_batch() {
+                    Ok(Some(batch)) => {
+                        row_count += batch.rows.len();
+                        let (message, transfer) =
+                            crate::result_format::stream_batch_message(&batch)?;
+                        port.post_message_with_transferable(&message, &transfer)?;
+                        crate::storage::retry_logic::sleep_ms(0).await;
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        post_end("error", "message", &JsValue::from_str(&e.to_string()))?;
+                        return Err(JsValue::from_str(&format!("Query stream failed: {}", e)));
                     }
                 }
             }
+            post_end("done", "rowCount", &JsValue::from_f64(row_count as f64))?;
+            Ok(JsValue::from_f64(row_count as f64))
+        }))
+    }
 
-            Ok(())
-        } else {
-            Err(JsValue::from_str(&format!(
-                "No storage found for database: {}",
-                db_name
-            )))
-        }
+    /// Register a JavaScript function as a scalar SQL function
+    ///
+    /// `arity` is the number of arguments (-1 for any). Arguments arrive as plain JS values
+    /// (number, string, Uint8Array or null) and the return value is stored the same way.
+    /// Pass `deterministic: true` for pure functions so SQLite can use them in generated
+    /// columns, partial-index WHERE clauses and CHECK constraints. Errors thrown by the
+    /// callback fail the query that called it.
+    ///
+    /// # Example
+    /// ```javascript
+    /// db.createScalarFunction('slugify', 1, (s) => s.toLowerCase().replace(/\W+/g, '-'), true);
+    /// await db.execute("SELECT slugify('Hello World')");
+    /// ```
+    #[wasm_bindgen(js_name = "createScalarFunction")]
+    pub fn create_scalar_function(
+        &mut self,
+        name: &str,
+        arity: i32,
+        callback: js_sys::Function,
+        deterministic: Option<bool>,
+    ) -> Result<(), JsValue> {
+        self.create_scalar_function_internal(name, arity, callback, deterministic.unwrap_or(false))
+            .map_err(|e| JsValue::from_str(&format!("Failed to create function: {}", e)))
     }
 
-    /// Get leader information
-    #[wasm_bindgen(js_name = "getLeaderInfo")]
-    pub async fn get_leader_info(&mut self) -> Result<JsValue, JsValue> {
-        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
+    /// Register a JavaScript function as a SQL function
+    ///
+    /// Shorthand for `createScalarFunction(name, arity, callback)` with a
+    /// non-deterministic function. The callback is released when the database closes or
+    /// `removeFunction(name)` is called.
+    ///
+    /// # Example
+    /// ```javascript
+    /// db.createFunction('haversine', 4, (lat1, lon1, lat2, lon2) => distanceKm(lat1, lon1, lat2, lon2));
+    /// await db.execute('SELECT name FROM stores WHERE haversine(lat, lon, ?, ?) < 5');
+    /// ```
+    #[wasm_bindgen(js_name = "createFunction")]
+    pub fn create_function(
+        &mut self,
+        name: &str,
+        arity: i32,
+        callback: js_sys::Function,
+    ) -> Result<(), JsValue> {
+        self.create_scalar_function(name, arity, callback, None)
+    }
 
-        let db_name = &self.name;
+    /// Unregister a function created with `createFunction`, `createScalarFunction` or
+    /// `createAggregateFunction`, whatever its arity
+    ///
+    /// Queries that use it afterwards fail with `no such function`.
+    #[wasm_bindgen(js_name = "removeFunction")]
+    pub fn remove_function(&mut self, name: &str) -> Result<(), JsValue> {
+        self.remove_function_internal(name)
+            .map_err(|e| JsValue::from_str(&format!("Failed to remove function: {}", e)))
+    }
 
-        let storage_rc = get_storage_with_fallback(db_name);
+    /// Register JavaScript functions as an aggregate SQL function
+    ///
+    /// `stepFn(acc, ...args)` runs once per row and returns the new accumulator, which
+    /// starts as `undefined` for each group. `finalFn(acc)` turns the last accumulator
+    /// into the result; it also runs for empty groups with `acc` still `undefined`.
+    /// Errors thrown by either callback fail the query.
+    ///
+    /// # Example
+    /// ```javascript
+    /// db.createAggregateFunction(
+    ///   'median', 1,
+    ///   (acc, x) => (acc ?? []).concat(x === null ? [] : [x]),
+    ///   (acc) => {
+    ///     if (!acc?.length) return null;
+    ///     const s = acc.sort((a, b) => a - b), m = s.length >> 1;
+    ///     return s.length % 2 ? s[m] : (s[m - 1] + s[m]) / 2;
+    ///   },
+    /// );
+    /// await db.execute('SELECT dept, median(salary) FROM staff GROUP BY dept');
+    /// ```
+    #[wasm_bindgen(js_name = "createAggregateFunction")]
+    pub fn create_aggregate_function(
+        &mut self,
+        name: &str,
+        arity: i32,
+        step_fn: js_sys::Function,
+        final_fn: js_sys::Function,
+    ) -> Result<(), JsValue> {
+        self.create_aggregate_function_internal(name, arity, step_fn, final_fn)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create function: {}", e)))
+    }
 
-        if let Some(storage) = storage_rc {
-            let is_leader = with_storage_async!(storage, "get_leader_info", |s| s.is_leader())
-                .ok_or_else(|| {
-                    JsValue::from_str(&format!(
-                        "Failed to access storage for database: {}",
-                        db_name
-                    ))
-                })?;
+    /// Block allocation counts, to decide when a VACUUM is worthwhile
+    ///
+    /// Returns `{ allocated, deallocatedReusable, highestBlockId, fragmentationRatio }`,
+    /// where the ratio is the share of the block id range holding no allocated block.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const { fragmentationRatio } = await db.getAllocationStats();
+    /// if (fragmentationRatio > 0.3) {
+    ///   await db.vacuum();
+    /// }
+    /// ```
+    #[wasm_bindgen(js_name = "getAllocationStats")]
+    pub async fn get_allocation_stats(&self) -> Result<JsValue, JsValue> {
+        let storage =
+            crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name).ok_or_else(|| {
+                JsValue::from_str(&format!(
+                    "Allocation stats unavailable: no block storage for {}",
+                    self.name
+                ))
+            })?;
+        serde_wasm_bindgen::to_value(&storage.allocation_stats())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-            // Get leader info - we'll use simpler data for now
-            // Real implementation would need public getters on BlockStorage
-            let leader_id_str = if is_leader {
-                format!("leader_{}", db_name)
-            } else {
-                "unknown".to_string()
-            };
+    /// Reserve `count` blocks ahead of a large write, such as a bulk insert
+    ///
+    /// Extends the allocation map in one step and persists it once, so the write that
+    /// follows does not update allocation state block by block. Returns the number of
+    /// blocks reserved.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await db.reserveBlocks(Math.ceil(expectedBytes / 4096));
+    /// await db.insertMany('INSERT INTO events VALUES (?, ?)', rows);
+    /// ```
+    #[wasm_bindgen(js_name = "reserveBlocks")]
+    pub fn reserve_blocks(&self, count: u32) -> Result<u32, JsValue> {
+        if self.read_only {
+            return Err(JsValue::from_str(&format!(
+                "Failed to reserve blocks: {}",
+                Self::read_only_error()
+            )));
+        }
+        let storage =
+            crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name).ok_or_else(|| {
+                JsValue::from_str(&format!(
+                    "Failed to reserve blocks: no block storage for {}",
+                    self.name
+                ))
+            })?;
+        storage
+            .reserve_blocks(count as u64)
+            .map(|reserved| reserved.len() as u32)
+            .map_err(|e| JsValue::from_str(&format!("Failed to reserve blocks: {}", e)))
+    }
 
-            // Create JavaScript object
-            let obj = js_sys::Object::new();
-            js_sys::Reflect::set(&obj, &"isLeader".into(), &JsValue::from_bool(is_leader))?;
-            js_sys::Reflect::set(&obj, &"leaderId".into(), &JsValue::from_str(&leader_id_str))?;
-            js_sys::Reflect::set(
-                &obj,
-                &"leaseExpiry".into(),
-                &JsValue::from_f64(js_sys::Date::now()),
-            )?;
+    /// Hand memory held by caches back after heavy work, such as a large import
+    ///
+    /// Releases the SQLite page cache and, when `clearBlockCache` is true, drops clean
+    /// blocks from the block cache too. Nothing unsynced is discarded; the caches refill
+    /// as the database is used. Returns `{ pageCacheBytesFreed, blocksEvicted }`.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await db.importFromFile(bytes);
+    /// const { pageCacheBytesFreed } = await db.releaseMemory(true);
+    /// ```
+    #[wasm_bindgen(js_name = "releaseMemory")]
+    pub async fn release_memory(
+        &self,
+        clear_block_cache: Option<bool>,
+    ) -> Result<JsValue, JsValue> {
+        let stats = self
+            .release_memory_internal(clear_block_cache.unwrap_or(false))
+            .map_err(|e| JsValue::from_str(&format!("Failed to release memory: {}", e)))?;
+        serde_wasm_bindgen::to_value(&stats).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-            Ok(obj.into())
+    /// Convert a JS pragma argument: strings stay text, booleans become 1/0
+    fn pragma_value_from_js(value: &JsValue) -> Result<ColumnValue, JsValue> {
+        if let Some(b) = value.as_bool() {
+            Ok(ColumnValue::Integer(b as i64))
+        } else if let Some(n) = value.as_f64() {
+            if n.fract() == 0.0 && n.abs() <= types::MAX_SAFE_INTEGER as f64 {
+                Ok(ColumnValue::Integer(n as i64))
+            } else {
+                Ok(ColumnValue::Real(n))
+            }
+        } else if let Some(text) = value.as_string() {
+            Ok(ColumnValue::Text(text))
         } else {
-            Err(JsValue::from_str(&format!(
-                "No storage found for database: {}",
-                db_name
-            )))
+            Err(JsValue::from_str(
+                "Pragma values must be a string, number or boolean",
+            ))
         }
     }
 
-    /// Queue a write operation to be executed by the leader
+    /// Read a pragma as a parsed value
     ///
-    /// Non-leader tabs can use this to request writes from the leader.
-    /// The write is forwarded via BroadcastChannel and executed by the leader.
-    ///
-    /// # Arguments
-    /// * `sql` - SQL statement to execute (must be a write operation)
+    /// Single values come back as a boolean, number or string, `integrity_check` and
+    /// `quick_check` as `{ ok, errors }`, and table pragmas as an array of row objects.
+    /// Pass an argument for pragmas that take one, such as a table name.
     ///
-    /// # Returns
-    /// Result indicating success or failure
-    #[wasm_bindgen(js_name = "queueWrite")]
-    pub async fn queue_write(&mut self, sql: String) -> Result<(), JsValue> {
-        self.queue_write_with_timeout(sql, 5000).await
+    /// # Example
+    /// ```javascript
+    /// const fk = await db.pragma('foreign_keys');          // true
+    /// const columns = await db.pragma('table_info', 'users');
+    /// const { ok, errors } = await db.pragma('integrity_check');
+    /// ```
+    #[wasm_bindgen(js_name = "pragma")]
+    pub async fn pragma(&mut self, name: String, argument: JsValue) -> Result<JsValue, JsValue> {
+        let argument = if argument.is_null() || argument.is_undefined() {
+            None
+        } else {
+            Some(Self::pragma_value_from_js(&argument)?)
+        };
+        let value = self
+            .pragma_internal(&name, argument.as_ref())
+            .await
+            .map_err(|e| JsValue::from_str(&format!("PRAGMA {} failed: {}", name, e)))?;
+        crate::result_format::pragma_value_to_js(&value)
+            .map_err(|e| JsValue::from_str(&format!("PRAGMA {} failed: {}", name, e)))
     }
 
-    /// Queue a write operation with a specific timeout
+    /// Set a pragma and return what it reports back, parsed like `pragma()`
     ///
-    /// # Arguments
-    /// * `sql` - SQL statement to execute
-    /// * `timeout_ms` - Timeout in milliseconds
-    #[wasm_bindgen(js_name = "queueWriteWithTimeout")]
-    pub async fn queue_write_with_timeout(
-        &mut self,
-        sql: String,
-        timeout_ms: u32,
-    ) -> Result<(), JsValue> {
-        use crate::storage::write_queue::{WriteQueueMessage, WriteResponse, send_write_request};
-        use std::cell::RefCell;
-        use std::rc::Rc;
-
-        log::debug!("Queuing write: {}", sql);
-
-        // Check if we're the leader - if so, just execute directly
-        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
-        let is_leader = {
-            let storage_rc = get_storage_with_fallback(&self.name);
+    /// # Example
+    /// ```javascript
+    /// await db.setPragma('foreign_keys', true);
+    /// await db.setPragma('user_version', 3);
+    /// const mode = await db.setPragma('journal_mode', 'wal'); // 'wal'
+    /// ```
+    #[wasm_bindgen(js_name = "setPragma")]
+    pub async fn set_pragma(&mut self, name: String, value: JsValue) -> Result<JsValue, JsValue> {
+        let value = Self::pragma_value_from_js(&value)?;
+        let result = self
+            .set_pragma_internal(&name, &value)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("PRAGMA {} failed: {}", name, e)))?;
+        crate::result_format::pragma_value_to_js(&result)
+            .map_err(|e| JsValue::from_str(&format!("PRAGMA {} failed: {}", name, e)))
+    }
 
-            if let Some(storage) = storage_rc {
-                with_storage_async!(storage, "queue_write_is_leader", |s| s.is_leader())
-                    .unwrap_or(false)
-            } else {
-                false
-            }
-        };
+    /// Skip block checksums during a trusted bulk load
+    ///
+    /// Checksums normally catch corrupted blocks; while disabled, nothing written is
+    /// protected and reads are not verified. Call `recomputeChecksums()` (or
+    /// `disableChecksums(false)`) when the load finishes. A sync never persists blocks
+    /// without checksums: it recomputes them and re-enables checksums first.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await db.disableChecksums(true);
+    /// await db.executeBatch(bulkInserts);
+    /// await db.recomputeChecksums();
+    /// await db.sync();
+    /// ```
+    #[wasm_bindgen(js_name = "disableChecksums")]
+    pub async fn disable_checksums(&self, disabled: bool) -> Result<(), JsValue> {
+        let storage =
+            crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name).ok_or_else(|| {
+                JsValue::from_str(&format!(
+                    "Cannot change checksums: no block storage for {}",
+                    self.name
+                ))
+            })?;
+        storage.disable_checksums(disabled);
+        Ok(())
+    }
 
-        if is_leader {
-            log::debug!("We are leader, executing directly");
-            return self
-                .execute_internal(&sql)
-                .await
-                .map(|_| ())
-                .map_err(|e| JsValue::from_str(&format!("Execute failed: {}", e)));
-        }
+    /// Re-enable checksums and compute them for blocks written while they were disabled
+    ///
+    /// Returns the number of blocks that were given a checksum.
+    #[wasm_bindgen(js_name = "recomputeChecksums")]
+    pub async fn recompute_checksums(&self) -> Result<u32, JsValue> {
+        let storage =
+            crate::vfs::indexeddb_vfs::get_storage_with_fallback(&self.name).ok_or_else(|| {
+                JsValue::from_str(&format!(
+                    "Cannot recompute checksums: no block storage for {}",
+                    self.name
+                ))
+            })?;
+        Ok(storage.recompute_checksums() as u32)
+    }
 
-        // Send write request to leader
-        let request_id = send_write_request(&self.name, &sql)
-            .map_err(|e| JsValue::from_str(&format!("Failed to send write request: {}", e)))?;
+    /// Current size of the database in bytes
+    ///
+    /// Counts allocated blocks times the block size, the same measure `max_db_bytes` is
+    /// enforced against, so apps can warn users as they approach the limit. Memory
+    /// databases report `page_count * page_size`.
+    ///
+    /// # Example
+    /// ```javascript
+    /// if (await db.getDatabaseSize() > 0.9 * maxDbBytes) {
+    ///   showStorageWarning();
+    /// }
+    /// ```
+    #[wasm_bindgen(js_name = "getDatabaseSize")]
+    pub async fn get_database_size(&self) -> Result<f64, JsValue> {
+        self.database_size_bytes_internal()
+            .map(|bytes| bytes as f64)
+            .map_err(|e| JsValue::from_str(&format!("Failed to get database size: {}", e)))
+    }
 
-        log::debug!("Write request sent with ID: {}", request_id);
+    /// Rebuild the database file and reclaim unused space
+    ///
+    /// Works on databases created without `auto_vacuum`. Returns
+    /// `{ bytesBefore, bytesAfter, blocksFreed }`. The optional `onProgress(deleted, total)`
+    /// callback runs after every `progressInterval` freed blocks (default 100) are removed
+    /// from IndexedDB.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const { bytesBefore, bytesAfter } = await db.vacuum((done, total) => {
+    ///   console.log(`Reclaimed ${done}/${total} blocks`);
+    /// });
+    /// ```
+    #[wasm_bindgen(js_name = "vacuum")]
+    pub async fn vacuum(
+        &mut self,
+        on_progress: Option<js_sys::Function>,
+        progress_interval: Option<u32>,
+    ) -> Result<JsValue, JsValue> {
+        let stats = self
+            .vacuum_internal(on_progress, progress_interval.unwrap_or(100) as usize)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Vacuum failed: {}", e)))?;
+        serde_wasm_bindgen::to_value(&stats).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-        // Wait for response with timeout
-        let response_received = Rc::new(RefCell::new(false));
-        let response_error = Rc::new(RefCell::new(None::<String>));
+    /// Run `callback` in a transaction that commits when it resolves and rolls back when it
+    /// throws or rejects
+    ///
+    /// The callback takes no arguments and keeps using the same `db` object; its return
+    /// value is what the returned promise resolves to. Calls made inside another
+    /// `transaction` nest as savepoints. `options.behavior` may be `DEFERRED` (default),
+    /// `IMMEDIATE` or `EXCLUSIVE`.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await db.transaction(async () => {
+    ///   await db.execute("UPDATE accounts SET balance = balance - 10 WHERE id = 1");
+    ///   await db.execute("UPDATE accounts SET balance = balance + 10 WHERE id = 2");
+    /// }, { behavior: 'IMMEDIATE' });
+    /// ```
+    #[wasm_bindgen(js_name = "transaction")]
+    pub fn transaction(
+        &mut self,
+        callback: js_sys::Function,
+        options: JsValue,
+    ) -> Result<js_sys::Promise, JsValue> {
+        // Not async: the instance must not stay borrowed while the callback uses it
+        let options: TransactionOptions = if options.is_undefined() || options.is_null() {
+            TransactionOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|e| JsValue::from_str(&format!("Invalid transaction options: {}", e)))?
+        };
+        let scope = self.transaction_scope(&options);
+        let begin_leaders = self
+            .required_leaders(&scope.begin)
+            .map_err(|e| JsValue::from_str(&format!("Failed to begin transaction: {}", e)))?;
+        // Whether COMMIT needs leadership depends on what the callback writes
+        let commit_leaders =
+            if !scope.nested && self.coordinates_writes() && !self.allow_non_leader_writes {
+                vec![self.name.clone()]
+            } else {
+                Vec::new()
+            };
+        let connection_state = Rc::clone(&self.connection_state);
+        let db_name = self.name.clone();
+        let sync_on_commit =
+            !scope.nested && self.sync_mode == SyncMode::OnWrite && !self.is_memory();
 
-        let response_received_clone = response_received.clone();
-        let response_error_clone = response_error.clone();
-        let request_id_clone = request_id.clone();
+        Ok(wasm_bindgen_futures::future_to_promise(async move {
+            for leader in &begin_leaders {
+                Self::ensure_leader(leader).await.map_err(|e| {
+                    JsValue::from_str(&format!("Failed to begin transaction: {}", e))
+                })?;
+            }
+            Self::exec_on_connection(connection_state.db.get(), &scope.begin)
+                .map_err(|e| JsValue::from_str(&format!("Failed to begin transaction: {}", e)))?;
 
-        // Set up listener for response
-        let callback = Closure::wrap(Box::new(move |msg: JsValue| {
-            // Parse the message
-            if let Ok(json_str) = js_sys::JSON::stringify(&msg) {
-                if let Some(json_str) = json_str.as_string() {
-                    if let Ok(message) = serde_json::from_str::<WriteQueueMessage>(&json_str) {
-                        if let WriteQueueMessage::WriteResponse(response) = message {
-                            match response {
-                                WriteResponse::Success { request_id, .. } => {
-                                    if request_id == request_id_clone {
-                                        *response_received_clone.borrow_mut() = true;
-                                        log::debug!("Write response received: Success");
-                                    }
-                                }
-                                WriteResponse::Error {
-                                    request_id,
-                                    error_message,
-                                } => {
-                                    if request_id == request_id_clone {
-                                        *response_received_clone.borrow_mut() = true;
-                                        *response_error_clone.borrow_mut() = Some(error_message);
-                                        log::debug!("Write response received: Error");
-                                    }
-                                }
-                            }
-                        }
+            let outcome = match callback.call0(&JsValue::UNDEFINED) {
+                Ok(result) => {
+                    wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&result)).await
+                }
+                Err(e) => Err(e),
+            };
+            let db = connection_state.db.get();
+            let value = match outcome {
+                Ok(value) => value,
+                Err(e) => {
+                    Self::rollback_on_connection(db, &scope);
+                    return Err(e);
+                }
+            };
+            let wrote = !db.is_null()
+                && unsafe { sqlite_wasm_rs::sqlite3_txn_state(db, std::ptr::null()) }
+                    == sqlite_wasm_rs::SQLITE_TXN_WRITE;
+            if wrote {
+                for leader in &commit_leaders {
+                    if let Err(e) = Self::ensure_leader(leader).await {
+                        Self::rollback_on_connection(connection_state.db.get(), &scope);
+                        return Err(JsValue::from_str(&format!(
+                            "Failed to commit transaction: {}",
+                            e
+                        )));
                     }
                 }
             }
-        }) as Box<dyn FnMut(JsValue)>);
-
-        // Register listener
-        use crate::storage::write_queue::register_write_queue_listener;
-        let callback_fn = callback.as_ref().unchecked_ref();
-        register_write_queue_listener(&self.name, callback_fn)
-            .map_err(|e| JsValue::from_str(&format!("Failed to register listener: {}", e)))?;
-
-        // Keep callback alive
-        callback.forget();
-
-        // Wait for response with polling (timeout_ms)
-        let start_time = js_sys::Date::now();
-        let timeout_f64 = timeout_ms as f64;
-
-        loop {
-            // Check if response received
-            if *response_received.borrow() {
-                if let Some(error_msg) = response_error.borrow().as_ref() {
-                    return Err(JsValue::from_str(&format!("Write failed: {}", error_msg)));
-                }
-                log::info!("Write completed successfully");
-                return Ok(());
+            let db = connection_state.db.get();
+            if let Err(e) = Self::exec_on_connection(db, &scope.commit) {
+                Self::rollback_on_connection(db, &scope);
+                return Err(JsValue::from_str(&format!(
+                    "Failed to commit transaction: {}",
+                    e
+                )));
             }
-
-            // Check timeout
-            let elapsed = js_sys::Date::now() - start_time;
-            if elapsed > timeout_f64 {
-                return Err(JsValue::from_str("Write request timed out"));
+            if sync_on_commit {
+                Self::sync_shared_connection(&connection_state, &db_name)
+                    .await
+                    .map_err(|e| JsValue::from_str(&format!("Sync failed: {}", e)))?;
             }
+            Ok(value)
+        }))
+    }
 
-            // Wait a bit before checking again
-            wasm_bindgen_futures::JsFuture::from(js_sys::Promise::new(&mut |resolve, _reject| {
-                if let Some(window) = web_sys::window() {
-                    let _ =
-                        window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 100);
-                } else {
-                    log::error!("Window unavailable in timeout handler");
-                }
-            }))
+    /// Begin a transaction that stays open until `commit()` or `rollback()`
+    ///
+    /// `options.behavior` may be `DEFERRED` (default), `IMMEDIATE` or `EXCLUSIVE`.
+    /// Beginning while a transaction is open fails with `TRANSACTION_ALREADY_ACTIVE`.
+    /// Prefer `transaction(callback)` where the work fits in one callback: it cannot
+    /// leave a transaction open by mistake.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await db.beginTransaction({ behavior: 'IMMEDIATE' });
+    /// try {
+    ///   await db.execute("UPDATE accounts SET balance = balance - 10 WHERE id = 1");
+    ///   await db.execute("UPDATE accounts SET balance = balance + 10 WHERE id = 2");
+    ///   await db.commit();
+    /// } catch (e) {
+    ///   await db.rollback();
+    ///   throw e;
+    /// }
+    /// ```
+    #[wasm_bindgen(js_name = "beginTransaction")]
+    pub async fn begin_transaction(&mut self, options: JsValue) -> Result<(), JsValue> {
+        let options: TransactionOptions = if options.is_undefined() || options.is_null() {
+            TransactionOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|e| JsValue::from_str(&format!("Invalid transaction options: {}", e)))?
+        };
+        self.begin_transaction_internal(&options)
             .await
-            .ok();
-        }
+            .map_err(|e| JsValue::from_str(&format!("Failed to begin transaction: {}", e)))
     }
 
-    #[wasm_bindgen(js_name = "isLeader")]
-    pub async fn is_leader_wasm(&self) -> Result<JsValue, JsValue> {
-        // Get the storage from STORAGE_REGISTRY
-        use crate::vfs::indexeddb_vfs::get_storage_with_fallback;
-
-        let db_name = &self.name;
-        log::debug!("isLeader() called for database: {} (self.name)", db_name);
-
-        let storage_rc = get_storage_with_fallback(db_name);
-
-        if let Some(storage) = storage_rc {
-            log::debug!("Found storage for {}, calling is_leader()", db_name);
-            let is_leader = with_storage_async!(storage, "is_leader_wasm", |s| s.is_leader())
-                .ok_or_else(|| {
-                    JsValue::from_str(&format!(
-                        "Failed to access storage for database: {}",
-                        db_name
-                    ))
-                })?;
-            log::debug!("is_leader() = {} for {}", is_leader, db_name);
+    /// Commit the transaction started with `beginTransaction()`
+    ///
+    /// Fails with `NO_ACTIVE_TRANSACTION` when there is none. A follower tab holding
+    /// writes gets `WRITE_PERMISSION_DENIED` and should roll back.
+    #[wasm_bindgen(js_name = "commit")]
+    pub async fn commit(&mut self) -> Result<(), JsValue> {
+        self.commit_internal()
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to commit transaction: {}", e)))
+    }
 
-            // Return as JsValue boolean
-            Ok(JsValue::from_bool(is_leader))
-        } else {
-            log::error!("ERROR: No storage found for database: {}", db_name);
-            Err(JsValue::from_str(&format!(
-                "No storage found for database: {}",
-                db_name
-            )))
-        }
+    /// Roll back the transaction started with `beginTransaction()`
+    #[wasm_bindgen(js_name = "rollback")]
+    pub async fn rollback(&mut self) -> Result<(), JsValue> {
+        self.rollback_internal()
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to roll back transaction: {}", e)))
     }
 
-    /// Check if this instance is the leader (non-wasm version for internal use/tests)
-    pub async fn is_leader(&self) -> Result<bool, JsValue> {
-        let result = self.is_leader_wasm().await?;
-        Ok(result.as_bool().unwrap_or(false))
+    /// Whether a transaction started with `beginTransaction()` is open
+    #[wasm_bindgen(js_name = "isInTransaction")]
+    pub fn is_in_transaction_js(&self) -> bool {
+        self.is_in_transaction()
     }
 
-    #[wasm_bindgen(js_name = "onDataChange")]
-    pub fn on_data_change_wasm(&mut self, callback: &js_sys::Function) -> Result<(), JsValue> {
-        log::debug!("Registering onDataChange callback for {}", self.name);
+    /// Run `callback` under a named parent span that groups the operations it performs
+    ///
+    /// Query spans started while the callback is pending link to this span as their
+    /// parent, including queries that are awaited concurrently. Scopes nest: operations
+    /// link to the innermost open `withSpan`. The group span is recorded when the callback
+    /// settles, with an error status if it throws or rejects. Without a span recorder the
+    /// callback simply runs.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await db.withSpan('load_dashboard', async () => {
+    ///   await Promise.all([
+    ///     db.execute('SELECT * FROM widgets'),
+    ///     db.execute('SELECT * FROM alerts'),
+    ///   ]);
+    /// });
+    /// ```
+    #[cfg(feature = "telemetry")]
+    #[wasm_bindgen(js_name = "withSpan")]
+    pub fn with_span(
+        &mut self,
+        name: String,
+        callback: js_sys::Function,
+    ) -> Result<js_sys::Promise, JsValue> {
+        // Not async for the same reason as `transaction`: the callback uses this instance
+        let (Some(recorder), Some(context)) =
+            (self.span_recorder.clone(), self.span_context.clone())
+        else {
+            let result = callback.call0(&JsValue::UNDEFINED)?;
+            return Ok(js_sys::Promise::resolve(&result));
+        };
 
-        // Store the callback
-        self.on_data_change_callback = Some(callback.clone());
+        let mut span = crate::telemetry::SpanBuilder::new(name)
+            .with_scope(&context)
+            .with_baggage_from_context(&context)
+            .build();
+        context.enter_scope(span.span_id.clone());
 
-        // Register listener for BroadcastChannel notifications from other tabs
-        use crate::storage::broadcast_notifications::register_change_listener;
+        let result = match callback.call0(&JsValue::UNDEFINED) {
+            Ok(result) => result,
+            Err(e) => {
+                context.exit_scope(&span.span_id);
+                span.status = crate::telemetry::SpanStatus::Error(format!("{:?}", e));
+                span.end_time_ms = Some(js_sys::Date::now());
+                recorder.record_span(span);
+                return Err(e);
+            }
+        };
 
-        let db_name = &self.name;
-        register_change_listener(db_name, callback).map_err(|e| {
-            JsValue::from_str(&format!("Failed to register change listener: {}", e))
-        })?;
+        Ok(wasm_bindgen_futures::future_to_promise(async move {
+            let outcome =
+                wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&result)).await;
+            context.exit_scope(&span.span_id);
+            span.status = match &outcome {
+                Ok(_) => crate::telemetry::SpanStatus::Ok,
+                Err(e) => crate::telemetry::SpanStatus::Error(format!("{:?}", e)),
+            };
+            span.end_time_ms = Some(js_sys::Date::now());
+            recorder.record_span(span);
+            outcome
+        }))
+    }
+
+    /// Open a named savepoint (a nested transaction)
+    ///
+    /// Starts a transaction if none is active. Savepoints nest; close them with
+    /// `releaseSavepoint` or undo their changes with `rollbackToSavepoint`. Releasing or
+    /// rolling back to a savepoint that is not open fails with `SAVEPOINT_UNBALANCED`.
+    /// Savepoint statements never need leadership; the writes made inside them do.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await db.savepoint('form');
+    /// await db.execute("UPDATE profile SET name = 'Ada'");
+    /// await db.savepoint('address');
+    /// await db.execute("UPDATE profile SET city = 'London'");
+    /// await db.rollbackToSavepoint('address'); // keep the name change only
+    /// await db.releaseSavepoint('form');       // commits
+    /// ```
+    #[wasm_bindgen(js_name = "savepoint")]
+    pub async fn savepoint(&mut self, name: &str) -> Result<(), JsValue> {
+        self.savepoint_internal(name)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to create savepoint: {}", e)))
+    }
 
-        log::debug!("onDataChange callback registered for {}", self.name);
-        Ok(())
+    /// Release a savepoint and all savepoints opened after it
+    ///
+    /// Releasing the outermost savepoint commits the transaction.
+    #[wasm_bindgen(js_name = "releaseSavepoint")]
+    pub async fn release_savepoint(&mut self, name: &str) -> Result<(), JsValue> {
+        self.release_savepoint_internal(name)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to release savepoint: {}", e)))
     }
 
-    /// Reload data from IndexedDB into memory
-    /// Call this when another tab has written data and you need to see the changes
-    /// This closes and reopens the SQLite connection to invalidate its page cache
-    #[wasm_bindgen(js_name = "reloadFromIndexedDB")]
-    pub async fn reload_from_indexed_db(&mut self) -> Result<(), JsValue> {
-        log::info!("Reloading data from IndexedDB for {}", self.name);
+    /// Undo all changes made since a savepoint was opened
+    ///
+    /// The savepoint stays open; savepoints opened after it are discarded.
+    #[wasm_bindgen(js_name = "rollbackToSavepoint")]
+    pub async fn rollback_to_savepoint(&mut self, name: &str) -> Result<(), JsValue> {
+        self.rollback_to_savepoint_internal(name)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to roll back to savepoint: {}", e)))
+    }
 
-        let db_name = self.name.clone();
+    /// Number of savepoints currently open
+    #[wasm_bindgen(js_name = "savepointDepth")]
+    pub fn savepoint_depth_js(&self) -> usize {
+        self.savepoint_depth()
+    }
 
-        // Step 1: Close the SQLite connection to invalidate page cache
-        let pool_key = db_name.trim_end_matches(".db");
-        crate::connection_pool::force_close_connection(pool_key);
-        self.connection_state.db.set(std::ptr::null_mut());
-        log::info!("[RELOAD] Closed SQLite connection for {}", db_name);
+    /// Start recording changes for a changeset
+    ///
+    /// Records every INSERT, UPDATE and DELETE on `tables` (all tables when omitted) made
+    /// through this connection. Only tables with a PRIMARY KEY are recorded. Returns a
+    /// handle for `captureChangeset`; free it with `endSession` when done.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const session = db.startSession(['todos']);
+    /// await db.execute("UPDATE todos SET done = 1 WHERE id = 7");
+    /// const changes = db.captureChangeset(session);
+    /// db.endSession(session);
+    /// const { conflicts } = await server.applyChangeset(changes, 'REPLACE');
+    /// ```
+    #[wasm_bindgen(js_name = "startSession")]
+    pub fn start_session(&self, tables: Option<Vec<String>>) -> Result<u32, JsValue> {
+        self.start_session_internal(&tables.unwrap_or_default())
+            .map_err(|e| JsValue::from_str(&format!("Failed to start session: {}", e)))
+    }
 
-        // Step 2: Restore from IndexedDB into global storage (force reload)
-        crate::storage::wasm_indexeddb::restore_from_indexeddb_force(&db_name)
-            .await
-            .map_err(|e| JsValue::from_str(&format!("Failed to restore from IndexedDB: {}", e)))?;
-        log::info!("[RELOAD] Restored from IndexedDB for {}", db_name);
+    /// Binary changeset of everything a session has recorded so far
+    ///
+    /// The session keeps recording; later captures include earlier changes too.
+    #[wasm_bindgen(js_name = "captureChangeset")]
+    pub fn capture_changeset(&self, session: u32) -> Result<js_sys::Uint8Array, JsValue> {
+        let bytes = self
+            .capture_changeset_internal(session)
+            .map_err(|e| JsValue::from_str(&format!("Failed to capture changeset: {}", e)))?;
+        Ok(js_sys::Uint8Array::from(bytes.as_slice()))
+    }
 
-        // Step 3: Reload BlockStorage cache from global storage
-        use crate::vfs::indexeddb_vfs::STORAGE_REGISTRY;
-        STORAGE_REGISTRY.with(|reg| unsafe {
-            let registry = &*reg.get();
-            if let Some(storage_rc) = registry.get(&db_name) {
-                storage_rc.reload_cache_from_global_storage();
-                log::info!("[RELOAD] Reloaded BlockStorage cache for {}", db_name);
-            }
-        });
+    /// Stop a session started with `startSession` and free it
+    #[wasm_bindgen(js_name = "endSession")]
+    pub fn end_session(&self, session: u32) {
+        self.end_session_internal(session);
+    }
 
-        // Step 4: Reopen the SQLite connection
-        use std::ffi::CString;
+    /// Apply a changeset captured with `captureChangeset`
+    ///
+    /// `conflictPolicy` is `ABORT` (the default; the first conflict rolls back every
+    /// change), `REPLACE` (incoming rows win) or `SKIP` (local rows win). Returns
+    /// `{ aborted, conflicts }` with one entry per conflicting row.
+    #[wasm_bindgen(js_name = "applyChangeset")]
+    pub async fn apply_changeset(
+        &mut self,
+        changeset: js_sys::Uint8Array,
+        conflict_policy: Option<String>,
+    ) -> Result<JsValue, JsValue> {
+        let policy = match conflict_policy {
+            Some(policy) => ConflictPolicy::from_name(&policy)
+                .map_err(|e| JsValue::from_str(&format!("Failed to apply changeset: {}", e)))?,
+            None => ConflictPolicy::default(),
+        };
+        let result = self
+            .apply_changeset_internal(&changeset.to_vec(), policy)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to apply changeset: {}", e)))?;
+        serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-        let vfs_name = format!("vfs_{}", db_name.trim_end_matches(".db"));
-        let pool_key = db_name.trim_end_matches(".db").to_string();
-        let db_name_for_closure = db_name.clone();
-        let vfs_name_for_closure = vfs_name.clone();
+    /// Checkpoint the WAL into the database file
+    ///
+    /// `mode` is `PASSIVE` (the default), `FULL`, `RESTART` or `TRUNCATE`. Returns
+    /// `{ busy, logFrames, checkpointedFrames }`. Checkpoint during bulk inserts to keep
+    /// the WAL below `walSizeLimit()`, past which writes fail with `WAL_TOO_LARGE`.
+    ///
+    /// # Example
+    /// ```javascript
+    /// if (db.walSize() > db.walSizeLimit() / 2) {
+    ///   const { busy } = await db.checkpoint('TRUNCATE');
+    /// }
+    /// ```
+    #[wasm_bindgen(js_name = "checkpoint")]
+    pub async fn checkpoint(&self, mode: Option<String>) -> Result<JsValue, JsValue> {
+        let mode = match mode {
+            Some(mode) => CheckpointMode::from_name(&mode)
+                .map_err(|e| JsValue::from_str(&format!("Checkpoint failed: {}", e)))?,
+            None => CheckpointMode::default(),
+        };
+        let result = self
+            .checkpoint_internal(mode)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Checkpoint failed: {}", e)))?;
+        serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 
-        let new_state = crate::connection_pool::get_or_create_connection(&pool_key, || {
-            let mut db = std::ptr::null_mut();
-            let db_name_cstr = CString::new(db_name_for_closure.clone())
-                .map_err(|_| "Invalid database name".to_string())?;
-            let vfs_cstr = CString::new(vfs_name_for_closure.as_str())
-                .map_err(|_| "Invalid VFS name".to_string())?;
+    /// Bytes currently held in the WAL (0 outside WAL mode)
+    #[wasm_bindgen(js_name = "walSize")]
+    pub fn wal_size(&self) -> f64 {
+        self.wal_size_bytes() as f64
+    }
 
-            log::info!(
-                "[RELOAD] Reopening database: {} with VFS: {}",
-                db_name_for_closure,
-                vfs_name_for_closure
-            );
+    /// Largest WAL, in bytes, before writes fail with `WAL_TOO_LARGE` (see `wal_max_bytes`)
+    #[wasm_bindgen(js_name = "walSizeLimit")]
+    pub fn wal_size_limit(&self) -> f64 {
+        self.wal_max_bytes() as f64
+    }
 
-            let ret = unsafe {
-                sqlite_wasm_rs::sqlite3_open_v2(
-                    db_name_cstr.as_ptr(),
-                    &mut db as *mut _,
-                    sqlite_wasm_rs::SQLITE_OPEN_READWRITE | sqlite_wasm_rs::SQLITE_OPEN_CREATE,
-                    vfs_cstr.as_ptr(),
-                )
-            };
+    /// Rowid of the most recent successful INSERT on this connection
+    ///
+    /// Unlike `QueryResult.lastInsertId` this needs no statement of its own, so it can
+    /// be read after a batch or transaction where only the final rowid matters. Returns
+    /// 0 when nothing has been inserted yet.
+    #[wasm_bindgen(js_name = "lastInsertRowId")]
+    pub fn last_insert_rowid_js(&self) -> Result<f64, JsValue> {
+        self.last_insert_rowid()
+            .map(|rowid| rowid as f64)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read last insert rowid: {}", e)))
+    }
 
-            if ret != sqlite_wasm_rs::SQLITE_OK {
-                let err_msg = unsafe {
-                    let msg_ptr = sqlite_wasm_rs::sqlite3_errmsg(db);
-                    if !msg_ptr.is_null() {
-                        std::ffi::CStr::from_ptr(msg_ptr)
-                            .to_string_lossy()
-                            .into_owned()
-                    } else {
-                        "Unknown error".to_string()
-                    }
-                };
-                return Err(format!("Failed to reopen database: {}", err_msg));
-            }
+    /// Rows changed by the most recent INSERT, UPDATE or DELETE
+    #[wasm_bindgen(js_name = "changes")]
+    pub fn changes_js(&self) -> Result<u32, JsValue> {
+        self.changes()
+            .map_err(|e| JsValue::from_str(&format!("Failed to read changes: {}", e)))
+    }
 
-            log::info!("[RELOAD] Database reopened successfully");
-            Ok(db)
-        })
-        .map_err(|e| JsValue::from_str(&format!("Failed to reopen connection: {}", e)))?;
+    /// Rows changed by every INSERT, UPDATE and DELETE since the connection opened
+    #[wasm_bindgen(js_name = "totalChanges")]
+    pub fn total_changes_js(&self) -> Result<u32, JsValue> {
+        self.total_changes()
+            .map_err(|e| JsValue::from_str(&format!("Failed to read total changes: {}", e)))
+    }
 
-        self.connection_state = new_state;
-        log::info!("[RELOAD] Connection state updated for {}", db_name);
+    /// Schema cookie (`PRAGMA schema_version`), bumped by SQLite on every schema change
+    ///
+    /// Compare values across calls to notice schema changes made by another connection
+    /// or an import, e.g. to refresh table lists or re-check migrations.
+    #[wasm_bindgen(js_name = "getSchemaVersion")]
+    pub fn get_schema_version(&self) -> Result<f64, JsValue> {
+        self.schema_version()
+            .map(|version| version as f64)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read schema version: {}", e)))
+    }
 
-        Ok(())
+    /// Combined bytes held in the WALs of every database in this tab
+    #[wasm_bindgen(js_name = "totalWalSize")]
+    pub fn total_wal_size() -> f64 {
+        crate::vfs::indexeddb_vfs::wal_total_size_bytes() as f64
     }
 
-    /// Enable or disable optimistic updates mode
-    #[wasm_bindgen(js_name = "enableOptimisticUpdates")]
-    pub async fn enable_optimistic_updates(&mut self, enabled: bool) -> Result<(), JsValue> {
-        self.optimistic_updates_manager
-            .borrow_mut()
-            .set_enabled(enabled);
-        log::debug!(
-            "Optimistic updates {}",
-            if enabled { "enabled" } else { "disabled" }
-        );
+    /// Cap the combined size of every WAL in this tab (default 64 MB)
+    ///
+    /// Past 75% of the cap the least recently written WAL is checkpointed and truncated;
+    /// a write that would exceed the cap fails with `WAL_TOO_LARGE`.
+    ///
+    /// # Example
+    /// ```javascript
+    /// Database.setTotalWalLimit(16 * 1024 * 1024);
+    /// ```
+    #[wasm_bindgen(js_name = "setTotalWalLimit")]
+    pub fn set_total_wal_limit(bytes: f64) -> Result<(), JsValue> {
+        if !bytes.is_finite() || bytes < 1.0 {
+            return Err(JsValue::from_str(
+                "Total WAL limit must be a positive number of bytes",
+            ));
+        }
+        crate::vfs::indexeddb_vfs::set_wal_total_max_bytes(bytes as usize);
         Ok(())
     }
 
-    /// Check if optimistic mode is enabled
-    #[wasm_bindgen(js_name = "isOptimisticMode")]
-    pub async fn is_optimistic_mode(&self) -> bool {
-        self.optimistic_updates_manager.borrow().is_enabled()
+    /// Cap how many IndexedDB connections this tab keeps open at once (default 8)
+    ///
+    /// Released connections stay open for reuse; once the cap is reached the least
+    /// recently used idle one is closed, and opens wait while every connection is busy.
+    ///
+    /// # Example
+    /// ```javascript
+    /// Database.setIndexedDbHandleLimit(4);
+    /// ```
+    #[wasm_bindgen(js_name = "setIndexedDbHandleLimit")]
+    pub fn set_indexeddb_handle_limit(limit: u32) -> Result<(), JsValue> {
+        if limit == 0 {
+            return Err(JsValue::from_str(
+                "IndexedDB handle limit must be at least 1",
+            ));
+        }
+        crate::storage::indexeddb_handles::set_max_open_handles(limit as usize);
+        Ok(())
     }
 
-    /// Track an optimistic write
-    #[wasm_bindgen(js_name = "trackOptimisticWrite")]
-    pub async fn track_optimistic_write(&mut self, sql: String) -> Result<String, JsValue> {
-        let id = self
-            .optimistic_updates_manager
-            .borrow_mut()
-            .track_write(sql);
-        Ok(id)
+    /// Open, idle and waiting IndexedDB connections in this tab
+    #[wasm_bindgen(js_name = "indexedDbHandleStats")]
+    pub fn indexeddb_handle_stats() -> IndexedDbHandleStats {
+        crate::storage::indexeddb_handles::handle_stats()
     }
 
-    /// Get count of pending writes
-    #[wasm_bindgen(js_name = "getPendingWritesCount")]
-    pub async fn get_pending_writes_count(&self) -> usize {
-        self.optimistic_updates_manager.borrow().get_pending_count()
+    /// Attach another persisted database so one query can read both
+    ///
+    /// Tables of the attached database are addressed as `alias.table`. Writes to them
+    /// need leadership of the attached database too, and are persisted by `sync()`.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await db.attach('archive', 'old');
+    /// const result = await db.execute(
+    ///   'SELECT o.id, a.note FROM orders o JOIN old.annotations a ON a.order_id = o.id',
+    /// );
+    /// await db.detach('old');
+    /// ```
+    #[wasm_bindgen(js_name = "attach")]
+    pub async fn attach(&mut self, name: &str, alias: &str) -> Result<(), JsValue> {
+        self.attach_internal(name, alias)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to attach database: {}", e)))
     }
 
-    /// Clear all optimistic writes
-    #[wasm_bindgen(js_name = "clearOptimisticWrites")]
-    pub async fn clear_optimistic_writes(&mut self) -> Result<(), JsValue> {
-        self.optimistic_updates_manager.borrow_mut().clear_all();
-        Ok(())
+    /// Detach a database attached with `attach`
+    #[wasm_bindgen(js_name = "detach")]
+    pub async fn detach(&mut self, alias: &str) -> Result<(), JsValue> {
+        self.detach_internal(alias)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to detach database: {}", e)))
     }
 
-    /// Enable or disable coordination metrics tracking
-    #[wasm_bindgen(js_name = "enableCoordinationMetrics")]
-    pub async fn enable_coordination_metrics(&mut self, enabled: bool) -> Result<(), JsValue> {
-        self.coordination_metrics_manager
-            .borrow_mut()
-            .set_enabled(enabled);
-        Ok(())
+    /// Limit what a single statement may do, e.g. to sandbox untrusted SQL
+    ///
+    /// `category` is one of `length`, `sql_length`, `column`, `expr_depth`,
+    /// `compound_select`, `vdbe_op`, `function_arg`, `attached`, `like_pattern_length`,
+    /// `variable_number`, `trigger_depth` or `worker_threads` (the `SQLITE_LIMIT_` names
+    /// are accepted too). Returns the previous value; pass a negative `value` to read the
+    /// current limit without changing it. Limits apply to the shared connection and are
+    /// reset when it is reopened, e.g. after an import.
+    ///
+    /// # Example
+    /// ```javascript
+    /// db.setLimit('sql_length', 10_000);
+    /// db.setLimit('attached', 0);
+    /// const depth = db.setLimit('expr_depth', -1);
+    /// ```
+    #[wasm_bindgen(js_name = "setLimit")]
+    pub fn set_limit(&self, category: &str, value: i32) -> Result<i32, JsValue> {
+        self.set_limit_internal(category, value)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set limit: {}", e)))
     }
 
-    /// Check if coordination metrics tracking is enabled
-    #[wasm_bindgen(js_name = "isCoordinationMetricsEnabled")]
-    pub async fn is_coordination_metrics_enabled(&self) -> bool {
-        self.coordination_metrics_manager.borrow().is_enabled()
+    /// Build a full-text index over `columns` of `table`
+    ///
+    /// Creates an FTS5 table named `{table}_fts` plus triggers that keep it in sync with
+    /// `table`, and indexes the existing rows. `options` may set `tokenize` (e.g.
+    /// `'porter unicode61'`) and `prefix` lengths (e.g. `[2, 3]`). Fails with
+    /// `FTS_UNAVAILABLE` if SQLite was built without FTS5.
+    ///
+    /// # Example
+    /// ```javascript
+    /// await db.createFtsIndex('notes', ['title', 'body'], { tokenize: 'porter unicode61' });
+    /// const hits = await db.searchFts('notes', 'running NEAR shoes', 10);
+    /// ```
+    #[wasm_bindgen(js_name = "createFtsIndex")]
+    pub async fn create_fts_index(
+        &mut self,
+        table: String,
+        columns: Vec<String>,
+        options: JsValue,
+    ) -> Result<(), JsValue> {
+        let options: FtsOptions = if options.is_undefined() || options.is_null() {
+            FtsOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|e| JsValue::from_str(&format!("Invalid FTS options: {}", e)))?
+        };
+        self.create_fts_index_internal(&table, &columns, &options)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to create FTS index: {}", e)))
     }
 
-    /// Record a leadership change
-    #[wasm_bindgen(js_name = "recordLeadershipChange")]
-    pub async fn record_leadership_change(&mut self, became_leader: bool) -> Result<(), JsValue> {
-        self.coordination_metrics_manager
-            .borrow_mut()
-            .record_leadership_change(became_leader);
-        Ok(())
+    /// Search a table indexed with `createFtsIndex`
+    ///
+    /// `query` uses FTS5 syntax (`word`, `"a phrase"`, `pre*`, `a AND b`, `NEAR(a b)`).
+    /// Returns the matching rows of `table`, best first, with an extra `fts_rank` column.
+    /// `limit` defaults to 20.
+    #[wasm_bindgen(js_name = "searchFts")]
+    pub async fn search_fts(
+        &mut self,
+        table: String,
+        query: String,
+        limit: Option<u32>,
+    ) -> Result<JsValue, JsValue> {
+        let result = self
+            .search_fts_internal(&table, &query, limit.unwrap_or(20))
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Full-text search failed: {}", e)))?;
+        crate::result_format::tagged_result_to_js(&result)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
-    /// Record a notification latency in milliseconds
-    #[wasm_bindgen(js_name = "recordNotificationLatency")]
-    pub async fn record_notification_latency(&mut self, latency_ms: f64) -> Result<(), JsValue> {
-        self.coordination_metrics_manager
-            .borrow_mut()
-            .record_notification_latency(latency_ms);
-        Ok(())
+    /// Whether window functions (`ROW_NUMBER() OVER (...)` etc.) are available
+    ///
+    /// The bundled SQLite supports them; queries using them on a build without support
+    /// fail with a `WINDOW_FUNCTIONS_UNSUPPORTED` error rather than a parse error.
+    #[wasm_bindgen(js_name = "supportsWindowFunctions")]
+    pub fn supports_window_functions(&self) -> bool {
+        Self::window_functions_supported()
     }
 
-    /// Record a write conflict (non-leader write attempt)
-    #[wasm_bindgen(js_name = "recordWriteConflict")]
-    pub async fn record_write_conflict(&mut self) -> Result<(), JsValue> {
-        self.coordination_metrics_manager
-            .borrow_mut()
-            .record_write_conflict();
-        Ok(())
+    /// Finalize and drop all cached prepared statements
+    #[wasm_bindgen(js_name = "clearStatementCache")]
+    pub fn clear_statement_cache(&mut self) {
+        self.finalize_statement_cache();
     }
 
-    /// Record a follower refresh
-    #[wasm_bindgen(js_name = "recordFollowerRefresh")]
-    pub async fn record_follower_refresh(&mut self) -> Result<(), JsValue> {
-        self.coordination_metrics_manager
-            .borrow_mut()
-            .record_follower_refresh();
-        Ok(())
+    /// Get statistics for every cache this database keeps
+    ///
+    /// Returns `{ statements, blocks }`, each `{ hits, misses, size, capacity }`: the
+    /// prepared-statement cache of this instance and the block cache of its storage.
+    /// Query results are not cached.
+    ///
+    /// # Example
+    /// ```javascript
+    /// const { statements, blocks } = db.getCacheStats();
+    /// console.log(`statement hit rate ${statements.hits / (statements.hits + statements.misses)}`);
+    /// db.clearCaches();
+    /// ```
+    #[wasm_bindgen(js_name = "getCacheStats")]
+    pub fn get_cache_stats(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.cache_stats())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
-    /// Get coordination metrics as JSON string
-    #[wasm_bindgen(js_name = "getCoordinationMetrics")]
-    pub async fn get_coordination_metrics(&self) -> Result<String, JsValue> {
-        self.coordination_metrics_manager
-            .borrow()
-            .get_metrics_json()
-            .map_err(|e| JsValue::from_str(&e))
+    /// Flush the statement cache and the clean blocks of the block cache
+    ///
+    /// Frees memory on demand and rules the caches out when chasing stale results.
+    /// Unsynced blocks are kept; hit/miss counters are not reset.
+    #[wasm_bindgen(js_name = "clearCaches")]
+    pub fn clear_caches(&mut self) {
+        self.clear_caches_internal();
     }
 
-    /// Reset all coordination metrics
-    #[wasm_bindgen(js_name = "resetCoordinationMetrics")]
-    pub async fn reset_coordination_metrics(&mut self) -> Result<(), JsValue> {
-        self.coordination_metrics_manager.borrow_mut().reset();
-        Ok(())
+    /// Get prepared statement cache statistics
+    ///
+    /// Returns `{ hits, misses, size, capacity }`
+    #[wasm_bindgen(js_name = "getStatementCacheStats")]
+    pub fn get_statement_cache_stats(&self) -> Result<JsValue, JsValue> {
+        let stats = self.statement_cache.borrow().stats();
+        serde_wasm_bindgen::to_value(&stats).map_err(|e| JsValue::from_str(&e.to_string()))
     }
 }
 
 // Export WasmColumnValue for WASM
+/// Whether `value` is a plain `{ ... }` object rather than a Date, typed array or class
+#[cfg(target_arch = "wasm32")]
+fn is_plain_object(value: &JsValue) -> bool {
+    let proto = js_sys::Object::get_prototype_of(value);
+    proto.is_null() || proto == js_sys::Object::get_prototype_of(&js_sys::Object::new())
+}
+
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 pub struct WasmColumnValue {
@@ -3199,6 +8828,16 @@ impl WasmColumnValue {
         }
     }
 
+    /// Wrap any JSON-serializable value; it is bound as TEXT holding its JSON
+    #[wasm_bindgen(js_name = "createJson")]
+    pub fn create_json(value: JsValue) -> Result<WasmColumnValue, JsValue> {
+        let json: serde_json::Value = serde_wasm_bindgen::from_value(value)
+            .map_err(|e| JsValue::from_str(&format!("Invalid JSON value: {}", e)))?;
+        Ok(WasmColumnValue {
+            inner: ColumnValue::Json(json),
+        })
+    }
+
     #[wasm_bindgen(js_name = "fromJsValue")]
     pub fn from_js_value(value: &JsValue) -> WasmColumnValue {
         if value.is_null() || value.is_undefined() {
@@ -3216,6 +8855,21 @@ impl WasmColumnValue {
                     inner: ColumnValue::Text(s),
                 }
             }
+        } else if value.is_bigint() {
+            // Exact 64-bit ids; anything wider is kept as its decimal string
+            match i64::try_from(value.clone()) {
+                Ok(n) => WasmColumnValue {
+                    inner: ColumnValue::Integer(n),
+                },
+                Err(_) => WasmColumnValue {
+                    inner: ColumnValue::BigInt(
+                        js_sys::BigInt::from(value.clone())
+                            .to_string(10)
+                            .map(String::from)
+                            .unwrap_or_default(),
+                    ),
+                },
+            }
         } else if let Some(n) = value.as_f64() {
             if n.fract() == 0.0 && n >= i64::MIN as f64 && n <= i64::MAX as f64 {
                 WasmColumnValue {
@@ -3227,6 +8881,14 @@ impl WasmColumnValue {
                 }
             }
         } else if value.is_object() {
+            // Plain objects and arrays are stored as JSON
+            if js_sys::Array::is_array(value) || is_plain_object(value) {
+                if let Ok(json) = serde_wasm_bindgen::from_value(value.clone()) {
+                    return WasmColumnValue {
+                        inner: ColumnValue::Json(json),
+                    };
+                }
+            }
             // Check if it's a Date
             if js_sys::Date::new(value).get_time().is_finite() {
                 let timestamp = js_sys::Date::new(value).get_time() as i64;
@@ -0,0 +1,71 @@
+//! Schema migrations tracked with `PRAGMA user_version`
+//! Each migration runs in its own transaction and bumps `user_version` as it commits, so
+//! a database only ever runs the migrations newer than its version
+
+use crate::types::DatabaseError;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+/// One schema change, applied when the database's `user_version` is below `version`
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct Migration {
+    /// `user_version` after this migration; positive and unique within a set
+    pub version: u32,
+    /// SQL that performs the change; may hold several statements
+    pub up_sql: String,
+}
+
+impl Migration {
+    pub fn new(version: u32, up_sql: impl Into<String>) -> Self {
+        Self {
+            version,
+            up_sql: up_sql.into(),
+        }
+    }
+}
+
+/// Migrations sorted by version
+///
+/// Fails with `INVALID_MIGRATION` for version 0, a version `user_version` cannot hold
+/// (above `i32::MAX`) or a version listed twice.
+pub fn in_order(migrations: &[Migration]) -> Result<Vec<&Migration>, DatabaseError> {
+    let mut ordered: Vec<&Migration> = migrations.iter().collect();
+    ordered.sort_by_key(|migration| migration.version);
+    for (index, migration) in ordered.iter().enumerate() {
+        if migration.version == 0 || migration.version > i32::MAX as u32 {
+            return Err(DatabaseError::new(
+                "INVALID_MIGRATION",
+                &format!(
+                    "Migration version {} must be between 1 and {}",
+                    migration.version,
+                    i32::MAX
+                ),
+            ));
+        }
+        if index > 0 && ordered[index - 1].version == migration.version {
+            return Err(DatabaseError::new(
+                "INVALID_MIGRATION",
+                &format!("Migration version {} is listed twice", migration.version),
+            ));
+        }
+    }
+    Ok(ordered)
+}
+
+/// `MIGRATION_FAILED` for a migration that was rolled back
+pub(crate) fn migration_failed(migration: &Migration, cause: &DatabaseError) -> DatabaseError {
+    let err = DatabaseError::new(
+        "MIGRATION_FAILED",
+        &format!(
+            "Migration {} failed and was rolled back: {}",
+            migration.version, cause.message
+        ),
+    )
+    .with_sql(&migration.up_sql);
+    match cause.sqlite_code {
+        Some(code) => err.with_sqlite_code(code),
+        None => err,
+    }
+}
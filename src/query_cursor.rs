@@ -0,0 +1,394 @@
+//! Streaming query cursor for the WASM Database
+//! Yields SELECT results in fixed-size batches instead of materializing every row
+
+use crate::types::{ColumnValue, DatabaseError, InvalidUtf8Handling, QueryResult, Row};
+use std::cell::Cell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// Read the column names of a prepared statement
+pub(crate) fn column_names(stmt: *mut sqlite_wasm_rs::sqlite3_stmt) -> Vec<String> {
+    let column_count = unsafe { sqlite_wasm_rs::sqlite3_column_count(stmt) };
+    (0..column_count)
+        .map(|i| unsafe {
+            let name_ptr = sqlite_wasm_rs::sqlite3_column_name(stmt, i);
+            if name_ptr.is_null() {
+                format!("col_{}", i)
+            } else {
+                std::ffi::CStr::from_ptr(name_ptr)
+                    .to_string_lossy()
+                    .into_owned()
+            }
+        })
+        .collect()
+}
+
+/// Read a TEXT column, decoding it according to `invalid_utf8`
+pub(crate) fn read_text(
+    stmt: *mut sqlite_wasm_rs::sqlite3_stmt,
+    i: i32,
+    invalid_utf8: InvalidUtf8Handling,
+) -> Result<ColumnValue, DatabaseError> {
+    unsafe {
+        let text_ptr = sqlite_wasm_rs::sqlite3_column_text(stmt, i);
+        if text_ptr.is_null() {
+            return Ok(ColumnValue::Null);
+        }
+        let len = sqlite_wasm_rs::sqlite3_column_bytes(stmt, i);
+        invalid_utf8.decode(std::slice::from_raw_parts(text_ptr, len.max(0) as usize))
+    }
+}
+
+/// How `read_row` turns SQLite values into `ColumnValue`s
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ReadOptions {
+    pub invalid_utf8: InvalidUtf8Handling,
+    /// Return values of columns declared as dates as `ColumnValue::Date`
+    pub date_columns: bool,
+}
+
+/// Whether a result column was declared `DATE`, `DATETIME` or `TIMESTAMP`
+pub(crate) fn is_date_column(stmt: *mut sqlite_wasm_rs::sqlite3_stmt, i: i32) -> bool {
+    unsafe {
+        let decltype = sqlite_wasm_rs::sqlite3_column_decltype(stmt, i);
+        if decltype.is_null() {
+            return false;
+        }
+        let decltype = std::ffi::CStr::from_ptr(decltype).to_bytes();
+        [&b"DATE"[..], b"DATETIME", b"TIMESTAMP"]
+            .iter()
+            .any(|name| decltype.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Whether a TEXT result column holds JSON: produced by a JSON function (which tags
+/// its result with the `'J'` subtype) or read from a column declared `JSON`
+pub(crate) fn is_json_column(stmt: *mut sqlite_wasm_rs::sqlite3_stmt, i: i32) -> bool {
+    unsafe {
+        let value = sqlite_wasm_rs::sqlite3_column_value(stmt, i);
+        if !value.is_null()
+            && sqlite_wasm_rs::sqlite3_value_subtype(value) == crate::functions::JSON_SUBTYPE
+        {
+            return true;
+        }
+        let decltype = sqlite_wasm_rs::sqlite3_column_decltype(stmt, i);
+        !decltype.is_null()
+            && std::ffi::CStr::from_ptr(decltype)
+                .to_bytes()
+                .eq_ignore_ascii_case(b"JSON")
+    }
+}
+
+/// Read the current row of a statement that just returned SQLITE_ROW
+pub(crate) fn read_row(
+    stmt: *mut sqlite_wasm_rs::sqlite3_stmt,
+    column_count: i32,
+    options: ReadOptions,
+) -> Result<Row, DatabaseError> {
+    let mut values = Vec::with_capacity(column_count.max(0) as usize);
+    for i in 0..column_count {
+        let is_date = options.date_columns && is_date_column(stmt, i);
+        let value = unsafe {
+            match sqlite_wasm_rs::sqlite3_column_type(stmt, i) {
+                sqlite_wasm_rs::SQLITE_NULL => ColumnValue::Null,
+                // Dates are stored as milliseconds since the epoch
+                sqlite_wasm_rs::SQLITE_INTEGER if is_date => {
+                    ColumnValue::Date(sqlite_wasm_rs::sqlite3_column_int64(stmt, i))
+                }
+                sqlite_wasm_rs::SQLITE_INTEGER => {
+                    ColumnValue::Integer(sqlite_wasm_rs::sqlite3_column_int64(stmt, i))
+                }
+                sqlite_wasm_rs::SQLITE_FLOAT => {
+                    ColumnValue::Real(sqlite_wasm_rs::sqlite3_column_double(stmt, i))
+                }
+                sqlite_wasm_rs::SQLITE_TEXT => match read_text(stmt, i, options.invalid_utf8)? {
+                    ColumnValue::Text(text) if is_json_column(stmt, i) => {
+                        serde_json::from_str(&text)
+                            .map(ColumnValue::Json)
+                            .unwrap_or(ColumnValue::Text(text))
+                    }
+                    // ISO 8601 strings, e.g. from datetime('now'), when they parse
+                    ColumnValue::Text(text) if is_date => {
+                        let ms = js_sys::Date::parse(&text);
+                        if ms.is_finite() {
+                            ColumnValue::Date(ms as i64)
+                        } else {
+                            ColumnValue::Text(text)
+                        }
+                    }
+                    other => other,
+                },
+                sqlite_wasm_rs::SQLITE_BLOB => {
+                    let blob_ptr = sqlite_wasm_rs::sqlite3_column_blob(stmt, i);
+                    let blob_size = sqlite_wasm_rs::sqlite3_column_bytes(stmt, i);
+                    if blob_ptr.is_null() || blob_size == 0 {
+                        ColumnValue::Blob(vec![])
+                    } else {
+                        ColumnValue::Blob(
+                            std::slice::from_raw_parts(blob_ptr as *const u8, blob_size as usize)
+                                .to_vec(),
+                        )
+                    }
+                }
+                _ => ColumnValue::Null,
+            }
+        };
+        values.push(value);
+    }
+    Ok(Row { values })
+}
+
+/// Bind positional parameters to a prepared statement
+///
+/// Fails on the first parameter SQLite rejects, or with `INTEGER_OVERFLOW` for a
+/// `BigInt` wider than 64 bits. Text and blob values are copied (`SQLITE_TRANSIENT`),
+/// so the statement does not borrow from `params`.
+pub(crate) fn bind_params(
+    stmt: *mut sqlite_wasm_rs::sqlite3_stmt,
+    params: &[ColumnValue],
+) -> Result<(), DatabaseError> {
+    for (i, param) in params.iter().enumerate() {
+        let param_index = (i + 1) as i32;
+        let bind_ret = unsafe {
+            match param {
+                ColumnValue::Null => sqlite_wasm_rs::sqlite3_bind_null(stmt, param_index),
+                ColumnValue::Integer(val) => {
+                    sqlite_wasm_rs::sqlite3_bind_int64(stmt, param_index, *val)
+                }
+                ColumnValue::Date(ms) => sqlite_wasm_rs::sqlite3_bind_int64(stmt, param_index, *ms),
+                ColumnValue::BigInt(val) => sqlite_wasm_rs::sqlite3_bind_int64(
+                    stmt,
+                    param_index,
+                    ColumnValue::parse_bigint(val)?,
+                ),
+                ColumnValue::Real(val) => {
+                    sqlite_wasm_rs::sqlite3_bind_double(stmt, param_index, *val)
+                }
+                ColumnValue::Text(val) => {
+                    // SQLite text shouldn't contain null bytes
+                    let sanitized = val.replace('\0', "");
+                    sqlite_wasm_rs::sqlite3_bind_text(
+                        stmt,
+                        param_index,
+                        sanitized.as_ptr() as *const _,
+                        sanitized.len() as i32,
+                        sqlite_wasm_rs::SQLITE_TRANSIENT(),
+                    )
+                }
+                ColumnValue::Blob(val) => sqlite_wasm_rs::sqlite3_bind_blob(
+                    stmt,
+                    param_index,
+                    val.as_ptr() as *const _,
+                    val.len() as i32,
+                    sqlite_wasm_rs::SQLITE_TRANSIENT(),
+                ),
+                ColumnValue::Json(val) => {
+                    let text = val.to_string();
+                    sqlite_wasm_rs::sqlite3_bind_text(
+                        stmt,
+                        param_index,
+                        text.as_ptr() as *const _,
+                        text.len() as i32,
+                        sqlite_wasm_rs::SQLITE_TRANSIENT(),
+                    )
+                }
+            }
+        };
+        if bind_ret != sqlite_wasm_rs::SQLITE_OK {
+            return Err(
+                DatabaseError::new("SQLITE_ERROR", "Failed to bind parameter")
+                    .with_sqlite_code(bind_ret),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Cursor over a SELECT statement that yields rows in batches
+///
+/// Keeps the `sqlite3_stmt` alive between pulls. While a cursor is open the owning
+/// Database rejects other queries with `CURSOR_BUSY`. The statement is finalized when
+/// the cursor is exhausted, closed, or dropped.
+///
+/// Implements the JavaScript async iterator protocol:
+/// ```javascript
+/// for await (const batch of await db.queryStream('SELECT * FROM big_table', 500)) {
+///   console.log(batch.rows.length);
+/// }
+/// ```
+#[wasm_bindgen]
+pub struct QueryCursor {
+    stmt: Cell<*mut sqlite_wasm_rs::sqlite3_stmt>,
+    connection_state: Rc<crate::connection_pool::ConnectionState>,
+    cursor_open: Rc<Cell<bool>>,
+    columns: Vec<String>,
+    batch_size: usize,
+    sql: String,
+    read_options: ReadOptions,
+}
+
+impl QueryCursor {
+    pub(crate) fn new(
+        stmt: *mut sqlite_wasm_rs::sqlite3_stmt,
+        connection_state: Rc<crate::connection_pool::ConnectionState>,
+        cursor_open: Rc<Cell<bool>>,
+        batch_size: usize,
+        sql: &str,
+        read_options: ReadOptions,
+    ) -> Self {
+        cursor_open.set(true);
+        Self {
+            columns: column_names(stmt),
+            stmt: Cell::new(stmt),
+            connection_state,
+            cursor_open,
+            batch_size: batch_size.max(1),
+            sql: sql.to_string(),
+            read_options,
+        }
+    }
+
+    /// Fetch the next batch of rows, or None once the cursor is exhausted
+    pub fn next_batch(&self) -> Result<Option<QueryResult>, DatabaseError> {
+        let stmt = self.stmt.get();
+        if stmt.is_null() {
+            return Ok(None);
+        }
+
+        let db = self.connection_state.db.get();
+        if db.is_null() {
+            self.finish();
+            return Err(DatabaseError::new(
+                "NULL_CONNECTION",
+                "Database connection was closed while the cursor was open",
+            )
+            .with_sql(&self.sql));
+        }
+
+        let start_time = js_sys::Date::now();
+        let column_count = self.columns.len() as i32;
+        let mut rows = Vec::with_capacity(self.batch_size);
+
+        while rows.len() < self.batch_size {
+            let step_ret = unsafe { sqlite_wasm_rs::sqlite3_step(stmt) };
+            if step_ret == sqlite_wasm_rs::SQLITE_ROW {
+                match read_row(stmt, column_count, self.read_options) {
+                    Ok(row) => rows.push(row),
+                    Err(e) => {
+                        self.finish();
+                        return Err(e.with_sql(&self.sql));
+                    }
+                }
+            } else if step_ret == sqlite_wasm_rs::SQLITE_DONE {
+                self.finish();
+                break;
+            } else {
+                let err_msg = unsafe {
+                    let err_ptr = sqlite_wasm_rs::sqlite3_errmsg(db);
+                    if !err_ptr.is_null() {
+                        std::ffi::CStr::from_ptr(err_ptr)
+                            .to_string_lossy()
+                            .into_owned()
+                    } else {
+                        "Unknown SQLite error".to_string()
+                    }
+                };
+                self.finish();
+                return Err(DatabaseError::new(
+                    "SQLITE_ERROR",
+                    &format!("Error streaming SELECT statement: {}", err_msg),
+                )
+                .with_sql(&self.sql));
+            }
+        }
+
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(QueryResult {
+            columns: self.columns.clone(),
+            rows,
+            affected_rows: 0,
+            last_insert_id: None,
+            execution_time_ms: js_sys::Date::now() - start_time,
+        }))
+    }
+
+    /// Check whether the cursor has been exhausted or closed
+    pub fn is_finished(&self) -> bool {
+        self.stmt.get().is_null()
+    }
+
+    /// Finalize the statement and release the Database for other queries
+    fn finish(&self) {
+        let stmt = self.stmt.replace(std::ptr::null_mut());
+        if !stmt.is_null() {
+            unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+            self.cursor_open.set(false);
+        }
+    }
+
+    /// Build an iterator result object: `{ value, done }`
+    fn iter_result(value: &JsValue, done: bool) -> Result<JsValue, JsValue> {
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &"value".into(), value)?;
+        js_sys::Reflect::set(&result, &"done".into(), &JsValue::from_bool(done))?;
+        Ok(result.into())
+    }
+}
+
+#[wasm_bindgen]
+impl QueryCursor {
+    /// Async iterator `next()`: resolves to `{ value: QueryResult, done: false }`
+    /// for each batch and `{ value: undefined, done: true }` once exhausted
+    #[wasm_bindgen]
+    pub async fn next(&self) -> Result<JsValue, JsValue> {
+        match self
+            .next_batch()
+            .map_err(|e| JsValue::from_str(&format!("Query stream failed: {}", e)))?
+        {
+            Some(batch) => {
+                let value = crate::result_format::to_js(&batch)
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?;
+                Self::iter_result(&value, false)
+            }
+            None => Self::iter_result(&JsValue::UNDEFINED, true),
+        }
+    }
+
+    /// Async iterator `return()`: called by `for await` on early exit
+    #[wasm_bindgen(js_name = "return")]
+    pub async fn return_(&self) -> Result<JsValue, JsValue> {
+        self.finish();
+        Self::iter_result(&JsValue::UNDEFINED, true)
+    }
+
+    /// Close the cursor, finalizing the underlying statement
+    #[wasm_bindgen]
+    pub fn close(&self) {
+        self.finish();
+    }
+
+    /// Column names of the streamed query
+    #[wasm_bindgen(getter)]
+    pub fn columns(&self) -> Vec<String> {
+        self.columns.clone()
+    }
+}
+
+impl Drop for QueryCursor {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Make a cursor object usable with `for await`
+///
+/// `Object.prototype.valueOf` returns `this`, which is exactly what `[Symbol.asyncIterator]`
+/// must return; reusing it avoids leaking a closure or evaluating code.
+pub(crate) fn install_async_iterator(cursor: &JsValue) -> Result<(), JsValue> {
+    let value_of = js_sys::Reflect::get(&js_sys::Object::new(), &"valueOf".into())?;
+    js_sys::Reflect::set(cursor, &js_sys::Symbol::async_iterator(), &value_of)?;
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! Per-statement time budget
+//! A SQLite progress handler interrupts statements that run past their deadline, so a
+//! runaway query fails with `QUERY_TIMEOUT` instead of hanging the tab
+
+use crate::types::DatabaseError;
+use std::os::raw::{c_int, c_void};
+
+/// Virtual machine instructions between deadline checks
+const PROGRESS_HANDLER_OPS: c_int = 1000;
+
+/// Progress handler installed for the duration of one call, removed when dropped
+pub struct QueryDeadline {
+    db: *mut sqlite_wasm_rs::sqlite3,
+    timeout_ms: u32,
+    // Boxed so the pointer handed to SQLite stays valid while the guard moves
+    deadline: Box<f64>,
+}
+
+unsafe extern "C" fn check_deadline(arg: *mut c_void) -> c_int {
+    let deadline = unsafe { *(arg as *const f64) };
+    (js_sys::Date::now() > deadline) as c_int
+}
+
+impl QueryDeadline {
+    /// Start the clock on `db`; returns `None` when there is no limit
+    ///
+    /// # Safety
+    /// `db` must be null or an open connection that outlives the returned guard, which
+    /// removes the progress handler from it when dropped.
+    pub(crate) unsafe fn install(
+        db: *mut sqlite_wasm_rs::sqlite3,
+        timeout_ms: Option<u32>,
+    ) -> Option<Self> {
+        let timeout_ms = timeout_ms?;
+        if db.is_null() {
+            return None;
+        }
+        let deadline = Box::new(js_sys::Date::now() + timeout_ms as f64);
+        unsafe {
+            sqlite_wasm_rs::sqlite3_progress_handler(
+                db,
+                PROGRESS_HANDLER_OPS,
+                Some(check_deadline),
+                &*deadline as *const f64 as *mut c_void,
+            )
+        };
+        Some(Self {
+            db,
+            timeout_ms,
+            deadline,
+        })
+    }
+
+    fn expired(&self) -> bool {
+        js_sys::Date::now() > *self.deadline
+    }
+}
+
+impl Drop for QueryDeadline {
+    fn drop(&mut self) {
+        unsafe { sqlite_wasm_rs::sqlite3_progress_handler(self.db, 0, None, std::ptr::null_mut()) };
+    }
+}
+
+/// Name the cause of an `SQLITE_INTERRUPT`: the deadline passing (`QUERY_TIMEOUT`) or
+/// a call to `interrupt()` (`QUERY_INTERRUPTED`)
+pub fn interrupt_error(err: DatabaseError, deadline: Option<&QueryDeadline>) -> DatabaseError {
+    if err.sqlite_code.map(|code| code & 0xff) != Some(sqlite_wasm_rs::SQLITE_INTERRUPT) {
+        return err;
+    }
+    match deadline.filter(|d| d.expired()) {
+        Some(d) => DatabaseError {
+            code: "QUERY_TIMEOUT".to_string(),
+            message: format!("Query exceeded query_timeout_ms of {} ms", d.timeout_ms),
+            ..err
+        },
+        None => DatabaseError {
+            code: "QUERY_INTERRUPTED".to_string(),
+            message: "Query was interrupted".to_string(),
+            ..err
+        },
+    }
+}
@@ -0,0 +1,209 @@
+//! Alternative JS shapes for query results
+//! `executeFormatted` returns rows as tagged values, plain arrays or plain objects
+
+use crate::types::{
+    ColumnValue, DatabaseError, MAX_SAFE_INTEGER, PragmaValue, QueryResult, ResultFormat,
+};
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+
+/// Convert a column value to a plain JS value
+///
+/// Integers outside the safe range and `BigInt` values become `BigInt`s, BLOBs become
+/// `Uint8Array`s and dates become JS `Date`s.
+pub fn column_value_to_js(value: &ColumnValue) -> JsValue {
+    match value {
+        ColumnValue::Null => JsValue::NULL,
+        ColumnValue::Integer(i) if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(i) => {
+            JsValue::from_f64(*i as f64)
+        }
+        ColumnValue::Integer(i) => js_sys::BigInt::from(*i).into(),
+        ColumnValue::Real(f) => JsValue::from_f64(*f),
+        ColumnValue::Text(s) => JsValue::from_str(s),
+        ColumnValue::Blob(b) => js_sys::Uint8Array::from(b.as_slice()).into(),
+        ColumnValue::Date(ms) => date_to_js(*ms),
+        ColumnValue::BigInt(s) => js_sys::BigInt::new(&JsValue::from_str(s))
+            .map(JsValue::from)
+            .unwrap_or_else(|_| JsValue::from_str(s)),
+        ColumnValue::Json(v) => js_sys::JSON::parse(&v.to_string())
+            .unwrap_or_else(|_| JsValue::from_str(&v.to_string())),
+    }
+}
+
+/// Convert milliseconds since the epoch to a JS `Date`
+fn date_to_js(ms: i64) -> JsValue {
+    js_sys::Date::new(&JsValue::from_f64(ms as f64)).into()
+}
+
+/// Read JS parameters, binding `Date` objects as `ColumnValue::Date`
+///
+/// Everything else goes through serde as tagged `{ type, value }` objects.
+pub fn params_from_js(params: JsValue) -> Result<Vec<ColumnValue>, serde_wasm_bindgen::Error> {
+    let Some(array) = params.dyn_ref::<js_sys::Array>() else {
+        return serde_wasm_bindgen::from_value(params);
+    };
+    array
+        .iter()
+        .map(|param| match param.dyn_ref::<js_sys::Date>() {
+            Some(date) if date.get_time().is_finite() => {
+                Ok(ColumnValue::Date(date.get_time() as i64))
+            }
+            Some(_) => Err(serde::de::Error::custom("Invalid Date parameter")),
+            None => serde_wasm_bindgen::from_value(param),
+        })
+        .collect()
+}
+
+/// Serialize a result for JS, turning JSON objects into plain objects rather than `Map`s
+pub fn to_js<T: serde::Serialize + ?Sized>(
+    value: &T,
+) -> Result<JsValue, serde_wasm_bindgen::Error> {
+    value.serialize(&serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true))
+}
+
+/// Serialize a query result as tagged values
+///
+/// The `value` of every `Date` cell is a JS `Date` rather than milliseconds.
+pub fn tagged_result_to_js(result: &QueryResult) -> Result<JsValue, serde_wasm_bindgen::Error> {
+    let output = to_js(result)?;
+    let js_error =
+        |e: JsValue| <serde_wasm_bindgen::Error as serde::ser::Error>::custom(format!("{:?}", e));
+    let rows = js_sys::Reflect::get(&output, &JsValue::from_str("rows")).map_err(js_error)?;
+    for (row_index, row) in result.rows.iter().enumerate() {
+        for (col_index, value) in row.values.iter().enumerate() {
+            let ColumnValue::Date(ms) = value else {
+                continue;
+            };
+            let row_js = js_sys::Reflect::get_u32(&rows, row_index as u32).map_err(js_error)?;
+            let values =
+                js_sys::Reflect::get(&row_js, &JsValue::from_str("values")).map_err(js_error)?;
+            let cell = js_sys::Reflect::get_u32(&values, col_index as u32).map_err(js_error)?;
+            js_sys::Reflect::set(&cell, &JsValue::from_str("value"), &date_to_js(*ms))
+                .map_err(js_error)?;
+        }
+    }
+    Ok(output)
+}
+
+/// Convert a query result to JS in the requested shape
+///
+/// Every format keeps `columns`, `affectedRows`, `lastInsertId` and `executionTimeMs`;
+/// only `rows` changes. With `ArrayOfObjects`, a repeated column name keeps the value
+/// of its last occurrence.
+pub fn query_result_to_js(
+    result: &QueryResult,
+    format: ResultFormat,
+) -> Result<JsValue, DatabaseError> {
+    let to_js_error = |e: JsValue| {
+        DatabaseError::new(
+            "SERIALIZATION_ERROR",
+            &format!("Failed to convert result: {:?}", e),
+        )
+    };
+
+    if format == ResultFormat::TaggedEnum {
+        return tagged_result_to_js(result)
+            .map_err(|e| DatabaseError::new("SERIALIZATION_ERROR", &e.to_string()));
+    }
+
+    let rows = js_sys::Array::new();
+    for row in &result.rows {
+        match format {
+            ResultFormat::ArrayOfArrays => {
+                let values: js_sys::Array = row.values.iter().map(column_value_to_js).collect();
+                rows.push(&values);
+            }
+            ResultFormat::ArrayOfObjects => {
+                let object = js_sys::Object::new();
+                for (column, value) in result.columns.iter().zip(&row.values) {
+                    js_sys::Reflect::set(
+                        &object,
+                        &JsValue::from_str(column),
+                        &column_value_to_js(value),
+                    )
+                    .map_err(to_js_error)?;
+                }
+                rows.push(&object);
+            }
+            ResultFormat::TaggedEnum => unreachable!("handled above"),
+        }
+    }
+
+    let columns: js_sys::Array = result.columns.iter().map(JsValue::from).collect();
+    let last_insert_id = result
+        .last_insert_id
+        .map(|id| column_value_to_js(&ColumnValue::Integer(id)))
+        .unwrap_or(JsValue::NULL);
+
+    let output = js_sys::Object::new();
+    for (key, value) in [
+        ("columns", JsValue::from(columns)),
+        ("rows", JsValue::from(rows)),
+        ("affectedRows", JsValue::from(result.affected_rows)),
+        ("lastInsertId", last_insert_id),
+        (
+            "executionTimeMs",
+            JsValue::from_f64(result.execution_time_ms),
+        ),
+    ] {
+        js_sys::Reflect::set(&output, &JsValue::from_str(key), &value).map_err(to_js_error)?;
+    }
+    Ok(output.into())
+}
+
+/// Build the message `streamToPort` posts for one batch, plus the buffers to transfer
+///
+/// The message is `{ type: 'rows', columns, rows }` with each row a plain array, as in
+/// `ArrayOfArrays`. Every BLOB gets its own `Uint8Array`, so its buffer can be
+/// transferred to the receiver instead of copied.
+pub fn stream_batch_message(batch: &QueryResult) -> Result<(JsValue, js_sys::Array), JsValue> {
+    let transfer = js_sys::Array::new();
+    let rows = js_sys::Array::new();
+    for row in &batch.rows {
+        let values: js_sys::Array = row
+            .values
+            .iter()
+            .map(|value| {
+                let js = column_value_to_js(value);
+                if let Some(bytes) = js.dyn_ref::<js_sys::Uint8Array>() {
+                    transfer.push(&bytes.buffer());
+                }
+                js
+            })
+            .collect();
+        rows.push(&values);
+    }
+    let columns: js_sys::Array = batch.columns.iter().map(JsValue::from).collect();
+
+    let message = js_sys::Object::new();
+    js_sys::Reflect::set(&message, &"type".into(), &"rows".into())?;
+    js_sys::Reflect::set(&message, &"columns".into(), &columns)?;
+    js_sys::Reflect::set(&message, &"rows".into(), &rows)?;
+    Ok((message.into(), transfer))
+}
+
+/// Convert a parsed pragma to JS
+///
+/// Scalars become plain values, checks `{ ok, errors }` and table pragmas an array of
+/// row objects keyed by column name.
+pub fn pragma_value_to_js(value: &PragmaValue) -> Result<JsValue, DatabaseError> {
+    Ok(match value {
+        PragmaValue::Null => JsValue::NULL,
+        PragmaValue::Bool(b) => JsValue::from_bool(*b),
+        PragmaValue::Integer(i) => column_value_to_js(&ColumnValue::Integer(*i)),
+        PragmaValue::Real(f) => JsValue::from_f64(*f),
+        PragmaValue::Text(text) => JsValue::from_str(text),
+        PragmaValue::Check(check) => {
+            to_js(check).map_err(|e| DatabaseError::new("SERIALIZATION_ERROR", &e.to_string()))?
+        }
+        PragmaValue::Rows(result) => {
+            let output = query_result_to_js(result, ResultFormat::ArrayOfObjects)?;
+            js_sys::Reflect::get(&output, &JsValue::from_str("rows")).map_err(|e| {
+                DatabaseError::new(
+                    "SERIALIZATION_ERROR",
+                    &format!("Failed to convert result: {:?}", e),
+                )
+            })?
+        }
+    })
+}
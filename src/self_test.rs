@@ -0,0 +1,252 @@
+//! Persistence self-test
+//! Round-trips a row through IndexedDB with a throwaway database and reports every step,
+//! so "data doesn't survive a refresh" can be narrowed down to the step that broke
+
+use crate::Database;
+use crate::storage::vfs_sync::{
+    with_global_allocation_map, with_global_commit_marker, with_global_metadata,
+    with_global_storage,
+};
+use crate::types::{DatabaseError, PersistenceSelfTestReport, SelfTestStep};
+use crate::utils::normalize_db_name;
+use wasm_bindgen::prelude::*;
+
+/// Run the persistence self-test
+///
+/// Creates a temporary database, writes a row, syncs, drops every in-memory copy of the
+/// database, restores it from IndexedDB and reads the row back. Stops at the first failed
+/// step; the temporary database is deleted afterwards either way.
+///
+/// # Example
+/// ```javascript
+/// const report = await runPersistenceSelfTest();
+/// if (!report.passed) {
+///   console.table(report.steps);
+/// }
+/// ```
+#[wasm_bindgen(js_name = "runPersistenceSelfTest")]
+pub async fn run_persistence_self_test() -> Result<JsValue, JsValue> {
+    let report = run_persistence_self_test_internal().await;
+    serde_wasm_bindgen::to_value(&report).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Run the persistence self-test and return the report (see `runPersistenceSelfTest`)
+pub async fn run_persistence_self_test_internal() -> PersistenceSelfTestReport {
+    let start = js_sys::Date::now();
+    let name = format!(
+        "__absurder_selftest_{}_{}",
+        start as u64,
+        (js_sys::Math::random() * 1e9) as u64
+    );
+    let normalized = normalize_db_name(&name);
+    let mut steps = Vec::new();
+
+    let passed = run_steps(&name, &normalized, &mut steps).await;
+
+    if let Err(e) = Database::delete_database(name.clone()).await {
+        log::warn!("Self-test could not delete {}: {:?}", normalized, e);
+    }
+
+    PersistenceSelfTestReport {
+        passed,
+        database: normalized,
+        steps,
+        duration_ms: js_sys::Date::now() - start,
+    }
+}
+
+/// Record the outcome of one step, returning whether it passed
+fn record(steps: &mut Vec<SelfTestStep>, name: &str, outcome: Result<String, String>) -> bool {
+    let passed = outcome.is_ok();
+    let detail = outcome.unwrap_or_else(|e| e);
+    log::info!(
+        "Persistence self-test {}: {} ({})",
+        name,
+        if passed { "passed" } else { "FAILED" },
+        detail
+    );
+    steps.push(SelfTestStep {
+        name: name.to_string(),
+        passed,
+        detail,
+    });
+    passed
+}
+
+async fn run_steps(name: &str, normalized: &str, steps: &mut Vec<SelfTestStep>) -> bool {
+    let token = format!("token_{}", js_sys::Math::random());
+
+    // 1. Open through the IndexedDB VFS
+    let mut db = match Database::new_wasm(name.to_string()).await {
+        Ok(db) => db,
+        Err(e) => {
+            return record(
+                steps,
+                "vfs_registered",
+                Err(format!("Open failed: {:?}", e)),
+            );
+        }
+    };
+    let registered = crate::vfs::indexeddb_vfs::get_storage_with_fallback(normalized).is_some();
+    if !record(
+        steps,
+        "vfs_registered",
+        if registered {
+            Ok(format!(
+                "IndexedDB VFS storage registered for {}",
+                normalized
+            ))
+        } else {
+            Err(format!("No VFS storage registered for {}", normalized))
+        },
+    ) {
+        return false;
+    }
+
+    // 2. Write a row
+    let _ = db.allow_non_leader_writes(true).await;
+    let write = async {
+        db.execute_internal("CREATE TABLE selftest (token TEXT NOT NULL)")
+            .await?;
+        db.execute_with_params_internal(
+            "INSERT INTO selftest (token) VALUES (?)",
+            &[crate::ColumnValue::Text(token.clone())],
+        )
+        .await?;
+        Ok::<_, DatabaseError>(())
+    };
+    if !record(
+        steps,
+        "row_written",
+        write
+            .await
+            .map(|_| "Inserted one row".to_string())
+            .map_err(|e| format!("Write failed: {}", e)),
+    ) {
+        return false;
+    }
+
+    // 3. Sync and confirm the commit marker moved
+    let marker_before = commit_marker(normalized);
+    if let Err(e) = db.sync_internal().await {
+        return record(
+            steps,
+            "commit_marker_advanced",
+            Err(format!("Sync failed: {}", e)),
+        );
+    }
+    let marker_after = commit_marker(normalized);
+    if !record(
+        steps,
+        "commit_marker_advanced",
+        if marker_after > marker_before {
+            Ok(format!(
+                "Commit marker advanced from {} to {}",
+                marker_before, marker_after
+            ))
+        } else {
+            Err(format!(
+                "Commit marker stayed at {} after sync",
+                marker_before
+            ))
+        },
+    ) {
+        return false;
+    }
+
+    // 4. Drop every in-memory copy, as a page refresh would
+    if let Err(e) = db.force_close_connection().await {
+        return record(
+            steps,
+            "in_memory_state_dropped",
+            Err(format!("Close failed: {:?}", e)),
+        );
+    }
+    drop(db);
+    if !record(
+        steps,
+        "in_memory_state_dropped",
+        drop_in_memory_state(normalized),
+    ) {
+        return false;
+    }
+
+    // 5. The synced blocks and commit marker must be in IndexedDB
+    let persisted =
+        match crate::storage::wasm_indexeddb::restore_from_indexeddb_force(normalized).await {
+            Ok(()) => {
+                let blocks =
+                    with_global_storage(|gs| gs.borrow().get(normalized).map_or(0, |b| b.len()));
+                let marker = commit_marker(normalized);
+                if blocks == 0 {
+                    Err("No blocks found in IndexedDB".to_string())
+                } else if marker != marker_after {
+                    Err(format!(
+                        "IndexedDB commit marker is {}, expected {}",
+                        marker, marker_after
+                    ))
+                } else {
+                    Ok(format!(
+                        "{} blocks and commit marker {} found in IndexedDB",
+                        blocks, marker
+                    ))
+                }
+            }
+            Err(e) => Err(format!("Reading IndexedDB failed: {}", e)),
+        };
+    if !record(steps, "blocks_persisted", persisted) {
+        return false;
+    }
+    // The reopen below must restore on its own, not reuse the blocks just read
+    if let Err(e) = drop_in_memory_state(normalized) {
+        return record(steps, "restore_succeeded", Err(e));
+    }
+
+    // 6. Reopen from IndexedDB alone and read the row back
+    let mut db = match Database::new_wasm(name.to_string()).await {
+        Ok(db) => db,
+        Err(e) => {
+            return record(
+                steps,
+                "restore_succeeded",
+                Err(format!("Reopen failed: {:?}", e)),
+            );
+        }
+    };
+    let restored = match db
+        .query_scalar_internal("SELECT token FROM selftest", &[], true)
+        .await
+    {
+        Ok(crate::ColumnValue::Text(read)) if read == token => {
+            Ok("Row read back after reopening from IndexedDB".to_string())
+        }
+        Ok(other) => Err(format!("Expected the written row, read {:?}", other)),
+        Err(e) => Err(format!("Read after reopen failed: {}", e)),
+    };
+    let _ = db.force_close_connection().await;
+    record(steps, "restore_succeeded", restored)
+}
+
+fn commit_marker(normalized: &str) -> u64 {
+    with_global_commit_marker(|cm| cm.borrow().get(normalized).copied().unwrap_or(0))
+}
+
+/// Forget the database's blocks, metadata and registered storage in this tab
+fn drop_in_memory_state(normalized: &str) -> Result<String, String> {
+    crate::vfs::indexeddb_vfs::remove_storage_from_registry(normalized);
+    with_global_storage(|gs| gs.borrow_mut().remove(normalized));
+    with_global_metadata(|gm| gm.borrow_mut().remove(normalized));
+    with_global_commit_marker(|cm| cm.borrow_mut().remove(normalized));
+    with_global_allocation_map(|gam| gam.borrow_mut().remove(normalized));
+
+    if crate::vfs::indexeddb_vfs::get_storage_with_fallback(normalized).is_some()
+        || with_global_storage(|gs| gs.borrow().contains_key(normalized))
+    {
+        Err(format!(
+            "In-memory state for {} is still present",
+            normalized
+        ))
+    } else {
+        Ok("Blocks, metadata and commit marker cleared from memory".to_string())
+    }
+}
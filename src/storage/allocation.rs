@@ -19,7 +19,7 @@ macro_rules! lock_mutex {
 }
 
 use super::block_storage::BlockStorage;
-use crate::types::DatabaseError;
+use crate::types::{AllocationStats, DatabaseError};
 #[cfg(any(
     target_arch = "wasm32",
     all(not(target_arch = "wasm32"), not(feature = "fs_persist"))
@@ -63,8 +63,9 @@ pub async fn allocate_block_impl(storage: &mut BlockStorage) -> Result<u64, Data
     // Find the next available block ID and atomically increment
     let block_id = storage.next_block_id.fetch_add(1, Ordering::SeqCst);
 
-    // Mark block as allocated
+    // Mark block as allocated, clearing any tombstone (block was reallocated)
     lock_mutex!(storage.allocated_blocks).insert(block_id);
+    lock_mutex!(storage.deallocated_blocks).remove(&block_id);
 
     // For WASM, persist allocation state to global storage
     #[cfg(target_arch = "wasm32")]
@@ -115,7 +116,6 @@ pub async fn allocate_block_impl(storage: &mut BlockStorage) -> Result<u64, Data
         // Remove any tombstone (block was reallocated) and persist deallocated.json
         let mut dealloc_path = db_dir.clone();
         dealloc_path.push("deallocated.json");
-        lock_mutex!(storage.deallocated_blocks).remove(&block_id);
         let mut dealloc = FsDealloc::default();
         // best effort read to preserve any existing entries
         if let Ok(mut f) = fs::File::open(&dealloc_path) {
@@ -156,9 +156,9 @@ pub async fn allocate_block_impl(storage: &mut BlockStorage) -> Result<u64, Data
     #[cfg(feature = "telemetry")]
     if let Some(ref metrics) = storage.metrics {
         metrics.blocks_allocated_total().inc();
-        // Update memory gauge: total allocated blocks × BLOCK_SIZE
-        let total_memory = (lock_mutex!(storage.allocated_blocks).len() as f64)
-            * (super::block_storage::BLOCK_SIZE as f64);
+        // Update memory gauge: total allocated blocks × block size
+        let total_memory =
+            (lock_mutex!(storage.allocated_blocks).len() as f64) * (storage.block_size as f64);
         metrics.memory_bytes().set(total_memory);
     }
 
@@ -170,6 +170,97 @@ pub async fn allocate_block_impl(storage: &mut BlockStorage) -> Result<u64, Data
     Ok(block_id)
 }
 
+/// Reserve `count` block ids in one step ahead of a bulk write
+///
+/// Ids are taken from `next_block_id` upwards, skipping any that are already allocated,
+/// and the allocation state is persisted once for the whole range instead of per block.
+pub fn reserve_blocks_impl(storage: &BlockStorage, count: u64) -> Result<Vec<u64>, DatabaseError> {
+    let mut reserved = Vec::with_capacity(count as usize);
+    {
+        let mut allocated = lock_mutex!(storage.allocated_blocks);
+        let mut deallocated = lock_mutex!(storage.deallocated_blocks);
+        let mut block_id = storage.next_block_id.load(Ordering::SeqCst);
+        while (reserved.len() as u64) < count {
+            if allocated.insert(block_id) {
+                deallocated.remove(&block_id);
+                reserved.push(block_id);
+            }
+            block_id = block_id.checked_add(1).ok_or_else(|| {
+                DatabaseError::new("ALLOCATION_ERROR", "Block id space exhausted")
+            })?;
+        }
+        storage.next_block_id.store(block_id, Ordering::SeqCst);
+    }
+
+    #[cfg(any(
+        target_arch = "wasm32",
+        all(not(target_arch = "wasm32"), not(feature = "fs_persist"))
+    ))]
+    {
+        vfs_sync::with_global_allocation_map(|allocation_map| {
+            let mut map = allocation_map.borrow_mut();
+            map.entry(storage.db_name.clone())
+                .or_insert_with(HashSet::new)
+                .extend(reserved.iter().copied());
+        });
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "fs_persist"))]
+    {
+        let mut db_dir: PathBuf = storage.base_dir.clone();
+        db_dir.push(&storage.db_name);
+        let _ = fs::create_dir_all(&db_dir);
+        let mut alloc_path = db_dir.clone();
+        alloc_path.push("allocations.json");
+        let mut alloc = FsAlloc {
+            allocated: lock_mutex!(storage.allocated_blocks)
+                .iter()
+                .copied()
+                .collect(),
+        };
+        alloc.allocated.sort_unstable();
+        if let Ok(mut f) = fs::File::create(&alloc_path) {
+            let _ = f.write_all(
+                serde_json::to_string(&alloc)
+                    .unwrap_or_else(|_| "{}".into())
+                    .as_bytes(),
+            );
+        }
+
+        let mut dealloc_path = db_dir.clone();
+        dealloc_path.push("deallocated.json");
+        let mut dealloc = FsDealloc {
+            tombstones: lock_mutex!(storage.deallocated_blocks)
+                .iter()
+                .copied()
+                .collect(),
+        };
+        dealloc.tombstones.sort_unstable();
+        if let Ok(mut f) = fs::File::create(&dealloc_path) {
+            let _ = f.write_all(
+                serde_json::to_string(&dealloc)
+                    .unwrap_or_else(|_| "{}".into())
+                    .as_bytes(),
+            );
+        }
+    }
+
+    #[cfg(feature = "telemetry")]
+    if let Some(ref metrics) = storage.metrics {
+        metrics.blocks_allocated_total().inc_by(count as f64);
+        let total_memory =
+            (lock_mutex!(storage.allocated_blocks).len() as f64) * (storage.block_size as f64);
+        metrics.memory_bytes().set(total_memory);
+    }
+
+    log::info!(
+        "Reserved {} blocks (total allocated: {})",
+        reserved.len(),
+        lock_mutex!(storage.allocated_blocks).len()
+    );
+    Ok(reserved)
+}
+
 /// Deallocate a block and mark it as available for reuse
 pub async fn deallocate_block_impl(
     storage: &mut BlockStorage,
@@ -183,8 +274,9 @@ pub async fn deallocate_block_impl(
         ));
     }
 
-    // Remove from allocated set
+    // Remove from allocated set and tombstone it for reuse
     lock_mutex!(storage.allocated_blocks).remove(&block_id);
+    lock_mutex!(storage.deallocated_blocks).insert(block_id);
 
     // Clear from cache and dirty blocks
     lock_mutex!(storage.cache).remove(&block_id);
@@ -281,7 +373,6 @@ pub async fn deallocate_block_impl(
         // Append to deallocated tombstones and persist deallocated.json
         let mut dealloc_path = db_dir.clone();
         dealloc_path.push("deallocated.json");
-        lock_mutex!(storage.deallocated_blocks).insert(block_id);
         let mut dealloc = FsDealloc::default();
         if let Ok(mut f) = fs::File::open(&dealloc_path) {
             let mut s = String::new();
@@ -339,9 +430,9 @@ pub async fn deallocate_block_impl(
     #[cfg(feature = "telemetry")]
     if let Some(ref metrics) = storage.metrics {
         metrics.blocks_deallocated_total().inc();
-        // Update memory gauge: total allocated blocks × BLOCK_SIZE
-        let total_memory = (lock_mutex!(storage.allocated_blocks).len() as f64)
-            * (super::block_storage::BLOCK_SIZE as f64);
+        // Update memory gauge: total allocated blocks × block size
+        let total_memory =
+            (lock_mutex!(storage.allocated_blocks).len() as f64) * (storage.block_size as f64);
         metrics.memory_bytes().set(total_memory);
     }
 
@@ -352,3 +443,78 @@ pub async fn deallocate_block_impl(
     );
     Ok(())
 }
+
+/// Release every block at or past `first_block` after the database file shrank (e.g. VACUUM)
+///
+/// The blocks are dropped from the cache, dirty set, allocation map, checksums, global
+/// storage and persisted metadata. Returns the released ids so the caller can delete them
+/// from IndexedDB.
+#[cfg(target_arch = "wasm32")]
+pub fn release_blocks_from_impl(storage: &BlockStorage, first_block: u64) -> Vec<u64> {
+    let mut released: HashSet<u64> = lock_mutex!(storage.allocated_blocks)
+        .iter()
+        .copied()
+        .filter(|&id| id >= first_block)
+        .collect();
+    vfs_sync::with_global_storage(|storage_map| {
+        if let Some(db_storage) = storage_map.borrow_mut().get_mut(&storage.db_name) {
+            released.extend(db_storage.keys().copied().filter(|&id| id >= first_block));
+            db_storage.retain(|&id, _| id < first_block);
+        }
+    });
+    vfs_sync::with_global_allocation_map(|allocation_map| {
+        if let Some(db_allocations) = allocation_map.borrow_mut().get_mut(&storage.db_name) {
+            db_allocations.retain(|&id| id < first_block);
+        }
+    });
+    vfs_sync::with_global_metadata(|meta_map| {
+        if let Some(db_meta) = meta_map.borrow_mut().get_mut(&storage.db_name) {
+            released.extend(db_meta.keys().copied().filter(|&id| id >= first_block));
+            db_meta.retain(|&id, _| id < first_block);
+        }
+    });
+
+    lock_mutex!(storage.allocated_blocks).retain(|&id| id < first_block);
+    lock_mutex!(storage.cache).retain(|&id, _| id < first_block);
+    lock_mutex!(storage.lru_order).retain(|&id| id < first_block);
+    lock_mutex!(storage.dirty_blocks).retain(|&id, _| id < first_block);
+    for &block_id in &released {
+        storage.checksum_manager.remove_checksum(block_id);
+    }
+    if storage.next_block_id.load(Ordering::SeqCst) > first_block {
+        storage.next_block_id.store(first_block, Ordering::SeqCst);
+    }
+
+    let mut released: Vec<u64> = released.into_iter().collect();
+    released.sort_unstable();
+    log::info!(
+        "Released {} blocks at or past block {} for {}",
+        released.len(),
+        first_block,
+        storage.db_name
+    );
+    released
+}
+
+/// Allocation counts and how sparse the block id range is
+pub fn allocation_stats_impl(storage: &BlockStorage) -> AllocationStats {
+    let allocated_blocks = lock_mutex!(storage.allocated_blocks);
+    let allocated = allocated_blocks.len() as u64;
+    let highest_block_id = allocated_blocks.iter().max().copied().unwrap_or(0);
+    let deallocated_reusable = lock_mutex!(storage.deallocated_blocks)
+        .iter()
+        .filter(|&&id| !allocated_blocks.contains(&id))
+        .count() as u64;
+    let fragmentation_ratio = if allocated == 0 {
+        0.0
+    } else {
+        let span = highest_block_id + 1;
+        span.saturating_sub(allocated) as f64 / span as f64
+    };
+    AllocationStats {
+        allocated,
+        deallocated_reusable,
+        highest_block_id,
+        fragmentation_ratio,
+    }
+}
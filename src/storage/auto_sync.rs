@@ -600,7 +600,7 @@ impl super::BlockStorage {
         // Check if we should trigger threshold-based sync
         if let Some(policy) = lock_mutex!(self.policy).clone() {
             let dirty_count = self.get_dirty_count();
-            let dirty_bytes = dirty_count * super::BLOCK_SIZE;
+            let dirty_bytes = dirty_count * self.block_size;
 
             // Check max_dirty threshold
             if let Some(max_dirty) = policy.max_dirty {
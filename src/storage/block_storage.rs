@@ -12,6 +12,8 @@ use super::metadata::{ChecksumAlgorithm, ChecksumManager};
 use super::vfs_sync;
 use crate::types::DatabaseError;
 #[cfg(target_arch = "wasm32")]
+use crate::types::StorageReloadReport;
+#[cfg(target_arch = "wasm32")]
 use js_sys::Date;
 #[cfg(not(target_arch = "wasm32"))]
 use parking_lot::Mutex;
@@ -60,6 +62,12 @@ pub enum RecoveryMode {
     Sample {
         count: usize,
     },
+    /// Verify `count` blocks picked pseudo-randomly across all allocated blocks;
+    /// the same `seed` always picks the same blocks
+    Random {
+        count: usize,
+        seed: u64,
+    },
     Skip,
 }
 
@@ -71,6 +79,15 @@ pub enum CorruptionAction {
     Fail,
 }
 
+/// How `CorruptionAction::Repair` dealt with a corrupted block
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RepairOutcome {
+    /// Rewritten from the copy persisted at the current commit marker
+    Restored,
+    /// Removed along with its checksum
+    Deleted,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum CrashRecoveryAction {
     NoActionNeeded,
@@ -83,7 +100,13 @@ pub struct RecoveryReport {
     pub total_blocks_verified: usize,
     pub corrupted_blocks: Vec<u64>,
     pub repaired_blocks: Vec<u64>,
+    /// Repaired blocks rewritten from their last synced copy
+    pub restored_blocks: Vec<u64>,
+    /// Repaired blocks deleted because no good synced copy existed
+    pub deleted_blocks: Vec<u64>,
     pub verification_duration_ms: u64,
+    /// Block ids checked by `Sample` or `Random` recovery (empty for `Full` and `Skip`)
+    pub sampled_blocks: Vec<u64>,
 }
 
 // On-disk JSON schema for fs_persist
@@ -151,9 +174,82 @@ impl Drop for BlockStorage {
     }
 }
 
+/// Default block size in bytes
 pub const BLOCK_SIZE: usize = 4096;
+/// Smallest supported block size (matches SQLite's minimum page size)
+pub const MIN_BLOCK_SIZE: usize = 512;
+/// Largest supported block size (matches SQLite's maximum page size)
+pub const MAX_BLOCK_SIZE: usize = 65536;
+
+/// Validate a configured block size: must be a power of two between 512 and 65536 bytes
+pub fn validate_block_size(block_size: usize) -> Result<(), DatabaseError> {
+    if !block_size.is_power_of_two() || !(MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE).contains(&block_size) {
+        return Err(DatabaseError::new(
+            "INVALID_BLOCK_SIZE",
+            &format!(
+                "Block size must be a power of two between {} and {} bytes, got {}",
+                MIN_BLOCK_SIZE, MAX_BLOCK_SIZE, block_size
+            ),
+        ));
+    }
+    Ok(())
+}
 #[allow(dead_code)]
 pub(super) const DEFAULT_CACHE_CAPACITY: usize = 128;
+
+thread_local! {
+    // Block cache capacity configured per database (name without ".db")
+    static DB_CACHE_CAPACITY: std::cell::RefCell<HashMap<String, usize>> =
+        std::cell::RefCell::new(HashMap::new());
+}
+
+/// Set the LRU block cache capacity used when a database's storage is opened
+pub fn set_block_cache_capacity(db_name: &str, capacity: usize) {
+    DB_CACHE_CAPACITY.with(|capacities| {
+        capacities
+            .borrow_mut()
+            .insert(db_name.trim_end_matches(".db").to_string(), capacity);
+    });
+}
+
+/// Block cache capacity for a database, or `default` when none was configured
+pub(super) fn block_cache_capacity_for(db_name: &str, default: usize) -> usize {
+    DB_CACHE_CAPACITY.with(|capacities| {
+        capacities
+            .borrow()
+            .get(db_name.trim_end_matches(".db"))
+            .copied()
+            .unwrap_or(default)
+    })
+}
+
+thread_local! {
+    // Stored size limit configured per database (name without ".db")
+    static DB_MAX_BYTES: std::cell::RefCell<HashMap<String, u64>> =
+        std::cell::RefCell::new(HashMap::new());
+}
+
+/// Set the stored size limit enforced on a database's writes, or None for no limit
+pub fn set_max_db_bytes(db_name: &str, max_bytes: Option<u64>) {
+    DB_MAX_BYTES.with(|limits| {
+        let mut limits = limits.borrow_mut();
+        let key = db_name.trim_end_matches(".db").to_string();
+        match max_bytes {
+            Some(max_bytes) => limits.insert(key, max_bytes),
+            None => limits.remove(&key),
+        };
+    });
+}
+
+/// Stored size limit for a database, if one was configured
+pub(super) fn max_db_bytes_for(db_name: &str) -> Option<u64> {
+    DB_MAX_BYTES.with(|limits| {
+        limits
+            .borrow()
+            .get(db_name.trim_end_matches(".db"))
+            .copied()
+    })
+}
 #[allow(dead_code)]
 const STORE_NAME: &str = "sqlite_blocks";
 #[allow(dead_code)]
@@ -265,6 +361,11 @@ pub struct BlockStorage {
     pub(super) deallocated_blocks: Mutex<HashSet<u64>>,
     pub(super) next_block_id: AtomicU64,
     pub(super) capacity: usize,
+    // Block reads served from / missing the LRU cache
+    pub(super) cache_hits: AtomicU64,
+    pub(super) cache_misses: AtomicU64,
+    // Size of every block in bytes (fixed for the lifetime of the database)
+    pub(super) block_size: usize,
 
     #[cfg(target_arch = "wasm32")]
     pub(super) lru_order: RefCell<VecDeque<u64>>,
@@ -325,6 +426,25 @@ pub struct BlockStorage {
     // Leader election manager (WASM only) - wrapped in RefCell for interior mutability
     #[cfg(target_arch = "wasm32")]
     pub leader_election: std::cell::RefCell<Option<super::leader_election::LeaderElectionManager>>,
+    // Lease and heartbeat timing used when leader election starts (WASM only)
+    #[cfg(target_arch = "wasm32")]
+    pub(super) leader_election_config:
+        std::cell::Cell<super::leader_election::LeaderElectionConfig>,
+    // False when leader election is disabled because only one tab uses the database (WASM only)
+    #[cfg(target_arch = "wasm32")]
+    pub(super) multi_tab: std::cell::Cell<bool>,
+    // Run crash recovery when this instance becomes leader (WASM only)
+    #[cfg(target_arch = "wasm32")]
+    pub(super) recover_on_leadership: std::cell::Cell<bool>,
+    // Whether recovery already ran during the current leadership term (WASM only)
+    #[cfg(target_arch = "wasm32")]
+    pub(super) recovered_this_term: std::cell::Cell<bool>,
+    // Read back and checksum every block persisted by a sync (WASM only)
+    #[cfg(target_arch = "wasm32")]
+    pub(super) verify_after_sync: std::cell::Cell<bool>,
+    // Callback for leadership transitions, handed to the election manager (WASM only)
+    #[cfg(target_arch = "wasm32")]
+    pub(super) leadership_callback: std::cell::RefCell<Option<js_sys::Function>>,
 
     // Observability manager
     pub(super) observability: super::observability::ObservabilityManager,
@@ -356,6 +476,12 @@ impl BlockStorage {
                 (HashMap::new(), HashSet::new(), 0)
             }
         });
+        // Adopt the block size the existing data was written with
+        let block_size = cache
+            .values()
+            .next()
+            .map(|block| block.len())
+            .unwrap_or(BLOCK_SIZE);
 
         log::info!(
             "Loaded {} blocks from GLOBAL_STORAGE for {} (max_block_id={})",
@@ -375,9 +501,13 @@ impl BlockStorage {
                     checksums.insert(*block_id, meta.checksum);
                     algos.insert(*block_id, meta.algo);
                 }
-                ChecksumManager::with_data(checksums, algos, ChecksumAlgorithm::FastHash)
+                ChecksumManager::with_data(
+                    checksums,
+                    algos,
+                    ChecksumAlgorithm::default_for(db_name),
+                )
             } else {
-                ChecksumManager::new(ChecksumAlgorithm::FastHash)
+                ChecksumManager::new(ChecksumAlgorithm::default_for(db_name))
             }
         });
 
@@ -387,7 +517,10 @@ impl BlockStorage {
             allocated_blocks: RefCell::new(allocated_blocks),
             deallocated_blocks: RefCell::new(HashSet::new()),
             next_block_id: AtomicU64::new(max_block_id + 1),
-            capacity: 128,
+            capacity: block_cache_capacity_for(db_name, DEFAULT_CACHE_CAPACITY),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
+            block_size,
             lru_order: RefCell::new(VecDeque::new()),
             checksum_manager,
             db_name: db_name.to_string(),
@@ -429,6 +562,18 @@ impl BlockStorage {
             recovery_report: RecoveryReport::default(),
             #[cfg(target_arch = "wasm32")]
             leader_election: std::cell::RefCell::new(None),
+            #[cfg(target_arch = "wasm32")]
+            leader_election_config: std::cell::Cell::new(Default::default()),
+            #[cfg(target_arch = "wasm32")]
+            multi_tab: std::cell::Cell::new(true),
+            #[cfg(target_arch = "wasm32")]
+            recover_on_leadership: std::cell::Cell::new(true),
+            #[cfg(target_arch = "wasm32")]
+            recovered_this_term: std::cell::Cell::new(false),
+            #[cfg(target_arch = "wasm32")]
+            verify_after_sync: std::cell::Cell::new(false),
+            #[cfg(target_arch = "wasm32")]
+            leadership_callback: std::cell::RefCell::new(None),
             observability: super::observability::ObservabilityManager::new(),
             #[cfg(feature = "telemetry")]
             metrics: None,
@@ -571,11 +716,7 @@ impl BlockStorage {
                                         let algo = obj
                                             .get("algo")
                                             .and_then(|v| v.as_str())
-                                            .and_then(|s| match s {
-                                                "CRC32" => Some(ChecksumAlgorithm::CRC32),
-                                                "FastHash" => Some(ChecksumAlgorithm::FastHash),
-                                                _ => None,
-                                            })
+                                            .and_then(ChecksumAlgorithm::from_name)
                                             .unwrap_or(ChecksumAlgorithm::FastHash);
                                         map.insert(block_id, algo);
                                     }
@@ -603,14 +744,8 @@ impl BlockStorage {
             }
         };
 
-        // Determine default checksum algorithm from environment (fs_persist native), fallback to FastHash
-        #[cfg(feature = "fs_persist")]
-        let checksum_algo_default = match std::env::var("DATASYNC_CHECKSUM_ALGO").ok().as_deref() {
-            Some("CRC32") => ChecksumAlgorithm::CRC32,
-            _ => ChecksumAlgorithm::FastHash,
-        };
-        #[cfg(not(feature = "fs_persist"))]
-        let checksum_algo_default = ChecksumAlgorithm::FastHash;
+        // Default checksum algorithm: configured per database, then environment, then FastHash
+        let checksum_algo_default = ChecksumAlgorithm::default_for(db_name);
 
         // Load deallocated blocks from filesystem
         let deallocated_blocks_init: HashSet<u64> = {
@@ -646,7 +781,10 @@ impl BlockStorage {
             db_name: db_name.to_string(),
             cache: Mutex::new(HashMap::new()),
             lru_order: Mutex::new(VecDeque::new()),
-            capacity: 1000,
+            capacity: block_cache_capacity_for(db_name, 1000),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
+            block_size: BLOCK_SIZE,
             checksum_manager: ChecksumManager::with_data(
                 checksums_init,
                 checksum_algos_init,
@@ -694,6 +832,18 @@ impl BlockStorage {
             recovery_report: RecoveryReport::default(),
             #[cfg(target_arch = "wasm32")]
             leader_election: std::cell::RefCell::new(None),
+            #[cfg(target_arch = "wasm32")]
+            leader_election_config: std::cell::Cell::new(Default::default()),
+            #[cfg(target_arch = "wasm32")]
+            multi_tab: std::cell::Cell::new(true),
+            #[cfg(target_arch = "wasm32")]
+            recover_on_leadership: std::cell::Cell::new(true),
+            #[cfg(target_arch = "wasm32")]
+            recovered_this_term: std::cell::Cell::new(false),
+            #[cfg(target_arch = "wasm32")]
+            verify_after_sync: std::cell::Cell::new(false),
+            #[cfg(target_arch = "wasm32")]
+            leadership_callback: std::cell::RefCell::new(None),
             observability: super::observability::ObservabilityManager::new(),
             #[cfg(feature = "telemetry")]
             metrics: None,
@@ -706,6 +856,21 @@ impl BlockStorage {
         Ok(s)
     }
 
+    /// Create storage whose blocks are `block_size` bytes instead of the default 4096
+    ///
+    /// Fails with `INVALID_BLOCK_SIZE` for unsupported sizes and with `BLOCK_SIZE_MISMATCH`
+    /// if the database already holds blocks written with a different size.
+    pub async fn new_with_block_size(
+        db_name: &str,
+        block_size: usize,
+    ) -> Result<Self, DatabaseError> {
+        validate_block_size(block_size)?;
+        let mut s = Self::new(db_name).await?;
+        s.block_size = block_size;
+        s.verify_existing_block_size()?;
+        Ok(s)
+    }
+
     pub async fn new_with_recovery_options(
         db_name: &str,
         recovery_opts: RecoveryOptions,
@@ -743,6 +908,25 @@ impl BlockStorage {
                 sampled.truncate(sample_count);
                 Ok(sampled)
             }
+            RecoveryMode::Random { count, seed } => {
+                let sample_count = (*count).min(all_blocks.len());
+                let mut sampled = all_blocks;
+                // Sort first so the seed alone decides the sample, not set iteration order
+                sampled.sort_unstable();
+                // Partial Fisher-Yates shuffle driven by splitmix64
+                let mut state = *seed;
+                for i in 0..sample_count {
+                    state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                    let mut z = state;
+                    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                    z ^= z >> 31;
+                    let j = i + (z % (sampled.len() - i) as u64) as usize;
+                    sampled.swap(i, j);
+                }
+                sampled.truncate(sample_count);
+                Ok(sampled)
+            }
             RecoveryMode::Skip => Ok(Vec::new()),
         }
     }
@@ -787,7 +971,7 @@ impl BlockStorage {
             let block_file = blocks_dir.join(format!("block_{}.bin", block_id));
 
             if let Ok(data) = std::fs::read(&block_file) {
-                if data.len() == BLOCK_SIZE {
+                if data.len() == self.block_size {
                     return Ok(data);
                 }
             }
@@ -842,11 +1026,35 @@ impl BlockStorage {
     pub(super) async fn repair_corrupted_block(
         &mut self,
         block_id: u64,
-    ) -> Result<bool, DatabaseError> {
+    ) -> Result<RepairOutcome, DatabaseError> {
         log::info!("Attempting to repair corrupted block {}", block_id);
 
-        // For now, repair by removing the corrupted block and clearing its metadata
-        // In a real implementation, this might involve restoring from backup or rebuilding
+        // Prefer the last synced copy, as long as it still matches the stored checksum
+        if let Some(data) = self.read_committed_block(block_id).await {
+            if self.verify_against_stored_checksum(block_id, &data).is_ok() {
+                #[cfg(target_arch = "wasm32")]
+                vfs_sync::with_global_storage(|storage| {
+                    storage
+                        .borrow_mut()
+                        .entry(self.db_name.clone())
+                        .or_default()
+                        .insert(block_id, data.clone());
+                });
+                lock_mutex!(self.cache).insert(block_id, data);
+                self.touch_lru(block_id);
+                log::info!(
+                    "Corrupted block {} restored from its last synced copy",
+                    block_id
+                );
+                return Ok(RepairOutcome::Restored);
+            }
+            log::warn!(
+                "Last synced copy of block {} does not match its checksum either",
+                block_id
+            );
+        }
+
+        // No good copy: remove the corrupted block and clear its metadata
 
         // Remove from cache
         lock_mutex!(self.cache).remove(&block_id);
@@ -893,7 +1101,37 @@ impl BlockStorage {
             "Corrupted block {} has been removed (repair completed)",
             block_id
         );
-        Ok(true)
+        Ok(RepairOutcome::Deleted)
+    }
+
+    /// Block bytes as of the last sync, when a copy separate from the live one is kept
+    ///
+    /// Only IndexedDB keeps one; the native stores are the live copy.
+    async fn read_committed_block(&self, block_id: u64) -> Option<Vec<u8>> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            match super::wasm_indexeddb::read_committed_block_from_indexeddb(
+                &self.db_name,
+                block_id,
+            )
+            .await
+            {
+                Ok(data) => data,
+                Err(e) => {
+                    log::warn!(
+                        "Could not read synced copy of block {}: {}",
+                        block_id,
+                        e.message
+                    );
+                    None
+                }
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = block_id;
+            None
+        }
     }
 
     pub(super) fn touch_lru(&self, block_id: u64) {
@@ -957,6 +1195,65 @@ impl BlockStorage {
         now.as_millis() as u64
     }
 
+    /// Skip checksum maintenance for a trusted bulk load, or turn it back on
+    ///
+    /// While disabled, written blocks carry no checksum and reads are not verified, so
+    /// corruption goes unnoticed. Re-enabling recomputes the missing checksums (see
+    /// `recompute_checksums`), and so does every sync: blocks never reach persistent
+    /// storage without integrity metadata.
+    pub fn disable_checksums(&self, disabled: bool) -> usize {
+        if disabled {
+            log::warn!(
+                "Checksums disabled for {}: block corruption will not be detected until they are recomputed",
+                self.db_name
+            );
+            self.checksum_manager.set_disabled(true);
+            0
+        } else {
+            self.recompute_checksums()
+        }
+    }
+
+    /// Whether checksums are currently disabled with `disable_checksums`
+    pub fn checksums_disabled(&self) -> bool {
+        self.checksum_manager.is_disabled()
+    }
+
+    /// Re-enable checksums and compute them for blocks written while they were off
+    ///
+    /// Returns how many blocks were given a checksum. Those blocks are still unsynced, since
+    /// syncing re-enables checksums first.
+    pub fn recompute_checksums(&self) -> usize {
+        self.checksum_manager.set_disabled(false);
+        let dirty = lock_mutex!(self.dirty_blocks);
+        let mut recomputed = 0;
+        for (block_id, data) in dirty.iter() {
+            if self.checksum_manager.get_checksum(*block_id).is_none() {
+                self.checksum_manager.store_checksum(*block_id, data);
+                recomputed += 1;
+            }
+        }
+        if recomputed > 0 {
+            log::info!(
+                "Recomputed checksums for {} blocks of {}",
+                recomputed,
+                self.db_name
+            );
+        }
+        recomputed
+    }
+
+    /// Restore checksums before a sync if a bulk load left them disabled
+    pub(super) fn ensure_checksums_before_sync(&self) {
+        if self.checksum_manager.is_disabled() {
+            log::warn!(
+                "Sync of {} with checksums disabled; recomputing them first",
+                self.db_name
+            );
+            self.recompute_checksums();
+        }
+    }
+
     pub(super) fn verify_against_stored_checksum(
         &self,
         block_id: u64,
@@ -1265,6 +1562,18 @@ impl BlockStorage {
         lock_mutex!(self.lru_order).clear();
     }
 
+    /// Drop every cached block that has no unsynced changes, returning how many were dropped
+    ///
+    /// Dirty blocks stay cached until they are synced, so this never loses writes.
+    pub fn release_clean_blocks(&self) -> usize {
+        let dirty = lock_mutex!(self.dirty_blocks);
+        let mut cache = lock_mutex!(self.cache);
+        let before = cache.len();
+        cache.retain(|id, _| dirty.contains_key(id));
+        lock_mutex!(self.lru_order).retain(|id| dirty.contains_key(id));
+        before - cache.len()
+    }
+
     /// Handle notification that the database has been imported
     ///
     /// This method should be called after a database import to ensure
@@ -1284,7 +1593,7 @@ impl BlockStorage {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn on_database_import(&mut self) -> Result<(), DatabaseError> {
+    pub async fn on_database_import(&self) -> Result<(), DatabaseError> {
         log::info!(
             "Clearing cache for database '{}' after import",
             self.db_name
@@ -1363,10 +1672,11 @@ impl BlockStorage {
                                 ) {
                                     new_checksums.insert(block_id, checksum);
 
-                                    let algo = match algo_str {
-                                        "CRC32" => super::metadata::ChecksumAlgorithm::CRC32,
-                                        _ => super::metadata::ChecksumAlgorithm::FastHash,
-                                    };
+                                    let algo =
+                                        super::metadata::ChecksumAlgorithm::from_name(algo_str)
+                                            .unwrap_or(
+                                                super::metadata::ChecksumAlgorithm::FastHash,
+                                            );
                                     new_algos.insert(block_id, algo);
                                 }
                             }
@@ -1416,6 +1726,52 @@ impl BlockStorage {
         Ok(())
     }
 
+    /// Throw away every in-memory copy of this database and read it back from IndexedDB
+    ///
+    /// Clears the block cache, dirty blocks and this database's GLOBAL_STORAGE and
+    /// GLOBAL_METADATA entries, restores them through the regular restore path, then
+    /// checks every restored block against its persisted checksum. Unsynced writes are
+    /// lost. Blocks whose checksum does not match are reported rather than failing the
+    /// reload, so callers can tell persisted corruption from in-memory corruption.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn reload_from_indexeddb(&self) -> Result<StorageReloadReport, DatabaseError> {
+        log::info!("Reloading {} from IndexedDB", self.db_name);
+        self.clear_cache();
+        lock_mutex!(self.dirty_blocks).clear();
+        vfs_sync::with_global_storage(|gs| gs.borrow_mut().remove(&self.db_name));
+        vfs_sync::with_global_metadata(|gm| gm.borrow_mut().remove(&self.db_name));
+
+        super::wasm_indexeddb::restore_from_indexeddb_force(&self.db_name).await?;
+        self.on_database_import().await?;
+        self.reload_cache_from_global_storage();
+
+        let blocks = vfs_sync::with_global_storage(|gs| {
+            gs.borrow().get(&self.db_name).cloned().unwrap_or_default()
+        });
+        let mut corrupt_blocks: Vec<u64> = blocks
+            .iter()
+            .filter(|(id, data)| self.verify_against_stored_checksum(**id, data).is_err())
+            .map(|(id, _)| *id)
+            .collect();
+        corrupt_blocks.sort_unstable();
+        if !corrupt_blocks.is_empty() {
+            log::error!(
+                "{} blocks of {} failed checksum verification after reload: {:?}",
+                corrupt_blocks.len(),
+                self.db_name,
+                corrupt_blocks
+            );
+        }
+        Ok(StorageReloadReport {
+            blocks_loaded: blocks.len() as u64,
+            file_size_bytes: blocks
+                .keys()
+                .max()
+                .map_or(0, |max| (max + 1) * self.block_size as u64),
+            corrupt_blocks,
+        })
+    }
+
     /// Reload cache from GLOBAL_STORAGE (WASM only, for multi-connection support)
     #[cfg(target_arch = "wasm32")]
     pub fn reload_cache_from_global_storage(&self) {
@@ -1594,6 +1950,16 @@ impl BlockStorage {
         lock_mutex!(self.cache).len()
     }
 
+    /// Hit/miss counters, size and capacity of the block cache
+    pub fn block_cache_stats(&self) -> crate::types::BlockCacheStats {
+        crate::types::BlockCacheStats {
+            hits: self.cache_hits.load(Ordering::Relaxed),
+            misses: self.cache_misses.load(Ordering::Relaxed),
+            size: self.get_cache_size(),
+            capacity: self.capacity,
+        }
+    }
+
     pub fn get_dirty_count(&self) -> usize {
         lock_mutex!(self.dirty_blocks).len()
     }
@@ -1602,6 +1968,50 @@ impl BlockStorage {
         &self.db_name
     }
 
+    /// Get the size of each block in bytes
+    pub fn get_block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Reject persisted block data written with a different block size
+    pub(super) fn check_block_len(&self, block_id: u64, len: usize) -> Result<(), DatabaseError> {
+        if len != self.block_size {
+            return Err(DatabaseError::new(
+                "BLOCK_SIZE_MISMATCH",
+                &format!(
+                    "Block {} of database {} is {} bytes but the configured block size is {}",
+                    block_id, self.db_name, len, self.block_size
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Verify that blocks already loaded for this database match the configured block size
+    pub(super) fn verify_existing_block_size(&self) -> Result<(), DatabaseError> {
+        let cached = lock_mutex!(self.cache)
+            .iter()
+            .next()
+            .map(|(id, data)| (*id, data.len()));
+        if let Some((block_id, len)) = cached {
+            self.check_block_len(block_id, len)?;
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let stored = super::vfs_sync::with_global_storage(|gs| {
+                gs.borrow()
+                    .get(&self.db_name)
+                    .and_then(|db| db.iter().next().map(|(id, data)| (*id, data.len())))
+            });
+            if let Some((block_id, len)) = stored {
+                self.check_block_len(block_id, len)?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn is_cached(&self, block_id: u64) -> bool {
         lock_mutex!(self.cache).contains_key(&block_id)
     }
@@ -1611,16 +2021,104 @@ impl BlockStorage {
         super::allocation::allocate_block_impl(self).await
     }
 
+    /// Reserve `count` block ids at once ahead of a bulk write, returning them
+    ///
+    /// The allocation map is persisted once for the whole reservation rather than on
+    /// every `allocate_block` call.
+    pub fn reserve_blocks(&self, count: u64) -> Result<Vec<u64>, DatabaseError> {
+        super::allocation::reserve_blocks_impl(self, count)
+    }
+
     /// Deallocate a block and mark it as available for reuse
     pub async fn deallocate_block(&mut self, block_id: u64) -> Result<(), DatabaseError> {
         super::allocation::deallocate_block_impl(self, block_id).await
     }
 
+    /// Release every block at or past `first_block`, returning the released ids
+    #[cfg(target_arch = "wasm32")]
+    pub fn release_blocks_from(&self, first_block: u64) -> Vec<u64> {
+        super::allocation::release_blocks_from_impl(self, first_block)
+    }
+
     /// Get the number of currently allocated blocks
     pub fn get_allocated_count(&self) -> usize {
         lock_mutex!(self.allocated_blocks).len()
     }
 
+    /// Whether `block_id` is allocated or holds data
+    fn is_stored_block(&self, block_id: u64) -> bool {
+        if lock_mutex!(self.allocated_blocks).contains(&block_id)
+            || lock_mutex!(self.cache).contains_key(&block_id)
+        {
+            return true;
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            vfs_sync::with_global_storage(|gs| {
+                gs.borrow()
+                    .get(&self.db_name)
+                    .is_some_and(|blocks| blocks.contains_key(&block_id))
+            })
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        false
+    }
+
+    /// Number of blocks the database occupies: allocated blocks plus any written ones
+    pub fn stored_block_count(&self) -> usize {
+        // Writes reach the global store immediately in WASM, even for blocks since evicted
+        #[cfg(target_arch = "wasm32")]
+        let stored: Vec<u64> = vfs_sync::with_global_storage(|gs| {
+            gs.borrow()
+                .get(&self.db_name)
+                .map(|blocks| blocks.keys().copied().collect())
+                .unwrap_or_default()
+        });
+        #[cfg(not(target_arch = "wasm32"))]
+        let stored: Vec<u64> = Vec::new();
+
+        let allocated = lock_mutex!(self.allocated_blocks);
+        let written: HashSet<u64> = lock_mutex!(self.cache)
+            .keys()
+            .chain(stored.iter())
+            .filter(|id| !allocated.contains(id))
+            .copied()
+            .collect();
+        allocated.len() + written.len()
+    }
+
+    /// Current stored size of the database in bytes
+    pub fn database_size_bytes(&self) -> u64 {
+        self.stored_block_count() as u64 * self.block_size as u64
+    }
+
+    /// Fail with `QUOTA_EXCEEDED` if writing `block_id` would grow the database past its
+    /// configured `max_db_bytes`
+    pub(super) fn ensure_within_quota(&self, block_id: u64) -> Result<(), DatabaseError> {
+        let Some(max_bytes) = max_db_bytes_for(&self.db_name) else {
+            return Ok(());
+        };
+        if self.is_stored_block(block_id) {
+            return Ok(());
+        }
+        let size_after = self.database_size_bytes() + self.block_size as u64;
+        if size_after > max_bytes {
+            return Err(DatabaseError::new(
+                "QUOTA_EXCEEDED",
+                &format!(
+                    "Writing block {} would grow {} to {} bytes, over its max_db_bytes limit of {} bytes",
+                    block_id, self.db_name, size_after, max_bytes
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Allocated and reusable block counts, highest block id and fragmentation ratio
+    pub fn allocation_stats(&self) -> crate::types::AllocationStats {
+        super::allocation::allocation_stats_impl(self)
+    }
+
     /// Crash simulation: simulate crash during IndexedDB commit
     /// If `blocks_written` is true, blocks are written to IndexedDB but commit marker doesn't advance
     /// If `blocks_written` is false, crash occurs before blocks are written
@@ -1766,6 +2264,12 @@ impl BlockStorage {
     /// and either finalizes or rolls back incomplete transactions
     #[cfg(target_arch = "wasm32")]
     pub async fn perform_crash_recovery(&mut self) -> Result<CrashRecoveryAction, DatabaseError> {
+        self.recover_incomplete_transactions().await
+    }
+
+    /// Crash recovery behind a shared borrow, so leadership checks can run it
+    #[cfg(target_arch = "wasm32")]
+    async fn recover_incomplete_transactions(&self) -> Result<CrashRecoveryAction, DatabaseError> {
         log::info!(
             "CRASH RECOVERY: Starting crash recovery scan for database: {}",
             self.db_name
@@ -1878,7 +2382,7 @@ impl BlockStorage {
     /// Rollback incomplete transaction by removing inconsistent blocks
     #[cfg(target_arch = "wasm32")]
     async fn rollback_incomplete_transaction(
-        &mut self,
+        &self,
         inconsistent_blocks: &[(u64, u64)],
     ) -> Result<(), DatabaseError> {
         log::info!(
@@ -1941,7 +2445,7 @@ impl BlockStorage {
     /// Finalize complete transaction by advancing commit marker
     #[cfg(target_arch = "wasm32")]
     async fn finalize_complete_transaction(
-        &mut self,
+        &self,
         inconsistent_blocks: &[(u64, u64)],
     ) -> Result<(), DatabaseError> {
         log::info!(
@@ -1984,6 +2488,93 @@ impl BlockStorage {
 
     // Leader Election Methods (WASM only)
 
+    /// Set the lease and heartbeat timing used by leader election
+    ///
+    /// Takes effect the next time an election starts for this storage.
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_leader_election_config(&self, config: super::leader_election::LeaderElectionConfig) {
+        self.leader_election_config.set(config);
+    }
+
+    /// Get the lease and heartbeat timing used by leader election
+    #[cfg(target_arch = "wasm32")]
+    pub fn leader_election_config(&self) -> super::leader_election::LeaderElectionConfig {
+        self.leader_election_config.get()
+    }
+
+    /// Enable or disable leader election for this storage
+    ///
+    /// With `false`, `is_leader()` always returns true and no election is ever started.
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_multi_tab(&self, multi_tab: bool) {
+        self.multi_tab.set(multi_tab);
+    }
+
+    /// Enable or disable crash recovery when this instance becomes leader
+    ///
+    /// When enabled, the first leadership check of each term runs
+    /// `perform_crash_recovery` so blocks left behind by a leader that died mid-sync
+    /// are rolled back or finalized before this instance accepts writes.
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_recover_on_leadership(&self, enabled: bool) {
+        self.recover_on_leadership.set(enabled);
+    }
+
+    /// Enable or disable read-back verification of every sync
+    ///
+    /// When enabled, a sync reads the blocks it just wrote back from IndexedDB and fails
+    /// with `SYNC_VERIFICATION_FAILED` if any is missing or its checksum differs.
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_verify_after_sync(&self, enabled: bool) {
+        self.verify_after_sync.set(enabled);
+    }
+
+    /// Whether syncs read back and verify the blocks they persist
+    #[cfg(target_arch = "wasm32")]
+    pub fn verify_after_sync(&self) -> bool {
+        self.verify_after_sync.get()
+    }
+
+    /// Run crash recovery once per leadership term
+    ///
+    /// Losing leadership resets the term, so recovery runs again on the next takeover.
+    /// Failures are logged rather than returned so they never cost the tab its leadership.
+    #[cfg(target_arch = "wasm32")]
+    async fn recover_after_election(&self, is_leader: bool) {
+        if !is_leader {
+            self.recovered_this_term.set(false);
+            return;
+        }
+        if !self.recover_on_leadership.get() || self.recovered_this_term.replace(true) {
+            return;
+        }
+        match self.recover_incomplete_transactions().await {
+            Ok(action) => log::info!(
+                "Crash recovery on leadership for {}: {:?}",
+                self.db_name,
+                action
+            ),
+            Err(e) => log::error!(
+                "Crash recovery on leadership failed for {}: {}",
+                self.db_name,
+                e.message
+            ),
+        }
+    }
+
+    /// Register a callback invoked whenever this instance gains or loses leadership
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_leadership_callback(
+        &self,
+        callback: Option<js_sys::Function>,
+    ) -> Result<(), DatabaseError> {
+        *self.leadership_callback.borrow_mut() = callback.clone();
+        if let Some(ref mut manager) = *self.leader_election.borrow_mut() {
+            manager.set_leadership_callback(callback)?;
+        }
+        Ok(())
+    }
+
     /// Start leader election process
     #[cfg(target_arch = "wasm32")]
     pub async fn start_leader_election(&self) -> Result<(), DatabaseError> {
@@ -1992,8 +2583,11 @@ impl BlockStorage {
                 "BlockStorage::start_leader_election() - Creating new LeaderElectionManager for {}",
                 self.db_name
             );
-            let mut manager =
-                super::leader_election::LeaderElectionManager::new(self.db_name.clone());
+            let mut manager = super::leader_election::LeaderElectionManager::new_with_config(
+                self.db_name.clone(),
+                self.leader_election_config.get(),
+            );
+            manager.set_leadership_callback(self.leadership_callback.borrow().clone())?;
             log::debug!("BlockStorage::start_leader_election() - Calling manager.start_election()");
             manager.start_election().await?;
             log::debug!(
@@ -2009,6 +2603,7 @@ impl BlockStorage {
                 manager.force_become_leader().await?;
             }
         }
+        self.recover_after_election(self.was_leader()).await;
         Ok(())
     }
 
@@ -2023,6 +2618,21 @@ impl BlockStorage {
             .unwrap_or(false)
     }
 
+    /// Check whether an election has run and a leader is known, without starting one
+    #[cfg(target_arch = "wasm32")]
+    pub fn leader_known(&self) -> bool {
+        self.leader_election
+            .try_borrow()
+            .ok()
+            .and_then(|m| {
+                m.as_ref().map(|m| {
+                    let state = m.state.borrow();
+                    state.is_leader || state.leader_id.is_some()
+                })
+            })
+            .unwrap_or(false)
+    }
+
     /// Stop heartbeat interval synchronously (for Drop implementation)
     /// This is idempotent and safe to call multiple times
     /// Silently skips if already stopped or if manager is borrowed
@@ -2060,8 +2670,23 @@ impl BlockStorage {
     }
 
     /// Check if this instance is the leader (with re-election on lease expiry)
+    ///
+    /// A newly elected leader runs crash recovery before this returns true.
     #[cfg(target_arch = "wasm32")]
     pub async fn is_leader(&self) -> bool {
+        // Single-tab databases are always their own leader and never start an election
+        if !self.multi_tab.get() {
+            return true;
+        }
+
+        let is_leader = self.check_leadership().await;
+        self.recover_after_election(is_leader).await;
+        is_leader
+    }
+
+    /// Elect or re-elect as needed and report whether this instance leads
+    #[cfg(target_arch = "wasm32")]
+    async fn check_leadership(&self) -> bool {
         // Start leader election if not already started
         if self.leader_election.borrow().is_none() {
             log::debug!(
@@ -2162,7 +2787,7 @@ impl BlockStorage {
     /// Get comprehensive metrics for observability
     pub fn get_metrics(&self) -> super::observability::StorageMetrics {
         let dirty_count = self.get_dirty_count();
-        let dirty_bytes = dirty_count * BLOCK_SIZE;
+        let dirty_bytes = dirty_count * self.block_size;
 
         #[cfg(not(target_arch = "wasm32"))]
         let (sync_count, timer_sync_count, debounce_sync_count, last_sync_duration_ms) = {
@@ -2182,6 +2807,14 @@ impl BlockStorage {
 
         let error_count = self.observability.get_error_count();
         let checksum_failures = self.observability.get_checksum_failures();
+        let cache_hits = self.cache_hits.load(Ordering::Relaxed);
+        let cache_misses = self.cache_misses.load(Ordering::Relaxed);
+        let cache_reads = cache_hits + cache_misses;
+        let cache_hit_rate = if cache_reads == 0 {
+            0.0
+        } else {
+            cache_hits as f64 / cache_reads as f64
+        };
 
         // Calculate throughput and error rate
         let total_operations = sync_count + error_count;
@@ -2202,6 +2835,9 @@ impl BlockStorage {
             throughput_blocks_per_sec,
             throughput_bytes_per_sec,
             error_rate,
+            cache_hits,
+            cache_misses,
+            cache_hit_rate,
         }
     }
 
@@ -2233,6 +2869,17 @@ impl BlockStorage {
         self.observability.error_callback = Some(callback);
     }
 
+    /// Sync and backpressure event reporting for this database
+    pub fn observability(&self) -> &super::observability::ObservabilityManager {
+        &self.observability
+    }
+
+    /// Set or clear the JS callback that receives sync and backpressure events
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_storage_event_callback(&self, callback: Option<js_sys::Function>) {
+        self.observability.set_storage_event_callback(callback);
+    }
+
     /// Set WASM sync success callback
     #[cfg(target_arch = "wasm32")]
     pub fn set_sync_success_callback(
@@ -2300,6 +2947,9 @@ impl BlockStorage {
             deallocated_blocks: Mutex::new(HashSet::new()),
             next_block_id: AtomicU64::new(1),
             capacity: 128,
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
+            block_size: BLOCK_SIZE,
             lru_order: Mutex::new(VecDeque::new()),
             checksum_manager: crate::storage::metadata::ChecksumManager::new(
                 crate::storage::metadata::ChecksumAlgorithm::FastHash,
@@ -2340,6 +2990,18 @@ impl BlockStorage {
             recovery_report: RecoveryReport::default(),
             #[cfg(target_arch = "wasm32")]
             leader_election: std::cell::RefCell::new(None),
+            #[cfg(target_arch = "wasm32")]
+            leader_election_config: std::cell::Cell::new(Default::default()),
+            #[cfg(target_arch = "wasm32")]
+            multi_tab: std::cell::Cell::new(true),
+            #[cfg(target_arch = "wasm32")]
+            recover_on_leadership: std::cell::Cell::new(true),
+            #[cfg(target_arch = "wasm32")]
+            recovered_this_term: std::cell::Cell::new(false),
+            #[cfg(target_arch = "wasm32")]
+            verify_after_sync: std::cell::Cell::new(false),
+            #[cfg(target_arch = "wasm32")]
+            leadership_callback: std::cell::RefCell::new(None),
             observability: super::observability::ObservabilityManager::new(),
             metrics: None,
         }
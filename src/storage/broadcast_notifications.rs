@@ -1,7 +1,8 @@
 //! BroadcastChannel-based change notification system
 //!
 //! Allows tabs to notify each other of data changes, schema changes, and leader changes
-//! Uses BroadcastChannel API for cross-tab communication
+//! Uses BroadcastChannel API for cross-tab communication, falling back to `localStorage`
+//! `storage` events where BroadcastChannel is unavailable
 
 use crate::types::DatabaseError;
 use serde::{Deserialize, Serialize};
@@ -21,6 +22,140 @@ pub enum BroadcastNotification {
     LeaderChanged { db_name: String, new_leader: String },
 }
 
+/// Prefix of the localStorage keys used by the fallback transport
+#[cfg(target_arch = "wasm32")]
+const FALLBACK_KEY_PREFIX: &str = "absurder_channel:";
+
+/// Whether this environment provides BroadcastChannel
+#[cfg(target_arch = "wasm32")]
+pub fn broadcast_channel_supported() -> bool {
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("BroadcastChannel"))
+        .map(|ctor| ctor.is_function())
+        .unwrap_or(false)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn fallback_storage() -> Result<web_sys::Storage, DatabaseError> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| {
+            DatabaseError::new(
+                "BROADCAST_ERROR",
+                "Neither BroadcastChannel nor localStorage is available for cross-tab messages",
+            )
+        })
+}
+
+/// Post a message to every other listener on `channel_name`
+///
+/// Uses BroadcastChannel when available. Otherwise the message is written to localStorage
+/// and removed again, which fires a `storage` event in every other tab of the origin.
+/// Unlike BroadcastChannel, the fallback does not reach listeners in the sending tab.
+#[cfg(target_arch = "wasm32")]
+pub fn post_to_channel(channel_name: &str, message: &JsValue) -> Result<(), DatabaseError> {
+    if broadcast_channel_supported() {
+        let channel = BroadcastChannel::new(channel_name).map_err(|e| {
+            DatabaseError::new(
+                "BROADCAST_ERROR",
+                &format!("Failed to create BroadcastChannel: {:?}", e),
+            )
+        })?;
+        return channel.post_message(message).map_err(|e| {
+            DatabaseError::new(
+                "BROADCAST_ERROR",
+                &format!("Failed to post message: {:?}", e),
+            )
+        });
+    }
+
+    let storage = fallback_storage()?;
+    // The nonce makes every write a change, so repeated identical messages still fire events
+    let envelope = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(
+        &envelope,
+        &"nonce".into(),
+        &JsValue::from_f64(js_sys::Math::random()),
+    );
+    let _ = js_sys::Reflect::set(&envelope, &"data".into(), message);
+    let json = js_sys::JSON::stringify(&envelope)
+        .ok()
+        .and_then(|json| json.as_string())
+        .ok_or_else(|| DatabaseError::new("SERIALIZATION_ERROR", "Failed to serialize message"))?;
+    let key = format!("{}{}", FALLBACK_KEY_PREFIX, channel_name);
+    storage.set_item(&key, &json).map_err(|e| {
+        DatabaseError::new(
+            "BROADCAST_ERROR",
+            &format!("Failed to write message to localStorage: {:?}", e),
+        )
+    })?;
+    let _ = storage.remove_item(&key);
+    Ok(())
+}
+
+/// Call `callback` with every message posted to `channel_name` by other listeners
+///
+/// Picks the same transport as `post_to_channel`. The listener lives for the rest of the page.
+#[cfg(target_arch = "wasm32")]
+pub fn listen_on_channel(
+    channel_name: &str,
+    mut callback: impl FnMut(JsValue) + 'static,
+) -> Result<(), DatabaseError> {
+    if broadcast_channel_supported() {
+        let channel = BroadcastChannel::new(channel_name).map_err(|e| {
+            DatabaseError::new(
+                "BROADCAST_ERROR",
+                &format!("Failed to create BroadcastChannel: {:?}", e),
+            )
+        })?;
+        let onmessage_closure = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+            callback(event.data());
+        }) as Box<dyn FnMut(web_sys::MessageEvent)>);
+        channel.set_onmessage(Some(onmessage_closure.as_ref().unchecked_ref()));
+        // Forget the closure to keep it alive (this is intentional - it should live as long as the channel)
+        onmessage_closure.forget();
+        return Ok(());
+    }
+
+    fallback_storage()?;
+    let window = web_sys::window().ok_or_else(|| {
+        DatabaseError::new("BROADCAST_ERROR", "No window to listen for storage events")
+    })?;
+    let key = format!("{}{}", FALLBACK_KEY_PREFIX, channel_name);
+    let onstorage_closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+        let field = |name: &str| {
+            js_sys::Reflect::get(&event, &JsValue::from_str(name))
+                .ok()
+                .and_then(|value| value.as_string())
+        };
+        if field("key").as_deref() != Some(key.as_str()) {
+            return;
+        }
+        // The removal that follows each message fires an event without a new value
+        let Some(json) = field("newValue") else {
+            return;
+        };
+        if let Ok(data) = js_sys::JSON::parse(&json)
+            .and_then(|envelope| js_sys::Reflect::get(&envelope, &"data".into()))
+        {
+            callback(data);
+        }
+    }) as Box<dyn FnMut(web_sys::Event)>);
+    window
+        .add_event_listener_with_callback("storage", onstorage_closure.as_ref().unchecked_ref())
+        .map_err(|e| {
+            DatabaseError::new(
+                "BROADCAST_ERROR",
+                &format!("Failed to listen for storage events: {:?}", e),
+            )
+        })?;
+    onstorage_closure.forget();
+    log::info!(
+        "BroadcastChannel unavailable; {} uses localStorage storage events",
+        channel_name
+    );
+    Ok(())
+}
+
 /// Send a change notification to all tabs
 ///
 /// # Arguments
@@ -42,13 +177,6 @@ pub fn send_change_notification(notification: &BroadcastNotification) -> Result<
         &format!("DEBUG: Sending notification on channel: {}", channel_name).into(),
     );
 
-    // Create BroadcastChannel
-    let channel = BroadcastChannel::new(&channel_name).map_err(|e| {
-        let err_msg = format!("Failed to create BroadcastChannel: {:?}", e);
-        web_sys::console::log_1(&format!("ERROR: {}", err_msg).into());
-        DatabaseError::new("BROADCAST_ERROR", &err_msg)
-    })?;
-
     // Serialize notification to JSON
     let json = serde_json::to_string(notification).map_err(|e| {
         DatabaseError::new(
@@ -68,10 +196,8 @@ pub fn send_change_notification(notification: &BroadcastNotification) -> Result<
     })?;
 
     // Post message
-    channel.post_message(&js_value).map_err(|e| {
-        let err_msg = format!("Failed to post message: {:?}", e);
-        web_sys::console::log_1(&format!("ERROR: {}", err_msg).into());
-        DatabaseError::new("BROADCAST_ERROR", &err_msg)
+    post_to_channel(&channel_name, &js_value).inspect_err(|e| {
+        web_sys::console::log_1(&format!("ERROR: {}", e.message).into());
     })?;
 
     web_sys::console::log_1(
@@ -99,32 +225,17 @@ pub fn register_change_listener(
         &format!("DEBUG: Registering listener on channel: {}", channel_name).into(),
     );
 
-    // Create BroadcastChannel
-    let channel = BroadcastChannel::new(&channel_name).map_err(|e| {
-        let err_msg = format!("Failed to create BroadcastChannel: {:?}", e);
-        web_sys::console::log_1(&format!("ERROR: {}", err_msg).into());
-        DatabaseError::new("BROADCAST_ERROR", &err_msg)
-    })?;
-
-    // Create closure to handle incoming messages
+    // Call the user's callback with each notification
     let callback_clone = callback.clone();
-    let onmessage_closure = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+    listen_on_channel(&channel_name, move |data| {
         web_sys::console::log_1(&"DEBUG: Message received on BroadcastChannel".into());
-
-        // Get the data from the event
-        let data = event.data();
-
-        // Call the user's callback with the data
         if let Err(e) = callback_clone.call1(&JsValue::NULL, &data) {
             web_sys::console::log_1(&format!("ERROR: Callback failed: {:?}", e).into());
         }
-    }) as Box<dyn FnMut(web_sys::MessageEvent)>);
-
-    // Set the onmessage handler
-    channel.set_onmessage(Some(onmessage_closure.as_ref().unchecked_ref()));
-
-    // Forget the closure to keep it alive (this is intentional - it should live as long as the channel)
-    onmessage_closure.forget();
+    })
+    .inspect_err(|e| {
+        web_sys::console::log_1(&format!("ERROR: {}", e.message).into());
+    })?;
 
     web_sys::console::log_1(
         &format!(
@@ -137,6 +248,79 @@ pub fn register_change_listener(
     Ok(())
 }
 
+/// Round-trip a ping through a BroadcastChannel and return the latency in milliseconds
+///
+/// Uses a probe channel next to the database's change channel, so change listeners never
+/// see the ping. Fails when BroadcastChannel is unavailable or the ping does not arrive
+/// within `timeout_ms`.
+#[cfg(target_arch = "wasm32")]
+pub async fn ping_notification_channel(
+    db_name: &str,
+    timeout_ms: u32,
+) -> Result<f64, DatabaseError> {
+    if !broadcast_channel_supported() {
+        // storage events never fire in the writing tab, so the fallback cannot be pinged
+        return Err(DatabaseError::new(
+            "BROADCAST_UNSUPPORTED",
+            "BroadcastChannel is not available; cross-tab notifications use the localStorage fallback, which cannot be tested from a single tab",
+        ));
+    }
+    let channel_name = format!("datasync_ping_{}", db_name);
+    let open = || {
+        BroadcastChannel::new(&channel_name).map_err(|e| {
+            DatabaseError::new(
+                "BROADCAST_ERROR",
+                &format!("Failed to create BroadcastChannel: {:?}", e),
+            )
+        })
+    };
+    // A channel never receives its own messages, so ping from a second instance
+    let sender = open()?;
+    let receiver = open()?;
+
+    let nonce = format!("{}_{}", js_sys::Date::now(), js_sys::Math::random());
+    let arrived = std::rc::Rc::new(std::cell::Cell::new(None::<f64>));
+    let onmessage = {
+        let arrived = arrived.clone();
+        let nonce = nonce.clone();
+        Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+            if event.data().as_string().as_deref() == Some(nonce.as_str()) {
+                arrived.set(Some(js_sys::Date::now()));
+            }
+        }) as Box<dyn FnMut(web_sys::MessageEvent)>)
+    };
+    receiver.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+
+    let sent_at = js_sys::Date::now();
+    let posted = sender.post_message(&JsValue::from_str(&nonce));
+    let result = match posted {
+        Err(e) => Err(DatabaseError::new(
+            "BROADCAST_ERROR",
+            &format!("Failed to post message: {:?}", e),
+        )),
+        Ok(()) => loop {
+            if let Some(at) = arrived.get() {
+                break Ok(at - sent_at);
+            }
+            if js_sys::Date::now() - sent_at >= timeout_ms as f64 {
+                break Err(DatabaseError::new(
+                    "BROADCAST_TIMEOUT",
+                    &format!(
+                        "Ping on {} was not delivered within {} ms",
+                        channel_name, timeout_ms
+                    ),
+                ));
+            }
+            super::retry_logic::sleep_ms(5).await;
+        },
+    };
+
+    receiver.set_onmessage(None);
+    receiver.close();
+    sender.close();
+    result
+}
+
 // Stub implementations for native (not used, but needed for compilation)
 #[cfg(not(target_arch = "wasm32"))]
 pub fn send_change_notification(
@@ -159,6 +343,17 @@ pub fn register_change_listener(
     ))
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn ping_notification_channel(
+    _db_name: &str,
+    _timeout_ms: u32,
+) -> Result<f64, DatabaseError> {
+    Err(DatabaseError::new(
+        "NOT_SUPPORTED",
+        "BroadcastChannel only available in WASM",
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
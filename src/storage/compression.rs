@@ -0,0 +1,200 @@
+/// Block Compression Module
+///
+/// Optional compression applied to blocks as they are persisted to IndexedDB.
+///
+/// Key Features:
+/// - Pluggable codecs via the `BlockCodec` trait (LZ4 by default)
+/// - Blocks that do not shrink are stored uncompressed, so mixed databases are normal
+/// - Per-database compression setting consulted by the persistence layer
+/// - Checksums are always computed over the uncompressed bytes
+use crate::types::DatabaseError;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use tsify::Tsify;
+
+/// Compression applied to blocks before they are written to IndexedDB
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionKind {
+    /// Store blocks as-is
+    #[default]
+    None,
+    /// Fast LZ4 block compression
+    Lz4,
+}
+
+/// A block compression codec
+pub trait BlockCodec {
+    /// Short identifier persisted alongside compressed blocks
+    fn name(&self) -> &'static str;
+    /// Compress a block
+    fn compress(&self, data: &[u8]) -> Vec<u8>;
+    /// Decompress a block produced by `compress`
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, DatabaseError>;
+}
+
+/// LZ4 block codec (uncompressed size is prepended to the payload)
+pub struct Lz4Codec;
+
+impl BlockCodec for Lz4Codec {
+    fn name(&self) -> &'static str {
+        "lz4"
+    }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        lz4_flex::compress_prepend_size(data)
+    }
+
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, DatabaseError> {
+        let error = |detail: &str| {
+            DatabaseError::new(
+                "DECOMPRESSION_ERROR",
+                &format!("Failed to decompress LZ4 block: {}", detail),
+            )
+        };
+        let Some((size, payload)) = data.split_first_chunk::<4>() else {
+            return Err(error("missing size prefix"));
+        };
+        // Check the prefix before trusting it as an allocation size
+        let size = u32::from_le_bytes(*size) as usize;
+        if size > super::block_storage::MAX_BLOCK_SIZE {
+            return Err(error(&format!(
+                "declared size {} exceeds the largest block size",
+                size
+            )));
+        }
+        let block =
+            lz4_flex::block::decompress(payload, size).map_err(|e| error(&e.to_string()))?;
+        if block.len() != size {
+            return Err(error(&format!(
+                "decoded {} bytes but the block declares {}",
+                block.len(),
+                size
+            )));
+        }
+        Ok(block)
+    }
+}
+
+impl CompressionKind {
+    /// Get the codec for this kind, or None when compression is disabled
+    pub fn codec(&self) -> Option<&'static dyn BlockCodec> {
+        match self {
+            CompressionKind::None => None,
+            CompressionKind::Lz4 => Some(&Lz4Codec),
+        }
+    }
+
+    /// Look up a compression kind by the codec name stored with a block
+    pub fn from_codec_name(name: &str) -> Option<Self> {
+        match name {
+            "lz4" => Some(CompressionKind::Lz4),
+            _ => None,
+        }
+    }
+
+    /// Compress a block, returning None if compression is disabled or does not save space
+    pub fn compress(&self, data: &[u8]) -> Option<Vec<u8>> {
+        let compressed = self.codec()?.compress(data);
+        if compressed.len() < data.len() {
+            Some(compressed)
+        } else {
+            None
+        }
+    }
+
+    /// Decompress a block previously compressed with this kind
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, DatabaseError> {
+        match self.codec() {
+            Some(codec) => codec.decompress(data),
+            None => Ok(data.to_vec()),
+        }
+    }
+}
+
+thread_local! {
+    /// Compression setting for each database, keyed by normalized name
+    static DB_COMPRESSION: RefCell<HashMap<String, CompressionKind>> = RefCell::new(HashMap::new());
+}
+
+/// Set the compression used when persisting a database's blocks
+pub fn set_compression(db_name: &str, kind: CompressionKind) {
+    DB_COMPRESSION.with(|settings| {
+        let mut settings = settings.borrow_mut();
+        if kind == CompressionKind::None {
+            settings.remove(db_name);
+        } else {
+            settings.insert(db_name.to_string(), kind);
+        }
+    });
+}
+
+/// Get the compression used when persisting a database's blocks
+pub fn compression_for(db_name: &str) -> CompressionKind {
+    DB_COMPRESSION.with(|settings| settings.borrow().get(db_name).copied().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lz4_roundtrip() {
+        let mut block = vec![0u8; 4096];
+        block[..11].copy_from_slice(b"hello world");
+
+        let compressed = CompressionKind::Lz4
+            .compress(&block)
+            .expect("zero-filled block should compress");
+        assert!(compressed.len() < block.len());
+        assert_eq!(CompressionKind::Lz4.decompress(&compressed).unwrap(), block);
+    }
+
+    #[test]
+    fn test_incompressible_block_stored_raw() {
+        // Simple LCG noise does not compress
+        let mut state: u32 = 12345;
+        let block: Vec<u8> = (0..4096)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect();
+        assert!(CompressionKind::Lz4.compress(&block).is_none());
+    }
+
+    #[test]
+    fn test_none_never_compresses() {
+        assert!(CompressionKind::None.compress(&[0u8; 4096]).is_none());
+        assert_eq!(
+            CompressionKind::from_codec_name("lz4"),
+            Some(CompressionKind::Lz4)
+        );
+        assert_eq!(CompressionKind::from_codec_name("zstd"), None);
+    }
+
+    #[test]
+    fn test_per_database_setting() {
+        set_compression("compressed.db", CompressionKind::Lz4);
+        assert_eq!(compression_for("compressed.db"), CompressionKind::Lz4);
+        assert_eq!(compression_for("other.db"), CompressionKind::None);
+
+        set_compression("compressed.db", CompressionKind::None);
+        assert_eq!(compression_for("compressed.db"), CompressionKind::None);
+    }
+
+    #[test]
+    fn test_corrupt_payload_errors() {
+        let err = CompressionKind::Lz4
+            .decompress(&[0xff, 0xff, 0xff, 0x7f, 1])
+            .unwrap_err();
+        assert_eq!(err.code, "DECOMPRESSION_ERROR");
+
+        // A size prefix that disagrees with the payload is rejected too
+        let mut compressed = CompressionKind::Lz4.compress(&[0u8; 4096]).unwrap();
+        compressed[..4].copy_from_slice(&8192u32.to_le_bytes());
+        let err = CompressionKind::Lz4.decompress(&compressed).unwrap_err();
+        assert_eq!(err.code, "DECOMPRESSION_ERROR");
+    }
+}
@@ -2,7 +2,9 @@
 //! This module contains platform-specific constructor implementations
 
 #[cfg(target_arch = "wasm32")]
-use super::block_storage::{BlockStorage, DEFAULT_CACHE_CAPACITY, RecoveryReport};
+use super::block_storage::{
+    BLOCK_SIZE, BlockStorage, DEFAULT_CACHE_CAPACITY, RecoveryReport, block_cache_capacity_for,
+};
 #[cfg(target_arch = "wasm32")]
 use super::metadata::{ChecksumAlgorithm, ChecksumManager};
 #[cfg(target_arch = "wasm32")]
@@ -55,6 +57,8 @@ pub async fn new_wasm(db_name: &str) -> Result<BlockStorage, DatabaseError> {
             "Successfully restored BlockStorage from IndexedDB for: {}",
             db_name
         ),
+        // Carrying on would hand SQLite an empty database in place of the encrypted one
+        Err(e) if e.code == "DECRYPTION_FAILED" => return Err(e),
         Err(e) => log::warn!(
             "IndexedDB restoration failed for {}: {}",
             db_name,
@@ -329,11 +333,8 @@ pub async fn new_wasm(db_name: &str) -> Result<BlockStorage, DatabaseError> {
                                     let meta_opt = arr.get(1).and_then(|v| v.as_object());
                                     if let (Some(bid), Some(meta)) = (id_opt, meta_opt) {
                                         let algo_opt = meta.get("algo").and_then(|v| v.as_str());
-                                        let algo = match algo_opt {
-                                            Some("FastHash") => Some(ChecksumAlgorithm::FastHash),
-                                            Some("CRC32") => Some(ChecksumAlgorithm::CRC32),
-                                            _ => None, // tolerate invalid/missing by not inserting; will fallback to default later
-                                        };
+                                        // tolerate invalid/missing by not inserting; will fallback to default later
+                                        let algo = algo_opt.and_then(ChecksumAlgorithm::from_name);
                                         if let Some(a) = algo {
                                             map.insert(bid, a);
                                         }
@@ -442,14 +443,8 @@ pub async fn new_wasm(db_name: &str) -> Result<BlockStorage, DatabaseError> {
         map
     };
 
-    // Determine default checksum algorithm from environment (fs_persist native), fallback to FastHash
-    #[cfg(all(not(target_arch = "wasm32"), feature = "fs_persist"))]
-    let checksum_algo_default = match env::var("DATASYNC_CHECKSUM_ALGO").ok().as_deref() {
-        Some("CRC32") => ChecksumAlgorithm::CRC32,
-        _ => ChecksumAlgorithm::FastHash,
-    };
-    #[cfg(not(all(not(target_arch = "wasm32"), feature = "fs_persist")))]
-    let checksum_algo_default = ChecksumAlgorithm::FastHash;
+    // Default checksum algorithm: configured per database, then FastHash
+    let checksum_algo_default = ChecksumAlgorithm::default_for(db_name);
 
     Ok(BlockStorage {
         #[cfg(target_arch = "wasm32")]
@@ -468,7 +463,10 @@ pub async fn new_wasm(db_name: &str) -> Result<BlockStorage, DatabaseError> {
         allocated_blocks: Mutex::new(allocated_blocks),
 
         next_block_id: std::sync::atomic::AtomicU64::new(next_block_id),
-        capacity: DEFAULT_CACHE_CAPACITY,
+        capacity: block_cache_capacity_for(db_name, DEFAULT_CACHE_CAPACITY),
+        cache_hits: std::sync::atomic::AtomicU64::new(0),
+        cache_misses: std::sync::atomic::AtomicU64::new(0),
+        block_size: BLOCK_SIZE,
 
         #[cfg(target_arch = "wasm32")]
         lru_order: RefCell::new(VecDeque::new()),
@@ -533,6 +531,18 @@ pub async fn new_wasm(db_name: &str) -> Result<BlockStorage, DatabaseError> {
         recovery_report: RecoveryReport::default(),
         #[cfg(target_arch = "wasm32")]
         leader_election: std::cell::RefCell::new(None),
+        #[cfg(target_arch = "wasm32")]
+        leader_election_config: std::cell::Cell::new(Default::default()),
+        #[cfg(target_arch = "wasm32")]
+        multi_tab: std::cell::Cell::new(true),
+        #[cfg(target_arch = "wasm32")]
+        recover_on_leadership: std::cell::Cell::new(true),
+        #[cfg(target_arch = "wasm32")]
+        recovered_this_term: std::cell::Cell::new(false),
+        #[cfg(target_arch = "wasm32")]
+        verify_after_sync: std::cell::Cell::new(false),
+        #[cfg(target_arch = "wasm32")]
+        leadership_callback: std::cell::RefCell::new(None),
         observability: super::observability::ObservabilityManager::new(),
         #[cfg(feature = "telemetry")]
         metrics: None,
@@ -0,0 +1,83 @@
+/// IndexedDB Transaction Durability
+///
+/// Per-database durability hint applied to the IndexedDB transactions that persist blocks.
+///
+/// Key Features:
+/// - `strict` waits for the data to reach disk before the transaction completes
+/// - `relaxed` completes once the OS has the data, trading crash safety for speed
+/// - Browsers that do not support the hint ignore it and use their default
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use tsify::Tsify;
+
+/// Durability hint passed to IndexedDB when blocks are persisted
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Durability {
+    /// Leave the choice to the browser
+    #[default]
+    Default,
+    /// Flush to disk before the sync completes; the last write survives a power loss
+    Strict,
+    /// Complete once the OS has the data; faster, but the last sync may be lost on a crash
+    Relaxed,
+}
+
+impl Durability {
+    /// Value of the `durability` transaction option
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Durability::Default => "default",
+            Durability::Strict => "strict",
+            Durability::Relaxed => "relaxed",
+        }
+    }
+}
+
+thread_local! {
+    /// Durability setting for each database, keyed by normalized name
+    static DB_DURABILITY: RefCell<HashMap<String, Durability>> = RefCell::new(HashMap::new());
+}
+
+/// Set the durability hint used when persisting a database's blocks
+pub fn set_durability(db_name: &str, durability: Durability) {
+    DB_DURABILITY.with(|settings| {
+        let mut settings = settings.borrow_mut();
+        if durability == Durability::Default {
+            settings.remove(db_name);
+        } else {
+            settings.insert(db_name.to_string(), durability);
+        }
+    });
+}
+
+/// Get the durability hint used when persisting a database's blocks
+pub fn durability_for(db_name: &str) -> Durability {
+    DB_DURABILITY.with(|settings| settings.borrow().get(db_name).copied().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_database_setting() {
+        set_durability("relaxed.db", Durability::Relaxed);
+        assert_eq!(durability_for("relaxed.db"), Durability::Relaxed);
+        assert_eq!(durability_for("other.db"), Durability::Default);
+
+        set_durability("relaxed.db", Durability::Default);
+        assert_eq!(durability_for("relaxed.db"), Durability::Default);
+    }
+
+    #[test]
+    fn test_option_values() {
+        assert_eq!(Durability::Strict.as_str(), "strict");
+        assert_eq!(Durability::Relaxed.as_str(), "relaxed");
+        assert_eq!(
+            serde_json::to_string(&Durability::Relaxed).unwrap(),
+            "\"relaxed\""
+        );
+    }
+}
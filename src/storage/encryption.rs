@@ -0,0 +1,239 @@
+/// Block Encryption Module
+///
+/// Optional encryption applied to blocks as they are persisted to IndexedDB.
+///
+/// Key Features:
+/// - AES-256-GCM per block, with the block id as associated data so blocks cannot be swapped
+/// - A fresh random 96-bit nonce per write, stored in front of the ciphertext
+/// - Keys are derived from the supplied passphrase with PBKDF2-HMAC-SHA256, salted by database
+/// - Blocks are compressed before they are encrypted; checksums always cover the plaintext,
+///   so a failed decryption (`DECRYPTION_FAILED`) is never confused with corruption
+use crate::types::DatabaseError;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Identifier persisted alongside encrypted blocks
+pub const CIPHER_NAME: &str = "aes-256-gcm";
+
+/// PBKDF2 rounds used to turn a passphrase into a block key
+pub const KEY_DERIVATION_ROUNDS: u32 = 100_000;
+
+const NONCE_LEN: usize = 12;
+
+/// Encrypts and decrypts the blocks of one database
+#[derive(Clone)]
+pub struct BlockCipher {
+    key: [u8; 32],
+    cipher: Aes256Gcm,
+}
+
+impl BlockCipher {
+    /// Derive the block key for `db_name` from a passphrase
+    pub fn derive(db_name: &str, passphrase: &str) -> Self {
+        let salt = format!("absurder-sql:{}", db_name);
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(
+            passphrase.as_bytes(),
+            salt.as_bytes(),
+            KEY_DERIVATION_ROUNDS,
+            &mut key,
+        );
+        Self::from_key(key)
+    }
+
+    /// Use a raw 256-bit key
+    pub fn from_key(key: [u8; 32]) -> Self {
+        Self {
+            key,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        }
+    }
+
+    /// Encrypt a block, returning the nonce followed by the ciphertext and tag
+    pub fn encrypt(&self, block_id: u64, data: &[u8]) -> Result<Vec<u8>, DatabaseError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce).map_err(|e| {
+            DatabaseError::new(
+                "ENCRYPTION_FAILED",
+                &format!("Failed to generate a nonce: {}", e),
+            )
+        })?;
+        let aad = block_id.to_le_bytes();
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: data,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| {
+                DatabaseError::new(
+                    "ENCRYPTION_FAILED",
+                    &format!("Failed to encrypt block {}", block_id),
+                )
+            })?;
+
+        let mut out = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    /// Decrypt a block produced by `encrypt`
+    ///
+    /// Fails with `DECRYPTION_FAILED` for a wrong key, a block stored under another id or a
+    /// tampered payload.
+    pub fn decrypt(&self, block_id: u64, data: &[u8]) -> Result<Vec<u8>, DatabaseError> {
+        let failed = || {
+            DatabaseError::new(
+                "DECRYPTION_FAILED",
+                &format!(
+                    "Failed to decrypt block {} (wrong encryption key?)",
+                    block_id
+                ),
+            )
+        };
+        if data.len() < NONCE_LEN {
+            return Err(failed());
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let aad = block_id.to_le_bytes();
+        self.cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| failed())
+    }
+}
+
+thread_local! {
+    /// Encryption key for each database, keyed by normalized name
+    static DB_ENCRYPTION: RefCell<HashMap<String, BlockCipher>> = RefCell::new(HashMap::new());
+}
+
+/// Set the passphrase used to encrypt a database's blocks, or None to store them unencrypted
+///
+/// Fails with `DECRYPTION_FAILED` if the database's blocks are already loaded in this tab under
+/// a different key (or a key is dropped), since those blocks were decrypted with the old key and
+/// would otherwise be served without checking the new one.
+pub fn set_encryption_key(db_name: &str, passphrase: Option<&str>) -> Result<(), DatabaseError> {
+    let cipher = passphrase.map(|p| BlockCipher::derive(db_name, p));
+    let loaded = super::vfs_sync::with_global_storage(|gs| {
+        gs.borrow()
+            .get(db_name)
+            .is_some_and(|blocks| !blocks.is_empty())
+    });
+
+    DB_ENCRYPTION.with(|settings| {
+        let mut settings = settings.borrow_mut();
+        let current = settings.get(db_name).map(|c| c.key);
+        if loaded && current.is_some() && current != cipher.as_ref().map(|c| c.key) {
+            return Err(DatabaseError::new(
+                "DECRYPTION_FAILED",
+                &format!(
+                    "{} is already open with a different encryption key",
+                    db_name
+                ),
+            ));
+        }
+        match cipher {
+            Some(cipher) => settings.insert(db_name.to_string(), cipher),
+            None => settings.remove(db_name),
+        };
+        Ok(())
+    })
+}
+
+/// Get the cipher used when persisting a database's blocks, if it is encrypted
+pub fn cipher_for(db_name: &str) -> Option<BlockCipher> {
+    DB_ENCRYPTION.with(|settings| settings.borrow().get(db_name).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let cipher = BlockCipher::from_key([7u8; 32]);
+        let mut block = vec![0u8; 4096];
+        block[..11].copy_from_slice(b"hello world");
+
+        let encrypted = cipher.encrypt(3, &block).unwrap();
+        assert_ne!(&encrypted[NONCE_LEN..NONCE_LEN + 11], b"hello world");
+        assert_eq!(cipher.decrypt(3, &encrypted).unwrap(), block);
+    }
+
+    #[test]
+    fn test_nonce_differs_per_write() {
+        let cipher = BlockCipher::from_key([7u8; 32]);
+        let a = cipher.encrypt(1, &[0u8; 64]).unwrap();
+        let b = cipher.encrypt(1, &[0u8; 64]).unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let encrypted = BlockCipher::derive("secret.db", "right")
+            .encrypt(1, &[1u8; 512])
+            .unwrap();
+        let err = BlockCipher::derive("secret.db", "wrong")
+            .decrypt(1, &encrypted)
+            .unwrap_err();
+        assert_eq!(err.code, "DECRYPTION_FAILED");
+    }
+
+    #[test]
+    fn test_block_id_is_authenticated() {
+        let cipher = BlockCipher::from_key([9u8; 32]);
+        let encrypted = cipher.encrypt(1, &[1u8; 512]).unwrap();
+        assert_eq!(
+            cipher.decrypt(2, &encrypted).unwrap_err().code,
+            "DECRYPTION_FAILED"
+        );
+        assert_eq!(
+            cipher.decrypt(1, &encrypted[..4]).unwrap_err().code,
+            "DECRYPTION_FAILED"
+        );
+    }
+
+    #[test]
+    fn test_key_change_refused_while_loaded() {
+        let db = "encryption_loaded.db";
+        super::super::vfs_sync::with_global_storage(|gs| {
+            gs.borrow_mut()
+                .insert(db.to_string(), HashMap::from([(1, vec![0u8; 16])]))
+        });
+
+        set_encryption_key(db, Some("first")).unwrap();
+        set_encryption_key(db, Some("first")).unwrap();
+        assert_eq!(
+            set_encryption_key(db, Some("second")).unwrap_err().code,
+            "DECRYPTION_FAILED"
+        );
+        assert_eq!(
+            set_encryption_key(db, None).unwrap_err().code,
+            "DECRYPTION_FAILED"
+        );
+
+        super::super::vfs_sync::with_global_storage(|gs| gs.borrow_mut().remove(db));
+        set_encryption_key(db, None).unwrap();
+        assert!(cipher_for(db).is_none());
+    }
+
+    #[test]
+    fn test_key_is_salted_by_database() {
+        let a = BlockCipher::derive("a.db", "passphrase");
+        let b = BlockCipher::derive("b.db", "passphrase");
+        assert_ne!(a.key, b.key);
+        assert_eq!(a.key, BlockCipher::derive("a.db", "passphrase").key);
+    }
+}
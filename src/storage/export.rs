@@ -8,14 +8,13 @@
 //! - **Validation**: Verify SQLite file format integrity
 //!
 //! # Architecture
-//! The system works with 4096-byte blocks stored in IndexedDB. Export reads all allocated blocks
+//! The system works with fixed-size blocks (4096 bytes by default) stored in IndexedDB. Export reads all allocated blocks
 //! and concatenates them into a standard SQLite file. Import splits a .db file into blocks and
 //! writes them to IndexedDB with proper metadata tracking.
 
 use crate::storage::block_storage::BlockStorage;
 use crate::types::DatabaseError;
 
-const BLOCK_SIZE: usize = 4096;
 /// Default maximum export size: 2GB
 ///
 /// Rationale:
@@ -418,7 +417,7 @@ async fn export_database_to_bytes_impl(
         page_count,
         total_db_size
     );
-    let total_blocks = total_db_size.div_ceil(BLOCK_SIZE as u64);
+    let total_blocks = total_db_size.div_ceil(storage.get_block_size() as u64);
 
     // Build list of block IDs to read
     let block_ids: Vec<u64> = (0..total_blocks).collect();
@@ -599,9 +598,10 @@ async fn export_database_with_options_impl(
         total_db_size
     );
 
-    let total_blocks = total_db_size.div_ceil(BLOCK_SIZE as u64);
+    let block_size = storage.get_block_size() as u64;
+    let total_blocks = total_db_size.div_ceil(block_size);
     let chunk_size = options.chunk_size_bytes.unwrap_or(DEFAULT_CHUNK_SIZE);
-    let blocks_per_chunk = (chunk_size / BLOCK_SIZE as u64).max(1);
+    let blocks_per_chunk = (chunk_size / block_size).max(1);
 
     // Preallocate result vector
     let mut result = Vec::with_capacity(total_db_size as usize);
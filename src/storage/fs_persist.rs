@@ -72,7 +72,7 @@ impl super::BlockStorage {
     pub(super) fn fs_persist_sync(&mut self) -> Result<(), DatabaseError> {
         // Record sync start for observability
         let dirty_count = lock_mutex!(self.dirty_blocks).len();
-        let dirty_bytes = dirty_count * super::BLOCK_SIZE;
+        let dirty_bytes = dirty_count * self.block_size;
         self.observability
             .record_sync_start(dirty_count, dirty_bytes);
 
@@ -136,13 +136,10 @@ impl super::BlockStorage {
                                     let ok = obj
                                         .get("algo")
                                         .and_then(|v| v.as_str())
-                                        .map(|s| s == "FastHash" || s == "CRC32")
+                                        .map(|s| ChecksumAlgorithm::from_name(s).is_some())
                                         .unwrap_or(false);
                                     if !ok {
-                                        let def = match self.checksum_manager.default_algorithm() {
-                                            ChecksumAlgorithm::CRC32 => "CRC32",
-                                            _ => "FastHash",
-                                        };
+                                        let def = self.checksum_manager.default_algorithm().name();
                                         obj.insert(
                                             "algo".into(),
                                             serde_json::Value::String(def.into()),
@@ -412,12 +409,12 @@ impl super::BlockStorage {
                         let version = next_commit as u32;
                         db_meta.insert(
                             block_id,
-                            BlockMetadataPersist {
+                            BlockMetadataPersist::new(
                                 checksum,
-                                last_modified_ms: Self::now_millis(),
                                 version,
-                                algo: self.checksum_manager.get_algorithm(block_id),
-                            },
+                                Self::now_millis(),
+                                self.checksum_manager.get_algorithm(block_id),
+                            ),
                         );
                         log::debug!("Persisted metadata for block {}", block_id);
                     }
@@ -704,10 +701,7 @@ impl super::BlockStorage {
                         .unwrap_or(0)
                         .saturating_add(1);
                     let algo = self.checksum_manager.get_algorithm(*block_id);
-                    let algo_str = match algo {
-                        ChecksumAlgorithm::CRC32 => "CRC32",
-                        _ => "FastHash",
-                    };
+                    let algo_str = algo.name();
                     let mut obj = serde_json::Map::new();
                     obj.insert("checksum".into(), serde_json::Value::from(checksum));
                     obj.insert("last_modified_ms".into(), serde_json::Value::from(now_ms));
@@ -721,13 +715,10 @@ impl super::BlockStorage {
                 let ok = obj
                     .get("algo")
                     .and_then(|v| v.as_str())
-                    .map(|s| s == "FastHash" || s == "CRC32")
+                    .map(|s| ChecksumAlgorithm::from_name(s).is_some())
                     .unwrap_or(false);
                 if !ok {
-                    let def = match self.checksum_manager.default_algorithm() {
-                        ChecksumAlgorithm::CRC32 => "CRC32",
-                        _ => "FastHash",
-                    };
+                    let def = self.checksum_manager.default_algorithm().name();
                     obj.insert("algo".into(), serde_json::Value::String(def.into()));
                 }
             }
@@ -915,12 +906,12 @@ impl super::BlockStorage {
                         let version = next_commit as u32;
                         db_meta.insert(
                             block_id,
-                            BlockMetadataPersist {
+                            BlockMetadataPersist::new(
                                 checksum,
-                                last_modified_ms: Self::now_millis(),
                                 version,
-                                algo: self.checksum_manager.get_algorithm(block_id),
-                            },
+                                Self::now_millis(),
+                                self.checksum_manager.get_algorithm(block_id),
+                            ),
                         );
                         log::debug!("[test] Persisted metadata for block {}", block_id);
                     }
@@ -4,9 +4,13 @@
 
 use super::block_storage::BLOCK_SIZE;
 use super::export::validate_sqlite_file;
-use crate::types::DatabaseError;
+use crate::types::{DatabaseError, ImportReport};
 use crate::utils::normalize_db_name;
 
+/// Header offsets read by `validate_import_bytes`
+const SCHEMA_COOKIE_OFFSET: usize = 40;
+const TEXT_ENCODING_OFFSET: usize = 56;
+
 /// Clear all storage data for a specific database
 ///
 /// Removes all blocks, metadata, commit markers, and allocation maps for the specified
@@ -148,6 +152,254 @@ pub async fn clear_database_storage(db_name: &str) -> Result<(), DatabaseError>
     Ok(())
 }
 
+/// Check whether `data` can be imported without writing anything to storage
+///
+/// Reads the page size, page count and schema cookie from the header, runs the same
+/// checks as `validate_sqlite_file`, then opens a private in-memory copy and runs
+/// `PRAGMA integrity_check`. Problems are collected in the report rather than returned
+/// as errors so callers can show all of them; an `Err` means the bytes could not be
+/// examined at all.
+///
+/// # Example
+/// ```rust,no_run
+/// use absurder_sql::storage::import::validate_import_bytes;
+///
+/// let bytes = std::fs::read("upload.db").unwrap();
+/// let report = validate_import_bytes(&bytes).unwrap();
+/// if !report.problems.is_empty() {
+///     eprintln!("Rejected upload: {}", report.problems.join("; "));
+/// }
+/// ```
+pub fn validate_import_bytes(data: &[u8]) -> Result<ImportReport, DatabaseError> {
+    if data.len() < 100 {
+        return Err(DatabaseError::new(
+            "INVALID_SQLITE_FILE",
+            &format!(
+                "File too small: {} bytes (minimum 100 required)",
+                data.len()
+            ),
+        ));
+    }
+
+    let read_u32 = |offset: usize| {
+        u32::from_be_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ])
+    };
+    let mut report = ImportReport {
+        page_size: 0,
+        page_count: 0,
+        schema_version: 0,
+        integrity_ok: false,
+        encrypted: false,
+        problems: Vec::new(),
+    };
+
+    if !data.starts_with(b"SQLite format 3\0") {
+        // Encrypted databases keep their page layout but scramble the header
+        report.encrypted = data.len() % 512 == 0;
+        report.problems.push(if report.encrypted {
+            "File is encrypted or has no SQLite header".to_string()
+        } else {
+            "File is not a SQLite database".to_string()
+        });
+        return Ok(report);
+    }
+
+    let page_size = u16::from_be_bytes([data[16], data[17]]);
+    report.page_size = if page_size == 1 {
+        65536
+    } else {
+        page_size as u32
+    };
+    report.page_count = read_u32(28);
+    report.schema_version = read_u32(SCHEMA_COOKIE_OFFSET);
+
+    if let Err(e) = validate_sqlite_file(data) {
+        report.problems.push(e.message);
+    }
+    let encoding = read_u32(TEXT_ENCODING_OFFSET);
+    if !(1..=3).contains(&encoding) {
+        report
+            .problems
+            .push(format!("Unknown text encoding {} in header", encoding));
+    }
+
+    if report.problems.is_empty() {
+        match integrity_check_bytes(data) {
+            Ok(messages) => report.problems.extend(messages),
+            Err(e) => report
+                .problems
+                .push(format!("Integrity check could not run: {}", e.message)),
+        }
+    }
+    report.integrity_ok = report.problems.is_empty();
+    Ok(report)
+}
+
+/// Run `PRAGMA integrity_check` on a private in-memory copy of `data`
+///
+/// Returns the problems SQLite found, or an empty list when the check reports `ok`.
+#[cfg(target_arch = "wasm32")]
+fn integrity_check_bytes(data: &[u8]) -> Result<Vec<String>, DatabaseError> {
+    use std::ffi::{CStr, CString};
+
+    let mut db = std::ptr::null_mut();
+    let name = CString::new(":memory:").expect("valid name");
+    let ret = unsafe {
+        sqlite_wasm_rs::sqlite3_open_v2(
+            name.as_ptr(),
+            &mut db,
+            sqlite_wasm_rs::SQLITE_OPEN_READWRITE | sqlite_wasm_rs::SQLITE_OPEN_CREATE,
+            std::ptr::null(),
+        )
+    };
+    let error = |db: *mut sqlite_wasm_rs::sqlite3, code: i32| {
+        let message = unsafe { CStr::from_ptr(sqlite_wasm_rs::sqlite3_errmsg(db)) }
+            .to_string_lossy()
+            .into_owned();
+        DatabaseError::new("SQLITE_ERROR", &message).with_sqlite_code(code)
+    };
+    if ret != sqlite_wasm_rs::SQLITE_OK {
+        let e = error(db, ret);
+        unsafe { sqlite_wasm_rs::sqlite3_close(db) };
+        return Err(e);
+    }
+
+    let result = (|| {
+        let buffer = unsafe { sqlite_wasm_rs::sqlite3_malloc64(data.len() as u64) } as *mut u8;
+        if buffer.is_null() {
+            return Err(DatabaseError::new(
+                "OUT_OF_MEMORY",
+                &format!("Failed to allocate {} bytes for validation", data.len()),
+            ));
+        }
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), buffer, data.len()) };
+        let schema = CString::new("main").expect("valid schema name");
+        // SQLite owns the buffer from here on, even when deserialize fails
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_deserialize(
+                db,
+                schema.as_ptr(),
+                buffer,
+                data.len() as i64,
+                data.len() as i64,
+                sqlite_wasm_rs::SQLITE_DESERIALIZE_FREEONCLOSE
+                    | sqlite_wasm_rs::SQLITE_DESERIALIZE_READONLY,
+            )
+        };
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            return Err(error(db, ret));
+        }
+
+        let sql = CString::new("PRAGMA integrity_check").expect("valid SQL");
+        let mut stmt = std::ptr::null_mut();
+        let ret = unsafe {
+            sqlite_wasm_rs::sqlite3_prepare_v2(
+                db,
+                sql.as_ptr(),
+                -1,
+                &mut stmt,
+                std::ptr::null_mut(),
+            )
+        };
+        if ret != sqlite_wasm_rs::SQLITE_OK {
+            return Err(error(db, ret));
+        }
+        let mut messages = Vec::new();
+        let ret = loop {
+            match unsafe { sqlite_wasm_rs::sqlite3_step(stmt) } {
+                sqlite_wasm_rs::SQLITE_ROW => {
+                    let text = unsafe { sqlite_wasm_rs::sqlite3_column_text(stmt, 0) };
+                    if !text.is_null() {
+                        messages.push(
+                            unsafe { CStr::from_ptr(text as *const std::ffi::c_char) }
+                                .to_string_lossy()
+                                .into_owned(),
+                        );
+                    }
+                }
+                code => break code,
+            }
+        };
+        unsafe { sqlite_wasm_rs::sqlite3_finalize(stmt) };
+        if ret != sqlite_wasm_rs::SQLITE_DONE {
+            // A file damaged badly enough fails the check outright
+            return Ok(vec![error(db, ret).message]);
+        }
+        messages.retain(|message| message != "ok");
+        Ok(messages)
+    })();
+    unsafe { sqlite_wasm_rs::sqlite3_close(db) };
+    result
+}
+
+/// Run `PRAGMA integrity_check` on a scratch copy of `data`
+///
+/// Returns the problems SQLite found, or an empty list when the check reports `ok`.
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(
+        feature = "bundled-sqlite",
+        feature = "encryption",
+        feature = "encryption-commoncrypto",
+        feature = "encryption-ios"
+    )
+))]
+fn integrity_check_bytes(data: &[u8]) -> Result<Vec<String>, DatabaseError> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let path = std::env::temp_dir().join(format!(
+        "absurder_validate_{}_{}.db",
+        std::process::id(),
+        nanos
+    ));
+    std::fs::write(&path, data).map_err(|e| {
+        DatabaseError::new(
+            "IO_ERROR",
+            &format!("Failed to write scratch copy for validation: {}", e),
+        )
+    })?;
+
+    let result =
+        Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY).and_then(|conn| {
+            let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+            let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+            rows.collect::<Result<Vec<_>, _>>()
+        });
+    let _ = std::fs::remove_file(&path);
+
+    match result {
+        Ok(mut messages) => {
+            messages.retain(|message| message != "ok");
+            Ok(messages)
+        }
+        // A file damaged badly enough fails the check outright
+        Err(e) => Ok(vec![e.to_string()]),
+    }
+}
+
+/// Without a SQLite library only the header checks run
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    not(any(
+        feature = "bundled-sqlite",
+        feature = "encryption",
+        feature = "encryption-commoncrypto",
+        feature = "encryption-ios"
+    ))
+))]
+fn integrity_check_bytes(_data: &[u8]) -> Result<Vec<String>, DatabaseError> {
+    Ok(Vec::new())
+}
+
 /// Import SQLite database from bytes into BlockStorage
 ///
 /// Takes a complete SQLite .db file and imports it into the block-based storage system.
@@ -180,6 +432,18 @@ pub async fn clear_database_storage(db_name: &str) -> Result<(), DatabaseError>
 /// # }
 /// ```
 pub async fn import_database_from_bytes(db_name: &str, data: Vec<u8>) -> Result<(), DatabaseError> {
+    import_database_from_bytes_with_block_size(db_name, data, BLOCK_SIZE).await
+}
+
+/// Import a SQLite database, splitting it into blocks of `block_size` bytes
+///
+/// Use this for databases configured with a non-default `block_size` so the imported
+/// blocks match what the storage layer expects.
+pub async fn import_database_from_bytes_with_block_size(
+    db_name: &str,
+    data: Vec<u8>,
+    block_size: usize,
+) -> Result<(), DatabaseError> {
     use super::vfs_sync::{with_global_allocation_map, with_global_storage};
     use std::collections::{HashMap, HashSet};
 
@@ -193,8 +457,9 @@ pub async fn import_database_from_bytes(db_name: &str, data: Vec<u8>) -> Result<
         data.len()
     );
 
-    // Step 1: Validate SQLite file format
+    // Step 1: Validate SQLite file format and block size
     validate_sqlite_file(&data)?;
+    super::block_storage::validate_block_size(block_size)?;
     log::debug!("SQLite file validation passed");
 
     // Step 2: Clear existing storage from memory (this also does registry and connection pool cleanup)
@@ -214,35 +479,35 @@ pub async fn import_database_from_bytes(db_name: &str, data: Vec<u8>) -> Result<
         log::debug!("All old blocks deleted from IndexedDB");
     }
 
-    // Step 4: Split data into BLOCK_SIZE chunks
-    let total_blocks = data.len().div_ceil(BLOCK_SIZE);
+    // Step 4: Split data into block_size chunks
+    let total_blocks = data.len().div_ceil(block_size);
     log::debug!(
         "Splitting {} bytes into {} blocks of {} bytes",
         data.len(),
         total_blocks,
-        BLOCK_SIZE
+        block_size
     );
 
     let mut blocks = HashMap::new();
     let mut allocated_ids = HashSet::new();
 
     for block_id in 0..total_blocks {
-        let start = block_id * BLOCK_SIZE;
-        let end = std::cmp::min(start + BLOCK_SIZE, data.len());
+        let start = block_id * block_size;
+        let end = std::cmp::min(start + block_size, data.len());
 
-        let mut block_data = Vec::with_capacity(BLOCK_SIZE);
+        let mut block_data = Vec::with_capacity(block_size);
         block_data.extend_from_slice(&data[start..end]);
 
         // Step 4: Pad last block with zeros if needed
-        if block_data.len() < BLOCK_SIZE {
-            let padding = BLOCK_SIZE - block_data.len();
-            block_data.resize(BLOCK_SIZE, 0);
+        if block_data.len() < block_size {
+            let padding = block_size - block_data.len();
+            block_data.resize(block_size, 0);
             log::debug!(
                 "Block {} padded with {} zero bytes ({} -> {} bytes)",
                 block_id,
                 padding,
                 end - start,
-                BLOCK_SIZE
+                block_size
             );
         }
 
@@ -289,12 +554,12 @@ pub async fn import_database_from_bytes(db_name: &str, data: Vec<u8>) -> Result<
 
                 db_metadata.insert(
                     *block_id,
-                    BlockMetadataPersist {
-                        version: 1, // All imported blocks start at version 1
+                    BlockMetadataPersist::new(
                         checksum,
-                        last_modified_ms: 0,
-                        algo: ChecksumAlgorithm::CRC32,
-                    },
+                        1, // All imported blocks start at version 1
+                        0,
+                        ChecksumAlgorithm::CRC32,
+                    ),
                 );
             }
 
@@ -327,12 +592,12 @@ pub async fn import_database_from_bytes(db_name: &str, data: Vec<u8>) -> Result<
 
                 db_metadata.insert(
                     *block_id,
-                    BlockMetadataPersist {
-                        version: 1, // All imported blocks start at version 1
+                    BlockMetadataPersist::new(
                         checksum,
-                        last_modified_ms: 0,
-                        algo: ChecksumAlgorithm::CRC32,
-                    },
+                        1, // All imported blocks start at version 1
+                        0,
+                        ChecksumAlgorithm::CRC32,
+                    ),
                 );
             }
 
@@ -374,12 +639,12 @@ pub async fn import_database_from_bytes(db_name: &str, data: Vec<u8>) -> Result<
 
             meta_entries.push((
                 *block_id,
-                super::metadata::BlockMetadataPersist {
-                    version: 1,
+                super::metadata::BlockMetadataPersist::new(
                     checksum,
-                    last_modified_ms: 0,
-                    algo: ChecksumAlgorithm::CRC32,
-                },
+                    1,
+                    0,
+                    ChecksumAlgorithm::CRC32,
+                ),
             ));
         }
 
@@ -590,3 +855,117 @@ pub fn invalidate_block_storage_caches(db_name: &str) {
         );
     }
 }
+
+/// Replace a database's blocks with an imported file without tearing down its storage
+///
+/// The file is validated and split into blocks before anything is touched, so a
+/// rejected file leaves the existing data untouched. The new blocks are persisted to
+/// IndexedDB in one transaction with a new commit marker and only then replace the live
+/// ones, so a failed write leaves the old contents in place. Stale blocks past the end of
+/// the new file are deleted last.
+///
+/// Open `BlockStorage` instances stay registered; call `on_database_import` on them
+/// afterwards so their caches pick up the new contents. Returns the number of blocks
+/// imported.
+#[cfg(target_arch = "wasm32")]
+pub async fn import_database_in_place(
+    db_name: &str,
+    data: &[u8],
+    block_size: usize,
+) -> Result<usize, DatabaseError> {
+    use super::metadata::{BlockMetadataPersist, ChecksumAlgorithm, ChecksumManager};
+    use super::vfs_sync::{
+        with_global_allocation_map, with_global_commit_marker, with_global_metadata,
+        with_global_storage,
+    };
+    use std::collections::{HashMap, HashSet};
+
+    let db_name = normalize_db_name(db_name);
+    let db_name = db_name.as_str();
+
+    validate_sqlite_file(data)?;
+    super::block_storage::validate_block_size(block_size)?;
+
+    // Split the file into blocks and their metadata
+    let algo = ChecksumAlgorithm::default_for(db_name);
+    let mut blocks = HashMap::new();
+    let mut metadata = HashMap::new();
+    for (block_id, chunk) in data.chunks(block_size).enumerate() {
+        let mut block = chunk.to_vec();
+        block.resize(block_size, 0);
+        metadata.insert(
+            block_id as u64,
+            BlockMetadataPersist::new(
+                ChecksumManager::compute_checksum_with(&block, algo),
+                1,
+                0,
+                algo,
+            ),
+        );
+        blocks.insert(block_id as u64, block);
+    }
+    let block_count = blocks.len();
+    let new_ids: HashSet<u64> = blocks.keys().copied().collect();
+    let blocks_to_persist: Vec<(u64, Vec<u8>)> = blocks
+        .iter()
+        .map(|(&id, block)| (id, block.clone()))
+        .collect();
+    let metadata_to_persist: Vec<(u64, u64)> = metadata
+        .iter()
+        .map(|(&id, meta)| (id, meta.checksum))
+        .collect();
+    let commit_marker =
+        with_global_commit_marker(|cm| cm.borrow().get(db_name).copied().unwrap_or(0) + 1);
+
+    // Persist before touching the live state so a failed write changes nothing
+    super::wasm_indexeddb::persist_to_indexeddb_event_based(
+        db_name,
+        blocks_to_persist,
+        metadata_to_persist,
+        commit_marker,
+        #[cfg(feature = "telemetry")]
+        None,
+        #[cfg(feature = "telemetry")]
+        None,
+    )
+    .await
+    .map_err(|e| {
+        DatabaseError::new(
+            "IMPORT_SYNC_FAILED",
+            &format!("Failed to persist imported data: {}", e),
+        )
+    })?;
+
+    // Swap the new blocks into place; nothing awaits between the steps
+    let old_ids: HashSet<u64> = with_global_storage(|gs| {
+        gs.borrow_mut()
+            .insert(db_name.to_string(), blocks)
+            .map(|old| old.into_keys().collect())
+            .unwrap_or_default()
+    });
+    with_global_metadata(|gm| gm.borrow_mut().insert(db_name.to_string(), metadata));
+    let old_ids: HashSet<u64> = with_global_allocation_map(|gam| {
+        gam.borrow_mut()
+            .insert(db_name.to_string(), new_ids.clone())
+            .unwrap_or_default()
+    })
+    .union(&old_ids)
+    .copied()
+    .collect();
+    with_global_commit_marker(|cm| cm.borrow_mut().insert(db_name.to_string(), commit_marker));
+
+    // Blocks past the end of the imported file belong to the old database
+    let stale: Vec<u64> = old_ids.difference(&new_ids).copied().collect();
+    if !stale.is_empty() {
+        super::wasm_indexeddb::delete_blocks_from_indexeddb(db_name, &stale).await?;
+    }
+
+    log::info!(
+        "In-place import complete for {} ({} blocks, {} bytes, {} stale blocks removed)",
+        db_name,
+        block_count,
+        data.len(),
+        stale.len()
+    );
+    Ok(block_count)
+}
@@ -0,0 +1,347 @@
+//! Incremental export and import
+//!
+//! Serializes only the blocks committed after a given commit marker so a backup can be
+//! kept current without re-uploading the whole database.
+//!
+//! # Delta Format
+//! All integers are little-endian.
+//! - Bytes 0-7: Magic string "ABSQLDLT"
+//! - u32: Format version (currently 1)
+//! - u32: Block size
+//! - u64: Commit marker the delta starts after
+//! - u64: Commit marker the delta brings the database up to
+//! - u64: Database size in bytes (page_size * page_count)
+//! - u32: Number of blocks, followed by that many (u64 block id, block bytes) entries
+//!
+//! Markers are only meaningful for the database that produced them. Importing a full
+//! database file resets the marker, so a fresh full export is needed afterwards.
+
+use crate::types::DatabaseError;
+
+const DELTA_MAGIC: &[u8; 8] = b"ABSQLDLT";
+const DELTA_FORMAT_VERSION: u32 = 1;
+const DELTA_HEADER_SIZE: usize = 8 + 4 + 4 + 8 + 8 + 8 + 4;
+
+/// Blocks changed between two commit markers
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncrementalDelta {
+    /// Block size the blocks were written with
+    pub block_size: usize,
+    /// Commit marker the delta starts after
+    pub since_marker: u64,
+    /// Commit marker the delta brings the database up to
+    pub new_marker: u64,
+    /// Size of the database file in bytes; blocks past this size are dropped on apply
+    pub db_size: u64,
+    /// Changed blocks, ordered by block id
+    pub blocks: Vec<(u64, Vec<u8>)>,
+}
+
+impl IncrementalDelta {
+    /// Serialize the delta to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out =
+            Vec::with_capacity(DELTA_HEADER_SIZE + self.blocks.len() * (8 + self.block_size));
+        out.extend_from_slice(DELTA_MAGIC);
+        out.extend_from_slice(&DELTA_FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&(self.block_size as u32).to_le_bytes());
+        out.extend_from_slice(&self.since_marker.to_le_bytes());
+        out.extend_from_slice(&self.new_marker.to_le_bytes());
+        out.extend_from_slice(&self.db_size.to_le_bytes());
+        out.extend_from_slice(&(self.blocks.len() as u32).to_le_bytes());
+        for (block_id, data) in &self.blocks {
+            out.extend_from_slice(&block_id.to_le_bytes());
+            out.extend_from_slice(data);
+        }
+        out
+    }
+
+    /// Parse a delta produced by `to_bytes`
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DatabaseError> {
+        let invalid = |msg: &str| DatabaseError::new("INVALID_DELTA", msg);
+
+        if data.len() < DELTA_HEADER_SIZE || &data[0..8] != DELTA_MAGIC {
+            return Err(invalid("Not an incremental export"));
+        }
+        let u32_at = |pos: usize| u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap());
+        let u64_at = |pos: usize| u64::from_le_bytes(data[pos..pos + 8].try_into().unwrap());
+
+        let format_version = u32_at(8);
+        if format_version != DELTA_FORMAT_VERSION {
+            return Err(invalid(&format!(
+                "Unsupported delta format version: {}",
+                format_version
+            )));
+        }
+        let block_size = u32_at(12) as usize;
+        super::block_storage::validate_block_size(block_size)?;
+        let since_marker = u64_at(16);
+        let new_marker = u64_at(24);
+        let db_size = u64_at(32);
+        let block_count = u32_at(40) as usize;
+
+        let expected_len = DELTA_HEADER_SIZE + block_count * (8 + block_size);
+        if data.len() != expected_len {
+            return Err(invalid(&format!(
+                "Delta is {} bytes, expected {} for {} blocks",
+                data.len(),
+                expected_len,
+                block_count
+            )));
+        }
+
+        let blocks = (0..block_count)
+            .map(|i| {
+                let pos = DELTA_HEADER_SIZE + i * (8 + block_size);
+                (u64_at(pos), data[pos + 8..pos + 8 + block_size].to_vec())
+            })
+            .collect();
+
+        Ok(Self {
+            block_size,
+            since_marker,
+            new_marker,
+            db_size,
+            blocks,
+        })
+    }
+
+    /// Number of blocks needed to hold the database
+    pub fn total_blocks(&self) -> u64 {
+        self.db_size.div_ceil(self.block_size as u64)
+    }
+}
+
+/// Collect the blocks committed after `since_marker`
+///
+/// The caller should sync the database first so all writes are committed.
+#[cfg(target_arch = "wasm32")]
+pub fn export_incremental(
+    db_name: &str,
+    since_marker: u64,
+    block_size: usize,
+) -> Result<IncrementalDelta, DatabaseError> {
+    use super::vfs_sync::{with_global_commit_marker, with_global_metadata, with_global_storage};
+
+    let db_name = crate::utils::normalize_db_name(db_name);
+    let new_marker =
+        with_global_commit_marker(|cm| cm.borrow().get(&db_name).copied().unwrap_or(0));
+    if since_marker > new_marker {
+        return Err(DatabaseError::new(
+            "INVALID_MARKER",
+            &format!(
+                "Marker {} is ahead of the current commit marker {}; take a full export",
+                since_marker, new_marker
+            ),
+        ));
+    }
+
+    let changed: Vec<u64> = with_global_metadata(|meta| {
+        meta.borrow()
+            .get(&db_name)
+            .map(|db_meta| {
+                db_meta
+                    .iter()
+                    .filter(|(_, m)| {
+                        let version = m.version as u64;
+                        version > since_marker && version <= new_marker
+                    })
+                    .map(|(id, _)| *id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    });
+
+    with_global_storage(|gs| {
+        let storage = gs.borrow();
+        let db_storage = storage.get(&db_name).ok_or_else(|| {
+            DatabaseError::new(
+                "DATABASE_NOT_FOUND",
+                &format!("No storage found for database: {}", db_name),
+            )
+        })?;
+        let header = db_storage
+            .get(&0)
+            .ok_or_else(|| DatabaseError::new("INVALID_HEADER", "Database has no header block"))?;
+        let (page_size, page_count) = super::export::parse_sqlite_header(header)?;
+        let db_size = page_size as u64 * page_count as u64;
+        let total_blocks = db_size.div_ceil(block_size as u64);
+
+        let mut blocks: Vec<(u64, Vec<u8>)> = changed
+            .into_iter()
+            .filter(|id| *id < total_blocks)
+            .filter_map(|id| db_storage.get(&id).map(|data| (id, data.clone())))
+            .collect();
+        blocks.sort_by_key(|(id, _)| *id);
+
+        log::info!(
+            "Incremental export for {}: {} blocks changed between markers {} and {}",
+            db_name,
+            blocks.len(),
+            since_marker,
+            new_marker
+        );
+
+        Ok(IncrementalDelta {
+            block_size,
+            since_marker,
+            new_marker,
+            db_size,
+            blocks,
+        })
+    })
+}
+
+/// Apply a delta onto a database's existing storage and persist it to IndexedDB
+///
+/// The changed blocks are committed under a new commit marker, which is returned.
+/// Blocks past the delta's database size are removed. The database's connection and
+/// BlockStorage must be closed before calling this.
+#[cfg(target_arch = "wasm32")]
+pub async fn import_incremental(
+    db_name: &str,
+    delta: IncrementalDelta,
+    block_size: usize,
+) -> Result<u64, DatabaseError> {
+    use super::metadata::{BlockMetadataPersist, ChecksumAlgorithm, ChecksumManager};
+    use super::vfs_sync::{
+        with_global_allocation_map, with_global_commit_marker, with_global_metadata,
+        with_global_storage,
+    };
+
+    let db_name = crate::utils::normalize_db_name(db_name);
+    let db_name = db_name.as_str();
+    if delta.block_size != block_size {
+        return Err(DatabaseError::new(
+            "BLOCK_SIZE_MISMATCH",
+            &format!(
+                "Delta uses {}-byte blocks but the database uses {}-byte blocks",
+                delta.block_size, block_size
+            ),
+        ));
+    }
+
+    let total_blocks = delta.total_blocks();
+    let applied = delta.blocks.len();
+    let next_commit =
+        with_global_commit_marker(|cm| cm.borrow().get(db_name).copied().unwrap_or(0) + 1);
+    let checksums: Vec<(u64, u64)> = delta
+        .blocks
+        .iter()
+        .map(|(id, data)| {
+            (
+                *id,
+                ChecksumManager::compute_checksum_with(data, ChecksumAlgorithm::CRC32),
+            )
+        })
+        .collect();
+
+    // Drop blocks the database no longer covers
+    let removed: Vec<u64> = with_global_storage(|gs| {
+        let mut storage = gs.borrow_mut();
+        let db_storage = storage.entry(db_name.to_string()).or_default();
+        let removed: Vec<u64> = db_storage
+            .keys()
+            .copied()
+            .filter(|id| *id >= total_blocks)
+            .collect();
+        for id in &removed {
+            db_storage.remove(id);
+        }
+        for (id, data) in &delta.blocks {
+            db_storage.insert(*id, data.clone());
+        }
+        removed
+    });
+    with_global_allocation_map(|gam| {
+        let mut allocations = gam.borrow_mut();
+        let allocated = allocations.entry(db_name.to_string()).or_default();
+        allocated.retain(|id| *id < total_blocks);
+        allocated.extend(delta.blocks.iter().map(|(id, _)| *id));
+    });
+    with_global_metadata(|gm| {
+        let mut metadata = gm.borrow_mut();
+        let db_meta = metadata.entry(db_name.to_string()).or_default();
+        db_meta.retain(|id, _| *id < total_blocks);
+        for (id, checksum) in &checksums {
+            db_meta.insert(
+                *id,
+                BlockMetadataPersist::new(
+                    *checksum,
+                    next_commit as u32,
+                    0,
+                    ChecksumAlgorithm::CRC32,
+                ),
+            );
+        }
+    });
+    with_global_commit_marker(|cm| {
+        cm.borrow_mut().insert(db_name.to_string(), next_commit);
+    });
+
+    super::wasm_indexeddb::delete_blocks_from_indexeddb(db_name, &removed).await?;
+    super::wasm_indexeddb::persist_to_indexeddb_event_based(
+        db_name,
+        delta.blocks,
+        checksums,
+        next_commit,
+        #[cfg(feature = "telemetry")]
+        None,
+        #[cfg(feature = "telemetry")]
+        None,
+    )
+    .await?;
+
+    log::info!(
+        "Incremental import for {}: {} blocks applied at commit marker {}",
+        db_name,
+        applied,
+        next_commit
+    );
+    Ok(next_commit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_delta() -> IncrementalDelta {
+        IncrementalDelta {
+            block_size: 4096,
+            since_marker: 3,
+            new_marker: 7,
+            db_size: 3 * 4096,
+            blocks: vec![(0, vec![1u8; 4096]), (2, vec![2u8; 4096])],
+        }
+    }
+
+    #[test]
+    fn test_delta_roundtrip() {
+        let delta = sample_delta();
+        let parsed = IncrementalDelta::from_bytes(&delta.to_bytes()).unwrap();
+        assert_eq!(parsed, delta);
+        assert_eq!(parsed.total_blocks(), 3);
+    }
+
+    #[test]
+    fn test_empty_delta_roundtrip() {
+        let delta = IncrementalDelta {
+            blocks: vec![],
+            ..sample_delta()
+        };
+        let bytes = delta.to_bytes();
+        assert_eq!(bytes.len(), DELTA_HEADER_SIZE);
+        assert_eq!(IncrementalDelta::from_bytes(&bytes).unwrap(), delta);
+    }
+
+    #[test]
+    fn test_rejects_invalid_delta() {
+        let err = IncrementalDelta::from_bytes(b"SQLite format 3\0").unwrap_err();
+        assert_eq!(err.code, "INVALID_DELTA");
+
+        let mut truncated = sample_delta().to_bytes();
+        truncated.pop();
+        let err = IncrementalDelta::from_bytes(&truncated).unwrap_err();
+        assert_eq!(err.code, "INVALID_DELTA");
+    }
+}
@@ -0,0 +1,298 @@
+//! IndexedDB Connection Pool
+//!
+//! Caps how many IndexedDB connections this tab holds open at once. Browsers limit open
+//! connections, and apps that manage many databases would otherwise open one per
+//! operation. Released connections are kept idle in an LRU and reused by the next open
+//! of the same IndexedDB database. When the cap is reached the least recently used idle
+//! connection is closed to make room; if every connection is in use, opens wait until
+//! one is released.
+
+use crate::types::{DatabaseError, IndexedDbHandleStats};
+use futures::channel::oneshot;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::closure::Closure;
+
+/// Default cap on IndexedDB connections open in this tab
+pub const DEFAULT_MAX_OPEN_HANDLES: usize = 8;
+
+thread_local! {
+    static POOL: RefCell<HandlePool> = RefCell::new(HandlePool::new());
+}
+
+/// A connection parked in the pool
+struct IdleHandle {
+    name: String,
+    db: web_sys::IdbDatabase,
+    /// Set once the browser asked us to close the connection (versionchange)
+    closed: Rc<Cell<bool>>,
+}
+
+struct HandlePool {
+    max_open: usize,
+    /// Connections open in this tab, idle or in use
+    open: usize,
+    /// Idle connections, least recently used first
+    idle: VecDeque<IdleHandle>,
+    waiters: VecDeque<oneshot::Sender<()>>,
+    opened_total: u64,
+    reused_total: u64,
+    evicted_total: u64,
+}
+
+impl HandlePool {
+    fn new() -> Self {
+        Self {
+            max_open: DEFAULT_MAX_OPEN_HANDLES,
+            open: 0,
+            idle: VecDeque::new(),
+            waiters: VecDeque::new(),
+            opened_total: 0,
+            reused_total: 0,
+            evicted_total: 0,
+        }
+    }
+
+    /// Close the least recently used idle connection, returning whether there was one
+    fn evict_lru(&mut self) -> bool {
+        match self.idle.pop_front() {
+            Some(handle) => {
+                handle.db.close();
+                self.open -= 1;
+                self.evicted_total += 1;
+                log::debug!("Closed idle IndexedDB connection to {}", handle.name);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop idle connections the browser has already closed
+    fn prune_closed(&mut self) {
+        let before = self.idle.len();
+        self.idle.retain(|handle| !handle.closed.get());
+        self.open -= before - self.idle.len();
+    }
+
+    /// Let the longest waiting open try again
+    fn wake_one(&mut self) {
+        while let Some(waiter) = self.waiters.pop_front() {
+            if waiter.send(()).is_ok() {
+                break;
+            }
+        }
+    }
+}
+
+/// What `open_pooled` does next
+enum Step {
+    Reuse(IdleHandle),
+    Open,
+    Wait(oneshot::Receiver<()>),
+}
+
+/// An IndexedDB connection borrowed from the pool
+///
+/// Dereferences to the `IdbDatabase`. Dropping it returns the connection to the pool
+/// instead of closing it; never call `close()` on it directly.
+pub struct PooledDatabase {
+    name: String,
+    db: Option<web_sys::IdbDatabase>,
+    closed: Rc<Cell<bool>>,
+}
+
+impl std::ops::Deref for PooledDatabase {
+    type Target = web_sys::IdbDatabase;
+
+    fn deref(&self) -> &Self::Target {
+        self.db
+            .as_ref()
+            .expect("pooled connection present until dropped")
+    }
+}
+
+impl Drop for PooledDatabase {
+    fn drop(&mut self) {
+        let Some(db) = self.db.take() else {
+            return;
+        };
+        POOL.with(|pool| {
+            let mut pool = pool.borrow_mut();
+            if self.closed.get() {
+                pool.open -= 1;
+            } else {
+                pool.idle.push_back(IdleHandle {
+                    name: std::mem::take(&mut self.name),
+                    db,
+                    closed: Rc::clone(&self.closed),
+                });
+            }
+            pool.wake_one();
+        });
+    }
+}
+
+/// Set the cap on open IndexedDB connections (at least 1), closing idle ones above it
+pub fn set_max_open_handles(max_open: usize) {
+    POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        pool.max_open = max_open.max(1);
+        while pool.open > pool.max_open && pool.evict_lru() {}
+    });
+}
+
+/// Current connection counts and totals since the page loaded
+pub fn handle_stats() -> IndexedDbHandleStats {
+    POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        pool.prune_closed();
+        IndexedDbHandleStats {
+            max_open: pool.max_open as u32,
+            open: pool.open as u32,
+            idle: pool.idle.len() as u32,
+            waiting: pool.waiters.len() as u32,
+            opened_total: pool.opened_total,
+            reused_total: pool.reused_total,
+            evicted_total: pool.evicted_total,
+        }
+    })
+}
+
+/// Borrow a connection to IndexedDB database `name`, opening one if none is idle
+///
+/// Creates the `blocks` and `metadata` stores when the database is new.
+pub async fn open_pooled(name: &str, version: u32) -> Result<PooledDatabase, DatabaseError> {
+    loop {
+        let step = POOL.with(|pool| {
+            let mut pool = pool.borrow_mut();
+            pool.prune_closed();
+            if let Some(pos) = pool.idle.iter().rposition(|handle| handle.name == name) {
+                pool.reused_total += 1;
+                return Step::Reuse(pool.idle.remove(pos).expect("valid position"));
+            }
+            if pool.open < pool.max_open || pool.evict_lru() {
+                pool.open += 1;
+                return Step::Open;
+            }
+            let (tx, rx) = oneshot::channel();
+            pool.waiters.push_back(tx);
+            log::debug!(
+                "All {} IndexedDB connections in use; waiting to open {}",
+                pool.max_open,
+                name
+            );
+            Step::Wait(rx)
+        });
+        match step {
+            Step::Reuse(handle) => {
+                return Ok(PooledDatabase {
+                    name: handle.name,
+                    db: Some(handle.db),
+                    closed: handle.closed,
+                });
+            }
+            Step::Open => break,
+            Step::Wait(rx) => {
+                let _ = rx.await;
+            }
+        }
+    }
+
+    match open_connection(name, version).await {
+        Ok(db) => {
+            POOL.with(|pool| pool.borrow_mut().opened_total += 1);
+            let closed = Rc::new(Cell::new(false));
+            // Another tab upgrading or deleting the database needs us to let go
+            let on_version_change = {
+                let closed = Rc::clone(&closed);
+                let db = db.clone();
+                Closure::wrap(Box::new(move |_event: web_sys::Event| {
+                    db.close();
+                    closed.set(true);
+                }) as Box<dyn FnMut(_)>)
+            };
+            db.set_onversionchange(Some(on_version_change.as_ref().unchecked_ref()));
+            on_version_change.forget();
+            Ok(PooledDatabase {
+                name: name.to_string(),
+                db: Some(db),
+                closed,
+            })
+        }
+        Err(e) => {
+            POOL.with(|pool| {
+                let mut pool = pool.borrow_mut();
+                pool.open -= 1;
+                pool.wake_one();
+            });
+            Err(e)
+        }
+    }
+}
+
+/// Open a new connection, creating the block stores on first use
+async fn open_connection(name: &str, version: u32) -> Result<web_sys::IdbDatabase, DatabaseError> {
+    let factory = super::wasm_indexeddb::get_indexeddb_factory()?;
+    let open_req = factory.open_with_u32(name, version).map_err(|e| {
+        DatabaseError::new(
+            "INDEXEDDB_OPEN_ERROR",
+            &format!("Failed to open IndexedDB '{}': {:?}", name, e),
+        )
+    })?;
+
+    let upgrade_closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+        let db = event
+            .target()
+            .and_then(|t| t.dyn_into::<web_sys::IdbOpenDbRequest>().ok())
+            .and_then(|req| req.result().ok())
+            .and_then(|r| r.dyn_into::<web_sys::IdbDatabase>().ok());
+        if let Some(db) = db {
+            for store in ["blocks", "metadata"] {
+                if !db.object_store_names().contains(store)
+                    && db.create_object_store(store).is_err()
+                {
+                    log::error!("Failed to create IndexedDB store {}", store);
+                }
+            }
+        }
+    }) as Box<dyn FnMut(_)>);
+    open_req.set_onupgradeneeded(Some(upgrade_closure.as_ref().unchecked_ref()));
+    upgrade_closure.forget();
+
+    let (open_tx, open_rx) = oneshot::channel();
+    let open_tx = Rc::new(RefCell::new(Some(open_tx)));
+    let success_closure = {
+        let open_tx = open_tx.clone();
+        Closure::wrap(Box::new(move |event: web_sys::Event| {
+            if let Some(sender) = open_tx.borrow_mut().take() {
+                let db = event
+                    .target()
+                    .and_then(|t| t.dyn_into::<web_sys::IdbOpenDbRequest>().ok())
+                    .and_then(|req| req.result().ok())
+                    .and_then(|r| r.dyn_into::<web_sys::IdbDatabase>().ok());
+                let _ = sender.send(db);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    let error_closure = {
+        let open_tx = open_tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = open_tx.borrow_mut().take() {
+                let _ = sender.send(None);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    open_req.set_onsuccess(Some(success_closure.as_ref().unchecked_ref()));
+    open_req.set_onerror(Some(error_closure.as_ref().unchecked_ref()));
+    success_closure.forget();
+    error_closure.forget();
+
+    open_rx.await.ok().flatten().ok_or_else(|| {
+        DatabaseError::new(
+            "INDEXEDDB_OPEN_ERROR",
+            &format!("Failed to open IndexedDB '{}'", name),
+        )
+    })
+}
@@ -48,7 +48,7 @@ macro_rules! try_read_lock {
     };
 }
 
-use super::block_storage::{BLOCK_SIZE, BlockStorage};
+use super::block_storage::BlockStorage;
 use crate::types::DatabaseError;
 #[cfg(not(target_arch = "wasm32"))]
 use std::sync::atomic::Ordering;
@@ -98,6 +98,9 @@ pub fn read_block_sync_impl(
 
     if let Some(data) = cached_data {
         // Record cache hit
+        storage
+            .cache_hits
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         #[cfg(feature = "telemetry")]
         if let Some(ref metrics) = storage.metrics {
             metrics.cache_hits().inc();
@@ -120,6 +123,9 @@ pub fn read_block_sync_impl(
     }
 
     // Record cache miss
+    storage
+        .cache_misses
+        .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     #[cfg(feature = "telemetry")]
     if let Some(ref metrics) = storage.metrics {
         metrics.cache_misses().inc();
@@ -152,7 +158,7 @@ pub fn read_block_sync_impl(
                             ).into());
                     }
 
-                    result.unwrap_or_else(|| vec![0; BLOCK_SIZE])
+                    result.unwrap_or_else(|| vec![0; storage.block_size])
                 });
                 return (data, true);
             }
@@ -180,12 +186,12 @@ pub fn read_block_sync_impl(
                                 .get(&storage.db_name)
                                 .and_then(|db_storage| db_storage.get(&block_id))
                                 .cloned()
-                                .unwrap_or_else(|| vec![0; BLOCK_SIZE])
+                                .unwrap_or_else(|| vec![0; storage.block_size])
                         });
                         (data, true)
                     } else {
                         // Not visible (version > commit marker) - return zeroed data for SQLite
-                        (vec![0; BLOCK_SIZE], false)
+                        (vec![0; storage.block_size], false)
                     }
                 } else {
                     // No metadata - check if data exists in global storage
@@ -197,13 +203,16 @@ pub fn read_block_sync_impl(
                     });
 
                     match data {
-                        Some(data) => (data, true),          // Old data before metadata tracking
-                        None => (vec![0; BLOCK_SIZE], true), // Return zeros for RMW (read-modify-write)
+                        Some(data) => (data, true), // Old data before metadata tracking
+                        None => (vec![0; storage.block_size], true), // Return zeros for RMW (read-modify-write)
                     }
                 }
             })
         });
 
+        // Persisted blocks from a database created with another block size are unreadable
+        storage.check_block_len(block_id, data.len())?;
+
         // Verify checksum ONLY for visible blocks in WASM
         // Skip block 0 as it's the SQLite header which can be modified by SQLite
         if is_visible && block_id != 0 {
@@ -239,7 +248,10 @@ pub fn read_block_sync_impl(
             ));
         }
         if let Ok(mut f) = fs::File::open(&block_path) {
-            let mut data = vec![0u8; BLOCK_SIZE];
+            if let Ok(meta) = f.metadata() {
+                storage.check_block_len(block_id, meta.len() as usize)?;
+            }
+            let mut data = vec![0u8; storage.block_size];
             f.read_exact(&mut data).map_err(|e| {
                 DatabaseError::new(
                     "IO_ERROR",
@@ -254,7 +266,7 @@ pub fn read_block_sync_impl(
         }
         // If file missing, treat as zeroed data (compat). This covers never-written blocks
         // and avoids depending on allocated_blocks for read behavior.
-        let data = vec![0; BLOCK_SIZE];
+        let data = vec![0; storage.block_size];
         lock_mutex!(storage.cache).insert(block_id, data.clone());
         storage.verify_against_stored_checksum(block_id, &data)?;
         storage.touch_lru(block_id);
@@ -304,7 +316,7 @@ pub fn read_block_sync_impl(
                         return data.clone();
                     }
                 }
-                vec![0; BLOCK_SIZE]
+                vec![0; storage.block_size]
             })
         } else {
             log::debug!(
@@ -312,7 +324,7 @@ pub fn read_block_sync_impl(
                 block_id,
                 committed
             );
-            vec![0; BLOCK_SIZE]
+            vec![0; storage.block_size]
         };
 
         // Check if block is actually allocated before returning zeroed data
@@ -326,6 +338,7 @@ pub fn read_block_sync_impl(
             return Err(error);
         }
 
+        storage.check_block_len(block_id, data.len())?;
         lock_mutex!(storage.cache).insert(block_id, data.clone());
         log::debug!(
             "[test] Block {} cached from global storage (sync)",
@@ -399,19 +412,20 @@ fn write_block_impl_inner(
         // Threshold for backpressure
         storage
             .observability
-            .record_backpressure("high", "too_many_dirty_blocks");
+            .record_backpressure("high", "too_many_dirty_blocks", dirty_count);
     }
 
-    if data.len() != BLOCK_SIZE {
+    if data.len() != storage.block_size {
         return Err(DatabaseError::new(
             "INVALID_BLOCK_SIZE",
             &format!(
                 "Block size must be {} bytes, got {}",
-                BLOCK_SIZE,
+                storage.block_size,
                 data.len()
             ),
         ));
     }
+    storage.ensure_within_quota(block_id)?;
 
     // If requested by policy, verify existing data integrity BEFORE accepting the new write.
     // This prevents overwriting a block whose prior contents no longer match the stored checksum.
@@ -623,12 +637,12 @@ fn write_block_impl_inner(
 
             db_meta.insert(
                 block_id,
-                BlockMetadataPersist {
+                BlockMetadataPersist::new(
                     checksum,
                     version,
-                    last_modified_ms: 0, // Will be updated during sync
-                    algo: ChecksumAlgorithm::CRC32,
-                },
+                    0, // Will be updated during sync
+                    ChecksumAlgorithm::CRC32,
+                ),
             );
         });
 
@@ -678,12 +692,12 @@ fn write_block_impl_inner(
 
             db_meta.insert(
                 block_id,
-                BlockMetadataPersist {
+                BlockMetadataPersist::new(
                     checksum,
                     version,
-                    last_modified_ms: 0, // Will be updated during sync
-                    algo: ChecksumAlgorithm::CRC32,
-                },
+                    0, // Will be updated during sync
+                    ChecksumAlgorithm::CRC32,
+                ),
             );
             log::debug!(
                 "Updated test metadata for block {} with checksum {} (version {})",
@@ -780,7 +794,7 @@ fn write_block_impl_inner(
         metrics.storage_bytes().set(total_bytes as f64);
 
         // Update cache size bytes gauge
-        let cache_bytes: usize = cache_guard.len() * BLOCK_SIZE;
+        let cache_bytes: usize = cache_guard.len() * storage.block_size;
         metrics.cache_size_bytes().set(cache_bytes as f64);
     }
 
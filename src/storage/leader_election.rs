@@ -6,8 +6,10 @@
 
 use crate::types::DatabaseError;
 use js_sys::Date;
-use std::cell::RefCell;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
+use tsify::Tsify;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::prelude::*;
 use web_sys::BroadcastChannel;
@@ -18,6 +20,54 @@ thread_local! {
     static HEARTBEAT_RUNNING: RefCell<bool> = const { RefCell::new(false) };
 }
 
+/// Timing used by leader election
+///
+/// Shorter timings give faster failover when a leader tab closes; longer ones wake the
+/// leader less often. `heartbeat_ms` must be shorter than `lease_ms` or the leader's
+/// lease lapses between heartbeats.
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct LeaderElectionConfig {
+    /// How long a leader's claim stays valid without a heartbeat. Default: 5000
+    pub lease_ms: u64,
+    /// How often the leader renews its claim. Default: 1000
+    pub heartbeat_ms: u32,
+    /// Default time `waitForLeadership` waits before giving up. Default: 5000
+    pub election_timeout_ms: u32,
+}
+
+impl Default for LeaderElectionConfig {
+    fn default() -> Self {
+        Self {
+            lease_ms: 5000,
+            heartbeat_ms: 1000,
+            election_timeout_ms: 5000,
+        }
+    }
+}
+
+impl LeaderElectionConfig {
+    /// Check that the timings are usable together
+    pub fn validate(&self) -> Result<(), DatabaseError> {
+        if self.heartbeat_ms == 0 || self.election_timeout_ms == 0 {
+            return Err(DatabaseError::new(
+                "INVALID_LEADER_ELECTION_CONFIG",
+                "heartbeat_ms and election_timeout_ms must be greater than 0",
+            ));
+        }
+        if self.heartbeat_ms as u64 >= self.lease_ms {
+            return Err(DatabaseError::new(
+                "INVALID_LEADER_ELECTION_CONFIG",
+                &format!(
+                    "heartbeat_ms ({}) must be shorter than lease_ms ({})",
+                    self.heartbeat_ms, self.lease_ms
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Leader election state for a database instance
 #[derive(Debug, Clone)]
 pub struct LeaderElectionState {
@@ -29,6 +79,50 @@ pub struct LeaderElectionState {
     pub last_heartbeat: u64,
 }
 
+/// Reports leadership transitions to a JS callback
+///
+/// The callback receives `{ isLeader, leaderId }` whenever this instance gains or loses
+/// leadership. `leaderId` is an empty string when no instance holds a valid lease.
+#[derive(Default)]
+pub struct LeadershipNotifier {
+    callback: RefCell<Option<js_sys::Function>>,
+    last_reported: Cell<bool>,
+    watching: Cell<bool>,
+}
+
+impl LeadershipNotifier {
+    /// Invoke the callback if leadership changed since the last report
+    fn notify(&self, state: &Rc<RefCell<LeaderElectionState>>) {
+        let Ok(state) = state.try_borrow() else {
+            return;
+        };
+        let is_leader = state.is_leader;
+        let leader_id = state.leader_id.clone().unwrap_or_default();
+        let db_name = state.db_name.clone();
+        drop(state);
+
+        if is_leader == self.last_reported.replace(is_leader) {
+            return;
+        }
+        log::info!(
+            "Leadership changed for {}: is_leader={}, leader_id={}",
+            db_name,
+            is_leader,
+            leader_id
+        );
+
+        let Some(callback) = self.callback.borrow().clone() else {
+            return;
+        };
+        let event = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&event, &"isLeader".into(), &JsValue::from_bool(is_leader));
+        let _ = js_sys::Reflect::set(&event, &"leaderId".into(), &JsValue::from_str(&leader_id));
+        if let Err(e) = callback.call1(&JsValue::NULL, &event) {
+            log::warn!("onLeadershipChange callback threw for {}: {:?}", db_name, e);
+        }
+    }
+}
+
 /// Manager for multi-tab leader election
 pub struct LeaderElectionManager {
     pub state: Rc<RefCell<LeaderElectionState>>,
@@ -38,15 +132,23 @@ pub struct LeaderElectionManager {
     // to prevent "closure invoked after being dropped" errors from pending ticks.
     // The heartbeat_valid flag makes the leaked closure a no-op after stop.
     message_listener: Option<Closure<dyn FnMut(web_sys::MessageEvent)>>,
-    lease_duration_ms: u64,
+    config: LeaderElectionConfig,
     /// Validity flag - set to false before clearing interval to prevent
     /// leaked closure from doing any work after stop_election is called
     heartbeat_valid: Rc<RefCell<bool>>,
+    notifier: Rc<LeadershipNotifier>,
+    /// Interval that watches for lease expiry while a leadership callback is registered
+    lease_watch_interval: Option<i32>,
 }
 
 impl LeaderElectionManager {
     /// Create new leader election manager with deterministic instance ID
     pub fn new(db_name: String) -> Self {
+        Self::new_with_config(db_name, LeaderElectionConfig::default())
+    }
+
+    /// Create a leader election manager with custom lease and heartbeat timing
+    pub fn new_with_config(db_name: String, config: LeaderElectionConfig) -> Self {
         // Create deterministic instance ID: timestamp + random for uniqueness and ordering
         let timestamp = Date::now() as u64;
         let random_part = (js_sys::Math::random() * 1000.0) as u64;
@@ -66,8 +168,10 @@ impl LeaderElectionManager {
             broadcast_channel: None,
             heartbeat_interval: None,
             message_listener: None,
-            lease_duration_ms: 1000, // 1 second - fast leader election
+            config,
             heartbeat_valid: Rc::new(RefCell::new(false)),
+            notifier: Rc::new(LeadershipNotifier::default()),
+            lease_watch_interval: None,
         }
     }
 
@@ -90,6 +194,7 @@ impl LeaderElectionManager {
 
         // Set up EVENT LISTENER for leadership change messages
         let state_clone = self.state.clone();
+        let notifier = self.notifier.clone();
         let listener = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
             if let Ok(data) = event.data().dyn_into::<js_sys::JsString>() {
                 let message: String = data.into();
@@ -125,6 +230,7 @@ impl LeaderElectionManager {
                     }
                 }
             }
+            notifier.notify(&state_clone);
         }) as Box<dyn FnMut(web_sys::MessageEvent)>);
 
         broadcast_channel.set_onmessage(Some(listener.as_ref().unchecked_ref()));
@@ -135,6 +241,10 @@ impl LeaderElectionManager {
         log::debug!("LeaderElectionManager::start_election() - Calling try_become_leader()");
         self.try_become_leader().await?;
 
+        if self.notifier.callback.borrow().is_some() {
+            self.start_lease_watch()?;
+        }
+
         // Start heartbeat if we're leader
         let is_leader = self.state.borrow().is_leader;
         web_sys::console::log_1(
@@ -227,8 +337,8 @@ impl LeaderElectionManager {
             all_instances.push(instance_data);
         }
 
-        // Clean up expired instances (older than 10 seconds)
-        let cutoff_time = current_time - 10000;
+        // Clean up expired instances (older than two leases)
+        let cutoff_time = current_time.saturating_sub(2 * self.config.lease_ms);
         all_instances.retain(|inst| {
             if let Some(colon_pos) = inst.rfind(':') {
                 if let Ok(timestamp) = inst[colon_pos + 1..].parse::<u64>() {
@@ -283,7 +393,8 @@ impl LeaderElectionManager {
                         if let Ok(existing_timestamp) =
                             existing_data[colon_pos + 1..].parse::<u64>()
                         {
-                            let existing_lease_expired = (current_time - existing_timestamp) > 5000;
+                            let existing_lease_expired =
+                                (current_time - existing_timestamp) > self.config.lease_ms;
 
                             if !force
                                 && !existing_lease_expired
@@ -299,7 +410,7 @@ impl LeaderElectionManager {
                                 let mut state = self.state.borrow_mut();
                                 state.is_leader = false;
                                 state.leader_id = Some(existing_leader_id.to_string());
-                                state.lease_expiry = existing_timestamp + self.lease_duration_ms;
+                                state.lease_expiry = existing_timestamp + self.config.lease_ms;
                                 return Ok(());
                             }
                         }
@@ -318,7 +429,7 @@ impl LeaderElectionManager {
                 let mut state = self.state.borrow_mut();
                 state.is_leader = true;
                 state.leader_id = Some(my_instance_id.clone());
-                state.lease_expiry = current_time + self.lease_duration_ms;
+                state.lease_expiry = current_time + self.config.lease_ms;
                 drop(state);
 
                 log::info!("Became leader for {} with ID {}", db_name, my_instance_id);
@@ -348,7 +459,7 @@ impl LeaderElectionManager {
                 let mut state = self.state.borrow_mut();
                 state.is_leader = false;
                 state.leader_id = Some(lowest_id.clone());
-                state.lease_expiry = current_time + self.lease_duration_ms;
+                state.lease_expiry = current_time + self.config.lease_ms;
             }
         }
 
@@ -357,12 +468,120 @@ impl LeaderElectionManager {
 
     /// Try to become leader (respects existing leader's lease)
     pub async fn try_become_leader(&mut self) -> Result<(), DatabaseError> {
-        self.try_become_leader_internal(false).await
+        let result = self.try_become_leader_internal(false).await;
+        self.notifier.notify(&self.state);
+        result
     }
 
     /// Force leadership takeover (ignores existing leader's lease)
     pub async fn force_become_leader(&mut self) -> Result<(), DatabaseError> {
-        self.try_become_leader_internal(true).await
+        let result = self.try_become_leader_internal(true).await;
+        self.notifier.notify(&self.state);
+        result
+    }
+
+    /// Register the callback invoked whenever this instance gains or loses leadership
+    ///
+    /// While a callback is registered, a watcher checks the lease every heartbeat so an
+    /// expired lease is reported even if nothing calls `is_leader()`. Pass `None` to
+    /// unregister.
+    pub fn set_leadership_callback(
+        &mut self,
+        callback: Option<js_sys::Function>,
+    ) -> Result<(), DatabaseError> {
+        let has_callback = callback.is_some();
+        *self.notifier.callback.borrow_mut() = callback;
+        // Only report transitions that happen after registration
+        self.notifier
+            .last_reported
+            .set(self.state.borrow().is_leader);
+
+        if !has_callback {
+            self.stop_lease_watch();
+        } else if self.broadcast_channel.is_some() && self.lease_watch_interval.is_none() {
+            self.start_lease_watch()?;
+        }
+        Ok(())
+    }
+
+    /// Start checking the leader lease in localStorage every heartbeat
+    fn start_lease_watch(&mut self) -> Result<(), DatabaseError> {
+        if self.lease_watch_interval.is_some() {
+            return Ok(());
+        }
+
+        let state_clone = self.state.clone();
+        let notifier = self.notifier.clone();
+        let lease_ms = self.config.lease_ms;
+        notifier.watching.set(true);
+
+        let closure = Closure::wrap(Box::new(move || {
+            // Same validity pattern as the heartbeat: the closure is leaked, so a
+            // pending tick after stop must do nothing
+            if !notifier.watching.get() {
+                return;
+            }
+
+            let Ok(Some(storage)) = web_sys::window()
+                .map(|w| w.local_storage())
+                .unwrap_or(Ok(None))
+            else {
+                return;
+            };
+            let Ok(mut state) = state_clone.try_borrow_mut() else {
+                return;
+            };
+            let leader_key = format!("datasync_leader_{}", state.db_name);
+            let now = Date::now() as u64;
+            let current_leader = storage
+                .get_item(&leader_key)
+                .ok()
+                .flatten()
+                .and_then(|data| {
+                    let colon_pos = data.rfind(':')?;
+                    let timestamp = data[colon_pos + 1..].parse::<u64>().ok()?;
+                    Some((data[..colon_pos].to_string(), timestamp))
+                });
+
+            match current_leader {
+                Some((leader_id, timestamp)) if now.saturating_sub(timestamp) <= lease_ms => {
+                    state.is_leader = leader_id == state.instance_id;
+                    state.leader_id = Some(leader_id);
+                }
+                _ => {
+                    // Lease expired and nobody has re-claimed it yet
+                    state.is_leader = false;
+                    state.leader_id = None;
+                }
+            }
+            drop(state);
+
+            notifier.notify(&state_clone);
+        }) as Box<dyn FnMut()>);
+
+        let interval_id = web_sys::window()
+            .ok_or_else(|| DatabaseError::new("LEADER_ELECTION_ERROR", "Window unavailable"))?
+            .set_interval_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                self.config.heartbeat_ms as i32,
+            )
+            .map_err(|_| {
+                DatabaseError::new("LEADER_ELECTION_ERROR", "Failed to start lease watch")
+            })?;
+        self.lease_watch_interval = Some(interval_id);
+        closure.forget();
+
+        Ok(())
+    }
+
+    /// Stop the lease watcher (idempotent)
+    fn stop_lease_watch(&mut self) {
+        self.notifier.watching.set(false);
+        if let Some(interval_id) = self.lease_watch_interval.take() {
+            if let Some(window) = web_sys::window() {
+                window.clear_interval_with_handle(interval_id);
+            }
+        }
     }
 
     /// Start sending heartbeats as leader using localStorage
@@ -505,7 +724,7 @@ impl LeaderElectionManager {
             .unwrap()
             .set_interval_with_callback_and_timeout_and_arguments_0(
                 closure.as_ref().unchecked_ref(),
-                1000, // Send heartbeat every 1 second
+                self.config.heartbeat_ms as i32,
             )
             .map_err(|_| {
                 DatabaseError::new(
@@ -536,6 +755,8 @@ impl LeaderElectionManager {
 
     /// Stop leader election (e.g., when tab is closing)
     pub async fn stop_election(&mut self) -> Result<(), DatabaseError> {
+        self.stop_lease_watch();
+
         // CRITICAL: Check if already stopped (idempotent)
         if self.heartbeat_interval.is_none() && !*self.heartbeat_valid.borrow() {
             web_sys::console::log_1(&"[STOP] Already stopped - skipping".into());
@@ -628,6 +849,8 @@ impl LeaderElectionManager {
         let mut state = self.state.borrow_mut();
         state.is_leader = false;
         state.leader_id = None;
+        drop(state);
+        self.notifier.notify(&self.state);
 
         Ok(())
     }
@@ -664,7 +887,7 @@ impl LeaderElectionManager {
             if let Some(colon_pos) = leader_data.rfind(':') {
                 let leader_id = &leader_data[..colon_pos];
                 if let Ok(timestamp) = leader_data[colon_pos + 1..].parse::<u64>() {
-                    let lease_expired = (now - timestamp) > 5000; // 5 second lease
+                    let lease_expired = (now - timestamp) > self.config.lease_ms;
 
                     if leader_id == my_instance_id && !lease_expired {
                         return true; // We're still the valid leader
@@ -694,13 +917,12 @@ impl LeaderElectionManager {
             let mut state = self.state.borrow_mut();
             state.is_leader = false;
             state.leader_id = None;
-            false
         } else {
             // Update our state to reflect we're not leader
-            let mut state = self.state.borrow_mut();
-            state.is_leader = false;
-            false
+            self.state.borrow_mut().is_leader = false;
         }
+        self.notifier.notify(&self.state);
+        false
     }
 
     /// Send a heartbeat (for testing)
@@ -766,6 +988,7 @@ impl Drop for LeaderElectionManager {
     fn drop(&mut self) {
         // CRITICAL: Invalidate heartbeat FIRST - leaked closure will become no-op
         *self.heartbeat_valid.borrow_mut() = false;
+        self.stop_lease_watch();
 
         // Clear heartbeat interval (stops future scheduling)
         if let Some(interval_id) = self.heartbeat_interval.take() {
@@ -792,3 +1015,38 @@ impl Drop for LeaderElectionManager {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(LeaderElectionConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_rejects_heartbeat_not_shorter_than_lease() {
+        let config = LeaderElectionConfig {
+            lease_ms: 1000,
+            heartbeat_ms: 1000,
+            election_timeout_ms: 5000,
+        };
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.code, "INVALID_LEADER_ELECTION_CONFIG");
+    }
+
+    #[test]
+    fn test_rejects_zero_timings() {
+        let config = LeaderElectionConfig {
+            heartbeat_ms: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+        let config = LeaderElectionConfig {
+            election_timeout_ms: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
@@ -7,6 +7,7 @@ use parking_lot::Mutex;
 #[cfg(target_arch = "wasm32")]
 use std::cell::RefCell;
 use std::collections::HashMap;
+use tsify::Tsify;
 
 // Reentrancy-safe lock macros
 #[allow(unused_macros)]
@@ -36,12 +37,72 @@ use js_sys::Date;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 // MOVED from block_storage.rs lines 38-41
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Tsify, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 #[allow(dead_code)]
-#[cfg_attr(feature = "fs_persist", derive(serde::Serialize, serde::Deserialize))]
 pub enum ChecksumAlgorithm {
     FastHash,
     CRC32,
+    /// CRC32 with the Castagnoli polynomial; hardware accelerated on x86_64 and aarch64
+    CRC32C,
+}
+
+impl ChecksumAlgorithm {
+    /// Name persisted alongside block metadata
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::FastHash => "FastHash",
+            ChecksumAlgorithm::CRC32 => "CRC32",
+            ChecksumAlgorithm::CRC32C => "CRC32C",
+        }
+    }
+
+    /// Parse a name produced by `name()`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "FastHash" => Some(ChecksumAlgorithm::FastHash),
+            "CRC32" => Some(ChecksumAlgorithm::CRC32),
+            "CRC32C" => Some(ChecksumAlgorithm::CRC32C),
+            _ => None,
+        }
+    }
+
+    /// Algorithm for new blocks of a database
+    ///
+    /// Uses the value set with `set_default_checksum_algorithm`, then the
+    /// `DATASYNC_CHECKSUM_ALGO` environment variable (native only), then FastHash.
+    pub fn default_for(db_name: &str) -> Self {
+        let configured = DB_CHECKSUM_ALGO
+            .with(|algos| algos.borrow().get(db_name.trim_end_matches(".db")).copied());
+        if let Some(algo) = configured {
+            return algo;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(algo) = std::env::var("DATASYNC_CHECKSUM_ALGO")
+            .ok()
+            .as_deref()
+            .and_then(Self::from_name)
+        {
+            return algo;
+        }
+        ChecksumAlgorithm::FastHash
+    }
+}
+
+thread_local! {
+    /// Checksum algorithm configured for each database, keyed by name without `.db`
+    static DB_CHECKSUM_ALGO: std::cell::RefCell<HashMap<String, ChecksumAlgorithm>> =
+        std::cell::RefCell::new(HashMap::new());
+}
+
+/// Set the checksum algorithm used for new blocks when a database's storage is opened
+///
+/// Existing blocks keep the algorithm they were written with.
+pub fn set_default_checksum_algorithm(db_name: &str, algo: ChecksumAlgorithm) {
+    DB_CHECKSUM_ALGO.with(|algos| {
+        algos
+            .borrow_mut()
+            .insert(db_name.trim_end_matches(".db").to_string(), algo);
+    });
 }
 
 // MOVED from block_storage.rs lines 43-54
@@ -55,6 +116,27 @@ pub struct BlockMetadataPersist {
     pub version: u32,
     #[allow(dead_code)]
     pub algo: ChecksumAlgorithm,
+    /// Whether the persisted copy of the block is compressed (checksum covers uncompressed bytes)
+    #[cfg_attr(feature = "fs_persist", serde(default))]
+    pub compressed: bool,
+}
+
+impl BlockMetadataPersist {
+    /// Metadata for a block whose persisted copy is not compressed
+    pub fn new(
+        checksum: u64,
+        version: u32,
+        last_modified_ms: u64,
+        algo: ChecksumAlgorithm,
+    ) -> Self {
+        Self {
+            checksum,
+            last_modified_ms,
+            version,
+            algo,
+            compressed: false,
+        }
+    }
 }
 
 /// Metadata manager that encapsulates the checksum logic extracted from BlockStorage
@@ -71,6 +153,9 @@ pub struct ChecksumManager {
 
     /// Default algorithm for new blocks (MOVED from BlockStorage.checksum_algo_default)
     checksum_algo_default: ChecksumAlgorithm,
+
+    /// Set during trusted bulk loads: writes drop their checksum and reads skip verification
+    disabled: std::sync::atomic::AtomicBool,
 }
 
 impl ChecksumManager {
@@ -88,6 +173,7 @@ impl ChecksumManager {
             checksum_algos: Mutex::new(HashMap::new()),
 
             checksum_algo_default: default_algorithm,
+            disabled: std::sync::atomic::AtomicBool::new(false),
         }
     }
 
@@ -109,6 +195,7 @@ impl ChecksumManager {
             checksum_algos: Mutex::new(checksum_algos),
 
             checksum_algo_default: default_algorithm,
+            disabled: std::sync::atomic::AtomicBool::new(false),
         }
     }
 
@@ -128,11 +215,32 @@ impl ChecksumManager {
                 hasher.update(data);
                 hasher.finalize() as u64
             }
+            ChecksumAlgorithm::CRC32C => super::crc32c::crc32c(data) as u64,
         }
     }
 
+    /// Turn checksum maintenance off or back on
+    ///
+    /// While disabled, `store_checksum` forgets the block's checksum instead of computing
+    /// one, so those blocks are unprotected until `store_checksum` runs again with
+    /// checksums enabled.
+    pub fn set_disabled(&self, disabled: bool) {
+        self.disabled
+            .store(disabled, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Whether checksum maintenance is currently disabled
+    pub fn is_disabled(&self) -> bool {
+        self.disabled.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
     /// Store checksum for a block (MOVED from lines 2442-2444)
     pub fn store_checksum(&self, block_id: u64, data: &[u8]) {
+        if self.is_disabled() {
+            // A stale checksum would fail verification once checksums are re-enabled
+            self.remove_checksum(block_id);
+            return;
+        }
         let algo = {
             let algos = lock_mutex!(self.checksum_algos);
             algos
@@ -147,6 +255,9 @@ impl ChecksumManager {
 
     /// Validate checksum for a block (MOVED from lines 1843-1870)
     pub fn validate_checksum(&self, block_id: u64, data: &[u8]) -> Result<(), DatabaseError> {
+        if self.is_disabled() {
+            return Ok(());
+        }
         let expected_opt = lock_mutex!(self.checksums).get(&block_id).copied();
         if let Some(expected) = expected_opt {
             let algo = lock_mutex!(self.checksum_algos)
@@ -156,7 +267,11 @@ impl ChecksumManager {
             let actual = Self::compute_checksum_with(data, algo);
             if expected != actual {
                 // Try other known algorithms to detect algorithm mismatch (MOVED from lines 1851-1869)
-                let known_algos = [ChecksumAlgorithm::FastHash, ChecksumAlgorithm::CRC32];
+                let known_algos = [
+                    ChecksumAlgorithm::FastHash,
+                    ChecksumAlgorithm::CRC32,
+                    ChecksumAlgorithm::CRC32C,
+                ];
                 for alt in known_algos.iter().copied().filter(|a| *a != algo) {
                     let alt_sum = Self::compute_checksum_with(data, alt);
                     if expected == alt_sum {
@@ -236,3 +351,40 @@ impl ChecksumManager {
         lock_mutex!(self.checksum_algos).clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_algorithm_names_roundtrip() {
+        for algo in [
+            ChecksumAlgorithm::FastHash,
+            ChecksumAlgorithm::CRC32,
+            ChecksumAlgorithm::CRC32C,
+        ] {
+            assert_eq!(ChecksumAlgorithm::from_name(algo.name()), Some(algo));
+        }
+        assert_eq!(ChecksumAlgorithm::from_name("crc32c"), None);
+    }
+
+    #[test]
+    fn test_crc32c_algo_mismatch_detected() {
+        let data = vec![0x5Au8; 4096];
+        let manager = ChecksumManager::new(ChecksumAlgorithm::CRC32C);
+        manager.store_checksum(1, &data);
+        assert_eq!(manager.get_algorithm(1), ChecksumAlgorithm::CRC32C);
+        manager.validate_checksum(1, &data).unwrap();
+
+        let crc32 = ChecksumManager::compute_checksum_with(&data, ChecksumAlgorithm::CRC32);
+        let mut algos = HashMap::new();
+        algos.insert(1, ChecksumAlgorithm::CRC32C);
+        let tampered = ChecksumManager::with_data(
+            HashMap::from([(1, crc32)]),
+            algos,
+            ChecksumAlgorithm::FastHash,
+        );
+        let err = tampered.validate_checksum(1, &data).unwrap_err();
+        assert_eq!(err.code, "ALGO_MISMATCH");
+    }
+}
@@ -4,12 +4,19 @@ pub mod block_info;
 pub mod block_storage;
 #[cfg(target_arch = "wasm32")]
 pub mod broadcast_notifications;
+pub mod compression;
 pub mod constructors;
 pub mod coordination_metrics;
+pub mod crc32c;
+pub mod durability;
+pub mod encryption;
 pub mod export;
 pub mod export_import_lock;
 pub mod fs_persist;
 pub mod import;
+pub mod incremental;
+#[cfg(target_arch = "wasm32")]
+pub mod indexeddb_handles;
 #[cfg(target_arch = "wasm32")]
 pub mod indexeddb_queue;
 pub mod io_operations;
@@ -23,6 +30,8 @@ pub mod recovery;
 #[cfg(target_arch = "wasm32")]
 pub mod reentrancy_handler;
 pub mod retry_logic;
+pub mod statement_cache;
+pub mod subscriptions;
 pub mod sync_operations;
 pub mod vfs_sync;
 #[cfg(target_arch = "wasm32")]
@@ -34,7 +43,9 @@ pub mod wasm_vfs_sync;
 pub mod write_queue;
 
 pub use block_info::{BlockInfo, BlockStorageInfo};
-pub use block_storage::{BLOCK_SIZE, BlockStorage, CrashRecoveryAction, SyncPolicy};
+pub use block_storage::{
+    BLOCK_SIZE, BlockStorage, CrashRecoveryAction, SyncPolicy, set_block_cache_capacity,
+};
 #[cfg(any(
     target_arch = "wasm32",
     all(not(target_arch = "wasm32"), any(test, debug_assertions)),
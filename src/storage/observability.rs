@@ -21,6 +21,12 @@ pub struct StorageMetrics {
     pub throughput_blocks_per_sec: f64,
     pub throughput_bytes_per_sec: f64,
     pub error_rate: f64,
+    /// Block reads served from the LRU cache
+    pub cache_hits: u64,
+    /// Block reads that missed the LRU cache
+    pub cache_misses: u64,
+    /// `cache_hits / (cache_hits + cache_misses)`; 0 before any read
+    pub cache_hit_rate: f64,
 }
 
 impl Default for StorageMetrics {
@@ -37,6 +43,9 @@ impl Default for StorageMetrics {
             throughput_blocks_per_sec: 0.0,
             throughput_bytes_per_sec: 0.0,
             error_rate: 0.0,
+            cache_hits: 0,
+            cache_misses: 0,
+            cache_hit_rate: 0.0,
         }
     }
 }
@@ -69,6 +78,11 @@ pub struct ObservabilityManager {
     // WASM-specific callbacks
     #[cfg(target_arch = "wasm32")]
     pub(super) wasm_sync_success_callback: Option<WasmSyncSuccessCallback>,
+    /// JS callback registered with `onStorageEvent`
+    #[cfg(target_arch = "wasm32")]
+    pub(super) storage_event_callback: std::cell::RefCell<Option<js_sys::Function>>,
+    #[cfg(target_arch = "wasm32")]
+    pub(super) last_sync_started_ms: std::cell::Cell<f64>,
 
     // Throughput tracking (use interior mutability)
     #[cfg(not(target_arch = "wasm32"))]
@@ -90,6 +104,10 @@ impl Default for ObservabilityManager {
             backpressure_callback: None,
             #[cfg(target_arch = "wasm32")]
             wasm_sync_success_callback: None,
+            #[cfg(target_arch = "wasm32")]
+            storage_event_callback: std::cell::RefCell::new(None),
+            #[cfg(target_arch = "wasm32")]
+            last_sync_started_ms: std::cell::Cell::new(0.0),
             #[cfg(not(target_arch = "wasm32"))]
             last_sync_start: Mutex::new(None),
             #[cfg(not(target_arch = "wasm32"))]
@@ -139,6 +157,12 @@ impl ObservabilityManager {
         if let Some(ref callback) = self.sync_start_callback {
             callback(dirty_count, dirty_bytes);
         }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.last_sync_started_ms.set(js_sys::Date::now());
+            self.emit_storage_event("sync_start", dirty_count, 0.0, None);
+        }
     }
 
     /// Record sync success
@@ -155,6 +179,7 @@ impl ObservabilityManager {
             if let Some(ref callback) = self.wasm_sync_success_callback {
                 callback(duration_ms, blocks_synced);
             }
+            self.emit_storage_event("sync_success", blocks_synced, duration_ms as f64, None);
         }
     }
 
@@ -163,13 +188,69 @@ impl ObservabilityManager {
         if let Some(ref callback) = self.sync_failure_callback {
             callback(error);
         }
+
+        #[cfg(target_arch = "wasm32")]
+        self.emit_storage_event(
+            "sync_failure",
+            self.last_sync_blocks.load(Ordering::SeqCst) as usize,
+            js_sys::Date::now() - self.last_sync_started_ms.get(),
+            Some(("error", &error.message)),
+        );
     }
 
     /// Record backpressure event
-    pub fn record_backpressure(&self, level: &str, reason: &str) {
+    pub fn record_backpressure(&self, level: &str, reason: &str, dirty_count: usize) {
         if let Some(ref callback) = self.backpressure_callback {
             callback(level, reason);
         }
+
+        #[cfg(target_arch = "wasm32")]
+        self.emit_storage_event("backpressure", dirty_count, 0.0, Some(("reason", reason)));
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = dirty_count;
+    }
+
+    /// Set or clear the JS callback that receives storage events
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_storage_event_callback(&self, callback: Option<js_sys::Function>) {
+        *self.storage_event_callback.borrow_mut() = callback;
+    }
+
+    /// Call the `onStorageEvent` callback with `{ type, dirtyCount, durationMs }`
+    ///
+    /// `detail` adds one more string field, e.g. the error message of a failed sync.
+    #[cfg(target_arch = "wasm32")]
+    fn emit_storage_event(
+        &self,
+        kind: &str,
+        dirty_count: usize,
+        duration_ms: f64,
+        detail: Option<(&str, &str)>,
+    ) {
+        use wasm_bindgen::JsValue;
+
+        // Clone so the callback may replace itself
+        let Some(callback) = self.storage_event_callback.borrow().clone() else {
+            return;
+        };
+        let event = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&event, &"type".into(), &JsValue::from_str(kind));
+        let _ = js_sys::Reflect::set(
+            &event,
+            &"dirtyCount".into(),
+            &JsValue::from_f64(dirty_count as f64),
+        );
+        let _ = js_sys::Reflect::set(
+            &event,
+            &"durationMs".into(),
+            &JsValue::from_f64(duration_ms),
+        );
+        if let Some((key, value)) = detail {
+            let _ = js_sys::Reflect::set(&event, &key.into(), &JsValue::from_str(value));
+        }
+        if let Err(e) = callback.call1(&JsValue::NULL, &event) {
+            log::warn!("onStorageEvent callback threw: {:?}", e);
+        }
     }
 
     /// Calculate throughput metrics
@@ -2,7 +2,7 @@
 //! This module contains startup recovery and integrity verification functionality
 
 use super::block_storage::{
-    BlockStorage, CorruptionAction, RecoveryMode, RecoveryOptions, RecoveryReport,
+    BlockStorage, CorruptionAction, RecoveryMode, RecoveryOptions, RecoveryReport, RepairOutcome,
 };
 use crate::types::DatabaseError;
 
@@ -26,7 +26,7 @@ macro_rules! lock_mutex {
 }
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "fs_persist"))]
-use crate::storage::{BLOCK_SIZE, ChecksumAlgorithm};
+use crate::storage::ChecksumAlgorithm;
 #[cfg(all(not(target_arch = "wasm32"), feature = "fs_persist"))]
 use std::collections::HashMap;
 #[cfg(all(not(target_arch = "wasm32"), feature = "fs_persist"))]
@@ -72,7 +72,8 @@ pub async fn perform_startup_recovery(
                                     let bpath = blocks_dir.join(format!("block_{}.bin", block_id));
                                     match std::fs::metadata(&bpath) {
                                         Ok(meta) => {
-                                            if !meta.is_file() || meta.len() as usize != BLOCK_SIZE
+                                            if !meta.is_file()
+                                                || meta.len() as usize != storage.block_size
                                             {
                                                 log::warn!(
                                                     "Pending commit references block {} but file invalid: {:?}",
@@ -133,11 +134,7 @@ pub async fn perform_startup_recovery(
                                         }
                                         let algo_str =
                                             meta.get("algo").and_then(|v| v.as_str()).unwrap_or("");
-                                        let algo = match algo_str {
-                                            "CRC32" => Some(ChecksumAlgorithm::CRC32),
-                                            "FastHash" => Some(ChecksumAlgorithm::FastHash),
-                                            _ => None,
-                                        };
+                                        let algo = ChecksumAlgorithm::from_name(algo_str);
                                         if let Some(a) = algo {
                                             algos_new.insert(bid, a);
                                         }
@@ -244,7 +241,7 @@ pub async fn perform_startup_recovery(
                         let p = blocks_dir.join(format!("block_{}.bin", id));
                         match fs::metadata(&p) {
                             Ok(meta) => {
-                                if meta.is_file() && meta.len() as usize == BLOCK_SIZE {
+                                if meta.is_file() && meta.len() as usize == storage.block_size {
                                     true
                                 } else {
                                     // Invalid-sized or non-regular file: drop metadata and delete file now
@@ -336,11 +333,10 @@ pub async fn perform_startup_recovery(
                             if let Some(csum) = meta.get("checksum").and_then(|v| v.as_u64()) {
                                 checksums_new.insert(bid, csum);
                             }
-                            let algo = match meta.get("algo").and_then(|v| v.as_str()) {
-                                Some("CRC32") => Some(ChecksumAlgorithm::CRC32),
-                                Some("FastHash") => Some(ChecksumAlgorithm::FastHash),
-                                _ => None,
-                            };
+                            let algo = meta
+                                .get("algo")
+                                .and_then(|v| v.as_str())
+                                .and_then(ChecksumAlgorithm::from_name);
                             if let Some(a) = algo {
                                 algos_new.insert(bid, a);
                             }
@@ -408,6 +404,8 @@ pub async fn perform_startup_recovery(
 
     let mut corrupted_blocks = Vec::new();
     let mut repaired_blocks = Vec::new();
+    let mut restored_blocks = Vec::new();
+    let mut deleted_blocks = Vec::new();
 
     // Skip recovery if requested
     if matches!(opts.mode, RecoveryMode::Skip) {
@@ -416,7 +414,10 @@ pub async fn perform_startup_recovery(
             total_blocks_verified: 0,
             corrupted_blocks: Vec::new(),
             repaired_blocks: Vec::new(),
+            restored_blocks: Vec::new(),
+            deleted_blocks: Vec::new(),
             verification_duration_ms: BlockStorage::now_millis() - start_time,
+            sampled_blocks: Vec::new(),
         };
         return Ok(());
     }
@@ -424,6 +425,10 @@ pub async fn perform_startup_recovery(
     // Get list of blocks to verify based on mode
     let blocks_to_verify = storage.get_blocks_for_verification(&opts.mode).await?;
     let total_verified = blocks_to_verify.len();
+    let sampled_blocks = match opts.mode {
+        RecoveryMode::Sample { .. } | RecoveryMode::Random { .. } => blocks_to_verify.clone(),
+        RecoveryMode::Full | RecoveryMode::Skip => Vec::new(),
+    };
 
     log::info!(
         "Verifying {} blocks during startup recovery",
@@ -446,12 +451,12 @@ pub async fn perform_startup_recovery(
                         log::info!("Corruption in block {} reported", block_id);
                     }
                     CorruptionAction::Repair => {
-                        if storage.repair_corrupted_block(block_id).await? {
-                            log::info!("Successfully repaired block {}", block_id);
-                            repaired_blocks.push(block_id);
-                        } else {
-                            log::error!("Failed to repair block {}", block_id);
+                        match storage.repair_corrupted_block(block_id).await? {
+                            RepairOutcome::Restored => restored_blocks.push(block_id),
+                            RepairOutcome::Deleted => deleted_blocks.push(block_id),
                         }
+                        log::info!("Successfully repaired block {}", block_id);
+                        repaired_blocks.push(block_id);
                     }
                     CorruptionAction::Fail => {
                         return Err(DatabaseError::new(
@@ -475,10 +480,12 @@ pub async fn perform_startup_recovery(
 
     let duration = BlockStorage::now_millis() - start_time;
     log::info!(
-        "Startup recovery completed: {} blocks verified, {} corrupted, {} repaired in {}ms",
+        "Startup recovery completed: {} blocks verified, {} corrupted, {} repaired ({} restored, {} deleted) in {}ms",
         total_verified,
         corrupted_blocks.len(),
         repaired_blocks.len(),
+        restored_blocks.len(),
+        deleted_blocks.len(),
         duration
     );
 
@@ -486,7 +493,10 @@ pub async fn perform_startup_recovery(
         total_blocks_verified: total_verified,
         corrupted_blocks,
         repaired_blocks,
+        restored_blocks,
+        deleted_blocks,
         verification_duration_ms: duration,
+        sampled_blocks,
     };
 
     Ok(())
@@ -8,9 +8,12 @@
 //! - Quota exceeded errors are NOT retried (permanent failures)
 //! - Transient errors are retried (transaction failures, network issues)
 //! - Comprehensive logging for debugging
+//! - Configurable retry of `SQLITE_BUSY`/`SQLITE_LOCKED` statements (`BusyRetryConfig`)
 
 use crate::types::DatabaseError;
+use serde::{Deserialize, Serialize};
 use std::future::Future;
+use tsify::Tsify;
 
 /// Maximum number of retry attempts for transient failures
 const MAX_RETRY_ATTEMPTS: u32 = 3;
@@ -18,6 +21,143 @@ const MAX_RETRY_ATTEMPTS: u32 = 3;
 /// Base delay in milliseconds for exponential backoff
 const BASE_DELAY_MS: u32 = 100;
 
+/// Retry policy for statements that fail with `SQLITE_BUSY` or `SQLITE_LOCKED`
+///
+/// Attempt `n` (1-based) waits `base_delay_ms * 2^(n-1)` before the statement is
+/// re-run, yielding to the event loop so the connection holding the lock can finish.
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BusyRetryConfig {
+    /// Total attempts including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry in milliseconds
+    pub base_delay_ms: u32,
+}
+
+impl Default for BusyRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay_ms: 10,
+        }
+    }
+}
+
+/// Primary result codes for a busy/locked database
+const SQLITE_BUSY: i32 = 5;
+const SQLITE_LOCKED: i32 = 6;
+/// Primary result code for a statement invalidated by a schema change
+const SQLITE_SCHEMA: i32 = 17;
+
+/// Determine if an error is a SQLite busy/locked failure that may succeed on retry
+pub fn is_busy_error(error: &DatabaseError) -> bool {
+    if error.code == "SQLITE_BUSY" {
+        return true;
+    }
+    matches!(
+        error.sqlite_code.map(|code| code & 0xff),
+        Some(SQLITE_BUSY) | Some(SQLITE_LOCKED)
+    )
+}
+
+/// Determine if an error means the schema changed under a prepared statement
+pub fn is_schema_error(error: &DatabaseError) -> bool {
+    error.sqlite_code.map(|code| code & 0xff) == Some(SQLITE_SCHEMA)
+}
+
+/// Run `operation`, running it once more if it fails with `SQLITE_SCHEMA`
+///
+/// The failed statement has been finalized by then, so the retry prepares it against
+/// the current schema. A second schema failure is returned as is.
+pub async fn with_schema_retry<F, Fut, T>(mut operation: F) -> Result<T, DatabaseError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, DatabaseError>>,
+{
+    match operation().await {
+        Err(error) if is_schema_error(&error) => {
+            log::debug!(
+                "Schema changed under statement, re-preparing once: {}",
+                error.message
+            );
+            operation().await
+        }
+        result => result,
+    }
+}
+
+/// Exponential backoff delay before retry `attempt` (1-based)
+pub fn backoff_delay_ms(base_delay_ms: u32, attempt: u32) -> u32 {
+    base_delay_ms.saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+}
+
+/// Run a SQLite statement, retrying busy/locked failures according to `config`
+///
+/// With no config the statement runs once. A busy/locked failure that survives
+/// every attempt is returned with the distinct `SQLITE_BUSY` error code.
+pub async fn with_busy_retry<F, Fut, T>(
+    config: Option<BusyRetryConfig>,
+    mut operation: F,
+) -> Result<T, DatabaseError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, DatabaseError>>,
+{
+    let max_attempts = config.map(|c| c.max_attempts.max(1)).unwrap_or(1);
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+
+        match operation().await {
+            Err(error) if is_busy_error(&error) => {
+                if attempt >= max_attempts {
+                    log::warn!(
+                        "Database still busy after {} attempts: {}",
+                        attempt,
+                        error.message
+                    );
+                    return Err(DatabaseError {
+                        code: "SQLITE_BUSY".to_string(),
+                        ..error
+                    });
+                }
+
+                let delay_ms =
+                    backoff_delay_ms(config.map(|c| c.base_delay_ms).unwrap_or_default(), attempt);
+                log::debug!(
+                    "Database busy, retrying after {}ms (attempt {}/{})",
+                    delay_ms,
+                    attempt,
+                    max_attempts
+                );
+                sleep_ms(delay_ms).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Wait without blocking the event loop
+pub async fn sleep_ms(delay_ms: u32) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        // Use setTimeout to yield to browser event loop
+        let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+            web_sys::window()
+                .unwrap()
+                .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, delay_ms as i32)
+                .unwrap();
+        });
+        wasm_bindgen_futures::JsFuture::from(promise).await.ok();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        tokio::time::sleep(std::time::Duration::from_millis(delay_ms as u64)).await;
+    }
+}
+
 /// Determine if an error is retriable
 ///
 /// # Retriable Errors
@@ -28,7 +168,7 @@ const BASE_DELAY_MS: u32 = 100;
 /// - GET_ERROR, PUT_ERROR - Operation-specific errors
 ///
 /// # Non-Retriable Errors
-/// - QuotaExceededError - Storage quota exceeded (needs user intervention)
+/// - QuotaExceededError, STORAGE_QUOTA_EXCEEDED - Storage quota exceeded (needs user intervention)
 /// - INVALID_STATE_ERROR - Invalid state (programming error)
 /// - NOT_FOUND_ERROR - Resource not found (won't exist on retry)
 /// - CONSTRAINT_ERROR - Database constraint violation
@@ -36,7 +176,7 @@ pub fn is_retriable_error(error: &DatabaseError) -> bool {
     let code = error.code.as_str();
 
     // Quota errors are never retriable
-    if code.contains("Quota") || code.contains("quota") {
+    if code.contains("Quota") || code.contains("quota") || code.contains("QUOTA") {
         log::debug!("Error is quota-related, not retriable: {}", code);
         return false;
     }
@@ -139,7 +279,7 @@ where
                 }
 
                 // Calculate exponential backoff delay: 100ms, 200ms, 400ms
-                let delay_ms = BASE_DELAY_MS * 2_u32.pow(attempt - 1);
+                let delay_ms = backoff_delay_ms(BASE_DELAY_MS, attempt);
                 log::debug!(
                     "Retrying '{}' after {}ms delay (attempt {}/{})",
                     operation_name,
@@ -149,25 +289,7 @@ where
                 );
 
                 // Wait before retrying
-                #[cfg(target_arch = "wasm32")]
-                {
-                    // Use setTimeout to yield to browser event loop
-                    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
-                        web_sys::window()
-                            .unwrap()
-                            .set_timeout_with_callback_and_timeout_and_arguments_0(
-                                &resolve,
-                                delay_ms as i32,
-                            )
-                            .unwrap();
-                    });
-                    wasm_bindgen_futures::JsFuture::from(promise).await.ok();
-                }
-
-                #[cfg(not(target_arch = "wasm32"))]
-                {
-                    tokio::time::sleep(std::time::Duration::from_millis(delay_ms as u64)).await;
-                }
+                sleep_ms(delay_ms).await;
             }
         }
     }
@@ -186,6 +308,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_retriable_storage_quota_exceeded() {
+        let error =
+            DatabaseError::new("STORAGE_QUOTA_EXCEEDED", "IndexedDB storage quota exceeded");
+        assert!(
+            !is_retriable_error(&error),
+            "STORAGE_QUOTA_EXCEEDED should not be retriable"
+        );
+    }
+
     #[test]
     fn test_is_retriable_transaction_error() {
         let error = DatabaseError::new("TRANSACTION_ERROR", "Transaction failed");
@@ -221,4 +353,105 @@ mod tests {
             "IndexedDB error should be retriable"
         );
     }
+
+    #[test]
+    fn test_is_busy_error_matches_extended_codes() {
+        // SQLITE_BUSY_SNAPSHOT (517) and SQLITE_LOCKED_SHAREDCACHE (262)
+        for code in [5, 6, 517, 262] {
+            let error = DatabaseError::new("SQLITE_ERROR", "busy").with_sqlite_code(code);
+            assert!(is_busy_error(&error), "code {} should be busy", code);
+        }
+        let error = DatabaseError::new("SQLITE_ERROR", "constraint").with_sqlite_code(2067);
+        assert!(!is_busy_error(&error));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_schema_retry_retries_once() {
+        let schema_error = || {
+            DatabaseError::new("SQLITE_ERROR", "database schema has changed").with_sqlite_code(17)
+        };
+
+        let mut calls = 0;
+        let result = with_schema_retry(|| {
+            calls += 1;
+            let first = calls == 1;
+            async move {
+                if first {
+                    Err(schema_error())
+                } else {
+                    Ok(calls)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 2);
+
+        let mut calls = 0;
+        let result: Result<(), _> = with_schema_retry(|| {
+            calls += 1;
+            async { Err(schema_error()) }
+        })
+        .await;
+        assert!(is_schema_error(&result.unwrap_err()));
+        assert_eq!(calls, 2, "only one retry");
+    }
+
+    #[test]
+    fn test_backoff_delay_doubles() {
+        assert_eq!(backoff_delay_ms(100, 1), 100);
+        assert_eq!(backoff_delay_ms(100, 2), 200);
+        assert_eq!(backoff_delay_ms(100, 3), 400);
+        assert_eq!(backoff_delay_ms(u32::MAX, 5), u32::MAX);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_busy_retry_succeeds_after_busy_attempts() {
+        let mut calls = 0;
+        let result = with_busy_retry(
+            Some(BusyRetryConfig {
+                max_attempts: 4,
+                base_delay_ms: 1,
+            }),
+            || {
+                calls += 1;
+                let busy = calls < 3;
+                async move {
+                    if busy {
+                        Err(DatabaseError::new("SQLITE_ERROR", "database is locked")
+                            .with_sqlite_code(5))
+                    } else {
+                        Ok(calls)
+                    }
+                }
+            },
+        )
+        .await;
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_busy_retry_exhausted_returns_sqlite_busy() {
+        let mut calls = 0;
+        let result: Result<(), DatabaseError> = with_busy_retry(
+            Some(BusyRetryConfig {
+                max_attempts: 2,
+                base_delay_ms: 1,
+            }),
+            || {
+                calls += 1;
+                async {
+                    Err(DatabaseError::new("SQLITE_ERROR", "database is locked")
+                        .with_sqlite_code(5))
+                }
+            },
+        )
+        .await;
+        let error = result.unwrap_err();
+        assert_eq!(error.code, "SQLITE_BUSY");
+        assert_eq!(error.sqlite_code, Some(5));
+        assert_eq!(calls, 2);
+    }
 }
@@ -0,0 +1,257 @@
+/// Statement Cache Module
+///
+/// Bounded LRU cache of compiled statements keyed by SQL text.
+/// Hot queries reuse the compiled statement instead of re-running prepare.
+///
+/// Key Features:
+/// - Least-recently-used eviction once capacity is reached
+/// - Statements are taken out while in use, so re-entrant execution never shares one
+/// - Evicted/cleared statements are handed back to the caller for finalization
+/// - Hit/miss counters for verifying cache effectiveness
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Default number of statements cached per database connection
+pub const DEFAULT_STATEMENT_CACHE_SIZE: usize = 32;
+
+/// Default cap on prepared statements in use on a connection at once
+pub const DEFAULT_MAX_OPEN_STATEMENTS: usize = 64;
+
+/// Statement cache statistics
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementCacheStats {
+    /// Lookups that found a cached statement
+    pub hits: u64,
+    /// Lookups that required a fresh prepare
+    pub misses: u64,
+    /// Statements currently cached
+    pub size: usize,
+    /// Maximum number of cached statements
+    pub capacity: usize,
+}
+
+/// LRU cache of compiled statements keyed by SQL text
+///
+/// Generic over the statement handle so the eviction logic stays platform independent;
+/// on WASM the handle is a raw `sqlite3_stmt` pointer.
+pub struct StatementCache<S> {
+    /// Maximum number of cached statements (0 disables caching)
+    capacity: usize,
+    /// Cached statements keyed by SQL text
+    entries: HashMap<String, S>,
+    /// SQL keys ordered from least to most recently used
+    order: VecDeque<String>,
+    /// SQL keys stored by `put_unused`; their first counted `take` is the real miss
+    unused: HashSet<String>,
+    hits: u64,
+    misses: u64,
+}
+
+impl<S> StatementCache<S> {
+    /// Create a new statement cache with the given capacity
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            unused: HashSet::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Check if caching is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Take a cached statement for the given SQL, recording a hit or miss
+    ///
+    /// The statement is removed from the cache until it is returned via `put`.
+    pub fn take(&mut self, sql: &str) -> Option<S> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        match self.entries.remove(sql) {
+            Some(stmt) => {
+                self.order.retain(|key| key != sql);
+                if self.unused.remove(sql) {
+                    self.misses += 1;
+                } else {
+                    self.hits += 1;
+                }
+                Some(stmt)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Take a cached statement without touching the hit/miss counters
+    ///
+    /// For looking at a statement ahead of the lookup that runs it.
+    pub fn take_uncounted(&mut self, sql: &str) -> Option<S> {
+        let stmt = self.entries.remove(sql)?;
+        self.order.retain(|key| key != sql);
+        Some(stmt)
+    }
+
+    /// Return a statement to the cache as the most recently used entry
+    ///
+    /// Returns the statements that no longer fit (evicted entries, a replaced duplicate,
+    /// or the statement itself when caching is disabled) so the caller can finalize them.
+    pub fn put(&mut self, sql: &str, stmt: S) -> Vec<S> {
+        if !self.is_enabled() {
+            return vec![stmt];
+        }
+
+        let mut released = Vec::new();
+
+        if let Some(previous) = self.entries.insert(sql.to_string(), stmt) {
+            self.order.retain(|key| key != sql);
+            released.push(previous);
+        }
+        self.order.push_back(sql.to_string());
+
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    self.unused.remove(&oldest);
+                    if let Some(evicted) = self.entries.remove(&oldest) {
+                        released.push(evicted);
+                    }
+                }
+                None => break,
+            }
+        }
+
+        released
+    }
+
+    /// Cache a statement prepared ahead of its first lookup
+    ///
+    /// The next `take` counts as the miss that prepared it rather than as a hit.
+    pub fn put_unused(&mut self, sql: &str, stmt: S) -> Vec<S> {
+        let released = self.put(sql, stmt);
+        if self.entries.contains_key(sql) {
+            self.unused.insert(sql.to_string());
+        }
+        released
+    }
+
+    /// Remove all cached statements, returning them for finalization
+    pub fn clear(&mut self) -> Vec<S> {
+        self.order.clear();
+        self.unused.clear();
+        self.entries.drain().map(|(_, stmt)| stmt).collect()
+    }
+
+    /// Get the number of cached statements
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the cache holds no statements
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get hit/miss statistics
+    pub fn stats(&self) -> StatementCacheStats {
+        StatementCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            size: self.entries.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl<S> Default for StatementCache<S> {
+    fn default() -> Self {
+        Self::new(DEFAULT_STATEMENT_CACHE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_miss_then_hit() {
+        let mut cache: StatementCache<u32> = StatementCache::new(4);
+        assert_eq!(cache.take("SELECT 1"), None);
+        assert!(cache.put("SELECT 1", 1).is_empty());
+        assert_eq!(cache.take("SELECT 1"), Some(1));
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.size, 0);
+    }
+
+    #[test]
+    fn test_uncounted_lookups() {
+        let mut cache: StatementCache<u32> = StatementCache::new(4);
+        assert_eq!(cache.take_uncounted("SELECT 1"), None);
+        assert!(cache.put_unused("SELECT 1", 1).is_empty());
+        let stmt = cache.take_uncounted("SELECT 1").unwrap();
+        assert!(cache.put("SELECT 1", stmt).is_empty());
+        assert_eq!(cache.stats().misses, 0);
+
+        // The first counted lookup pays for the prepare, later ones reuse it
+        let stmt = cache.take("SELECT 1").unwrap();
+        assert!(cache.put("SELECT 1", stmt).is_empty());
+        assert_eq!(cache.take("SELECT 1"), Some(1));
+        let stats = cache.stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 1);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache: StatementCache<u32> = StatementCache::new(2);
+        assert!(cache.put("a", 1).is_empty());
+        assert!(cache.put("b", 2).is_empty());
+
+        // Touch "a" so "b" becomes least recently used
+        let a = cache.take("a").unwrap();
+        assert!(cache.put("a", a).is_empty());
+
+        assert_eq!(cache.put("c", 3), vec![2]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.take("b"), None);
+    }
+
+    #[test]
+    fn test_duplicate_put_releases_previous() {
+        let mut cache: StatementCache<u32> = StatementCache::new(4);
+        assert!(cache.put("a", 1).is_empty());
+        assert_eq!(cache.put("a", 2), vec![1]);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_disabled_cache() {
+        let mut cache: StatementCache<u32> = StatementCache::new(0);
+        assert!(!cache.is_enabled());
+        assert_eq!(cache.put("a", 1), vec![1]);
+        assert_eq!(cache.take("a"), None);
+        assert_eq!(cache.stats().misses, 0);
+    }
+
+    #[test]
+    fn test_clear_returns_all() {
+        let mut cache: StatementCache<u32> = StatementCache::new(4);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        let mut cleared = cache.clear();
+        cleared.sort();
+        assert_eq!(cleared, vec![1, 2]);
+        assert!(cache.is_empty());
+    }
+}
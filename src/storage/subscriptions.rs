@@ -0,0 +1,346 @@
+/// Query Subscriptions Module
+///
+/// Provides reactive (live) queries that re-run whenever the tables they read change.
+///
+/// Key Features:
+/// - Track subscribed queries together with the tables they reference
+/// - Match changed tables (from the SQLite update hook) against subscriptions
+/// - Coalesce changes per committed transaction so each subscription re-runs at most once
+/// - Referenced-table detection via the SQLite authorizer (WASM)
+use crate::types::ColumnValue;
+use std::collections::HashSet;
+
+/// A registered live query
+pub struct Subscription<C> {
+    /// Unique ID for this subscription
+    pub id: u32,
+    /// The SELECT statement to re-run
+    pub sql: String,
+    /// Bound parameters for the statement
+    pub params: Vec<ColumnValue>,
+    /// Lowercased names of the tables the query reads
+    pub tables: HashSet<String>,
+    /// Callback invoked with fresh results
+    pub callback: C,
+}
+
+/// Registry of live queries for a database instance
+///
+/// Generic over the callback type so matching logic stays platform independent;
+/// on WASM the callback is a `js_sys::Function`.
+pub struct SubscriptionManager<C> {
+    next_id: u32,
+    subscriptions: Vec<Subscription<C>>,
+}
+
+impl<C> SubscriptionManager<C> {
+    /// Create a new, empty subscription manager
+    pub fn new() -> Self {
+        Self {
+            next_id: 1,
+            subscriptions: Vec::new(),
+        }
+    }
+
+    /// Register a subscription and return its ID
+    pub fn add(
+        &mut self,
+        sql: String,
+        params: Vec<ColumnValue>,
+        tables: HashSet<String>,
+        callback: C,
+    ) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.subscriptions.push(Subscription {
+            id,
+            sql,
+            params,
+            tables: tables.iter().map(|t| t.to_lowercase()).collect(),
+            callback,
+        });
+        id
+    }
+
+    /// Remove a subscription, returning whether it existed
+    pub fn remove(&mut self, id: u32) -> bool {
+        let before = self.subscriptions.len();
+        self.subscriptions.retain(|s| s.id != id);
+        self.subscriptions.len() != before
+    }
+
+    /// Get subscriptions that read any of the changed tables
+    pub fn matching(&self, changed_tables: &HashSet<String>) -> Vec<&Subscription<C>> {
+        self.subscriptions
+            .iter()
+            .filter(|s| {
+                s.tables
+                    .iter()
+                    .any(|t| changed_tables.iter().any(|c| c.eq_ignore_ascii_case(t)))
+            })
+            .collect()
+    }
+
+    /// Get the number of active subscriptions
+    pub fn len(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    /// Check if there are no active subscriptions
+    pub fn is_empty(&self) -> bool {
+        self.subscriptions.is_empty()
+    }
+
+    /// Remove all subscriptions
+    pub fn clear(&mut self) {
+        self.subscriptions.clear();
+    }
+}
+
+impl<C> Default for SubscriptionManager<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tables changed on a connection, coalesced per transaction
+///
+/// Changes recorded inside a transaction are held back until it commits and
+/// discarded if it rolls back, so subscribers see each committed transaction once.
+#[derive(Debug, Default)]
+pub struct ChangeTracker {
+    pending: HashSet<String>,
+    committed: HashSet<String>,
+}
+
+impl ChangeTracker {
+    /// Record a table written by the current transaction
+    pub fn record(&mut self, table: &str) {
+        self.pending.insert(table.to_lowercase());
+    }
+
+    /// Mark the current transaction's changes as committed
+    pub fn commit(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        self.committed.extend(pending);
+    }
+
+    /// Discard the current transaction's changes
+    pub fn rollback(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Take the tables changed by transactions committed since the last call
+    pub fn take_committed(&mut self) -> HashSet<String> {
+        std::mem::take(&mut self.committed)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    /// Change trackers for each connection, keyed by sqlite3 pointer
+    static CHANGE_TRACKERS: std::cell::RefCell<std::collections::HashMap<usize, ChangeTracker>> =
+        std::cell::RefCell::new(std::collections::HashMap::new());
+}
+
+#[cfg(target_arch = "wasm32")]
+fn with_tracker<R>(arg: *mut std::os::raw::c_void, f: impl FnOnce(&mut ChangeTracker) -> R) -> R {
+    CHANGE_TRACKERS.with(|trackers| f(trackers.borrow_mut().entry(arg as usize).or_default()))
+}
+
+/// SQLite update hook: record the table touched by each INSERT/UPDATE/DELETE
+#[cfg(target_arch = "wasm32")]
+unsafe extern "C" fn record_table_change(
+    arg: *mut std::os::raw::c_void,
+    _op: std::os::raw::c_int,
+    _db_name: *const std::os::raw::c_char,
+    table: *const std::os::raw::c_char,
+    _rowid: sqlite_wasm_rs::sqlite3_int64,
+) {
+    if table.is_null() {
+        return;
+    }
+    let table = unsafe { std::ffi::CStr::from_ptr(table) }.to_string_lossy();
+    with_tracker(arg, |tracker| tracker.record(&table));
+}
+
+/// SQLite commit hook: promote the transaction's changes (never vetoes the commit)
+#[cfg(target_arch = "wasm32")]
+unsafe extern "C" fn commit_table_changes(arg: *mut std::os::raw::c_void) -> std::os::raw::c_int {
+    with_tracker(arg, |tracker| tracker.commit());
+    0
+}
+
+/// SQLite rollback hook: drop the transaction's changes
+#[cfg(target_arch = "wasm32")]
+unsafe extern "C" fn rollback_table_changes(arg: *mut std::os::raw::c_void) {
+    with_tracker(arg, |tracker| tracker.rollback());
+}
+
+/// Install the change-tracking update, commit and rollback hooks on a connection
+///
+/// # Safety
+/// `db` must be an open connection. The commit and rollback hooks keep its changes
+/// keyed by its address until `remove_change_tracker` is called, which must happen
+/// before the connection closes so a later connection at the same address starts clean.
+#[cfg(target_arch = "wasm32")]
+pub(crate) unsafe fn install_update_hook(db: *mut sqlite_wasm_rs::sqlite3) {
+    unsafe {
+        sqlite_wasm_rs::sqlite3_update_hook(db, Some(record_table_change), db as *mut _);
+        sqlite_wasm_rs::sqlite3_commit_hook(db, Some(commit_table_changes), db as *mut _);
+        sqlite_wasm_rs::sqlite3_rollback_hook(db, Some(rollback_table_changes), db as *mut _);
+    }
+}
+
+/// Take the set of tables changed by transactions committed on a connection since the last call
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn take_changed_tables(db: *mut sqlite_wasm_rs::sqlite3) -> HashSet<String> {
+    CHANGE_TRACKERS.with(|trackers| {
+        trackers
+            .borrow_mut()
+            .get_mut(&(db as usize))
+            .map(|tracker| tracker.take_committed())
+            .unwrap_or_default()
+    })
+}
+
+/// Forget any recorded changes for a connection that is being closed
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn remove_change_tracker(db: *mut sqlite_wasm_rs::sqlite3) {
+    CHANGE_TRACKERS.with(|trackers| {
+        trackers.borrow_mut().remove(&(db as usize));
+    });
+}
+
+/// Authorizer callback collecting every table read by the statement being prepared
+#[cfg(target_arch = "wasm32")]
+unsafe extern "C" fn collect_read_tables(
+    arg: *mut std::os::raw::c_void,
+    action: std::os::raw::c_int,
+    table: *const std::os::raw::c_char,
+    _column: *const std::os::raw::c_char,
+    _db_name: *const std::os::raw::c_char,
+    _trigger: *const std::os::raw::c_char,
+) -> std::os::raw::c_int {
+    if action == sqlite_wasm_rs::SQLITE_READ && !table.is_null() {
+        let tables = unsafe { &mut *(arg as *mut HashSet<String>) };
+        let name = unsafe { std::ffi::CStr::from_ptr(table) }
+            .to_string_lossy()
+            .to_lowercase();
+        if !name.starts_with("sqlite_") {
+            tables.insert(name);
+        }
+    }
+    sqlite_wasm_rs::SQLITE_OK
+}
+
+/// Determine the tables a statement reads by preparing it under an authorizer
+///
+/// Views are resolved to their underlying tables. Returns the SQLite result code
+/// if the statement fails to prepare.
+///
+/// # Safety
+/// `db` must be an open connection that is not preparing or stepping a statement
+/// elsewhere, since its authorizer is swapped out while this runs.
+#[cfg(target_arch = "wasm32")]
+pub(crate) unsafe fn referenced_tables(
+    db: *mut sqlite_wasm_rs::sqlite3,
+    sql: &std::ffi::CStr,
+) -> Result<HashSet<String>, i32> {
+    let mut tables: HashSet<String> = HashSet::new();
+    let mut stmt = std::ptr::null_mut();
+    let ret = unsafe {
+        sqlite_wasm_rs::sqlite3_set_authorizer(
+            db,
+            Some(collect_read_tables),
+            &mut tables as *mut HashSet<String> as *mut _,
+        );
+        let ret = sqlite_wasm_rs::sqlite3_prepare_v2(
+            db,
+            sql.as_ptr(),
+            -1,
+            &mut stmt,
+            std::ptr::null_mut(),
+        );
+        if !stmt.is_null() {
+            sqlite_wasm_rs::sqlite3_finalize(stmt);
+        }
+        ret
+    };
+    // Hand the connection back to the app's authorizer, if it set one
+    crate::authorizer::restore(db);
+
+    if ret != sqlite_wasm_rs::SQLITE_OK {
+        return Err(ret);
+    }
+    Ok(tables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut manager: SubscriptionManager<()> = SubscriptionManager::new();
+        let id = manager.add("SELECT * FROM users".into(), vec![], tables(&["users"]), ());
+        assert_eq!(manager.len(), 1);
+        assert!(manager.remove(id));
+        assert!(!manager.remove(id));
+        assert!(manager.is_empty());
+    }
+
+    #[test]
+    fn test_unique_ids() {
+        let mut manager: SubscriptionManager<()> = SubscriptionManager::new();
+        let a = manager.add("SELECT 1".into(), vec![], tables(&[]), ());
+        let b = manager.add("SELECT 2".into(), vec![], tables(&[]), ());
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_matching_is_case_insensitive() {
+        let mut manager: SubscriptionManager<u8> = SubscriptionManager::new();
+        manager.add("SELECT * FROM Users".into(), vec![], tables(&["Users"]), 1);
+        manager.add(
+            "SELECT * FROM orders".into(),
+            vec![],
+            tables(&["orders"]),
+            2,
+        );
+
+        let matched = manager.matching(&tables(&["users"]));
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].callback, 1);
+
+        assert!(manager.matching(&tables(&["products"])).is_empty());
+    }
+
+    #[test]
+    fn test_changes_coalesced_until_commit() {
+        let mut tracker = ChangeTracker::default();
+        tracker.record("Users");
+        tracker.record("orders");
+        tracker.record("users");
+        assert!(tracker.take_committed().is_empty());
+
+        tracker.commit();
+        assert_eq!(tracker.take_committed(), tables(&["users", "orders"]));
+        assert!(tracker.take_committed().is_empty());
+    }
+
+    #[test]
+    fn test_rollback_discards_changes() {
+        let mut tracker = ChangeTracker::default();
+        tracker.record("users");
+        tracker.commit();
+        tracker.record("orders");
+        tracker.rollback();
+        tracker.commit();
+        assert_eq!(tracker.take_committed(), tables(&["users"]));
+    }
+}
@@ -61,12 +61,14 @@ use super::block_storage::GLOBAL_METADATA_TEST;
 
 /// Internal sync implementation shared by sync() and sync_now()
 pub fn sync_implementation_impl(storage: &mut BlockStorage) -> Result<(), DatabaseError> {
+    storage.ensure_checksums_before_sync();
+
     #[cfg(all(not(target_arch = "wasm32"), not(feature = "fs_persist")))]
     let start = std::time::Instant::now();
 
     // Record sync start for observability
     let dirty_count = lock_mutex!(storage.dirty_blocks).len();
-    let dirty_bytes = dirty_count * super::block_storage::BLOCK_SIZE;
+    let dirty_bytes = dirty_count * storage.block_size;
     storage
         .observability
         .record_sync_start(dirty_count, dirty_bytes);
@@ -129,15 +131,15 @@ pub fn sync_implementation_impl(storage: &mut BlockStorage) -> Result<(), Databa
                     let version = next_commit as u32;
                     db_meta.insert(
                         block_id,
-                        BlockMetadataPersist {
+                        BlockMetadataPersist::new(
                             checksum,
-                            last_modified_ms: std::time::SystemTime::now()
+                            version,
+                            std::time::SystemTime::now()
                                 .duration_since(std::time::UNIX_EPOCH)
                                 .unwrap_or_default()
                                 .as_millis() as u64,
-                            version,
-                            algo: storage.checksum_manager.get_algorithm(block_id),
-                        },
+                            storage.checksum_manager.get_algorithm(block_id),
+                        ),
                     );
                 }
             }
@@ -244,12 +246,12 @@ pub fn sync_implementation_impl(storage: &mut BlockStorage) -> Result<(), Databa
                     let version = next_commit as u32;
                     db_meta.insert(
                         block_id,
-                        BlockMetadataPersist {
+                        BlockMetadataPersist::new(
                             checksum,
-                            last_modified_ms: BlockStorage::now_millis(),
                             version,
-                            algo: storage.checksum_manager.get_algorithm(block_id),
-                        },
+                            BlockStorage::now_millis(),
+                            storage.checksum_manager.get_algorithm(block_id),
+                        ),
                     );
                 }
             }
@@ -27,6 +27,127 @@ thread_local! {
     static INDEXEDDB_MUTEX: RefCell<Arc<Mutex<()>>> = RefCell::new(Arc::new(Mutex::new(())));
 }
 
+/// Encode a block for the IndexedDB blocks store
+///
+/// Plain blocks are stored as a `Uint8Array` (the original format). Compressed and/or
+/// encrypted blocks are stored as `{ codec?, cipher?, data }` so databases holding several
+/// kinds still load. Blocks are compressed before they are encrypted.
+/// Returns the value and whether it was compressed.
+#[cfg(target_arch = "wasm32")]
+fn encode_block_value(
+    compression: super::compression::CompressionKind,
+    cipher: Option<&super::encryption::BlockCipher>,
+    block_id: u64,
+    data: &[u8],
+) -> Result<(JsValue, bool), DatabaseError> {
+    let compressed = compression
+        .codec()
+        .and_then(|codec| compression.compress(data).map(|c| (codec, c)));
+    if compressed.is_none() && cipher.is_none() {
+        return Ok((js_sys::Uint8Array::from(data).into(), false));
+    }
+
+    let value = js_sys::Object::new();
+    let mut payload = data.to_vec();
+    let mut encoded = Ok(true);
+    if let Some((codec, bytes)) = compressed.as_ref() {
+        payload = bytes.clone();
+        encoded = js_sys::Reflect::set(&value, &"codec".into(), &codec.name().into());
+    }
+    if let Some(cipher) = cipher {
+        payload = cipher.encrypt(block_id, &payload)?;
+        encoded = encoded.and_then(|_| {
+            js_sys::Reflect::set(
+                &value,
+                &"cipher".into(),
+                &super::encryption::CIPHER_NAME.into(),
+            )
+        });
+    }
+    let encoded = encoded.and_then(|_| {
+        js_sys::Reflect::set(
+            &value,
+            &"data".into(),
+            &js_sys::Uint8Array::from(&payload[..]),
+        )
+    });
+    match encoded {
+        Ok(_) => Ok((value.into(), compressed.is_some())),
+        // Fall back to the raw block rather than persisting a partial object, unless that
+        // would write plaintext for an encrypted database
+        Err(_) if cipher.is_none() => Ok((js_sys::Uint8Array::from(data).into(), false)),
+        Err(e) => Err(DatabaseError::new(
+            "ENCRYPTION_FAILED",
+            &format!("Failed to encode encrypted block {}: {:?}", block_id, e),
+        )),
+    }
+}
+
+/// Decode a value from the IndexedDB blocks store into plain block bytes
+///
+/// Returns None for values that are not blocks; the flag reports whether it was compressed.
+/// Encrypted blocks fail with `DECRYPTION_FAILED` when `cipher` is missing or wrong.
+#[cfg(target_arch = "wasm32")]
+fn decode_block_value(
+    value: JsValue,
+    cipher: Option<&super::encryption::BlockCipher>,
+    block_id: u64,
+) -> Option<Result<(Vec<u8>, bool), DatabaseError>> {
+    use wasm_bindgen::JsCast;
+
+    if let Some(array) = value.dyn_ref::<js_sys::Uint8Array>() {
+        return Some(Ok((array.to_vec(), false)));
+    }
+
+    let field = |name: &str| {
+        js_sys::Reflect::get(&value, &name.into())
+            .ok()
+            .and_then(|v| v.as_string())
+    };
+    let codec = field("codec");
+    let cipher_name = field("cipher");
+    if codec.is_none() && cipher_name.is_none() {
+        return None;
+    }
+    let mut data = js_sys::Reflect::get(&value, &"data".into())
+        .ok()?
+        .dyn_into::<js_sys::Uint8Array>()
+        .ok()?
+        .to_vec();
+
+    if let Some(cipher_name) = cipher_name {
+        let decrypted = match cipher {
+            _ if cipher_name != super::encryption::CIPHER_NAME => Err(DatabaseError::new(
+                "DECRYPTION_FAILED",
+                &format!("Unknown block cipher '{}'", cipher_name),
+            )),
+            None => Err(DatabaseError::new(
+                "DECRYPTION_FAILED",
+                &format!(
+                    "Block {} is encrypted but no encryption key was supplied",
+                    block_id
+                ),
+            )),
+            Some(cipher) => cipher.decrypt(block_id, &data),
+        };
+        data = match decrypted {
+            Ok(data) => data,
+            Err(e) => return Some(Err(e)),
+        };
+    }
+
+    let Some(codec) = codec else {
+        return Some(Ok((data, false)));
+    };
+    let Some(kind) = super::compression::CompressionKind::from_codec_name(&codec) else {
+        return Some(Err(DatabaseError::new(
+            "DECOMPRESSION_ERROR",
+            &format!("Unknown block codec '{}'", codec),
+        )));
+    };
+    Some(kind.decompress(&data).map(|block| (block, true)))
+}
+
 // Reentrancy-safe lock macros
 #[allow(unused_macros)]
 macro_rules! lock_mutex {
@@ -40,7 +161,7 @@ macro_rules! lock_mutex {
 /// Helper: Safely get IndexedDB factory
 /// Works in both Window and Worker contexts by using js_sys::global()
 #[cfg(target_arch = "wasm32")]
-fn get_indexeddb_factory() -> Result<web_sys::IdbFactory, DatabaseError> {
+pub(super) fn get_indexeddb_factory() -> Result<web_sys::IdbFactory, DatabaseError> {
     use wasm_bindgen::JsCast;
 
     // Get the global object (works in both Window and Worker contexts)
@@ -134,6 +255,13 @@ pub async fn restore_from_indexeddb(db_name: &str) -> Result<(), DatabaseError>
     .await
 }
 
+/// Wait until no restore or persist is running against IndexedDB
+#[cfg(target_arch = "wasm32")]
+pub async fn wait_for_idle() {
+    let mutex = INDEXEDDB_MUTEX.with(|m| m.borrow().clone());
+    drop(mutex.lock().await);
+}
+
 /// Force restore variant that always loads from IndexedDB even if blocks exist
 #[cfg(target_arch = "wasm32")]
 pub async fn restore_from_indexeddb_force(db_name: &str) -> Result<(), DatabaseError> {
@@ -516,7 +644,14 @@ async fn restore_blocks_from_indexeddb(
     let tx = std::rc::Rc::new(std::cell::RefCell::new(Some(tx)));
     let blocks_data = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
 
+    let compressed_ids =
+        std::rc::Rc::new(std::cell::RefCell::new(std::collections::HashSet::new()));
+    let decode_error = std::rc::Rc::new(std::cell::RefCell::new(None::<DatabaseError>));
+    let cipher = super::encryption::cipher_for(db_name);
+
     let blocks_data_clone = blocks_data.clone();
+    let compressed_ids_clone = compressed_ids.clone();
+    let decode_error_clone = decode_error.clone();
     let tx_clone = tx.clone();
     let success_closure =
         wasm_bindgen::closure::Closure::wrap(Box::new(move |event: web_sys::Event| {
@@ -538,16 +673,33 @@ async fn restore_blocks_from_indexeddb(
                 let parts: Vec<&str> = key.split(':').collect();
                 if parts.len() >= 2 {
                     if let Ok(block_id) = parts[1].parse::<u64>() {
-                        // Get the block data (Uint8Array)
-                        if let Ok(array) = value.dyn_into::<js_sys::Uint8Array>() {
-                            let mut data = vec![0u8; array.length() as usize];
-                            array.copy_to(&mut data);
-                            #[cfg(target_arch = "wasm32")]
-                            web_sys::console::log_1(
-                                &format!("[RESTORE] Block {} has {} bytes", block_id, data.len())
+                        // Get the block data (Uint8Array, or a compressed block object)
+                        match decode_block_value(value, cipher.as_ref(), block_id) {
+                            Some(Ok((data, compressed))) => {
+                                #[cfg(target_arch = "wasm32")]
+                                web_sys::console::log_1(
+                                    &format!(
+                                        "[RESTORE] Block {} has {} bytes (compressed={})",
+                                        block_id,
+                                        data.len(),
+                                        compressed
+                                    )
                                     .into(),
-                            );
-                            blocks_data_clone.borrow_mut().push((block_id, data));
+                                );
+                                if compressed {
+                                    compressed_ids_clone.borrow_mut().insert(block_id);
+                                }
+                                blocks_data_clone.borrow_mut().push((block_id, data));
+                            }
+                            Some(Err(e)) => {
+                                log::error!(
+                                    "[RESTORE] Block {} could not be decoded: {}",
+                                    block_id,
+                                    e
+                                );
+                                decode_error_clone.borrow_mut().get_or_insert(e);
+                            }
+                            None => {}
                         }
                     }
                 }
@@ -568,6 +720,12 @@ async fn restore_blocks_from_indexeddb(
     // Wait for cursor iteration to complete
     let _ = rx.await;
 
+    // A block we cannot decompress would otherwise read back as zeros
+    if let Some(e) = decode_error.borrow_mut().take() {
+        return Err(e);
+    }
+    let compressed_ids = compressed_ids.borrow().clone();
+
     // Now restore blocks to global storage
     // CRITICAL: De-duplicate by block_id, keeping only the LAST occurrence (highest version)
     let restored_blocks = blocks_data.borrow().clone();
@@ -767,6 +925,7 @@ async fn restore_blocks_from_indexeddb(
                         version: *stored_version,
                         last_modified_ms: 0, // Will be updated on next write
                         algo: ChecksumAlgorithm::FastHash,
+                        compressed: compressed_ids.contains(block_id),
                     },
                 );
             }
@@ -864,113 +1023,9 @@ async fn persist_to_indexeddb_event_based_internal(
 
     log::debug!("persist_to_indexeddb_event_based starting");
 
-    // Use shared block_storage database for all SQLite databases
-    let open_req = open_indexeddb("block_storage", 2)?;
-    log::info!("Created open request for block_storage version 2");
-
-    // Set up upgrade handler
-    let upgrade_closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
-        log::debug!("IndexedDB upgrade handler called");
-
-        match (|| -> Result<(), Box<dyn std::error::Error>> {
-            let target = event.target().ok_or("No event target")?;
-            log::debug!("Got event target in upgrade handler");
-
-            let request: web_sys::IdbOpenDbRequest = target
-                .dyn_into()
-                .map_err(|_| "Failed to cast to IdbOpenDbRequest")?;
-            log::debug!("Cast to IdbOpenDbRequest in upgrade handler");
-
-            let result = request
-                .result()
-                .map_err(|_| "Failed to get result from request")?;
-            log::debug!("Got result from request in upgrade handler");
-
-            let db: web_sys::IdbDatabase = result
-                .dyn_into()
-                .map_err(|_| "Failed to cast result to IdbDatabase")?;
-            log::debug!("Cast result to IdbDatabase in upgrade handler");
-
-            if !db.object_store_names().contains("blocks") {
-                db.create_object_store("blocks")
-                    .map_err(|_| "Failed to create blocks store")?;
-                log::info!("Created blocks object store");
-            }
-            if !db.object_store_names().contains("metadata") {
-                db.create_object_store("metadata")
-                    .map_err(|_| "Failed to create metadata store")?;
-                log::info!("Created metadata object store");
-            }
-
-            log::info!("Upgrade handler completed successfully");
-            Ok(())
-        })() {
-            Ok(_) => {}
-            Err(e) => {
-                log::error!("Upgrade handler error: {}", e);
-            }
-        }
-    }) as Box<dyn FnMut(_)>);
-    open_req.set_onupgradeneeded(Some(upgrade_closure.as_ref().unchecked_ref()));
-    upgrade_closure.forget();
-
-    // Wait for database to open
-    let (open_tx, open_rx) = oneshot::channel();
-    let open_tx = std::rc::Rc::new(std::cell::RefCell::new(Some(open_tx)));
-
-    let success_closure = {
-        let open_tx = open_tx.clone();
-        Closure::wrap(Box::new(move |event: web_sys::Event| {
-            log::info!("IndexedDB open success handler called");
-            if let Some(sender) = open_tx.borrow_mut().take() {
-                log::debug!("Got sender from RefCell");
-                let target = event.target().unwrap();
-                log::debug!("Got event target");
-                let request: web_sys::IdbOpenDbRequest = target.dyn_into().unwrap();
-                log::debug!("Cast to IdbOpenDbRequest");
-                let result = request.result().unwrap();
-                log::debug!("Got result from request");
-                let db: web_sys::IdbDatabase = result.dyn_into().unwrap();
-                log::debug!("Cast result to IdbDatabase");
-                log::debug!("Sending database to channel");
-                let send_result = sender.send(Ok(db));
-                log::debug!("Channel send result: {:?}", send_result.is_ok());
-            } else {
-                log::debug!("No sender available in RefCell");
-            }
-        }) as Box<dyn FnMut(_)>)
-    };
-
-    let error_closure = {
-        let open_tx = open_tx.clone();
-        Closure::wrap(Box::new(move |_event: web_sys::Event| {
-            log::error!("IndexedDB open error handler called");
-            if let Some(sender) = open_tx.borrow_mut().take() {
-                let _ = sender.send(Err("Failed to open IndexedDB".to_string()));
-            }
-        }) as Box<dyn FnMut(_)>)
-    };
-
-    open_req.set_onsuccess(Some(success_closure.as_ref().unchecked_ref()));
-    open_req.set_onerror(Some(error_closure.as_ref().unchecked_ref()));
-    success_closure.forget();
-    error_closure.forget();
-
-    log::debug!("About to await open_rx channel");
-    let db = match open_rx.await {
-        Ok(Ok(db)) => {
-            log::info!("Successfully received database from channel");
-            db
-        }
-        Ok(Err(e)) => {
-            log::error!("Database open error: {}", e);
-            return Err(DatabaseError::new("INDEXEDDB_ERROR", &e));
-        }
-        Err(_) => {
-            log::error!("Channel error while waiting for database");
-            return Err(DatabaseError::new("INDEXEDDB_ERROR", "Channel error"));
-        }
-    };
+    // Use shared block_storage database for all SQLite databases, borrowing an idle
+    // connection from the pool when there is one
+    let db = super::indexeddb_handles::open_pooled("block_storage", 2).await?;
 
     log::debug!("Starting IndexedDB transaction");
 
@@ -992,6 +1047,18 @@ async fn persist_to_indexeddb_event_based_internal(
         ));
     }
 
+    // Encode every block up front so an encryption failure cannot leave a partial write
+    let compression = super::compression::compression_for(db_name);
+    let cipher = super::encryption::cipher_for(db_name);
+    let mut compressed_flags = Vec::with_capacity(blocks.len());
+    let mut encoded_blocks = Vec::with_capacity(blocks.len());
+    for (block_id, block_data) in &blocks {
+        let (value, compressed) =
+            encode_block_value(compression, cipher.as_ref(), *block_id, block_data)?;
+        compressed_flags.push((*block_id, compressed));
+        encoded_blocks.push((*block_id, value));
+    }
+
     // Acquire queue slot to prevent browser-level IndexedDB contention
     super::indexeddb_queue::acquire_indexeddb_slot().await;
     log::info!("Acquired IndexedDB transaction slot");
@@ -1010,18 +1077,17 @@ async fn persist_to_indexeddb_event_based_internal(
     let store_names = js_sys::Array::new();
     store_names.push(&"blocks".into());
     store_names.push(&"metadata".into());
-    let transaction = db
-        .transaction_with_str_sequence_and_mode(
-            &store_names,
-            web_sys::IdbTransactionMode::Readwrite,
+    let durability = super::durability::durability_for(db_name);
+    let transaction = readwrite_transaction(&db, &store_names, durability).map_err(|e| {
+        DatabaseError::new(
+            "TRANSACTION_ERROR",
+            &format!("Failed to create transaction: {:?}", e),
         )
-        .map_err(|e| {
-            DatabaseError::new(
-                "TRANSACTION_ERROR",
-                &format!("Failed to create transaction: {:?}", e),
-            )
-        })?;
-    log::info!("Created IndexedDB transaction");
+    })?;
+    log::info!(
+        "Created IndexedDB transaction (durability: {})",
+        durability.as_str()
+    );
 
     let blocks_store = transaction.object_store("blocks").map_err(|e| {
         DatabaseError::new(
@@ -1038,9 +1104,8 @@ async fn persist_to_indexeddb_event_based_internal(
 
     // Store blocks with truly idempotent keys: (db_name, block_id)
     // FIX: Removed checksum from key - updates now OVERWRITE instead of creating duplicates
-    for (block_id, block_data) in &blocks {
+    for (block_id, value) in encoded_blocks {
         let key = format!("{}:{}", db_name, block_id);
-        let value = js_sys::Uint8Array::from(&block_data[..]);
         #[cfg(target_arch = "wasm32")]
         {
             log::debug!("Storing block with idempotent key: {}", key);
@@ -1066,6 +1131,11 @@ async fn persist_to_indexeddb_event_based_internal(
     let commit_value = js_sys::Number::from(commit_marker as f64);
     let _ = metadata_store.put_with_key(&commit_value, &commit_key.into());
 
+    // Record when the database last persisted, for Database.listDatabases
+    let modified_key = format!("{}:last_modified", db_name);
+    let modified_value = js_sys::Number::from(js_sys::Date::now());
+    let _ = metadata_store.put_with_key(&modified_value, &modified_key.into());
+
     // Wait for transaction to complete
     let (tx_tx, tx_rx) = oneshot::channel();
     let tx_tx = std::rc::Rc::new(std::cell::RefCell::new(Some(tx_tx)));
@@ -1079,17 +1149,20 @@ async fn persist_to_indexeddb_event_based_internal(
         }) as Box<dyn FnMut(_)>)
     };
 
+    // A quota failure aborts the transaction, which may fire `abort` without `error`
     let tx_error_closure = {
         let tx_tx = tx_tx.clone();
+        let transaction = transaction.clone();
         Closure::wrap(Box::new(move |_event: web_sys::Event| {
             if let Some(sender) = tx_tx.borrow_mut().take() {
-                let _ = sender.send(Err("Transaction failed".to_string()));
+                let _ = sender.send(Err(transaction_failure(&transaction)));
             }
         }) as Box<dyn FnMut(_)>)
     };
 
     transaction.set_oncomplete(Some(complete_closure.as_ref().unchecked_ref()));
     transaction.set_onerror(Some(tx_error_closure.as_ref().unchecked_ref()));
+    transaction.set_onabort(Some(tx_error_closure.as_ref().unchecked_ref()));
 
     // CRITICAL: Keep closures alive until await completes, then drop them
     // This allows proper cleanup of IDBDatabase reference
@@ -1098,20 +1171,30 @@ async fn persist_to_indexeddb_event_based_internal(
     let result = match tx_rx.await {
         Ok(Ok(())) => {
             log::info!("IndexedDB persistence completed successfully");
+            // Record how each block was stored now that the write is durable
+            vfs_sync::with_global_metadata(|gm| {
+                if let Some(db_meta) = gm.borrow_mut().get_mut(db_name) {
+                    for (block_id, compressed) in &compressed_flags {
+                        if let Some(meta) = db_meta.get_mut(block_id) {
+                            meta.compressed = *compressed;
+                        }
+                    }
+                }
+            });
             Ok(())
         }
-        Ok(Err(e)) => Err(DatabaseError::new("INDEXEDDB_ERROR", &e)),
+        Ok(Err(e)) => Err(e),
         Err(_) => Err(DatabaseError::new("INDEXEDDB_ERROR", "Channel error")),
     };
 
     // CRITICAL: Drop closures first to release references
+    transaction.set_onabort(None);
+    transaction.set_onerror(None);
     drop(complete_closure);
     drop(tx_error_closure);
 
-    // CRITICAL: Close the IDBDatabase connection to allow subsequent opens
-    // This MUST be done after tx_rx.await resolves (transaction complete)
-    db.close();
-    log::debug!("Closed IndexedDB connection after transaction completion");
+    // Hand the connection back to the pool now that the transaction is complete
+    drop(db);
 
     // Note: my_done_tx already sent signal right after DB opened (line 730)
     // This allows concurrent transactions while serializing DB opens
@@ -1119,10 +1202,61 @@ async fn persist_to_indexeddb_event_based_internal(
     result
 }
 
+/// Open a readwrite transaction carrying a durability hint
+///
+/// The `durability` option is not part of web-sys's stable API, so the options object is
+/// passed to `IDBDatabase.transaction` directly. Browsers without support ignore it.
+#[cfg(target_arch = "wasm32")]
+fn readwrite_transaction(
+    db: &web_sys::IdbDatabase,
+    store_names: &js_sys::Array,
+    durability: super::durability::Durability,
+) -> Result<web_sys::IdbTransaction, JsValue> {
+    use wasm_bindgen::JsCast;
+
+    if durability == super::durability::Durability::Default {
+        return db.transaction_with_str_sequence_and_mode(
+            store_names,
+            web_sys::IdbTransactionMode::Readwrite,
+        );
+    }
+    let options = js_sys::Object::new();
+    js_sys::Reflect::set(
+        &options,
+        &JsValue::from_str("durability"),
+        &JsValue::from_str(durability.as_str()),
+    )?;
+    let transaction: js_sys::Function =
+        js_sys::Reflect::get(db, &JsValue::from_str("transaction"))?.dyn_into()?;
+    transaction
+        .call3(db, store_names, &JsValue::from_str("readwrite"), &options)?
+        .dyn_into()
+}
+
+/// Map a failed or aborted IndexedDB transaction to a `DatabaseError`
+///
+/// Running out of storage quota is reported as `STORAGE_QUOTA_EXCEEDED` so callers can
+/// tell it apart from transient failures; everything else is an `INDEXEDDB_ERROR`.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn transaction_failure(transaction: &web_sys::IdbTransaction) -> DatabaseError {
+    match transaction.error() {
+        Some(e) if e.name() == "QuotaExceededError" => DatabaseError::new(
+            "STORAGE_QUOTA_EXCEEDED",
+            &format!("IndexedDB storage quota exceeded: {}", e.message()),
+        ),
+        Some(e) => DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            &format!("Transaction failed: {}: {}", e.name(), e.message()),
+        ),
+        None => DatabaseError::new("INDEXEDDB_ERROR", "Transaction failed"),
+    }
+}
+
 /// Async version of sync for WASM that properly awaits IndexedDB persistence
 #[cfg(target_arch = "wasm32")]
 pub async fn sync_async(storage: &BlockStorage) -> Result<(), DatabaseError> {
     log::debug!("Using ASYNC sync_async method");
+    storage.ensure_checksums_before_sync();
     // Get current commit marker
     let current_commit = vfs_sync::with_global_commit_marker(|cm| {
         let cm = cm;
@@ -1221,12 +1355,12 @@ pub async fn sync_async(storage: &BlockStorage) -> Result<(), DatabaseError> {
         for (block_id, version) in &metadata_to_persist {
             db_metadata.insert(
                 *block_id,
-                BlockMetadataPersist {
-                    version: *version as u32,
-                    checksum: 0,
-                    algo: ChecksumAlgorithm::FastHash,
-                    last_modified_ms: js_sys::Date::now() as u64,
-                },
+                BlockMetadataPersist::new(
+                    0,
+                    *version as u32,
+                    js_sys::Date::now() as u64,
+                    ChecksumAlgorithm::FastHash,
+                ),
             );
         }
     });
@@ -1246,6 +1380,7 @@ pub async fn sync_async(storage: &BlockStorage) -> Result<(), DatabaseError> {
             "Awaiting IndexedDB persistence for {} blocks",
             to_persist.len()
         );
+        let written = storage.verify_after_sync().then(|| to_persist.clone());
         persist_to_indexeddb_event_based(
             &storage.db_name,
             to_persist,
@@ -1257,6 +1392,9 @@ pub async fn sync_async(storage: &BlockStorage) -> Result<(), DatabaseError> {
             None,
         )
         .await?;
+        if let Some(written) = written {
+            verify_persisted_blocks(&storage.db_name, &written).await?;
+        }
     }
 
     // Clear dirty blocks
@@ -1330,80 +1468,7 @@ pub async fn delete_blocks_from_indexeddb(
         db_name
     );
 
-    let open_req = open_indexeddb("block_storage", 2)?;
-
-    // Set up upgrade handler (should not be needed, but included for safety)
-    let upgrade_closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
-        log::debug!("IndexedDB upgrade handler called during delete");
-
-        match (|| -> Result<(), Box<dyn std::error::Error>> {
-            let target = event.target().ok_or("No event target")?;
-            let request: web_sys::IdbOpenDbRequest = target
-                .dyn_into()
-                .map_err(|_| "Failed to cast to IdbOpenDbRequest")?;
-            let result = request
-                .result()
-                .map_err(|_| "Failed to get result from request")?;
-            let db: web_sys::IdbDatabase = result
-                .dyn_into()
-                .map_err(|_| "Failed to cast result to IdbDatabase")?;
-
-            if !db.object_store_names().contains("blocks") {
-                db.create_object_store("blocks")
-                    .map_err(|_| "Failed to create blocks store")?;
-            }
-            if !db.object_store_names().contains("metadata") {
-                db.create_object_store("metadata")
-                    .map_err(|_| "Failed to create metadata store")?;
-            }
-
-            Ok(())
-        })() {
-            Ok(_) => {}
-            Err(e) => {
-                log::error!("Upgrade handler error: {}", e);
-            }
-        }
-    }) as Box<dyn FnMut(_)>);
-    open_req.set_onupgradeneeded(Some(upgrade_closure.as_ref().unchecked_ref()));
-    upgrade_closure.forget();
-
-    // Wait for database to open
-    let (open_tx, open_rx) = oneshot::channel();
-    let open_tx = std::rc::Rc::new(std::cell::RefCell::new(Some(open_tx)));
-
-    let success_closure = {
-        let open_tx = open_tx.clone();
-        Closure::wrap(Box::new(move |event: web_sys::Event| {
-            if let Some(sender) = open_tx.borrow_mut().take() {
-                let target = event.target().unwrap();
-                let request: web_sys::IdbOpenDbRequest = target.dyn_into().unwrap();
-                let result = request.result().unwrap();
-                let db: web_sys::IdbDatabase = result.dyn_into().unwrap();
-                let _ = sender.send(Ok(db));
-            }
-        }) as Box<dyn FnMut(_)>)
-    };
-
-    let error_closure = {
-        let open_tx = open_tx.clone();
-        Closure::wrap(Box::new(move |_event: web_sys::Event| {
-            if let Some(sender) = open_tx.borrow_mut().take() {
-                let _ = sender.send(Err("Failed to open IndexedDB".to_string()));
-            }
-        }) as Box<dyn FnMut(_)>)
-    };
-
-    open_req.set_onsuccess(Some(success_closure.as_ref().unchecked_ref()));
-    open_req.set_onerror(Some(error_closure.as_ref().unchecked_ref()));
-    success_closure.forget();
-    error_closure.forget();
-
-    let db = match open_rx.await {
-        Ok(Ok(db)) => db,
-        Ok(Err(e)) => return Err(DatabaseError::new("INDEXEDDB_ERROR", &e)),
-        Err(_) => return Err(DatabaseError::new("INDEXEDDB_ERROR", "Channel error")),
-    };
+    let db = super::indexeddb_handles::open_pooled("block_storage", 2).await?;
 
     // Acquire queue slot to prevent browser-level IndexedDB contention
     super::indexeddb_queue::acquire_indexeddb_slot().await;
@@ -1505,6 +1570,599 @@ pub async fn delete_blocks_from_indexeddb(
     }
 }
 
+/// Read the last synced copy of one block from IndexedDB
+///
+/// Returns the block bytes when IndexedDB holds a copy written at or before the persisted
+/// commit marker, and None when there is no such copy (or no IndexedDB stores at all).
+#[cfg(target_arch = "wasm32")]
+pub async fn read_committed_block_from_indexeddb(
+    db_name: &str,
+    block_id: u64,
+) -> Result<Option<Vec<u8>>, DatabaseError> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::closure::Closure;
+
+    let db = super::indexeddb_handles::open_pooled("block_storage", 2).await?;
+
+    let store_names = js_sys::Array::new();
+    store_names.push(&"blocks".into());
+    store_names.push(&"metadata".into());
+    let Ok(transaction) = db.transaction_with_str_sequence(&store_names) else {
+        // A database that never synced has no stores to read from
+        return Ok(None);
+    };
+    let (blocks_store, metadata_store) = match (
+        transaction.object_store("blocks"),
+        transaction.object_store("metadata"),
+    ) {
+        (Ok(blocks), Ok(metadata)) => (blocks, metadata),
+        _ => {
+            return Err(DatabaseError::new(
+                "INDEXEDDB_ERROR",
+                "Failed to get blocks or metadata store",
+            ));
+        }
+    };
+
+    let key = JsValue::from_str(&format!("{}:{}", db_name, block_id));
+    let commit_key = JsValue::from_str(&format!("{}:commit_marker", db_name));
+    let requests = (
+        blocks_store.get(&key),
+        metadata_store.get(&key),
+        metadata_store.get(&commit_key),
+    );
+    let (Ok(block_req), Ok(version_req), Ok(marker_req)) = requests else {
+        return Err(DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            &format!("Failed to request block {} from IndexedDB", block_id),
+        ));
+    };
+
+    let (tx_tx, tx_rx) = oneshot::channel();
+    let tx_tx = std::rc::Rc::new(RefCell::new(Some(tx_tx)));
+    let complete_closure = {
+        let tx_tx = tx_tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx_tx.borrow_mut().take() {
+                let _ = sender.send(true);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    let tx_error_closure = {
+        let tx_tx = tx_tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx_tx.borrow_mut().take() {
+                let _ = sender.send(false);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    transaction.set_oncomplete(Some(complete_closure.as_ref().unchecked_ref()));
+    transaction.set_onerror(Some(tx_error_closure.as_ref().unchecked_ref()));
+    complete_closure.forget();
+    tx_error_closure.forget();
+
+    let completed = tx_rx.await.unwrap_or(false);
+    if !completed {
+        return Err(DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            &format!("Reading block {} from IndexedDB failed", block_id),
+        ));
+    }
+
+    let version = version_req.result().ok().and_then(|v| v.as_f64());
+    let marker = marker_req.result().ok().and_then(|v| v.as_f64());
+    let committed = match (version, marker) {
+        (Some(version), Some(marker)) => version <= marker,
+        // Older databases did not record per-block versions
+        (None, Some(_)) => true,
+        _ => false,
+    };
+    if !committed {
+        return Ok(None);
+    }
+
+    let cipher = super::encryption::cipher_for(db_name);
+    match block_req
+        .result()
+        .ok()
+        .and_then(|value| decode_block_value(value, cipher.as_ref(), block_id))
+    {
+        Some(Ok((data, _))) => Ok(Some(data)),
+        Some(Err(e)) => Err(e),
+        None => Ok(None),
+    }
+}
+
+/// Read back blocks a sync just persisted and compare their checksums
+///
+/// Uses a fresh read transaction, so a write IndexedDB reported as complete but did not
+/// store fails with `SYNC_VERIFICATION_FAILED` naming the first missing or mismatched block.
+#[cfg(target_arch = "wasm32")]
+pub async fn verify_persisted_blocks(
+    db_name: &str,
+    blocks: &[(u64, Vec<u8>)],
+) -> Result<(), DatabaseError> {
+    use super::metadata::ChecksumManager;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::closure::Closure;
+
+    if blocks.is_empty() {
+        return Ok(());
+    }
+
+    let db = super::indexeddb_handles::open_pooled("block_storage", 2).await?;
+    let transaction = db.transaction_with_str("blocks").map_err(|e| {
+        DatabaseError::new(
+            "TRANSACTION_ERROR",
+            &format!("Failed to create verification transaction: {:?}", e),
+        )
+    })?;
+    let blocks_store = transaction.object_store("blocks").map_err(|e| {
+        DatabaseError::new(
+            "STORE_ERROR",
+            &format!("Failed to access blocks store: {:?}", e),
+        )
+    })?;
+    let mut requests = Vec::with_capacity(blocks.len());
+    for (block_id, _) in blocks {
+        let key = JsValue::from_str(&format!("{}:{}", db_name, block_id));
+        let request = blocks_store.get(&key).map_err(|_| {
+            DatabaseError::new(
+                "INDEXEDDB_ERROR",
+                &format!("Failed to request block {} from IndexedDB", block_id),
+            )
+        })?;
+        requests.push(request);
+    }
+
+    let (tx_tx, tx_rx) = oneshot::channel();
+    let tx_tx = std::rc::Rc::new(RefCell::new(Some(tx_tx)));
+    let complete_closure = {
+        let tx_tx = tx_tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx_tx.borrow_mut().take() {
+                let _ = sender.send(true);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    let tx_error_closure = {
+        let tx_tx = tx_tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx_tx.borrow_mut().take() {
+                let _ = sender.send(false);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    transaction.set_oncomplete(Some(complete_closure.as_ref().unchecked_ref()));
+    transaction.set_onerror(Some(tx_error_closure.as_ref().unchecked_ref()));
+    complete_closure.forget();
+    tx_error_closure.forget();
+
+    if !tx_rx.await.unwrap_or(false) {
+        return Err(DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            "Reading synced blocks back from IndexedDB failed",
+        ));
+    }
+
+    let cipher = super::encryption::cipher_for(db_name);
+    for ((block_id, expected), request) in blocks.iter().zip(requests) {
+        let stored = request
+            .result()
+            .ok()
+            .and_then(|value| decode_block_value(value, cipher.as_ref(), *block_id))
+            .transpose()?;
+        let Some((data, _)) = stored else {
+            return Err(DatabaseError::new(
+                "SYNC_VERIFICATION_FAILED",
+                &format!("Block {} is missing from IndexedDB after sync", block_id),
+            ));
+        };
+        let expected_checksum =
+            ChecksumManager::compute_checksum_with(expected, ChecksumAlgorithm::FastHash);
+        let stored_checksum =
+            ChecksumManager::compute_checksum_with(&data, ChecksumAlgorithm::FastHash);
+        if stored_checksum != expected_checksum {
+            return Err(DatabaseError::new(
+                "SYNC_VERIFICATION_FAILED",
+                &format!(
+                    "Block {} read back from IndexedDB does not match what was written (checksum {} != {})",
+                    block_id, stored_checksum, expected_checksum
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Read the commit marker persisted in IndexedDB for a database
+///
+/// Uses a fresh connection and read transaction, so the value is what IndexedDB actually
+/// stored rather than this tab's in-memory copy. None when no marker was ever persisted.
+#[cfg(target_arch = "wasm32")]
+pub async fn read_persisted_commit_marker(db_name: &str) -> Result<Option<u64>, DatabaseError> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::closure::Closure;
+
+    let db = super::indexeddb_handles::open_pooled("block_storage", 2).await?;
+
+    let Ok(transaction) = db.transaction_with_str("metadata") else {
+        // A database that never synced has no stores to read from
+        return Ok(None);
+    };
+    let marker_req = transaction
+        .object_store("metadata")
+        .and_then(|store| store.get(&JsValue::from_str(&format!("{}:commit_marker", db_name))));
+    let Ok(marker_req) = marker_req else {
+        return Err(DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            "Failed to request the commit marker from IndexedDB",
+        ));
+    };
+
+    let (tx_tx, tx_rx) = oneshot::channel();
+    let tx_tx = std::rc::Rc::new(RefCell::new(Some(tx_tx)));
+    let complete_closure = {
+        let tx_tx = tx_tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx_tx.borrow_mut().take() {
+                let _ = sender.send(true);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    let tx_error_closure = {
+        let tx_tx = tx_tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx_tx.borrow_mut().take() {
+                let _ = sender.send(false);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    transaction.set_oncomplete(Some(complete_closure.as_ref().unchecked_ref()));
+    transaction.set_onerror(Some(tx_error_closure.as_ref().unchecked_ref()));
+    complete_closure.forget();
+    tx_error_closure.forget();
+
+    let completed = tx_rx.await.unwrap_or(false);
+    if !completed {
+        return Err(DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            "Reading the commit marker from IndexedDB failed",
+        ));
+    }
+    Ok(marker_req
+        .result()
+        .ok()
+        .and_then(|v| v.as_f64())
+        .map(|marker| marker as u64))
+}
+
+/// Assemble the database file as of the persisted commit marker
+///
+/// Blocks, their versions and the commit marker are read in one read transaction, so a
+/// sync landing meanwhile cannot mix two states. Writes that were never synced are not
+/// included. Fails with `SNAPSHOT_UNAVAILABLE` when the database never synced or a block
+/// was overwritten past the marker.
+#[cfg(target_arch = "wasm32")]
+pub async fn read_committed_database_from_indexeddb(
+    db_name: &str,
+    block_size: usize,
+) -> Result<Vec<u8>, DatabaseError> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::closure::Closure;
+
+    let unavailable = || {
+        DatabaseError::new(
+            "SNAPSHOT_UNAVAILABLE",
+            &format!("{} has not been synced to IndexedDB yet", db_name),
+        )
+    };
+
+    let db = super::indexeddb_handles::open_pooled("block_storage", 2).await?;
+    let store_names = js_sys::Array::new();
+    store_names.push(&"blocks".into());
+    store_names.push(&"metadata".into());
+    let Ok(transaction) = db.transaction_with_str_sequence(&store_names) else {
+        return Err(unavailable());
+    };
+    let (blocks_store, metadata_store) = match (
+        transaction.object_store("blocks"),
+        transaction.object_store("metadata"),
+    ) {
+        (Ok(blocks), Ok(metadata)) => (blocks, metadata),
+        _ => {
+            return Err(DatabaseError::new(
+                "INDEXEDDB_ERROR",
+                "Failed to get blocks or metadata store",
+            ));
+        }
+    };
+
+    let key_range = web_sys::IdbKeyRange::bound(
+        &JsValue::from_str(&format!("{}:", db_name)),
+        &JsValue::from_str(&format!("{}:\u{FFFF}", db_name)),
+    )
+    .map_err(|_| DatabaseError::new("INDEXEDDB_ERROR", "Failed to create key range"))?;
+    let commit_key = JsValue::from_str(&format!("{}:commit_marker", db_name));
+    let requests = (
+        blocks_store.get_all_keys_with_key(&key_range),
+        blocks_store.get_all_with_key(&key_range),
+        metadata_store.get_all_keys_with_key(&key_range),
+        metadata_store.get_all_with_key(&key_range),
+        metadata_store.get(&commit_key),
+    );
+    let (
+        Ok(block_keys_req),
+        Ok(blocks_req),
+        Ok(version_keys_req),
+        Ok(versions_req),
+        Ok(marker_req),
+    ) = requests
+    else {
+        return Err(DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            &format!("Failed to request blocks of {} from IndexedDB", db_name),
+        ));
+    };
+
+    let (tx_tx, tx_rx) = oneshot::channel();
+    let tx_tx = std::rc::Rc::new(RefCell::new(Some(tx_tx)));
+    let complete_closure = {
+        let tx_tx = tx_tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx_tx.borrow_mut().take() {
+                let _ = sender.send(true);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    let tx_error_closure = {
+        let tx_tx = tx_tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx_tx.borrow_mut().take() {
+                let _ = sender.send(false);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    transaction.set_oncomplete(Some(complete_closure.as_ref().unchecked_ref()));
+    transaction.set_onerror(Some(tx_error_closure.as_ref().unchecked_ref()));
+    complete_closure.forget();
+    tx_error_closure.forget();
+
+    if !tx_rx.await.unwrap_or(false) {
+        return Err(DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            &format!("Reading blocks of {} from IndexedDB failed", db_name),
+        ));
+    }
+
+    let Some(marker) = marker_req.result().ok().and_then(|v| v.as_f64()) else {
+        return Err(unavailable());
+    };
+    let array = |req: &web_sys::IdbRequest| -> js_sys::Array {
+        req.result()
+            .map(|v| v.unchecked_into())
+            .unwrap_or_else(|_| js_sys::Array::new())
+    };
+    let block_id_of =
+        |key: JsValue| -> Option<u64> { key.as_string()?.rsplit_once(':')?.1.parse::<u64>().ok() };
+
+    let mut versions = HashMap::new();
+    for (key, version) in array(&version_keys_req)
+        .iter()
+        .zip(array(&versions_req).iter())
+    {
+        if let (Some(block_id), Some(version)) = (block_id_of(key), version.as_f64()) {
+            versions.insert(block_id, version);
+        }
+    }
+
+    let cipher = super::encryption::cipher_for(db_name);
+    let mut blocks = std::collections::BTreeMap::new();
+    for (key, value) in array(&block_keys_req).iter().zip(array(&blocks_req).iter()) {
+        let Some(block_id) = block_id_of(key) else {
+            continue;
+        };
+        if versions
+            .get(&block_id)
+            .is_some_and(|version| *version > marker)
+        {
+            return Err(DatabaseError::new(
+                "SNAPSHOT_UNAVAILABLE",
+                &format!(
+                    "Block {} of {} was overwritten after commit {}",
+                    block_id, db_name, marker
+                ),
+            ));
+        }
+        if let Some(decoded) = decode_block_value(value, cipher.as_ref(), block_id) {
+            blocks.insert(block_id, decoded?.0);
+        }
+    }
+
+    let Some(last_block) = blocks.keys().next_back().copied() else {
+        return Err(unavailable());
+    };
+    let mut file = vec![0u8; (last_block as usize + 1) * block_size];
+    for (block_id, data) in blocks {
+        let offset = block_id as usize * block_size;
+        let len = data.len().min(block_size);
+        file[offset..offset + len].copy_from_slice(&data[..len]);
+    }
+    // Blocks past the last page are leftovers of a larger file
+    if let Ok((page_size, page_count)) = super::export::parse_sqlite_header(&file) {
+        let size = page_size * page_count as usize;
+        if size > 0 && size <= file.len() {
+            file.truncate(size);
+        }
+    }
+    Ok(file)
+}
+
+/// Enumerate the databases persisted in IndexedDB
+///
+/// Reads the metadata store keys (`{db_name}:{block_id}`, `{db_name}:commit_marker` and
+/// `{db_name}:last_modified`) in one read transaction. Sizes are logical: block count
+/// times `BLOCK_SIZE`. Results are sorted by name; empty when nothing was ever persisted.
+#[cfg(target_arch = "wasm32")]
+pub async fn list_persisted_databases() -> Result<Vec<crate::types::DatabaseInfo>, DatabaseError> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::closure::Closure;
+
+    let db = super::indexeddb_handles::open_pooled("block_storage", 2).await?;
+
+    let Ok(transaction) = db.transaction_with_str("metadata") else {
+        // No stores yet: nothing has been persisted
+        return Ok(Vec::new());
+    };
+    let store = transaction.object_store("metadata").map_err(|e| {
+        DatabaseError::new(
+            "STORE_ERROR",
+            &format!("Failed to access metadata store: {:?}", e),
+        )
+    })?;
+    let keys_req = store.get_all_keys().map_err(|_| {
+        DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            "Failed to request metadata keys from IndexedDB",
+        )
+    })?;
+    let values_req = store.get_all().map_err(|_| {
+        DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            "Failed to request metadata values from IndexedDB",
+        )
+    })?;
+
+    let (tx_tx, tx_rx) = oneshot::channel();
+    let tx_tx = std::rc::Rc::new(RefCell::new(Some(tx_tx)));
+    let complete_closure = {
+        let tx_tx = tx_tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx_tx.borrow_mut().take() {
+                let _ = sender.send(true);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    let tx_error_closure = {
+        let tx_tx = tx_tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx_tx.borrow_mut().take() {
+                let _ = sender.send(false);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    transaction.set_oncomplete(Some(complete_closure.as_ref().unchecked_ref()));
+    transaction.set_onerror(Some(tx_error_closure.as_ref().unchecked_ref()));
+    complete_closure.forget();
+    tx_error_closure.forget();
+
+    if !tx_rx.await.unwrap_or(false) {
+        return Err(DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            "Reading database metadata from IndexedDB failed",
+        ));
+    }
+
+    let keys: js_sys::Array = keys_req
+        .result()
+        .map(|v| v.unchecked_into())
+        .unwrap_or_else(|_| js_sys::Array::new());
+    let values: js_sys::Array = values_req
+        .result()
+        .map(|v| v.unchecked_into())
+        .unwrap_or_else(|_| js_sys::Array::new());
+
+    let mut databases: std::collections::BTreeMap<String, crate::types::DatabaseInfo> =
+        std::collections::BTreeMap::new();
+    for (key, value) in keys.iter().zip(values.iter()) {
+        let Some(key) = key.as_string() else {
+            continue;
+        };
+        // Database names may contain ':', the suffix never does
+        let Some((name, suffix)) = key.rsplit_once(':') else {
+            continue;
+        };
+        let info =
+            databases
+                .entry(name.to_string())
+                .or_insert_with(|| crate::types::DatabaseInfo {
+                    name: name.to_string(),
+                    size_bytes: 0,
+                    block_count: 0,
+                    last_modified: None,
+                });
+        if suffix.parse::<u64>().is_ok() {
+            info.block_count += 1;
+            info.size_bytes += super::block_storage::BLOCK_SIZE as u64;
+        } else if suffix == "last_modified" {
+            info.last_modified = value.as_f64();
+        }
+    }
+    Ok(databases.into_values().collect())
+}
+
+/// Delete a whole IndexedDB database by name and wait for the deletion to finish
+///
+/// Succeeds when IndexedDB is unavailable (nothing to delete). A deletion blocked by
+/// connections open in other tabs completes once they close.
+#[cfg(target_arch = "wasm32")]
+pub async fn delete_indexeddb_database(idb_name: &str) -> Result<(), DatabaseError> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::closure::Closure;
+
+    let indexed_db = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("indexedDB"))
+        .map_err(|e| {
+            DatabaseError::new(
+                "INDEXEDDB_ERROR",
+                &format!("IndexedDB access failed: {:?}", e),
+            )
+        })?;
+    if indexed_db.is_null() || indexed_db.is_undefined() {
+        return Ok(());
+    }
+    let factory: web_sys::IdbFactory = indexed_db
+        .dyn_into()
+        .map_err(|_| DatabaseError::new("INDEXEDDB_ERROR", "indexedDB is not an IdbFactory"))?;
+    let request = factory.delete_database(idb_name).map_err(|e| {
+        DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            &format!("Failed to delete IndexedDB {}: {:?}", idb_name, e),
+        )
+    })?;
+
+    let (tx, rx) = oneshot::channel();
+    let tx = std::rc::Rc::new(RefCell::new(Some(tx)));
+    let success_closure = {
+        let tx = tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx.borrow_mut().take() {
+                let _ = sender.send(true);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    let error_closure = {
+        let tx = tx.clone();
+        Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            if let Some(sender) = tx.borrow_mut().take() {
+                let _ = sender.send(false);
+            }
+        }) as Box<dyn FnMut(_)>)
+    };
+    request.set_onsuccess(Some(success_closure.as_ref().unchecked_ref()));
+    request.set_onerror(Some(error_closure.as_ref().unchecked_ref()));
+
+    let deleted = rx.await.unwrap_or(false);
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    if !deleted {
+        return Err(DatabaseError::new(
+            "INDEXEDDB_ERROR",
+            &format!("Deleting IndexedDB {} failed", idb_name),
+        ));
+    }
+    Ok(())
+}
+
 /// Delete ALL blocks and metadata for a database from IndexedDB
 ///
 /// Unlike `delete_blocks_from_indexeddb`, this function does NOT require knowing
@@ -1540,77 +2198,7 @@ pub async fn delete_all_database_blocks_from_indexeddb(db_name: &str) -> Result<
         .into(),
     );
 
-    let open_req = open_indexeddb("block_storage", 2)?;
-
-    // Set up upgrade handler (should not be needed, but included for safety)
-    let upgrade_closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
-        match (|| -> Result<(), Box<dyn std::error::Error>> {
-            let target = event.target().ok_or("No event target")?;
-            let request: web_sys::IdbOpenDbRequest =
-                target.dyn_into().map_err(|_| "Cast failed")?;
-            let result = request.result().map_err(|_| "No result")?;
-            let db: web_sys::IdbDatabase = result
-                .dyn_into()
-                .map_err(|_| "Cast to IdbDatabase failed")?;
-
-            if !db.object_store_names().contains("blocks") {
-                db.create_object_store("blocks")
-                    .map_err(|_| "Create blocks store failed")?;
-            }
-            if !db.object_store_names().contains("metadata") {
-                db.create_object_store("metadata")
-                    .map_err(|_| "Create metadata store failed")?;
-            }
-            Ok(())
-        })() {
-            Ok(_) => {}
-            Err(e) => log::error!("[DELETE_ALL] Upgrade handler error: {}", e),
-        }
-    }) as Box<dyn FnMut(_)>);
-    open_req.set_onupgradeneeded(Some(upgrade_closure.as_ref().unchecked_ref()));
-    upgrade_closure.forget();
-
-    // Wait for database to open
-    let (open_tx, open_rx) = oneshot::channel();
-    let open_tx = std::rc::Rc::new(std::cell::RefCell::new(Some(open_tx)));
-
-    let success_closure = {
-        let open_tx = open_tx.clone();
-        Closure::wrap(Box::new(move |event: web_sys::Event| {
-            if let Some(sender) = open_tx.borrow_mut().take() {
-                let target = event.target().unwrap();
-                let request: web_sys::IdbOpenDbRequest = target.dyn_into().unwrap();
-                let result = request.result().unwrap();
-                let db: web_sys::IdbDatabase = result.dyn_into().unwrap();
-                let _ = sender.send(Ok(db));
-            }
-        }) as Box<dyn FnMut(_)>)
-    };
-
-    let error_closure = {
-        let open_tx = open_tx.clone();
-        Closure::wrap(Box::new(move |_event: web_sys::Event| {
-            if let Some(sender) = open_tx.borrow_mut().take() {
-                let _ = sender.send(Err("Failed to open IndexedDB".to_string()));
-            }
-        }) as Box<dyn FnMut(_)>)
-    };
-
-    open_req.set_onsuccess(Some(success_closure.as_ref().unchecked_ref()));
-    open_req.set_onerror(Some(error_closure.as_ref().unchecked_ref()));
-    success_closure.forget();
-    error_closure.forget();
-
-    let db = match open_rx.await {
-        Ok(Ok(db)) => db,
-        Ok(Err(e)) => return Err(DatabaseError::new("INDEXEDDB_ERROR", &e)),
-        Err(_) => {
-            return Err(DatabaseError::new(
-                "INDEXEDDB_ERROR",
-                "Channel error opening DB",
-            ));
-        }
-    };
+    let db = super::indexeddb_handles::open_pooled("block_storage", 2).await?;
 
     // Acquire queue slot to prevent browser-level IndexedDB contention
     super::indexeddb_queue::acquire_indexeddb_slot().await;
@@ -1,13 +1,15 @@
 //! Write Queue System for Non-Leader Tabs
 //!
 //! Allows non-leader tabs to queue writes that get forwarded to the leader tab
-//! for execution. Uses BroadcastChannel for communication and receives acknowledgments.
+//! for execution. Uses BroadcastChannel (or the localStorage fallback in
+//! `broadcast_notifications`) for communication and receives acknowledgments.
 
+#[cfg(target_arch = "wasm32")]
+use super::broadcast_notifications::{listen_on_channel, post_to_channel};
 use crate::types::DatabaseError;
 use serde::{Deserialize, Serialize};
-use wasm_bindgen::JsCast;
+use std::collections::HashSet;
 use wasm_bindgen::prelude::*;
-use web_sys::BroadcastChannel;
 
 /// A queued write request
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +50,68 @@ pub enum WriteQueueMessage {
     WriteResponse(WriteResponse),
 }
 
+/// Forwarded writes still waiting for the leader's response
+///
+/// Bounds how many requests a follower can have in flight so a burst of writes fails
+/// fast with `WRITE_QUEUE_FULL` instead of piling up response listeners and timers.
+#[derive(Debug)]
+pub struct PendingWrites {
+    request_ids: HashSet<String>,
+    max_depth: Option<usize>,
+}
+
+impl PendingWrites {
+    /// Create a tracker; `None` means no limit
+    pub fn new(max_depth: Option<usize>) -> Self {
+        Self {
+            request_ids: HashSet::new(),
+            max_depth,
+        }
+    }
+
+    /// Fail with `WRITE_QUEUE_FULL` when no more requests can be queued
+    pub fn ensure_capacity(&self) -> Result<(), DatabaseError> {
+        if let Some(max_depth) = self.max_depth {
+            if self.request_ids.len() >= max_depth {
+                return Err(DatabaseError::new(
+                    "WRITE_QUEUE_FULL",
+                    &format!(
+                        "{} queued writes are still waiting for the leader (limit {}). Wait for them or call flushWriteQueue() before queuing more.",
+                        self.request_ids.len(),
+                        max_depth
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Track a request that was sent to the leader
+    pub fn insert(&mut self, request_id: &str) {
+        self.request_ids.insert(request_id.to_string());
+    }
+
+    /// Stop tracking a request once it was answered or timed out
+    pub fn complete(&mut self, request_id: &str) -> bool {
+        self.request_ids.remove(request_id)
+    }
+
+    /// Number of requests awaiting a response
+    pub fn depth(&self) -> usize {
+        self.request_ids.len()
+    }
+}
+
+/// Generate a request ID that is unique even for writes queued in the same millisecond
+#[cfg(target_arch = "wasm32")]
+fn next_request_id() -> String {
+    thread_local! {
+        static NEXT_SEQ: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+    }
+    let seq = NEXT_SEQ.with(|next| next.replace(next.get() + 1));
+    format!("req_{}_{}", js_sys::Date::now() as u64, seq)
+}
+
 /// Send a write request to the leader
 ///
 /// # Arguments
@@ -61,7 +125,7 @@ pub fn send_write_request(db_name: &str, sql: &str) -> Result<String, DatabaseEr
     let channel_name = format!("datasync_writequeue_{}", db_name);
 
     // Generate unique request ID
-    let request_id = format!("req_{}", js_sys::Date::now() as u64);
+    let request_id = next_request_id();
 
     let request = WriteRequest {
         request_id: request_id.clone(),
@@ -72,14 +136,6 @@ pub fn send_write_request(db_name: &str, sql: &str) -> Result<String, DatabaseEr
 
     let message = WriteQueueMessage::WriteRequest(request);
 
-    // Create BroadcastChannel
-    let channel = BroadcastChannel::new(&channel_name).map_err(|e| {
-        DatabaseError::new(
-            "BROADCAST_ERROR",
-            &format!("Failed to create channel: {:?}", e),
-        )
-    })?;
-
     // Serialize and send
     let json = serde_json::to_string(&message).map_err(|e| {
         DatabaseError::new(
@@ -92,12 +148,7 @@ pub fn send_write_request(db_name: &str, sql: &str) -> Result<String, DatabaseEr
         DatabaseError::new("JSON_PARSE_ERROR", &format!("Failed to parse: {:?}", e))
     })?;
 
-    channel.post_message(&js_value).map_err(|e| {
-        DatabaseError::new(
-            "BROADCAST_ERROR",
-            &format!("Failed to post message: {:?}", e),
-        )
-    })?;
+    post_to_channel(&channel_name, &js_value)?;
 
     web_sys::console::log_1(&format!("Write request sent: {}", request_id).into());
 
@@ -115,14 +166,6 @@ pub fn send_write_response(db_name: &str, response: WriteResponse) -> Result<(),
 
     let message = WriteQueueMessage::WriteResponse(response);
 
-    // Create BroadcastChannel
-    let channel = BroadcastChannel::new(&channel_name).map_err(|e| {
-        DatabaseError::new(
-            "BROADCAST_ERROR",
-            &format!("Failed to create channel: {:?}", e),
-        )
-    })?;
-
     // Serialize and send
     let json = serde_json::to_string(&message).map_err(|e| {
         DatabaseError::new(
@@ -135,12 +178,7 @@ pub fn send_write_response(db_name: &str, response: WriteResponse) -> Result<(),
         DatabaseError::new("JSON_PARSE_ERROR", &format!("Failed to parse: {:?}", e))
     })?;
 
-    channel.post_message(&js_value).map_err(|e| {
-        DatabaseError::new(
-            "BROADCAST_ERROR",
-            &format!("Failed to post message: {:?}", e),
-        )
-    })?;
+    post_to_channel(&channel_name, &js_value)?;
 
     Ok(())
 }
@@ -157,32 +195,13 @@ pub fn register_write_queue_listener(
 ) -> Result<(), DatabaseError> {
     let channel_name = format!("datasync_writequeue_{}", db_name);
 
-    // Create BroadcastChannel
-    let channel = BroadcastChannel::new(&channel_name).map_err(|e| {
-        DatabaseError::new(
-            "BROADCAST_ERROR",
-            &format!("Failed to create channel: {:?}", e),
-        )
-    })?;
-
-    // Create closure to handle incoming messages
+    // Call the user's callback with each message
     let callback_clone = callback.clone();
-    let onmessage_closure = Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
-        let data = event.data();
-
-        // Call the user's callback with the data
+    listen_on_channel(&channel_name, move |data| {
         if let Err(e) = callback_clone.call1(&JsValue::NULL, &data) {
             web_sys::console::log_1(&format!("Write queue callback error: {:?}", e).into());
         }
-    }) as Box<dyn FnMut(web_sys::MessageEvent)>);
-
-    // Set the onmessage handler
-    channel.set_onmessage(Some(onmessage_closure.as_ref().unchecked_ref()));
-
-    // Forget the closure to keep it alive
-    onmessage_closure.forget();
-
-    Ok(())
+    })
 }
 
 // Stub implementations for native
@@ -241,4 +260,30 @@ mod tests {
         let _msg2 = WriteQueueMessage::WriteResponse(success);
         let _msg3 = WriteQueueMessage::WriteResponse(error);
     }
+
+    #[test]
+    fn test_pending_writes_bounded() {
+        let mut pending = PendingWrites::new(Some(2));
+        pending.insert("a");
+        pending.ensure_capacity().unwrap();
+        pending.insert("b");
+        let err = pending.ensure_capacity().unwrap_err();
+        assert_eq!(err.code, "WRITE_QUEUE_FULL");
+        assert_eq!(pending.depth(), 2);
+
+        assert!(pending.complete("a"));
+        assert!(!pending.complete("a"));
+        pending.ensure_capacity().unwrap();
+        assert_eq!(pending.depth(), 1);
+    }
+
+    #[test]
+    fn test_pending_writes_unbounded() {
+        let mut pending = PendingWrites::new(None);
+        for i in 0..1000 {
+            pending.ensure_capacity().unwrap();
+            pending.insert(&i.to_string());
+        }
+        assert_eq!(pending.depth(), 1000);
+    }
 }
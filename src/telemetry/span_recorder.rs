@@ -9,6 +9,7 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
 /// Represents a recorded span with its metadata
@@ -229,9 +230,14 @@ impl Default for SpanRecorder {
 /// Maintains a stack of active spans to enable automatic context propagation.
 /// When creating a new span, it can automatically use the current active span as the parent.
 /// Also manages baggage (key-value pairs) that propagate across span boundaries.
+///
+/// Scopes opened with `enter_scope` (used by `withSpan`) are tracked separately from
+/// the active span stack, so concurrently awaited queries each link to the enclosing
+/// scope rather than to whichever query happens to be on top of the stack.
 #[derive(Clone)]
 pub struct SpanContext {
     span_stack: Rc<RefCell<Vec<String>>>,
+    scope_stack: Rc<RefCell<Vec<String>>>,
     baggage: Rc<RefCell<HashMap<String, String>>>,
 }
 
@@ -240,6 +246,7 @@ impl SpanContext {
     pub fn new() -> Self {
         Self {
             span_stack: Rc::new(RefCell::new(Vec::new())),
+            scope_stack: Rc::new(RefCell::new(Vec::new())),
             baggage: Rc::new(RefCell::new(HashMap::new())),
         }
     }
@@ -256,10 +263,43 @@ impl SpanContext {
         stack.pop();
     }
 
+    /// Exit a specific span context
+    ///
+    /// Unlike `exit_span`, this removes the given span wherever it sits in the stack,
+    /// so spans that finish out of order (interleaved async queries) don't pop each other.
+    pub fn exit_span_id(&self, span_id: &str) {
+        let mut stack = self.span_stack.borrow_mut();
+        if let Some(pos) = stack.iter().rposition(|id| id == span_id) {
+            stack.remove(pos);
+        }
+    }
+
     /// Get the current active span ID
+    ///
+    /// Falls back to the innermost open scope when no span is active.
     pub fn current_span_id(&self) -> Option<String> {
         let stack = self.span_stack.borrow();
-        stack.last().cloned()
+        stack.last().cloned().or_else(|| self.current_scope_id())
+    }
+
+    /// Open a scope that child spans created through `with_scope` will link to
+    pub fn enter_scope(&self, span_id: String) {
+        let mut scopes = self.scope_stack.borrow_mut();
+        scopes.push(span_id);
+    }
+
+    /// Close a scope opened with `enter_scope`
+    pub fn exit_scope(&self, span_id: &str) {
+        let mut scopes = self.scope_stack.borrow_mut();
+        if let Some(pos) = scopes.iter().rposition(|id| id == span_id) {
+            scopes.remove(pos);
+        }
+    }
+
+    /// Get the innermost open scope ID
+    pub fn current_scope_id(&self) -> Option<String> {
+        let scopes = self.scope_stack.borrow();
+        scopes.last().cloned()
     }
 
     /// Set baggage value
@@ -296,6 +336,8 @@ impl Default for SpanContext {
     }
 }
 
+static NEXT_SPAN_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
 /// Builder for creating RecordedSpan instances
 pub struct SpanBuilder {
     name: String,
@@ -343,6 +385,17 @@ impl SpanBuilder {
         self
     }
 
+    /// Set parent from the innermost scope of the span context
+    ///
+    /// Ignores other in-flight spans, so sibling operations started inside the same
+    /// scope all share the scope as their parent.
+    pub fn with_scope(mut self, context: &SpanContext) -> Self {
+        if let Some(parent_id) = context.current_scope_id() {
+            self.parent_id = Some(parent_id);
+        }
+        self
+    }
+
     /// Copy baggage from span context
     ///
     /// This attaches all baggage from the context to the span,
@@ -353,7 +406,9 @@ impl SpanBuilder {
     }
 
     pub fn build(self) -> RecordedSpan {
-        let span_id = format!("span_{}", self.start_time_ms as u64);
+        // Spans built within the same millisecond still need distinct ids for parent linking
+        let sequence = NEXT_SPAN_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+        let span_id = format!("span_{}_{}", self.start_time_ms as u64, sequence);
 
         RecordedSpan {
             name: self.name,
@@ -440,4 +495,41 @@ mod tests {
         // Should not record when disabled
         assert_eq!(recorder.span_count(), 0);
     }
+
+    #[test]
+    fn test_span_ids_unique_within_same_millisecond() {
+        let span1 = SpanBuilder::new("a".to_string()).build();
+        let span2 = SpanBuilder::new("b".to_string()).build();
+        assert_ne!(span1.span_id, span2.span_id);
+    }
+
+    #[test]
+    fn test_scope_parents_interleaved_spans() {
+        let context = SpanContext::new();
+        let scope = SpanBuilder::new("group".to_string()).build();
+        context.enter_scope(scope.span_id.clone());
+
+        // Two spans in flight at once, as with concurrently awaited queries
+        let first = SpanBuilder::new("query".to_string())
+            .with_scope(&context)
+            .build();
+        context.enter_span(first.span_id.clone());
+        let second = SpanBuilder::new("query".to_string())
+            .with_scope(&context)
+            .build();
+        context.enter_span(second.span_id.clone());
+
+        assert_eq!(first.parent_id.as_deref(), Some(scope.span_id.as_str()));
+        assert_eq!(second.parent_id.as_deref(), Some(scope.span_id.as_str()));
+
+        // Finishing out of order leaves the other span active
+        context.exit_span_id(&first.span_id);
+        assert_eq!(context.current_span_id(), Some(second.span_id.clone()));
+        context.exit_span_id(&second.span_id);
+        assert_eq!(context.current_span_id(), Some(scope.span_id.clone()));
+
+        context.exit_scope(&scope.span_id);
+        assert_eq!(context.current_scope_id(), None);
+        assert_eq!(context.current_span_id(), None);
+    }
 }
@@ -36,6 +36,189 @@ pub struct DatabaseConfig {
     /// Rationale: Balances IndexedDB capacity (10GB+) with browser memory limits (~2-4GB/tab)
     /// Set to None for no limit (not recommended - may cause OOM errors)
     pub max_export_size_bytes: Option<u64>,
+    /// Maximum number of compiled statements cached per connection (WASM only).
+    /// Repeated identical SQL reuses the cached statement instead of re-preparing.
+    /// Default: 32. Set to Some(0) to disable caching.
+    pub statement_cache_size: Option<usize>,
+    /// Size in bytes of each storage block. Must be a power of two between 512 and 65536.
+    /// Fixed when the database is created; reopening with a different size fails with
+    /// `BLOCK_SIZE_MISMATCH`. Default: 4096
+    pub block_size: Option<usize>,
+    /// Compression applied to blocks before they are persisted to IndexedDB (WASM only).
+    /// Blocks that do not shrink are stored uncompressed. Default: None
+    pub compression: Option<crate::storage::compression::CompressionKind>,
+    /// Durability hint for the IndexedDB transactions that persist blocks (WASM only).
+    /// `Relaxed` makes syncs faster at the risk of losing the last one on a crash or power
+    /// loss; `Strict` flushes to disk first. Honored by Chromium, ignored elsewhere.
+    /// Default: None (the browser's default)
+    pub durability: Option<crate::storage::durability::Durability>,
+    /// Maximum number of prepared statements in use on a connection at once (WASM only).
+    /// Only statements mid-iteration (e.g. open cursors) count. Exceeding it fails with
+    /// `TOO_MANY_OPEN_STATEMENTS`, which usually means a cursor was never closed.
+    /// Default: 64. Set to None for no limit.
+    pub max_open_statements: Option<usize>,
+    /// Lease, heartbeat and election timeout used for multi-tab leader election (WASM only).
+    /// Default: None (5000ms lease, 1000ms heartbeat, 5000ms election timeout)
+    pub leader_election: Option<crate::storage::leader_election::LeaderElectionConfig>,
+    /// Maximum number of rows a single query may return before failing with
+    /// `RESULT_TOO_LARGE`. Guards against unbounded SELECTs exhausting tab memory;
+    /// use a streaming cursor for intentionally large reads. Default: None (no limit)
+    pub max_result_rows: Option<usize>,
+    /// Maximum number of writes a follower may have forwarded to the leader without a
+    /// response (WASM only). Further `queueWrite` calls fail with `WRITE_QUEUE_FULL`.
+    /// Default: 64. Set to None for no limit.
+    pub max_queued_writes: Option<usize>,
+    /// Checksum algorithm for newly written blocks. Existing blocks keep the algorithm
+    /// they were written with. `CRC32C` is hardware accelerated on x86_64 and aarch64.
+    /// Default: None (FastHash, or `DATASYNC_CHECKSUM_ALGO` on native)
+    pub checksum_algorithm: Option<crate::storage::metadata::ChecksumAlgorithm>,
+    /// How TEXT values that are not valid UTF-8 are returned from queries.
+    /// Default: None (`Lossy`, invalid sequences become U+FFFD)
+    pub invalid_utf8: Option<InvalidUtf8Handling>,
+    /// Retry statements that fail with `SQLITE_BUSY`/`SQLITE_LOCKED` using exponential
+    /// backoff (WASM only). When set, SQLite's blocking busy timeout is disabled so the wait
+    /// yields to the event loop. Default: None (fail on the first busy result)
+    pub busy_retry: Option<crate::storage::retry_logic::BusyRetryConfig>,
+    /// Where the database lives. `Memory` skips IndexedDB, leader election and sync
+    /// entirely (WASM only). Default: None (`IndexedDB`)
+    pub persistence: Option<Persistence>,
+    /// Row shape returned by `executeFormatted` when no format is passed (WASM only).
+    /// Default: None (`TaggedEnum`)
+    pub result_format: Option<ResultFormat>,
+    /// Largest WAL, in bytes, before writes fail with `WAL_TOO_LARGE` (WASM only). Raise it
+    /// for bulk imports; lower it when many databases share a memory-constrained tab.
+    /// Default: None (16MB)
+    pub wal_max_bytes: Option<usize>,
+    /// Fraction of `wal_max_bytes` at which SQLite runs a PASSIVE checkpoint on commit,
+    /// instead of its default 1000-page threshold (WASM only). Default: None (0.5)
+    pub wal_checkpoint_fraction: Option<f64>,
+    /// When writes are persisted to IndexedDB without an explicit `sync()` (WASM only).
+    /// Default: None (`Manual`)
+    pub sync_mode: Option<SyncMode>,
+    /// With `OnWrite`, wait this long after the last write before syncing so bursts of
+    /// writes share one sync. Default: None (sync before each write returns)
+    pub sync_debounce_ms: Option<u32>,
+    /// Abort a statement that runs longer than this with `QUERY_TIMEOUT` (WASM only), so a
+    /// runaway query cannot hang the tab. Default: None (no limit)
+    pub query_timeout_ms: Option<u32>,
+    /// Blocks kept in the LRU block cache. Raise it for read-heavy work on large
+    /// databases; lower it in memory-constrained tabs. Default: None (128 in WASM, 1000 native)
+    pub block_cache_capacity: Option<usize>,
+    /// Passphrase used to encrypt blocks with AES-256-GCM before they are persisted to
+    /// IndexedDB (WASM only; native builds use SQLCipher via `new_encrypted`). Opening an
+    /// encrypted database with a wrong or missing key fails with `DECRYPTION_FAILED`.
+    /// Unencrypted blocks still load, so setting a key on an existing database encrypts it
+    /// as blocks are rewritten. Default: None (blocks stored unencrypted)
+    pub encryption_key: Option<String>,
+    /// Coordinate writes across tabs with leader election (WASM only). Single-tab apps
+    /// can set `false` to skip leader election, the write-queue listener and the
+    /// leadership check on every write. Only safe when no other tab opens the database.
+    /// Default: None (true, or false when built with the `single-tab` feature)
+    pub multi_tab: Option<bool>,
+    /// Run a `wal_checkpoint(PASSIVE)` in the background once no write has happened for
+    /// this many milliseconds (WASM only), keeping the WAL small during quiet periods.
+    /// Never runs inside an open transaction. Default: None (no idle checkpoint)
+    pub idle_checkpoint_ms: Option<u32>,
+    /// Limit on the database's stored size in bytes (allocated blocks × block size). A
+    /// write that would grow the database past it fails with `QUOTA_EXCEEDED` before
+    /// anything is stored. Unrelated to `max_export_size_bytes`, which only guards
+    /// exports. Default: None (no limit)
+    pub max_db_bytes: Option<u64>,
+    /// Run crash recovery whenever this tab becomes leader (WASM only), rolling back or
+    /// finalizing blocks a previous leader wrote before dying mid-sync, ahead of any new
+    /// write. Default: None (true)
+    pub recover_on_leadership: Option<bool>,
+    /// Read back every block a sync persists and compare checksums before reporting
+    /// success; a mismatch fails the sync with `SYNC_VERIFICATION_FAILED` (WASM only).
+    /// Unlike `SyncPolicy::verify_after_write` this checks IndexedDB, not the cache.
+    /// Default: None (false)
+    pub verify_after_sync: Option<bool>,
+    /// Return values of columns declared `DATE`, `DATETIME` or `TIMESTAMP` as
+    /// `ColumnValue::Date`, which reach JS as `Date` objects (WASM only). Dates are
+    /// stored as INTEGER milliseconds since the Unix epoch, which is how JS `Date` and
+    /// `ColumnValue::Date` parameters are bound; ISO 8601 TEXT in such columns is
+    /// parsed as well. Default: None (false, plain values)
+    pub date_columns: Option<bool>,
+    /// Multi-tab coordination (WASM only). `Coordination::Disabled` is for apps that
+    /// are guaranteed a single tab, such as an Electron or Tauri webview: no leader
+    /// election, write-queue listener or change broadcasts, and no leadership check on
+    /// writes. Takes precedence over `multi_tab`. Default: None (decided by `multi_tab`)
+    pub coordination: Option<Coordination>,
+}
+
+/// Whether an instance coordinates with other tabs opening the same database
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Coordination {
+    /// Leader election, the write queue and change broadcasts across tabs
+    #[default]
+    MultiTab,
+    /// None of the cross-tab machinery is started; only safe with a single tab
+    Disabled,
+}
+
+/// When writes are persisted to IndexedDB
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncMode {
+    /// Only `sync()` and `close()` persist writes
+    #[default]
+    Manual,
+    /// Every successful write outside an explicit transaction (and every COMMIT) syncs
+    OnWrite,
+    /// Dirty blocks are synced when the tab is idle, hidden or unloading
+    Auto,
+}
+
+/// Shape of the rows returned by `executeFormatted`
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResultFormat {
+    /// `{ type, value }` per cell, as returned by `execute`
+    #[default]
+    TaggedEnum,
+    /// One array of plain values per row
+    ArrayOfArrays,
+    /// One object per row keyed by column name
+    ArrayOfObjects,
+}
+
+/// Storage backend for a database
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Persistence {
+    /// Blocks are persisted to IndexedDB through the IndexedDB VFS
+    #[default]
+    IndexedDB,
+    /// SQLite's built-in `memdb` VFS; contents are lost when the last connection closes
+    Memory,
+}
+
+/// Handling of TEXT column values that are not valid UTF-8
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidUtf8Handling {
+    /// Replace invalid sequences with U+FFFD
+    #[default]
+    Lossy,
+    /// Return the raw bytes as a Blob
+    Blob,
+    /// Fail the query with `INVALID_UTF8`
+    Error,
+}
+
+impl InvalidUtf8Handling {
+    /// Decode the bytes of a TEXT value into a Text (or, for `Blob`, Blob) column value
+    pub fn decode(self, bytes: &[u8]) -> Result<ColumnValue, DatabaseError> {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Ok(ColumnValue::Text(text.to_string())),
+            Err(e) => match self {
+                InvalidUtf8Handling::Lossy => Ok(ColumnValue::Text(
+                    String::from_utf8_lossy(bytes).into_owned(),
+                )),
+                InvalidUtf8Handling::Blob => Ok(ColumnValue::Blob(bytes.to_vec())),
+                InvalidUtf8Handling::Error => Err(DatabaseError::new(
+                    "INVALID_UTF8",
+                    &format!("TEXT value is not valid UTF-8: {}", e),
+                )),
+            },
+        }
+    }
 }
 
 impl Default for DatabaseConfig {
@@ -50,6 +233,33 @@ impl Default for DatabaseConfig {
             // WAL mode is fully supported - explicitly set journal_mode to enable
             journal_mode: Some("MEMORY".to_string()),
             max_export_size_bytes: Some(2 * 1024 * 1024 * 1024), // 2GB default
+            statement_cache_size: Some(32),
+            block_size: Some(4096),
+            compression: None,
+            durability: None,
+            max_open_statements: Some(64),
+            leader_election: None,
+            max_result_rows: None,
+            max_queued_writes: Some(DEFAULT_MAX_QUEUED_WRITES),
+            checksum_algorithm: None,
+            invalid_utf8: None,
+            busy_retry: None,
+            persistence: None,
+            result_format: None,
+            wal_max_bytes: None,
+            wal_checkpoint_fraction: None,
+            sync_mode: None,
+            sync_debounce_ms: None,
+            query_timeout_ms: None,
+            block_cache_capacity: None,
+            encryption_key: None,
+            multi_tab: None,
+            idle_checkpoint_ms: None,
+            max_db_bytes: None,
+            recover_on_leadership: None,
+            verify_after_sync: None,
+            date_columns: None,
+            coordination: None,
         }
     }
 }
@@ -81,9 +291,163 @@ impl DatabaseConfig {
             auto_vacuum: Some(true),
             journal_mode: Some("WAL".to_string()), // WAL for mobile performance
             max_export_size_bytes: Some(2 * 1024 * 1024 * 1024),
+            statement_cache_size: Some(32),
+            block_size: Some(4096),
+            compression: None,
+            durability: None,
+            max_open_statements: Some(64),
+            leader_election: None,
+            max_result_rows: None,
+            max_queued_writes: Some(DEFAULT_MAX_QUEUED_WRITES),
+            checksum_algorithm: None,
+            invalid_utf8: None,
+            busy_retry: None,
+            persistence: None,
+            result_format: None,
+            wal_max_bytes: None,
+            wal_checkpoint_fraction: None,
+            sync_mode: None,
+            sync_debounce_ms: None,
+            query_timeout_ms: None,
+            block_cache_capacity: None,
+            encryption_key: None,
+            multi_tab: None,
+            idle_checkpoint_ms: None,
+            max_db_bytes: None,
+            recover_on_leadership: None,
+            verify_after_sync: None,
+            date_columns: None,
+            coordination: None,
+        }
+    }
+
+    /// Check the SQLite settings, reporting every problem at once
+    ///
+    /// Fails with `CONFIG_INVALID` when `page_size` is not a power of two between 512 and
+    /// 65536, `journal_mode` is not a mode SQLite accepts, or `cache_size` is zero.
+    pub fn validate(&self) -> Result<(), DatabaseError> {
+        let mut problems = Vec::new();
+        if let Some(page_size) = self.page_size {
+            if !page_size.is_power_of_two() || !(512..=65536).contains(&page_size) {
+                problems.push(format!(
+                    "page_size {} must be a power of two between 512 and 65536",
+                    page_size
+                ));
+            }
+        }
+        if let Some(journal_mode) = &self.journal_mode {
+            if !JOURNAL_MODES.contains(&journal_mode.to_uppercase().as_str()) {
+                problems.push(format!(
+                    "journal_mode '{}' must be one of {}",
+                    journal_mode,
+                    JOURNAL_MODES.join(", ")
+                ));
+            }
+        }
+        if self.cache_size == Some(0) {
+            problems.push("cache_size must be greater than 0".to_string());
+        }
+        if self.max_db_bytes == Some(0) {
+            problems.push("max_db_bytes must be greater than 0".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(DatabaseError::new("CONFIG_INVALID", &problems.join("; ")))
         }
     }
 }
+
+/// Default maximum number of forwarded writes awaiting a leader response
+pub const DEFAULT_MAX_QUEUED_WRITES: usize = 64;
+
+/// Journal modes accepted by `PRAGMA journal_mode`
+pub const JOURNAL_MODES: &[&str] = &["DELETE", "TRUNCATE", "PERSIST", "MEMORY", "WAL", "OFF"];
+
+/// Fluent builder for `DatabaseConfig` that validates the result
+///
+/// Starts from `DatabaseConfig::default()`; fields without a setter keep their defaults.
+///
+/// # Example
+/// ```javascript
+/// const config = new DatabaseConfigBuilder('app')
+///   .pageSize(8192)
+///   .journalMode('WAL')
+///   .build(); // throws CONFIG_INVALID listing every bad setting
+/// const db = await Database.openWithConfig(config);
+/// ```
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct DatabaseConfigBuilder {
+    config: DatabaseConfig,
+}
+
+#[wasm_bindgen]
+impl DatabaseConfigBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: String) -> Self {
+        Self {
+            config: DatabaseConfig {
+                name,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[wasm_bindgen(js_name = "pageSize")]
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.config.page_size = Some(page_size);
+        self
+    }
+
+    #[wasm_bindgen(js_name = "cacheSize")]
+    pub fn cache_size(mut self, cache_size: usize) -> Self {
+        self.config.cache_size = Some(cache_size);
+        self
+    }
+
+    #[wasm_bindgen(js_name = "journalMode")]
+    pub fn journal_mode(mut self, journal_mode: String) -> Self {
+        self.config.journal_mode = Some(journal_mode);
+        self
+    }
+
+    #[wasm_bindgen(js_name = "autoVacuum")]
+    pub fn auto_vacuum(mut self, auto_vacuum: bool) -> Self {
+        self.config.auto_vacuum = Some(auto_vacuum);
+        self
+    }
+
+    /// Maximum database size for exports, or no limit when omitted
+    #[wasm_bindgen(js_name = "maxExportSizeBytes")]
+    pub fn max_export_size_bytes(mut self, max_bytes: Option<u64>) -> Self {
+        self.config.max_export_size_bytes = max_bytes;
+        self
+    }
+
+    /// Limit on the stored database size, or no limit when omitted
+    #[wasm_bindgen(js_name = "maxDbBytes")]
+    pub fn max_db_bytes(mut self, max_bytes: Option<u64>) -> Self {
+        self.config.max_db_bytes = max_bytes;
+        self
+    }
+
+    /// Validate and return the configuration
+    #[wasm_bindgen(js_name = "build")]
+    pub fn build_js(self) -> Result<DatabaseConfig, JsValue> {
+        self.build()
+            .map_err(|e| JsValue::from_str(&format!("{}: {}", e.code, e.message)))
+    }
+}
+
+impl DatabaseConfigBuilder {
+    /// Validate and return the configuration, failing with `CONFIG_INVALID`
+    pub fn build(self) -> Result<DatabaseConfig, DatabaseError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
 // Query result types with proper TypeScript mapping
 #[derive(Tsify, Serialize, Deserialize, Debug)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
@@ -96,26 +460,157 @@ pub struct QueryResult {
     pub execution_time_ms: f64,
 }
 
+impl QueryResult {
+    /// Index of the column called `name`, for use with the `Row` getters
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|column| column == name)
+    }
+
+    /// First column of the first row, or `Null` when there are no rows
+    ///
+    /// With `strict`, more than one row or column fails with `MULTIPLE_ROWS` or
+    /// `MULTIPLE_COLUMNS` instead of being ignored.
+    pub fn scalar(&self, strict: bool) -> Result<ColumnValue, DatabaseError> {
+        if strict && self.columns.len() > 1 {
+            return Err(DatabaseError::new(
+                "MULTIPLE_COLUMNS",
+                &format!(
+                    "Scalar query returned {} columns, expected 1",
+                    self.columns.len()
+                ),
+            ));
+        }
+        if strict && self.rows.len() > 1 {
+            return Err(DatabaseError::new(
+                "MULTIPLE_ROWS",
+                &format!(
+                    "Scalar query returned {} rows, expected at most 1",
+                    self.rows.len()
+                ),
+            ));
+        }
+        Ok(self
+            .rows
+            .first()
+            .and_then(|row| row.values.first())
+            .cloned()
+            .unwrap_or(ColumnValue::Null))
+    }
+}
+
 #[derive(Tsify, Serialize, Deserialize, Debug, Clone)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
 pub struct Row {
     pub values: Vec<ColumnValue>,
 }
 
+impl Row {
+    /// Value at `col`, or `COLUMN_OUT_OF_RANGE`
+    fn value(&self, col: usize) -> Result<&ColumnValue, DatabaseError> {
+        self.values.get(col).ok_or_else(|| {
+            DatabaseError::new(
+                "COLUMN_OUT_OF_RANGE",
+                &format!(
+                    "Column {} out of range for a row with {} columns",
+                    col,
+                    self.values.len()
+                ),
+            )
+        })
+    }
+
+    fn type_mismatch(col: usize, expected: &str, found: &ColumnValue) -> DatabaseError {
+        DatabaseError::new(
+            "TYPE_MISMATCH",
+            &format!("Column {} is {:?}, expected {}", col, found, expected),
+        )
+    }
+
+    /// INTEGER value at `col`
+    pub fn get_i64(&self, col: usize) -> Result<i64, DatabaseError> {
+        match self.value(col)? {
+            ColumnValue::Integer(i) => Ok(*i),
+            other => Err(Self::type_mismatch(col, "Integer", other)),
+        }
+    }
+
+    /// REAL value at `col`; INTEGER values are widened
+    pub fn get_f64(&self, col: usize) -> Result<f64, DatabaseError> {
+        match self.value(col)? {
+            ColumnValue::Real(f) => Ok(*f),
+            ColumnValue::Integer(i) => Ok(*i as f64),
+            other => Err(Self::type_mismatch(col, "Real", other)),
+        }
+    }
+
+    /// TEXT value at `col`
+    pub fn get_text(&self, col: usize) -> Result<&str, DatabaseError> {
+        match self.value(col)? {
+            ColumnValue::Text(s) => Ok(s),
+            other => Err(Self::type_mismatch(col, "Text", other)),
+        }
+    }
+
+    /// BLOB value at `col`
+    pub fn get_blob(&self, col: usize) -> Result<&[u8], DatabaseError> {
+        match self.value(col)? {
+            ColumnValue::Blob(b) => Ok(b),
+            other => Err(Self::type_mismatch(col, "Blob", other)),
+        }
+    }
+
+    /// Whether the value at `col` is NULL
+    pub fn is_null(&self, col: usize) -> Result<bool, DatabaseError> {
+        Ok(matches!(self.value(col)?, ColumnValue::Null))
+    }
+}
+
 #[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
 #[serde(tag = "type", content = "value")]
 pub enum ColumnValue {
     Null,
-    Integer(i64),
+    /// Serialized to JS as a `BigInt` outside `Number.MAX_SAFE_INTEGER`
+    #[serde(serialize_with = "serialize_integer")]
+    Integer(#[tsify(type = "number | bigint")] i64),
     Real(f64),
     Text(String),
     Blob(Vec<u8>),
-    Date(i64),      // Store as UTC timestamp (milliseconds since epoch)
+    /// Milliseconds since the Unix epoch (UTC), bound as INTEGER. Returned for date
+    /// columns when `DatabaseConfig::date_columns` is set
+    Date(i64),
     BigInt(String), // Store as string to handle large integers beyond i64
+    /// JSON document, bound as TEXT. Returned for `json(...)`/`->` results and for
+    /// columns declared `JSON`
+    Json(#[tsify(type = "unknown")] serde_json::Value),
+}
+
+/// Largest integer a JS number represents exactly (`Number.MAX_SAFE_INTEGER`)
+pub const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// Serialize integers beyond the JS safe range as `i128`, which `serde_wasm_bindgen`
+/// turns into a `BigInt` while JSON keeps the exact number
+fn serialize_integer<S: serde::Serializer>(value: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(value) {
+        serializer.serialize_i64(*value)
+    } else {
+        serializer.serialize_i128(*value as i128)
+    }
 }
 
 impl ColumnValue {
+    /// Parse a `BigInt` decimal string into the 64-bit integer SQLite stores
+    ///
+    /// Fails with `INTEGER_OVERFLOW` instead of binding a truncated value.
+    pub fn parse_bigint(value: &str) -> Result<i64, DatabaseError> {
+        value.trim().parse::<i64>().map_err(|_| {
+            DatabaseError::new(
+                "INTEGER_OVERFLOW",
+                &format!("BigInt {} does not fit in a 64-bit SQLite integer", value),
+            )
+        })
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn from_rusqlite_value(value: &rusqlite::types::Value) -> Self {
         match value {
@@ -145,6 +640,23 @@ impl ColumnValue {
         }
     }
 
+    /// Convert a borrowed rusqlite value, decoding TEXT according to `invalid_utf8`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_rusqlite_value_ref(
+        value: rusqlite::types::ValueRef<'_>,
+        invalid_utf8: InvalidUtf8Handling,
+    ) -> Result<Self, DatabaseError> {
+        match value {
+            rusqlite::types::ValueRef::Text(bytes) => match invalid_utf8.decode(bytes)? {
+                ColumnValue::Text(s) => {
+                    Ok(Self::from_rusqlite_value(&rusqlite::types::Value::Text(s)))
+                }
+                other => Ok(other),
+            },
+            other => Ok(Self::from_rusqlite_value(&other.into())),
+        }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn to_rusqlite_value(&self) -> rusqlite::types::Value {
         match self {
@@ -163,27 +675,670 @@ impl ColumnValue {
                 rusqlite::types::Value::Text(formatted)
             }
             ColumnValue::BigInt(s) => rusqlite::types::Value::Text(s.clone()),
+            ColumnValue::Json(v) => rusqlite::types::Value::Text(v.to_string()),
         }
     }
 }
 
+// Result of Database::vacuum
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct VacuumStats {
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub blocks_freed: u64,
+}
+
+// Result of Database::releaseMemory
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryReleaseStats {
+    /// Bytes of SQLite page cache released
+    pub page_cache_bytes_freed: u64,
+    /// Clean blocks dropped from the block cache
+    pub blocks_evicted: u64,
+}
+
+// Block cache section of Database::getCacheStats
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockCacheStats {
+    /// Block reads served from the cache
+    pub hits: u64,
+    /// Block reads that went to the in-memory store or IndexedDB
+    pub misses: u64,
+    /// Blocks currently cached
+    pub size: usize,
+    /// Maximum number of cached blocks
+    pub capacity: usize,
+}
+
+// Result of Database::getCacheStats
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    /// Prepared-statement cache of this instance
+    pub statements: crate::storage::statement_cache::StatementCacheStats,
+    /// Block cache of the database's storage (empty for memory databases)
+    pub blocks: BlockCacheStats,
+}
+
+// Result of PRAGMA integrity_check / quick_check through Database::pragma
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityCheckResult {
+    pub ok: bool,
+    /// Problems SQLite reported; empty when `ok`
+    pub errors: Vec<String>,
+}
+
+/// Flag pragmas whose 0/1 value is returned as a bool
+const BOOLEAN_PRAGMAS: &[&str] = &[
+    "automatic_index",
+    "cell_size_check",
+    "checkpoint_fullfsync",
+    "defer_foreign_keys",
+    "foreign_keys",
+    "fullfsync",
+    "ignore_check_constraints",
+    "legacy_alter_table",
+    "query_only",
+    "read_uncommitted",
+    "recursive_triggers",
+    "reverse_unordered_selects",
+    "trusted_schema",
+    "writable_schema",
+];
+
+/// One-column pragmas that list values and so stay rows even with a single entry
+const LIST_PRAGMAS: &[&str] = &["compile_options", "module_list", "pragma_list"];
+
+/// Parsed result of Database::pragma / Database::setPragma
+#[derive(Debug)]
+pub enum PragmaValue {
+    /// The pragma returned no rows
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    /// `integrity_check` and `quick_check`
+    Check(IntegrityCheckResult),
+    /// Table pragmas such as `table_info`, and anything not a single value
+    Rows(QueryResult),
+}
+
+impl PragmaValue {
+    /// Parse the result of running `PRAGMA <name>`, where `name` has no schema prefix
+    pub fn from_result(name: &str, result: QueryResult) -> Self {
+        let name = name.to_ascii_lowercase();
+        if name == "integrity_check" || name == "quick_check" {
+            let errors: Vec<String> = result
+                .rows
+                .iter()
+                .filter_map(|row| match row.values.first() {
+                    Some(ColumnValue::Text(text)) if text != "ok" => Some(text.clone()),
+                    _ => None,
+                })
+                .collect();
+            return PragmaValue::Check(IntegrityCheckResult {
+                ok: errors.is_empty(),
+                errors,
+            });
+        }
+        if result.rows.is_empty() {
+            return PragmaValue::Null;
+        }
+        if result.columns.len() != 1
+            || result.rows.len() != 1
+            || LIST_PRAGMAS.contains(&name.as_str())
+        {
+            return PragmaValue::Rows(result);
+        }
+        match &result.rows[0].values[0] {
+            ColumnValue::Null => PragmaValue::Null,
+            ColumnValue::Integer(i) if BOOLEAN_PRAGMAS.contains(&name.as_str()) => {
+                PragmaValue::Bool(*i != 0)
+            }
+            ColumnValue::Integer(i) => PragmaValue::Integer(*i),
+            ColumnValue::Real(f) => PragmaValue::Real(*f),
+            ColumnValue::Text(text) => PragmaValue::Text(text.clone()),
+            _ => PragmaValue::Rows(result),
+        }
+    }
+}
+
+// Result of import::validate_import_bytes / Database::validateImport
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    /// Page size from the file header (0 when the header is unreadable)
+    pub page_size: u32,
+    /// Page count from the file header
+    pub page_count: u32,
+    /// Schema cookie from the file header
+    pub schema_version: u32,
+    /// Whether the header checks and `PRAGMA integrity_check` passed
+    pub integrity_ok: bool,
+    /// The file has no plaintext SQLite header but is page-aligned, as encrypted files are
+    pub encrypted: bool,
+    /// Why the file would be rejected; empty when it can be imported
+    pub problems: Vec<String>,
+}
+
+// Result of BlockStorage::reload_from_indexeddb / Database::reloadFromStorage
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageReloadReport {
+    /// Blocks read back from IndexedDB
+    pub blocks_loaded: u64,
+    /// Size of the database file the restored blocks make up
+    pub file_size_bytes: u64,
+    /// Restored blocks whose contents do not match their persisted checksum
+    pub corrupt_blocks: Vec<u64>,
+}
+
+// Entry returned by Database::listDatabases
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseInfo {
+    pub name: String,
+    /// Logical size of the persisted blocks (block count times the block size)
+    pub size_bytes: u64,
+    pub block_count: u64,
+    /// Time of the last sync to IndexedDB in milliseconds since the epoch; None for
+    /// databases last synced by a version that did not record it
+    pub last_modified: Option<f64>,
+}
+
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchOptions {
+    /// Run every operation on its own and report each outcome instead of rolling back
+    /// the batch at the first failure
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+// Outcome of one operation in a best-effort batch
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchOperationResult {
+    /// Position of the operation in the batch
+    pub index: usize,
+    pub ok: bool,
+    pub affected_rows: u32,
+    pub last_insert_id: Option<i64>,
+    /// Error code when the operation failed (e.g. `SQLITE_ERROR`)
+    pub error_code: Option<String>,
+    /// Error message when the operation failed
+    pub error: Option<String>,
+}
+
+// Result of a batch run with continue_on_error
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchReport {
+    pub succeeded: u32,
+    pub failed: u32,
+    /// Rows changed by the operations that succeeded
+    pub affected_rows: u32,
+    /// Rowid of the last successful insert
+    pub last_insert_id: Option<i64>,
+    /// One entry per operation, in batch order
+    pub results: Vec<BatchOperationResult>,
+}
+
+impl BatchReport {
+    pub fn record_success(
+        &mut self,
+        index: usize,
+        affected_rows: u32,
+        last_insert_id: Option<i64>,
+    ) {
+        self.succeeded += 1;
+        self.affected_rows += affected_rows;
+        if last_insert_id.is_some() {
+            self.last_insert_id = last_insert_id;
+        }
+        self.results.push(BatchOperationResult {
+            index,
+            ok: true,
+            affected_rows,
+            last_insert_id,
+            error_code: None,
+            error: None,
+        });
+    }
+
+    pub fn record_failure(&mut self, index: usize, error: &DatabaseError) {
+        self.failed += 1;
+        self.results.push(BatchOperationResult {
+            index,
+            ok: false,
+            affected_rows: 0,
+            last_insert_id: None,
+            error_code: Some(error.code.clone()),
+            error: Some(error.message.clone()),
+        });
+    }
+}
+
+// A table listed by Database::listTables
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct TableSummary {
+    pub name: String,
+    /// `table`, `virtual`, or `shadow` for the backing tables of a virtual table
+    pub kind: String,
+    pub column_count: u32,
+    pub without_rowid: bool,
+    pub strict: bool,
+}
+
+// A column reported by Database::getTableSchema
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnInfo {
+    pub name: String,
+    /// Declared type as written in CREATE TABLE; empty when none was given
+    pub declared_type: String,
+    pub nullable: bool,
+    /// Default value as SQL text (e.g. `'draft'` or `CURRENT_TIMESTAMP`)
+    pub default_value: Option<String>,
+    /// Position in the primary key, starting at 1; 0 when not part of it
+    pub primary_key: u32,
+}
+
+// Result of Database::getTableSchema
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct TableSchema {
+    pub name: String,
+    pub columns: Vec<ColumnInfo>,
+}
+
+// An index listed by Database::listIndexes
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexInfo {
+    pub name: String,
+    pub unique: bool,
+    /// `c` for CREATE INDEX, `u` for a UNIQUE constraint, `pk` for the primary key
+    pub origin: String,
+    pub partial: bool,
+    /// Indexed columns in key order; None for an expression
+    pub columns: Vec<Option<String>>,
+}
+
+// A foreign key listed by Database::listForeignKeys
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct ForeignKeyInfo {
+    /// Referenced table
+    pub table: String,
+    /// Columns in this table
+    pub from: Vec<String>,
+    /// Referenced columns; None when the key refers to the parent's primary key
+    pub to: Vec<Option<String>>,
+    pub on_update: String,
+    pub on_delete: String,
+}
+
+// Result of Database::indexedDbHandleStats
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedDbHandleStats {
+    /// Cap on connections open at once
+    pub max_open: u32,
+    /// Connections currently open, idle or in use
+    pub open: u32,
+    /// Open connections parked in the pool
+    pub idle: u32,
+    /// Opens waiting for a connection to be released
+    pub waiting: u32,
+    /// Connections opened since the page loaded
+    pub opened_total: u64,
+    /// Opens served by an idle connection
+    pub reused_total: u64,
+    /// Idle connections closed to stay under the cap
+    pub evicted_total: u64,
+}
+
+// Result of BlockStorage::allocation_stats / Database::getAllocationStats
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationStats {
+    /// Blocks currently allocated
+    pub allocated: u64,
+    /// Deallocated blocks whose ids can be handed out again
+    pub deallocated_reusable: u64,
+    /// Highest allocated block id (0 when nothing is allocated)
+    pub highest_block_id: u64,
+    /// `(highest_block_id + 1 - allocated) / (highest_block_id + 1)`; 0 when empty
+    pub fragmentation_ratio: f64,
+}
+
+// Mode of Database::checkpoint, as in `PRAGMA wal_checkpoint(MODE)`
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CheckpointMode {
+    /// Checkpoint as much as possible without waiting for readers or writers
+    #[default]
+    Passive,
+    /// Wait for writers, then checkpoint everything
+    Full,
+    /// Like `Full`, then wait for readers so the next writer restarts the WAL
+    Restart,
+    /// Like `Restart`, then truncate the WAL to zero bytes
+    Truncate,
+}
+
+impl CheckpointMode {
+    /// Parse `PASSIVE`, `FULL`, `RESTART` or `TRUNCATE` (any case)
+    pub fn from_name(name: &str) -> Result<Self, DatabaseError> {
+        match name.to_ascii_uppercase().as_str() {
+            "PASSIVE" => Ok(CheckpointMode::Passive),
+            "FULL" => Ok(CheckpointMode::Full),
+            "RESTART" => Ok(CheckpointMode::Restart),
+            "TRUNCATE" => Ok(CheckpointMode::Truncate),
+            _ => Err(DatabaseError::new(
+                "INVALID_CHECKPOINT_MODE",
+                &format!(
+                    "Unknown checkpoint mode '{}'; expected PASSIVE, FULL, RESTART or TRUNCATE",
+                    name
+                ),
+            )),
+        }
+    }
+
+    /// The mode as written in the PRAGMA
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            CheckpointMode::Passive => "PASSIVE",
+            CheckpointMode::Full => "FULL",
+            CheckpointMode::Restart => "RESTART",
+            CheckpointMode::Truncate => "TRUNCATE",
+        }
+    }
+}
+
+// Result of Database::checkpoint, the row returned by `PRAGMA wal_checkpoint`
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointResult {
+    /// The checkpoint could not finish because another connection was busy
+    pub busy: bool,
+    /// Frames in the WAL (-1 when the database is not in WAL mode)
+    pub log_frames: i64,
+    /// Frames copied back into the database (-1 when not in WAL mode)
+    pub checkpointed_frames: i64,
+}
+
+// How far Database::sync waits before resolving
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncLevel {
+    /// Resolve once the IndexedDB transaction writing the blocks completes
+    #[default]
+    Normal,
+    /// Also read the commit marker back from IndexedDB and fail with `SYNC_NOT_DURABLE`
+    /// unless it matches the one just written
+    Durable,
+}
+
+impl SyncLevel {
+    /// Parse `NORMAL` or `DURABLE` (any case)
+    pub fn from_name(name: &str) -> Result<Self, DatabaseError> {
+        match name.to_ascii_uppercase().as_str() {
+            "NORMAL" => Ok(SyncLevel::Normal),
+            "DURABLE" => Ok(SyncLevel::Durable),
+            _ => Err(DatabaseError::new(
+                "INVALID_SYNC_LEVEL",
+                &format!("Unknown sync level '{}'; expected NORMAL or DURABLE", name),
+            )),
+        }
+    }
+}
+
+// Result of Database::sync
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncResult {
+    /// Blocks written to IndexedDB by this sync
+    pub blocks_persisted: u32,
+    /// Commit marker after the sync (0 for memory databases)
+    pub commit_marker: u64,
+}
+
+// How applyChangeset resolves a conflicting change
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// Roll back the whole changeset at the first conflict
+    #[default]
+    Abort,
+    /// Overwrite the conflicting row with the incoming change where SQLite allows it
+    /// (data and unique-key conflicts); other conflicts are skipped
+    Replace,
+    /// Leave the local row as-is and continue with the next change
+    Skip,
+}
+
+impl ConflictPolicy {
+    /// Parse `ABORT`, `REPLACE` or `SKIP` (any case)
+    pub fn from_name(name: &str) -> Result<Self, DatabaseError> {
+        match name.to_ascii_uppercase().as_str() {
+            "ABORT" => Ok(ConflictPolicy::Abort),
+            "REPLACE" => Ok(ConflictPolicy::Replace),
+            "SKIP" => Ok(ConflictPolicy::Skip),
+            _ => Err(DatabaseError::new(
+                "INVALID_CONFLICT_POLICY",
+                &format!(
+                    "Unknown conflict policy '{}'; expected ABORT, REPLACE or SKIP",
+                    name
+                ),
+            )),
+        }
+    }
+}
+
+// Why a change in a changeset could not be applied as-is
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangesetConflictKind {
+    /// The row exists but its current values differ from the change's old values
+    Data,
+    /// The row to update or delete does not exist
+    NotFound,
+    /// An inserted row's primary key already exists
+    Conflict,
+    /// The change violates a constraint other than the primary key
+    Constraint,
+    /// Applying the changeset left foreign key violations
+    ForeignKey,
+}
+
+// One conflicting row reported by applyChangeset
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangesetConflict {
+    /// Empty for `ForeignKey`, which is reported once for the whole changeset
+    pub table: String,
+    /// `INSERT`, `UPDATE` or `DELETE` (empty for `ForeignKey`)
+    pub operation: String,
+    pub kind: ChangesetConflictKind,
+    /// Primary key values of the conflicting row, in column order
+    pub primary_key: Vec<ColumnValue>,
+    /// What was done with the change
+    pub resolution: ConflictPolicy,
+}
+
+// Result of Database::applyChangeset
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangesetApplyResult {
+    /// A conflict under the `Abort` policy rolled back every change
+    pub aborted: bool,
+    /// Conflicts in the order they were met
+    pub conflicts: Vec<ChangesetConflict>,
+}
+
+// How Database::copyTable treats rows whose key already exists in the target
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CopyMode {
+    /// Plain `INSERT`: a duplicate key fails the copy and rolls it back
+    #[default]
+    Insert,
+    /// `INSERT OR IGNORE`: keep the target's row
+    Ignore,
+    /// `INSERT OR REPLACE`: overwrite the target's row with the source's
+    Replace,
+}
+
+impl CopyMode {
+    /// Parse `INSERT`, `IGNORE` or `REPLACE` (any case)
+    pub fn from_name(name: &str) -> Result<Self, DatabaseError> {
+        match name.to_ascii_uppercase().as_str() {
+            "INSERT" => Ok(CopyMode::Insert),
+            "IGNORE" => Ok(CopyMode::Ignore),
+            "REPLACE" => Ok(CopyMode::Replace),
+            _ => Err(DatabaseError::new(
+                "INVALID_COPY_MODE",
+                &format!(
+                    "Unknown copy mode '{}'; expected INSERT, IGNORE or REPLACE",
+                    name
+                ),
+            )),
+        }
+    }
+
+    /// The insert verb for this mode
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            CopyMode::Insert => "INSERT",
+            CopyMode::Ignore => "INSERT OR IGNORE",
+            CopyMode::Replace => "INSERT OR REPLACE",
+        }
+    }
+}
+
+// Result of testNotificationChannel
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationChannelHealth {
+    /// A ping made the round trip through BroadcastChannel
+    pub healthy: bool,
+    /// Round-trip time of the ping when healthy
+    pub latency_ms: Option<f64>,
+    /// Why the ping failed when not healthy
+    pub error: Option<String>,
+}
+
+// Report returned by runPersistenceSelfTest
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistenceSelfTestReport {
+    /// Every step passed
+    pub passed: bool,
+    /// Temporary database the test ran against (deleted afterwards)
+    pub database: String,
+    /// Steps in the order they ran; the run stops at the first failure
+    pub steps: Vec<SelfTestStep>,
+    pub duration_ms: f64,
+}
+
+// One step of the persistence self-test
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestStep {
+    /// `vfs_registered`, `row_written`, `commit_marker_advanced`,
+    /// `in_memory_state_dropped`, `blocks_persisted` or `restore_succeeded`
+    pub name: String,
+    pub passed: bool,
+    /// What was observed, or why the step failed
+    pub detail: String,
+}
+
+// Options for Database::createFtsIndex
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FtsOptions {
+    /// FTS5 tokenizer, e.g. `"porter unicode61"` or `"trigram"`. Default: unicode61
+    pub tokenize: Option<String>,
+    /// Prefix lengths to index for fast `term*` queries, e.g. `[2, 3]`
+    pub prefix: Option<Vec<u32>>,
+}
+
 // Transaction options
-#[derive(Tsify, Serialize, Deserialize, Debug)]
+#[derive(Tsify, Serialize, Deserialize, Debug, Default)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
 pub struct TransactionOptions {
+    #[serde(default)]
     pub isolation_level: IsolationLevel,
     pub timeout_ms: Option<u32>,
+    /// How `Database::transaction` begins: DEFERRED (default), IMMEDIATE or EXCLUSIVE
+    #[serde(default)]
+    pub behavior: TransactionBehavior,
 }
 
-#[derive(Tsify, Serialize, Deserialize, Debug)]
+#[derive(Tsify, Serialize, Deserialize, Debug, Default)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
 pub enum IsolationLevel {
     ReadUncommitted,
     ReadCommitted,
     RepeatableRead,
+    #[default]
     Serializable,
 }
 
+// Locking behavior of the BEGIN issued by Database::transaction
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum TransactionBehavior {
+    /// Take locks when the first statement needs them
+    #[default]
+    #[serde(alias = "DEFERRED")]
+    Deferred,
+    /// Take the write lock immediately
+    #[serde(alias = "IMMEDIATE")]
+    Immediate,
+    /// Take the write lock immediately and keep readers out (rollback journal only)
+    #[serde(alias = "EXCLUSIVE")]
+    Exclusive,
+}
+
+impl TransactionBehavior {
+    /// SQL keyword following BEGIN
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            TransactionBehavior::Deferred => "DEFERRED",
+            TransactionBehavior::Immediate => "IMMEDIATE",
+            TransactionBehavior::Exclusive => "EXCLUSIVE",
+        }
+    }
+}
+
 // Error types
 #[derive(Tsify, Serialize, Deserialize, Debug, Clone, thiserror::Error)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
@@ -192,6 +1347,10 @@ pub struct DatabaseError {
     pub code: String,
     pub message: String,
     pub sql: Option<String>,
+    /// Extended SQLite result code (e.g. 5 for SQLITE_BUSY, 2067 for
+    /// SQLITE_CONSTRAINT_UNIQUE) when the error came from SQLite
+    #[serde(default)]
+    pub sqlite_code: Option<i32>,
 }
 
 impl DatabaseError {
@@ -200,6 +1359,7 @@ impl DatabaseError {
             code: code.to_string(),
             message: message.to_string(),
             sql: None,
+            sqlite_code: None,
         }
     }
 
@@ -207,12 +1367,26 @@ impl DatabaseError {
         self.sql = Some(sql.to_string());
         self
     }
+
+    pub fn with_sqlite_code(mut self, sqlite_code: i32) -> Self {
+        self.sqlite_code = Some(sqlite_code);
+        self
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 impl From<rusqlite::Error> for DatabaseError {
     fn from(err: rusqlite::Error) -> Self {
-        DatabaseError::new("SQLITE_ERROR", &err.to_string())
+        let error = DatabaseError::new("SQLITE_ERROR", &err.to_string());
+        // Syntax errors arrive as `SqlInputError`, which wraps the SQLite error as its source
+        let sqlite_err = err.sqlite_error().or_else(|| {
+            std::error::Error::source(&err)
+                .and_then(|source| source.downcast_ref::<rusqlite::ffi::Error>())
+        });
+        match sqlite_err {
+            Some(sqlite_err) => error.with_sqlite_code(sqlite_err.extended_code),
+            None => error,
+        }
     }
 }
 
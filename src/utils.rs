@@ -1,4 +1,4 @@
-use crate::types::DatabaseError;
+use crate::types::{ColumnValue, DatabaseError};
 use wasm_bindgen::prelude::*;
 
 // Utility functions for the SQLite IndexedDB library
@@ -96,6 +96,172 @@ pub fn format_bytes(bytes: usize) -> String {
     }
 }
 
+/// Quote an identifier (table, column, savepoint or schema name) for use in SQL
+///
+/// Wraps the name in double quotes and doubles any embedded double quote, so names with
+/// spaces, reserved words or quotes can never break out of the identifier.
+///
+/// # Example
+/// ```rust
+/// use absurder_sql::utils::quote_identifier;
+///
+/// assert_eq!(quote_identifier("order"), "\"order\"");
+/// assert_eq!(quote_identifier("my \"table\""), "\"my \"\"table\"\"\"");
+/// ```
+#[wasm_bindgen(js_name = "quoteIdentifier")]
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Schema names that qualify an object name in `sql`, e.g. `archive` in `archive.orders`
+///
+/// Tokenizes the SQL, so string literals, comments and quoted identifiers (`"..."`,
+/// `[...]`, `` `...` ``) that merely contain a dot are not mistaken for qualifiers.
+/// Names are returned unquoted and lowercased, in order of appearance.
+pub fn qualified_schema_names(sql: &str) -> Vec<String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut names = Vec::new();
+    let mut previous: Option<String> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        // Comments are skipped without resetting `previous`, like whitespace
+        if c == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            continue;
+        }
+
+        let token = if c == '.' {
+            if let Some(name) = previous.take() {
+                names.push(name);
+            }
+            i += 1;
+            continue;
+        } else if let Some(close) = match c {
+            '"' => Some('"'),
+            '`' => Some('`'),
+            '[' => Some(']'),
+            _ => None,
+        } {
+            let mut name = String::new();
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == close {
+                    // A doubled quote is an escaped quote inside the identifier
+                    if close != ']' && chars.get(i + 1) == Some(&close) {
+                        name.push(close);
+                        i += 2;
+                        continue;
+                    }
+                    break;
+                }
+                name.push(chars[i]);
+                i += 1;
+            }
+            i += 1;
+            Some(name)
+        } else if c == '\'' {
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == '\'' {
+                    if chars.get(i + 1) == Some(&'\'') {
+                        i += 2;
+                        continue;
+                    }
+                    break;
+                }
+                i += 1;
+            }
+            i += 1;
+            None
+        } else if c.is_alphanumeric() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+            {
+                i += 1;
+            }
+            // Numbers like `1.5` are not qualified names
+            (!c.is_ascii_digit()).then(|| chars[start..i].iter().collect())
+        } else {
+            i += 1;
+            None
+        };
+        previous = token.map(|name| name.to_lowercase());
+    }
+    names
+}
+
+/// Render a value as a SQL literal, for generating SQL dumps
+///
+/// Text (and JSON/BigInt, which are bound as TEXT) is single-quoted with embedded quotes
+/// doubled, blobs become `X'..'` hex literals, NULL becomes `NULL` and numbers are written
+/// verbatim. Dates are written as their millisecond timestamp. NUL characters are dropped
+/// from text, as they are when binding parameters.
+///
+/// # Example
+/// ```rust
+/// use absurder_sql::ColumnValue;
+/// use absurder_sql::utils::quote_literal;
+///
+/// assert_eq!(quote_literal(&ColumnValue::Text("it's".into())), "'it''s'");
+/// assert_eq!(quote_literal(&ColumnValue::Blob(vec![0xde, 0xad])), "X'DEAD'");
+/// ```
+pub fn quote_literal(value: &ColumnValue) -> String {
+    fn quote_text(text: &str) -> String {
+        format!("'{}'", text.replace('\0', "").replace('\'', "''"))
+    }
+
+    match value {
+        ColumnValue::Null => "NULL".to_string(),
+        ColumnValue::Integer(i) => i.to_string(),
+        ColumnValue::Date(ms) => ms.to_string(),
+        // SQLite stores NaN as NULL and reads 9e999 back as infinity
+        ColumnValue::Real(f) if f.is_nan() => "NULL".to_string(),
+        ColumnValue::Real(f) if f.is_infinite() => {
+            if *f > 0.0 { "9e999" } else { "-9e999" }.to_string()
+        }
+        // Debug formatting keeps a decimal point, so the value stays REAL when re-read
+        ColumnValue::Real(f) => format!("{:?}", f),
+        ColumnValue::Text(s) | ColumnValue::BigInt(s) => quote_text(s),
+        ColumnValue::Json(v) => quote_text(&v.to_string()),
+        ColumnValue::Blob(bytes) => {
+            let mut literal = String::with_capacity(bytes.len() * 2 + 3);
+            literal.push_str("X'");
+            for byte in bytes {
+                literal.push_str(&format!("{:02X}", byte));
+            }
+            literal.push('\'');
+            literal
+        }
+    }
+}
+
+/// Render a value as a SQL literal (JS binding for [`quote_literal`])
+///
+/// # Example
+/// ```javascript
+/// quoteLiteral({ type: 'Text', value: "O'Brien" }); // "'O''Brien'"
+/// ```
+#[wasm_bindgen(js_name = "quoteLiteral")]
+pub fn quote_literal_js(value: ColumnValue) -> String {
+    quote_literal(&value)
+}
+
 /// Generate a unique identifier
 pub fn generate_id() -> String {
     let timestamp = js_sys::Date::now() as u64;
@@ -474,6 +640,85 @@ mod tests {
         assert!(validate_sql("DELETE FROM users WHERE id = 1").is_err());
     }
 
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("users"), "\"users\"");
+        assert_eq!(quote_identifier("select"), "\"select\"");
+        assert_eq!(quote_identifier("first name"), "\"first name\"");
+        assert_eq!(quote_identifier("a\"b"), "\"a\"\"b\"");
+        assert_eq!(
+            quote_identifier("x\"; DROP TABLE t; --"),
+            "\"x\"\"; DROP TABLE t; --\""
+        );
+        assert_eq!(quote_identifier(""), "\"\"");
+    }
+
+    #[test]
+    fn test_qualified_schema_names() {
+        assert_eq!(
+            qualified_schema_names("INSERT INTO archive.orders SELECT * FROM main.orders"),
+            vec!["archive", "main"]
+        );
+        assert_eq!(
+            qualified_schema_names("UPDATE \"Arch ive\" . t SET x = 1"),
+            vec!["arch ive"]
+        );
+        assert_eq!(
+            qualified_schema_names("UPDATE [archive].t SET note = 'see archive.t' -- archive.t"),
+            vec!["archive"]
+        );
+        assert!(qualified_schema_names("SELECT 1.5, 'a.b', \"x.y\" FROM t").is_empty());
+        assert!(qualified_schema_names("SELECT * FROM myarchive /* archive.t */").is_empty());
+    }
+
+    #[test]
+    fn test_quote_literal() {
+        assert_eq!(quote_literal(&ColumnValue::Null), "NULL");
+        assert_eq!(quote_literal(&ColumnValue::Integer(-42)), "-42");
+        assert_eq!(quote_literal(&ColumnValue::Real(1.0)), "1.0");
+        assert_eq!(quote_literal(&ColumnValue::Real(f64::NAN)), "NULL");
+        assert_eq!(quote_literal(&ColumnValue::Real(f64::INFINITY)), "9e999");
+        assert_eq!(
+            quote_literal(&ColumnValue::Text("O'Brien said ''hi''".into())),
+            "'O''Brien said ''''hi'''''"
+        );
+        assert_eq!(
+            quote_literal(&ColumnValue::Text("line 1\nline 2".into())),
+            "'line 1\nline 2'"
+        );
+        assert_eq!(quote_literal(&ColumnValue::Blob(vec![])), "X''");
+        assert_eq!(
+            quote_literal(&ColumnValue::Blob(vec![0x00, 0x0f, 0xa0, 0xff])),
+            "X'000FA0FF'"
+        );
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "bundled-sqlite"))]
+    #[test]
+    fn test_quote_literal_reparses() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let text = "it's a \"test\"\n\twith 'quotes'; DROP TABLE x; --";
+        let blob: Vec<u8> = (0..=255).collect();
+
+        let sql = format!(
+            "SELECT {}, {}, {}, {}",
+            quote_literal(&ColumnValue::Text(text.into())),
+            quote_literal(&ColumnValue::Blob(blob.clone())),
+            quote_literal(&ColumnValue::Real(0.1)),
+            quote_literal(&ColumnValue::Integer(i64::MIN)),
+        );
+        let (t, b, r, i): (String, Vec<u8>, f64, i64) = conn
+            .query_row(&sql, [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap();
+
+        assert_eq!(t, text);
+        assert_eq!(b, blob);
+        assert_eq!(r, 0.1);
+        assert_eq!(i, i64::MIN);
+    }
+
     #[test]
     fn test_normalize_db_name() {
         // Already has .db suffix - should be unchanged
@@ -1,5 +1,4 @@
 use crate::DatabaseError;
-#[cfg(target_arch = "wasm32")]
 use crate::storage::BLOCK_SIZE;
 use crate::storage::BlockStorage;
 #[cfg(target_arch = "wasm32")]
@@ -75,6 +74,179 @@ pub fn get_storage_with_fallback(db_name: &str) -> Option<Rc<BlockStorage>> {
     try_get_storage_from_registry(db_name)
 }
 
+/// Name reported for `SQLITE_FCNTL_VFSNAME` by files opened through this VFS
+pub const VFS_IMPLEMENTATION_NAME: &str = "indexeddb";
+
+/// Default largest WAL a database may grow to before writes fail with `WAL_TOO_LARGE`
+pub const MAX_WAL_SIZE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Default fraction of the WAL limit at which an automatic checkpoint runs
+pub const DEFAULT_WAL_CHECKPOINT_FRACTION: f64 = 0.5;
+
+/// Default cap on the combined size of every in-memory WAL in this tab
+pub const DEFAULT_WAL_TOTAL_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Fraction of the combined WAL cap at which the least recently written WAL is checkpointed
+pub const WAL_TOTAL_CHECKPOINT_FRACTION: f64 = 0.75;
+
+#[cfg(target_arch = "wasm32")]
+/// WAL limit in bytes for `db_name` (`MAX_WAL_SIZE_BYTES` unless overridden)
+pub fn wal_max_bytes(db_name: &str) -> usize {
+    let db_name = normalize_db_name(db_name);
+    WAL_LIMITS.with(|limits| {
+        limits
+            .borrow()
+            .get(&db_name)
+            .copied()
+            .unwrap_or(MAX_WAL_SIZE_BYTES)
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Take the `WAL_TOO_LARGE` or `QUOTA_EXCEEDED` error behind the last failed write of
+/// `db_name`, if any
+pub fn take_limit_error(db_name: &str) -> Option<DatabaseError> {
+    let db_name = normalize_db_name(db_name);
+    LIMIT_ERRORS.with(|errors| errors.borrow_mut().remove(&db_name))
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Override the WAL limit for `db_name`; the limit is shared by all its connections
+pub fn set_wal_max_bytes(db_name: &str, max_bytes: usize) {
+    let db_name = normalize_db_name(db_name);
+    WAL_LIMITS.with(|limits| {
+        limits.borrow_mut().insert(db_name, max_bytes);
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Cap on the combined size of every WAL in this tab
+pub fn wal_total_max_bytes() -> usize {
+    WAL_TOTAL_MAX_BYTES.with(|max| max.get())
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Change the cap on the combined size of every WAL in this tab
+pub fn set_wal_total_max_bytes(max_bytes: usize) {
+    WAL_TOTAL_MAX_BYTES.with(|max| max.set(max_bytes));
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Combined size in bytes of every in-memory WAL in this tab
+pub fn wal_total_size_bytes() -> usize {
+    WAL_STORAGE.with(|wal| wal.borrow().values().map(Vec::len).sum())
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Tell the open main-database handle of `db` that its file is now `size` bytes
+///
+/// Handles compute their size when opened, so storage replaced underneath an open
+/// connection (an in-place import) must update it before SQLite reads again.
+///
+/// # Safety
+/// `db` must be an open connection with no statement running, since the handle's
+/// buffered writes are dropped.
+pub(crate) unsafe fn set_main_file_size(
+    db: *mut sqlite_wasm_rs::sqlite3,
+    size: u64,
+) -> Result<(), DatabaseError> {
+    let schema = CString::new("main").expect("valid schema name");
+    let mut p_file: *mut sqlite_wasm_rs::sqlite3_file = std::ptr::null_mut();
+    let ret = unsafe {
+        sqlite_wasm_rs::sqlite3_file_control(
+            db,
+            schema.as_ptr(),
+            sqlite_wasm_rs::SQLITE_FCNTL_FILE_POINTER,
+            &mut p_file as *mut _ as *mut c_void,
+        )
+    };
+    let ours = IO_METHODS.get().is_some_and(|methods| {
+        !p_file.is_null() && std::ptr::eq(unsafe { (*p_file).pMethods }, methods)
+    });
+    if ret != sqlite_wasm_rs::SQLITE_OK || !ours {
+        return Err(DatabaseError::new(
+            "WRONG_VFS",
+            "The main database is not open through the IndexedDB VFS",
+        ));
+    }
+    unsafe {
+        let vf = file_from_ptr(p_file);
+        (*vf).handle.file_size = size;
+        (*vf).handle.write_buffer.clear();
+    }
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Checkpoint least recently written WALs until the total is back under the threshold
+///
+/// Runs as a task rather than inside the VFS write that crossed the threshold, since a
+/// checkpoint truncates WAL_STORAGE, which the write is still holding.
+fn schedule_wal_eviction() {
+    if WAL_EVICTION_SCHEDULED.with(|scheduled| scheduled.replace(true)) {
+        return;
+    }
+    wasm_bindgen_futures::spawn_local(async {
+        let threshold = (wal_total_max_bytes() as f64 * WAL_TOTAL_CHECKPOINT_FRACTION) as usize;
+        // Least recently written first; each WAL is tried at most once per round
+        let mut candidates: Vec<(u64, String)> = WAL_STORAGE.with(|wal| {
+            let wal = wal.borrow();
+            WAL_LAST_WRITE.with(|last| {
+                let last = last.borrow();
+                wal.iter()
+                    .filter(|(_, data)| !data.is_empty())
+                    .map(|(name, _)| (last.get(name).copied().unwrap_or(0), name.clone()))
+                    .collect()
+            })
+        });
+        candidates.sort();
+
+        for (_, db_name) in candidates {
+            if wal_total_size_bytes() <= threshold {
+                break;
+            }
+            let pool_key = crate::Database::connection_pool_key(
+                &db_name,
+                false,
+                crate::types::Persistence::IndexedDB,
+            );
+            let Some(connection) = crate::connection_pool::get_connection(&pool_key) else {
+                continue;
+            };
+            let db = connection.db.get();
+            if db.is_null() {
+                continue;
+            }
+            let before = wal_size_bytes(&db_name);
+            let checkpoint = CString::new("PRAGMA wal_checkpoint(TRUNCATE)").expect("valid SQL");
+            unsafe {
+                sqlite_wasm_rs::sqlite3_exec(
+                    db,
+                    checkpoint.as_ptr(),
+                    None,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                )
+            };
+            log::info!(
+                "Combined WAL size over {} bytes: checkpointed {} ({} -> {} bytes)",
+                threshold,
+                db_name,
+                before,
+                wal_size_bytes(&db_name)
+            );
+        }
+        WAL_EVICTION_SCHEDULED.with(|scheduled| scheduled.set(false));
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Current size in bytes of the in-memory WAL for `db_name` (0 when there is none)
+pub fn wal_size_bytes(db_name: &str) -> usize {
+    let db_name = normalize_db_name(db_name);
+    WAL_STORAGE.with(|wal| wal.borrow().get(&db_name).map_or(0, Vec::len))
+}
+
 #[cfg(target_arch = "wasm32")]
 /// Helper to remove storage from registry
 /// SAFETY: WASM is single-threaded, no concurrent access possible
@@ -105,6 +277,27 @@ pub(crate) fn registry_contains_key(db_name: &str) -> bool {
     })
 }
 
+/// Reservation of a database name in `INIT_IN_PROGRESS`, released when dropped
+#[cfg(target_arch = "wasm32")]
+struct InitReservation(String);
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for InitReservation {
+    fn drop(&mut self) {
+        INIT_IN_PROGRESS.with(|init| {
+            init.borrow_mut().remove(&self.0);
+        });
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Whether a BlockStorage for `db_name` is currently being created
+///
+/// Used to verify that concurrent opens never leak a reservation.
+pub fn init_in_progress(db_name: &str) -> bool {
+    INIT_IN_PROGRESS.with(|init| init.borrow().contains(db_name))
+}
+
 /// Custom SQLite VFS implementation that uses IndexedDB for storage
 pub struct IndexedDBVFS {
     #[cfg(target_arch = "wasm32")]
@@ -115,9 +308,44 @@ pub struct IndexedDBVFS {
     name: String,
 }
 
+/// Ensure shared storage uses the block size this VFS was asked for
+#[cfg(target_arch = "wasm32")]
+fn check_storage_block_size(
+    storage: &BlockStorage,
+    db_name: &str,
+    block_size: usize,
+) -> Result<(), DatabaseError> {
+    if storage.get_block_size() != block_size {
+        return Err(DatabaseError::new(
+            "BLOCK_SIZE_MISMATCH",
+            &format!(
+                "Database {} uses {}-byte blocks but {} was requested",
+                db_name,
+                storage.get_block_size(),
+                block_size
+            ),
+        ));
+    }
+    Ok(())
+}
+
 impl IndexedDBVFS {
     pub async fn new(db_name: &str) -> Result<Self, DatabaseError> {
-        log::info!("Creating IndexedDBVFS for database: {}", db_name);
+        Self::new_with_block_size(db_name, BLOCK_SIZE).await
+    }
+
+    /// Create the VFS with storage split into `block_size`-byte blocks
+    ///
+    /// Fails with `BLOCK_SIZE_MISMATCH` if the database was created with a different block size.
+    pub async fn new_with_block_size(
+        db_name: &str,
+        block_size: usize,
+    ) -> Result<Self, DatabaseError> {
+        log::info!(
+            "Creating IndexedDBVFS for database: {} (block_size={})",
+            db_name,
+            block_size
+        );
 
         #[cfg(target_arch = "wasm32")]
         {
@@ -159,6 +387,7 @@ impl IndexedDBVFS {
                         set.remove(db_name);
                     });
                     log::info!("Reusing existing BlockStorage for database: {}", db_name);
+                    check_storage_block_size(&existing, db_name, block_size)?;
                     existing.reload_cache_from_global_storage();
                     return Ok(Self {
                         storage: existing,
@@ -189,7 +418,9 @@ impl IndexedDBVFS {
                     continue;
                 }
 
-                // We got the reservation - create BlockStorage
+                // We got the reservation - create BlockStorage. The guard releases it on
+                // every exit, including when this future is dropped mid-initialization.
+                let reservation = InitReservation(db_name.to_string());
                 web_sys::console::log_1(
                     &format!(
                         "[VFS] {} - ACQUIRED init reservation (attempt {})",
@@ -203,13 +434,13 @@ impl IndexedDBVFS {
                 web_sys::console::log_1(
                     &format!("[VFS] {} - START BlockStorage::new()", db_name).into(),
                 );
-                let storage_result = BlockStorage::new(db_name).await;
+                let storage_result = BlockStorage::new_with_block_size(db_name, block_size).await;
                 web_sys::console::log_1(
                     &format!("[VFS] {} - END BlockStorage::new()", db_name).into(),
                 );
 
-                let storage = storage_result?;
-                let rc = Rc::new(storage);
+                // On error the reservation is released so later opens are not stuck waiting
+                let rc = Rc::new(storage_result?);
 
                 // Try to register - CRITICAL: Keep INIT_IN_PROGRESS set until AFTER registration
                 let registration_result = STORAGE_REGISTRY.with(|reg| {
@@ -242,10 +473,7 @@ impl IndexedDBVFS {
                 });
 
                 // NOW clear the reservation flag AFTER registration is complete
-                INIT_IN_PROGRESS.with(|init| {
-                    let mut set = init.borrow_mut();
-                    set.remove(db_name);
-                });
+                drop(reservation);
 
                 return match registration_result {
                     Ok(()) => {
@@ -263,6 +491,7 @@ impl IndexedDBVFS {
                             db_name
                         );
                         drop(rc);
+                        check_storage_block_size(&winner, db_name, block_size)?;
                         winner.reload_cache_from_global_storage();
                         Ok(Self {
                             storage: winner,
@@ -284,7 +513,7 @@ impl IndexedDBVFS {
             // Native: Use Arc<Mutex<>> for thread-safe Send implementation
             // Note: Storage not actually used in native mode (direct file I/O instead)
             log::info!("Creating new BlockStorage for database: {}", db_name);
-            let storage = BlockStorage::new(db_name).await?;
+            let storage = BlockStorage::new_with_block_size(db_name, block_size).await?;
             let arc = Arc::new(Mutex::new(storage));
 
             Ok(Self {
@@ -294,6 +523,12 @@ impl IndexedDBVFS {
         }
     }
 
+    /// The BlockStorage shared by every VFS and connection for this database
+    #[cfg(target_arch = "wasm32")]
+    pub fn storage(&self) -> &Rc<BlockStorage> {
+        &self.storage
+    }
+
     pub fn register(&self, vfs_name: &str) -> Result<(), DatabaseError> {
         log::info!("Registering VFS: {}", vfs_name);
 
@@ -575,21 +810,22 @@ impl IndexedDBFile {
                 &format!("No storage found for {}", self.filename),
             ));
         };
-        let start_block = offset / BLOCK_SIZE as u64;
-        let end_block = (offset + buffer.len() as u64 - 1) / BLOCK_SIZE as u64;
+        let block_size = storage_rc.get_block_size();
+        let start_block = offset / block_size as u64;
+        let end_block = (offset + buffer.len() as u64 - 1) / block_size as u64;
         let mut bytes_read = 0;
         let mut buffer_offset = 0;
         for block_id in start_block..=end_block {
             let block_start = if block_id == start_block {
-                (offset % BLOCK_SIZE as u64) as usize
+                (offset % block_size as u64) as usize
             } else {
                 0
             };
             let block_end = if block_id == end_block {
                 let remaining = buffer.len() - buffer_offset;
-                std::cmp::min(BLOCK_SIZE, block_start + remaining)
+                std::cmp::min(block_size, block_start + remaining)
             } else {
-                BLOCK_SIZE
+                block_size
             };
             let copy_len = block_end - block_start;
             let dest_end = buffer_offset + copy_len;
@@ -623,28 +859,49 @@ impl IndexedDBFile {
             return Ok(data.len());
         }
 
-        // WAL files use dedicated WAL_STORAGE (bounded memory, max 16MB per WAL)
-        // SQLite auto-checkpoints at default ~1000 pages, but bulk inserts can exceed this
-        // 16MB allows ~4000 rows of 4KB data between checkpoints
+        // WAL files use dedicated WAL_STORAGE (bounded by wal_max_bytes, 16MB by default)
+        // Database::open lowers SQLite's auto-checkpoint threshold below the limit, but a
+        // single bulk transaction can still exceed it
         if self.is_wal {
-            const MAX_WAL_SIZE: usize = 16 * 1024 * 1024; // 16MB limit
-            return WAL_STORAGE.with(|wal| {
+            let max_bytes = wal_max_bytes(&self.filename);
+            let total_max_bytes = wal_total_max_bytes();
+            let total_bytes = wal_total_size_bytes();
+            let result = WAL_STORAGE.with(|wal| {
                 let mut wal_map = wal.borrow_mut();
                 let wal_data = wal_map
                     .entry(self.filename.clone())
                     .or_insert_with(Vec::new);
 
                 let end = offset as usize + data.len();
+                let total_after = total_bytes + end.saturating_sub(wal_data.len());
                 // Enforce max size to prevent OOM with multiple concurrent databases
-                if end > MAX_WAL_SIZE {
-                    return Err(DatabaseError::new(
+                let limit_error = if end > max_bytes {
+                    Some(DatabaseError::new(
                         "WAL_TOO_LARGE",
                         &format!(
-                            "WAL file {} exceeds {}MB limit (checkpoint required)",
-                            self.filename,
-                            MAX_WAL_SIZE / 1024 / 1024
+                            "WAL file {} exceeds wal_max_bytes limit of {} bytes (checkpoint required)",
+                            self.filename, max_bytes
                         ),
-                    ));
+                    ))
+                } else if total_after > total_max_bytes {
+                    Some(DatabaseError::new(
+                        "WAL_TOO_LARGE",
+                        &format!(
+                            "WAL file {} would bring the combined WAL size of this tab to {} bytes, over the limit of {} bytes (checkpoint required)",
+                            self.filename, total_after, total_max_bytes
+                        ),
+                    ))
+                } else {
+                    None
+                };
+                if let Some(err) = limit_error {
+                    // SQLite only sees SQLITE_IOERR_WRITE; keep the details for the caller
+                    LIMIT_ERRORS.with(|errors| {
+                        errors
+                            .borrow_mut()
+                            .insert(self.filename.clone(), err.clone());
+                    });
+                    return Err(err);
                 }
 
                 if end > wal_data.len() {
@@ -653,8 +910,24 @@ impl IndexedDBFile {
                 wal_data[offset as usize..end].copy_from_slice(data);
                 self.current_position = end as u64;
                 self.file_size = std::cmp::max(self.file_size, self.current_position);
-                Ok(data.len())
+                Ok((data.len(), total_after))
+            });
+
+            let clock = WAL_WRITE_CLOCK.with(|clock| {
+                clock.set(clock.get() + 1);
+                clock.get()
+            });
+            WAL_LAST_WRITE.with(|last| {
+                last.borrow_mut().insert(self.filename.clone(), clock);
             });
+            return result
+                .map(|(written, total_after)| {
+                    if total_after as f64 > total_max_bytes as f64 * WAL_TOTAL_CHECKPOINT_FRACTION {
+                        schedule_wal_eviction();
+                    }
+                    written
+                })
+                .inspect_err(|_| schedule_wal_eviction());
         }
 
         // KEY OPTIMIZATION: Buffer writes during transactions (absurd-sql strategy)
@@ -675,25 +948,26 @@ impl IndexedDBFile {
                     &format!("No storage found for {}", self.filename),
                 ));
             };
-            let start_block = offset / BLOCK_SIZE as u64;
-            let end_block = (offset + data.len() as u64 - 1) / BLOCK_SIZE as u64;
+            let block_size = storage_rc.get_block_size();
+            let start_block = offset / block_size as u64;
+            let end_block = (offset + data.len() as u64 - 1) / block_size as u64;
             let mut bytes_written = 0;
             let mut data_offset = 0;
             for block_id in start_block..=end_block {
                 let block_start = if block_id == start_block {
-                    (offset % BLOCK_SIZE as u64) as usize
+                    (offset % block_size as u64) as usize
                 } else {
                     0
                 };
                 let remaining_data = data.len() - data_offset;
-                let available_space = BLOCK_SIZE - block_start;
+                let available_space = block_size - block_start;
                 let copy_len = std::cmp::min(remaining_data, available_space);
                 let block_end = block_start + copy_len;
                 let src_end = data_offset + copy_len;
 
-                if src_end <= data.len() && block_end <= BLOCK_SIZE {
+                if src_end <= data.len() && block_end <= block_size {
                     // Check if this is a full block write (no need to read existing data)
-                    let is_full_block_write = block_start == 0 && copy_len == BLOCK_SIZE;
+                    let is_full_block_write = block_start == 0 && copy_len == block_size;
 
                     let mut block_data = if is_full_block_write {
                         // Full block write - just use new data directly
@@ -713,9 +987,9 @@ impl IndexedDBFile {
                         block_data
                     };
 
-                    // Ensure block is exactly BLOCK_SIZE (pad with zeros if needed)
-                    if block_data.len() < BLOCK_SIZE {
-                        block_data.resize(BLOCK_SIZE, 0);
+                    // Ensure block is exactly block_size (pad with zeros if needed)
+                    if block_data.len() < block_size {
+                        block_data.resize(block_size, 0);
                     }
 
                     self.write_buffer.insert(block_id, block_data);
@@ -736,25 +1010,26 @@ impl IndexedDBFile {
                 &format!("No storage found for {}", self.filename),
             ));
         };
-        let start_block = offset / BLOCK_SIZE as u64;
-        let end_block = (offset + data.len() as u64 - 1) / BLOCK_SIZE as u64;
+        let block_size = storage_rc.get_block_size();
+        let start_block = offset / block_size as u64;
+        let end_block = (offset + data.len() as u64 - 1) / block_size as u64;
         let mut bytes_written = 0;
         let mut data_offset = 0;
         for block_id in start_block..=end_block {
             // Read existing block data
             let mut block_data = storage_rc.read_block_sync(block_id)?;
             let block_start = if block_id == start_block {
-                (offset % BLOCK_SIZE as u64) as usize
+                (offset % block_size as u64) as usize
             } else {
                 0
             };
             let remaining_data = data.len() - data_offset;
-            let available_space = BLOCK_SIZE - block_start;
+            let available_space = block_size - block_start;
             let copy_len = std::cmp::min(remaining_data, available_space);
             let block_end = block_start + copy_len;
             let src_end = data_offset + copy_len;
 
-            if src_end <= data.len() && block_end <= BLOCK_SIZE {
+            if src_end <= data.len() && block_end <= block_size {
                 // Debug: Log the write operation details
                 #[cfg(target_arch = "wasm32")]
                 {
@@ -882,7 +1157,11 @@ unsafe extern "C" fn x_open_simple(
                         0
                     } else {
                         let max_block_id = db.keys().max().copied().unwrap_or(0);
-                        (max_block_id + 1) * 4096
+                        let block_size = db
+                            .get(&max_block_id)
+                            .map(|block| block.len())
+                            .unwrap_or(BLOCK_SIZE);
+                        (max_block_id + 1) * block_size as u64
                     }
                 } else {
                     0 // New database, size is 0
@@ -1046,8 +1325,11 @@ unsafe extern "C" fn x_open(
                         .unwrap_or(0)
                 });
 
-                // SQLite uses 4KB blocks, so file_size is (max_block_id + 1) * 4096
-                let calculated_size = (max_block_id + 1) * 4096;
+                // file_size is (max_block_id + 1) * block_size
+                let block_size = try_get_storage_from_registry(&db_name)
+                    .map(|storage| storage.get_block_size())
+                    .unwrap_or(BLOCK_SIZE);
+                let calculated_size = (max_block_id + 1) * block_size as u64;
                 (*vf).handle.file_size = calculated_size;
 
                 #[cfg(target_arch = "wasm32")]
@@ -1384,11 +1666,14 @@ unsafe extern "C" fn x_read(
 ) -> c_int {
     let vf: *mut VfsFile = unsafe { file_from_ptr(p_file) };
     let slice = unsafe { std::slice::from_raw_parts_mut(buf as *mut u8, amt as usize) };
+    let block_size = try_get_storage_from_registry(unsafe { &(*vf).handle.filename })
+        .map(|storage| storage.get_block_size())
+        .unwrap_or(BLOCK_SIZE) as i64;
 
     // CRITICAL DEBUG: Log ALL reads during database open
     #[cfg(target_arch = "wasm32")]
     {
-        let block_id = offset / 4096;
+        let block_id = offset / block_size;
         let page_id = offset / 4096;
         web_sys::console::log_1(
             &format!(
@@ -1405,7 +1690,7 @@ unsafe extern "C" fn x_read(
             // CRITICAL DEBUG: Check what data was actually read
             #[cfg(target_arch = "wasm32")]
             {
-                let block_id = offset / 4096;
+                let block_id = offset / block_size;
                 web_sys::console::log_1(
                     &format!(
                         "[VFS x_read] SUCCESS - read {} bytes from block {}",
@@ -1460,7 +1745,7 @@ unsafe extern "C" fn x_read(
         Err(e) => {
             #[cfg(target_arch = "wasm32")]
             {
-                let block_id = offset / 4096;
+                let block_id = offset / block_size;
                 let page_id = offset / 4096;
                 web_sys::console::log_1(
                     &format!(
@@ -1507,6 +1792,14 @@ unsafe extern "C" fn x_write(
             vfs_log!("VFS x_write: SUCCESS wrote {} bytes", _n);
             sqlite_wasm_rs::SQLITE_OK
         }
+        Err(e) if e.code == "QUOTA_EXCEEDED" => {
+            // SQLite only sees SQLITE_FULL; keep the details for the caller
+            let filename = unsafe { (*vf).handle.filename.clone() };
+            LIMIT_ERRORS.with(|errors| {
+                errors.borrow_mut().insert(filename, e);
+            });
+            sqlite_wasm_rs::SQLITE_FULL
+        }
         Err(_e) => {
             vfs_log!("VFS x_write: ERROR {:?}", _e);
             sqlite_wasm_rs::SQLITE_IOERR_WRITE
@@ -1847,6 +2140,23 @@ unsafe extern "C" fn x_file_control(
             }
             sqlite_wasm_rs::SQLITE_OK
         }
+        // SQLITE_FCNTL_VFSNAME - report which VFS implementation owns this file
+        // The string must come from sqlite3_malloc; the caller frees it
+        12 => {
+            if !_p_arg.is_null() {
+                let name = VFS_IMPLEMENTATION_NAME.as_bytes();
+                unsafe {
+                    let buf = sqlite_wasm_rs::sqlite3_malloc64(name.len() as u64 + 1) as *mut u8;
+                    if buf.is_null() {
+                        return sqlite_wasm_rs::SQLITE_NOMEM;
+                    }
+                    std::ptr::copy_nonoverlapping(name.as_ptr(), buf, name.len());
+                    *buf.add(name.len()) = 0;
+                    *(_p_arg as *mut *mut std::os::raw::c_char) = buf as *mut _;
+                }
+            }
+            sqlite_wasm_rs::SQLITE_OK
+        }
         // SQLITE_FCNTL_SIZE_HINT - SQLite is providing a size hint
         5 => sqlite_wasm_rs::SQLITE_OK,
         // SQLITE_FCNTL_CHUNK_SIZE - SQLite is setting chunk size
@@ -1915,6 +2225,21 @@ thread_local! {
     // This is separate from BlockStorage to control memory usage
     static WAL_STORAGE: std::cell::RefCell<std::collections::HashMap<String, Vec<u8>>> =
         std::cell::RefCell::new(std::collections::HashMap::new());
+    // Per-database WAL size limits configured through wal_max_bytes
+    static WAL_LIMITS: std::cell::RefCell<std::collections::HashMap<String, usize>> =
+        std::cell::RefCell::new(std::collections::HashMap::new());
+    // Most recent WAL_TOO_LARGE or QUOTA_EXCEEDED error per database, reported in place
+    // of SQLite's I/O or disk-full error
+    static LIMIT_ERRORS: std::cell::RefCell<std::collections::HashMap<String, DatabaseError>> =
+        std::cell::RefCell::new(std::collections::HashMap::new());
+    // Cap on the combined size of every WAL in WAL_STORAGE
+    static WAL_TOTAL_MAX_BYTES: std::cell::Cell<usize> =
+        const { std::cell::Cell::new(DEFAULT_WAL_TOTAL_MAX_BYTES) };
+    // Logical clock value of each WAL's most recent write, for least-recently-written eviction
+    static WAL_LAST_WRITE: std::cell::RefCell<std::collections::HashMap<String, u64>> =
+        std::cell::RefCell::new(std::collections::HashMap::new());
+    static WAL_WRITE_CLOCK: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+    static WAL_EVICTION_SCHEDULED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -0,0 +1,117 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database};
+
+/// A JOIN reads from the main database and an attached one in a single query
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_attach_and_join_across_databases() {
+    let mut orders = Database::new_wasm("attach_orders".to_string())
+        .await
+        .unwrap();
+    orders.allow_non_leader_writes(true).await.unwrap();
+    for sql in [
+        "DROP TABLE IF EXISTS orders",
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, customer_id INTEGER, total REAL)",
+        "INSERT INTO orders VALUES (1, 10, 9.5), (2, 11, 20.0), (3, 10, 1.5)",
+    ] {
+        orders.execute_internal(sql).await.unwrap();
+    }
+    orders.sync().await.unwrap();
+
+    let mut customers = Database::new_wasm("attach_customers".to_string())
+        .await
+        .unwrap();
+    customers.allow_non_leader_writes(true).await.unwrap();
+    for sql in [
+        "DROP TABLE IF EXISTS customers",
+        "CREATE TABLE customers (id INTEGER PRIMARY KEY, name TEXT)",
+        "INSERT INTO customers VALUES (10, 'Ada'), (11, 'Lin')",
+    ] {
+        customers.execute_internal(sql).await.unwrap();
+    }
+    customers.sync().await.unwrap();
+
+    orders
+        .attach_internal("attach_customers", "crm")
+        .await
+        .unwrap();
+    assert_eq!(orders.attached_aliases(), vec!["crm".to_string()]);
+
+    let result = orders
+        .execute_internal(
+            "SELECT c.name, SUM(o.total) FROM orders o \
+             JOIN crm.customers c ON c.id = o.customer_id \
+             GROUP BY c.name ORDER BY c.name",
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 2);
+    assert_eq!(
+        result.rows[0].values[0],
+        ColumnValue::Text("Ada".to_string())
+    );
+    assert_eq!(result.rows[0].values[1], ColumnValue::Real(11.0));
+    assert_eq!(
+        result.rows[1].values[0],
+        ColumnValue::Text("Lin".to_string())
+    );
+
+    // Writes through the alias land in the attached database
+    orders
+        .execute("INSERT INTO crm.customers VALUES (12, 'Sam')")
+        .await
+        .unwrap();
+    orders.detach_internal("crm").await.unwrap();
+    assert!(orders.attached_aliases().is_empty());
+
+    let err = orders
+        .execute_internal("SELECT * FROM crm.customers")
+        .await
+        .expect_err("alias is gone after detach");
+    assert!(err.message.contains("no such table"), "{}", err.message);
+
+    let count = customers
+        .execute_internal("SELECT COUNT(*) FROM customers")
+        .await
+        .unwrap();
+    assert_eq!(count.rows[0].values[0], ColumnValue::Integer(3));
+
+    orders.close().await.unwrap();
+    customers.close().await.unwrap();
+}
+
+/// Aliases must be unique and detaching an unknown alias fails
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_attach_alias_errors() {
+    let mut main = Database::new_wasm("attach_alias_main".to_string())
+        .await
+        .unwrap();
+    main.allow_non_leader_writes(true).await.unwrap();
+    main.sync().await.unwrap();
+    main.attach_internal("attach_alias_other", "other")
+        .await
+        .unwrap();
+
+    let err = main
+        .attach_internal("attach_alias_other", "other")
+        .await
+        .expect_err("duplicate alias");
+    assert_eq!(err.code, "ALREADY_ATTACHED");
+
+    let err = main
+        .detach_internal("missing")
+        .await
+        .expect_err("unknown alias");
+    assert_eq!(err.code, "NOT_ATTACHED");
+
+    main.detach_internal("other").await.unwrap();
+    main.close().await.unwrap();
+}
@@ -0,0 +1,145 @@
+//! Tests for restricting statements with Database::set_authorizer
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{Database, DatabaseConfig, Persistence};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Only SELECTs reading `products` get through
+fn products_only() -> js_sys::Function {
+    js_sys::Function::new_with_args(
+        "action, table",
+        "if (action === 21) return 'ALLOW'; \
+         if (action === 20 && table === 'products') return 'ALLOW'; \
+         return 'DENY';",
+    )
+}
+
+#[wasm_bindgen_test]
+async fn test_authorizer_restricts_statements() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "authorizer_restricts.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    db.execute_internal("CREATE TABLE products (id INTEGER, name TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE secrets (token TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO products VALUES (1, 'lamp')")
+        .await
+        .unwrap();
+    // Cached before the authorizer exists; must be authorized again
+    db.execute_internal("SELECT token FROM secrets")
+        .await
+        .unwrap();
+
+    db.set_authorizer_internal(Some(products_only())).unwrap();
+
+    let result = db
+        .execute_internal("SELECT name FROM products")
+        .await
+        .expect("allowed select");
+    assert_eq!(result.rows.len(), 1);
+    assert!(
+        db.execute_internal("SELECT token FROM secrets")
+            .await
+            .is_err()
+    );
+    assert!(
+        db.execute_internal("INSERT INTO products VALUES (2, 'desk')")
+            .await
+            .is_err()
+    );
+
+    // Removing the authorizer lifts the restrictions
+    db.set_authorizer_internal(None).unwrap();
+    db.execute_internal("SELECT token FROM secrets")
+        .await
+        .unwrap();
+    db.close().await.unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn test_authorizer_ignore_and_replace() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "authorizer_ignore.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    db.execute_internal("CREATE TABLE products (id INTEGER, name TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE secrets (token TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO products VALUES (1, 'lamp')")
+        .await
+        .unwrap();
+
+    // IGNORE on a column read turns the value into NULL
+    let ignore_names = js_sys::Function::new_with_args(
+        "action, table, column",
+        "return action === 20 && column === 'name' ? 'IGNORE' : 'ALLOW';",
+    );
+    db.set_authorizer_internal(Some(ignore_names)).unwrap();
+    let result = db
+        .execute_internal("SELECT id, name FROM products")
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[1], absurder_sql::ColumnValue::Null);
+
+    // A throwing callback denies
+    let throws = js_sys::Function::new_no_args("throw new Error('nope');");
+    db.set_authorizer_internal(Some(throws)).unwrap();
+    assert!(db.execute_internal("SELECT 1").await.is_err());
+
+    db.close().await.unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn test_authorizer_survives_subscribe() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "authorizer_subscribe.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    db.execute_internal("CREATE TABLE products (id INTEGER, name TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE secrets (token TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO products VALUES (1, 'lamp')")
+        .await
+        .unwrap();
+    db.set_authorizer_internal(Some(products_only())).unwrap();
+
+    // Subscribing prepares the query under its own authorizer to find the tables it reads
+    let id = db
+        .subscribe_internal(
+            "SELECT name FROM products",
+            vec![],
+            js_sys::Function::new_no_args(""),
+        )
+        .expect("allowed subscription");
+    assert!(
+        db.execute_internal("SELECT token FROM secrets")
+            .await
+            .is_err(),
+        "the app's authorizer must still deny after subscribe"
+    );
+
+    db.unsubscribe(id);
+    db.close().await.unwrap();
+}
@@ -38,6 +38,7 @@ async fn test_custom_database_config() {
         auto_vacuum: Some(false),
         journal_mode: Some("DELETE".to_string()),
         max_export_size_bytes: Some(2 * 1024 * 1024 * 1024),
+        ..Default::default()
     };
 
     assert_eq!(config.name, "test.db");
@@ -164,3 +165,196 @@ async fn test_database_creation() {
         }
     }
 }
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_error_carries_sqlite_code() {
+    let _tmp = setup_fs_base();
+    let config = DatabaseConfig {
+        name: "test_sqlite_error_code.db".to_string(),
+        ..Default::default()
+    };
+    let mut db = SqliteIndexedDB::new(config).await.expect("create db");
+
+    db.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT UNIQUE)")
+        .await
+        .expect("create table");
+    db.execute("INSERT INTO users (email) VALUES ('a@example.com')")
+        .await
+        .expect("first insert");
+
+    let err = db
+        .execute("INSERT INTO users (email) VALUES ('a@example.com')")
+        .await
+        .expect_err("duplicate email should fail");
+    assert_eq!(err.code, "SQLITE_ERROR");
+    assert_eq!(err.sqlite_code, Some(2067), "SQLITE_CONSTRAINT_UNIQUE");
+
+    let err = db
+        .execute("SELEC 1")
+        .await
+        .expect_err("syntax error should fail");
+    assert_eq!(err.sqlite_code, Some(1), "SQLITE_ERROR");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_max_result_rows() {
+    let _tmp = setup_fs_base();
+    let config = DatabaseConfig {
+        name: "test_sqlite_max_result_rows.db".to_string(),
+        max_result_rows: Some(10),
+        ..Default::default()
+    };
+    let mut db = SqliteIndexedDB::new(config).await.expect("create db");
+
+    let within = db
+        .execute("WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 10) SELECT x FROM n")
+        .await
+        .expect("result at the limit");
+    assert_eq!(within.rows.len(), 10);
+
+    let err = db
+        .execute("WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT x FROM n")
+        .await
+        .expect_err("unbounded result should be stopped");
+    assert_eq!(err.code, "RESULT_TOO_LARGE");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_invalid_utf8_text_handling() {
+    let _tmp = setup_fs_base();
+    // CAST keeps the raw bytes but stores them with TEXT affinity
+    let setup = [
+        "DROP TABLE IF EXISTS legacy",
+        "CREATE TABLE legacy (v TEXT)",
+        "INSERT INTO legacy VALUES (CAST(X'48FF69' AS TEXT))",
+    ];
+
+    let mut outcomes = Vec::new();
+    for (suffix, handling) in [
+        ("lossy", InvalidUtf8Handling::Lossy),
+        ("blob", InvalidUtf8Handling::Blob),
+        ("error", InvalidUtf8Handling::Error),
+    ] {
+        let config = DatabaseConfig {
+            name: format!("test_sqlite_invalid_utf8_{}.db", suffix),
+            invalid_utf8: Some(handling),
+            ..Default::default()
+        };
+        let mut db = SqliteIndexedDB::new(config).await.expect("create db");
+        for sql in setup {
+            db.execute(sql).await.expect("setup");
+        }
+        outcomes.push(db.execute("SELECT v, typeof(v) FROM legacy").await);
+    }
+
+    let lossy = outcomes[0].as_ref().expect("lossy read");
+    assert_eq!(
+        lossy.rows[0].values[0],
+        ColumnValue::Text("H\u{FFFD}i".to_string())
+    );
+    assert_eq!(
+        lossy.rows[0].values[1],
+        ColumnValue::Text("text".to_string())
+    );
+
+    let blob = outcomes[1].as_ref().expect("blob read");
+    assert_eq!(
+        blob.rows[0].values[0],
+        ColumnValue::Blob(vec![0x48, 0xFF, 0x69])
+    );
+
+    let err = outcomes[2].as_ref().expect_err("invalid UTF-8 should fail");
+    assert_eq!(err.code, "INVALID_UTF8");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_typed_row_getters() {
+    let _tmp = setup_fs_base();
+    let config = DatabaseConfig {
+        name: "test_sqlite_typed_row_getters.db".to_string(),
+        ..Default::default()
+    };
+    let mut db = SqliteIndexedDB::new(config).await.expect("create db");
+
+    let result = db
+        .execute("SELECT 42 AS id, 'alice' AS name, 1.5 AS score, X'0102' AS data, NULL AS note")
+        .await
+        .expect("select");
+    let id = result.column_index("id").expect("id column");
+    let name = result.column_index("name").expect("name column");
+    let score = result.column_index("score").expect("score column");
+    let data = result.column_index("data").expect("data column");
+    let note = result.column_index("note").expect("note column");
+    assert_eq!(result.column_index("missing"), None);
+
+    let row = &result.rows[0];
+    assert_eq!(row.get_i64(id).unwrap(), 42);
+    assert_eq!(row.get_text(name).unwrap(), "alice");
+    assert_eq!(row.get_f64(score).unwrap(), 1.5);
+    assert_eq!(row.get_f64(id).unwrap(), 42.0);
+    assert_eq!(row.get_blob(data).unwrap(), &[1u8, 2][..]);
+    assert!(row.is_null(note).unwrap());
+
+    assert_eq!(row.get_i64(name).unwrap_err().code, "TYPE_MISMATCH");
+    assert_eq!(row.get_text(note).unwrap_err().code, "TYPE_MISMATCH");
+    assert_eq!(row.get_i64(99).unwrap_err().code, "COLUMN_OUT_OF_RANGE");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_query_result_scalar() {
+    let _tmp = setup_fs_base();
+    let config = DatabaseConfig {
+        name: "test_sqlite_query_result_scalar.db".to_string(),
+        ..Default::default()
+    };
+    let mut db = SqliteIndexedDB::new(config).await.expect("create db");
+
+    let count = db.execute("SELECT 3").await.expect("select");
+    assert_eq!(count.scalar(true).unwrap(), ColumnValue::Integer(3));
+
+    let empty = db.execute("SELECT 1 WHERE 0").await.expect("select");
+    assert_eq!(empty.scalar(true).unwrap(), ColumnValue::Null);
+
+    let wide = db.execute("SELECT 'a', 'b'").await.expect("select");
+    assert_eq!(wide.scalar(false).unwrap(), ColumnValue::Text("a".into()));
+    assert_eq!(wide.scalar(true).unwrap_err().code, "MULTIPLE_COLUMNS");
+
+    let tall = db
+        .execute("SELECT 1 UNION ALL SELECT 2")
+        .await
+        .expect("select");
+    assert_eq!(tall.scalar(false).unwrap(), ColumnValue::Integer(1));
+    assert_eq!(tall.scalar(true).unwrap_err().code, "MULTIPLE_ROWS");
+}
+
+#[test]
+fn test_large_integer_serializes_exactly() {
+    let value = ColumnValue::Integer(9_007_199_254_740_993);
+    let json = serde_json::to_string(&value).expect("serialize");
+    assert_eq!(json, r#"{"type":"Integer","value":9007199254740993}"#);
+    let back: ColumnValue = serde_json::from_str(&json).expect("deserialize");
+    assert_eq!(back, value);
+
+    let small = serde_json::to_string(&ColumnValue::Integer(-42)).expect("serialize");
+    assert_eq!(small, r#"{"type":"Integer","value":-42}"#);
+}
+
+#[test]
+fn test_json_column_value_serializes_tagged() {
+    let value = ColumnValue::Json(serde_json::json!({ "tags": ["a"], "n": 1 }));
+    let json = serde_json::to_string(&value).expect("serialize");
+    assert_eq!(json, r#"{"type":"Json","value":{"n":1,"tags":["a"]}}"#);
+    let back: ColumnValue = serde_json::from_str(&json).expect("deserialize");
+    assert_eq!(back, value);
+
+    // Stored as TEXT holding the document
+    assert_eq!(
+        ColumnValue::from_rusqlite_value(&value.to_rusqlite_value()),
+        ColumnValue::Text(r#"{"n":1,"tags":["a"]}"#.to_string())
+    );
+}
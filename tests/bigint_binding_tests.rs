@@ -0,0 +1,108 @@
+//! Tests for binding and reading integers beyond the JS safe range
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database, DatabaseConfig, Persistence};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// 2^53 + 1, the first integer a JS number cannot hold
+const UNSAFE: i64 = 9007199254740993;
+
+/// A BigInt parameter is stored as an exact INTEGER rather than NULL
+#[wasm_bindgen_test]
+async fn test_bind_bigint_parameter() {
+    let config = DatabaseConfig {
+        name: "bigint_bind".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE ids (id INTEGER PRIMARY KEY, big INTEGER)")
+        .await
+        .unwrap();
+    db.execute_with_params_internal(
+        "INSERT INTO ids (id, big) VALUES (1, ?)",
+        &[ColumnValue::BigInt(UNSAFE.to_string())],
+    )
+    .await
+    .unwrap();
+
+    let stored = db
+        .query_scalar_internal("SELECT typeof(big) || ':' || big FROM ids", &[], true)
+        .await
+        .unwrap();
+    assert_eq!(stored, ColumnValue::Text(format!("integer:{}", UNSAFE)));
+    let value = db
+        .query_scalar_internal("SELECT big FROM ids", &[], true)
+        .await
+        .unwrap();
+    assert_eq!(value, ColumnValue::Integer(UNSAFE));
+}
+
+/// A BigInt wider than 64 bits is rejected instead of truncated
+#[wasm_bindgen_test]
+async fn test_bind_bigint_overflow() {
+    let config = DatabaseConfig {
+        name: "bigint_overflow".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE ids (id INTEGER PRIMARY KEY, big INTEGER)")
+        .await
+        .unwrap();
+    let err = db
+        .execute_with_params_internal(
+            "INSERT INTO ids (id, big) VALUES (1, ?)",
+            &[ColumnValue::BigInt(
+                "123456789012345678901234567890".to_string(),
+            )],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "INTEGER_OVERFLOW");
+
+    let count = db
+        .query_scalar_internal("SELECT count(*) FROM ids", &[], true)
+        .await
+        .unwrap();
+    assert_eq!(count, ColumnValue::Integer(0));
+}
+
+/// Values outside the safe range read back as exact integers that reach JS as BigInt
+#[wasm_bindgen_test]
+async fn test_read_large_integers_as_bigint() {
+    let config = DatabaseConfig {
+        name: "bigint_read".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE ids (id INTEGER PRIMARY KEY, big INTEGER)")
+        .await
+        .unwrap();
+    db.execute_with_params_internal(
+        "INSERT INTO ids (id, big) VALUES (1, ?), (2, ?)",
+        &[ColumnValue::Integer(UNSAFE), ColumnValue::Integer(42)],
+    )
+    .await
+    .unwrap();
+
+    let result = db
+        .execute_internal("SELECT big FROM ids ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Integer(UNSAFE));
+    assert_eq!(result.rows[1].values[0], ColumnValue::Integer(42));
+
+    let json = serde_json::to_string(&result.rows[0].values[0]).unwrap();
+    assert!(json.contains("9007199254740993"), "{}", json);
+    let js = serde_wasm_bindgen::to_value(&result.rows[0].values[0]).unwrap();
+    let value = js_sys::Reflect::get(&js, &"value".into()).unwrap();
+    assert_eq!(
+        value,
+        wasm_bindgen::JsValue::from(js_sys::BigInt::from(UNSAFE))
+    );
+}
@@ -0,0 +1,118 @@
+//! Block compression tests: blocks are compressed when persisted to IndexedDB and
+//! decompressed on restore, with compressed and raw blocks coexisting in one database.
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::storage::compression::{CompressionKind, set_compression};
+use absurder_sql::storage::vfs_sync;
+use absurder_sql::storage::wasm_indexeddb::restore_from_indexeddb_force;
+use absurder_sql::storage::{BLOCK_SIZE, BlockStorage};
+use absurder_sql::utils::normalize_db_name;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Pseudo-random bytes that do not compress
+fn noise_block() -> Vec<u8> {
+    let mut state: u32 = 0x1234_5678;
+    (0..BLOCK_SIZE)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .collect()
+}
+
+fn compressed_flag(db_name: &str, block_id: u64) -> Option<bool> {
+    vfs_sync::with_global_metadata(|gm| {
+        gm.borrow()
+            .get(db_name)
+            .and_then(|m| m.get(&block_id))
+            .map(|m| m.compressed)
+    })
+}
+
+/// Compressed and incompressible blocks both survive a restore from IndexedDB
+#[wasm_bindgen_test]
+async fn test_mixed_blocks_roundtrip_through_indexeddb() {
+    let db_name = "block_compression_roundtrip";
+    let normalized_name = normalize_db_name(db_name);
+    set_compression(&normalized_name, CompressionKind::Lz4);
+
+    let mut storage = BlockStorage::new(db_name).await.expect("create storage");
+    let text_block = storage.allocate_block().await.expect("allocate text block");
+    let noise = storage
+        .allocate_block()
+        .await
+        .expect("allocate noise block");
+
+    let text_data: Vec<u8> = b"compressible text "
+        .iter()
+        .copied()
+        .cycle()
+        .take(BLOCK_SIZE)
+        .collect();
+    let noise_data = noise_block();
+    storage
+        .write_block(text_block, text_data.clone())
+        .await
+        .expect("write text block");
+    storage
+        .write_block(noise, noise_data.clone())
+        .await
+        .expect("write noise block");
+    storage.sync_async().await.expect("sync");
+
+    assert_eq!(compressed_flag(&normalized_name, text_block), Some(true));
+    assert_eq!(compressed_flag(&normalized_name, noise), Some(false));
+
+    // Drop in-memory copies so the blocks must come back from IndexedDB
+    vfs_sync::with_global_storage(|gs| {
+        gs.borrow_mut().remove(&normalized_name);
+    });
+    restore_from_indexeddb_force(&normalized_name)
+        .await
+        .expect("restore");
+
+    let restored = vfs_sync::with_global_storage(|gs| gs.borrow().get(&normalized_name).cloned())
+        .expect("blocks restored");
+    assert_eq!(restored.get(&text_block), Some(&text_data));
+    assert_eq!(restored.get(&noise), Some(&noise_data));
+
+    set_compression(&normalized_name, CompressionKind::None);
+}
+
+/// Blocks written without compression still load once compression is enabled
+#[wasm_bindgen_test]
+async fn test_enabling_compression_keeps_existing_blocks_readable() {
+    let db_name = "block_compression_enable_later";
+    let normalized_name = normalize_db_name(db_name);
+    set_compression(&normalized_name, CompressionKind::None);
+
+    let mut storage = BlockStorage::new(db_name).await.expect("create storage");
+    let block = storage.allocate_block().await.expect("allocate block");
+    let data = vec![0x42u8; BLOCK_SIZE];
+    storage
+        .write_block(block, data.clone())
+        .await
+        .expect("write block");
+    storage.sync_async().await.expect("sync");
+    assert_eq!(compressed_flag(&normalized_name, block), Some(false));
+
+    set_compression(&normalized_name, CompressionKind::Lz4);
+    vfs_sync::with_global_storage(|gs| {
+        gs.borrow_mut().remove(&normalized_name);
+    });
+    restore_from_indexeddb_force(&normalized_name)
+        .await
+        .expect("restore");
+
+    let restored = vfs_sync::with_global_storage(|gs| {
+        gs.borrow()
+            .get(&normalized_name)
+            .and_then(|db| db.get(&block).cloned())
+    });
+    assert_eq!(restored, Some(data));
+
+    set_compression(&normalized_name, CompressionKind::None);
+}
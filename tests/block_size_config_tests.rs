@@ -0,0 +1,73 @@
+// Configurable block size tests for BlockStorage
+
+#![cfg(not(target_arch = "wasm32"))]
+use absurder_sql::storage::{BLOCK_SIZE, BlockStorage};
+use serial_test::serial;
+use tempfile::TempDir;
+#[path = "common/mod.rs"]
+mod common;
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_invalid_block_sizes_rejected() {
+    let tmp = TempDir::new().expect("tempdir");
+    common::set_var("ABSURDERSQL_FS_BASE", tmp.path());
+
+    for size in [0, 256, 3000, 131072] {
+        let err = BlockStorage::new_with_block_size("test_block_size_invalid", size)
+            .await
+            .err()
+            .expect("invalid block size should be rejected");
+        assert_eq!(err.code, "INVALID_BLOCK_SIZE", "size {}", size);
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_custom_block_size_roundtrip() {
+    let tmp = TempDir::new().expect("tempdir");
+    common::set_var("ABSURDERSQL_FS_BASE", tmp.path());
+    let mut storage = BlockStorage::new_with_block_size("test_block_size_8k", 8192)
+        .await
+        .expect("create storage");
+    assert_eq!(storage.get_block_size(), 8192);
+
+    let data = vec![7u8; 8192];
+    storage
+        .write_block(1, data.clone())
+        .await
+        .expect("write 8KB block");
+    assert_eq!(storage.read_block(1).await.expect("read block"), data);
+
+    // Default-sized blocks no longer fit
+    let err = storage
+        .write_block(2, vec![1u8; BLOCK_SIZE])
+        .await
+        .expect_err("4KB block should be rejected");
+    assert_eq!(err.code, "INVALID_BLOCK_SIZE");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_reading_with_different_block_size_fails() {
+    let tmp = TempDir::new().expect("tempdir");
+    common::set_var("ABSURDERSQL_FS_BASE", tmp.path());
+    let db = "test_block_size_mismatch";
+
+    let mut storage = BlockStorage::new_with_block_size(db, 8192)
+        .await
+        .expect("create storage");
+    storage
+        .write_block(1, vec![3u8; 8192])
+        .await
+        .expect("write block");
+    storage.sync().await.expect("sync");
+    drop(storage);
+
+    let reopened = BlockStorage::new(db).await.expect("reopen storage");
+    let err = reopened
+        .read_block(1)
+        .await
+        .expect_err("block written with 8KB size must not read as 4KB");
+    assert_eq!(err.code, "BLOCK_SIZE_MISMATCH");
+}
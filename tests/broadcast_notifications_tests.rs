@@ -159,6 +159,63 @@ async fn test_multiple_notification_types() {
     web_sys::console::log_1(&"Multiple notification types test passed".into());
 }
 
+/// Without BroadcastChannel, notifications travel through localStorage storage events
+#[wasm_bindgen_test]
+async fn test_local_storage_fallback_without_broadcast_channel() {
+    use absurder_sql::storage::broadcast_notifications::broadcast_channel_supported;
+
+    let db_name = "test_broadcast_fallback_db";
+    let global = js_sys::global();
+    let ctor = js_sys::Reflect::get(&global, &"BroadcastChannel".into()).unwrap();
+    js_sys::Reflect::set(&global, &"BroadcastChannel".into(), &JsValue::UNDEFINED).unwrap();
+    assert!(!broadcast_channel_supported());
+
+    let received = Rc::new(RefCell::new(Vec::<String>::new()));
+    let received_clone = received.clone();
+    let callback = Closure::wrap(Box::new(move |notification: JsValue| {
+        let kind = js_sys::Reflect::get(&notification, &"type".into())
+            .unwrap()
+            .as_string()
+            .unwrap();
+        received_clone.borrow_mut().push(kind);
+    }) as Box<dyn FnMut(JsValue)>);
+    register_change_listener(db_name, callback.as_ref().unchecked_ref())
+        .expect("Should fall back to storage events");
+
+    // Sending writes the message and removes it again
+    send_change_notification(&BroadcastNotification::DataChanged {
+        db_name: db_name.to_string(),
+        timestamp: 1,
+    })
+    .expect("Should send through localStorage");
+    let storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+    let key = format!("absurder_channel:datasync_changes_{}", db_name);
+    assert!(storage.get_item(&key).unwrap().is_none());
+
+    // storage events only fire in other tabs, so deliver one as another tab would
+    let init = js_sys::Object::new();
+    js_sys::Reflect::set(&init, &"key".into(), &key.clone().into()).unwrap();
+    js_sys::Reflect::set(
+        &init,
+        &"newValue".into(),
+        &r#"{"nonce":0.5,"data":{"type":"SchemaChanged","db_name":"test_broadcast_fallback_db","timestamp":2}}"#.into(),
+    )
+    .unwrap();
+    let event_ctor: js_sys::Function = js_sys::Reflect::get(&global, &"StorageEvent".into())
+        .unwrap()
+        .unchecked_into();
+    let event: web_sys::Event =
+        js_sys::Reflect::construct(&event_ctor, &js_sys::Array::of2(&"storage".into(), &init))
+            .unwrap()
+            .unchecked_into();
+    web_sys::window().unwrap().dispatch_event(&event).unwrap();
+
+    js_sys::Reflect::set(&global, &"BroadcastChannel".into(), &ctor).unwrap();
+    callback.forget();
+
+    assert_eq!(received.borrow().as_slice(), &["SchemaChanged".to_string()]);
+}
+
 // Helper function for async sleep
 async fn sleep_ms(ms: i32) {
     let promise = js_sys::Promise::new(&mut |resolve, _| {
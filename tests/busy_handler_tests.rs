@@ -0,0 +1,112 @@
+//! Tests for setBusyHandler
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database, DatabaseConfig, Persistence};
+use std::ffi::CString;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+async fn busy_timeout(db: &mut Database) -> ColumnValue {
+    db.query_scalar_internal("PRAGMA busy_timeout", &[], true)
+        .await
+        .unwrap()
+}
+
+/// A second connection to the same shared memdb file, outside the connection pool
+fn open_raw(name: &str) -> *mut sqlite_wasm_rs::sqlite3 {
+    let path = CString::new(format!("/{}", name)).unwrap();
+    let vfs = CString::new("memdb").unwrap();
+    let mut db = std::ptr::null_mut();
+    let rc = unsafe {
+        sqlite_wasm_rs::sqlite3_open_v2(
+            path.as_ptr(),
+            &mut db,
+            sqlite_wasm_rs::SQLITE_OPEN_READWRITE | sqlite_wasm_rs::SQLITE_OPEN_CREATE,
+            vfs.as_ptr(),
+        )
+    };
+    assert_eq!(rc, sqlite_wasm_rs::SQLITE_OK, "open raw connection");
+    db
+}
+
+fn exec_raw(db: *mut sqlite_wasm_rs::sqlite3, sql: &str) {
+    let sql = CString::new(sql).unwrap();
+    let rc = unsafe {
+        sqlite_wasm_rs::sqlite3_exec(
+            db,
+            sql.as_ptr(),
+            None,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    assert_eq!(rc, sqlite_wasm_rs::SQLITE_OK, "exec on raw connection");
+}
+
+/// The handler replaces busy_timeout and clearing it restores the timeout
+#[wasm_bindgen_test]
+async fn test_busy_handler_replaces_timeout() {
+    let config = DatabaseConfig {
+        name: "busy_handler_timeout.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    assert_eq!(busy_timeout(&mut db).await, ColumnValue::Integer(10000));
+
+    db.set_busy_handler_internal(Some(js_sys::Function::new_with_args("n", "return false")))
+        .unwrap();
+    assert_eq!(busy_timeout(&mut db).await, ColumnValue::Integer(0));
+
+    db.set_busy_handler_internal(None).unwrap();
+    assert_eq!(busy_timeout(&mut db).await, ColumnValue::Integer(10000));
+
+    db.close().await.unwrap();
+}
+
+/// The callback sees the retry count and decides when to give up
+#[wasm_bindgen_test]
+async fn test_busy_handler_controls_retries() {
+    let name = "busy_handler_retries.db";
+    let config = DatabaseConfig {
+        name: name.to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE t (x INTEGER)")
+        .await
+        .unwrap();
+
+    let calls = js_sys::Array::new();
+    let handler =
+        js_sys::Function::new_with_args("calls", "return (n) => { calls.push(n); return n < 2; }")
+            .call1(&JsValue::NULL, &calls)
+            .unwrap();
+    db.set_busy_handler_internal(Some(handler.into())).unwrap();
+
+    let other = open_raw(name);
+    exec_raw(other, "BEGIN IMMEDIATE; INSERT INTO t VALUES (1);");
+
+    let err = db
+        .execute_internal("INSERT INTO t VALUES (2)")
+        .await
+        .expect_err("locked database must fail once the handler gives up");
+    assert_eq!(
+        err.sqlite_code.map(|code| code & 0xff),
+        Some(sqlite_wasm_rs::SQLITE_BUSY)
+    );
+    let counts: Vec<f64> = calls.iter().map(|n| n.as_f64().unwrap()).collect();
+    assert_eq!(counts, vec![0.0, 1.0, 2.0]);
+
+    exec_raw(other, "COMMIT");
+    unsafe { sqlite_wasm_rs::sqlite3_close(other) };
+    db.execute_internal("INSERT INTO t VALUES (2)")
+        .await
+        .unwrap();
+
+    db.close().await.unwrap();
+}
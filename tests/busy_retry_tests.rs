@@ -0,0 +1,82 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::storage::retry_logic::sleep_ms;
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{BusyRetryConfig, ColumnValue, Database, DatabaseConfig};
+#[cfg(target_arch = "wasm32")]
+use std::{cell::Cell, rc::Rc};
+
+/// A second connection retries while the first holds a write transaction, then
+/// succeeds once the writer commits
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_busy_retry_succeeds_after_writer_commits() {
+    let name = "busy_retry_two_connections.db";
+    let mut writer = Database::new(DatabaseConfig {
+        name: name.to_string(),
+        journal_mode: Some("WAL".to_string()),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    writer.allow_non_leader_writes(true).await.unwrap();
+    writer
+        .execute_internal("DROP TABLE IF EXISTS jobs")
+        .await
+        .unwrap();
+    writer
+        .execute_internal("CREATE TABLE jobs (id INTEGER PRIMARY KEY, state TEXT)")
+        .await
+        .unwrap();
+
+    let mut reader = Database::new_read_only(DatabaseConfig {
+        name: name.to_string(),
+        journal_mode: Some("WAL".to_string()),
+        busy_retry: Some(BusyRetryConfig {
+            max_attempts: 8,
+            base_delay_ms: 5,
+        }),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+
+    writer.execute_internal("BEGIN IMMEDIATE").await.unwrap();
+    writer
+        .execute_internal("INSERT INTO jobs (state) VALUES ('queued')")
+        .await
+        .unwrap();
+
+    let committed = Rc::new(Cell::new(false));
+    let committed_flag = committed.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        sleep_ms(20).await;
+        writer.execute_internal("COMMIT").await.unwrap();
+        committed_flag.set(true);
+    });
+
+    // Runs while the writer's transaction is open; any busy result is retried
+    let during = reader
+        .execute_internal("SELECT COUNT(*) FROM jobs")
+        .await
+        .expect("query should succeed after retrying busy results");
+    assert_eq!(during.rows.len(), 1);
+
+    while !committed.get() {
+        sleep_ms(5).await;
+    }
+
+    let after = reader
+        .execute_internal("SELECT COUNT(*) FROM jobs")
+        .await
+        .expect("query after commit should succeed");
+    assert_eq!(after.rows[0].values[0], ColumnValue::Integer(1));
+
+    reader.close_internal().await.ok();
+}
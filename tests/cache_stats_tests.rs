@@ -0,0 +1,83 @@
+//! Tests for Database::cache_stats and clearCaches
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Both caches report activity, and clearing empties them without losing data
+#[wasm_bindgen_test]
+async fn test_cache_stats_and_clear_caches() {
+    let mut db = Database::new_wasm("cache_stats_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+
+    db.execute_internal("CREATE TABLE IF NOT EXISTS items (id INTEGER PRIMARY KEY, name TEXT)")
+        .await
+        .unwrap();
+    for i in 0..3 {
+        db.execute_with_params_internal(
+            "INSERT INTO items (name) VALUES (?)",
+            &[ColumnValue::Text(format!("item{}", i))],
+        )
+        .await
+        .unwrap();
+    }
+    db.sync_internal().await.unwrap();
+
+    let stats = db.cache_stats();
+    assert!(stats.statements.size > 0);
+    assert!(stats.statements.hits >= 2, "Repeated inserts should hit");
+    assert!(stats.blocks.size > 0);
+    assert!(stats.blocks.capacity > 0);
+    assert!(stats.blocks.hits + stats.blocks.misses > 0);
+
+    db.clear_caches();
+    let cleared = db.cache_stats();
+    assert_eq!(cleared.statements.size, 0);
+    assert_eq!(cleared.blocks.size, 0, "Synced blocks should be dropped");
+    assert!(cleared.statements.hits >= stats.statements.hits);
+    assert!(cleared.blocks.misses >= stats.blocks.misses);
+
+    let result = db
+        .execute_internal("SELECT COUNT(*) FROM items")
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+
+    let js = db.get_cache_stats().unwrap();
+    assert!(js_sys::Reflect::has(&js, &"statements".into()).unwrap());
+    assert!(js_sys::Reflect::has(&js, &"blocks".into()).unwrap());
+}
+
+/// Classifying SQL before running it doesn't count as a second lookup
+#[wasm_bindgen_test]
+async fn test_write_classification_is_not_counted() {
+    let mut writer = Database::new_wasm("cache_stats_classify".to_string())
+        .await
+        .unwrap();
+    writer.allow_non_leader_writes(true).await.unwrap();
+    writer
+        .execute_internal("CREATE TABLE IF NOT EXISTS items (id INTEGER PRIMARY KEY)")
+        .await
+        .unwrap();
+    writer.sync_internal().await.unwrap();
+
+    // Read-only instances classify every statement before running it
+    let mut reader = Database::open_read_only("cache_stats_classify".to_string())
+        .await
+        .unwrap();
+    let before = reader.cache_stats().statements;
+    for _ in 0..2 {
+        reader
+            .execute_internal("SELECT COUNT(*) FROM items")
+            .await
+            .unwrap();
+    }
+    let after = reader.cache_stats().statements;
+    assert_eq!(after.misses - before.misses, 1);
+    assert_eq!(after.hits - before.hits, 1);
+}
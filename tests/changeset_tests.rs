@@ -0,0 +1,140 @@
+//! Tests for startSession, captureChangeset and applyChangeset
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{
+    ChangesetConflictKind, ColumnValue, ConflictPolicy, Database, DatabaseConfig, Persistence,
+};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const SCHEMA: &str = "CREATE TABLE todos (id INTEGER PRIMARY KEY, title TEXT, done INTEGER)";
+
+async fn title(db: &mut Database, id: i64) -> ColumnValue {
+    db.query_scalar_internal(
+        "SELECT title FROM todos WHERE id = ?",
+        &[ColumnValue::Integer(id)],
+        false,
+    )
+    .await
+    .expect("read title")
+}
+
+/// Changes recorded on one database replay on another
+#[wasm_bindgen_test]
+async fn test_changeset_roundtrip() {
+    let source_config = DatabaseConfig {
+        name: "changeset_source.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut source = Database::new(source_config).await.expect("open database");
+    source.execute_internal(SCHEMA).await.expect("create table");
+    let replica_config = DatabaseConfig {
+        name: "changeset_replica.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut replica = Database::new(replica_config).await.expect("open database");
+    replica
+        .execute_internal(SCHEMA)
+        .await
+        .expect("create table");
+
+    let session = source.start_session_internal(&[]).expect("start session");
+    source
+        .execute_internal("INSERT INTO todos VALUES (1, 'write tests', 0), (2, 'ship', 0)")
+        .await
+        .unwrap();
+    source
+        .execute_internal("UPDATE todos SET done = 1 WHERE id = 1")
+        .await
+        .unwrap();
+    let changeset = source.capture_changeset_internal(session).expect("capture");
+    assert!(!changeset.is_empty());
+    source.end_session_internal(session);
+    assert_eq!(
+        source.capture_changeset_internal(session).unwrap_err().code,
+        "SESSION_NOT_FOUND"
+    );
+
+    let result = replica
+        .apply_changeset_internal(&changeset, ConflictPolicy::Abort)
+        .await
+        .expect("apply");
+    assert!(!result.aborted);
+    assert!(result.conflicts.is_empty());
+    assert_eq!(
+        replica
+            .query_scalar_internal("SELECT count(*) FROM todos WHERE done = 1", &[], false)
+            .await
+            .unwrap(),
+        ColumnValue::Integer(1)
+    );
+
+    source.close().await.unwrap();
+    replica.close().await.unwrap();
+}
+
+/// Each policy resolves a primary key conflict its own way and reports the row
+#[wasm_bindgen_test]
+async fn test_conflict_policies() {
+    let source_config = DatabaseConfig {
+        name: "changeset_conflict_source.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut source = Database::new(source_config).await.expect("open database");
+    source.execute_internal(SCHEMA).await.expect("create table");
+    let session = source
+        .start_session_internal(&["todos".to_string()])
+        .unwrap();
+    source
+        .execute_internal("INSERT INTO todos VALUES (1, 'from source', 0)")
+        .await
+        .unwrap();
+    let changeset = source.capture_changeset_internal(session).unwrap();
+
+    for (policy, expected) in [
+        (ConflictPolicy::Abort, "local"),
+        (ConflictPolicy::Skip, "local"),
+        (ConflictPolicy::Replace, "from source"),
+    ] {
+        let replica_config = DatabaseConfig {
+            name: format!("changeset_conflict_{:?}.db", policy),
+            persistence: Some(Persistence::Memory),
+            ..Default::default()
+        };
+        let mut replica = Database::new(replica_config).await.expect("open database");
+        replica
+            .execute_internal(SCHEMA)
+            .await
+            .expect("create table");
+        replica
+            .execute_internal("INSERT INTO todos VALUES (1, 'local', 0)")
+            .await
+            .unwrap();
+
+        let result = replica
+            .apply_changeset_internal(&changeset, policy)
+            .await
+            .expect("apply");
+        assert_eq!(result.aborted, policy == ConflictPolicy::Abort);
+        assert_eq!(result.conflicts.len(), 1, "{:?}", policy);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.table, "todos");
+        assert_eq!(conflict.operation, "INSERT");
+        assert_eq!(conflict.kind, ChangesetConflictKind::Conflict);
+        assert_eq!(conflict.primary_key, vec![ColumnValue::Integer(1)]);
+        assert_eq!(conflict.resolution, policy);
+        assert_eq!(
+            title(&mut replica, 1).await,
+            ColumnValue::Text(expected.to_string())
+        );
+        replica.close().await.unwrap();
+    }
+
+    source.end_session_internal(session);
+    source.close().await.unwrap();
+}
@@ -432,3 +432,52 @@ async fn test_algo_mismatch_triggers_verification_error() {
         );
     }
 }
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+#[cfg(feature = "fs_persist")]
+async fn test_crc32c_configured_per_database_persisted_and_verified() {
+    use absurder_sql::storage::crc32c::crc32c;
+    use absurder_sql::storage::metadata::{ChecksumAlgorithm, set_default_checksum_algorithm};
+
+    let tmp = TempDir::new().expect("tempdir");
+    common::set_var("ABSURDERSQL_FS_BASE", tmp.path());
+    {
+        let _g = common::ENV_LOCK.lock().expect("env lock poisoned");
+        unsafe { std::env::remove_var("DATASYNC_CHECKSUM_ALGO") }
+        drop(_g);
+    }
+    let db = "test_crc32c_algo_persist";
+    set_default_checksum_algorithm(db, ChecksumAlgorithm::CRC32C);
+
+    let data: Vec<u8> = (0..BLOCK_SIZE).map(|i| (i % 251) as u8).collect();
+    {
+        let mut a = BlockStorage::new_with_capacity(db, 4)
+            .await
+            .expect("create A");
+        a.write_block(1, data.clone()).await.expect("write block 1");
+        a.sync().await.expect("sync A");
+    }
+
+    let mut meta_path = PathBuf::from(tmp.path());
+    meta_path.push(db);
+    meta_path.push("metadata.json");
+    let text = fs::read_to_string(&meta_path).expect("read metadata.json");
+    let parsed: TestFsMeta = serde_json::from_str(&text).expect("parse FsMeta");
+    let entry = &parsed
+        .entries
+        .iter()
+        .find(|(bid, _)| *bid == 1)
+        .expect("entry for block 1")
+        .1;
+    assert_eq!(entry.algo.as_str(), "CRC32C");
+    assert_eq!(entry.checksum, crc32c(&data) as u64);
+
+    // Switching the default must not affect verification of existing CRC32C blocks
+    set_default_checksum_algorithm(db, ChecksumAlgorithm::FastHash);
+    let b = BlockStorage::new_with_capacity(db, 4)
+        .await
+        .expect("create B");
+    let bytes = b.read_block(1).await.expect("read block 1 in B");
+    assert_eq!(bytes, data);
+}
@@ -0,0 +1,64 @@
+// Tests for disabling checksums during trusted bulk loads
+
+#![cfg(not(target_arch = "wasm32"))]
+use absurder_sql::storage::{BLOCK_SIZE, BlockStorage};
+use serial_test::serial;
+use tempfile::TempDir;
+#[path = "common/mod.rs"]
+mod common;
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_recompute_restores_checksums_after_bulk_load() {
+    let tmp = TempDir::new().expect("tempdir");
+    common::set_var("ABSURDERSQL_FS_BASE", tmp.path());
+    let mut storage = BlockStorage::new_with_capacity("test_checksums_bulk_load", 8)
+        .await
+        .expect("create storage");
+
+    storage
+        .write_block(1, vec![1u8; BLOCK_SIZE])
+        .await
+        .expect("write block 1");
+    assert!(storage.get_block_checksum(1).is_some());
+
+    storage.disable_checksums(true);
+    assert!(storage.checksums_disabled());
+    storage
+        .write_block(1, vec![2u8; BLOCK_SIZE])
+        .await
+        .expect("overwrite block 1");
+    storage
+        .write_block(2, vec![3u8; BLOCK_SIZE])
+        .await
+        .expect("write block 2");
+    // No stale checksum survives the overwrite and reads are not verified
+    assert!(storage.get_block_checksum(1).is_none());
+    assert!(storage.get_block_checksum(2).is_none());
+    assert_eq!(storage.read_block(1).await.unwrap(), vec![2u8; BLOCK_SIZE]);
+
+    assert_eq!(storage.recompute_checksums(), 2);
+    assert!(!storage.checksums_disabled());
+    assert!(storage.get_block_checksum(1).is_some());
+    assert!(storage.verify_block_checksum(2).await.is_ok());
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_sync_reenables_checksums() {
+    let tmp = TempDir::new().expect("tempdir");
+    common::set_var("ABSURDERSQL_FS_BASE", tmp.path());
+    let mut storage = BlockStorage::new_with_capacity("test_checksums_sync_guard", 8)
+        .await
+        .expect("create storage");
+
+    storage.disable_checksums(true);
+    storage
+        .write_block(1, vec![7u8; BLOCK_SIZE])
+        .await
+        .expect("write block 1");
+    storage.sync().await.expect("sync");
+
+    assert!(!storage.checksums_disabled());
+    assert!(storage.get_block_checksum(1).is_some());
+}
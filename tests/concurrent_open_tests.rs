@@ -0,0 +1,110 @@
+//! Concurrency contract for opening the same database from several tasks at once
+//!
+//! Concurrent opens of one name must share a single BlockStorage, release their
+//! initialization reservation, and see each other's writes.
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::vfs::IndexedDBVFS;
+use absurder_sql::vfs::indexeddb_vfs::{get_storage_with_fallback, init_in_progress};
+use absurder_sql::{ColumnValue, Database};
+use std::rc::Rc;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Concurrent `newDatabase` calls share one registered BlockStorage and one dataset
+#[wasm_bindgen_test]
+async fn test_concurrent_new_wasm_shares_storage() {
+    let name = "concurrent_open_same";
+    let opens = (0..6).map(|_| Database::new_wasm(name.to_string()));
+    let mut dbs: Vec<Database> = futures::future::join_all(opens)
+        .await
+        .into_iter()
+        .map(|db| db.expect("every concurrent open should succeed"))
+        .collect();
+
+    assert!(!init_in_progress("concurrent_open_same.db"));
+    let storage = get_storage_with_fallback("concurrent_open_same.db")
+        .expect("storage should be registered once the opens finish");
+
+    for db in dbs.iter_mut() {
+        db.allow_non_leader_writes(true).await.unwrap();
+    }
+    dbs[0]
+        .execute_internal("DROP TABLE IF EXISTS hits")
+        .await
+        .unwrap();
+    dbs[0]
+        .execute_internal("CREATE TABLE hits (worker INTEGER)")
+        .await
+        .unwrap();
+    for (i, db) in dbs.iter_mut().enumerate() {
+        db.execute_internal(&format!("INSERT INTO hits VALUES ({})", i))
+            .await
+            .unwrap();
+    }
+
+    for db in dbs.iter_mut() {
+        let result = db
+            .execute_internal("SELECT COUNT(*) FROM hits")
+            .await
+            .unwrap();
+        assert_eq!(result.rows[0].values[0], ColumnValue::Integer(6));
+    }
+
+    let after = get_storage_with_fallback("concurrent_open_same.db").unwrap();
+    assert!(
+        Rc::ptr_eq(&storage, &after),
+        "the registered BlockStorage must not be replaced while databases are open"
+    );
+
+    for mut db in dbs {
+        db.close().await.unwrap();
+    }
+}
+
+/// Concurrent VFS creation hands every caller the same BlockStorage
+#[wasm_bindgen_test]
+async fn test_concurrent_vfs_creation_returns_one_storage() {
+    let name = "concurrent_vfs_same.db";
+    let creations = (0..8).map(|_| IndexedDBVFS::new(name));
+    let vfss: Vec<IndexedDBVFS> = futures::future::join_all(creations)
+        .await
+        .into_iter()
+        .map(|vfs| vfs.expect("every concurrent VFS creation should succeed"))
+        .collect();
+
+    let first = vfss[0].storage();
+    for vfs in &vfss[1..] {
+        assert!(Rc::ptr_eq(first, vfs.storage()));
+    }
+    assert!(Rc::ptr_eq(
+        first,
+        &get_storage_with_fallback(name).expect("storage should be registered")
+    ));
+    assert!(
+        !init_in_progress(name),
+        "no reservation may outlive creation"
+    );
+}
+
+/// Dropping an open while it holds the reservation releases it for later opens
+#[wasm_bindgen_test]
+async fn test_cancelled_open_does_not_leak_reservation() {
+    let name = "concurrent_open_cancelled.db";
+    {
+        let mut pending = Box::pin(IndexedDBVFS::new(name));
+        // Poll once so the future takes the reservation, then drop it
+        let _ = futures::poll!(pending.as_mut());
+    }
+    assert!(!init_in_progress(name));
+
+    let vfs = IndexedDBVFS::new(name)
+        .await
+        .expect("a later open must not wait on the cancelled reservation");
+    assert!(Rc::ptr_eq(
+        vfs.storage(),
+        &get_storage_with_fallback(name).unwrap()
+    ));
+}
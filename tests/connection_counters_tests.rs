@@ -0,0 +1,59 @@
+//! Tests for lastInsertRowId, changes and totalChanges
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{Database, DatabaseConfig, Persistence};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// The counters reflect the last statement without running another one
+#[wasm_bindgen_test]
+async fn test_counters_after_batch() {
+    let config = DatabaseConfig {
+        name: "counters_batch".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)")
+        .await
+        .unwrap();
+    assert_eq!(db.last_insert_rowid().unwrap(), 0);
+
+    for (id, name) in [(10, "a"), (11, "b"), (42, "c")] {
+        db.execute_internal(&format!("INSERT INTO items VALUES ({}, '{}')", id, name))
+            .await
+            .unwrap();
+    }
+    assert_eq!(db.last_insert_rowid().unwrap(), 42);
+    assert_eq!(db.changes().unwrap(), 1);
+
+    db.execute_internal("UPDATE items SET name = 'z' WHERE id < 42")
+        .await
+        .unwrap();
+    assert_eq!(db.changes().unwrap(), 2);
+    assert_eq!(db.total_changes().unwrap(), 5);
+
+    // Reads leave the counters alone
+    db.execute_internal("SELECT * FROM items").await.unwrap();
+    assert_eq!(db.changes().unwrap(), 2);
+    assert_eq!(db.last_insert_rowid().unwrap(), 42);
+}
+
+/// Reading a counter after close fails instead of touching a freed connection
+#[wasm_bindgen_test]
+async fn test_counters_after_close() {
+    let config = DatabaseConfig {
+        name: "counters_closed".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT)")
+        .await
+        .unwrap();
+    db.close_internal().await.unwrap();
+    let err = db.changes().unwrap_err();
+    assert_eq!(err.code, "DATABASE_CLOSED");
+}
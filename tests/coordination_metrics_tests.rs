@@ -200,3 +200,26 @@ async fn test_reset_coordination_metrics() {
 
     console::log_1(&"TEST PASSED: Coordination metrics reset".into());
 }
+
+/// Test that the notification channel round-trips a ping and records its latency
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_notification_channel_health() {
+    let mut db = Database::new_wasm("coord_metrics_channel_test".to_string())
+        .await
+        .unwrap();
+    db.enable_coordination_metrics(true).await.unwrap();
+
+    let health = db.test_notification_channel_internal(2000).await;
+    assert!(health.healthy, "ping should arrive: {:?}", health.error);
+    assert!(health.error.is_none());
+    let latency = health.latency_ms.expect("healthy ping reports latency");
+    assert!(latency >= 0.0);
+
+    let metrics = db.get_coordination_metrics().await.unwrap();
+    let metrics_obj: serde_json::Value = serde_json::from_str(&metrics).unwrap();
+    assert_eq!(
+        metrics_obj["avg_notification_latency_ms"].as_f64().unwrap(),
+        latency
+    );
+}
@@ -0,0 +1,93 @@
+//! Tests for copyTable between two open databases
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, CopyMode, Database, DatabaseConfig, Persistence};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+async fn count(db: &mut Database, sql: &str) -> ColumnValue {
+    db.query_scalar_internal(sql, &[], false)
+        .await
+        .expect("count")
+}
+
+/// Rows stream across in batches; IGNORE keeps existing rows and REPLACE overwrites them
+#[wasm_bindgen_test]
+async fn test_copy_table_modes() {
+    let source_config = DatabaseConfig {
+        name: "copy_table_source.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut source = Database::new(source_config).await.expect("open database");
+    source
+        .execute_internal("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT, data BLOB)")
+        .await
+        .expect("create table");
+    source
+        .execute_internal(
+            "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 1200) \
+             INSERT INTO notes SELECT x, 'note ' || x, randomblob(8) FROM n",
+        )
+        .await
+        .unwrap();
+
+    let target_config = DatabaseConfig {
+        name: "copy_table_target.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut target = Database::new(target_config).await.expect("open database");
+    target
+        .execute_internal("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT, data BLOB)")
+        .await
+        .expect("create table");
+    target
+        .execute_internal("INSERT INTO notes VALUES (1, 'local', NULL)")
+        .await
+        .unwrap();
+
+    // A duplicate key fails a plain INSERT copy and leaves the target untouched
+    let err = target
+        .copy_table_internal(&mut source, "notes", CopyMode::Insert)
+        .await
+        .unwrap_err();
+    assert!(err.message.contains("UNIQUE"), "{}", err.message);
+    assert_eq!(
+        count(&mut target, "SELECT count(*) FROM notes").await,
+        ColumnValue::Integer(1)
+    );
+
+    let copied = target
+        .copy_table_internal(&mut source, "notes", CopyMode::Ignore)
+        .await
+        .expect("copy ignoring duplicates");
+    assert_eq!(copied, 1199);
+    assert_eq!(
+        count(&mut target, "SELECT body FROM notes WHERE id = 1").await,
+        ColumnValue::Text("local".to_string())
+    );
+
+    let copied = target
+        .copy_table_internal(&mut source, "notes", CopyMode::Replace)
+        .await
+        .expect("copy replacing duplicates");
+    assert_eq!(copied, 1200);
+    assert_eq!(
+        count(&mut target, "SELECT body FROM notes WHERE id = 1").await,
+        ColumnValue::Text("note 1".to_string())
+    );
+    assert_eq!(
+        count(
+            &mut target,
+            "SELECT count(*) FROM notes WHERE length(data) = 8"
+        )
+        .await,
+        ColumnValue::Integer(1200)
+    );
+
+    source.close().await.unwrap();
+    target.close().await.unwrap();
+}
@@ -0,0 +1,267 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database};
+
+/// Scalar functions receive plain JS values and their results come back as columns
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_scalar_function_roundtrip() {
+    let mut db = Database::new_wasm("custom_function_scalar_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    let double_it = js_sys::Function::new_with_args("x", "return x * 2");
+    db.create_scalar_function_internal("double_it", 1, double_it, false)
+        .unwrap();
+
+    let result = db
+        .execute_internal("SELECT double_it(21), double_it(1.25), double_it(NULL)")
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Integer(42));
+    assert_eq!(result.rows[0].values[1], ColumnValue::Real(2.5));
+    assert_eq!(result.rows[0].values[2], ColumnValue::Integer(0));
+
+    db.close().await.unwrap();
+}
+
+/// Only deterministic functions may be used in generated columns
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_deterministic_function_in_generated_column() {
+    let mut db = Database::new_wasm("custom_function_deterministic_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS prices")
+        .await
+        .unwrap();
+
+    let create =
+        "CREATE TABLE prices (net INTEGER, gross INTEGER GENERATED ALWAYS AS (add_tax(net)))";
+    let add_tax = js_sys::Function::new_with_args("x", "return Math.round(x * 1.2)");
+    db.create_scalar_function_internal("add_tax", 1, add_tax.clone(), false)
+        .unwrap();
+
+    // SQLite rejects the function either when the schema is created or when it is used
+    let err = match db.execute_internal(create).await {
+        Err(e) => e,
+        Ok(_) => db
+            .execute_internal("INSERT INTO prices (net) VALUES (100)")
+            .await
+            .expect_err("non-deterministic function must be rejected in a generated column"),
+    };
+    assert!(
+        err.message.contains("non-deterministic"),
+        "unexpected error: {}",
+        err.message
+    );
+
+    db.execute_internal("DROP TABLE IF EXISTS prices")
+        .await
+        .unwrap();
+    db.create_scalar_function_internal("add_tax", 1, add_tax, true)
+        .unwrap();
+    db.execute_internal(create).await.unwrap();
+    db.execute_internal("INSERT INTO prices (net) VALUES (100)")
+        .await
+        .unwrap();
+    let result = db
+        .execute_internal("SELECT gross FROM prices")
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Integer(120));
+
+    db.close().await.unwrap();
+}
+
+/// Exceptions thrown by the callback fail the query with the thrown message
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_scalar_function_error_propagates() {
+    let mut db = Database::new_wasm("custom_function_error_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    let fail = js_sys::Function::new_no_args("throw new Error('boom from js')");
+    db.create_scalar_function_internal("fail_always", 0, fail, false)
+        .unwrap();
+
+    let err = db
+        .execute_internal("SELECT fail_always()")
+        .await
+        .expect_err("callback exception should fail the query");
+    assert!(err.message.contains("boom from js"), "{}", err.message);
+
+    db.close().await.unwrap();
+}
+
+/// Aggregates keep separate state per group and see empty groups as undefined
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_aggregate_function_over_groups() {
+    let mut db = Database::new_wasm("custom_function_aggregate_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS staff")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE staff (dept TEXT, salary INTEGER)")
+        .await
+        .unwrap();
+    db.execute_internal(
+        "INSERT INTO staff VALUES ('eng', 100), ('eng', 300), ('eng', 200), ('ops', 50), ('ops', 70)",
+    )
+    .await
+    .unwrap();
+
+    let step = js_sys::Function::new_with_args("acc, x", "return (acc || []).concat([x])");
+    let finalize = js_sys::Function::new_with_args(
+        "acc",
+        "if (acc === undefined) return null; \
+         const s = acc.sort((a, b) => a - b); const m = s.length >> 1; \
+         return s.length % 2 ? s[m] : (s[m - 1] + s[m]) / 2;",
+    );
+    db.create_aggregate_function_internal("median", 1, step, finalize)
+        .unwrap();
+
+    let result = db
+        .execute_internal("SELECT dept, median(salary) FROM staff GROUP BY dept ORDER BY dept")
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 2);
+    assert_eq!(
+        result.rows[0].values[0],
+        ColumnValue::Text("eng".to_string())
+    );
+    assert_eq!(result.rows[0].values[1], ColumnValue::Integer(200));
+    assert_eq!(
+        result.rows[1].values[0],
+        ColumnValue::Text("ops".to_string())
+    );
+    assert_eq!(result.rows[1].values[1], ColumnValue::Integer(60));
+
+    let result = db
+        .execute_internal("SELECT median(salary) FROM staff WHERE dept = 'none'")
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Null);
+
+    db.close().await.unwrap();
+}
+
+/// Functions and collations passed at open time are usable by the very first query
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_extensions_installed_at_open() {
+    use absurder_sql::DatabaseConfig;
+    use absurder_sql::functions::Extensions;
+
+    let spec = js_sys::Function::new_no_args(
+        "return { \
+           functions: [{ name: 'shout', arity: 1, fn: (s) => s.toUpperCase() + '!', deterministic: true }], \
+           aggregates: [{ name: 'joined', arity: 1, step: (acc, x) => (acc ?? []).concat([x]), final: (acc) => (acc ?? []).join('|') }], \
+           collations: [{ name: 'by_length', compare: (a, b) => a.length - b.length }], \
+         };",
+    )
+    .call0(&wasm_bindgen::JsValue::NULL)
+    .unwrap();
+    let extensions = Extensions::from_js(&spec).unwrap();
+    let config = DatabaseConfig {
+        name: "custom_function_open_extensions_test.db".to_string(),
+        ..DatabaseConfig::default()
+    };
+    let mut db = Database::new_with_extensions(config, extensions)
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+
+    let result = db
+        .execute_internal(
+            "WITH w(v) AS (VALUES ('ccc'), ('a'), ('bb')) \
+             SELECT shout('hi'), (SELECT joined(v) FROM (SELECT v FROM w ORDER BY v COLLATE by_length))",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        result.rows[0].values[0],
+        ColumnValue::Text("HI!".to_string())
+    );
+    assert_eq!(
+        result.rows[0].values[1],
+        ColumnValue::Text("a|bb|ccc".to_string())
+    );
+
+    db.close().await.unwrap();
+}
+
+/// Malformed extension specs are rejected before anything is registered
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_invalid_extensions_rejected() {
+    use absurder_sql::functions::Extensions;
+
+    let spec = js_sys::Function::new_no_args(
+        "return { functions: [{ name: 'ok', arity: 0, fn: () => 1 }, { name: 'broken', arity: 1 }] };",
+    )
+    .call0(&wasm_bindgen::JsValue::NULL)
+    .unwrap();
+    let err = Extensions::from_js(&spec)
+        .err()
+        .expect("missing fn must be rejected");
+    assert_eq!(err.code, "INVALID_EXTENSIONS");
+    assert!(err.message.contains("broken"), "{}", err.message);
+
+    assert!(
+        Extensions::from_js(&wasm_bindgen::JsValue::UNDEFINED)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+/// removeFunction drops every overload, and built-ins cannot be removed
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_remove_function() {
+    let mut db = Database::new_wasm("custom_function_remove_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    let shout = js_sys::Function::new_with_args("s", "return s.toUpperCase()");
+    let shout_n = js_sys::Function::new_with_args("s, n", "return s.toUpperCase().repeat(n)");
+    db.create_function("shout", 1, shout).unwrap();
+    db.create_function("shout", 2, shout_n).unwrap();
+    let result = db
+        .execute_internal("SELECT shout('a'), shout('b', 2)")
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Text("A".to_string()));
+    assert_eq!(
+        result.rows[0].values[1],
+        ColumnValue::Text("BB".to_string())
+    );
+
+    db.remove_function_internal("SHOUT").unwrap();
+    for sql in ["SELECT shout('a')", "SELECT shout('b', 2)"] {
+        let err = db.execute_internal(sql).await.unwrap_err();
+        assert!(err.message.contains("no such function"), "{}", err.message);
+    }
+
+    assert_eq!(
+        db.remove_function_internal("shout").unwrap_err().code,
+        "FUNCTION_NOT_FOUND"
+    );
+    assert_eq!(
+        db.remove_function_internal("upper").unwrap_err().code,
+        "FUNCTION_NOT_FOUND"
+    );
+
+    db.close().await.unwrap();
+}
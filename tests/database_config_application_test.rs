@@ -153,6 +153,7 @@ async fn test_all_config_options_together() {
         auto_vacuum: Some(true),
         journal_mode: Some("WAL".to_string()),
         max_export_size_bytes: Some(100 * 1024 * 1024), // 100MB
+        ..Default::default()
     };
 
     let mut db = Database::new(config).await.unwrap();
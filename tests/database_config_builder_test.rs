@@ -0,0 +1,75 @@
+use absurder_sql::types::{DatabaseConfig, DatabaseConfigBuilder};
+
+/// Test that the builder applies its setters on top of the defaults
+#[test]
+fn test_builder_applies_setters() {
+    let config = DatabaseConfigBuilder::new("built.db".to_string())
+        .page_size(8192)
+        .cache_size(500)
+        .journal_mode("wal".to_string())
+        .auto_vacuum(false)
+        .max_export_size_bytes(Some(1024))
+        .build()
+        .expect("valid config");
+
+    assert_eq!(config.name, "built.db");
+    assert_eq!(config.page_size, Some(8192));
+    assert_eq!(config.cache_size, Some(500));
+    assert_eq!(config.journal_mode, Some("wal".to_string()));
+    assert_eq!(config.auto_vacuum, Some(false));
+    assert_eq!(config.max_export_size_bytes, Some(1024));
+    assert_eq!(
+        config.block_size,
+        DatabaseConfig::default().block_size,
+        "fields without a setter keep their defaults"
+    );
+}
+
+/// Test that every invalid setting is reported in a single CONFIG_INVALID error
+#[test]
+fn test_builder_reports_all_problems() {
+    let err = DatabaseConfigBuilder::new("bad.db".to_string())
+        .page_size(3000)
+        .cache_size(0)
+        .journal_mode("FAST".to_string())
+        .build()
+        .unwrap_err();
+
+    assert_eq!(err.code, "CONFIG_INVALID");
+    assert!(err.message.contains("page_size 3000"), "{}", err.message);
+    assert!(
+        err.message.contains("journal_mode 'FAST'"),
+        "{}",
+        err.message
+    );
+    assert!(err.message.contains("cache_size"), "{}", err.message);
+}
+
+/// Test that page sizes outside SQLite's range are rejected even when powers of two
+#[test]
+fn test_page_size_range() {
+    for page_size in [256, 131072] {
+        let err = DatabaseConfigBuilder::new("range.db".to_string())
+            .page_size(page_size)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.code, "CONFIG_INVALID");
+    }
+    for page_size in [512, 65536] {
+        assert!(
+            DatabaseConfigBuilder::new("range.db".to_string())
+                .page_size(page_size)
+                .build()
+                .is_ok()
+        );
+    }
+}
+
+/// Test that the built-in configurations pass validation
+#[test]
+fn test_default_configs_are_valid() {
+    DatabaseConfig::default().validate().unwrap();
+    DatabaseConfig::mobile_optimized("mobile.db")
+        .validate()
+        .unwrap();
+}
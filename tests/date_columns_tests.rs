@@ -0,0 +1,154 @@
+//! Tests for reading declared date columns back as ColumnValue::Date
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database, DatabaseConfig, Persistence};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// 2023-08-15T16:00:00Z
+const AUG_15: i64 = 1692115200000;
+
+/// A Date goes in as epoch millis and comes back as a Date
+#[wasm_bindgen_test]
+async fn test_date_round_trip() {
+    let config = DatabaseConfig {
+        name: "date_round_trip".to_string(),
+        persistence: Some(Persistence::Memory),
+        date_columns: Some(true),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, at DATETIME, n INTEGER)")
+        .await
+        .unwrap();
+    db.execute_with_params_internal(
+        "INSERT INTO events (id, at, n) VALUES (1, ?, ?)",
+        &[ColumnValue::Date(AUG_15), ColumnValue::Integer(7)],
+    )
+    .await
+    .unwrap();
+
+    let stored = db
+        .query_scalar_internal("SELECT typeof(at) FROM events", &[], true)
+        .await
+        .unwrap();
+    assert_eq!(stored, ColumnValue::Text("integer".to_string()));
+
+    let result = db
+        .execute_with_params_internal("SELECT at, n FROM events", &[])
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Date(AUG_15));
+    assert_eq!(result.rows[0].values[1], ColumnValue::Integer(7));
+}
+
+/// ISO 8601 text in a date column is parsed; other text is left alone
+#[wasm_bindgen_test]
+async fn test_date_column_text() {
+    let config = DatabaseConfig {
+        name: "date_column_text".to_string(),
+        persistence: Some(Persistence::Memory),
+        date_columns: Some(true),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, at DATETIME, n INTEGER)")
+        .await
+        .unwrap();
+    db.execute_internal(
+        "INSERT INTO events (id, at) VALUES (1, '2023-08-15T16:00:00Z'), (2, 'someday')",
+    )
+    .await
+    .unwrap();
+
+    let result = db
+        .execute_internal("SELECT at FROM events ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Date(AUG_15));
+    assert_eq!(
+        result.rows[1].values[0],
+        ColumnValue::Text("someday".to_string())
+    );
+}
+
+/// Without the option, date columns read back as plain values
+#[wasm_bindgen_test]
+async fn test_date_columns_opt_in() {
+    let config = DatabaseConfig {
+        name: "date_columns_off".to_string(),
+        persistence: Some(Persistence::Memory),
+        date_columns: Some(false),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, at DATETIME, n INTEGER)")
+        .await
+        .unwrap();
+    db.execute_with_params_internal(
+        "INSERT INTO events (id, at) VALUES (1, ?)",
+        &[ColumnValue::Date(AUG_15)],
+    )
+    .await
+    .unwrap();
+
+    let value = db
+        .query_scalar_internal("SELECT at FROM events", &[], true)
+        .await
+        .unwrap();
+    assert_eq!(value, ColumnValue::Integer(AUG_15));
+}
+
+/// JS Date parameters go in and JS Dates come out of date columns
+#[wasm_bindgen_test]
+async fn test_js_date_round_trip() {
+    let config = DatabaseConfig {
+        name: "js_date_round_trip".to_string(),
+        persistence: Some(Persistence::Memory),
+        date_columns: Some(true),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, at DATETIME)")
+        .await
+        .unwrap();
+
+    let params = js_sys::Array::of1(&js_sys::Date::new(&JsValue::from_f64(AUG_15 as f64)));
+    db.execute_with_params("INSERT INTO events (id, at) VALUES (1, ?)", params.into())
+        .await
+        .unwrap();
+
+    let tagged = db.execute("SELECT at FROM events").await.unwrap();
+    let rows = js_sys::Reflect::get(&tagged, &"rows".into()).unwrap();
+    let values = js_sys::Reflect::get(
+        &js_sys::Reflect::get_u32(&rows, 0).unwrap(),
+        &"values".into(),
+    )
+    .unwrap();
+    let cell = js_sys::Reflect::get_u32(&values, 0).unwrap();
+    assert_eq!(
+        js_sys::Reflect::get(&cell, &"type".into()).unwrap(),
+        JsValue::from_str("Date")
+    );
+    let date = js_sys::Reflect::get(&cell, &"value".into())
+        .unwrap()
+        .dyn_into::<js_sys::Date>()
+        .expect("tagged value should be a Date");
+    assert_eq!(date.get_time(), AUG_15 as f64);
+
+    let objects = db
+        .execute_formatted(
+            "SELECT at FROM events",
+            JsValue::UNDEFINED,
+            "ArrayOfObjects".into(),
+        )
+        .await
+        .unwrap();
+    let rows = js_sys::Reflect::get(&objects, &"rows".into()).unwrap();
+    let at =
+        js_sys::Reflect::get(&js_sys::Reflect::get_u32(&rows, 0).unwrap(), &"at".into()).unwrap();
+    assert!(at.is_instance_of::<js_sys::Date>());
+}
@@ -0,0 +1,50 @@
+//! Tests for the max_db_bytes quota and getDatabaseSize
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{Database, DatabaseConfig};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const MAX_DB_BYTES: u64 = 64 * 1024;
+
+/// Growing past max_db_bytes fails with QUOTA_EXCEEDED and the size stays within it
+#[wasm_bindgen_test]
+async fn test_writes_past_quota_fail() {
+    let config = DatabaseConfig {
+        name: "db_quota.db".to_string(),
+        max_db_bytes: Some(MAX_DB_BYTES),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE files (id INTEGER PRIMARY KEY, data BLOB)")
+        .await
+        .unwrap();
+    let initial = db.database_size_bytes_internal().unwrap();
+    assert!(initial > 0 && initial <= MAX_DB_BYTES);
+
+    let mut error = None;
+    for _ in 0..64 {
+        if let Err(e) = db
+            .execute_internal("INSERT INTO files (data) VALUES (randomblob(8192))")
+            .await
+        {
+            error = Some(e);
+            break;
+        }
+    }
+    let error = error.expect("quota should be hit");
+    assert_eq!(error.code, "QUOTA_EXCEEDED", "{}", error.message);
+
+    let size = db.database_size_bytes_internal().unwrap();
+    assert!(size > initial);
+    assert!(size <= MAX_DB_BYTES, "{} > {}", size, MAX_DB_BYTES);
+
+    // Reads and writes that fit keep working
+    db.execute_internal("DELETE FROM files").await.unwrap();
+    db.execute_internal("INSERT INTO files (data) VALUES (x'00')")
+        .await
+        .unwrap();
+}
@@ -0,0 +1,60 @@
+//! Tests for Database.deleteDatabase removing every trace of a database
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::Database;
+use absurder_sql::storage::wasm_indexeddb::read_persisted_commit_marker;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// After deleteDatabase resolves, nothing survives in memory or IndexedDB
+#[wasm_bindgen_test]
+async fn test_delete_database_removes_persisted_state() {
+    let mut db = Database::new_wasm("delete_me".to_string())
+        .await
+        .expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE secrets (v TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO secrets VALUES ('token')")
+        .await
+        .unwrap();
+    db.sync_internal().await.unwrap();
+    assert!(
+        read_persisted_commit_marker("delete_me.db")
+            .await
+            .unwrap()
+            .is_some()
+    );
+    db.close().await.unwrap();
+
+    Database::delete_database("delete_me".to_string())
+        .await
+        .expect("delete database");
+
+    assert_eq!(
+        read_persisted_commit_marker("delete_me.db").await.unwrap(),
+        None
+    );
+    assert!(absurder_sql::vfs::indexeddb_vfs::get_storage_with_fallback("delete_me.db").is_none());
+
+    // Reopening starts from an empty database
+    let mut db = Database::new_wasm("delete_me".to_string())
+        .await
+        .expect("reopen");
+    let tables = db
+        .query_scalar_internal(
+            "SELECT count(*) FROM sqlite_master WHERE name = 'secrets'",
+            &[],
+            true,
+        )
+        .await
+        .unwrap();
+    assert_eq!(tables, absurder_sql::ColumnValue::Integer(0));
+    db.close().await.unwrap();
+    Database::delete_database("delete_me".to_string())
+        .await
+        .unwrap();
+}
@@ -0,0 +1,172 @@
+//! Tests for beginTransaction / commit / rollback
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{
+    ColumnValue, Database, DatabaseConfig, Persistence, TransactionBehavior, TransactionOptions,
+};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+async fn balances(db: &mut Database) -> ColumnValue {
+    db.query_scalar_internal(
+        "SELECT group_concat(balance, ',') FROM (SELECT balance FROM accounts ORDER BY id)",
+        &[],
+        false,
+    )
+    .await
+    .unwrap()
+}
+
+/// Commit keeps the writes, rollback discards them, and the flag follows along
+#[wasm_bindgen_test]
+async fn test_begin_commit_rollback() {
+    let config = DatabaseConfig {
+        name: "explicit_tx_commit_rollback".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER)")
+        .await
+        .expect("create table");
+    db.execute_internal("INSERT INTO accounts VALUES (1, 100), (2, 0)")
+        .await
+        .expect("seed");
+    assert!(!db.is_in_transaction());
+
+    db.begin_transaction_internal(&TransactionOptions {
+        behavior: TransactionBehavior::Immediate,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    assert!(db.is_in_transaction());
+    db.execute_internal("UPDATE accounts SET balance = balance - 10 WHERE id = 1")
+        .await
+        .unwrap();
+    db.execute_internal("UPDATE accounts SET balance = balance + 10 WHERE id = 2")
+        .await
+        .unwrap();
+    db.commit_internal().await.unwrap();
+    assert!(!db.is_in_transaction());
+    assert_eq!(
+        balances(&mut db).await,
+        ColumnValue::Text("90,10".to_string())
+    );
+
+    db.begin_transaction_internal(&TransactionOptions::default())
+        .await
+        .unwrap();
+    db.execute_internal("DELETE FROM accounts").await.unwrap();
+    db.rollback_internal().await.unwrap();
+    assert!(!db.is_in_transaction());
+    assert_eq!(
+        balances(&mut db).await,
+        ColumnValue::Text("90,10".to_string())
+    );
+}
+
+/// Beginning twice, or finishing without beginning, is reported rather than sent to SQLite
+#[wasm_bindgen_test]
+async fn test_unbalanced_transaction_calls() {
+    let config = DatabaseConfig {
+        name: "explicit_tx_unbalanced".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER)")
+        .await
+        .expect("create table");
+    db.execute_internal("INSERT INTO accounts VALUES (1, 100), (2, 0)")
+        .await
+        .expect("seed");
+
+    let err = db.commit_internal().await.unwrap_err();
+    assert_eq!(err.code, "NO_ACTIVE_TRANSACTION");
+    let err = db.rollback_internal().await.unwrap_err();
+    assert_eq!(err.code, "NO_ACTIVE_TRANSACTION");
+
+    db.begin_transaction_internal(&TransactionOptions::default())
+        .await
+        .unwrap();
+    let err = db
+        .begin_transaction_internal(&TransactionOptions::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "TRANSACTION_ALREADY_ACTIVE");
+    db.rollback_internal().await.unwrap();
+
+    // A raw BEGIN counts as an open transaction too
+    db.execute_internal("BEGIN").await.unwrap();
+    let err = db
+        .begin_transaction_internal(&TransactionOptions::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "TRANSACTION_ALREADY_ACTIVE");
+    db.execute_internal("COMMIT").await.unwrap();
+
+    // A transaction ended with raw SQL is forgotten
+    db.begin_transaction_internal(&TransactionOptions::default())
+        .await
+        .unwrap();
+    db.execute_internal("COMMIT").await.unwrap();
+    assert!(!db.is_in_transaction());
+}
+
+/// Savepoints and `transaction()` nest inside `beginTransaction()` and share its state
+#[wasm_bindgen_test]
+async fn test_transaction_apis_share_state() {
+    let config = DatabaseConfig {
+        name: "explicit_tx_shared_state".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER)")
+        .await
+        .expect("create table");
+    db.execute_internal("INSERT INTO accounts VALUES (1, 100), (2, 0)")
+        .await
+        .expect("seed");
+
+    db.begin_transaction_internal(&TransactionOptions::default())
+        .await
+        .unwrap();
+    db.savepoint_internal("edit").await.unwrap();
+    db.execute_internal("UPDATE accounts SET balance = 50 WHERE id = 1")
+        .await
+        .unwrap();
+    // A failing transaction() scope only undoes its own work
+    let err = db
+        .transaction_internal(&TransactionOptions::default(), async |db: &mut Database| {
+            db.execute_internal("UPDATE accounts SET balance = 0")
+                .await?;
+            db.execute_internal("SELECT * FROM missing_table").await
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "SQLITE_ERROR");
+    assert!(db.is_in_transaction());
+    assert_eq!(db.savepoint_depth(), 1);
+
+    // Committing also closes the savepoint still open inside the transaction
+    db.commit_internal().await.unwrap();
+    assert!(!db.is_in_transaction());
+    assert_eq!(db.savepoint_depth(), 0);
+    assert_eq!(
+        balances(&mut db).await,
+        ColumnValue::Text("50,0".to_string())
+    );
+
+    // A savepoint that started the transaction blocks beginTransaction()
+    db.savepoint_internal("outer").await.unwrap();
+    let err = db
+        .begin_transaction_internal(&TransactionOptions::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "TRANSACTION_ALREADY_ACTIVE");
+    db.release_savepoint_internal("outer").await.unwrap();
+}
@@ -176,6 +176,7 @@ async fn test_database_export_to_file() {
         auto_vacuum: None,
         journal_mode: None,
         max_export_size_bytes: Some(2 * 1024 * 1024 * 1024),
+        ..Default::default()
     };
 
     let mut db = Database::new(config)
@@ -232,6 +233,7 @@ async fn test_export_multi_table_database() {
         auto_vacuum: None,
         journal_mode: None,
         max_export_size_bytes: Some(2 * 1024 * 1024 * 1024),
+        ..Default::default()
     };
 
     let mut db = Database::new(config)
@@ -432,12 +434,7 @@ fn test_clear_database_storage() {
             let mut meta_map = HashMap::new();
             meta_map.insert(
                 0,
-                BlockMetadataPersist {
-                    checksum: 123,
-                    version: 1,
-                    last_modified_ms: 1000,
-                    algo: ChecksumAlgorithm::FastHash,
-                },
+                BlockMetadataPersist::new(123, 1, 1000, ChecksumAlgorithm::FastHash),
             );
             metadata.insert(db_name.to_string(), meta_map);
         });
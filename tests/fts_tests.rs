@@ -0,0 +1,116 @@
+//! Tests for createFtsIndex and searchFts
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database, DatabaseConfig, FtsOptions, Persistence};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+async fn matching_ids(db: &mut Database, query: &str) -> Vec<ColumnValue> {
+    let result = db.search_fts_internal("notes", query, 20).await.unwrap();
+    assert_eq!(result.columns.last().map(String::as_str), Some("fts_rank"));
+    result
+        .rows
+        .into_iter()
+        .map(|row| row.values[0].clone())
+        .collect()
+}
+
+/// Existing rows are indexed and later inserts, updates and deletes stay in sync
+#[wasm_bindgen_test]
+async fn test_fts_index_tracks_content_table() {
+    let config = DatabaseConfig {
+        name: "fts_sync.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE notes (id INTEGER PRIMARY KEY, title TEXT, body TEXT)")
+        .await
+        .expect("create table");
+    db.execute_internal("INSERT INTO notes VALUES (1, 'Groceries', 'apples and pears')")
+        .await
+        .unwrap();
+
+    db.create_fts_index_internal(
+        "notes",
+        &["title".to_string(), "body".to_string()],
+        &FtsOptions::default(),
+    )
+    .await
+    .expect("create index");
+    assert_eq!(
+        matching_ids(&mut db, "apples").await,
+        vec![ColumnValue::Integer(1)]
+    );
+
+    db.execute_internal("INSERT INTO notes VALUES (2, 'Orchard', 'apples apples apples')")
+        .await
+        .unwrap();
+    // The note mentioning apples most often ranks first
+    assert_eq!(
+        matching_ids(&mut db, "apples").await,
+        vec![ColumnValue::Integer(2), ColumnValue::Integer(1)]
+    );
+
+    db.execute_internal("UPDATE notes SET body = 'bananas' WHERE id = 1")
+        .await
+        .unwrap();
+    assert_eq!(
+        matching_ids(&mut db, "apples").await,
+        vec![ColumnValue::Integer(2)]
+    );
+    assert_eq!(
+        matching_ids(&mut db, "bananas").await,
+        vec![ColumnValue::Integer(1)]
+    );
+
+    db.execute_internal("DELETE FROM notes WHERE id = 2")
+        .await
+        .unwrap();
+    assert!(matching_ids(&mut db, "apples").await.is_empty());
+    assert_eq!(
+        matching_ids(&mut db, "title:groceries").await,
+        vec![ColumnValue::Integer(1)]
+    );
+}
+
+/// Tokenizer and prefix options reach the FTS5 table
+#[wasm_bindgen_test]
+async fn test_fts_index_options() {
+    let config = DatabaseConfig {
+        name: "fts_options.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE notes (id INTEGER PRIMARY KEY, title TEXT, body TEXT)")
+        .await
+        .expect("create table");
+    db.execute_internal("INSERT INTO notes VALUES (1, 'Runner', 'she was running fast')")
+        .await
+        .unwrap();
+
+    let options = FtsOptions {
+        tokenize: Some("porter unicode61".to_string()),
+        prefix: Some(vec![2, 3]),
+    };
+    db.create_fts_index_internal("notes", &["body".to_string()], &options)
+        .await
+        .expect("create index");
+    assert_eq!(
+        matching_ids(&mut db, "runs").await,
+        vec![ColumnValue::Integer(1)]
+    );
+    assert_eq!(
+        matching_ids(&mut db, "fa*").await,
+        vec![ColumnValue::Integer(1)]
+    );
+
+    let err = db
+        .create_fts_index_internal("notes", &[], &FtsOptions::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "INVALID_FTS_INDEX");
+}
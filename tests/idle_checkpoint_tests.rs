@@ -0,0 +1,59 @@
+//! Tests for the background checkpoint after `idle_checkpoint_ms` without writes
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{Database, DatabaseConfig};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+async fn sleep_ms(ms: i32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms)
+            .unwrap();
+    });
+    wasm_bindgen_futures::JsFuture::from(promise).await.unwrap();
+}
+
+/// WAL frames reach the main database file once writes stop for the idle period
+#[wasm_bindgen_test]
+async fn test_idle_checkpoint_copies_wal_into_database() {
+    let config = DatabaseConfig {
+        name: "idle_checkpoint.db".to_string(),
+        journal_mode: Some("WAL".to_string()),
+        idle_checkpoint_ms: Some(200),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, payload TEXT)")
+        .await
+        .unwrap();
+    for i in 0..50 {
+        db.execute_internal(&format!(
+            "INSERT INTO events (payload) VALUES ('{}')",
+            "x".repeat(i * 20)
+        ))
+        .await
+        .unwrap();
+    }
+    assert!(db.wal_size() > 0.0, "writes should land in the WAL first");
+
+    let storage = absurder_sql::vfs::indexeddb_vfs::get_storage_with_fallback("idle_checkpoint.db")
+        .expect("storage registered");
+    let before = storage.allocation_stats().highest_block_id;
+
+    // Still inside the idle window: nothing has been checkpointed yet
+    sleep_ms(50).await;
+    assert_eq!(storage.allocation_stats().highest_block_id, before);
+
+    sleep_ms(500).await;
+    assert!(
+        storage.allocation_stats().highest_block_id > before,
+        "idle checkpoint should write the WAL pages into the database file"
+    );
+
+    db.close().await.unwrap();
+}
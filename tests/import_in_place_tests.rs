@@ -0,0 +1,107 @@
+//! Tests for importFromFileInPlace
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database, DatabaseConfig};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// The same instance sees the imported contents, including a larger schema
+#[wasm_bindgen_test]
+async fn test_import_in_place_keeps_connection() {
+    let unique_id = js_sys::Date::now() as u64;
+    let mut source = Database::new(DatabaseConfig {
+        name: format!("in_place_source_{}.db", unique_id),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    source.allow_non_leader_writes(true).await.unwrap();
+    source
+        .execute_internal("CREATE TABLE restored (id INTEGER PRIMARY KEY, note TEXT)")
+        .await
+        .unwrap();
+    source
+        .execute_internal(
+            "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 500) \
+             INSERT INTO restored SELECT x, hex(randomblob(32)) FROM n",
+        )
+        .await
+        .unwrap();
+    let backup = source.export_to_file().await.expect("export");
+    source.close().await.unwrap();
+
+    let mut target = Database::new(DatabaseConfig {
+        name: format!("in_place_target_{}.db", unique_id),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    target.allow_non_leader_writes(true).await.unwrap();
+    target
+        .execute_internal("CREATE TABLE old_data (id INTEGER)")
+        .await
+        .unwrap();
+    target
+        .execute_internal("INSERT INTO old_data VALUES (1)")
+        .await
+        .unwrap();
+    target.sync().await.unwrap();
+
+    target
+        .import_from_file_in_place(backup)
+        .await
+        .expect("import in place");
+
+    let count = target
+        .query_scalar_internal("SELECT COUNT(*) FROM restored", &[], false)
+        .await
+        .unwrap();
+    assert_eq!(count, ColumnValue::Integer(500));
+    assert!(
+        target
+            .execute_internal("SELECT * FROM old_data")
+            .await
+            .is_err(),
+        "old tables are gone after the import"
+    );
+
+    // Writes keep working on the same connection
+    target
+        .execute_internal("INSERT INTO restored (note) VALUES ('after')")
+        .await
+        .unwrap();
+    target.sync().await.unwrap();
+    target.close().await.unwrap();
+}
+
+/// A file without a SQLite header is rejected and the existing data is untouched
+#[wasm_bindgen_test]
+async fn test_import_in_place_rejects_invalid_file() {
+    let unique_id = js_sys::Date::now() as u64;
+    let mut db = Database::new(DatabaseConfig {
+        name: format!("in_place_invalid_{}.db", unique_id),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE kept (id INTEGER)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO kept VALUES (7)")
+        .await
+        .unwrap();
+    db.sync().await.unwrap();
+
+    let garbage = js_sys::Uint8Array::from(vec![0u8; 8192].as_slice());
+    assert!(db.import_from_file_in_place(garbage).await.is_err());
+
+    let value = db
+        .query_scalar_internal("SELECT id FROM kept", &[], false)
+        .await
+        .unwrap();
+    assert_eq!(value, ColumnValue::Integer(7));
+    db.close().await.unwrap();
+}
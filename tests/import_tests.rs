@@ -475,7 +475,7 @@ async fn test_import_large_database() {
     println!("Imported large database successfully");
 
     // Step 4: Verify the imported data
-    let mut storage_imported = BlockStorage::new(db_name_imported)
+    let storage_imported = BlockStorage::new(db_name_imported)
         .await
         .expect("create imported storage");
     storage_imported
@@ -658,3 +658,71 @@ async fn test_export_import_with_indexes_and_triggers() {
     println!("Database with indexes and triggers exported and imported successfully");
     println!("Schema objects preserved through export/import cycle");
 }
+
+/// Build a small real database file with rusqlite for validation tests
+#[cfg(not(target_arch = "wasm32"))]
+fn sqlite_file_bytes(tag: &str) -> Vec<u8> {
+    use rusqlite::Connection;
+
+    let conn = Connection::open_in_memory().expect("create connection");
+    conn.execute_batch(
+        "CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT);
+         CREATE INDEX idx_notes_body ON notes(body);
+         INSERT INTO notes (body) VALUES ('a'), ('b'), ('c');",
+    )
+    .expect("create schema");
+    let temp_path =
+        std::env::temp_dir().join(format!("test_validate_{}_{}.db", tag, std::process::id()));
+    conn.execute("VACUUM INTO ?1", [temp_path.to_str().unwrap()])
+        .expect("vacuum");
+    let bytes = std::fs::read(&temp_path).expect("read temp file");
+    std::fs::remove_file(&temp_path).ok();
+    bytes
+}
+
+/// A healthy file passes validation and its header fields are reported
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_validate_import_bytes_reports_header() {
+    use absurder_sql::storage::import::validate_import_bytes;
+
+    let bytes = sqlite_file_bytes("valid");
+    let report = validate_import_bytes(&bytes).expect("validate");
+
+    assert!(report.integrity_ok, "{:?}", report);
+    assert!(report.problems.is_empty(), "{:?}", report);
+    assert!(!report.encrypted);
+    assert_eq!(
+        report.page_size as usize * report.page_count as usize,
+        bytes.len()
+    );
+    assert!(report.schema_version > 0);
+}
+
+/// Damaged pages, scrambled headers and truncated files are all reported
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_validate_import_bytes_reports_problems() {
+    use absurder_sql::storage::import::validate_import_bytes;
+
+    let mut damaged = sqlite_file_bytes("damaged");
+    let page_size = u16::from_be_bytes([damaged[16], damaged[17]]) as usize;
+    // Overwrite the b-tree page header of page 2
+    damaged[page_size..page_size + 8].copy_from_slice(&[0xFF; 8]);
+    let report = validate_import_bytes(&damaged).expect("validate");
+    assert!(!report.integrity_ok, "{:?}", report);
+    assert!(!report.problems.is_empty());
+
+    let scrambled = vec![0x5Au8; 8192];
+    let report = validate_import_bytes(&scrambled).expect("validate");
+    assert!(report.encrypted);
+    assert!(!report.integrity_ok);
+
+    let mut truncated = sqlite_file_bytes("truncated");
+    truncated.truncate(truncated.len() - 100);
+    let report = validate_import_bytes(&truncated).expect("validate");
+    assert!(!report.integrity_ok);
+    assert!(!report.encrypted);
+
+    assert!(validate_import_bytes(&[0u8; 10]).is_err());
+}
@@ -0,0 +1,117 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database};
+
+#[cfg(target_arch = "wasm32")]
+async fn export_since(db: &Database, marker: f64) -> (js_sys::Uint8Array, f64) {
+    let result = db.export_incremental(marker).await.unwrap();
+    let delta = js_sys::Reflect::get(&result, &"delta".into())
+        .unwrap()
+        .dyn_into::<js_sys::Uint8Array>()
+        .unwrap();
+    let marker = js_sys::Reflect::get(&result, &"marker".into())
+        .unwrap()
+        .as_f64()
+        .unwrap();
+    (delta, marker)
+}
+
+/// A full import followed by deltas keeps a replica in step with the source
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_incremental_export_roundtrip() {
+    let mut source = Database::new_wasm("incremental_export_source".to_string())
+        .await
+        .unwrap();
+    source.allow_non_leader_writes(true).await.unwrap();
+    source
+        .execute_internal("DROP TABLE IF EXISTS events")
+        .await
+        .unwrap();
+    source
+        .execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, payload TEXT)")
+        .await
+        .unwrap();
+    for i in 0..50 {
+        source
+            .execute_internal(&format!(
+                "INSERT INTO events (payload) VALUES ('initial event {}')",
+                i
+            ))
+            .await
+            .unwrap();
+    }
+
+    let full = source.export_to_file().await.unwrap();
+    let (_, marker) = export_since(&source, 0.0).await;
+
+    let mut replica = Database::new_wasm("incremental_export_replica".to_string())
+        .await
+        .unwrap();
+    replica.allow_non_leader_writes(true).await.unwrap();
+    replica.import_from_file(full.clone()).await.unwrap();
+
+    source
+        .execute_internal("INSERT INTO events (payload) VALUES ('late event')")
+        .await
+        .unwrap();
+    let (delta, next_marker) = export_since(&source, marker).await;
+    assert!(next_marker > marker);
+    assert!(
+        delta.length() < full.length(),
+        "delta ({} bytes) should be smaller than a full export ({} bytes)",
+        delta.length(),
+        full.length()
+    );
+
+    replica.import_incremental(delta).await.unwrap();
+    let result = replica
+        .execute_internal("SELECT COUNT(*), MAX(payload) FROM events")
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Integer(51));
+    assert_eq!(
+        result.rows[0].values[1],
+        ColumnValue::Text("late event".to_string())
+    );
+
+    replica.close().await.unwrap();
+    source.close().await.unwrap();
+}
+
+/// Markers from the future and foreign bytes are rejected
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_incremental_rejects_invalid_input() {
+    let mut db = Database::new_wasm("incremental_export_invalid".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE IF NOT EXISTS t (x INTEGER)")
+        .await
+        .unwrap();
+
+    let err = db.export_incremental(1.0e12).await.unwrap_err();
+    assert!(err.as_string().unwrap().contains("commit marker"));
+
+    let garbage = js_sys::Uint8Array::from(&b"SQLite format 3\0"[..]);
+    let err = db.import_incremental(garbage).await.unwrap_err();
+    assert!(
+        err.as_string()
+            .unwrap()
+            .contains("Not an incremental export")
+    );
+
+    // The instance stays usable after a rejected delta
+    db.execute_internal("INSERT INTO t VALUES (1)")
+        .await
+        .unwrap();
+    db.close().await.unwrap();
+}
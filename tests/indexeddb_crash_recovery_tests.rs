@@ -400,21 +400,21 @@ async fn test_indexeddb_rollback_deletes_orphaned_blocks() {
             .or_insert_with(std::collections::HashMap::new);
         db_meta.insert(
             block2,
-            BlockMetadataPersist {
-                version: 3, // Inconsistent version
-                checksum: 0,
-                algo: ChecksumAlgorithm::FastHash,
-                last_modified_ms: js_sys::Date::now() as u64,
-            },
+            BlockMetadataPersist::new(
+                0,
+                3, // Inconsistent version
+                js_sys::Date::now() as u64,
+                ChecksumAlgorithm::FastHash,
+            ),
         );
         db_meta.insert(
             block3,
-            BlockMetadataPersist {
-                version: 4, // Inconsistent version
-                checksum: 0,
-                algo: ChecksumAlgorithm::FastHash,
-                last_modified_ms: js_sys::Date::now() as u64,
-            },
+            BlockMetadataPersist::new(
+                0,
+                4, // Inconsistent version
+                js_sys::Date::now() as u64,
+                ChecksumAlgorithm::FastHash,
+            ),
         );
     });
 
@@ -0,0 +1,44 @@
+//! Tests for the IndexedDB durability hint applied to persist transactions
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::storage::durability::durability_for;
+use absurder_sql::storage::wasm_indexeddb::read_persisted_commit_marker;
+use absurder_sql::{Database, DatabaseConfig, Durability};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+async fn sync_with(name: &str, durability: Durability) {
+    let mut db = Database::new(DatabaseConfig {
+        name: name.to_string(),
+        durability: Some(durability),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    assert_eq!(durability_for(name), durability);
+
+    db.execute_internal("CREATE TABLE t (v INTEGER)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO t VALUES (1)")
+        .await
+        .unwrap();
+    db.sync_internal().await.expect("sync with durability hint");
+    assert!(read_persisted_commit_marker(name).await.unwrap().is_some());
+
+    db.close().await.unwrap();
+}
+
+/// Syncs succeed with either hint, whether or not the browser honors it
+#[wasm_bindgen_test]
+async fn test_sync_with_relaxed_durability() {
+    sync_with("durability_relaxed.db", Durability::Relaxed).await;
+}
+
+#[wasm_bindgen_test]
+async fn test_sync_with_strict_durability() {
+    sync_with("durability_strict.db", Durability::Strict).await;
+}
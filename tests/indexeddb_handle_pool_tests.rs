@@ -0,0 +1,48 @@
+//! Tests for the IndexedDB connection pool
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{Database, DatabaseConfig};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// With a cap of one, syncing several databases reuses a single connection
+#[wasm_bindgen_test]
+async fn test_handle_limit_reuses_connection() {
+    Database::set_indexeddb_handle_limit(1).unwrap();
+    let before = Database::indexeddb_handle_stats();
+
+    for name in ["handle_pool_a.db", "handle_pool_b.db"] {
+        let config = DatabaseConfig {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let mut db = Database::new(config).await.expect("open");
+        db.allow_non_leader_writes(true).await.unwrap();
+        db.execute_internal("CREATE TABLE IF NOT EXISTS t (id INTEGER PRIMARY KEY, v TEXT)")
+            .await
+            .unwrap();
+        db.execute_internal("INSERT INTO t (v) VALUES ('x')")
+            .await
+            .unwrap();
+        db.sync().await.unwrap();
+
+        let stats = Database::indexeddb_handle_stats();
+        assert!(stats.open <= 1, "{:?}", stats);
+        assert_eq!(stats.max_open, 1);
+        db.close().await.unwrap();
+    }
+
+    let after = Database::indexeddb_handle_stats();
+    assert!(after.reused_total > before.reused_total, "{:?}", after);
+    assert_eq!(after.waiting, 0);
+
+    Database::set_indexeddb_handle_limit(8).unwrap();
+}
+
+/// A zero limit is rejected
+#[wasm_bindgen_test]
+fn test_zero_handle_limit_rejected() {
+    assert!(Database::set_indexeddb_handle_limit(0).is_err());
+}
@@ -0,0 +1,195 @@
+//! Tests for insertMany: one prepared statement, one transaction, many rows
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+async fn count(db: &mut Database) -> ColumnValue {
+    db.query_scalar_internal("SELECT count(*) FROM events", &[], true)
+        .await
+        .unwrap()
+}
+
+fn row(id: i64, name: &str) -> Vec<ColumnValue> {
+    vec![
+        ColumnValue::Integer(id),
+        ColumnValue::Text(name.to_string()),
+    ]
+}
+
+/// Ten thousand rows go in with one call
+#[wasm_bindgen_test]
+async fn test_insert_many_rows() {
+    let mut db = Database::new_wasm("insert_many_rows.db".to_string())
+        .await
+        .expect("open database");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS events")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .await
+        .unwrap();
+    let rows: Vec<_> = (1..=10_000)
+        .map(|i| row(i, &format!("event{}", i)))
+        .collect();
+
+    let result = db
+        .insert_many_internal("INSERT INTO events (id, name) VALUES (?, ?)", &rows)
+        .await
+        .expect("bulk insert");
+
+    assert_eq!(result.affected_rows, 10_000);
+    assert_eq!(result.last_insert_id, Some(10_000));
+    assert_eq!(count(&mut db).await, ColumnValue::Integer(10_000));
+
+    db.close().await.unwrap();
+}
+
+/// Anything but a single INSERT is rejected before writing
+#[wasm_bindgen_test]
+async fn test_rejects_non_insert_sql() {
+    let mut db = Database::new_wasm("insert_many_reject.db".to_string())
+        .await
+        .expect("open database");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS events")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .await
+        .unwrap();
+
+    let err = db
+        .insert_many_internal("UPDATE events SET name = ?", &[vec![ColumnValue::Null]])
+        .await
+        .expect_err("UPDATE must be rejected");
+    assert_eq!(err.code, "INVALID_BULK_INSERT");
+
+    let err = db
+        .insert_many_internal(
+            "INSERT INTO events (id, name) VALUES (?, ?); DELETE FROM events",
+            &[row(1, "a")],
+        )
+        .await
+        .expect_err("multiple statements must be rejected");
+    assert_eq!(err.code, "INVALID_BULK_INSERT");
+    assert_eq!(count(&mut db).await, ColumnValue::Integer(0));
+
+    db.close().await.unwrap();
+}
+
+/// A row with the wrong number of values fails the call up front
+#[wasm_bindgen_test]
+async fn test_param_count_mismatch() {
+    let mut db = Database::new_wasm("insert_many_mismatch.db".to_string())
+        .await
+        .expect("open database");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS events")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .await
+        .unwrap();
+
+    let err = db
+        .insert_many_internal(
+            "INSERT INTO events (id, name) VALUES (?, ?)",
+            &[row(1, "a"), vec![ColumnValue::Integer(2)]],
+        )
+        .await
+        .expect_err("short row must be rejected");
+    assert_eq!(err.code, "PARAM_COUNT_MISMATCH");
+    assert_eq!(count(&mut db).await, ColumnValue::Integer(0));
+
+    db.close().await.unwrap();
+}
+
+/// A row that violates a constraint rolls back the rows before it
+#[wasm_bindgen_test]
+async fn test_failing_row_rolls_back_batch() {
+    let mut db = Database::new_wasm("insert_many_rollback.db".to_string())
+        .await
+        .expect("open database");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS events")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .await
+        .unwrap();
+
+    let err = db
+        .insert_many_internal(
+            "INSERT INTO events (id, name) VALUES (?, ?)",
+            &[row(1, "a"), row(2, "b"), row(1, "duplicate")],
+        )
+        .await
+        .expect_err("duplicate key must fail");
+    assert!(err.message.contains("row 2"), "{}", err.message);
+    assert_eq!(count(&mut db).await, ColumnValue::Integer(0));
+
+    // The connection is left outside any transaction
+    db.execute_internal("INSERT INTO events (id, name) VALUES (1, 'a')")
+        .await
+        .unwrap();
+    assert_eq!(count(&mut db).await, ColumnValue::Integer(1));
+
+    db.close().await.unwrap();
+}
+
+/// With continue_on_error, failing rows are skipped and reported
+#[wasm_bindgen_test]
+async fn test_best_effort_skips_failing_rows() {
+    let mut db = Database::new_wasm("insert_many_best_effort.db".to_string())
+        .await
+        .expect("open database");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS events")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .await
+        .unwrap();
+
+    let report = db
+        .insert_many_best_effort_internal(
+            "INSERT INTO events (id, name) VALUES (?, ?)",
+            &[
+                row(1, "a"),
+                row(1, "duplicate"),
+                vec![ColumnValue::Integer(2), ColumnValue::Null],
+                row(3, "c"),
+            ],
+        )
+        .await
+        .expect("best-effort insert");
+
+    assert_eq!(report.succeeded, 2);
+    assert_eq!(report.failed, 2);
+    assert_eq!(report.affected_rows, 2);
+    assert_eq!(report.last_insert_id, Some(3));
+    let failed: Vec<usize> = report
+        .results
+        .iter()
+        .filter(|r| !r.ok)
+        .map(|r| r.index)
+        .collect();
+    assert_eq!(failed, vec![1, 2]);
+    assert!(
+        report.results[2]
+            .error
+            .as_deref()
+            .unwrap_or_default()
+            .contains("NOT NULL"),
+        "{:?}",
+        report.results[2]
+    );
+    assert_eq!(count(&mut db).await, ColumnValue::Integer(2));
+
+    db.close().await.unwrap();
+}
@@ -0,0 +1,126 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database, DatabaseConfig, InvalidUtf8Handling};
+
+/// The default keeps the old lossy decoding
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_invalid_utf8_lossy_by_default() {
+    let config = DatabaseConfig {
+        name: "invalid_utf8_lossy_test.db".to_string(),
+        invalid_utf8: Some(InvalidUtf8Handling::Lossy),
+        ..DatabaseConfig::default()
+    };
+    let mut db = Database::new(config).await.unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS legacy")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE legacy (v TEXT)")
+        .await
+        .unwrap();
+    // CAST keeps the raw bytes but stores them with TEXT affinity
+    db.execute_internal("INSERT INTO legacy VALUES (CAST(X'48FF69' AS TEXT))")
+        .await
+        .unwrap();
+    let result = db.execute_internal("SELECT v FROM legacy").await.unwrap();
+    assert_eq!(
+        result.rows[0].values[0],
+        ColumnValue::Text("H\u{FFFD}i".to_string())
+    );
+    db.close().await.unwrap();
+}
+
+/// Blob mode returns the stored bytes untouched, for plain and parameterized queries
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_invalid_utf8_as_blob() {
+    let config = DatabaseConfig {
+        name: "invalid_utf8_blob_test.db".to_string(),
+        invalid_utf8: Some(InvalidUtf8Handling::Blob),
+        ..DatabaseConfig::default()
+    };
+    let mut db = Database::new(config).await.unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS legacy")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE legacy (v TEXT)")
+        .await
+        .unwrap();
+    // CAST keeps the raw bytes but stores them with TEXT affinity
+    db.execute_internal("INSERT INTO legacy VALUES (CAST(X'48FF69' AS TEXT))")
+        .await
+        .unwrap();
+    let result = db
+        .execute_internal("SELECT v, typeof(v), 'still text' FROM legacy")
+        .await
+        .unwrap();
+    assert_eq!(
+        result.rows[0].values[0],
+        ColumnValue::Blob(vec![0x48, 0xFF, 0x69])
+    );
+    assert_eq!(
+        result.rows[0].values[1],
+        ColumnValue::Text("text".to_string())
+    );
+    assert_eq!(
+        result.rows[0].values[2],
+        ColumnValue::Text("still text".to_string())
+    );
+
+    let result = db
+        .execute_with_params_internal(
+            "SELECT v FROM legacy WHERE ?1 = 1",
+            &[ColumnValue::Integer(1)],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        result.rows[0].values[0],
+        ColumnValue::Blob(vec![0x48, 0xFF, 0x69])
+    );
+    db.close().await.unwrap();
+}
+
+/// Error mode fails the query instead of mangling the value
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_invalid_utf8_error() {
+    let config = DatabaseConfig {
+        name: "invalid_utf8_error_test.db".to_string(),
+        invalid_utf8: Some(InvalidUtf8Handling::Error),
+        ..DatabaseConfig::default()
+    };
+    let mut db = Database::new(config).await.unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS legacy")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE legacy (v TEXT)")
+        .await
+        .unwrap();
+    // CAST keeps the raw bytes but stores them with TEXT affinity
+    db.execute_internal("INSERT INTO legacy VALUES (CAST(X'48FF69' AS TEXT))")
+        .await
+        .unwrap();
+    let err = db
+        .execute_internal("SELECT v FROM legacy")
+        .await
+        .expect_err("invalid UTF-8 should fail the query");
+    assert_eq!(err.code, "INVALID_UTF8");
+
+    // Valid text is unaffected
+    let result = db.execute_internal("SELECT 'ok'").await.unwrap();
+    assert_eq!(
+        result.rows[0].values[0],
+        ColumnValue::Text("ok".to_string())
+    );
+    db.close().await.unwrap();
+}
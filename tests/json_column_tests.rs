@@ -0,0 +1,115 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Json parameters are stored as TEXT and come back parsed from JSON columns
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_json_binds_as_text_and_reads_back_parsed() {
+    let mut db = Database::new_wasm("json_column_round_trip".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS docs")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE docs (body JSON, raw TEXT)")
+        .await
+        .unwrap();
+    let doc = serde_json::json!({ "tags": ["a", "b"], "count": 2 });
+
+    db.execute_with_params_internal(
+        "INSERT INTO docs VALUES (?, ?)",
+        &[
+            ColumnValue::Json(doc.clone()),
+            ColumnValue::Text(doc.to_string()),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let stored = db
+        .execute_internal("SELECT typeof(body) FROM docs")
+        .await
+        .unwrap();
+    assert_eq!(stored.rows[0].values[0], ColumnValue::Text("text".into()));
+
+    let result = db
+        .execute_internal("SELECT body, json(raw), raw, raw -> '$.tags' FROM docs")
+        .await
+        .unwrap();
+    let values = &result.rows[0].values;
+    assert_eq!(
+        values[0],
+        ColumnValue::Json(doc.clone()),
+        "declared JSON column"
+    );
+    assert_eq!(values[1], ColumnValue::Json(doc.clone()), "json() result");
+    assert_eq!(
+        values[2],
+        ColumnValue::Text(doc.to_string()),
+        "plain TEXT stays text"
+    );
+    assert_eq!(
+        values[3],
+        ColumnValue::Json(serde_json::json!(["a", "b"])),
+        "-> operator result"
+    );
+
+    db.close().await.unwrap();
+}
+
+/// From JS, JSON values arrive as plain objects and can be bound with `type: 'Json'`
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_json_values_are_plain_js_objects() {
+    let mut db = Database::new_wasm("json_column_js".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS docs")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE docs (body JSON, raw TEXT)")
+        .await
+        .unwrap();
+
+    let params = js_sys::JSON::parse(r#"[{"type":"Json","value":{"theme":"dark"}},null]"#).unwrap();
+    let params = js_sys::Array::from(&params);
+    params.set(1, js_sys::JSON::parse(r#"{"type":"Null"}"#).unwrap());
+    db.execute_with_params("INSERT INTO docs VALUES (?, ?)", params.into())
+        .await
+        .unwrap();
+
+    let result = db.execute("SELECT body FROM docs").await.unwrap();
+    let rows = js_sys::Array::from(&js_sys::Reflect::get(&result, &"rows".into()).unwrap());
+    let values =
+        js_sys::Array::from(&js_sys::Reflect::get(&rows.get(0), &"values".into()).unwrap());
+    let cell = values.get(0);
+    assert_eq!(
+        js_sys::Reflect::get(&cell, &"type".into()).unwrap(),
+        JsValue::from_str("Json")
+    );
+    let body = js_sys::Reflect::get(&cell, &"value".into()).unwrap();
+    assert!(
+        !body.is_instance_of::<js_sys::Map>(),
+        "should be a plain object"
+    );
+    assert_eq!(
+        js_sys::Reflect::get(&body, &"theme".into()).unwrap(),
+        JsValue::from_str("dark")
+    );
+
+    let created = absurder_sql::WasmColumnValue::create_json(body);
+    assert!(created.is_ok(), "createJson accepts plain objects");
+
+    db.close().await.unwrap();
+}
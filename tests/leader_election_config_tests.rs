@@ -0,0 +1,103 @@
+//! Tests for configurable leader election lease and heartbeat timing
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::storage::BlockStorage;
+use absurder_sql::{Database, DatabaseConfig, LeaderElectionConfig};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const FAST: LeaderElectionConfig = LeaderElectionConfig {
+    lease_ms: 600,
+    heartbeat_ms: 100,
+    election_timeout_ms: 2000,
+};
+
+/// A heartbeat that is not shorter than the lease is rejected when opening
+#[wasm_bindgen_test]
+async fn test_invalid_config_rejected() {
+    let config = DatabaseConfig {
+        name: "leader_config_invalid.db".to_string(),
+        leader_election: Some(LeaderElectionConfig {
+            lease_ms: 500,
+            heartbeat_ms: 500,
+            election_timeout_ms: 1000,
+        }),
+        ..Default::default()
+    };
+    let err = Database::new(config)
+        .await
+        .err()
+        .expect("heartbeat >= lease must be rejected");
+    assert_eq!(err.code, "INVALID_LEADER_ELECTION_CONFIG");
+}
+
+/// The configured timings reach BlockStorage and the leader keeps a short lease alive
+#[wasm_bindgen_test]
+async fn test_short_lease_is_renewed_by_heartbeat() {
+    let config = DatabaseConfig {
+        name: "leader_config_renew.db".to_string(),
+        leader_election: Some(FAST),
+        ..Default::default()
+    };
+    let mut db = Database::new(config)
+        .await
+        .expect("open with fast election");
+    db.wait_for_leadership_with_timeout(None)
+        .await
+        .expect("should become leader");
+
+    // Several lease periods pass; only the 100ms heartbeat keeps leadership
+    sleep_ms(3 * FAST.lease_ms as u32).await;
+    assert!(db.is_leader_wasm().await.unwrap().as_bool().unwrap());
+
+    db.close().await.unwrap();
+}
+
+/// With a short lease a follower takes over soon after the leader stops heartbeating
+#[wasm_bindgen_test]
+async fn test_short_lease_fails_over_quickly() {
+    let db_name = "leader_config_failover";
+
+    let storage1 = BlockStorage::new(db_name).await.expect("create storage1");
+    storage1.set_leader_election_config(FAST);
+    assert!(storage1.is_leader().await, "first instance should lead");
+
+    let storage2 = BlockStorage::new(db_name).await.expect("create storage2");
+    storage2.set_leader_election_config(FAST);
+    assert!(!storage2.is_leader().await, "second instance should follow");
+
+    storage1.stop_heartbeat_sync();
+    // Well under the default 5s lease, but past the configured one
+    sleep_ms(FAST.lease_ms as u32 + 400).await;
+
+    assert!(
+        storage2.is_leader().await,
+        "follower should take over once the short lease expires"
+    );
+    storage2.stop_heartbeat_sync();
+}
+
+async fn sleep_ms(ms: u32) {
+    use wasm_bindgen_futures::JsFuture;
+
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let closure = wasm_bindgen::closure::Closure::wrap(Box::new(move || {
+            resolve.call0(&wasm_bindgen::JsValue::NULL).unwrap();
+        }) as Box<dyn FnMut()>);
+
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                ms as i32,
+            )
+            .unwrap();
+
+        closure.forget();
+    });
+
+    JsFuture::from(promise).await.unwrap();
+}
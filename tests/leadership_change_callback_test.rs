@@ -0,0 +1,125 @@
+//! Tests for leadership transition callbacks
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::storage::BlockStorage;
+use absurder_sql::{Database, DatabaseConfig, LeaderElectionConfig};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const FAST: LeaderElectionConfig = LeaderElectionConfig {
+    lease_ms: 600,
+    heartbeat_ms: 100,
+    election_timeout_ms: 2000,
+};
+
+type Events = Rc<RefCell<Vec<(bool, String)>>>;
+
+fn recorder() -> (Events, Closure<dyn FnMut(JsValue)>) {
+    let events: Events = Rc::new(RefCell::new(Vec::new()));
+    let sink = events.clone();
+    let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+        let is_leader = js_sys::Reflect::get(&event, &"isLeader".into())
+            .unwrap()
+            .as_bool()
+            .unwrap();
+        let leader_id = js_sys::Reflect::get(&event, &"leaderId".into())
+            .unwrap()
+            .as_string()
+            .unwrap();
+        sink.borrow_mut().push((is_leader, leader_id));
+    });
+    (events, closure)
+}
+
+/// Gaining leadership and losing it to an expired lease both reach the callback
+#[wasm_bindgen_test]
+async fn test_callback_fires_on_gain_and_lease_expiry() {
+    let storage = BlockStorage::new("leadership_callback_expiry")
+        .await
+        .expect("create storage");
+    storage.set_leader_election_config(FAST);
+    let (events, closure) = recorder();
+    storage
+        .set_leadership_callback(Some(
+            closure.as_ref().unchecked_ref::<js_sys::Function>().clone(),
+        ))
+        .unwrap();
+
+    assert!(storage.is_leader().await);
+    {
+        let events = events.borrow();
+        assert_eq!(events.len(), 1, "gaining leadership fires once");
+        assert!(events[0].0);
+        assert!(!events[0].1.is_empty());
+    }
+
+    // Without heartbeats the lease lapses and nobody re-elects
+    storage.stop_heartbeat_sync();
+    sleep_ms(FAST.lease_ms as u32 + 4 * FAST.heartbeat_ms).await;
+
+    let events = events.borrow();
+    assert_eq!(
+        events.len(),
+        2,
+        "lease expiry fires without polling isLeader"
+    );
+    assert_eq!(events[1], (false, String::new()));
+    drop(events);
+
+    storage.set_leadership_callback(None).unwrap();
+}
+
+/// Closing a leading database reports the loss, then stops notifying
+#[wasm_bindgen_test]
+async fn test_close_reports_lost_leadership() {
+    let config = DatabaseConfig {
+        name: "leadership_callback_close.db".to_string(),
+        leader_election: Some(FAST),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.wait_for_leadership()
+        .await
+        .expect("should become leader");
+
+    let (events, closure) = recorder();
+    db.on_leadership_change(closure.as_ref().unchecked_ref())
+        .unwrap();
+    assert!(
+        events.borrow().is_empty(),
+        "registration alone does not fire"
+    );
+
+    db.close().await.unwrap();
+    sleep_ms(3 * FAST.heartbeat_ms).await;
+
+    assert_eq!(events.borrow().as_slice(), &[(false, String::new())]);
+}
+
+async fn sleep_ms(ms: u32) {
+    use wasm_bindgen_futures::JsFuture;
+
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let closure = Closure::wrap(Box::new(move || {
+            resolve.call0(&JsValue::NULL).unwrap();
+        }) as Box<dyn FnMut()>);
+
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                ms as i32,
+            )
+            .unwrap();
+
+        closure.forget();
+    });
+
+    JsFuture::from(promise).await.unwrap();
+}
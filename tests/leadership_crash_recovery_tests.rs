@@ -0,0 +1,93 @@
+//! Crash recovery when a tab becomes leader
+//! A leader that dies between writing blocks and advancing the commit marker leaves
+//! blocks behind; the next leader must clean them up before accepting writes.
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::storage::{BLOCK_SIZE, BlockStorage, CrashRecoveryAction};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Write three blocks and crash before the commit marker advances
+async fn crash_mid_commit(db_name: &str) -> (Vec<u64>, u64) {
+    let mut storage = BlockStorage::new(db_name).await.expect("create storage");
+    let mut ids = Vec::new();
+    for fill in [0xA1u8, 0xB2, 0xC3] {
+        let id = storage.allocate_block().await.expect("alloc block");
+        storage
+            .write_block(id, vec![fill; BLOCK_SIZE])
+            .await
+            .expect("write block");
+        ids.push(id);
+    }
+    let marker = storage.get_commit_marker();
+    storage
+        .crash_simulation_sync(true)
+        .await
+        .expect("simulate crash");
+    assert_eq!(
+        storage.get_commit_marker(),
+        marker,
+        "crash must leave the marker behind"
+    );
+    (ids, marker)
+}
+
+#[wasm_bindgen_test]
+async fn test_new_leader_finalizes_previous_leaders_commit() {
+    let db_name = "leadership_recovery_finalize";
+    let (ids, crashed_marker) = crash_mid_commit(db_name).await;
+
+    let mut storage = BlockStorage::new(db_name).await.expect("create storage");
+    storage
+        .start_leader_election()
+        .await
+        .expect("start election");
+    assert!(storage.is_leader().await, "sole tab should lead");
+
+    assert!(
+        storage.get_commit_marker() > crashed_marker,
+        "becoming leader should finalize the crashed commit"
+    );
+    assert_eq!(
+        storage.read_block_sync(ids[1]).expect("read block"),
+        vec![0xB2u8; BLOCK_SIZE]
+    );
+    assert_eq!(
+        storage
+            .perform_crash_recovery()
+            .await
+            .expect("second recovery"),
+        CrashRecoveryAction::NoActionNeeded,
+        "nothing should be left to recover"
+    );
+
+    storage.stop_leader_election().await.expect("stop election");
+}
+
+#[wasm_bindgen_test]
+async fn test_recovery_on_leadership_can_be_disabled() {
+    let db_name = "leadership_recovery_disabled";
+    let (_, crashed_marker) = crash_mid_commit(db_name).await;
+
+    let mut storage = BlockStorage::new(db_name).await.expect("create storage");
+    storage.set_recover_on_leadership(false);
+    storage
+        .start_leader_election()
+        .await
+        .expect("start election");
+    assert!(storage.is_leader().await, "sole tab should lead");
+
+    assert_eq!(
+        storage.get_commit_marker(),
+        crashed_marker,
+        "disabled recovery must leave the marker alone"
+    );
+    assert_eq!(
+        storage.perform_crash_recovery().await.expect("recovery"),
+        CrashRecoveryAction::Finalize
+    );
+
+    storage.stop_leader_election().await.expect("stop election");
+}
@@ -0,0 +1,46 @@
+//! Tests for Database.listDatabases enumerating databases persisted in IndexedDB
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::storage::wasm_indexeddb::list_persisted_databases;
+use absurder_sql::{Database, DatabaseInfo};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// A synced database is listed with its block count and sync time; a deleted one is not
+#[wasm_bindgen_test]
+async fn test_list_databases_reports_synced_databases() {
+    let before = js_sys::Date::now();
+    let mut db = Database::new_wasm("listed_db".to_string())
+        .await
+        .expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE notes (body TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO notes VALUES ('hello')")
+        .await
+        .unwrap();
+    db.sync_internal().await.unwrap();
+    db.close().await.unwrap();
+
+    let databases = list_persisted_databases().await.expect("list");
+    let info: &DatabaseInfo = databases
+        .iter()
+        .find(|d| d.name == "listed_db.db")
+        .expect("synced database is listed");
+    assert!(info.block_count > 0);
+    assert_eq!(info.size_bytes, info.block_count * 4096);
+    assert!(info.last_modified.unwrap() >= before);
+
+    let js = Database::list_databases().await.expect("listDatabases");
+    let entries: Vec<DatabaseInfo> = serde_wasm_bindgen::from_value(js).unwrap();
+    assert!(entries.iter().any(|d| d.name == "listed_db.db"));
+
+    Database::delete_database("listed_db".to_string())
+        .await
+        .unwrap();
+    let databases = list_persisted_databases().await.expect("list");
+    assert!(!databases.iter().any(|d| d.name == "listed_db.db"));
+}
@@ -1,7 +1,7 @@
 // LRU cache behavior tests for BlockStorage
 
 #![cfg(not(target_arch = "wasm32"))]
-use absurder_sql::storage::{BLOCK_SIZE, BlockStorage};
+use absurder_sql::storage::{BLOCK_SIZE, BlockStorage, set_block_cache_capacity};
 use serial_test::serial;
 use tempfile::TempDir;
 #[path = "common/mod.rs"]
@@ -77,3 +77,40 @@ async fn test_lru_does_not_evict_dirty_blocks() {
         "cache should hold all three blocks since two are dirty"
     );
 }
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_configured_cache_capacity_evicts_and_counts_misses() {
+    let tmp = TempDir::new().expect("tempdir");
+    // Safety: per-test isolated env var, tests are serialized
+    common::set_var("ABSURDERSQL_FS_BASE", tmp.path());
+    set_block_cache_capacity("test_lru_configured", 2);
+    let mut storage = BlockStorage::new("test_lru_configured")
+        .await
+        .expect("Should create storage");
+
+    for id in 1..=3u64 {
+        storage
+            .write_block(id, vec![id as u8; BLOCK_SIZE])
+            .await
+            .expect("write");
+    }
+    storage.sync().await.expect("sync to clear dirty");
+
+    let misses_before = storage.get_metrics().cache_misses;
+    for id in 1..=3u64 {
+        let data = storage.read_block(id).await.expect("read");
+        assert_eq!(data[0], id as u8);
+    }
+
+    assert!(
+        storage.get_cache_size() <= 2,
+        "cache should be evicted down to the configured capacity"
+    );
+    let metrics = storage.get_metrics();
+    assert!(
+        metrics.cache_misses > misses_before,
+        "reading an evicted block should count a miss"
+    );
+    assert!(metrics.cache_hit_rate < 1.0);
+}
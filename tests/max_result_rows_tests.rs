@@ -0,0 +1,44 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database, DatabaseConfig};
+
+/// Queries past max_result_rows fail with RESULT_TOO_LARGE and leave the connection usable
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_max_result_rows_stops_runaway_select() {
+    let config = DatabaseConfig {
+        name: "max_result_rows_test.db".to_string(),
+        max_result_rows: Some(100),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+
+    let unbounded =
+        "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT x FROM n";
+    let err = db.execute_internal(unbounded).await.unwrap_err();
+    assert_eq!(err.code, "RESULT_TOO_LARGE");
+
+    let err = db
+        .execute_with_params_internal(
+            "WITH RECURSIVE n(x) AS (SELECT ?1 UNION ALL SELECT x + 1 FROM n) SELECT x FROM n",
+            &[ColumnValue::Integer(1)],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "RESULT_TOO_LARGE");
+
+    let result = db
+        .execute_internal(&format!("{} LIMIT 100", unbounded))
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 100);
+
+    db.close().await.unwrap();
+}
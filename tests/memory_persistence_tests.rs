@@ -0,0 +1,86 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::vfs::indexeddb_vfs::get_storage_with_fallback;
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database};
+
+/// Memory databases run queries, sync and close without creating IndexedDB storage
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_memory_database_never_touches_indexeddb() {
+    let mut db = Database::open_in_memory("memory_scratch".to_string())
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE IF NOT EXISTS sales (region TEXT, amount INTEGER)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO sales VALUES ('north', 10), ('north', 5), ('south', 7)")
+        .await
+        .unwrap();
+
+    let result = db
+        .execute_internal("SELECT SUM(amount) FROM sales WHERE region = 'north'")
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Integer(15));
+
+    db.sync()
+        .await
+        .expect("sync is a no-op for memory databases");
+    assert!(
+        get_storage_with_fallback("memory_scratch.db").is_none(),
+        "no BlockStorage should be registered for a memory database"
+    );
+
+    db.close()
+        .await
+        .expect("close must not checkpoint into IndexedDB");
+    assert!(get_storage_with_fallback("memory_scratch.db").is_none());
+}
+
+/// exportToFile returns the in-memory bytes, which import into another memory database
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_memory_database_export_and_import() {
+    let mut source = Database::open_in_memory("memory_export_source".to_string())
+        .await
+        .unwrap();
+    source
+        .execute_internal("CREATE TABLE IF NOT EXISTS sales (region TEXT, amount INTEGER)")
+        .await
+        .unwrap();
+    source
+        .execute_internal("INSERT INTO sales VALUES ('east', 42)")
+        .await
+        .unwrap();
+
+    let bytes = source.export_to_file().await.unwrap().to_vec();
+    assert!(bytes.starts_with(b"SQLite format 3\0"));
+
+    let mut target = Database::open_in_memory("memory_export_target".to_string())
+        .await
+        .unwrap();
+    target
+        .import_from_file(js_sys::Uint8Array::from(bytes.as_slice()))
+        .await
+        .unwrap();
+    let result = target
+        .execute_internal("SELECT region, amount FROM sales")
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(
+        result.rows[0].values[0],
+        ColumnValue::Text("east".to_string())
+    );
+    assert_eq!(result.rows[0].values[1], ColumnValue::Integer(42));
+
+    source.close().await.unwrap();
+    target.close().await.unwrap();
+}
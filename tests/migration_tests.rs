@@ -0,0 +1,112 @@
+//! Tests for the migration runner (native)
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use absurder_sql::{ColumnValue, DatabaseConfig, Migration, SqliteIndexedDB};
+use serial_test::serial;
+
+async fn user_version(db: &mut SqliteIndexedDB) -> ColumnValue {
+    let result = db.execute("PRAGMA user_version").await.unwrap();
+    result.rows[0].values[0].clone()
+}
+
+fn migrations() -> Vec<Migration> {
+    vec![
+        Migration::new(2, "ALTER TABLE notes ADD COLUMN pinned INTEGER DEFAULT 0"),
+        Migration::new(
+            1,
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT); \
+             INSERT INTO notes (body) VALUES ('welcome');",
+        ),
+    ]
+}
+
+#[tokio::test]
+#[serial]
+async fn test_migrations_apply_in_order_once() {
+    let config = DatabaseConfig {
+        name: "migration_apply.db".to_string(),
+        ..Default::default()
+    };
+    let mut db = SqliteIndexedDB::new(config).await.unwrap();
+    for table in ["notes", "tags", "later"] {
+        db.execute(&format!("DROP TABLE IF EXISTS {}", table))
+            .await
+            .unwrap();
+    }
+    db.execute("PRAGMA user_version = 0").await.unwrap();
+
+    let applied = db.migrate(&migrations()).await.unwrap();
+    assert_eq!(applied, vec![1, 2]);
+    assert_eq!(user_version(&mut db).await, ColumnValue::Integer(2));
+    db.execute("SELECT pinned FROM notes").await.unwrap();
+
+    // Running the same set again is a no-op
+    assert!(db.migrate(&migrations()).await.unwrap().is_empty());
+
+    let mut next = migrations();
+    next.push(Migration::new(
+        3,
+        "CREATE INDEX idx_notes_pinned ON notes(pinned)",
+    ));
+    assert_eq!(db.migrate(&next).await.unwrap(), vec![3]);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_failed_migration_rolls_back_and_stops() {
+    let config = DatabaseConfig {
+        name: "migration_failure.db".to_string(),
+        ..Default::default()
+    };
+    let mut db = SqliteIndexedDB::new(config).await.unwrap();
+    for table in ["notes", "tags", "later"] {
+        db.execute(&format!("DROP TABLE IF EXISTS {}", table))
+            .await
+            .unwrap();
+    }
+    db.execute("PRAGMA user_version = 0").await.unwrap();
+
+    let mut set = migrations();
+    set.push(Migration::new(
+        3,
+        "CREATE TABLE tags (name TEXT); INSERT INTO missing VALUES (1);",
+    ));
+    set.push(Migration::new(4, "CREATE TABLE later (id INTEGER)"));
+
+    let err = db.migrate(&set).await.unwrap_err();
+    assert_eq!(err.code, "MIGRATION_FAILED");
+    assert!(err.message.contains("Migration 3"), "{}", err.message);
+    assert_eq!(user_version(&mut db).await, ColumnValue::Integer(2));
+
+    let tags = db
+        .execute("SELECT count(*) FROM sqlite_master WHERE name IN ('tags', 'later')")
+        .await
+        .unwrap();
+    assert_eq!(tags.rows[0].values[0], ColumnValue::Integer(0));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_invalid_migration_sets_are_rejected() {
+    let config = DatabaseConfig {
+        name: "migration_invalid.db".to_string(),
+        ..Default::default()
+    };
+    let mut db = SqliteIndexedDB::new(config).await.unwrap();
+    for table in ["notes", "tags", "later"] {
+        db.execute(&format!("DROP TABLE IF EXISTS {}", table))
+            .await
+            .unwrap();
+    }
+    db.execute("PRAGMA user_version = 0").await.unwrap();
+
+    for set in [
+        vec![Migration::new(0, "SELECT 1")],
+        vec![Migration::new(1, "SELECT 1"), Migration::new(1, "SELECT 2")],
+    ] {
+        let err = db.migrate(&set).await.unwrap_err();
+        assert_eq!(err.code, "INVALID_MIGRATION");
+    }
+    assert_eq!(user_version(&mut db).await, ColumnValue::Integer(0));
+}
@@ -137,6 +137,49 @@ mod mobile_bridge_overhead_tests {
         db.execute("ROLLBACK").await.unwrap();
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_execute_batch_best_effort_reports_each_statement() {
+        let config = DatabaseConfig {
+            name: "test_batch_best_effort.db".to_string(),
+            ..Default::default()
+        };
+
+        let mut db = SqliteIndexedDB::new(config).await.unwrap();
+
+        db.execute("DROP TABLE IF EXISTS test").await.unwrap();
+        db.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, value TEXT)")
+            .await
+            .unwrap();
+
+        let statements = vec![
+            "INSERT INTO test VALUES (1, 'valid')".to_string(),
+            "INSERT INTO nonexistent VALUES (2, 'invalid')".to_string(),
+            "INSERT INTO test VALUES (3, 'also_valid')".to_string(),
+        ];
+
+        let report = db.execute_batch_best_effort(&statements).await.unwrap();
+
+        assert_eq!(report.succeeded, 2);
+        assert_eq!(report.failed, 1);
+        assert!(report.results[0].ok);
+        assert!(!report.results[1].ok);
+        assert!(
+            report.results[1]
+                .error
+                .as_deref()
+                .unwrap_or_default()
+                .contains("nonexistent")
+        );
+        assert!(report.results[2].ok);
+
+        let result = db.execute("SELECT COUNT(*) FROM test").await.unwrap();
+        assert_eq!(
+            result.rows[0].values[0],
+            absurder_sql::ColumnValue::Integer(2)
+        );
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_prove_single_transaction_is_fast_without_bridge() {
@@ -43,6 +43,7 @@ async fn test_check_then_create_race() {
         auto_vacuum: Some(true),
         journal_mode: Some("WAL".to_string()),
         max_export_size_bytes: Some(2 * 1024 * 1024 * 1024),
+        ..Default::default()
     };
 
     // CRITICAL: Open sequentially, not in parallel, to avoid IndexedDB blocking
@@ -26,6 +26,7 @@ async fn test_simultaneous_database_creation() {
         auto_vacuum: Some(true),
         journal_mode: Some("WAL".to_string()),
         max_export_size_bytes: Some(2 * 1024 * 1024 * 1024),
+        ..Default::default()
     };
 
     // Simulate 2 tabs (instead of 3) to reduce memory pressure
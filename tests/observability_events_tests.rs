@@ -196,3 +196,56 @@ async fn test_wasm_sync_callbacks() {
     // but we can verify the callback was set without error
     assert!(true); // Test passes if no errors occurred
 }
+
+/// onStorageEvent reports the sync lifecycle of Database::sync
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_wasm_on_storage_event() {
+    use absurder_sql::Database;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::prelude::*;
+
+    let mut db = Database::new_wasm("wasm_storage_events_test".to_string())
+        .await
+        .expect("open database");
+    db.allow_non_leader_writes(true).await.unwrap();
+
+    let events = js_sys::Array::new();
+    let sink = events.clone();
+    let callback = Closure::wrap(Box::new(move |event: JsValue| {
+        sink.push(&event);
+    }) as Box<dyn FnMut(JsValue)>);
+    db.on_storage_event(Some(
+        callback
+            .as_ref()
+            .unchecked_ref::<js_sys::Function>()
+            .clone(),
+    ))
+    .expect("register callback");
+
+    db.execute_internal("CREATE TABLE IF NOT EXISTS events (id INTEGER PRIMARY KEY)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO events DEFAULT VALUES")
+        .await
+        .unwrap();
+    db.sync_internal().await.expect("sync");
+
+    let field = |event: &JsValue, key: &str| js_sys::Reflect::get(event, &key.into()).unwrap();
+    let types: Vec<String> = events
+        .iter()
+        .map(|event| field(&event, "type").as_string().unwrap())
+        .collect();
+    assert_eq!(types, vec!["sync_start", "sync_success"]);
+
+    let success = events.get(1);
+    assert!(field(&success, "dirtyCount").as_f64().unwrap() > 0.0);
+    assert!(field(&success, "durationMs").as_f64().unwrap() >= 0.0);
+
+    // Clearing the callback stops delivery
+    db.on_storage_event(None).unwrap();
+    db.sync_internal().await.expect("sync");
+    assert_eq!(events.length(), 2);
+
+    db.close().await.unwrap();
+}
@@ -0,0 +1,38 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::run_persistence_self_test_internal;
+
+/// The self-test passes every step in a working browser and cleans up after itself
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_persistence_self_test_passes() {
+    let report = run_persistence_self_test_internal().await;
+
+    let names: Vec<&str> = report.steps.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "vfs_registered",
+            "row_written",
+            "commit_marker_advanced",
+            "in_memory_state_dropped",
+            "blocks_persisted",
+            "restore_succeeded",
+        ]
+    );
+    for step in &report.steps {
+        assert!(step.passed, "{} failed: {}", step.name, step.detail);
+    }
+    assert!(report.passed);
+
+    assert!(
+        absurder_sql::vfs::indexeddb_vfs::get_storage_with_fallback(&report.database).is_none(),
+        "temporary database should be deleted"
+    );
+}
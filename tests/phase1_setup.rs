@@ -61,6 +61,7 @@ fn test_custom_database_config() {
         auto_vacuum: Some(false),
         journal_mode: Some("DELETE".to_string()),
         max_export_size_bytes: Some(2 * 1024 * 1024 * 1024),
+        ..Default::default()
     };
 
     assert_eq!(config.name, "test.db");
@@ -0,0 +1,92 @@
+//! Tests for the pragma and setPragma helpers
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database, DatabaseConfig, Persistence, PragmaValue};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Single values are typed, flag pragmas are bools and setters report back
+#[wasm_bindgen_test]
+async fn test_pragma_scalar_values() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "pragma_scalar.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+
+    db.set_pragma_internal("user_version", &ColumnValue::Integer(7))
+        .await
+        .unwrap();
+    let version = db.pragma_internal("user_version", None).await.unwrap();
+    assert!(matches!(version, PragmaValue::Integer(7)), "{:?}", version);
+
+    db.set_pragma_internal("foreign_keys", &ColumnValue::Integer(1))
+        .await
+        .unwrap();
+    let fk = db.pragma_internal("foreign_keys", None).await.unwrap();
+    assert!(matches!(fk, PragmaValue::Bool(true)), "{:?}", fk);
+
+    let encoding = db.pragma_internal("main.encoding", None).await.unwrap();
+    assert!(
+        matches!(&encoding, PragmaValue::Text(text) if text == "UTF-8"),
+        "{:?}",
+        encoding
+    );
+}
+
+/// Table pragmas return rows and checks return a structured result
+#[wasm_bindgen_test]
+async fn test_pragma_rows_and_checks() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "pragma_rows.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    db.execute_internal("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+        .await
+        .unwrap();
+
+    let info = db
+        .pragma_internal("table_info", Some(&ColumnValue::Text("users".to_string())))
+        .await
+        .unwrap();
+    let PragmaValue::Rows(result) = info else {
+        panic!("table_info should return rows, got {:?}", info);
+    };
+    assert_eq!(result.rows.len(), 2);
+    assert!(result.columns.iter().any(|c| c == "notnull"));
+
+    let check = db.pragma_internal("integrity_check", None).await.unwrap();
+    let PragmaValue::Check(check) = check else {
+        panic!("integrity_check should return a check, got {:?}", check);
+    };
+    assert!(check.ok);
+    assert!(check.errors.is_empty());
+}
+
+/// Names that are not plain identifiers are rejected before reaching SQLite
+#[wasm_bindgen_test]
+async fn test_pragma_rejects_invalid_names() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "pragma_invalid.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    for name in ["user_version; DROP TABLE x", "", "1abc", "a.b.c"] {
+        let err = db.pragma_internal(name, None).await.unwrap_err();
+        assert_eq!(err.code, "INVALID_PRAGMA", "{}", name);
+    }
+    let err = db
+        .set_pragma_internal("user_version", &ColumnValue::Null)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "INVALID_PRAGMA");
+}
@@ -0,0 +1,95 @@
+//! Tests for Database::query_at_snapshot reading the last synced state
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database, DatabaseConfig, Persistence};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Unsynced writes are invisible to the snapshot until the next sync
+#[wasm_bindgen_test]
+async fn test_snapshot_sees_only_synced_writes() {
+    let mut db = Database::new_wasm("snapshot_synced".to_string())
+        .await
+        .expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE orders (id INTEGER, status TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO orders VALUES (1, 'paid'), (2, 'paid')")
+        .await
+        .unwrap();
+    db.sync_internal().await.unwrap();
+
+    db.execute_internal("INSERT INTO orders VALUES (3, 'pending')")
+        .await
+        .unwrap();
+
+    let result = db
+        .query_at_snapshot_internal(
+            "SELECT count(*) FROM orders WHERE id > ?",
+            &[ColumnValue::Integer(0)],
+        )
+        .await
+        .expect("snapshot query");
+    assert_eq!(result.rows[0].values[0], ColumnValue::Integer(2));
+
+    // The live connection still sees its own write
+    let live = db
+        .query_scalar_internal("SELECT count(*) FROM orders", &[], true)
+        .await
+        .unwrap();
+    assert_eq!(live, ColumnValue::Integer(3));
+
+    db.sync_internal().await.unwrap();
+    let result = db
+        .query_at_snapshot_internal("SELECT count(*) FROM orders", &[])
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Integer(3));
+
+    db.close().await.unwrap();
+    Database::delete_database("snapshot_synced".to_string())
+        .await
+        .unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn test_snapshot_rejects_writes() {
+    let mut db = Database::new_wasm("snapshot_writes".to_string())
+        .await
+        .expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE t (v INTEGER)")
+        .await
+        .unwrap();
+    db.sync_internal().await.unwrap();
+
+    let err = db
+        .query_at_snapshot_internal("INSERT INTO t VALUES (1)", &[])
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "READ_ONLY_QUERY");
+
+    db.close().await.unwrap();
+    Database::delete_database("snapshot_writes".to_string())
+        .await
+        .unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn test_snapshot_unavailable_for_memory_database() {
+    let db = Database::new(DatabaseConfig {
+        name: "snapshot_memory.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    let err = db
+        .query_at_snapshot_internal("SELECT 1", &[])
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "SNAPSHOT_UNAVAILABLE");
+}
@@ -0,0 +1,213 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database};
+
+/// Cursor yields rows in batches of the requested size
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_query_stream_batches() {
+    let mut db = Database::new_wasm("query_stream_batches_test".to_string())
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS numbers")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE numbers (n INTEGER)")
+        .await
+        .unwrap();
+    db.execute_internal("BEGIN").await.unwrap();
+    for n in 0..25 {
+        db.execute_with_params_internal(
+            "INSERT INTO numbers (n) VALUES (?)",
+            &[ColumnValue::Integer(n)],
+        )
+        .await
+        .unwrap();
+    }
+    db.execute_internal("COMMIT").await.unwrap();
+
+    let cursor = db
+        .query_stream_internal("SELECT n FROM numbers ORDER BY n", 10)
+        .unwrap();
+
+    let mut sizes = Vec::new();
+    let mut next_expected = 0;
+    while let Some(batch) = cursor.next_batch().unwrap() {
+        assert_eq!(batch.columns, vec!["n".to_string()]);
+        for row in &batch.rows {
+            assert_eq!(row.values[0], ColumnValue::Integer(next_expected));
+            next_expected += 1;
+        }
+        sizes.push(batch.rows.len());
+    }
+
+    assert_eq!(sizes, vec![10, 10, 5]);
+    assert!(cursor.is_finished());
+
+    // Exhausted cursor releases the database
+    db.execute_internal("SELECT COUNT(*) FROM numbers")
+        .await
+        .unwrap();
+    db.close().await.unwrap();
+}
+
+/// Other queries fail with CURSOR_BUSY while a cursor is open
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_query_while_cursor_open_is_busy() {
+    let mut db = Database::new_wasm("query_stream_busy_test".to_string())
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS numbers")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE numbers (n INTEGER)")
+        .await
+        .unwrap();
+    db.execute_internal("BEGIN").await.unwrap();
+    for n in 0..5 {
+        db.execute_with_params_internal(
+            "INSERT INTO numbers (n) VALUES (?)",
+            &[ColumnValue::Integer(n)],
+        )
+        .await
+        .unwrap();
+    }
+    db.execute_internal("COMMIT").await.unwrap();
+
+    let cursor = db
+        .query_stream_internal("SELECT n FROM numbers", 2)
+        .unwrap();
+    cursor.next_batch().unwrap();
+
+    let err = db.execute_internal("SELECT 1").await.unwrap_err();
+    assert_eq!(err.code, "CURSOR_BUSY");
+
+    let err = db
+        .query_stream_internal("SELECT n FROM numbers", 2)
+        .err()
+        .unwrap();
+    assert_eq!(err.code, "CURSOR_BUSY");
+
+    cursor.close();
+    assert!(cursor.is_finished());
+    db.execute_internal("SELECT 1").await.unwrap();
+    db.close().await.unwrap();
+}
+
+/// Dropping an unfinished cursor finalizes it and frees the database
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_dropped_cursor_releases_database() {
+    let mut db = Database::new_wasm("query_stream_drop_test".to_string())
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS numbers")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE numbers (n INTEGER)")
+        .await
+        .unwrap();
+    db.execute_internal("BEGIN").await.unwrap();
+    for n in 0..5 {
+        db.execute_with_params_internal(
+            "INSERT INTO numbers (n) VALUES (?)",
+            &[ColumnValue::Integer(n)],
+        )
+        .await
+        .unwrap();
+    }
+    db.execute_internal("COMMIT").await.unwrap();
+
+    {
+        let cursor = db
+            .query_stream_internal("SELECT n FROM numbers", 1)
+            .unwrap();
+        cursor.next_batch().unwrap();
+    }
+
+    let result = db
+        .execute_internal("SELECT COUNT(*) FROM numbers")
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Integer(5));
+    db.close().await.unwrap();
+}
+
+/// Empty result set finishes immediately
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_query_stream_empty_result() {
+    let mut db = Database::new_wasm("query_stream_empty_test".to_string())
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS numbers")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE numbers (n INTEGER)")
+        .await
+        .unwrap();
+
+    let cursor = db
+        .query_stream_internal("SELECT n FROM numbers", 10)
+        .unwrap();
+    assert!(cursor.next_batch().unwrap().is_none());
+    assert!(cursor.is_finished());
+
+    db.close().await.unwrap();
+}
+
+/// Open cursors count toward the connection's statement cap
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_open_statement_limit() {
+    use absurder_sql::DatabaseConfig;
+
+    let mut db = Database::new_wasm("query_stream_limit_test".to_string())
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS numbers")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE numbers (n INTEGER)")
+        .await
+        .unwrap();
+    db.execute_internal("BEGIN").await.unwrap();
+    for n in 0..5 {
+        db.execute_with_params_internal(
+            "INSERT INTO numbers (n) VALUES (?)",
+            &[ColumnValue::Integer(n)],
+        )
+        .await
+        .unwrap();
+    }
+    db.execute_internal("COMMIT").await.unwrap();
+    let config = DatabaseConfig {
+        name: "query_stream_limit_test.db".to_string(),
+        max_open_statements: Some(1),
+        ..Default::default()
+    };
+    let mut other = Database::new(config).await.unwrap();
+
+    let cursor = db
+        .query_stream_internal("SELECT n FROM numbers", 1)
+        .unwrap();
+    cursor.next_batch().unwrap();
+
+    // Both instances share one connection, so the cursor uses up the limit
+    let err = other.execute_internal("SELECT 1").await.unwrap_err();
+    assert_eq!(err.code, "TOO_MANY_OPEN_STATEMENTS");
+    assert!(err.message.contains("limit 1"));
+
+    cursor.close();
+    other.execute_internal("SELECT 1").await.unwrap();
+
+    other.close().await.unwrap();
+    db.close().await.unwrap();
+}
@@ -0,0 +1,95 @@
+//! Tests for query_timeout_ms, executeWithTimeout and interrupt()
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database, DatabaseConfig, Persistence};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Counts forever; only the timeout stops it
+const RUNAWAY: &str = "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) \
+                       SELECT count(*) FROM n";
+
+/// A runaway recursive CTE is aborted at the configured timeout
+#[wasm_bindgen_test]
+async fn test_recursive_cte_aborts_at_timeout() {
+    let config = DatabaseConfig {
+        name: "query_timeout_cte.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        query_timeout_ms: Some(200),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+
+    let start = js_sys::Date::now();
+    let err = db
+        .execute_internal(RUNAWAY)
+        .await
+        .expect_err("runaway query must be aborted");
+    let elapsed = js_sys::Date::now() - start;
+
+    assert_eq!(err.code, "QUERY_TIMEOUT");
+    assert!(elapsed >= 200.0, "aborted too early: {}ms", elapsed);
+    assert!(elapsed < 5000.0, "aborted too late: {}ms", elapsed);
+
+    // The connection is usable afterwards and later queries get a fresh budget
+    let value = db
+        .query_scalar_internal("SELECT 1 + 1", &[], true)
+        .await
+        .expect("query after timeout");
+    assert_eq!(value, ColumnValue::Integer(2));
+
+    db.close().await.unwrap();
+}
+
+/// A per-call timeout applies to that call only
+#[wasm_bindgen_test]
+async fn test_per_call_timeout_override() {
+    let config = DatabaseConfig {
+        name: "query_timeout_override.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+
+    let err = db
+        .execute_with_timeout_internal(RUNAWAY, &[], 100)
+        .await
+        .expect_err("runaway query must be aborted");
+    assert_eq!(err.code, "QUERY_TIMEOUT");
+
+    // Without a configured timeout, bounded queries run to completion
+    let value = db
+        .query_scalar_internal(
+            "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 100000) \
+             SELECT count(*) FROM n",
+            &[],
+            true,
+        )
+        .await
+        .expect("bounded query");
+    assert_eq!(value, ColumnValue::Integer(100_000));
+
+    db.close().await.unwrap();
+}
+
+/// interrupt() with nothing running leaves the next statement unaffected
+#[wasm_bindgen_test]
+async fn test_interrupt_when_idle_is_noop() {
+    let config = DatabaseConfig {
+        name: "query_timeout_interrupt.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.interrupt();
+
+    let value = db
+        .query_scalar_internal("SELECT 42", &[], true)
+        .await
+        .expect("query after idle interrupt");
+    assert_eq!(value, ColumnValue::Integer(42));
+
+    db.close().await.unwrap();
+}
@@ -0,0 +1,122 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database};
+
+/// Read-only instances see existing data but reject writes with READ_ONLY_DB
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_read_only_open_rejects_writes() {
+    let mut writer = Database::new_wasm("read_only_mode_test".to_string())
+        .await
+        .unwrap();
+    writer.allow_non_leader_writes(true).await.unwrap();
+    writer
+        .execute_internal("DROP TABLE IF EXISTS reports")
+        .await
+        .unwrap();
+    writer
+        .execute_internal("CREATE TABLE reports (id INTEGER PRIMARY KEY, total INTEGER)")
+        .await
+        .unwrap();
+    writer
+        .execute_internal("INSERT INTO reports (total) VALUES (42)")
+        .await
+        .unwrap();
+    writer.sync_internal().await.unwrap();
+
+    let mut reader = Database::open_read_only("read_only_mode_test".to_string())
+        .await
+        .unwrap();
+    assert!(reader.is_read_only());
+    assert!(!writer.is_read_only());
+
+    let result = reader
+        .execute_internal("SELECT total FROM reports")
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Integer(42));
+
+    let err = reader
+        .execute_internal("INSERT INTO reports (total) VALUES (1)")
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "READ_ONLY_DB");
+
+    let err = reader
+        .execute_with_params_internal("UPDATE reports SET total = ?", &[ColumnValue::Integer(0)])
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "READ_ONLY_DB");
+
+    // The public write path fails before any leader check
+    let err = reader
+        .execute("DELETE FROM reports")
+        .await
+        .unwrap_err()
+        .as_string()
+        .unwrap();
+    assert!(err.contains("openReadOnly"), "unexpected error: {}", err);
+
+    reader.close().await.unwrap();
+    writer.close().await.unwrap();
+}
+
+/// Writes are recognised by what the statement does, not by its first keyword
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_read_only_classifies_statements() {
+    let mut writer = Database::new_wasm("read_only_classify_test".to_string())
+        .await
+        .unwrap();
+    writer.allow_non_leader_writes(true).await.unwrap();
+    writer
+        .execute_internal(
+            "CREATE TABLE IF NOT EXISTS reports (id INTEGER PRIMARY KEY, total INTEGER)",
+        )
+        .await
+        .unwrap();
+    writer.sync_internal().await.unwrap();
+
+    let mut reader = Database::open_read_only("read_only_classify_test".to_string())
+        .await
+        .unwrap();
+
+    let err = reader
+        .execute_internal("WITH t(v) AS (SELECT 1) INSERT INTO reports (total) SELECT v FROM t")
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "READ_ONLY_DB", "CTE writes must be gated");
+
+    let err = reader
+        .execute_internal("SELECT 1; DELETE FROM reports")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.code, "READ_ONLY_DB",
+        "later statements must be classified"
+    );
+
+    let err = reader
+        .execute_internal("CREATE TABLE extra (id INTEGER)")
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "READ_ONLY_DB");
+
+    reader
+        .execute_internal("EXPLAIN INSERT INTO reports (total) VALUES (1)")
+        .await
+        .expect("EXPLAIN only reads");
+    reader
+        .execute_internal("WITH t(v) AS (SELECT 1) SELECT v FROM t")
+        .await
+        .expect("CTE reads are allowed");
+
+    reader.close().await.unwrap();
+    writer.close().await.unwrap();
+}
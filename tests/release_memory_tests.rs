@@ -0,0 +1,43 @@
+//! Tests for releaseMemory
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database, DatabaseConfig};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Page cache and clean cached blocks are released, and the data is still readable
+#[wasm_bindgen_test]
+async fn test_release_memory_after_bulk_insert() {
+    let config = DatabaseConfig {
+        name: "release_memory.db".to_string(),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE items (id INTEGER PRIMARY KEY, payload TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal(
+        "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 2000) \
+         INSERT INTO items SELECT x, hex(randomblob(64)) FROM n",
+    )
+    .await
+    .unwrap();
+    db.sync().await.unwrap();
+
+    let stats = db.release_memory_internal(true).expect("release memory");
+    assert!(stats.page_cache_bytes_freed > 0, "{:?}", stats);
+    assert!(stats.blocks_evicted > 0, "{:?}", stats);
+
+    let count = db
+        .query_scalar_internal("SELECT COUNT(*) FROM items", &[], false)
+        .await
+        .unwrap();
+    assert_eq!(count, ColumnValue::Integer(2000));
+
+    // Without the flag only the page cache is touched
+    let stats = db.release_memory_internal(false).unwrap();
+    assert_eq!(stats.blocks_evicted, 0);
+}
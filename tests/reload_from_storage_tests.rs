@@ -0,0 +1,63 @@
+//! Tests for reloadFromStorage
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::storage::vfs_sync::with_global_storage;
+use absurder_sql::{ColumnValue, Database, DatabaseConfig, StorageReloadReport};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// In-memory damage is discarded and the persisted contents come back intact
+#[wasm_bindgen_test]
+async fn test_reload_from_storage_discards_in_memory_corruption() {
+    let name = format!("reload_storage_{}.db", js_sys::Date::now() as u64);
+    let mut db = Database::new(DatabaseConfig {
+        name: name.clone(),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE items (id INTEGER PRIMARY KEY, label TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO items (label) VALUES ('persisted')")
+        .await
+        .unwrap();
+    db.sync().await.unwrap();
+
+    // Scribble over the shared in-memory copy of every block past the header
+    with_global_storage(|gs| {
+        if let Some(blocks) = gs.borrow_mut().get_mut(&name) {
+            for (id, data) in blocks.iter_mut() {
+                if *id > 0 {
+                    data.fill(0xAB);
+                }
+            }
+        }
+    });
+
+    let report: StorageReloadReport =
+        serde_wasm_bindgen::from_value(db.reload_from_storage().await.expect("reload")).unwrap();
+    assert!(report.blocks_loaded > 0, "{:?}", report);
+    assert!(report.corrupt_blocks.is_empty(), "{:?}", report);
+
+    let label = db
+        .query_scalar_internal("SELECT label FROM items", &[], false)
+        .await
+        .unwrap();
+    assert_eq!(label, ColumnValue::Text("persisted".to_string()));
+
+    // Unsynced writes are discarded by the reload
+    db.execute_internal("INSERT INTO items (label) VALUES ('unsynced')")
+        .await
+        .unwrap();
+    db.reload_from_storage().await.expect("reload again");
+    let count = db
+        .query_scalar_internal("SELECT COUNT(*) FROM items", &[], false)
+        .await
+        .unwrap();
+    assert_eq!(count, ColumnValue::Integer(1));
+    db.close().await.unwrap();
+}
@@ -0,0 +1,134 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::Database;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsValue;
+
+#[cfg(target_arch = "wasm32")]
+fn get(target: &JsValue, key: &str) -> JsValue {
+    js_sys::Reflect::get(target, &key.into()).unwrap()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn rows(result: &JsValue) -> js_sys::Array {
+    js_sys::Array::from(&get(result, "rows"))
+}
+
+/// ArrayOfArrays returns plain values in column order
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_execute_formatted_array_of_arrays() {
+    let mut db = Database::new_wasm("result_format_arrays".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS users")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE users (id INTEGER, name TEXT, avatar BLOB)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO users VALUES (1, 'Ada', x'0102'), (2, 'Lin', NULL)")
+        .await
+        .unwrap();
+    let result = db
+        .execute_formatted(
+            "SELECT id, name, avatar FROM users ORDER BY id",
+            JsValue::UNDEFINED,
+            "ArrayOfArrays".into(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(js_sys::Array::from(&get(&result, "columns")).length(), 3);
+    let first = js_sys::Array::from(&rows(&result).get(0));
+    assert_eq!(first.get(0).as_f64(), Some(1.0));
+    assert_eq!(first.get(1).as_string().as_deref(), Some("Ada"));
+    assert_eq!(js_sys::Uint8Array::new(&first.get(2)).to_vec(), vec![1, 2]);
+    let second = js_sys::Array::from(&rows(&result).get(1));
+    assert!(second.get(2).is_null());
+
+    db.close().await.unwrap();
+}
+
+/// ArrayOfObjects keys each row by column name and honors bound parameters
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_execute_formatted_array_of_objects() {
+    let mut db = Database::new_wasm("result_format_objects".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS users")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE users (id INTEGER, name TEXT, avatar BLOB)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO users VALUES (1, 'Ada', x'0102'), (2, 'Lin', NULL)")
+        .await
+        .unwrap();
+    let params =
+        serde_wasm_bindgen::to_value(&vec![absurder_sql::ColumnValue::Integer(2)]).unwrap();
+    let result = db
+        .execute_formatted(
+            "SELECT id, name FROM users WHERE id = ?",
+            params,
+            "ArrayOfObjects".into(),
+        )
+        .await
+        .unwrap();
+
+    let all = rows(&result);
+    assert_eq!(all.length(), 1);
+    let row = all.get(0);
+    assert_eq!(get(&row, "id").as_f64(), Some(2.0));
+    assert_eq!(get(&row, "name").as_string().as_deref(), Some("Lin"));
+
+    db.close().await.unwrap();
+}
+
+/// TaggedEnum (the default) matches the shape returned by `execute`
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_execute_formatted_tagged_default() {
+    let mut db = Database::new_wasm("result_format_tagged".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS users")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE users (id INTEGER, name TEXT, avatar BLOB)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO users VALUES (1, 'Ada', x'0102'), (2, 'Lin', NULL)")
+        .await
+        .unwrap();
+    let result = db
+        .execute_formatted(
+            "SELECT name FROM users WHERE id = 1",
+            JsValue::UNDEFINED,
+            JsValue::UNDEFINED,
+        )
+        .await
+        .unwrap();
+
+    let cell = js_sys::Array::from(&get(&rows(&result).get(0), "values")).get(0);
+    assert_eq!(get(&cell, "type").as_string().as_deref(), Some("Text"));
+    assert_eq!(get(&cell, "value").as_string().as_deref(), Some("Ada"));
+
+    let err = db
+        .execute_formatted("SELECT 1", JsValue::UNDEFINED, "Csv".into())
+        .await
+        .expect_err("unknown formats are rejected");
+    assert!(err.as_string().unwrap().contains("Invalid result format"));
+
+    db.close().await.unwrap();
+}
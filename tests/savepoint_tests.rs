@@ -0,0 +1,95 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+use absurder_sql::{ColumnValue, Database};
+
+/// Rolling back an inner savepoint keeps the outer savepoint's changes
+#[wasm_bindgen_test]
+async fn test_nested_savepoint_partial_rollback() {
+    let mut db = Database::new_wasm("savepoint_nested_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS profile")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE profile (name TEXT, city TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO profile VALUES ('Grace', 'Arlington')")
+        .await
+        .unwrap();
+
+    db.savepoint_internal("form").await.unwrap();
+    db.execute_internal("UPDATE profile SET name = 'Ada'")
+        .await
+        .unwrap();
+    db.savepoint_internal("address").await.unwrap();
+    db.execute_internal("UPDATE profile SET city = 'London'")
+        .await
+        .unwrap();
+    assert_eq!(db.savepoint_depth(), 2);
+
+    db.rollback_to_savepoint_internal("address").await.unwrap();
+    assert_eq!(db.savepoint_depth(), 2, "rolled-back savepoint stays open");
+    db.release_savepoint_internal("form").await.unwrap();
+    assert_eq!(db.savepoint_depth(), 0);
+
+    let result = db
+        .execute_internal("SELECT name, city FROM profile")
+        .await
+        .unwrap();
+    assert_eq!(
+        result.rows[0].values[0],
+        ColumnValue::Text("Ada".to_string())
+    );
+    assert_eq!(
+        result.rows[0].values[1],
+        ColumnValue::Text("Arlington".to_string())
+    );
+
+    db.close().await.unwrap();
+}
+
+/// Releasing or rolling back to a savepoint that is not open is reported
+#[wasm_bindgen_test]
+async fn test_unbalanced_savepoint_calls() {
+    let mut db = Database::new_wasm("savepoint_unbalanced_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS profile")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE profile (name TEXT, city TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO profile VALUES ('Grace', 'Arlington')")
+        .await
+        .unwrap();
+
+    let err = db.release_savepoint_internal("never").await.unwrap_err();
+    assert_eq!(err.code, "SAVEPOINT_UNBALANCED");
+
+    db.savepoint_internal("outer").await.unwrap();
+    db.savepoint_internal("inner").await.unwrap();
+    db.release_savepoint_internal("outer").await.unwrap();
+    let err = db
+        .rollback_to_savepoint_internal("inner")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.code, "SAVEPOINT_UNBALANCED",
+        "releasing outer also released inner"
+    );
+
+    // A raw COMMIT ends the transaction and the stale savepoints are forgotten
+    db.savepoint_internal("raw").await.unwrap();
+    db.execute_internal("COMMIT").await.unwrap();
+    assert_eq!(db.savepoint_depth(), 0);
+
+    db.close().await.unwrap();
+}
@@ -0,0 +1,176 @@
+//! Tests for exportSchema/applySchema
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{ColumnValue, Database, DatabaseConfig, Persistence};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Tables, indexes, views and triggers round-trip without any rows
+#[wasm_bindgen_test]
+async fn test_schema_round_trip() {
+    let source_config = DatabaseConfig {
+        name: "schema_export_source.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut source = Database::new(source_config).await.expect("open database");
+    for sql in [
+        "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL)",
+        "CREATE TABLE audit (user_id INTEGER, at TEXT)",
+        "CREATE INDEX idx_users_name ON users(name)",
+        "CREATE VIEW user_names AS SELECT name FROM users",
+        "CREATE TRIGGER users_audit AFTER INSERT ON users \
+         BEGIN INSERT INTO audit VALUES (new.id, 'now'); END",
+        "INSERT INTO users (name) VALUES ('alice')",
+    ] {
+        source.execute_internal(sql).await.expect(sql);
+    }
+
+    let ddl = source
+        .export_schema_internal(&[])
+        .await
+        .expect("export schema");
+    assert!(ddl.contains("CREATE INDEX idx_users_name"));
+    assert!(ddl.contains("CREATE VIEW user_names"));
+    assert!(ddl.contains("CREATE TRIGGER users_audit"));
+    assert!(
+        !ddl.contains("sqlite_sequence"),
+        "internal tables are skipped"
+    );
+
+    let target_config = DatabaseConfig {
+        name: "schema_export_target.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut target = Database::new(target_config).await.expect("open database");
+    target
+        .apply_schema_internal(&ddl)
+        .await
+        .expect("apply schema");
+    assert_eq!(
+        target.export_schema_internal(&[]).await.unwrap(),
+        ddl,
+        "applied schema matches the source"
+    );
+    assert_eq!(
+        target
+            .query_scalar_internal("SELECT COUNT(*) FROM users", &[], false)
+            .await
+            .unwrap(),
+        ColumnValue::Integer(0)
+    );
+
+    // The trigger came across too
+    target
+        .execute_internal("INSERT INTO users (name) VALUES ('bob')")
+        .await
+        .unwrap();
+    assert_eq!(
+        target
+            .query_scalar_internal("SELECT COUNT(*) FROM audit", &[], false)
+            .await
+            .unwrap(),
+        ColumnValue::Integer(1)
+    );
+}
+
+/// A failing statement leaves none of the script applied
+#[wasm_bindgen_test]
+async fn test_apply_schema_is_atomic() {
+    let config = DatabaseConfig {
+        name: "schema_apply_atomic.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    let err = db
+        .apply_schema_internal("CREATE TABLE a (id INTEGER); CREATE TABLE a (id INTEGER);")
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "SQLITE_ERROR");
+
+    assert_eq!(
+        db.query_scalar_internal(
+            "SELECT COUNT(*) FROM sqlite_master WHERE name = 'a'",
+            &[],
+            false
+        )
+        .await
+        .unwrap(),
+        ColumnValue::Integer(0)
+    );
+}
+
+/// A table-scoped export carries the views and triggers built on the chosen tables only
+#[wasm_bindgen_test]
+async fn test_table_scoped_export_includes_dependents() {
+    let config = DatabaseConfig {
+        name: "schema_export_scoped.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    for sql in [
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, total REAL)",
+        "CREATE TABLE customers (id INTEGER PRIMARY KEY, name TEXT)",
+        "CREATE TABLE order_log (order_id INTEGER)",
+        "CREATE INDEX idx_orders_total ON orders(total)",
+        "CREATE VIEW big_orders AS SELECT * FROM orders WHERE total > 100",
+        "CREATE VIEW order_names AS SELECT o.id, c.name FROM orders o JOIN customers c",
+        "CREATE TRIGGER orders_log AFTER INSERT ON orders \
+         BEGIN INSERT INTO order_log VALUES (new.id); END",
+        "CREATE TRIGGER customers_touch AFTER UPDATE ON customers BEGIN SELECT 1; END",
+    ] {
+        db.execute_internal(sql).await.expect(sql);
+    }
+
+    let ddl = db
+        .export_schema_internal(&["orders".to_string(), "order_log".to_string()])
+        .await
+        .expect("scoped export");
+    assert!(ddl.contains("CREATE TABLE orders"));
+    assert!(ddl.contains("CREATE TABLE order_log"));
+    assert!(ddl.contains("CREATE INDEX idx_orders_total"));
+    assert!(ddl.contains("CREATE VIEW big_orders"));
+    assert!(ddl.contains("CREATE TRIGGER orders_log"));
+    assert!(!ddl.contains("customers"), "{}", ddl);
+    assert!(
+        !ddl.contains("order_names"),
+        "views on other tables are left out"
+    );
+
+    let err = db
+        .export_schema_internal(&["missing".to_string()])
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "TABLE_NOT_FOUND");
+}
+
+/// Statements cached before applySchema are prepared again afterwards
+#[wasm_bindgen_test]
+async fn test_apply_schema_invalidates_cached_statements() {
+    let config = DatabaseConfig {
+        name: "schema_apply_invalidate.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE items (id INTEGER PRIMARY KEY)")
+        .await
+        .unwrap();
+    db.execute_internal("SELECT * FROM items").await.unwrap();
+    let misses = db.cache_stats().statements.misses;
+
+    db.apply_schema_internal("ALTER TABLE items ADD COLUMN name TEXT;")
+        .await
+        .expect("apply schema");
+    let result = db.execute_internal("SELECT * FROM items").await.unwrap();
+    assert_eq!(result.columns, vec!["id", "name"]);
+    assert!(
+        db.cache_stats().statements.misses > misses,
+        "the statement cached before applySchema should be prepared again"
+    );
+}
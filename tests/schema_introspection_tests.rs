@@ -0,0 +1,174 @@
+//! Tests for listTables/getTableSchema/listIndexes/listForeignKeys
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{Database, DatabaseConfig, Persistence};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// User tables are listed by name; internal tables and views only on request
+#[wasm_bindgen_test]
+async fn test_list_tables() {
+    let config = DatabaseConfig {
+        name: "schema_list_tables.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    for sql in [
+        "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, \
+         email TEXT NOT NULL UNIQUE, status TEXT DEFAULT 'active')",
+        "CREATE TABLE orders (id INTEGER, user_id INTEGER NOT NULL, region TEXT, \
+         PRIMARY KEY (region, id), \
+         FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE) WITHOUT ROWID",
+        "CREATE INDEX idx_orders_user ON orders(user_id, lower(region)) WHERE user_id > 0",
+        "CREATE VIEW active_users AS SELECT * FROM users WHERE status = 'active'",
+    ] {
+        db.execute_internal(sql).await.expect(sql);
+    }
+
+    let tables = db.list_tables_internal(false).await.expect("list tables");
+    let names: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["orders", "users"]);
+    assert!(tables[0].without_rowid);
+    assert_eq!(tables[1].column_count, 3);
+
+    let all = db
+        .list_tables_internal(true)
+        .await
+        .expect("list all tables");
+    assert!(all.iter().any(|t| t.name == "sqlite_sequence"));
+    assert!(!all.iter().any(|t| t.name == "active_users"));
+
+    db.close().await.unwrap();
+}
+
+/// Columns report type, nullability, default and primary key position
+#[wasm_bindgen_test]
+async fn test_table_schema() {
+    let config = DatabaseConfig {
+        name: "schema_table_schema.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    for sql in [
+        "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, \
+         email TEXT NOT NULL UNIQUE, status TEXT DEFAULT 'active')",
+        "CREATE TABLE orders (id INTEGER, user_id INTEGER NOT NULL, region TEXT, \
+         PRIMARY KEY (region, id), \
+         FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE) WITHOUT ROWID",
+        "CREATE INDEX idx_orders_user ON orders(user_id, lower(region)) WHERE user_id > 0",
+        "CREATE VIEW active_users AS SELECT * FROM users WHERE status = 'active'",
+    ] {
+        db.execute_internal(sql).await.expect(sql);
+    }
+
+    let schema = db.table_schema_internal("ORDERS").await.expect("schema");
+    assert_eq!(schema.name, "orders");
+    let columns: Vec<(&str, u32)> = schema
+        .columns
+        .iter()
+        .map(|c| (c.name.as_str(), c.primary_key))
+        .collect();
+    assert_eq!(columns, vec![("id", 2), ("user_id", 0), ("region", 1)]);
+    assert!(!schema.columns[1].nullable);
+
+    let users = db.table_schema_internal("users").await.expect("schema");
+    assert_eq!(users.columns[2].declared_type, "TEXT");
+    assert_eq!(users.columns[2].default_value.as_deref(), Some("'active'"));
+
+    let err = db
+        .table_schema_internal("missing")
+        .await
+        .expect_err("unknown table");
+    assert_eq!(err.code, "TABLE_NOT_FOUND");
+
+    db.close().await.unwrap();
+}
+
+/// Declared indexes are listed with their columns; autoindexes only on request
+#[wasm_bindgen_test]
+async fn test_list_indexes() {
+    let config = DatabaseConfig {
+        name: "schema_list_indexes.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    for sql in [
+        "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, \
+         email TEXT NOT NULL UNIQUE, status TEXT DEFAULT 'active')",
+        "CREATE TABLE orders (id INTEGER, user_id INTEGER NOT NULL, region TEXT, \
+         PRIMARY KEY (region, id), \
+         FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE) WITHOUT ROWID",
+        "CREATE INDEX idx_orders_user ON orders(user_id, lower(region)) WHERE user_id > 0",
+        "CREATE VIEW active_users AS SELECT * FROM users WHERE status = 'active'",
+    ] {
+        db.execute_internal(sql).await.expect(sql);
+    }
+
+    let indexes = db
+        .list_indexes_internal("orders", false)
+        .await
+        .expect("list indexes");
+    assert_eq!(indexes.len(), 1);
+    assert_eq!(indexes[0].name, "idx_orders_user");
+    assert!(indexes[0].partial);
+    assert_eq!(indexes[0].columns, vec![Some("user_id".to_string()), None]);
+
+    let users = db
+        .list_indexes_internal("users", true)
+        .await
+        .expect("list internal indexes");
+    assert!(
+        users
+            .iter()
+            .any(|i| i.unique && i.origin == "u" && i.columns == vec![Some("email".to_string())])
+    );
+
+    db.close().await.unwrap();
+}
+
+/// Foreign keys are grouped per constraint with their actions
+#[wasm_bindgen_test]
+async fn test_list_foreign_keys() {
+    let config = DatabaseConfig {
+        name: "schema_list_foreign_keys.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    for sql in [
+        "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, \
+         email TEXT NOT NULL UNIQUE, status TEXT DEFAULT 'active')",
+        "CREATE TABLE orders (id INTEGER, user_id INTEGER NOT NULL, region TEXT, \
+         PRIMARY KEY (region, id), \
+         FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE) WITHOUT ROWID",
+        "CREATE INDEX idx_orders_user ON orders(user_id, lower(region)) WHERE user_id > 0",
+        "CREATE VIEW active_users AS SELECT * FROM users WHERE status = 'active'",
+    ] {
+        db.execute_internal(sql).await.expect(sql);
+    }
+
+    let keys = db
+        .list_foreign_keys_internal("orders")
+        .await
+        .expect("list foreign keys");
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].table, "users");
+    assert_eq!(keys[0].from, vec!["user_id".to_string()]);
+    assert_eq!(keys[0].to, vec![Some("id".to_string())]);
+    assert_eq!(keys[0].on_delete, "CASCADE");
+    assert_eq!(keys[0].on_update, "NO ACTION");
+
+    assert!(
+        db.list_foreign_keys_internal("users")
+            .await
+            .expect("no foreign keys")
+            .is_empty()
+    );
+
+    db.close().await.unwrap();
+}
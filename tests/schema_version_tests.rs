@@ -0,0 +1,107 @@
+//! Tests for reading the schema cookie through Database::schema_version
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{Database, DatabaseConfig, Persistence};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Schema changes bump the version; data changes leave it alone
+#[wasm_bindgen_test]
+async fn test_schema_version_tracks_schema_changes() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "schema_version_tracks.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    let initial = db.schema_version().unwrap();
+
+    db.execute_internal("CREATE TABLE items (id INTEGER)")
+        .await
+        .unwrap();
+    let after_create = db.schema_version().unwrap();
+    assert!(after_create > initial);
+
+    db.execute_internal("INSERT INTO items VALUES (1)")
+        .await
+        .unwrap();
+    assert_eq!(db.schema_version().unwrap(), after_create);
+
+    db.execute_internal("ALTER TABLE items ADD COLUMN name TEXT")
+        .await
+        .unwrap();
+    assert!(db.schema_version().unwrap() > after_create);
+
+    // Cached statements see the new column after the version moved
+    let result = db.execute_internal("SELECT * FROM items").await.unwrap();
+    assert_eq!(result.columns, vec!["id".to_string(), "name".to_string()]);
+
+    db.close().await.unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn test_schema_version_fails_after_close() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "schema_version_closed.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    db.close().await.unwrap();
+    let err = db.schema_version().unwrap_err();
+    assert_eq!(err.code, "DATABASE_CLOSED");
+}
+
+/// A schema change made on another connection drops statements cached before it
+#[wasm_bindgen_test]
+async fn test_schema_version_invalidates_after_external_change() {
+    let mut writer = Database::new_wasm("schema_version_external".to_string())
+        .await
+        .unwrap();
+    writer.allow_non_leader_writes(true).await.unwrap();
+    writer
+        .execute_internal("DROP TABLE IF EXISTS items")
+        .await
+        .unwrap();
+    writer
+        .execute_internal("CREATE TABLE items (id INTEGER)")
+        .await
+        .unwrap();
+    writer.sync_internal().await.unwrap();
+
+    // The read-only instance has its own connection, so only the cookie reports the change
+    let mut reader = Database::open_read_only("schema_version_external".to_string())
+        .await
+        .unwrap();
+    let before = reader.schema_version().unwrap();
+    reader
+        .execute_internal("SELECT * FROM items")
+        .await
+        .unwrap();
+    assert_eq!(reader.cache_stats().statements.size, 1);
+    let misses = reader.cache_stats().statements.misses;
+
+    writer
+        .execute_internal("ALTER TABLE items ADD COLUMN name TEXT")
+        .await
+        .unwrap();
+    writer.sync_internal().await.unwrap();
+    assert!(reader.schema_version().unwrap() > before);
+
+    let result = reader
+        .execute_internal("SELECT * FROM items")
+        .await
+        .unwrap();
+    assert_eq!(result.columns, vec!["id".to_string(), "name".to_string()]);
+    assert!(
+        reader.cache_stats().statements.misses > misses,
+        "the statement cached before the change should be prepared again"
+    );
+
+    reader.close().await.unwrap();
+    writer.close().await.unwrap();
+}
@@ -0,0 +1,81 @@
+//! Tests for disabling leader election with `multi_tab: false` or `Coordination::Disabled`
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::storage::BlockStorage;
+use absurder_sql::{Coordination, Database, DatabaseConfig};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Single-tab storage reports leadership without ever starting an election
+#[wasm_bindgen_test]
+async fn test_single_tab_storage_skips_election() {
+    let storage = BlockStorage::new("single_tab_storage")
+        .await
+        .expect("create storage");
+    storage.set_multi_tab(false);
+
+    assert!(storage.is_leader().await);
+    assert!(
+        storage.leader_election.borrow().is_none(),
+        "no LeaderElectionManager should be created"
+    );
+}
+
+/// Writes succeed without leadership checks or allowNonLeaderWrites
+#[wasm_bindgen_test]
+async fn test_single_tab_database_writes_without_election() {
+    let config = DatabaseConfig {
+        name: "single_tab_writes.db".to_string(),
+        multi_tab: Some(false),
+        ..Default::default()
+    };
+    let mut db = Database::new(config)
+        .await
+        .expect("open single-tab database");
+
+    db.execute_internal("CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT)")
+        .await
+        .expect("create table");
+    db.execute_internal("INSERT INTO t (v) VALUES ('a')")
+        .await
+        .expect("insert without leadership");
+    assert!(db.is_leader_wasm().await.unwrap().as_bool().unwrap());
+
+    let storage =
+        absurder_sql::vfs::indexeddb_vfs::get_storage_with_fallback("single_tab_writes.db")
+            .expect("storage registered");
+    assert!(storage.leader_election.borrow().is_none());
+
+    db.close().await.unwrap();
+}
+
+/// `Coordination::Disabled` wins over `multi_tab` and skips the election entirely
+#[wasm_bindgen_test]
+async fn test_coordination_disabled_skips_election() {
+    let config = DatabaseConfig {
+        name: "coordination_disabled.db".to_string(),
+        multi_tab: Some(true),
+        coordination: Some(Coordination::Disabled),
+        ..Default::default()
+    };
+    let mut db = Database::new(config)
+        .await
+        .expect("open uncoordinated database");
+
+    db.execute_internal("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+        .await
+        .expect("create table");
+    db.execute_internal("INSERT INTO t DEFAULT VALUES")
+        .await
+        .expect("insert without leadership");
+    db.sync_internal().await.expect("sync without broadcasting");
+
+    let storage =
+        absurder_sql::vfs::indexeddb_vfs::get_storage_with_fallback("coordination_disabled.db")
+            .expect("storage registered");
+    assert!(storage.leader_election.borrow().is_none());
+
+    db.close().await.unwrap();
+}
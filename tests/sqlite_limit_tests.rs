@@ -0,0 +1,54 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::Database;
+
+/// Lowered limits are enforced and the previous value is returned
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_set_limit_enforced() {
+    let mut db = Database::new_wasm("sqlite_limit_test".to_string())
+        .await
+        .unwrap();
+
+    let original = db.set_limit_internal("sql_length", 64).unwrap();
+    assert!(original > 64);
+    assert_eq!(db.set_limit_internal("sql_length", -1).unwrap(), 64);
+
+    let long_sql = format!("SELECT '{}'", "x".repeat(100));
+    let err = db
+        .execute_internal(&long_sql)
+        .await
+        .expect_err("statement over the SQL length limit must fail");
+    assert!(err.message.contains("too long"), "{}", err.message);
+
+    db.set_limit_internal("SQLITE_LIMIT_SQL_LENGTH", original)
+        .unwrap();
+    db.execute_internal(&long_sql).await.unwrap();
+
+    db.set_limit_internal("compound_select", 2).unwrap();
+    let err = db
+        .execute_internal("SELECT 1 UNION SELECT 2 UNION SELECT 3")
+        .await
+        .expect_err("compound SELECT over the limit must fail");
+    assert!(err.message.contains("too many terms"), "{}", err.message);
+
+    db.close().await.unwrap();
+}
+
+/// Unknown categories are rejected rather than ignored
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_set_limit_unknown_category() {
+    let mut db = Database::new_wasm("sqlite_limit_unknown_test".to_string())
+        .await
+        .unwrap();
+    let err = db.set_limit_internal("max_rows", 10).unwrap_err();
+    assert_eq!(err.code, "INVALID_LIMIT");
+    db.close().await.unwrap();
+}
@@ -119,6 +119,65 @@ async fn test_startup_recovery_sample_mode() {
     }
 }
 
+#[cfg(feature = "fs_persist")]
+#[tokio::test]
+#[serial]
+async fn test_startup_recovery_random_mode() {
+    let tmp = TempDir::new().expect("tempdir");
+    common::set_var("ABSURDERSQL_FS_BASE", tmp.path());
+    let db = "test_startup_recovery_random";
+
+    // Create instance A: write many blocks
+    {
+        let mut a = BlockStorage::new_with_capacity(db, 40)
+            .await
+            .expect("create A");
+        for i in 0..30 {
+            let id = a.allocate_block().await.expect("alloc");
+            let data = vec![i as u8 + 1; BLOCK_SIZE];
+            a.write_block(id, data).await.expect("write");
+        }
+        a.sync().await.expect("sync A");
+    }
+
+    let sample = |seed: u64| async move {
+        let recovery_opts = RecoveryOptions {
+            mode: RecoveryMode::Random { count: 5, seed },
+            on_corruption: CorruptionAction::Report,
+        };
+        let storage = BlockStorage::new_with_recovery_options(db, recovery_opts)
+            .await
+            .expect("open with random recovery");
+        storage.get_recovery_report().clone()
+    };
+
+    let first = sample(7).await;
+    assert_eq!(first.total_blocks_verified, 5);
+    assert_eq!(
+        first.sampled_blocks.len(),
+        5,
+        "Report should list sampled ids"
+    );
+    assert!(first.corrupted_blocks.is_empty());
+
+    let again = sample(7).await;
+    assert_eq!(
+        first.sampled_blocks, again.sampled_blocks,
+        "The same seed should sample the same blocks"
+    );
+
+    // Across seeds the samples should cover more than one fixed prefix of ids
+    let mut seen = std::collections::HashSet::new();
+    for seed in 0..8 {
+        seen.extend(sample(seed).await.sampled_blocks);
+    }
+    assert!(
+        seen.len() > 5,
+        "Random sampling should vary with the seed, saw {:?}",
+        seen
+    );
+}
+
 #[cfg(feature = "fs_persist")]
 #[tokio::test]
 #[serial]
@@ -168,5 +227,8 @@ async fn test_startup_recovery_repair_mode() {
             "Should repair corrupted blocks"
         );
         assert!(report.repaired_blocks.contains(&1), "Should repair block 1");
+        // The block files are the only copy, so there is nothing to restore from
+        assert_eq!(report.deleted_blocks, report.repaired_blocks);
+        assert!(report.restored_blocks.is_empty());
     }
 }
@@ -0,0 +1,161 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::storage::statement_cache::StatementCacheStats;
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database};
+
+#[cfg(target_arch = "wasm32")]
+fn cache_stats(db: &Database) -> StatementCacheStats {
+    serde_wasm_bindgen::from_value(db.get_statement_cache_stats().unwrap()).unwrap()
+}
+
+/// Repeated identical SQL should reuse the compiled statement
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_repeated_query_hits_cache() {
+    let mut db = Database::new_wasm("stmt_cache_hit_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+
+    db.execute_internal("CREATE TABLE IF NOT EXISTS items (id INTEGER PRIMARY KEY, name TEXT)")
+        .await
+        .unwrap();
+    db.clear_statement_cache();
+
+    let before = cache_stats(&db);
+
+    for i in 0..5 {
+        db.execute_with_params_internal(
+            "INSERT INTO items (name) VALUES (?)",
+            &[ColumnValue::Text(format!("item{}", i))],
+        )
+        .await
+        .unwrap();
+    }
+
+    let after = cache_stats(&db);
+    assert_eq!(
+        after.misses - before.misses,
+        1,
+        "Only the first insert should prepare"
+    );
+    assert_eq!(
+        after.hits - before.hits,
+        4,
+        "Later inserts should reuse the statement"
+    );
+
+    // Bindings must not leak between executions of a cached statement
+    let result = db
+        .execute_internal("SELECT name FROM items ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(result.rows.len(), 5);
+    assert_eq!(
+        result.rows[4].values[0],
+        ColumnValue::Text("item4".to_string())
+    );
+
+    db.close().await.unwrap();
+}
+
+/// clearStatementCache should empty the cache
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_clear_statement_cache() {
+    let mut db = Database::new_wasm("stmt_cache_clear_test".to_string())
+        .await
+        .unwrap();
+
+    db.execute_internal("SELECT 1").await.unwrap();
+    assert!(cache_stats(&db).size > 0, "SELECT should be cached");
+
+    db.clear_statement_cache();
+    assert_eq!(cache_stats(&db).size, 0);
+
+    // Executing again after clearing re-prepares the statement
+    let misses_before = cache_stats(&db).misses;
+    db.execute_internal("SELECT 1").await.unwrap();
+    assert_eq!(cache_stats(&db).misses, misses_before + 1);
+
+    db.close().await.unwrap();
+}
+
+/// Cache must stay within its capacity and keep working after eviction
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_statement_cache_eviction() {
+    let mut db = Database::new_wasm("stmt_cache_evict_test".to_string())
+        .await
+        .unwrap();
+
+    let capacity = cache_stats(&db).capacity;
+    for i in 0..(capacity + 10) {
+        db.execute_internal(&format!("SELECT {}", i)).await.unwrap();
+    }
+    assert_eq!(cache_stats(&db).size, capacity);
+
+    // The oldest statement was evicted and must be prepared again
+    let result = db.execute_internal("SELECT 0").await.unwrap();
+    assert_eq!(result.rows[0].values[0], ColumnValue::Integer(0));
+
+    db.close().await.unwrap();
+}
+
+/// Closing the database finalizes all cached statements
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_close_finalizes_cached_statements() {
+    let mut db = Database::new_wasm("stmt_cache_close_test".to_string())
+        .await
+        .unwrap();
+
+    db.execute_internal("SELECT 1").await.unwrap();
+    db.close().await.unwrap();
+
+    assert_eq!(cache_stats(&db).size, 0);
+}
+
+/// A schema change discards cached statements so results reflect the new schema
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_schema_change_invalidates_cache() {
+    let mut db = Database::new_wasm("stmt_cache_schema_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+
+    db.execute_internal("DROP TABLE IF EXISTS widgets")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE widgets (id INTEGER PRIMARY KEY)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO widgets (id) VALUES (1)")
+        .await
+        .unwrap();
+
+    let before = db.execute_internal("SELECT * FROM widgets").await.unwrap();
+    assert_eq!(before.columns, vec!["id".to_string()]);
+    assert!(cache_stats(&db).size > 0, "SELECT should be cached");
+
+    db.execute_internal("ALTER TABLE widgets ADD COLUMN color TEXT DEFAULT 'red'")
+        .await
+        .unwrap();
+
+    let after = db.execute_internal("SELECT * FROM widgets").await.unwrap();
+    assert_eq!(after.columns, vec!["id".to_string(), "color".to_string()]);
+    assert_eq!(
+        after.rows[0].values[1],
+        ColumnValue::Text("red".to_string())
+    );
+
+    db.close().await.unwrap();
+}
@@ -0,0 +1,98 @@
+//! Tests for streaming query results to a MessagePort
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{Database, DatabaseConfig, Persistence};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+async fn sleep_ms(ms: i32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms)
+            .unwrap();
+    });
+    wasm_bindgen_futures::JsFuture::from(promise).await.unwrap();
+}
+
+fn field(message: &JsValue, key: &str) -> JsValue {
+    js_sys::Reflect::get(message, &key.into()).unwrap()
+}
+
+/// Batches arrive in order with blobs intact, followed by a done message
+#[wasm_bindgen_test]
+async fn test_stream_to_port_posts_batches() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "stream_to_port.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    })
+    .await
+    .expect("open");
+    db.execute_internal("CREATE TABLE files (id INTEGER PRIMARY KEY, data BLOB)")
+        .await
+        .unwrap();
+    db.execute_internal(
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 25)
+         INSERT INTO files SELECT i, zeroblob(i) FROM n",
+    )
+    .await
+    .unwrap();
+
+    let channel = web_sys::MessageChannel::new().unwrap();
+    let received: Rc<RefCell<Vec<JsValue>>> = Rc::new(RefCell::new(Vec::new()));
+    let sink = received.clone();
+    let on_message =
+        Closure::<dyn FnMut(web_sys::MessageEvent)>::new(move |event: web_sys::MessageEvent| {
+            sink.borrow_mut().push(event.data())
+        });
+    channel
+        .port2()
+        .set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+    let promise = db
+        .stream_to_port(
+            "SELECT id, data FROM files ORDER BY id",
+            channel.port1(),
+            10,
+        )
+        .unwrap();
+    let row_count = wasm_bindgen_futures::JsFuture::from(promise).await.unwrap();
+    assert_eq!(row_count.as_f64(), Some(25.0));
+
+    for _ in 0..50 {
+        let done = received
+            .borrow()
+            .last()
+            .is_some_and(|m| field(m, "type") == JsValue::from_str("done"));
+        if done {
+            break;
+        }
+        sleep_ms(10).await;
+    }
+
+    let messages = received.borrow();
+    assert_eq!(messages.len(), 4, "three batches and a done message");
+    let mut next_id = 1.0;
+    for batch in &messages[..3] {
+        assert_eq!(field(batch, "type"), JsValue::from_str("rows"));
+        for row in js_sys::Array::from(&field(batch, "rows")).iter() {
+            let row = js_sys::Array::from(&row);
+            assert_eq!(row.get(0).as_f64(), Some(next_id));
+            let data = row.get(1).dyn_into::<js_sys::Uint8Array>().unwrap();
+            assert_eq!(data.length() as f64, next_id);
+            next_id += 1.0;
+        }
+    }
+    assert_eq!(field(&messages[3], "rowCount").as_f64(), Some(25.0));
+
+    // The cursor is closed once the stream ends
+    db.execute_internal("SELECT 1").await.unwrap();
+    channel.port2().set_onmessage(None);
+}
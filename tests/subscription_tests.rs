@@ -0,0 +1,276 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database, QueryResult};
+#[cfg(target_arch = "wasm32")]
+use std::cell::RefCell;
+#[cfg(target_arch = "wasm32")]
+use std::rc::Rc;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Build a JS callback that records every QueryResult it receives
+#[cfg(target_arch = "wasm32")]
+fn recording_callback() -> (js_sys::Function, Rc<RefCell<Vec<QueryResult>>>) {
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let sink = received.clone();
+    let closure = Closure::wrap(Box::new(move |value: JsValue| {
+        let result: QueryResult = serde_wasm_bindgen::from_value(value).unwrap();
+        sink.borrow_mut().push(result);
+    }) as Box<dyn FnMut(JsValue)>);
+    (closure.into_js_value().unchecked_into(), received)
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep(ms: i32) {
+    wasm_bindgen_futures::JsFuture::from(js_sys::Promise::new(&mut |resolve, _reject| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms)
+            .unwrap();
+    }))
+    .await
+    .unwrap();
+}
+
+/// Subscription emits the initial result and re-emits after a relevant write
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_subscription_reemits_on_write() {
+    let mut db = Database::new_wasm("subscription_reemit_test".to_string())
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS todos")
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS other")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE todos (id INTEGER PRIMARY KEY, title TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE other (id INTEGER PRIMARY KEY)")
+        .await
+        .unwrap();
+    let (callback, received) = recording_callback();
+
+    db.subscribe_internal("SELECT title FROM todos", vec![], callback)
+        .unwrap();
+    sleep(10).await;
+    assert_eq!(
+        received.borrow().len(),
+        1,
+        "Initial result should be emitted"
+    );
+    assert!(received.borrow()[0].rows.is_empty());
+
+    db.execute_with_params_internal(
+        "INSERT INTO todos (title) VALUES (?)",
+        &[ColumnValue::Text("write tests".to_string())],
+    )
+    .await
+    .unwrap();
+    sleep(10).await;
+
+    let results = received.borrow();
+    assert_eq!(results.len(), 2, "Write should re-run the query");
+    assert_eq!(
+        results[1].rows[0].values[0],
+        ColumnValue::Text("write tests".to_string())
+    );
+    drop(results);
+
+    db.close().await.unwrap();
+}
+
+/// Writes to unrelated tables do not trigger the subscription
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_subscription_ignores_unrelated_tables() {
+    let mut db = Database::new_wasm("subscription_unrelated_test".to_string())
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS todos")
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS other")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE todos (id INTEGER PRIMARY KEY, title TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE other (id INTEGER PRIMARY KEY)")
+        .await
+        .unwrap();
+    let (callback, received) = recording_callback();
+
+    db.subscribe_internal("SELECT COUNT(*) FROM todos", vec![], callback)
+        .unwrap();
+    db.execute_internal("INSERT INTO other (id) VALUES (1)")
+        .await
+        .unwrap();
+    sleep(10).await;
+
+    assert_eq!(
+        received.borrow().len(),
+        1,
+        "Only the initial result expected"
+    );
+    db.close().await.unwrap();
+}
+
+/// Unsubscribed queries stop receiving results
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_unsubscribe_stops_notifications() {
+    let mut db = Database::new_wasm("subscription_unsubscribe_test".to_string())
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS todos")
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS other")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE todos (id INTEGER PRIMARY KEY, title TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE other (id INTEGER PRIMARY KEY)")
+        .await
+        .unwrap();
+    let (callback, received) = recording_callback();
+
+    let id = db
+        .subscribe_internal("SELECT * FROM todos", vec![], callback)
+        .unwrap();
+    assert!(db.unsubscribe(id));
+
+    db.execute_internal("INSERT INTO todos (title) VALUES ('ignored')")
+        .await
+        .unwrap();
+    sleep(10).await;
+
+    assert_eq!(
+        received.borrow().len(),
+        1,
+        "Only the initial result expected"
+    );
+    db.close().await.unwrap();
+}
+
+/// Referenced tables are extracted without running the statement
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_referenced_tables_extraction() {
+    let mut db = Database::new_wasm("subscription_referenced_tables_test".to_string())
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS todos")
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS other")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE todos (id INTEGER PRIMARY KEY, title TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE other (id INTEGER PRIMARY KEY)")
+        .await
+        .unwrap();
+    db.execute_internal("DROP VIEW IF EXISTS todo_view")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE VIEW todo_view AS SELECT title FROM todos")
+        .await
+        .unwrap();
+
+    let tables = db
+        .referenced_tables_internal("SELECT * FROM todos JOIN other ON other.id = todos.id")
+        .unwrap();
+    assert_eq!(tables, vec!["other".to_string(), "todos".to_string()]);
+
+    let tables = db
+        .referenced_tables_internal("SELECT * FROM todo_view")
+        .unwrap();
+    assert_eq!(tables, vec!["todos".to_string()]);
+
+    let err = db
+        .referenced_tables_internal("SELECT * FROM missing_table")
+        .unwrap_err();
+    assert_eq!(err.code, "SQLITE_ERROR");
+
+    db.close().await.unwrap();
+}
+
+/// A multi-table transaction re-runs each subscription once, and only after COMMIT
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_transaction_notifications_coalesced() {
+    let mut db = Database::new_wasm("subscription_coalesce_test".to_string())
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS todos")
+        .await
+        .unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS other")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE todos (id INTEGER PRIMARY KEY, title TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE other (id INTEGER PRIMARY KEY)")
+        .await
+        .unwrap();
+    let (callback, received) = recording_callback();
+
+    db.subscribe_internal(
+        "SELECT COUNT(*) FROM todos JOIN other ON other.id = todos.id",
+        vec![],
+        callback,
+    )
+    .unwrap();
+
+    db.execute_internal("BEGIN").await.unwrap();
+    db.execute_internal("INSERT INTO todos (id, title) VALUES (1, 'a')")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO other (id) VALUES (1)")
+        .await
+        .unwrap();
+    db.execute_internal("UPDATE todos SET title = 'b' WHERE id = 1")
+        .await
+        .unwrap();
+    sleep(10).await;
+    assert_eq!(
+        received.borrow().len(),
+        1,
+        "No re-run before the transaction commits"
+    );
+
+    db.execute_internal("COMMIT").await.unwrap();
+    sleep(10).await;
+    assert_eq!(
+        received.borrow().len(),
+        2,
+        "Exactly one re-run per committed transaction"
+    );
+
+    db.execute_internal("BEGIN").await.unwrap();
+    db.execute_internal("INSERT INTO todos (id, title) VALUES (2, 'c')")
+        .await
+        .unwrap();
+    db.execute_internal("ROLLBACK").await.unwrap();
+    sleep(10).await;
+    assert_eq!(
+        received.borrow().len(),
+        2,
+        "Rolled back transactions do not notify"
+    );
+
+    db.close().await.unwrap();
+}
@@ -0,0 +1,72 @@
+//! Tests for sync levels and the blocks/commit marker sync reports
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{Database, DatabaseConfig, Persistence, SyncLevel};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// A durable sync reports the marker it verified in IndexedDB
+#[wasm_bindgen_test]
+async fn test_durable_sync_reports_commit_marker() {
+    let mut db = Database::new_wasm("sync_level_durable".to_string())
+        .await
+        .expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE t (v TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO t VALUES ('a')")
+        .await
+        .unwrap();
+
+    let first = db
+        .sync_with_level_internal(SyncLevel::Durable)
+        .await
+        .expect("durable sync");
+    assert!(first.blocks_persisted > 0);
+    assert_eq!(
+        absurder_sql::storage::wasm_indexeddb::read_persisted_commit_marker(
+            "sync_level_durable.db"
+        )
+        .await
+        .unwrap(),
+        Some(first.commit_marker)
+    );
+
+    let second = db
+        .sync_with_level_internal(SyncLevel::Normal)
+        .await
+        .expect("normal sync");
+    assert!(second.commit_marker > first.commit_marker);
+
+    db.close().await.unwrap();
+}
+
+/// Memory databases have nothing to persist
+#[wasm_bindgen_test]
+async fn test_memory_sync_is_empty() {
+    let config = DatabaseConfig {
+        name: "sync_level_memory.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open");
+    let result = db
+        .sync_with_level_internal(SyncLevel::Durable)
+        .await
+        .unwrap();
+    assert_eq!(result.blocks_persisted, 0);
+    assert_eq!(result.commit_marker, 0);
+}
+
+/// Unknown levels are rejected by name
+#[wasm_bindgen_test]
+fn test_sync_level_from_name() {
+    assert_eq!(SyncLevel::from_name("durable").unwrap(), SyncLevel::Durable);
+    assert_eq!(
+        SyncLevel::from_name("fsync").unwrap_err().code,
+        "INVALID_SYNC_LEVEL"
+    );
+}
@@ -0,0 +1,167 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::storage::vfs_sync::with_global_commit_marker;
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{Database, DatabaseConfig, SyncMode};
+
+#[cfg(target_arch = "wasm32")]
+fn commit_marker(db_name: &str) -> u64 {
+    with_global_commit_marker(|cm| cm.borrow().get(db_name).copied().unwrap_or(0))
+}
+
+/// `OnWrite` syncs before each write returns; reads do not sync
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_on_write_syncs_each_write() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "sync_mode_on_write".to_string(),
+        journal_mode: Some("WAL".to_string()),
+        sync_mode: Some(SyncMode::OnWrite),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS notes")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE notes (body TEXT)")
+        .await
+        .unwrap();
+    db.sync_internal().await.unwrap();
+    let before = commit_marker("sync_mode_on_write.db");
+
+    db.execute_internal("INSERT INTO notes VALUES ('a')")
+        .await
+        .unwrap();
+    let after_write = commit_marker("sync_mode_on_write.db");
+    assert!(after_write > before, "write should sync without sync()");
+
+    db.execute_internal("SELECT * FROM notes").await.unwrap();
+    assert_eq!(
+        commit_marker("sync_mode_on_write.db"),
+        after_write,
+        "reads should not sync"
+    );
+
+    db.close().await.unwrap();
+}
+
+/// Inside an explicit transaction only the COMMIT syncs
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_on_write_syncs_on_commit() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "sync_mode_on_write_txn".to_string(),
+        journal_mode: Some("WAL".to_string()),
+        sync_mode: Some(SyncMode::OnWrite),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS notes")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE notes (body TEXT)")
+        .await
+        .unwrap();
+    db.sync_internal().await.unwrap();
+    let before = commit_marker("sync_mode_on_write_txn.db");
+
+    db.execute_internal("BEGIN").await.unwrap();
+    db.execute_internal("INSERT INTO notes VALUES ('a')")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO notes VALUES ('b')")
+        .await
+        .unwrap();
+    assert_eq!(commit_marker("sync_mode_on_write_txn.db"), before);
+
+    db.execute_internal("COMMIT").await.unwrap();
+    assert!(commit_marker("sync_mode_on_write_txn.db") > before);
+
+    db.close().await.unwrap();
+}
+
+/// A debounced `OnWrite` coalesces a burst of writes into one later sync
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_on_write_debounced() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "sync_mode_debounced".to_string(),
+        journal_mode: Some("WAL".to_string()),
+        sync_mode: Some(SyncMode::OnWrite),
+        sync_debounce_ms: Some(50),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS notes")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE notes (body TEXT)")
+        .await
+        .unwrap();
+    db.sync_internal().await.unwrap();
+    let before = commit_marker("sync_mode_debounced.db");
+
+    for i in 0..5 {
+        db.execute_internal(&format!("INSERT INTO notes VALUES ('{}')", i))
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        commit_marker("sync_mode_debounced.db"),
+        before,
+        "sync should wait for the debounce delay"
+    );
+
+    absurder_sql::storage::retry_logic::sleep_ms(150).await;
+    assert!(
+        commit_marker("sync_mode_debounced.db") > before,
+        "the burst should be persisted once the delay passes"
+    );
+
+    db.close().await.unwrap();
+}
+
+/// `Manual` leaves persistence to sync()
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_manual_does_not_sync_on_write() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "sync_mode_manual".to_string(),
+        journal_mode: Some("WAL".to_string()),
+        sync_mode: Some(SyncMode::Manual),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS notes")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE notes (body TEXT)")
+        .await
+        .unwrap();
+    db.sync_internal().await.unwrap();
+    let before = commit_marker("sync_mode_manual.db");
+
+    db.execute_internal("INSERT INTO notes VALUES ('a')")
+        .await
+        .unwrap();
+    assert_eq!(commit_marker("sync_mode_manual.db"), before);
+
+    db.sync_internal().await.unwrap();
+    assert!(commit_marker("sync_mode_manual.db") > before);
+
+    db.close().await.unwrap();
+}
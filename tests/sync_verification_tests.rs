@@ -0,0 +1,59 @@
+//! Tests for read-back verification of synced blocks (verify_after_sync)
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::storage::wasm_indexeddb::verify_persisted_blocks;
+use absurder_sql::{Database, DatabaseConfig};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// A verified sync succeeds when IndexedDB stored what was written
+#[wasm_bindgen_test]
+async fn test_verified_sync_succeeds() {
+    let config = DatabaseConfig {
+        name: "sync_verify_ok".to_string(),
+        verify_after_sync: Some(true),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE t (v TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO t VALUES ('verified')")
+        .await
+        .unwrap();
+    let result = db.sync_internal().await;
+    assert!(result.is_ok(), "verified sync failed: {:?}", result.err());
+    db.close().await.unwrap();
+}
+
+/// Blocks whose stored bytes differ, or that are missing, fail verification
+#[wasm_bindgen_test]
+async fn test_mismatch_fails_verification() {
+    let config = DatabaseConfig {
+        name: "sync_verify_mismatch".to_string(),
+        verify_after_sync: Some(true),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("CREATE TABLE t (v TEXT)")
+        .await
+        .unwrap();
+    db.sync_internal().await.unwrap();
+
+    let err = verify_persisted_blocks("sync_verify_mismatch.db", &[(0, vec![0xAB; 4096])])
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "SYNC_VERIFICATION_FAILED");
+
+    let err = verify_persisted_blocks("sync_verify_mismatch.db", &[(999_999, vec![0; 4096])])
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, "SYNC_VERIFICATION_FAILED");
+    assert!(err.message.contains("missing"), "{}", err.message);
+
+    db.close().await.unwrap();
+}
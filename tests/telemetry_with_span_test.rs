@@ -0,0 +1,82 @@
+#![cfg(all(target_arch = "wasm32", feature = "telemetry"))]
+
+use absurder_sql::Database;
+use absurder_sql::telemetry::{SpanRecorder, SpanStatus};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+async fn test_queries_in_scope_link_to_scope_span() {
+    let recorder = SpanRecorder::new();
+    let mut db = Database::new_wasm("test_with_span_scope.db".to_string())
+        .await
+        .expect("Failed to create database");
+    db.set_span_recorder(Some(recorder.clone()));
+
+    let context = db.get_span_context().cloned().expect("span context");
+    let group = absurder_sql::telemetry::SpanBuilder::new("group".to_string()).build();
+    context.enter_scope(group.span_id.clone());
+
+    db.execute_internal("CREATE TABLE items (id INTEGER)")
+        .await
+        .expect("create");
+    db.execute_internal("INSERT INTO items VALUES (1)")
+        .await
+        .expect("insert");
+
+    context.exit_scope(&group.span_id);
+    db.execute_internal("SELECT * FROM items")
+        .await
+        .expect("select");
+
+    let spans = recorder.get_spans_by_name("execute_query");
+    assert_eq!(spans.len(), 3);
+    assert_eq!(spans[0].parent_id.as_deref(), Some(group.span_id.as_str()));
+    assert_eq!(spans[1].parent_id.as_deref(), Some(group.span_id.as_str()));
+    assert_eq!(
+        spans[2].parent_id, None,
+        "Query after the scope has no parent"
+    );
+    assert_ne!(spans[0].span_id, spans[1].span_id);
+    assert!(context.current_span_id().is_none());
+
+    let _ = db.close().await;
+}
+
+#[wasm_bindgen_test]
+async fn test_with_span_records_group_span() {
+    let recorder = SpanRecorder::new();
+    let mut db = Database::new_wasm("test_with_span_record.db".to_string())
+        .await
+        .expect("Failed to create database");
+    db.set_span_recorder(Some(recorder.clone()));
+
+    let ok = js_sys::Function::new_no_args("return Promise.resolve(42);");
+    let promise = db.with_span("ok_group".to_string(), ok).expect("withSpan");
+    let value = wasm_bindgen_futures::JsFuture::from(promise)
+        .await
+        .expect("resolves");
+    assert_eq!(value, JsValue::from_f64(42.0));
+
+    let failing = js_sys::Function::new_no_args("return Promise.reject(new Error('boom'));");
+    let promise = db
+        .with_span("failed_group".to_string(), failing)
+        .expect("withSpan");
+    assert!(wasm_bindgen_futures::JsFuture::from(promise).await.is_err());
+
+    let ok_spans = recorder.get_spans_by_name("ok_group");
+    assert_eq!(ok_spans.len(), 1);
+    assert_eq!(ok_spans[0].status, SpanStatus::Ok);
+    assert!(ok_spans[0].end_time_ms.is_some());
+
+    let failed_spans = recorder.get_spans_by_name("failed_group");
+    assert_eq!(failed_spans.len(), 1);
+    assert!(matches!(failed_spans[0].status, SpanStatus::Error(_)));
+
+    let context = db.get_span_context().expect("span context");
+    assert!(context.current_scope_id().is_none());
+
+    let _ = db.close().await;
+}
@@ -104,6 +104,45 @@ async fn test_block_allocation_tracking() {
     );
 }
 
+#[tokio::test]
+#[serial]
+async fn test_allocation_stats() {
+    let tmp = TempDir::new().expect("tempdir");
+    // Safety: per-test isolated env var, tests are serialized
+    common::set_var("ABSURDERSQL_FS_BASE", tmp.path());
+    let mut storage = BlockStorage::new("test_allocation_stats")
+        .await
+        .expect("Should create storage");
+
+    let empty = storage.allocation_stats();
+    assert_eq!(empty.allocated, 0);
+    assert_eq!(empty.fragmentation_ratio, 0.0);
+
+    let mut ids = Vec::new();
+    for _ in 0..4 {
+        ids.push(storage.allocate_block().await.expect("Should allocate"));
+    }
+    storage
+        .deallocate_block(ids[1])
+        .await
+        .expect("Should deallocate");
+
+    let stats = storage.allocation_stats();
+    let highest = *ids.iter().max().unwrap();
+    assert_eq!(stats.allocated, 3);
+    assert_eq!(stats.deallocated_reusable, 1);
+    assert_eq!(stats.highest_block_id, highest);
+    assert_eq!(
+        stats.fragmentation_ratio,
+        (highest + 1 - 3) as f64 / (highest + 1) as f64
+    );
+
+    // Reallocating the freed id clears its tombstone
+    let reused = storage.allocate_block().await.expect("Should allocate");
+    assert_eq!(reused, ids[1], "Freed block id should be reused");
+    assert_eq!(storage.allocation_stats().deallocated_reusable, 0);
+}
+
 #[tokio::test]
 #[serial]
 async fn test_block_allocation_errors() {
@@ -132,3 +171,47 @@ async fn test_block_allocation_errors() {
     let result = storage.deallocate_block(block).await;
     assert!(result.is_err(), "Should error on double deallocation");
 }
+
+#[tokio::test]
+#[serial]
+async fn test_reserve_blocks() {
+    let tmp = TempDir::new().expect("tempdir");
+    // Safety: per-test isolated env var, tests are serialized
+    common::set_var("ABSURDERSQL_FS_BASE", tmp.path());
+    let mut storage = BlockStorage::new("test_reserve")
+        .await
+        .expect("Should create storage");
+
+    let first = storage
+        .allocate_block()
+        .await
+        .expect("Should allocate block");
+    let reserved = storage.reserve_blocks(100).expect("Should reserve blocks");
+
+    assert_eq!(reserved.len(), 100, "Should reserve every requested block");
+    assert!(
+        !reserved.contains(&first),
+        "Reservation must skip allocated blocks"
+    );
+    assert_eq!(
+        storage.get_allocated_count(),
+        101,
+        "Reserved blocks count as allocated"
+    );
+
+    // Allocation continues after the reservation
+    let next = storage
+        .allocate_block()
+        .await
+        .expect("Should allocate after reservation");
+    assert!(
+        reserved.iter().all(|&id| id < next),
+        "Allocation should not hand out reserved blocks"
+    );
+    assert!(
+        storage
+            .reserve_blocks(0)
+            .expect("Empty reservation")
+            .is_empty()
+    );
+}
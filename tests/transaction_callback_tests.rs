@@ -0,0 +1,101 @@
+//! Tests for the transaction closure API
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{
+    ColumnValue, Database, DatabaseConfig, DatabaseError, Persistence, TransactionBehavior,
+    TransactionOptions,
+};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+async fn balances(db: &mut Database) -> ColumnValue {
+    db.query_scalar_internal(
+        "SELECT group_concat(balance, ',') FROM (SELECT balance FROM accounts ORDER BY id)",
+        &[],
+        false,
+    )
+    .await
+    .unwrap()
+}
+
+/// An error from the body rolls back everything it wrote
+#[wasm_bindgen_test]
+async fn test_transaction_rolls_back_on_error() {
+    let config = DatabaseConfig {
+        name: "transaction_rollback.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER)")
+        .await
+        .expect("create table");
+    db.execute_internal("INSERT INTO accounts VALUES (1, 100), (2, 0)")
+        .await
+        .expect("seed");
+
+    let err = db
+        .transaction_internal(&TransactionOptions::default(), async |db: &mut Database| {
+            db.execute_internal("UPDATE accounts SET balance = balance - 10 WHERE id = 1")
+                .await?;
+            Err::<(), _>(DatabaseError::new("INSUFFICIENT_FUNDS", "declined"))
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.code, "INSUFFICIENT_FUNDS");
+    assert_eq!(
+        balances(&mut db).await,
+        ColumnValue::Text("100,0".to_string())
+    );
+    assert_eq!(db.savepoint_depth(), 0);
+}
+
+/// A successful body commits, and nested calls only undo their own work
+#[wasm_bindgen_test]
+async fn test_transaction_commits_and_nests() {
+    let config = DatabaseConfig {
+        name: "transaction_nested.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open database");
+    db.execute_internal("CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER)")
+        .await
+        .expect("create table");
+    db.execute_internal("INSERT INTO accounts VALUES (1, 100), (2, 0)")
+        .await
+        .expect("seed");
+    let options = TransactionOptions {
+        behavior: TransactionBehavior::Immediate,
+        ..Default::default()
+    };
+
+    let moved = db
+        .transaction_internal(&options, async |db: &mut Database| {
+            db.execute_internal("UPDATE accounts SET balance = balance - 10 WHERE id = 1")
+                .await?;
+            db.execute_internal("UPDATE accounts SET balance = balance + 10 WHERE id = 2")
+                .await?;
+
+            let inner = db
+                .transaction_internal(&TransactionOptions::default(), async |db: &mut Database| {
+                    db.execute_internal("UPDATE accounts SET balance = 0")
+                        .await?;
+                    Err::<(), _>(DatabaseError::new("ABORTED", "inner failure"))
+                })
+                .await;
+            assert!(inner.is_err());
+            Ok(10)
+        })
+        .await
+        .expect("transaction");
+
+    assert_eq!(moved, 10);
+    assert_eq!(
+        balances(&mut db).await,
+        ColumnValue::Text("90,10".to_string())
+    );
+}
@@ -0,0 +1,90 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::storage::vfs_sync;
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database};
+
+/// VACUUM shrinks the file and removes the freed blocks from global storage
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_vacuum_reclaims_blocks() {
+    let mut db = Database::new_wasm("vacuum_reclaim_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS bulk")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE bulk (id INTEGER PRIMARY KEY, data BLOB)")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE keep (v TEXT)")
+        .await
+        .unwrap();
+    db.execute_internal("INSERT INTO keep VALUES ('still here')")
+        .await
+        .unwrap();
+    for _ in 0..20 {
+        db.execute_internal("INSERT INTO bulk (data) VALUES (zeroblob(16384))")
+            .await
+            .unwrap();
+    }
+    db.sync_internal().await.unwrap();
+    db.execute_internal("DELETE FROM bulk").await.unwrap();
+
+    let calls = std::rc::Rc::new(std::cell::Cell::new(0u32));
+    let counter = calls.clone();
+    let progress = Closure::<dyn FnMut(f64, f64)>::new(move |done: f64, total: f64| {
+        assert!(done <= total);
+        counter.set(counter.get() + 1);
+    });
+    let stats = db
+        .vacuum_internal(
+            Some(
+                progress
+                    .as_ref()
+                    .unchecked_ref::<js_sys::Function>()
+                    .clone(),
+            ),
+            10,
+        )
+        .await
+        .unwrap();
+
+    assert!(
+        stats.bytes_after < stats.bytes_before,
+        "vacuum should shrink the file: {:?}",
+        stats
+    );
+    assert!(stats.blocks_freed > 0);
+    assert_eq!(
+        calls.get() as u64,
+        stats.blocks_freed.div_ceil(10),
+        "progress reported once per batch"
+    );
+
+    let first_free = stats.bytes_after.div_ceil(4096);
+    let orphans = vfs_sync::with_global_storage(|gs| {
+        gs.borrow()
+            .get("vacuum_reclaim_test.db")
+            .map(|blocks| blocks.keys().filter(|id| **id >= first_free).count())
+            .unwrap_or(0)
+    });
+    assert_eq!(orphans, 0, "freed blocks must not linger in global storage");
+
+    let result = db.execute_internal("SELECT v FROM keep").await.unwrap();
+    assert_eq!(
+        result.rows[0].values[0],
+        ColumnValue::Text("still here".to_string())
+    );
+
+    db.close().await.unwrap();
+}
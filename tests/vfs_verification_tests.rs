@@ -0,0 +1,96 @@
+//! Post-open VFS verification
+//!
+//! Connections opened for IndexedDB persistence must report the IndexedDB VFS
+//! through `SQLITE_FCNTL_VFSNAME`; anything else is rejected with `WRONG_VFS`.
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::Database;
+use absurder_sql::vfs::IndexedDBVFS;
+use absurder_sql::vfs::indexeddb_vfs::VFS_IMPLEMENTATION_NAME;
+use std::ffi::{CStr, CString, c_char, c_void};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Read the VFS name SQLite reports for the main database of `db`
+unsafe fn reported_vfs_name(db: *mut sqlite_wasm_rs::sqlite3) -> (i32, Option<String>) {
+    let schema = CString::new("main").unwrap();
+    let mut name_ptr: *mut c_char = std::ptr::null_mut();
+    let rc = unsafe {
+        sqlite_wasm_rs::sqlite3_file_control(
+            db,
+            schema.as_ptr(),
+            sqlite_wasm_rs::SQLITE_FCNTL_VFSNAME,
+            &mut name_ptr as *mut _ as *mut c_void,
+        )
+    };
+    if name_ptr.is_null() {
+        return (rc, None);
+    }
+    let name = unsafe { CStr::from_ptr(name_ptr) }
+        .to_string_lossy()
+        .into_owned();
+    unsafe { sqlite_wasm_rs::sqlite3_free(name_ptr as *mut c_void) };
+    (rc, Some(name))
+}
+
+#[wasm_bindgen_test]
+async fn test_indexeddb_vfs_reports_its_name() {
+    let db_name = "vfs_verification_raw.db";
+    let vfs_name = "indexeddb_vfs_verification_raw";
+    let vfs = IndexedDBVFS::new(db_name).await.expect("create VFS");
+    vfs.register(vfs_name).expect("register VFS");
+
+    let fname = CString::new(format!("file:{}", db_name)).unwrap();
+    let vfs_c = CString::new(vfs_name).unwrap();
+    let mut db: *mut sqlite_wasm_rs::sqlite3 = std::ptr::null_mut();
+    let rc = unsafe {
+        sqlite_wasm_rs::sqlite3_open_v2(
+            fname.as_ptr(),
+            &mut db as *mut _,
+            sqlite_wasm_rs::SQLITE_OPEN_READWRITE | sqlite_wasm_rs::SQLITE_OPEN_CREATE,
+            vfs_c.as_ptr(),
+        )
+    };
+    assert_eq!(rc, sqlite_wasm_rs::SQLITE_OK, "open db");
+
+    let (rc, name) = unsafe { reported_vfs_name(db) };
+    assert_eq!(rc, sqlite_wasm_rs::SQLITE_OK);
+    assert_eq!(name.as_deref(), Some(VFS_IMPLEMENTATION_NAME));
+
+    unsafe { sqlite_wasm_rs::sqlite3_close(db) };
+}
+
+#[wasm_bindgen_test]
+async fn test_default_vfs_does_not_report_indexeddb() {
+    let fname = CString::new(":memory:").unwrap();
+    let mut db: *mut sqlite_wasm_rs::sqlite3 = std::ptr::null_mut();
+    let rc = unsafe {
+        sqlite_wasm_rs::sqlite3_open_v2(
+            fname.as_ptr(),
+            &mut db as *mut _,
+            sqlite_wasm_rs::SQLITE_OPEN_READWRITE | sqlite_wasm_rs::SQLITE_OPEN_CREATE,
+            std::ptr::null(),
+        )
+    };
+    assert_eq!(rc, sqlite_wasm_rs::SQLITE_OK, "open db");
+
+    let (_, name) = unsafe { reported_vfs_name(db) };
+    assert_ne!(name.as_deref(), Some(VFS_IMPLEMENTATION_NAME));
+
+    unsafe { sqlite_wasm_rs::sqlite3_close(db) };
+}
+
+#[wasm_bindgen_test]
+async fn test_database_open_passes_vfs_verification() {
+    let mut db = Database::new_wasm("vfs_verification_open".to_string())
+        .await
+        .expect("open should pass VFS verification");
+    db.allow_non_leader_writes(true).await.unwrap();
+
+    db.execute_internal("CREATE TABLE IF NOT EXISTS t (x INTEGER)")
+        .await
+        .expect("verified connection should be usable");
+    db.close_internal().await.unwrap();
+}
@@ -0,0 +1,229 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{CheckpointMode, CheckpointResult, Database, DatabaseConfig};
+
+/// TRUNCATE checkpoints every frame and empties the WAL
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_checkpoint_truncate_empties_wal() {
+    let mut db = Database::new_wasm("checkpoint_truncate".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS events")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, payload TEXT)")
+        .await
+        .unwrap();
+    for i in 0..50 {
+        db.execute_internal(&format!(
+            "INSERT INTO events (payload) VALUES ('{}')",
+            "x".repeat(i * 10)
+        ))
+        .await
+        .unwrap();
+    }
+    assert!(
+        db.wal_size() > 0.0,
+        "writes in WAL mode should grow the WAL"
+    );
+    assert!(db.wal_size() < db.wal_size_limit());
+
+    let result: CheckpointResult = serde_wasm_bindgen::from_value(
+        db.checkpoint(Some("truncate".to_string()))
+            .await
+            .expect("checkpoint should succeed"),
+    )
+    .unwrap();
+    assert!(!result.busy);
+    assert_eq!(result.log_frames, result.checkpointed_frames);
+    assert_eq!(db.wal_size(), 0.0, "TRUNCATE leaves an empty WAL");
+
+    db.close().await.unwrap();
+}
+
+/// PASSIVE is the default mode and reports the frames it copied
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_checkpoint_passive_default() {
+    let mut db = Database::new_wasm("checkpoint_passive".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS events")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE events (id INTEGER PRIMARY KEY, payload TEXT)")
+        .await
+        .unwrap();
+    for i in 0..50 {
+        db.execute_internal(&format!(
+            "INSERT INTO events (payload) VALUES ('{}')",
+            "x".repeat(i * 10)
+        ))
+        .await
+        .unwrap();
+    }
+
+    let result: CheckpointResult =
+        serde_wasm_bindgen::from_value(db.checkpoint(None).await.unwrap()).unwrap();
+    assert!(result.log_frames >= 0);
+    assert!(result.checkpointed_frames <= result.log_frames);
+
+    let internal = db
+        .checkpoint_internal(CheckpointMode::Restart)
+        .await
+        .unwrap();
+    assert!(!internal.busy);
+
+    let err = db
+        .checkpoint(Some("EVENTUALLY".to_string()))
+        .await
+        .expect_err("unknown mode");
+    assert!(err.as_string().unwrap().contains("Unknown checkpoint mode"));
+
+    db.close().await.unwrap();
+}
+
+/// A configured `wal_max_bytes` is enforced and named in the error
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_wal_max_bytes_limit_is_enforced() {
+    let mut db = Database::new(DatabaseConfig {
+        name: "wal_max_bytes_small".to_string(),
+        journal_mode: Some("WAL".to_string()),
+        wal_max_bytes: Some(64 * 1024),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    assert_eq!(db.wal_max_bytes(), 64 * 1024);
+    assert_eq!(db.wal_size_limit(), (64 * 1024) as f64);
+
+    db.execute_internal("CREATE TABLE IF NOT EXISTS big (payload BLOB)")
+        .await
+        .unwrap();
+    let err = db
+        .execute_internal("INSERT INTO big SELECT randomblob(256 * 1024)")
+        .await
+        .expect_err("a transaction larger than the WAL limit should fail");
+    assert!(
+        err.code == "WAL_TOO_LARGE" && err.message.contains("65536"),
+        "error should name the configured limit: {}",
+        err.message
+    );
+
+    db.close().await.unwrap();
+}
+
+/// Commits past `wal_checkpoint_fraction` of the limit checkpoint automatically
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_wal_auto_checkpoint_keeps_wal_below_limit() {
+    let max_bytes = 256 * 1024;
+    let mut db = Database::new(DatabaseConfig {
+        name: "wal_auto_checkpoint".to_string(),
+        journal_mode: Some("WAL".to_string()),
+        wal_max_bytes: Some(max_bytes),
+        wal_checkpoint_fraction: Some(0.25),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+
+    db.execute_internal("CREATE TABLE IF NOT EXISTS chunks (payload BLOB)")
+        .await
+        .unwrap();
+    // Far more data than the WAL may hold, one small transaction at a time
+    for _ in 0..200 {
+        db.execute_internal("INSERT INTO chunks VALUES (randomblob(4000))")
+            .await
+            .expect("auto-checkpoint should keep every commit under the limit");
+    }
+    assert!(
+        db.wal_size_bytes() < max_bytes,
+        "WAL should be recycled by PASSIVE checkpoints"
+    );
+
+    db.close().await.unwrap();
+}
+
+/// Crossing the combined WAL cap checkpoints the least recently written database
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_total_wal_limit_checkpoints_least_recently_written() {
+    Database::set_total_wal_limit((512 * 1024) as f64).unwrap();
+    let mut older = Database::new(DatabaseConfig {
+        name: "wal_total_older".to_string(),
+        journal_mode: Some("WAL".to_string()),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    older.allow_non_leader_writes(true).await.unwrap();
+    older
+        .execute_internal("CREATE TABLE IF NOT EXISTS chunks (payload BLOB)")
+        .await
+        .unwrap();
+    let mut newer = Database::new(DatabaseConfig {
+        name: "wal_total_newer".to_string(),
+        journal_mode: Some("WAL".to_string()),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    newer.allow_non_leader_writes(true).await.unwrap();
+    newer
+        .execute_internal("CREATE TABLE IF NOT EXISTS chunks (payload BLOB)")
+        .await
+        .unwrap();
+
+    for _ in 0..40 {
+        older
+            .execute_internal("INSERT INTO chunks VALUES (randomblob(4000))")
+            .await
+            .unwrap();
+    }
+    assert!(older.wal_size_bytes() > 0);
+    for _ in 0..80 {
+        newer
+            .execute_internal("INSERT INTO chunks VALUES (randomblob(4000))")
+            .await
+            .expect("eviction should keep the combined WAL under the cap");
+    }
+    sleep_ms(50).await;
+
+    assert_eq!(
+        older.wal_size_bytes(),
+        0,
+        "the least recently written WAL should be checkpointed and truncated"
+    );
+    assert!(Database::total_wal_size() <= (512 * 1024) as f64);
+
+    Database::set_total_wal_limit(
+        absurder_sql::vfs::indexeddb_vfs::DEFAULT_WAL_TOTAL_MAX_BYTES as f64,
+    )
+    .unwrap();
+    older.close().await.unwrap();
+    newer.close().await.unwrap();
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep_ms(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms as i32)
+            .unwrap();
+    });
+    wasm_bindgen_futures::JsFuture::from(promise).await.unwrap();
+}
@@ -519,6 +519,51 @@ async fn test_multiple_sync_notifications() {
     web_sys::console::log_1(&"Multiple sync notifications test passed".into());
 }
 
+/// Syncs that carry no writes do not notify other tabs
+#[wasm_bindgen_test]
+async fn test_sync_without_writes_skips_notification() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let mut db = Database::new_wasm("test_quiet_sync".to_string())
+        .await
+        .expect("Should create database");
+    db.execute("CREATE TABLE IF NOT EXISTS quiet (id INTEGER PRIMARY KEY)")
+        .await
+        .expect("Should create table");
+    db.sync().await.expect("Should sync");
+
+    let call_count = Rc::new(RefCell::new(0));
+    let call_count_clone = call_count.clone();
+    let callback = Closure::wrap(Box::new(move |_event: JsValue| {
+        *call_count_clone.borrow_mut() += 1;
+    }) as Box<dyn FnMut(JsValue)>);
+    db.on_data_change_wasm(callback.as_ref().unchecked_ref())
+        .expect("Should register callback");
+
+    // A read followed by a sync is not a data change
+    db.execute("SELECT COUNT(*) FROM quiet")
+        .await
+        .expect("Should select");
+    db.sync().await.expect("Should sync");
+    sleep_ms(50).await;
+    assert_eq!(*call_count.borrow(), 0, "read-only sync must not notify");
+
+    db.execute("INSERT INTO quiet VALUES (NULL)")
+        .await
+        .expect("Should insert");
+    db.sync().await.expect("Should sync");
+    db.sync().await.expect("Should sync again");
+    sleep_ms(50).await;
+    assert_eq!(
+        *call_count.borrow(),
+        1,
+        "only the sync carrying the write notifies"
+    );
+
+    callback.forget();
+}
+
 /// Test Phase 2.1: Write guard logic is implemented correctly
 #[wasm_bindgen_test]
 async fn test_write_guard_prevents_follower_writes() {
@@ -539,7 +584,7 @@ async fn test_write_guard_prevents_follower_writes() {
         .expect("Should create table");
 
     // Verify the is_write_operation logic works
-    // The write guard code checks for INSERT, UPDATE, DELETE, REPLACE
+    // The write guard classifies each statement with sqlite3_stmt_readonly
     db.execute("INSERT INTO test (value) VALUES ('test')")
         .await
         .expect("Leader should insert");
@@ -1608,3 +1653,96 @@ async fn test_e2e_import_export_inverse_flow() {
     web_sys::console::log_1(&"Phase 5: Inverse flow verified".into());
     web_sys::console::log_1(&"E2E Inverse Flow Test PASSED".into());
 }
+
+#[wasm_bindgen_test]
+async fn test_wasm_error_carries_sqlite_code() {
+    let mut db = Database::new_wasm("test_wasm_sqlite_code".to_string())
+        .await
+        .expect("Should create database");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute_internal("DROP TABLE IF EXISTS users")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT UNIQUE)")
+        .await
+        .unwrap();
+
+    let params = [ColumnValue::Text("a@example.com".to_string())];
+    db.execute_with_params_internal("INSERT INTO users (email) VALUES (?)", &params)
+        .await
+        .unwrap();
+    let err = db
+        .execute_with_params_internal("INSERT INTO users (email) VALUES (?)", &params)
+        .await
+        .expect_err("Duplicate email should fail");
+    assert_eq!(err.code, "SQLITE_ERROR");
+    assert_eq!(err.sqlite_code, Some(2067), "SQLITE_CONSTRAINT_UNIQUE");
+
+    let err = db
+        .execute_internal("SELECT * FROM missing_table")
+        .await
+        .expect_err("Missing table should fail");
+    assert_eq!(err.sqlite_code, Some(1), "SQLITE_ERROR");
+
+    db.close().await.unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn test_null_byte_in_sql_is_reported() {
+    let mut db = Database::new_wasm("test_wasm_null_byte_sql".to_string())
+        .await
+        .expect("Should create database");
+
+    let err = db
+        .execute_internal("SELECT 'abc\0def'")
+        .await
+        .expect_err("SQL with a null byte should fail");
+    assert_eq!(err.code, "NULL_BYTE_IN_SQL");
+    assert!(err.message.contains("position 11"), "{}", err.message);
+
+    let err = db
+        .execute_with_params_internal("SELECT ?\0", &[ColumnValue::Integer(1)])
+        .await
+        .expect_err("parameterized SQL with a null byte should fail");
+    assert_eq!(err.code, "NULL_BYTE_IN_SQL");
+
+    db.close().await.unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn test_large_integers_round_trip_as_bigint() {
+    let mut db = Database::new_wasm("test_wasm_large_integers".to_string())
+        .await
+        .expect("Should create database");
+    db.allow_non_leader_writes(true).await.unwrap();
+    db.execute("DROP TABLE IF EXISTS ids").await.unwrap();
+    db.execute("CREATE TABLE ids (id INTEGER, small INTEGER)")
+        .await
+        .unwrap();
+
+    // 2^53 + 1 is not representable as a JS number
+    let large = js_sys::BigInt::from(9_007_199_254_740_993_i64);
+    let params = serde_wasm_bindgen::to_value(&vec![
+        ColumnValue::Integer(9_007_199_254_740_993),
+        ColumnValue::Integer(42),
+    ])
+    .unwrap();
+    db.execute_with_params("INSERT INTO ids VALUES (?, ?)", params)
+        .await
+        .unwrap();
+
+    let result = db.execute("SELECT id, small FROM ids").await.unwrap();
+    let rows = js_sys::Reflect::get(&result, &"rows".into()).unwrap();
+    let values =
+        js_sys::Reflect::get(&js_sys::Array::from(&rows).get(0), &"values".into()).unwrap();
+    let values = js_sys::Array::from(&values);
+
+    let id = js_sys::Reflect::get(&values.get(0), &"value".into()).unwrap();
+    assert!(id.is_bigint(), "out-of-range integer should be a BigInt");
+    assert_eq!(id, JsValue::from(large));
+
+    let small = js_sys::Reflect::get(&values.get(1), &"value".into()).unwrap();
+    assert_eq!(small.as_f64(), Some(42.0), "safe integers stay numbers");
+
+    db.close().await.unwrap();
+}
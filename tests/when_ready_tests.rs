@@ -0,0 +1,70 @@
+//! Tests for whenReady(): awaiting restore and leader election after construction
+
+#![cfg(target_arch = "wasm32")]
+
+use absurder_sql::{Database, DatabaseConfig, Persistence};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Data synced by one instance is readable immediately after whenReady on a fresh open
+#[wasm_bindgen_test]
+async fn test_query_immediately_after_when_ready() {
+    let name = "when_ready_restore.db";
+    let mut writer = Database::new_wasm(name.to_string())
+        .await
+        .expect("open writer");
+    writer.allow_non_leader_writes(true).await.unwrap();
+    writer
+        .execute_internal("CREATE TABLE IF NOT EXISTS settings (k TEXT PRIMARY KEY, v TEXT)")
+        .await
+        .unwrap();
+    writer
+        .execute_internal("INSERT OR REPLACE INTO settings VALUES ('theme', 'dark')")
+        .await
+        .unwrap();
+    writer.sync_internal().await.unwrap();
+    writer.close().await.unwrap();
+
+    let mut db = Database::new_wasm(name.to_string()).await.expect("reopen");
+    db.when_ready_internal(None)
+        .await
+        .expect("database should become ready");
+
+    let value = db
+        .query_scalar_internal("SELECT v FROM settings WHERE k = 'theme'", &[], true)
+        .await
+        .expect("query after whenReady");
+    assert_eq!(value, absurder_sql::ColumnValue::Text("dark".to_string()));
+
+    db.close().await.unwrap();
+}
+
+/// Once ready, a writer knows the election outcome
+#[wasm_bindgen_test]
+async fn test_leader_known_after_when_ready() {
+    let mut db = Database::new_wasm("when_ready_leader.db".to_string())
+        .await
+        .expect("open");
+    db.when_ready_internal(None).await.expect("ready");
+
+    let storage =
+        absurder_sql::vfs::indexeddb_vfs::get_storage_with_fallback("when_ready_leader.db")
+            .expect("storage registered");
+    assert!(storage.leader_known(), "leader should be settled");
+
+    db.close().await.unwrap();
+}
+
+/// Memory databases are ready as soon as they are opened
+#[wasm_bindgen_test]
+async fn test_memory_database_ready_immediately() {
+    let config = DatabaseConfig {
+        name: "when_ready_memory.db".to_string(),
+        persistence: Some(Persistence::Memory),
+        ..Default::default()
+    };
+    let mut db = Database::new(config).await.expect("open in memory");
+    db.when_ready_internal(Some(1)).await.expect("ready");
+    db.close().await.unwrap();
+}
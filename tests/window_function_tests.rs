@@ -0,0 +1,71 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+#[cfg(target_arch = "wasm32")]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[cfg(target_arch = "wasm32")]
+use absurder_sql::{ColumnValue, Database};
+
+/// The bundled SQLite evaluates window functions over the IndexedDB VFS
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_window_functions_over_vfs() {
+    let mut db = Database::new_wasm("window_function_test".to_string())
+        .await
+        .unwrap();
+    db.allow_non_leader_writes(true).await.unwrap();
+    assert!(db.supports_window_functions());
+
+    db.execute_internal("DROP TABLE IF EXISTS sales")
+        .await
+        .unwrap();
+    db.execute_internal("CREATE TABLE sales (region TEXT, amount INTEGER)")
+        .await
+        .unwrap();
+    db.execute_internal(
+        "INSERT INTO sales VALUES ('east', 10), ('east', 30), ('west', 5), ('west', 20), ('west', 15)",
+    )
+    .await
+    .unwrap();
+    db.sync_internal().await.unwrap();
+
+    let result = db
+        .execute_internal(
+            "SELECT region, amount,
+                    ROW_NUMBER() OVER (PARTITION BY region ORDER BY amount DESC) AS rank,
+                    SUM(amount) OVER w AS running
+             FROM sales
+             WINDOW w AS (PARTITION BY region ORDER BY amount)
+             ORDER BY region, amount DESC",
+        )
+        .await
+        .unwrap();
+
+    let rows: Vec<(String, i64, i64, i64)> = result
+        .rows
+        .iter()
+        .map(|row| match row.values.as_slice() {
+            [
+                ColumnValue::Text(region),
+                ColumnValue::Integer(amount),
+                ColumnValue::Integer(rank),
+                ColumnValue::Integer(running),
+            ] => (region.clone(), *amount, *rank, *running),
+            other => panic!("unexpected row: {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            ("east".to_string(), 30, 1, 40),
+            ("east".to_string(), 10, 2, 10),
+            ("west".to_string(), 20, 1, 40),
+            ("west".to_string(), 15, 2, 20),
+            ("west".to_string(), 5, 3, 5),
+        ]
+    );
+
+    db.close().await.unwrap();
+}
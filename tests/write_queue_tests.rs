@@ -99,3 +99,51 @@ async fn test_write_queue_infrastructure_exists() {
 
     console::log_1(&"TEST PASSED: Write queue infrastructure verified".into());
 }
+
+/// Followers get WRITE_QUEUE_FULL past max_queued_writes and can wait for the queue to drain
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen_test]
+async fn test_write_queue_backpressure() {
+    use absurder_sql::DatabaseConfig;
+    use absurder_sql::storage::BlockStorage;
+
+    let db_name = "write_queue_backpressure_test";
+
+    // Another instance holds leadership and never answers, so requests stay pending
+    let leader = BlockStorage::new(db_name).await.unwrap();
+    assert!(leader.is_leader().await);
+
+    let config = DatabaseConfig {
+        name: format!("{}.db", db_name),
+        max_queued_writes: Some(1),
+        ..Default::default()
+    };
+    let db = Database::new(config).await.unwrap();
+    assert_eq!(db.get_write_queue_depth(), 0);
+    db.flush_write_queue(Some(100))
+        .await
+        .expect("empty queue flushes immediately");
+
+    let pending = db.queue_write_with_timeout("INSERT INTO t VALUES (1)".to_string(), 300);
+    let rejected = async {
+        assert_eq!(db.get_write_queue_depth(), 1);
+        let err = db
+            .queue_write("INSERT INTO t VALUES (2)".to_string())
+            .await
+            .unwrap_err();
+        assert!(err.as_string().unwrap().contains("WRITE_QUEUE_FULL"));
+
+        let err = db.flush_write_queue(Some(50)).await.unwrap_err();
+        assert!(
+            err.as_string()
+                .unwrap()
+                .contains("WRITE_QUEUE_FLUSH_TIMEOUT")
+        );
+    };
+    let (timed_out, ()) = futures::join!(pending, rejected);
+    assert!(timed_out.is_err(), "nobody answers the forwarded write");
+
+    assert_eq!(db.get_write_queue_depth(), 0);
+    db.flush_write_queue(None).await.unwrap();
+    leader.stop_heartbeat_sync();
+}